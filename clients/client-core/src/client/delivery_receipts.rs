@@ -0,0 +1,101 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use log::*;
+use tokio::sync::mpsc::{self, error::TrySendError};
+
+/// Maximum number of delivery receipts that can be waiting to be consumed, for example while
+/// no application is connected to the client. Any further receipts are dropped.
+pub const MAX_PENDING_DELIVERY_RECEIPTS: usize = 1024;
+
+pub type DeliveryReceiptReceiver = mpsc::Receiver<DeliveryReceipt>;
+
+/// Creates a bounded channel for reporting delivery receipts.
+pub fn delivery_receipt_channel() -> (DeliveryReceiptSender, DeliveryReceiptReceiver) {
+    let (sender, receiver) = mpsc::channel(MAX_PENDING_DELIVERY_RECEIPTS);
+    (DeliveryReceiptSender(sender), receiver)
+}
+
+#[derive(Debug, Clone)]
+pub struct DeliveryReceiptSender(mpsc::Sender<DeliveryReceipt>);
+
+impl DeliveryReceiptSender {
+    /// Sends the receipt without waiting. It's dropped if there are already too many receipts
+    /// waiting to be consumed or if nobody is listening for them anymore.
+    pub fn send(&self, receipt: DeliveryReceipt) {
+        match self.0.try_send(receipt) {
+            Ok(_) => (),
+            Err(TrySendError::Full(receipt)) => debug!(
+                "too many delivery receipts are waiting to be consumed - dropping {:?}",
+                receipt
+            ),
+            // the receiver going away is not our problem
+            Err(TrySendError::Closed(_)) => (),
+        }
+    }
+}
+
+/// Application-provided identifier of a sent message used for reporting its delivery status.
+/// It is never sent through the mix network.
+pub type MessageId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeliveryStatus {
+    /// Acknowledgements were received for all fragments of the message.
+    Delivered,
+
+    /// At least one fragment of the message was not acknowledged in time and is being
+    /// retransmitted. It is only reported once per message.
+    Retransmitting,
+
+    /// The client is no longer attempting to deliver the message.
+    GaveUp,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeliveryReceipt {
+    pub message_id: MessageId,
+    pub status: DeliveryStatus,
+}
+
+impl DeliveryReceipt {
+    pub fn new(message_id: MessageId, status: DeliveryStatus) -> Self {
+        DeliveryReceipt { message_id, status }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn receipts_beyond_the_limit_are_dropped() {
+        let (sender, mut receiver) = delivery_receipt_channel();
+        for message_id in 0..(MAX_PENDING_DELIVERY_RECEIPTS as u64 + 10) {
+            sender.send(DeliveryReceipt::new(message_id, DeliveryStatus::Delivered))
+        }
+
+        let mut received = Vec::new();
+        while let Ok(receipt) = receiver.try_recv() {
+            received.push(receipt.message_id)
+        }
+        assert_eq!(
+            received,
+            (0..MAX_PENDING_DELIVERY_RECEIPTS as u64).collect::<Vec<_>>()
+        );
+
+        // once the receipts are consumed, new ones can be sent again
+        sender.send(DeliveryReceipt::new(42, DeliveryStatus::GaveUp));
+        assert_eq!(
+            receiver.try_recv().unwrap(),
+            DeliveryReceipt::new(42, DeliveryStatus::GaveUp)
+        );
+    }
+
+    #[test]
+    fn sending_without_receiver_does_not_fail() {
+        let (sender, receiver) = delivery_receipt_channel();
+        drop(receiver);
+        sender.send(DeliveryReceipt::new(1, DeliveryStatus::Delivered));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::client::delivery_receipts::MessageId;
 use futures::channel::mpsc;
 use nymsphinx::addressing::clients::Recipient;
 use nymsphinx::anonymous_replies::ReplySurb;
@@ -10,7 +11,8 @@ pub enum InputMessage {
     Fresh {
         recipient: Recipient,
         data: Vec<u8>,
-        with_reply_surb: bool,
+        reply_surbs: u32,
+        message_id: Option<MessageId>,
     },
     Reply {
         reply_surb: ReplySurb,
@@ -20,11 +22,30 @@ pub enum InputMessage {
 
 impl InputMessage {
     pub fn new_fresh(recipient: Recipient, data: Vec<u8>, with_reply_surb: bool) -> Self {
+        Self::new_fresh_with_reply_surbs(recipient, data, with_reply_surb as u32)
+    }
+
+    pub fn new_fresh_with_reply_surbs(
+        recipient: Recipient,
+        data: Vec<u8>,
+        reply_surbs: u32,
+    ) -> Self {
         InputMessage::Fresh {
             recipient,
             data,
-            with_reply_surb,
+            reply_surbs,
+            message_id: None,
+        }
+    }
+
+    /// Requests delivery receipts of the fresh message to be reported under the provided id.
+    /// It has no effect on replies as those are not retransmitted.
+    #[must_use]
+    pub fn with_message_id(mut self, id: MessageId) -> Self {
+        if let InputMessage::Fresh { message_id, .. } = &mut self {
+            *message_id = Some(id)
         }
+        self
     }
 
     pub fn new_reply(reply_surb: ReplySurb, data: Vec<u8>) -> Self {
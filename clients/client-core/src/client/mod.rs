@@ -1,8 +1,10 @@
 pub mod cover_traffic_stream;
+pub mod delivery_receipts;
 pub mod inbound_messages;
 pub mod key_manager;
 pub mod mix_traffic;
 pub mod real_messages_control;
 pub mod received_buffer;
 pub mod reply_key_storage;
+pub mod reply_surb_storage;
 pub mod topology_control;
@@ -2,21 +2,40 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::PendingAcknowledgement;
+use crate::client::delivery_receipts::{
+    DeliveryReceipt, DeliveryReceiptSender, DeliveryStatus, MessageId,
+};
 use crate::client::real_messages_control::acknowledgement_control::RetransmissionRequestSender;
 use futures::channel::mpsc::{self, UnboundedReceiver, UnboundedSender};
 use futures::StreamExt;
 use log::*;
 use nonexhaustive_delayqueue::{Expired, NonExhaustiveDelayQueue, QueueKey};
+use nymsphinx::addressing::nodes::NymNodeRoutingAddress;
 use nymsphinx::chunking::fragment::FragmentIdentifier;
 use nymsphinx::Delay as SphinxDelay;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
 pub(crate) type ActionSender = UnboundedSender<Action>;
 
-// The actual data being sent off as well as potential key to the delay queue
-type PendingAckEntry = (Arc<PendingAcknowledgement>, Option<QueueKey>);
+/// Locally assigned identifier of a message with fragments pending acknowledgement.
+type MessageKey = u64;
+
+// The actual data being sent off, potential key to the delay queue and the message it's part of
+type PendingAckEntry = (Arc<PendingAcknowledgement>, Option<QueueKey>, MessageKey);
+
+/// Delivery state of a message with at least one fragment pending acknowledgement.
+struct PendingMessage {
+    /// Application-provided id of the message, if its delivery should be reported.
+    message_id: Option<MessageId>,
+
+    /// Fragments of the message that are yet to be acknowledged.
+    pending_fragments: HashSet<FragmentIdentifier>,
+
+    /// Indicates whether the retransmission of any of the fragments was already reported.
+    reported_retransmission: bool,
+}
 
 // we can either:
 // - have a completely new set of packets we just sent and need to create entries for
@@ -24,7 +43,7 @@ type PendingAckEntry = (Arc<PendingAcknowledgement>, Option<QueueKey>);
 // - start a retransmission timer for sending the packet into the network (on either first try or retransmission)
 // - update the internal sphinx delay of an expired packet
 pub(crate) enum Action {
-    /// Inserts new `PendingAcknowledgement`s into the 'shared' state.
+    /// Inserts new `PendingAcknowledgement`s, of all fragments of a single message, into the 'shared' state.
     /// Initiated by `InputMessageListener`
     InsertPending(Vec<PendingAcknowledgement>),
 
@@ -38,9 +57,10 @@ pub(crate) enum Action {
     /// Can also be initiated by `RetransmissionRequestListener` in the rare cases of invalid Topology.
     StartTimer(FragmentIdentifier),
 
-    /// Updates the expected delay of given `PendingAcknowledgement` with the new provided `SphinxDelay`.
+    /// Updates the expected delay of given `PendingAcknowledgement` with the new provided `SphinxDelay`
+    /// alongside the route it took and records that it has been retransmitted.
     /// Initiated by `RetransmissionRequestListener`
-    UpdateDelay(FragmentIdentifier, SphinxDelay),
+    UpdateDelay(FragmentIdentifier, SphinxDelay, Vec<NymNodeRoutingAddress>),
 }
 
 impl Action {
@@ -56,8 +76,12 @@ impl Action {
         Action::StartTimer(frag_id)
     }
 
-    pub(crate) fn new_update_delay(frag_id: FragmentIdentifier, delay: SphinxDelay) -> Self {
-        Action::UpdateDelay(frag_id, delay)
+    pub(crate) fn new_update_delay(
+        frag_id: FragmentIdentifier,
+        delay: SphinxDelay,
+        mix_route: Vec<NymNodeRoutingAddress>,
+    ) -> Self {
+        Action::UpdateDelay(frag_id, delay, mix_route)
     }
 }
 
@@ -68,15 +92,52 @@ pub(super) struct Config {
 
     /// Given ack timeout in the form a * BASE_DELAY + b, it specifies the multiplier `a`
     ack_wait_multiplier: f64,
+
+    /// Maximum number of retransmissions of a single packet before its message is abandoned.
+    maximum_retransmissions: u32,
+
+    /// Maximum time since the first transmission during which a packet can be retransmitted.
+    retransmission_deadline: Duration,
+
+    /// Multiplier applied to the ack timeout upon each subsequent retransmission of a packet.
+    retransmission_backoff_multiplier: f64,
 }
 
 impl Config {
-    pub(super) fn new(ack_wait_addition: Duration, ack_wait_multiplier: f64) -> Self {
+    pub(super) fn new(
+        ack_wait_addition: Duration,
+        ack_wait_multiplier: f64,
+        maximum_retransmissions: u32,
+        retransmission_deadline: Duration,
+        retransmission_backoff_multiplier: f64,
+    ) -> Self {
         Config {
             ack_wait_addition,
             ack_wait_multiplier,
+            maximum_retransmissions,
+            retransmission_deadline,
+            retransmission_backoff_multiplier,
         }
     }
+
+    /// Determines how long we should wait for an ack of the given `PendingAcknowledgement`,
+    /// with the timeout growing exponentially with each retransmission.
+    fn ack_timeout(&self, pending_ack: &PendingAcknowledgement) -> Duration {
+        let base_timeout = (pending_ack.delay.clone() * self.ack_wait_multiplier).to_duration()
+            + self.ack_wait_addition;
+
+        let backoff = self
+            .retransmission_backoff_multiplier
+            .powi(pending_ack.retransmissions as i32);
+        base_timeout.mul_f64(backoff)
+    }
+
+    /// Checks whether the given `PendingAcknowledgement` has used up its retransmission budget,
+    /// either by the number of attempts or by the time since it was first sent.
+    fn retransmission_limit_reached(&self, pending_ack: &PendingAcknowledgement) -> bool {
+        pending_ack.retransmissions >= self.maximum_retransmissions
+            || pending_ack.first_sent.elapsed() >= self.retransmission_deadline
+    }
 }
 
 pub(super) struct ActionController {
@@ -99,12 +160,22 @@ pub(super) struct ActionController {
 
     /// Channel for notifying `RetransmissionRequestListener` about expired acknowledgements.
     retransmission_sender: RetransmissionRequestSender,
+
+    /// All messages that are not yet fully acknowledged.
+    pending_messages: HashMap<MessageKey, PendingMessage>,
+
+    /// Key to be assigned to the next inserted message.
+    next_message_key: MessageKey,
+
+    /// Optional channel for reporting changes to the delivery state of tracked messages.
+    delivery_receipt_sender: Option<DeliveryReceiptSender>,
 }
 
 impl ActionController {
     pub(super) fn new(
         config: Config,
         retransmission_sender: RetransmissionRequestSender,
+        delivery_receipt_sender: Option<DeliveryReceiptSender>,
     ) -> (Self, ActionSender) {
         let (sender, receiver) = mpsc::unbounded();
         (
@@ -114,38 +185,68 @@ impl ActionController {
                 pending_acks_timers: NonExhaustiveDelayQueue::new(),
                 incoming_actions: receiver,
                 retransmission_sender,
+                pending_messages: HashMap::new(),
+                next_message_key: 0,
+                delivery_receipt_sender,
             },
             sender,
         )
     }
 
+    fn report_delivery_status(&self, message_id: MessageId, status: DeliveryStatus) {
+        if let Some(sender) = &self.delivery_receipt_sender {
+            trace!(
+                "message {} has changed its status to {:?}",
+                message_id,
+                status
+            );
+            sender.send(DeliveryReceipt::new(message_id, status));
+        }
+    }
+
     fn handle_insert(&mut self, pending_acks: Vec<PendingAcknowledgement>) {
+        let message_id = match pending_acks.first() {
+            Some(pending_ack) => pending_ack.message_id,
+            None => return,
+        };
+        let message_key = self.next_message_key;
+        self.next_message_key = self.next_message_key.wrapping_add(1);
+
+        let mut pending_fragments = HashSet::with_capacity(pending_acks.len());
         for pending_ack in pending_acks {
             let frag_id = pending_ack.message_chunk.fragment_identifier();
             trace!("{} is inserted", frag_id);
 
+            pending_fragments.insert(frag_id);
             if self
                 .pending_acks_data
-                .insert(frag_id, (Arc::new(pending_ack), None))
+                .insert(frag_id, (Arc::new(pending_ack), None, message_key))
                 .is_some()
             {
                 panic!("Tried to insert duplicate pending ack")
             }
         }
+
+        self.pending_messages.insert(
+            message_key,
+            PendingMessage {
+                message_id,
+                pending_fragments,
+                reported_retransmission: false,
+            },
+        );
     }
 
     fn handle_start_timer(&mut self, frag_id: FragmentIdentifier) {
         trace!("{} is starting its timer", frag_id);
 
-        if let Some((pending_ack_data, queue_key)) = self.pending_acks_data.get_mut(&frag_id) {
+        if let Some((pending_ack_data, queue_key, _)) = self.pending_acks_data.get_mut(&frag_id) {
             if queue_key.is_some() {
                 // this branch should be IMPOSSIBLE under ANY condition. It would imply starting
                 // timer TWICE for the SAME PendingAcknowledgement
                 panic!("Tried to start an already started ack timer!")
             }
-            let timeout = (pending_ack_data.delay.clone() * self.config.ack_wait_multiplier)
-                .to_duration()
-                + self.config.ack_wait_addition;
+            let timeout = self.config.ack_timeout(pending_ack_data);
 
             let new_queue_key = self.pending_acks_timers.insert(frag_id, timeout);
             *queue_key = Some(new_queue_key)
@@ -167,7 +268,9 @@ impl ActionController {
                     frag_id
                 );
             }
-            Some((_, queue_key)) => {
+            Some((_, queue_key, message_key)) => {
+                self.handle_acknowledged_fragment(message_key, frag_id);
+
                 if let Some(queue_key) = queue_key {
                     // there are no possible checks here, we must GUARANTEE that we NEVER try
                     // to remove an entry that doesn't exist (and we MUST GUARANTEE that
@@ -186,20 +289,89 @@ impl ActionController {
         }
     }
 
+    fn handle_acknowledged_fragment(
+        &mut self,
+        message_key: MessageKey,
+        frag_id: FragmentIdentifier,
+    ) {
+        let message = match self.pending_messages.get_mut(&message_key) {
+            Some(message) => message,
+            None => {
+                // this could only happen if the message was removed while some of its
+                // fragments were still pending, which is impossible
+                error!("Received an ack for unknown message of {}", frag_id);
+                return;
+            }
+        };
+
+        message.pending_fragments.remove(&frag_id);
+        if message.pending_fragments.is_empty() {
+            let message_id = message.message_id;
+            self.pending_messages.remove(&message_key);
+            if let Some(message_id) = message_id {
+                self.report_delivery_status(message_id, DeliveryStatus::Delivered)
+            }
+        }
+    }
+
+    // the fragment could not be delivered within the retransmission limits, so the whole
+    // message is abandoned, since it's never going to be reconstructed by the recipient anyway
+    // and there's no point in wasting bandwidth on the remaining fragments
+    fn handle_abandoned_fragment(&mut self, frag_id: FragmentIdentifier) {
+        let message_key = match self.pending_acks_data.get(&frag_id) {
+            Some((_, _, message_key)) => *message_key,
+            None => return,
+        };
+
+        let message = match self.pending_messages.remove(&message_key) {
+            Some(message) => message,
+            None => return,
+        };
+
+        for frag_id in message.pending_fragments {
+            if let Some((_, Some(queue_key), _)) = self.pending_acks_data.remove(&frag_id) {
+                self.pending_acks_timers.remove(&queue_key);
+            }
+        }
+
+        if let Some(message_id) = message.message_id {
+            self.report_delivery_status(message_id, DeliveryStatus::GaveUp)
+        }
+    }
+
+    fn handle_retransmitted_fragment(&mut self, message_key: MessageKey) {
+        if let Some(message) = self.pending_messages.get_mut(&message_key) {
+            if let Some(message_id) = message.message_id {
+                if !message.reported_retransmission {
+                    message.reported_retransmission = true;
+                    self.report_delivery_status(message_id, DeliveryStatus::Retransmitting)
+                }
+            }
+        }
+    }
+
     // initiated basically as a first step of retransmission. At first data has its delay updated
     // (as new sphinx packet was created with new expected delivery time)
-    fn handle_update_delay(&mut self, frag_id: FragmentIdentifier, delay: SphinxDelay) {
+    fn handle_update_delay(
+        &mut self,
+        frag_id: FragmentIdentifier,
+        delay: SphinxDelay,
+        mix_route: Vec<NymNodeRoutingAddress>,
+    ) {
         trace!("{} is updating its delay", frag_id);
         // TODO: is it possible to solve this without either locking or temporarily removing the value?
-        if let Some((pending_ack_data, queue_key)) = self.pending_acks_data.remove(&frag_id) {
+        if let Some((pending_ack_data, queue_key, message_key)) =
+            self.pending_acks_data.remove(&frag_id)
+        {
             // this Action is triggered by `RetransmissionRequestListener` which held the other potential
             // reference to this Arc. HOWEVER, before the Action was pushed onto the queue, the reference
             // was dropped hence this unwrap is safe.
             let mut inner_data = Arc::try_unwrap(pending_ack_data).unwrap();
-            inner_data.update_delay(delay);
+            inner_data.update_delay(delay, mix_route);
+            inner_data.increment_retransmissions();
 
             self.pending_acks_data
-                .insert(frag_id, (Arc::new(inner_data), queue_key));
+                .insert(frag_id, (Arc::new(inner_data), queue_key, message_key));
         } else {
             debug!(
                 "Tried to UPDATE TIMER on pending ack that is already gone! - {}",
@@ -217,19 +389,34 @@ impl ActionController {
 
         trace!("{} has expired", frag_id);
 
-        if let Some((pending_ack_data, queue_key)) = self.pending_acks_data.get_mut(&frag_id) {
+        if let Some((pending_ack_data, queue_key, message_key)) =
+            self.pending_acks_data.get_mut(&frag_id)
+        {
             if queue_key.is_none() {
                 // this branch should be IMPOSSIBLE under ANY condition. It would imply the timeout
                 // happened before it even started.
                 panic!("Ack expired before it was even scheduled!")
             }
             *queue_key = None;
+            let message_key = *message_key;
+
+            if self.config.retransmission_limit_reached(pending_ack_data) {
+                warn!(
+                    "{} has not been acknowledged after {} retransmissions - giving up on it",
+                    frag_id, pending_ack_data.retransmissions
+                );
+                self.handle_abandoned_fragment(frag_id);
+                return;
+            }
+
             // downgrading an arc and then upgrading vs cloning is difference of 30ns vs 15ns
             // so it's literally a NO difference while it might prevent us from unnecessarily
             // resending data (in maybe 1 in 1 million cases, but it's something)
             self.retransmission_sender
                 .unbounded_send(Arc::downgrade(pending_ack_data))
-                .unwrap()
+                .unwrap();
+
+            self.handle_retransmitted_fragment(message_key)
         } else {
             // this shouldn't cause any issues but shouldn't have happened to begin with!
             error!("An already removed pending ack has expired")
@@ -241,7 +428,9 @@ impl ActionController {
             Action::InsertPending(pending_acks) => self.handle_insert(pending_acks),
             Action::RemovePending(frag_id) => self.handle_remove(frag_id),
             Action::StartTimer(frag_id) => self.handle_start_timer(frag_id),
-            Action::UpdateDelay(frag_id, delay) => self.handle_update_delay(frag_id, delay),
+            Action::UpdateDelay(frag_id, delay, mix_route) => {
+                self.handle_update_delay(frag_id, delay, mix_route)
+            }
         }
     }
 
@@ -257,3 +446,264 @@ impl ActionController {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::delivery_receipts::{delivery_receipt_channel, DeliveryReceiptReceiver};
+    use crate::client::real_messages_control::acknowledgement_control::RetransmissionRequestReceiver;
+    use futures::FutureExt;
+    use nymsphinx::addressing::clients::Recipient;
+    use nymsphinx::chunking::split_into_sets;
+    use rand::rngs::OsRng;
+
+    const RECIPIENT: &str = "CytBseW6yFXUMzz4SGAKdNLGR7q3sJLLYxyBGvutNEQV.4QXYyEVc5fUDjmmi8PrHN9tdUFV4PCvSJE1278cHyvoe@FioFa8nMmPpQnYi7JyojoTuwGLeyNS8BF4ChPr29zUML";
+
+    fn test_config() -> Config {
+        Config::new(
+            Duration::from_secs(1),
+            1.5,
+            3,
+            Duration::from_secs(3600),
+            2.0,
+        )
+    }
+
+    fn test_controller(
+        config: Config,
+    ) -> (
+        ActionController,
+        RetransmissionRequestReceiver,
+        DeliveryReceiptReceiver,
+    ) {
+        let (retransmission_sender, retransmission_receiver) = mpsc::unbounded();
+        let (receipt_sender, receipt_receiver) = delivery_receipt_channel();
+        let (controller, _) =
+            ActionController::new(config, retransmission_sender, Some(receipt_sender));
+        (controller, retransmission_receiver, receipt_receiver)
+    }
+
+    // all fragments of a single message
+    fn pending_acks(message_id: Option<MessageId>) -> Vec<PendingAcknowledgement> {
+        let recipient = Recipient::try_from_base58_string(RECIPIENT).unwrap();
+        let fragments = split_into_sets(&mut OsRng, &[42; 1000], 200)
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+        assert!(fragments.len() > 1);
+
+        fragments
+            .into_iter()
+            .map(|fragment| {
+                PendingAcknowledgement::new(
+                    fragment,
+                    SphinxDelay::new_from_nanos(42),
+                    recipient,
+                    message_id,
+                    Vec::new(),
+                )
+            })
+            .collect()
+    }
+
+    // inserts the fragments and starts their timers as if they were just sent
+    fn send(
+        controller: &mut ActionController,
+        pending_acks: Vec<PendingAcknowledgement>,
+    ) -> Vec<FragmentIdentifier> {
+        let frag_ids = pending_acks
+            .iter()
+            .map(|pending_ack| pending_ack.message_chunk.fragment_identifier())
+            .collect::<Vec<_>>();
+
+        controller.process_action(Action::new_insert(pending_acks));
+        for frag_id in &frag_ids {
+            controller.process_action(Action::new_start_timer(*frag_id))
+        }
+        frag_ids
+    }
+
+    async fn expire_next_timer(controller: &mut ActionController) {
+        let expired = controller.pending_acks_timers.next().await.unwrap();
+        controller.handle_expired_ack_timer(expired)
+    }
+
+    // behaves as the `RetransmissionRequestListener` and `SentNotificationListener` would
+    fn retransmit_requested(
+        controller: &mut ActionController,
+        retransmission_receiver: &mut RetransmissionRequestReceiver,
+    ) -> Vec<FragmentIdentifier> {
+        let mut retransmitted = Vec::new();
+        while let Some(Some(timed_out_ack)) = retransmission_receiver.next().now_or_never() {
+            let frag_id = match timed_out_ack.upgrade() {
+                Some(timed_out_ack) => timed_out_ack.message_chunk.fragment_identifier(),
+                None => continue,
+            };
+            controller.process_action(Action::new_update_delay(
+                frag_id,
+                SphinxDelay::new_from_nanos(42),
+                Vec::new(),
+            ));
+            controller.process_action(Action::new_start_timer(frag_id));
+            retransmitted.push(frag_id);
+        }
+        retransmitted
+    }
+
+    fn received_receipts(receipt_receiver: &mut DeliveryReceiptReceiver) -> Vec<DeliveryReceipt> {
+        let mut receipts = Vec::new();
+        while let Ok(receipt) = receipt_receiver.try_recv() {
+            receipts.push(receipt)
+        }
+        receipts
+    }
+
+    #[tokio::test]
+    async fn delivery_is_reported_once_all_fragments_are_acknowledged() {
+        let (mut controller, _, mut receipt_receiver) = test_controller(test_config());
+        let frag_ids = send(&mut controller, pending_acks(Some(1)));
+
+        let (last, others) = frag_ids.split_last().unwrap();
+        for frag_id in others {
+            controller.process_action(Action::new_remove(*frag_id));
+        }
+        assert!(received_receipts(&mut receipt_receiver).is_empty());
+
+        controller.process_action(Action::new_remove(*last));
+        assert_eq!(
+            received_receipts(&mut receipt_receiver),
+            vec![DeliveryReceipt::new(1, DeliveryStatus::Delivered)]
+        );
+        assert!(controller.pending_messages.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retransmission_is_reported_only_once_per_message() {
+        let (mut controller, mut retransmission_receiver, mut receipt_receiver) =
+            test_controller(test_config());
+        let frag_ids = send(&mut controller, pending_acks(Some(2)));
+
+        // none of the fragments got acknowledged in time, twice
+        for _ in 0..2 {
+            for _ in 0..frag_ids.len() {
+                expire_next_timer(&mut controller).await;
+            }
+            assert_eq!(
+                retransmit_requested(&mut controller, &mut retransmission_receiver).len(),
+                frag_ids.len()
+            );
+        }
+        assert_eq!(
+            received_receipts(&mut receipt_receiver),
+            vec![DeliveryReceipt::new(2, DeliveryStatus::Retransmitting)]
+        );
+
+        for frag_id in frag_ids {
+            controller.process_action(Action::new_remove(frag_id));
+        }
+        assert_eq!(
+            received_receipts(&mut receipt_receiver),
+            vec![DeliveryReceipt::new(2, DeliveryStatus::Delivered)]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn messages_without_id_are_not_reported() {
+        let (mut controller, mut retransmission_receiver, mut receipt_receiver) =
+            test_controller(test_config());
+        let frag_ids = send(&mut controller, pending_acks(None));
+
+        expire_next_timer(&mut controller).await;
+        assert_eq!(
+            retransmit_requested(&mut controller, &mut retransmission_receiver).len(),
+            1
+        );
+        for frag_id in frag_ids {
+            controller.process_action(Action::new_remove(frag_id));
+        }
+
+        assert!(received_receipts(&mut receipt_receiver).is_empty());
+        assert!(controller.pending_acks_data.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn message_is_abandoned_after_maximum_retransmissions() {
+        let (mut controller, mut retransmission_receiver, mut receipt_receiver) =
+            test_controller(test_config());
+        let frag_ids = send(&mut controller, pending_acks(Some(3)));
+
+        // everything but the first fragment gets through
+        let (lost, delivered) = frag_ids.split_first().unwrap();
+        for frag_id in delivered {
+            controller.process_action(Action::new_remove(*frag_id));
+        }
+
+        for _ in 0..3 {
+            expire_next_timer(&mut controller).await;
+            assert_eq!(
+                retransmit_requested(&mut controller, &mut retransmission_receiver),
+                vec![*lost]
+            );
+        }
+
+        // the retransmission budget is used up
+        expire_next_timer(&mut controller).await;
+        assert!(retransmit_requested(&mut controller, &mut retransmission_receiver).is_empty());
+        assert!(controller.pending_acks_data.is_empty());
+        assert!(controller.pending_acks_timers.is_empty());
+        assert!(controller.pending_messages.is_empty());
+        assert_eq!(
+            received_receipts(&mut receipt_receiver),
+            vec![
+                DeliveryReceipt::new(3, DeliveryStatus::Retransmitting),
+                DeliveryReceipt::new(3, DeliveryStatus::GaveUp)
+            ]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn message_is_abandoned_after_retransmission_deadline() {
+        let config = Config::new(Duration::from_secs(1), 1.5, 100, Duration::ZERO, 2.0);
+        let (mut controller, mut retransmission_receiver, mut receipt_receiver) =
+            test_controller(config);
+        send(&mut controller, pending_acks(Some(4)));
+
+        expire_next_timer(&mut controller).await;
+        assert!(retransmit_requested(&mut controller, &mut retransmission_receiver).is_empty());
+        assert!(controller.pending_acks_data.is_empty());
+        assert_eq!(
+            received_receipts(&mut receipt_receiver),
+            vec![DeliveryReceipt::new(4, DeliveryStatus::GaveUp)]
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn siblings_of_abandoned_fragment_are_cancelled() {
+        let config = Config::new(
+            Duration::from_secs(1),
+            1.5,
+            0,
+            Duration::from_secs(3600),
+            2.0,
+        );
+        let (mut controller, mut retransmission_receiver, _) = test_controller(config);
+
+        // the message is not tracked by the application, but its fragments are still cancelled
+        let abandoned = send(&mut controller, pending_acks(None));
+
+        // while the fragments of other messages are left alone
+        let other = pending_acks(None);
+        let other_fragments = other.len();
+        controller.process_action(Action::new_insert(other));
+
+        expire_next_timer(&mut controller).await;
+
+        assert!(retransmit_requested(&mut controller, &mut retransmission_receiver).is_empty());
+        for frag_id in abandoned {
+            assert!(!controller.pending_acks_data.contains_key(&frag_id));
+        }
+        assert!(controller.pending_acks_timers.is_empty());
+        assert_eq!(controller.pending_acks_data.len(), other_fragments);
+        assert_eq!(controller.pending_messages.len(), 1);
+    }
+}
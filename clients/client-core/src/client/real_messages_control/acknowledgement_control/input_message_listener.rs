@@ -3,6 +3,9 @@
 
 use super::action_controller::{Action, ActionSender};
 use super::PendingAcknowledgement;
+use crate::client::delivery_receipts::{
+    DeliveryReceipt, DeliveryReceiptSender, DeliveryStatus, MessageId,
+};
 use crate::client::reply_key_storage::ReplyKeyStorage;
 use crate::client::{
     inbound_messages::{InputMessage, InputMessageReceiver},
@@ -12,7 +15,7 @@ use crate::client::{
 use futures::StreamExt;
 use log::*;
 use nymsphinx::anonymous_replies::ReplySurb;
-use nymsphinx::preparer::MessagePreparer;
+use nymsphinx::preparer::{MessagePreparer, PreparationError, MAX_REPLY_SURBS_PER_MESSAGE};
 use nymsphinx::{acknowledgements::AckKey, addressing::clients::Recipient};
 use rand::{CryptoRng, Rng};
 use std::sync::Arc;
@@ -32,6 +35,7 @@ where
     real_message_sender: BatchRealMessageSender,
     topology_access: TopologyAccessor,
     reply_key_storage: ReplyKeyStorage,
+    delivery_receipt_sender: Option<DeliveryReceiptSender>,
 }
 
 impl<R> InputMessageListener<R>
@@ -50,6 +54,7 @@ where
         real_message_sender: BatchRealMessageSender,
         topology_access: TopologyAccessor,
         reply_key_storage: ReplyKeyStorage,
+        delivery_receipt_sender: Option<DeliveryReceiptSender>,
     ) -> Self {
         InputMessageListener {
             ack_key,
@@ -60,6 +65,13 @@ where
             real_message_sender,
             topology_access,
             reply_key_storage,
+            delivery_receipt_sender,
+        }
+    }
+
+    fn report_undeliverable(&self, message_id: Option<MessageId>) {
+        if let (Some(message_id), Some(sender)) = (message_id, &self.delivery_receipt_sender) {
+            sender.send(DeliveryReceipt::new(message_id, DeliveryStatus::GaveUp));
         }
     }
 
@@ -98,7 +110,8 @@ where
         &mut self,
         recipient: Recipient,
         content: Vec<u8>,
-        with_reply_surb: bool,
+        reply_surbs: u32,
+        message_id: Option<MessageId>,
     ) -> Option<Vec<RealMessage>> {
         let topology_permit = self.topology_access.get_read_permit().await;
         let topology = match topology_permit
@@ -107,17 +120,29 @@ where
             Some(topology_ref) => topology_ref,
             None => {
                 warn!("Could not process the message - the network topology is invalid");
+                self.report_undeliverable(message_id);
                 return None;
             }
         };
 
-        // split the message, attach optional reply surb
-        let (split_message, reply_key) = self
+        // split the message, attach optional reply surbs
+        let (split_message, reply_keys) = match self
             .message_preparer
-            .prepare_and_split_message(content, with_reply_surb, topology)
-            .expect("somehow the topology was invalid after all!");
+            .prepare_and_split_message_with_reply_surbs(content, reply_surbs, topology)
+        {
+            Ok(prepared) => prepared,
+            Err(PreparationError::TooManyReplySurbs(requested)) => {
+                warn!(
+                    "Could not process the message - {} reply surbs were requested while at most {} can be attached",
+                    requested, MAX_REPLY_SURBS_PER_MESSAGE
+                );
+                self.report_undeliverable(message_id);
+                return None;
+            }
+            Err(err) => panic!("somehow the topology was invalid after all! - {:?}", err),
+        };
 
-        if let Some(reply_key) = reply_key {
+        for reply_key in reply_keys {
             self.reply_key_storage
                 .insert_encryption_key(reply_key)
                 .expect("Failed to insert surb reply key to the store!")
@@ -145,6 +170,8 @@ where
                 message_chunk,
                 prepared_fragment.total_delay,
                 recipient,
+                message_id,
+                prepared_fragment.mix_route,
             ));
         }
 
@@ -161,9 +188,10 @@ where
             InputMessage::Fresh {
                 recipient,
                 data,
-                with_reply_surb,
+                reply_surbs,
+                message_id,
             } => {
-                self.handle_fresh_message(recipient, data, with_reply_surb)
+                self.handle_fresh_message(recipient, data, reply_surbs, message_id)
                     .await
             }
             InputMessage::Reply { reply_surb, data } => self
@@ -8,6 +8,7 @@ use self::{
     sent_notification_listener::SentNotificationListener,
 };
 use super::real_traffic_stream::BatchRealMessageSender;
+use crate::client::delivery_receipts::{DeliveryReceiptSender, MessageId};
 use crate::client::reply_key_storage::ReplyKeyStorage;
 use crate::client::{inbound_messages::InputMessageReceiver, topology_control::TopologyAccessor};
 use futures::channel::mpsc;
@@ -15,7 +16,7 @@ use gateway_client::AcknowledgementReceiver;
 use log::*;
 use nymsphinx::{
     acknowledgements::AckKey,
-    addressing::clients::Recipient,
+    addressing::{clients::Recipient, nodes::NymNodeRoutingAddress},
     chunking::fragment::{Fragment, FragmentIdentifier},
     preparer::MessagePreparer,
     Delay as SphinxDelay,
@@ -23,7 +24,7 @@ use nymsphinx::{
 use rand::{CryptoRng, Rng};
 use std::{
     sync::{Arc, Weak},
-    time::Duration,
+    time::{Duration, Instant},
 };
 use tokio::task::JoinHandle;
 
@@ -53,20 +54,48 @@ pub(crate) struct PendingAcknowledgement {
     message_chunk: Fragment,
     delay: SphinxDelay,
     recipient: Recipient,
+
+    /// Application-provided id of the message this `Fragment` is part of, if its delivery
+    /// should be reported.
+    message_id: Option<MessageId>,
+
+    /// Number of times this `Fragment` has already been retransmitted.
+    retransmissions: u32,
+
+    /// Time at which this `Fragment` was first put on route.
+    first_sent: Instant,
+
+    /// Mix nodes the most recent transmission of this `Fragment` went through.
+    mix_route: Vec<NymNodeRoutingAddress>,
 }
 
 impl PendingAcknowledgement {
     /// Creates new instance of `PendingAcknowledgement` using the provided data.
-    fn new(message_chunk: Fragment, delay: SphinxDelay, recipient: Recipient) -> Self {
+    fn new(
+        message_chunk: Fragment,
+        delay: SphinxDelay,
+        recipient: Recipient,
+        message_id: Option<MessageId>,
+        mix_route: Vec<NymNodeRoutingAddress>,
+    ) -> Self {
         PendingAcknowledgement {
             message_chunk,
             delay,
             recipient,
+            message_id,
+            retransmissions: 0,
+            first_sent: Instant::now(),
+            mix_route,
         }
     }
 
-    fn update_delay(&mut self, new_delay: SphinxDelay) {
+    fn update_delay(&mut self, new_delay: SphinxDelay, new_mix_route: Vec<NymNodeRoutingAddress>) {
         self.delay = new_delay;
+        self.mix_route = new_mix_route;
+    }
+
+    fn increment_retransmissions(&mut self) {
+        self.retransmissions += 1;
     }
 }
 
@@ -88,6 +117,9 @@ pub(super) struct AcknowledgementControllerConnectors {
 
     /// Channel used for receiving acknowledgements from the mix network.
     ack_receiver: AcknowledgementReceiver,
+
+    /// Optional channel used for reporting delivery status of messages sent with an id.
+    delivery_receipt_sender: Option<DeliveryReceiptSender>,
 }
 
 impl AcknowledgementControllerConnectors {
@@ -96,12 +128,14 @@ impl AcknowledgementControllerConnectors {
         input_receiver: InputMessageReceiver,
         sent_notifier: SentPacketNotificationReceiver,
         ack_receiver: AcknowledgementReceiver,
+        delivery_receipt_sender: Option<DeliveryReceiptSender>,
     ) -> Self {
         AcknowledgementControllerConnectors {
             real_message_sender,
             input_receiver,
             sent_notifier,
             ack_receiver,
+            delivery_receipt_sender,
         }
     }
 }
@@ -114,6 +148,15 @@ pub(super) struct Config {
     /// Given ack timeout in the form a * BASE_DELAY + b, it specifies the multiplier `a`
     ack_wait_multiplier: f64,
 
+    /// Maximum number of retransmissions of a single packet before its message is abandoned.
+    maximum_retransmissions: u32,
+
+    /// Maximum time since the first transmission during which a packet can be retransmitted.
+    retransmission_deadline: Duration,
+
+    /// Multiplier applied to the ack timeout upon each subsequent retransmission of a packet.
+    retransmission_backoff_multiplier: f64,
+
     /// Average delay an acknowledgement packet is going to get delayed at a single mixnode.
     average_ack_delay: Duration,
 
@@ -125,12 +168,18 @@ impl Config {
     pub(super) fn new(
         ack_wait_addition: Duration,
         ack_wait_multiplier: f64,
+        maximum_retransmissions: u32,
+        retransmission_deadline: Duration,
+        retransmission_backoff_multiplier: f64,
         average_ack_delay: Duration,
         average_packet_delay: Duration,
     ) -> Self {
         Config {
             ack_wait_addition,
             ack_wait_multiplier,
+            maximum_retransmissions,
+            retransmission_deadline,
+            retransmission_backoff_multiplier,
             average_ack_delay,
             average_packet_delay,
         }
@@ -163,10 +212,18 @@ where
     ) -> Self {
         let (retransmission_tx, retransmission_rx) = mpsc::unbounded();
 
-        let action_config =
-            action_controller::Config::new(config.ack_wait_addition, config.ack_wait_multiplier);
-        let (action_controller, action_sender) =
-            ActionController::new(action_config, retransmission_tx);
+        let action_config = action_controller::Config::new(
+            config.ack_wait_addition,
+            config.ack_wait_multiplier,
+            config.maximum_retransmissions,
+            config.retransmission_deadline,
+            config.retransmission_backoff_multiplier,
+        );
+        let (action_controller, action_sender) = ActionController::new(
+            action_config,
+            retransmission_tx,
+            connectors.delivery_receipt_sender.clone(),
+        );
 
         let message_preparer = MessagePreparer::new(
             rng,
@@ -192,6 +249,7 @@ where
             connectors.real_message_sender.clone(),
             topology_access.clone(),
             reply_key_storage,
+            connectors.delivery_receipt_sender,
         );
 
         // will listen for any ack timeouts and trigger retransmission
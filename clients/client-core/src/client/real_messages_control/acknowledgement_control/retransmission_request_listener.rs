@@ -80,11 +80,37 @@ where
             }
         };
 
-        let prepared_fragment = self
+        // any of the nodes the previous attempt went through might be the reason the packet
+        // got lost, so try to route around them. If that's impossible, for example because
+        // the network is too small, just sample a fresh route from the whole topology.
+        let rerouted_topology = topology_ref.without_mixes(&timed_out_ack.mix_route);
+        let prepared_fragment = match self
             .message_preparer
-            .prepare_chunk_for_sending(chunk_clone, topology_ref, &self.ack_key, packet_recipient)
+            .prepare_chunk_for_sending(
+                chunk_clone.clone(),
+                &rerouted_topology,
+                &self.ack_key,
+                packet_recipient,
+            )
             .await
-            .unwrap();
+        {
+            Ok(prepared_fragment) => prepared_fragment,
+            Err(err) => {
+                debug!(
+                    "Could not route {} around its previous route - {:?}. Using any available nodes instead",
+                    frag_id, err
+                );
+                self.message_preparer
+                    .prepare_chunk_for_sending(
+                        chunk_clone,
+                        topology_ref,
+                        &self.ack_key,
+                        packet_recipient,
+                    )
+                    .await
+                    .unwrap()
+            }
+        };
 
         // if we have the ONLY strong reference to the ack data, it means it was removed from the
         // pending acks
@@ -99,6 +125,7 @@ where
         drop(timed_out_ack);
 
         let new_delay = prepared_fragment.total_delay;
+        let new_mix_route = prepared_fragment.mix_route;
 
         // We know this update will be reflected by the `StartTimer` Action performed when this
         // message is sent through the mix network.
@@ -108,7 +135,7 @@ where
         // with the additional poisson delay.
         // And since Actions are executed in order `UpdateTimer` will HAVE TO be executed before `StartTimer`
         self.action_sender
-            .unbounded_send(Action::new_update_delay(frag_id, new_delay))
+            .unbounded_send(Action::new_update_delay(frag_id, new_delay, new_mix_route))
             .unwrap();
 
         // send to `OutQueueControl` to eventually send to the mix network
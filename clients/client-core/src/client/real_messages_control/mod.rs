@@ -8,6 +8,7 @@
 use self::{
     acknowledgement_control::AcknowledgementController, real_traffic_stream::OutQueueControl,
 };
+use crate::client::delivery_receipts::DeliveryReceiptSender;
 use crate::client::real_messages_control::acknowledgement_control::AcknowledgementControllerConnectors;
 use crate::client::reply_key_storage::ReplyKeyStorage;
 use crate::client::{
@@ -38,6 +39,15 @@ pub struct Config {
     /// Given ack timeout in the form a * BASE_DELAY + b, it specifies the multiplier `a`
     ack_wait_multiplier: f64,
 
+    /// Maximum number of retransmissions of a single packet before its message is abandoned.
+    maximum_retransmissions: u32,
+
+    /// Maximum time since the first transmission during which a packet can be retransmitted.
+    retransmission_deadline: Duration,
+
+    /// Multiplier applied to the ack timeout upon each subsequent retransmission of a packet.
+    retransmission_backoff_multiplier: f64,
+
     /// Address of `this` client.
     self_recipient: Recipient,
 
@@ -56,6 +66,9 @@ impl Config {
         ack_key: Arc<AckKey>,
         ack_wait_multiplier: f64,
         ack_wait_addition: Duration,
+        maximum_retransmissions: u32,
+        retransmission_deadline: Duration,
+        retransmission_backoff_multiplier: f64,
         average_ack_delay_duration: Duration,
         average_message_sending_delay: Duration,
         average_packet_delay_duration: Duration,
@@ -65,6 +78,9 @@ impl Config {
             ack_key,
             ack_wait_addition,
             ack_wait_multiplier,
+            maximum_retransmissions,
+            retransmission_deadline,
+            retransmission_backoff_multiplier,
             self_recipient,
             average_message_sending_delay,
             average_packet_delay_duration,
@@ -91,6 +107,7 @@ impl RealMessagesController<OsRng> {
         mix_sender: BatchMixMessageSender,
         topology_access: TopologyAccessor,
         reply_key_storage: ReplyKeyStorage,
+        delivery_receipt_sender: Option<DeliveryReceiptSender>,
     ) -> Self {
         let rng = OsRng;
 
@@ -102,11 +119,15 @@ impl RealMessagesController<OsRng> {
             input_receiver,
             sent_notifier_rx,
             ack_receiver,
+            delivery_receipt_sender,
         );
 
         let ack_control_config = acknowledgement_control::Config::new(
             config.ack_wait_addition,
             config.ack_wait_multiplier,
+            config.maximum_retransmissions,
+            config.retransmission_deadline,
+            config.retransmission_backoff_multiplier,
             config.average_ack_delay_duration,
             config.average_packet_delay_duration,
         );
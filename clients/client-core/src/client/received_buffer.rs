@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::client::reply_key_storage::ReplyKeyStorage;
+use crate::client::reply_surb_storage::ReplySurbStorage;
 use crypto::asymmetric::encryption;
 use crypto::symmetric::stream_cipher;
 use crypto::Digest;
@@ -10,9 +11,12 @@ use futures::lock::Mutex;
 use futures::StreamExt;
 use gateway_client::MixnetMessageReceiver;
 use log::*;
-use nymsphinx::anonymous_replies::{encryption_key::EncryptionKeyDigest, SurbEncryptionKey};
+use nymsphinx::anonymous_replies::{
+    encryption_key::EncryptionKeyDigest, SenderTag, SurbEncryptionKey,
+};
 use nymsphinx::params::{ReplySurbEncryptionAlgorithm, ReplySurbKeyDigestAlgorithm};
 use nymsphinx::receiver::{MessageReceiver, MessageRecoveryError, ReconstructedMessage};
+use rand::rngs::OsRng;
 use std::collections::HashSet;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
@@ -114,12 +118,17 @@ struct ReceivedMessagesBuffer {
     /// Storage containing keys to all [`ReplySURB`]s ever sent out that we did not receive back.
     // There's no need to put it behind a Mutex since it's already properly concurrent
     reply_key_storage: ReplyKeyStorage,
+
+    /// Optional storage for received [`ReplySURB`]s. If set, the SURBs attached to received
+    /// messages are stored under a fresh [`SenderTag`] instead of being passed on.
+    reply_surb_storage: Option<ReplySurbStorage>,
 }
 
 impl ReceivedMessagesBuffer {
     fn new(
         local_encryption_keypair: Arc<encryption::KeyPair>,
         reply_key_storage: ReplyKeyStorage,
+        reply_surb_storage: Option<ReplySurbStorage>,
     ) -> Self {
         ReceivedMessagesBuffer {
             inner: Arc::new(Mutex::new(ReceivedMessagesBufferInner {
@@ -130,6 +139,7 @@ impl ReceivedMessagesBuffer {
                 recently_reconstructed: HashSet::new(),
             })),
             reply_key_storage,
+            reply_surb_storage,
         }
     }
 
@@ -195,11 +205,32 @@ impl ReceivedMessagesBuffer {
             // TODO: perhaps having to say it doesn't have a surb an indication the type should be changed?
             Some(ReconstructedMessage {
                 message: reply_msg,
-                reply_surb: None,
+                reply_surbs: Vec::new(),
+                sender_tag: None,
             })
         }
     }
 
+    fn store_reply_surbs(&self, message: &mut ReconstructedMessage) {
+        let reply_surb_storage = match &self.reply_surb_storage {
+            Some(reply_surb_storage) if !message.reply_surbs.is_empty() => reply_surb_storage,
+            _ => return,
+        };
+
+        let sender_tag = SenderTag::new_random(&mut OsRng);
+        let reply_surbs = std::mem::take(&mut message.reply_surbs);
+        trace!(
+            "Storing {} reply SURBs under tag {}",
+            reply_surbs.len(),
+            sender_tag
+        );
+        match reply_surb_storage.insert_reply_surbs(sender_tag, reply_surbs) {
+            Ok(0) => (),
+            Ok(_) => message.sender_tag = Some(sender_tag),
+            Err(err) => error!("Failed to store the received reply SURBs - {:?}", err),
+        }
+    }
+
     async fn handle_new_received(&mut self, msgs: Vec<Vec<u8>>) {
         debug!(
             "Processing {:?} new message that might get added to the buffer!",
@@ -224,11 +255,19 @@ impl ReceivedMessagesBuffer {
 
             // TODO: this might be a bottleneck - since the keys are stored on disk we, presumably,
             // are doing a disk operation every single received fragment
-            if let Some(reply_encryption_key) = self
+            let reply_encryption_key = match self
                 .reply_key_storage
                 .get_and_remove_encryption_key(possible_key_digest)
-                .expect("storage operation failed!")
             {
+                Ok(reply_encryption_key) => reply_encryption_key,
+                Err(err) => {
+                    // we can't tell whether it's a reply, so we can't process it either way
+                    error!("Failed to read the reply key storage - {:?}", err);
+                    continue;
+                }
+            };
+
+            if let Some(reply_encryption_key) = reply_encryption_key {
                 if let Some(completed_message) = Self::process_received_reply(
                     &msg[reply_surb_digest_size..],
                     reply_encryption_key,
@@ -237,7 +276,8 @@ impl ReceivedMessagesBuffer {
                 }
             } else {
                 // otherwise - it's a 'normal' message
-                if let Some(completed_message) = inner_guard.process_received_fragment(msg) {
+                if let Some(mut completed_message) = inner_guard.process_received_fragment(msg) {
+                    self.store_reply_surbs(&mut completed_message);
                     completed_messages.push(completed_message)
                 }
             }
@@ -341,9 +381,13 @@ impl ReceivedMessagesBufferController {
         query_receiver: ReceivedBufferRequestReceiver,
         mixnet_packet_receiver: MixnetMessageReceiver,
         reply_key_storage: ReplyKeyStorage,
+        reply_surb_storage: Option<ReplySurbStorage>,
     ) -> Self {
-        let received_buffer =
-            ReceivedMessagesBuffer::new(local_encryption_keypair, reply_key_storage);
+        let received_buffer = ReceivedMessagesBuffer::new(
+            local_encryption_keypair,
+            reply_key_storage,
+            reply_surb_storage,
+        );
 
         ReceivedMessagesBufferController {
             fragmented_message_receiver: FragmentedMessageReceiver::new(
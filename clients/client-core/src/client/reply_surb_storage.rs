@@ -0,0 +1,615 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::client::topology_control::TopologyAccessor;
+use log::*;
+use nymsphinx::addressing::nodes::NymNodeRoutingAddress;
+use nymsphinx::anonymous_replies::{ReplySurb, SenderTag};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use topology::NymTopology;
+
+const REPLY_SURBS_TREE: &str = "reply_surbs";
+const FIRST_HOPS_TREE: &str = "reply_surb_first_hops";
+const KNOWN_MIX_NODES_TREE: &str = "known_mix_nodes";
+
+/// Maximum number of [`ReplySurb`]s stored under a single [`SenderTag`], i.e. attached to a single message.
+const MAX_REPLY_SURBS_PER_SENDER: usize = 100;
+
+/// Maximum number of [`ReplySurb`]s stored in total.
+const MAX_STORED_REPLY_SURBS: usize = 10_000;
+
+#[derive(Debug)]
+pub enum ReplySurbStorageError {
+    DbReadError(sled::Error),
+    DbWriteError(sled::Error),
+    DbOpenError(sled::Error),
+}
+
+/// Permanent storage for [`ReplySurb`]s received from other clients.
+///
+/// All reply SURBs attached to a single received message are stored under a locally assigned
+/// [`SenderTag`], so that the application can reply to the (anonymous) sender by just referring
+/// to the tag. Each [`ReplySurb`] can only be used once and is removed upon retrieval.
+///
+/// Since the route of a [`ReplySurb`] is fixed at the time of its creation, the SURB becomes
+/// useless once any node on that route leaves the network or changes its sphinx key. However,
+/// only the first hop of the route is visible to us (the rest is hidden in the encrypted header),
+/// so the storage keeps track of the first hop of every SURB alongside the sphinx keys the mix nodes
+/// had when it last looked at the topology, and only invalidates the SURBs whose first hop
+/// is gone or has changed its key. SURBs routed through other changed nodes can't be detected
+/// and are going to be silently lost in the network.
+///
+/// As the SURBs are supplied by remote clients, the number of them kept under a single tag as well as
+/// in total is limited and any SURBs above those limits are dropped.
+#[derive(Debug, Clone)]
+pub struct ReplySurbStorage {
+    db: sled::Db,
+    reply_surbs: sled::Tree,
+    // key of the SURB (same as in `reply_surbs`) => address of its first hop
+    first_hops: sled::Tree,
+    // mix node address => sphinx key of the node when the topology was last checked
+    known_mix_nodes: sled::Tree,
+    max_surbs_per_sender: usize,
+    max_stored_surbs: usize,
+}
+
+impl ReplySurbStorage {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplySurbStorageError> {
+        let db = sled::open(path).map_err(ReplySurbStorageError::DbOpenError)?;
+        let reply_surbs = db
+            .open_tree(REPLY_SURBS_TREE)
+            .map_err(ReplySurbStorageError::DbOpenError)?;
+        let first_hops = db
+            .open_tree(FIRST_HOPS_TREE)
+            .map_err(ReplySurbStorageError::DbOpenError)?;
+        let known_mix_nodes = db
+            .open_tree(KNOWN_MIX_NODES_TREE)
+            .map_err(ReplySurbStorageError::DbOpenError)?;
+
+        Ok(ReplySurbStorage {
+            db,
+            reply_surbs,
+            first_hops,
+            known_mix_nodes,
+            max_surbs_per_sender: MAX_REPLY_SURBS_PER_SENDER,
+            max_stored_surbs: MAX_STORED_REPLY_SURBS,
+        })
+    }
+
+    // tag || id
+    fn make_key(&self, sender_tag: SenderTag) -> Result<Vec<u8>, ReplySurbStorageError> {
+        // ids are monotonic, so the SURBs are going to be retrieved in the order they were stored
+        let id = self
+            .db
+            .generate_id()
+            .map_err(ReplySurbStorageError::DbWriteError)?;
+
+        Ok(sender_tag
+            .as_bytes()
+            .iter()
+            .cloned()
+            .chain(id.to_be_bytes().iter().cloned())
+            .collect())
+    }
+
+    /// Stores the provided [`ReplySurb`]s under the tag, skipping the malformed ones and
+    /// the ones exceeding the storage limits. Returns the number of stored SURBs.
+    pub fn insert_reply_surbs(
+        &self,
+        sender_tag: SenderTag,
+        reply_surbs: Vec<ReplySurb>,
+    ) -> Result<usize, ReplySurbStorageError> {
+        let stored_for_sender = self.reply_surbs.scan_prefix(sender_tag.as_bytes()).count();
+        let available = self
+            .max_surbs_per_sender
+            .saturating_sub(stored_for_sender)
+            .min(self.max_stored_surbs.saturating_sub(self.reply_surbs.len()));
+
+        let received = reply_surbs.len();
+        let mut stored = 0;
+        for reply_surb in reply_surbs {
+            if stored == available {
+                break;
+            }
+
+            let first_hop = match reply_surb.first_hop_address() {
+                Ok(first_hop) => first_hop,
+                Err(err) => {
+                    warn!("Received an invalid reply SURB - {}", err);
+                    continue;
+                }
+            };
+
+            let key = self.make_key(sender_tag)?;
+            self.first_hops
+                .insert(&key, first_hop.as_bytes())
+                .map_err(ReplySurbStorageError::DbWriteError)?;
+            self.reply_surbs
+                .insert(key, reply_surb.to_bytes())
+                .map_err(ReplySurbStorageError::DbWriteError)?;
+            stored += 1;
+        }
+
+        if stored == available && stored < received {
+            warn!(
+                "The reply SURB storage limit has been reached - dropped {} reply SURBs of {}",
+                received - stored,
+                sender_tag
+            );
+        }
+
+        self.db
+            .flush()
+            .map_err(ReplySurbStorageError::DbWriteError)?;
+        Ok(stored)
+    }
+
+    fn decode_reply_surb(raw_surb: &[u8]) -> ReplySurb {
+        // if this fails it means we have some database corruption and we
+        // absolutely can't continue
+        ReplySurb::from_bytes(raw_surb).unwrap_or_else(|err| {
+            error!("REPLY SURB STORAGE DATA CORRUPTION - {}", err);
+            panic!("REPLY SURB STORAGE DATA CORRUPTION - {}", err)
+        })
+    }
+
+    /// Retrieves and removes the oldest [`ReplySurb`] stored under the provided tag.
+    pub fn get_and_remove_reply_surb(
+        &self,
+        sender_tag: SenderTag,
+    ) -> Result<Option<ReplySurb>, ReplySurbStorageError> {
+        loop {
+            let (key, _) = match self.reply_surbs.scan_prefix(sender_tag.as_bytes()).next() {
+                None => return Ok(None),
+                Some(entry) => entry.map_err(ReplySurbStorageError::DbReadError)?,
+            };
+
+            // somebody else might have used that SURB in the meantime, in which case try the next one
+            if let Some(raw_surb) = self
+                .reply_surbs
+                .remove(&key)
+                .map_err(ReplySurbStorageError::DbWriteError)?
+            {
+                self.first_hops
+                    .remove(&key)
+                    .map_err(ReplySurbStorageError::DbWriteError)?;
+                self.db
+                    .flush()
+                    .map_err(ReplySurbStorageError::DbWriteError)?;
+
+                return Ok(Some(Self::decode_reply_surb(&raw_surb)));
+            }
+        }
+    }
+
+    /// Removes all SURBs whose first hop is no longer present in the provided topology or
+    /// whose sphinx key has changed since the previous check. Returns the number of removed SURBs.
+    pub fn invalidate_stale_routes(
+        &self,
+        topology: &NymTopology,
+    ) -> Result<usize, ReplySurbStorageError> {
+        let current_nodes: HashMap<_, _> = topology
+            .mixes()
+            .values()
+            .flatten()
+            .map(|node| {
+                (
+                    NymNodeRoutingAddress::from(node.mix_host).as_bytes(),
+                    node.sphinx_key.to_bytes(),
+                )
+            })
+            .collect();
+
+        let mut changed_nodes = HashSet::new();
+        for entry in self.known_mix_nodes.iter() {
+            let (address, sphinx_key) = entry.map_err(ReplySurbStorageError::DbReadError)?;
+            match current_nodes.get(address.as_ref()) {
+                Some(current_key) if current_key[..] == sphinx_key[..] => (),
+                _ => {
+                    changed_nodes.insert(address.to_vec());
+                }
+            }
+        }
+
+        let mut removed = 0;
+        for entry in self.reply_surbs.iter() {
+            let (key, raw_surb) = entry.map_err(ReplySurbStorageError::DbReadError)?;
+            let (first_hop, indexed) = match self
+                .first_hops
+                .get(&key)
+                .map_err(ReplySurbStorageError::DbReadError)?
+            {
+                Some(first_hop) => (Some(first_hop.to_vec()), true),
+                // SURB stored before we started keeping track of the first hops
+                None => (
+                    Self::decode_reply_surb(&raw_surb)
+                        .first_hop_address()
+                        .ok()
+                        .map(|first_hop| first_hop.as_bytes()),
+                    false,
+                ),
+            };
+
+            // SURBs with malformed first hop can't ever be used
+            let is_stale = match &first_hop {
+                Some(first_hop) => {
+                    changed_nodes.contains(first_hop) || !current_nodes.contains_key(first_hop)
+                }
+                None => true,
+            };
+
+            if is_stale {
+                if self
+                    .reply_surbs
+                    .remove(&key)
+                    .map_err(ReplySurbStorageError::DbWriteError)?
+                    .is_some()
+                {
+                    removed += 1;
+                }
+                self.first_hops
+                    .remove(&key)
+                    .map_err(ReplySurbStorageError::DbWriteError)?;
+            } else if let (Some(first_hop), false) = (first_hop, indexed) {
+                self.first_hops
+                    .insert(&key, first_hop)
+                    .map_err(ReplySurbStorageError::DbWriteError)?;
+            }
+        }
+
+        self.known_mix_nodes
+            .clear()
+            .map_err(ReplySurbStorageError::DbWriteError)?;
+        for (address, sphinx_key) in current_nodes {
+            self.known_mix_nodes
+                .insert(address, &sphinx_key)
+                .map_err(ReplySurbStorageError::DbWriteError)?;
+        }
+
+        self.db
+            .flush()
+            .map_err(ReplySurbStorageError::DbWriteError)?;
+        Ok(removed)
+    }
+}
+
+/// Periodically checks the current network topology and expires the stored reply SURBs
+/// whose routes are no longer valid.
+pub struct ReplySurbExpirer {
+    storage: ReplySurbStorage,
+    topology_accessor: TopologyAccessor,
+    check_rate: Duration,
+}
+
+impl ReplySurbExpirer {
+    pub fn new(
+        storage: ReplySurbStorage,
+        topology_accessor: TopologyAccessor,
+        check_rate: Duration,
+    ) -> Self {
+        ReplySurbExpirer {
+            storage,
+            topology_accessor,
+            check_rate,
+        }
+    }
+
+    async fn check_topology(&self) {
+        let permit = self.topology_accessor.get_read_permit().await;
+        let topology = match permit.as_ref() {
+            // if we don't know the current topology, we can't tell whether the SURBs are valid
+            None => return,
+            Some(topology) => topology,
+        };
+
+        match self.storage.invalidate_stale_routes(topology) {
+            Err(err) => error!("Failed to expire stale reply SURBs - {:?}", err),
+            Ok(0) => (),
+            Ok(removed) => info!(
+                "The network topology has changed - removed {} stale reply SURBs",
+                removed
+            ),
+        }
+    }
+
+    pub fn start(self) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            loop {
+                self.check_topology().await;
+                tokio::time::sleep(self.check_rate).await;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crypto::asymmetric::{encryption, identity};
+    use nymsphinx::addressing::clients::Recipient;
+    use rand::rngs::OsRng;
+    use std::convert::TryFrom;
+    use topology::serialized::SerializableTopology;
+
+    const GATEWAY_HOST: &str = "1.2.3.4";
+
+    fn random_sphinx_key() -> String {
+        encryption::KeyPair::new(&mut OsRng)
+            .public_key()
+            .to_base58_string()
+    }
+
+    fn random_identity() -> identity::PublicKey {
+        *identity::KeyPair::new(&mut OsRng).public_key()
+    }
+
+    // (host, layer, sphinx key)
+    fn topology(mixes: &[(&str, u8, &str)], gateway: &identity::PublicKey) -> NymTopology {
+        let mut content = String::new();
+        for (host, layer, sphinx_key) in mixes {
+            content += &format!(
+                "[[mixnodes]]\nhost = '{}'\nmix_port = 1789\nidentity_key = '{}'\nsphinx_key = '{}'\nlayer = {}\nversion = '1.0.1'\n\n",
+                host,
+                random_identity().to_base58_string(),
+                sphinx_key,
+                layer
+            );
+        }
+        content += &format!(
+            "[[gateways]]\nhost = '{}'\nmix_port = 1789\nclients_port = 9000\nidentity_key = '{}'\nsphinx_key = '{}'\nversion = '1.0.1'\n",
+            GATEWAY_HOST,
+            gateway.to_base58_string(),
+            random_sphinx_key()
+        );
+
+        let serialized: SerializableTopology = toml::from_str(&content).unwrap();
+        NymTopology::try_from(serialized).unwrap()
+    }
+
+    fn reply_surbs(
+        topology: &NymTopology,
+        gateway: &identity::PublicKey,
+        n: usize,
+    ) -> Vec<ReplySurb> {
+        let recipient = Recipient::new(
+            random_identity(),
+            *encryption::KeyPair::new(&mut OsRng).public_key(),
+            *gateway,
+        );
+        (0..n)
+            .map(|_| {
+                ReplySurb::construct(&mut OsRng, &recipient, Duration::from_millis(50), topology)
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn drain(storage: &ReplySurbStorage, sender_tag: SenderTag) -> usize {
+        let mut count = 0;
+        while storage
+            .get_and_remove_reply_surb(sender_tag)
+            .unwrap()
+            .is_some()
+        {
+            count += 1;
+        }
+        count
+    }
+
+    struct TestNetwork {
+        gateway: identity::PublicKey,
+        first_hop_a: String,
+        first_hop_b: String,
+        second_layer: String,
+        third_layer: String,
+    }
+
+    impl TestNetwork {
+        fn new() -> Self {
+            TestNetwork {
+                gateway: random_identity(),
+                first_hop_a: random_sphinx_key(),
+                first_hop_b: random_sphinx_key(),
+                second_layer: random_sphinx_key(),
+                third_layer: random_sphinx_key(),
+            }
+        }
+
+        // topology in which the only available first hop is node 'a'
+        fn only_a(&self) -> NymTopology {
+            topology(
+                &[
+                    ("10.0.0.1", 1, &self.first_hop_a),
+                    ("10.0.0.3", 2, &self.second_layer),
+                    ("10.0.0.4", 3, &self.third_layer),
+                ],
+                &self.gateway,
+            )
+        }
+
+        // topology in which the only available first hop is node 'b'
+        fn only_b(&self) -> NymTopology {
+            topology(
+                &[
+                    ("10.0.0.2", 1, &self.first_hop_b),
+                    ("10.0.0.3", 2, &self.second_layer),
+                    ("10.0.0.4", 3, &self.third_layer),
+                ],
+                &self.gateway,
+            )
+        }
+
+        fn full(&self) -> NymTopology {
+            topology(
+                &[
+                    ("10.0.0.1", 1, &self.first_hop_a),
+                    ("10.0.0.2", 1, &self.first_hop_b),
+                    ("10.0.0.3", 2, &self.second_layer),
+                    ("10.0.0.4", 3, &self.third_layer),
+                ],
+                &self.gateway,
+            )
+        }
+    }
+
+    #[test]
+    fn reply_surbs_are_retrieved_in_order_and_only_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = ReplySurbStorage::load(dir.path().join("reply_surbs")).unwrap();
+        let network = TestNetwork::new();
+
+        let tag1 = SenderTag::new_random(&mut OsRng);
+        let tag2 = SenderTag::new_random(&mut OsRng);
+        let surbs = reply_surbs(&network.full(), &network.gateway, 2);
+        let expected = surbs.iter().map(|surb| surb.to_bytes()).collect::<Vec<_>>();
+
+        storage.insert_reply_surbs(tag1, surbs).unwrap();
+        storage
+            .insert_reply_surbs(tag2, reply_surbs(&network.full(), &network.gateway, 1))
+            .unwrap();
+
+        for expected in expected {
+            let retrieved = storage.get_and_remove_reply_surb(tag1).unwrap().unwrap();
+            assert_eq!(retrieved.to_bytes(), expected);
+        }
+        assert!(storage.get_and_remove_reply_surb(tag1).unwrap().is_none());
+        assert_eq!(drain(&storage, tag2), 1);
+    }
+
+    #[test]
+    fn first_hop_is_decoded_from_the_surb() {
+        let network = TestNetwork::new();
+        for reply_surb in reply_surbs(&network.only_a(), &network.gateway, 3) {
+            assert_eq!(
+                reply_surb.first_hop_address().unwrap().to_string(),
+                "10.0.0.1:1789"
+            );
+        }
+    }
+
+    #[test]
+    fn stored_reply_surbs_are_limited() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = ReplySurbStorage {
+            max_surbs_per_sender: 3,
+            max_stored_surbs: 5,
+            ..ReplySurbStorage::load(dir.path().join("reply_surbs")).unwrap()
+        };
+        let network = TestNetwork::new();
+
+        let tag1 = SenderTag::new_random(&mut OsRng);
+        let tag2 = SenderTag::new_random(&mut OsRng);
+        let tag3 = SenderTag::new_random(&mut OsRng);
+
+        let insert = |tag, n| {
+            storage
+                .insert_reply_surbs(tag, reply_surbs(&network.full(), &network.gateway, n))
+                .unwrap()
+        };
+
+        // the limit applies per sender
+        assert_eq!(insert(tag1, 4), 3);
+        assert_eq!(insert(tag1, 1), 0);
+        // and in total
+        assert_eq!(insert(tag2, 3), 2);
+        assert_eq!(insert(tag3, 1), 0);
+
+        // using up the SURBs frees the space
+        assert_eq!(drain(&storage, tag1), 3);
+        assert_eq!(insert(tag3, 2), 2);
+        assert_eq!(drain(&storage, tag2), 2);
+        assert_eq!(drain(&storage, tag3), 2);
+    }
+
+    #[test]
+    fn only_surbs_with_changed_first_hop_are_invalidated() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = ReplySurbStorage::load(dir.path().join("reply_surbs")).unwrap();
+        let network = TestNetwork::new();
+
+        let tag_a = SenderTag::new_random(&mut OsRng);
+        let tag_b = SenderTag::new_random(&mut OsRng);
+        storage
+            .insert_reply_surbs(tag_a, reply_surbs(&network.only_a(), &network.gateway, 3))
+            .unwrap();
+        storage
+            .insert_reply_surbs(tag_b, reply_surbs(&network.only_b(), &network.gateway, 2))
+            .unwrap();
+
+        // nothing changed
+        assert_eq!(storage.invalidate_stale_routes(&network.full()).unwrap(), 0);
+        assert_eq!(storage.invalidate_stale_routes(&network.full()).unwrap(), 0);
+
+        // first hop of the 'b' SURBs has rotated its key
+        let rotated = TestNetwork {
+            first_hop_b: random_sphinx_key(),
+            ..network
+        };
+        assert_eq!(storage.invalidate_stale_routes(&rotated.full()).unwrap(), 2);
+        assert_eq!(drain(&storage, tag_b), 0);
+
+        // first hop of the 'a' SURBs has left the network
+        assert_eq!(
+            storage.invalidate_stale_routes(&rotated.only_b()).unwrap(),
+            3
+        );
+        assert_eq!(drain(&storage, tag_a), 0);
+    }
+
+    #[test]
+    fn surbs_are_kept_if_hidden_hops_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = ReplySurbStorage::load(dir.path().join("reply_surbs")).unwrap();
+        let network = TestNetwork::new();
+
+        let tag = SenderTag::new_random(&mut OsRng);
+        storage
+            .insert_reply_surbs(tag, reply_surbs(&network.full(), &network.gateway, 2))
+            .unwrap();
+        assert_eq!(storage.invalidate_stale_routes(&network.full()).unwrap(), 0);
+
+        // we can't see the rest of the route, so we can't tell whether the SURBs got invalidated
+        let changed = TestNetwork {
+            second_layer: random_sphinx_key(),
+            ..network
+        };
+        assert_eq!(storage.invalidate_stale_routes(&changed.full()).unwrap(), 0);
+        assert_eq!(drain(&storage, tag), 2);
+    }
+
+    #[tokio::test]
+    async fn expirer_invalidates_surbs_after_topology_change() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = ReplySurbStorage::load(dir.path().join("reply_surbs")).unwrap();
+        let network = TestNetwork::new();
+
+        let tag = SenderTag::new_random(&mut OsRng);
+        storage
+            .insert_reply_surbs(tag, reply_surbs(&network.only_a(), &network.gateway, 2))
+            .unwrap();
+
+        let topology_accessor = TopologyAccessor::new();
+        let expirer = ReplySurbExpirer::new(
+            storage.clone(),
+            topology_accessor.clone(),
+            Duration::from_secs(60),
+        );
+
+        // unknown topology doesn't invalidate anything
+        expirer.check_topology().await;
+        topology_accessor
+            .update_global_topology(Some(network.full()))
+            .await;
+        expirer.check_topology().await;
+        assert!(storage.get_and_remove_reply_surb(tag).unwrap().is_some());
+
+        topology_accessor
+            .update_global_topology(Some(network.only_b()))
+            .await;
+        expirer.check_topology().await;
+        assert_eq!(drain(&storage, tag), 0);
+    }
+}
@@ -0,0 +1,248 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use async_trait::async_trait;
+use log::*;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use topology::provider::TopologyProvider;
+use topology::serialized::{SerializableTopology, SerializedTopologyError};
+use topology::NymTopology;
+
+#[derive(Debug)]
+pub enum TopologyFileError {
+    ReadError(io::Error),
+    MalformedJson(serde_json::Error),
+    MalformedToml(toml::de::Error),
+    InvalidTopology(SerializedTopologyError),
+}
+
+impl Display for TopologyFileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TopologyFileError::ReadError(err) => {
+                write!(f, "failed to read the topology file - {}", err)
+            }
+            TopologyFileError::MalformedJson(err) => {
+                write!(f, "the topology file is not a valid JSON - {}", err)
+            }
+            TopologyFileError::MalformedToml(err) => {
+                write!(f, "the topology file is not a valid TOML - {}", err)
+            }
+            TopologyFileError::InvalidTopology(err) => {
+                write!(f, "the topology file contains an invalid node - {}", err)
+            }
+        }
+    }
+}
+
+fn is_topology_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|ext| ext.to_str()),
+        Some("json") | Some("toml")
+    )
+}
+
+/// Returns the topology file the provided path refers to alongside its modification time.
+/// If the path points to a directory, it is the most recently modified JSON or TOML file in it.
+async fn resolve_topology_file(path: &Path) -> io::Result<(PathBuf, SystemTime)> {
+    let metadata = tokio::fs::metadata(path).await?;
+    if !metadata.is_dir() {
+        return Ok((path.to_path_buf(), metadata.modified()?));
+    }
+
+    let mut newest: Option<(PathBuf, SystemTime)> = None;
+    let mut entries = tokio::fs::read_dir(path).await?;
+    while let Some(entry) = entries.next_entry().await? {
+        let entry_path = entry.path();
+        let metadata = entry.metadata().await?;
+        if !metadata.is_file() || !is_topology_file(&entry_path) {
+            continue;
+        }
+        let modified = metadata.modified()?;
+        // compare the paths too, so that the choice does not depend on the directory order
+        if newest
+            .as_ref()
+            .map_or(true, |(newest_path, newest_modified)| {
+                (modified, &entry_path) > (*newest_modified, newest_path)
+            })
+        {
+            newest = Some((entry_path, modified));
+        }
+    }
+
+    newest.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("{:?} does not contain any JSON or TOML file", path),
+        )
+    })
+}
+
+/// Loads the network topology from the provided file, or the most recently modified JSON or TOML
+/// file if the path points to a directory. The file is expected to contain
+/// a [`SerializableTopology`] in TOML if its extension is `toml` and in JSON otherwise.
+pub async fn load_topology_file<P: AsRef<Path>>(path: P) -> Result<NymTopology, TopologyFileError> {
+    let (path, _) = resolve_topology_file(path.as_ref())
+        .await
+        .map_err(TopologyFileError::ReadError)?;
+    read_topology_file(&path).await
+}
+
+async fn read_topology_file(path: &Path) -> Result<NymTopology, TopologyFileError> {
+    let content = tokio::fs::read_to_string(path)
+        .await
+        .map_err(TopologyFileError::ReadError)?;
+
+    let serialized: SerializableTopology =
+        if path.extension().and_then(|ext| ext.to_str()) == Some("toml") {
+            toml::from_str(&content).map_err(TopologyFileError::MalformedToml)?
+        } else {
+            serde_json::from_str(&content).map_err(TopologyFileError::MalformedJson)?
+        };
+
+    NymTopology::try_from(serialized).map_err(TopologyFileError::InvalidTopology)
+}
+
+/// Provider reading the topology from a file, which gets reloaded whenever it is modified,
+/// so that the view of the network could be changed without restarting the client.
+/// If the path points to a directory, the most recently modified JSON or TOML file in it is used,
+/// so that a new topology could also be dropped into the directory as a new file.
+/// Note that the changes are only picked up whenever the topology gets refreshed.
+pub struct WatchedFileTopologyProvider {
+    path: PathBuf,
+    last_loaded: Option<(PathBuf, SystemTime)>,
+    topology: Option<NymTopology>,
+}
+
+impl WatchedFileTopologyProvider {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        WatchedFileTopologyProvider {
+            path: path.as_ref().to_path_buf(),
+            last_loaded: None,
+            topology: None,
+        }
+    }
+
+    async fn reload_if_modified(&mut self) {
+        let current = match resolve_topology_file(&self.path).await {
+            Ok(current) => current,
+            Err(err) => {
+                warn!(
+                    "failed to check modification time of the topology file {:?} - {}",
+                    self.path, err
+                );
+                return;
+            }
+        };
+
+        if self.last_loaded.as_ref() == Some(&current) {
+            return;
+        }
+
+        match read_topology_file(&current.0).await {
+            Ok(topology) => {
+                info!("Loaded network topology from {:?}", current.0);
+                self.topology = Some(topology);
+                self.last_loaded = Some(current);
+            }
+            // keep on using the previous topology, the file might be in the middle of being written
+            Err(err) => warn!("failed to reload {:?} - {}", current.0, err),
+        }
+    }
+}
+
+#[async_trait]
+impl TopologyProvider for WatchedFileTopologyProvider {
+    async fn get_new_topology(&mut self) -> Option<NymTopology> {
+        self.reload_if_modified().await;
+        self.topology.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    const TOPOLOGY_TOML: &str = r#"
+        [[mixnodes]]
+        host = '127.0.0.1'
+        mix_port = 1789
+        identity_key = '3ebjp1Fb9hdcS1AR6AZihgeJiMHkB5jjJUsvqNnfQwU7'
+        sphinx_key = 'C7cown6dYCLZpLiMFC1PaBmhvLvmJmLDJGeRTbPD45bX'
+        layer = 1
+        version = '1.0.1'
+
+        [[gateways]]
+        host = '127.0.0.1'
+        mix_port = 1790
+        clients_port = 9000
+        identity_key = '3ebjp1Fb9hdcS1AR6AZihgeJiMHkB5jjJUsvqNnfQwU7'
+        sphinx_key = 'C7cown6dYCLZpLiMFC1PaBmhvLvmJmLDJGeRTbPD45bX'
+        version = '1.0.1'
+    "#;
+
+    #[tokio::test]
+    async fn topology_is_loaded_from_toml_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("topology.toml");
+        std::fs::write(&path, TOPOLOGY_TOML).unwrap();
+
+        let topology = load_topology_file(&path).await.unwrap();
+        assert_eq!(topology.mixes()[&1].len(), 1);
+        assert_eq!(topology.gateways().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn watched_topology_is_reloaded_after_file_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("topology.toml");
+        std::fs::write(&path, TOPOLOGY_TOML).unwrap();
+
+        let mut provider = WatchedFileTopologyProvider::new(&path);
+        let topology = provider.get_new_topology().await.unwrap();
+        assert_eq!(topology.gateways()[0].clients_port, 9000);
+
+        // make sure the modification time is going to differ even on coarse filesystems
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        std::fs::write(&path, TOPOLOGY_TOML.replace("9000", "9005")).unwrap();
+
+        let topology = provider.get_new_topology().await.unwrap();
+        assert_eq!(topology.gateways()[0].clients_port, 9005);
+
+        // malformed changes do not invalidate the previously loaded topology
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        std::fs::write(&path, "definitely not a topology").unwrap();
+
+        let topology = provider.get_new_topology().await.unwrap();
+        assert_eq!(topology.gateways()[0].clients_port, 9005);
+    }
+
+    #[tokio::test]
+    async fn newest_topology_file_of_watched_directory_is_used() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("old.toml"), TOPOLOGY_TOML).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a topology").unwrap();
+
+        let mut provider = WatchedFileTopologyProvider::new(dir.path());
+        let topology = provider.get_new_topology().await.unwrap();
+        assert_eq!(topology.gateways()[0].clients_port, 9000);
+
+        tokio::time::sleep(Duration::from_millis(1100)).await;
+        std::fs::write(
+            dir.path().join("new.toml"),
+            TOPOLOGY_TOML.replace("9000", "9005"),
+        )
+        .unwrap();
+
+        let topology = provider.get_new_topology().await.unwrap();
+        assert_eq!(topology.gateways()[0].clients_port, 9005);
+        // as is for loading the topology just once
+        let topology = load_topology_file(dir.path()).await.unwrap();
+        assert_eq!(topology.gateways()[0].clients_port, 9005);
+    }
+}
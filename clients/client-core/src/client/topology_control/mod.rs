@@ -1,19 +1,26 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crate::config::Config;
+use config::NymConfig;
 use log::*;
 use nymsphinx::addressing::clients::Recipient;
 use nymsphinx::params::DEFAULT_NUM_MIX_HOPS;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
 use std::ops::Deref;
 use std::sync::Arc;
 use std::time;
 use std::time::Duration;
 use tokio::sync::{RwLock, RwLockReadGuard};
 use tokio::task::JoinHandle;
-use topology::{nym_topology_from_bonds, NymTopology};
-use url::Url;
+use topology::provider::{StaticTopologyProvider, TopologyProvider};
+use topology::route_selection::{RouteSelection, RouteSelectionStrategy};
+use topology::NymTopology;
+
+pub use file_provider::{load_topology_file, TopologyFileError, WatchedFileTopologyProvider};
+pub use validator_api_provider::ValidatorApiTopologyProvider;
+
+mod file_provider;
+mod validator_api_provider;
 
 // I'm extremely curious why compiler NEVER complained about lack of Debug here before
 #[derive(Debug)]
@@ -106,7 +113,7 @@ impl TopologyAccessor {
         self.inner.read().await.into()
     }
 
-    async fn update_global_topology(&self, new_topology: Option<NymTopology>) {
+    pub(crate) async fn update_global_topology(&self, new_topology: Option<NymTopology>) {
         self.inner.write().await.update(new_topology);
     }
 
@@ -127,166 +134,81 @@ impl Default for TopologyAccessor {
 }
 
 pub struct TopologyRefresherConfig {
-    validator_api_urls: Vec<Url>,
     refresh_rate: time::Duration,
-    client_version: String,
+    route_selection: RouteSelection,
 }
 
 impl TopologyRefresherConfig {
-    pub fn new(
-        validator_api_urls: Vec<Url>,
-        refresh_rate: time::Duration,
-        client_version: String,
-    ) -> Self {
+    pub fn new(refresh_rate: time::Duration, route_selection: RouteSelection) -> Self {
         TopologyRefresherConfig {
-            validator_api_urls,
             refresh_rate,
-            client_version,
+            route_selection,
         }
     }
 }
 
-pub struct TopologyRefresher {
-    validator_client: validator_client::ApiClient,
+/// Creates the source of the network topology set in the client configuration, i.e. either
+/// the validator APIs or a local topology file.
+///
+/// # Arguments
+///
+/// * `config`: base configuration of the client.
+/// * `client_version`: version of the client, used for filtering out incompatible nodes.
+pub async fn topology_provider_from_config<T: NymConfig>(
+    config: &Config<T>,
     client_version: String,
+) -> Result<Box<dyn TopologyProvider + Send>, TopologyFileError> {
+    Ok(match config.get_topology_file() {
+        Some(topology_file) if config.get_watch_topology_file() => {
+            Box::new(WatchedFileTopologyProvider::new(topology_file))
+        }
+        Some(topology_file) => Box::new(StaticTopologyProvider::new(
+            load_topology_file(&topology_file).await?,
+        )),
+        None => Box::new(ValidatorApiTopologyProvider::new(
+            config.get_validator_api_endpoints(),
+            client_version,
+            config.get_route_selection().strategy == RouteSelectionStrategy::UptimeWeighted,
+        )),
+    })
+}
+
+pub struct TopologyRefresher {
+    topology_provider: Box<dyn TopologyProvider + Send>,
+    route_selection: RouteSelection,
 
-    validator_api_urls: Vec<Url>,
     topology_accessor: TopologyAccessor,
     refresh_rate: Duration,
 
-    currently_used_api: usize,
     was_latest_valid: bool,
 }
 
 impl TopologyRefresher {
-    pub fn new(mut cfg: TopologyRefresherConfig, topology_accessor: TopologyAccessor) -> Self {
-        cfg.validator_api_urls.shuffle(&mut thread_rng());
-
+    pub fn new(
+        cfg: TopologyRefresherConfig,
+        topology_provider: Box<dyn TopologyProvider + Send>,
+        topology_accessor: TopologyAccessor,
+    ) -> Self {
         TopologyRefresher {
-            validator_client: validator_client::ApiClient::new(cfg.validator_api_urls[0].clone()),
-            client_version: cfg.client_version,
-            validator_api_urls: cfg.validator_api_urls,
+            topology_provider,
+            route_selection: cfg.route_selection,
             topology_accessor,
             refresh_rate: cfg.refresh_rate,
-            currently_used_api: 0,
             was_latest_valid: true,
         }
     }
 
-    fn use_next_validator_api(&mut self) {
-        if self.validator_api_urls.len() == 1 {
-            warn!("There's only a single validator API available - it won't be possible to use a different one");
-            return;
-        }
-
-        self.currently_used_api = (self.currently_used_api + 1) % self.validator_api_urls.len();
-        self.validator_client
-            .change_validator_api(self.validator_api_urls[self.currently_used_api].clone())
-    }
-
-    /// Verifies whether nodes a reasonably distributed among all mix layers.
-    ///
-    /// In ideal world we would have 33% nodes on layer 1, 33% on layer 2 and 33% on layer 3.
-    /// However, this is a rather unrealistic expectation, instead we check whether there exists
-    /// a layer with more than 66% of nodes or with fewer than 15% and if so, we trigger a failure.
-    ///
-    /// # Arguments
-    ///
-    /// * `topology`: active topology constructed from validator api data
-    /// * `mixnodes_count`: total number of active mixnodes
-    fn check_layer_distribution(
-        &self,
-        active_topology: &NymTopology,
-        mixnodes_count: usize,
-    ) -> bool {
-        let mixes = active_topology.mixes();
-        if active_topology.gateways().is_empty() {
-            return false;
-        }
-
-        // trivial check to see if have at least a single node on each layer (regardless of active set size)
-        if mixes.get(&1).is_none() || mixes.get(&2).is_none() || mixes.get(&3).is_none() {
-            return false;
-        }
-
-        let upper_bound = (mixnodes_count as f32 * 0.66) as usize;
-        let lower_bound = (mixnodes_count as f32 * 0.15) as usize;
-
-        let layer1 = mixes.get(&1).unwrap().len();
-        let layer2 = mixes.get(&2).unwrap().len();
-        let layer3 = mixes.get(&3).unwrap().len();
-
-        if layer1 < lower_bound || layer1 > upper_bound {
-            warn!(
-                "nodes: {}, layer1: {}, layer2: {}, layer3: {}",
-                mixnodes_count, layer1, layer2, layer3
-            );
-            return false;
-        }
-
-        if layer2 < lower_bound || layer2 > upper_bound {
-            warn!(
-                "nodes: {}, layer1: {}, layer2: {}, layer3: {}",
-                mixnodes_count, layer1, layer2, layer3
-            );
-            return false;
-        }
-
-        if layer3 < lower_bound || layer3 > upper_bound {
-            warn!(
-                "nodes: {}, layer1: {}, layer2: {}, layer3: {}",
-                mixnodes_count, layer1, layer2, layer3
-            );
-            return false;
-        }
-
-        true
-    }
-
-    async fn get_current_compatible_topology(&self) -> Option<NymTopology> {
-        // TODO: optimization for the future:
-        // only refresh mixnodes on timer and refresh gateways only when
-        // we have to send to a new, unknown, gateway
-
-        let mixnodes = match self.validator_client.get_cached_active_mixnodes().await {
-            Err(err) => {
-                error!("failed to get network mixnodes - {}", err);
-                return None;
-            }
-            Ok(mixes) => mixes,
-        };
-
-        let gateways = match self.validator_client.get_cached_gateways().await {
-            Err(err) => {
-                error!("failed to get network gateways - {}", err);
-                return None;
-            }
-            Ok(gateways) => gateways,
-        };
-
-        let mixnodes_count = mixnodes.len();
-        let topology =
-            nym_topology_from_bonds(mixnodes, gateways).filter_system_version(&self.client_version);
-
-        if !self.check_layer_distribution(&topology, mixnodes_count) {
-            warn!("The current filtered active topology has extremely skewed layer distribution. It cannot be used.");
-            None
-        } else {
-            Some(topology)
-        }
-    }
-
     pub async fn refresh(&mut self) {
         trace!("Refreshing the topology");
-        let new_topology = self.get_current_compatible_topology().await;
-
-        if new_topology.is_none() {
-            self.use_next_validator_api();
-        }
+        let new_topology = self
+            .topology_provider
+            .get_new_topology()
+            .await
+            .map(|topology| topology.with_route_selection(self.route_selection));
 
         if new_topology.is_none() && self.was_latest_valid {
             // if we failed to grab this topology, but the one before it was alright, let's assume
-            // validator had a tiny hiccup and use the old data
+            // the provider had a tiny hiccup and use the old data
             warn!("we're going to keep on using the old topology for this iteration");
             self.was_latest_valid = false;
             return;
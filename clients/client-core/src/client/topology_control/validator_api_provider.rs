@@ -0,0 +1,182 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use async_trait::async_trait;
+use log::*;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use std::collections::HashMap;
+use topology::provider::TopologyProvider;
+use topology::{nym_topology_from_bonds, NymTopology};
+use url::Url;
+
+/// Provider obtaining the active network topology from the validator APIs.
+pub struct ValidatorApiTopologyProvider {
+    validator_client: validator_client::ApiClient,
+    validator_api_urls: Vec<Url>,
+    currently_used_api: usize,
+
+    client_version: String,
+    fetch_uptimes: bool,
+}
+
+impl ValidatorApiTopologyProvider {
+    /// Creates new instance of the provider.
+    ///
+    /// # Arguments
+    ///
+    /// * `validator_api_urls`: endpoints of the validator APIs to query, in random order.
+    /// * `client_version`: version of the client, nodes with incompatible versions are ignored.
+    /// * `fetch_uptimes`: whether average uptimes of the mixnodes should also be retrieved.
+    pub fn new(
+        mut validator_api_urls: Vec<Url>,
+        client_version: String,
+        fetch_uptimes: bool,
+    ) -> Self {
+        validator_api_urls.shuffle(&mut thread_rng());
+
+        ValidatorApiTopologyProvider {
+            validator_client: validator_client::ApiClient::new(validator_api_urls[0].clone()),
+            validator_api_urls,
+            currently_used_api: 0,
+            client_version,
+            fetch_uptimes,
+        }
+    }
+
+    fn use_next_validator_api(&mut self) {
+        if self.validator_api_urls.len() == 1 {
+            warn!("There's only a single validator API available - it won't be possible to use a different one");
+            return;
+        }
+
+        self.currently_used_api = (self.currently_used_api + 1) % self.validator_api_urls.len();
+        self.validator_client
+            .change_validator_api(self.validator_api_urls[self.currently_used_api].clone())
+    }
+
+    /// Verifies whether nodes a reasonably distributed among all mix layers.
+    ///
+    /// In ideal world we would have 33% nodes on layer 1, 33% on layer 2 and 33% on layer 3.
+    /// However, this is a rather unrealistic expectation, instead we check whether there exists
+    /// a layer with more than 66% of nodes or with fewer than 15% and if so, we trigger a failure.
+    ///
+    /// # Arguments
+    ///
+    /// * `topology`: active topology constructed from validator api data
+    /// * `mixnodes_count`: total number of active mixnodes
+    fn check_layer_distribution(
+        &self,
+        active_topology: &NymTopology,
+        mixnodes_count: usize,
+    ) -> bool {
+        let mixes = active_topology.mixes();
+        if active_topology.gateways().is_empty() {
+            return false;
+        }
+
+        // trivial check to see if have at least a single node on each layer (regardless of active set size)
+        if mixes.get(&1).is_none() || mixes.get(&2).is_none() || mixes.get(&3).is_none() {
+            return false;
+        }
+
+        let upper_bound = (mixnodes_count as f32 * 0.66) as usize;
+        let lower_bound = (mixnodes_count as f32 * 0.15) as usize;
+
+        let layer1 = mixes.get(&1).unwrap().len();
+        let layer2 = mixes.get(&2).unwrap().len();
+        let layer3 = mixes.get(&3).unwrap().len();
+
+        if layer1 < lower_bound || layer1 > upper_bound {
+            warn!(
+                "nodes: {}, layer1: {}, layer2: {}, layer3: {}",
+                mixnodes_count, layer1, layer2, layer3
+            );
+            return false;
+        }
+
+        if layer2 < lower_bound || layer2 > upper_bound {
+            warn!(
+                "nodes: {}, layer1: {}, layer2: {}, layer3: {}",
+                mixnodes_count, layer1, layer2, layer3
+            );
+            return false;
+        }
+
+        if layer3 < lower_bound || layer3 > upper_bound {
+            warn!(
+                "nodes: {}, layer1: {}, layer2: {}, layer3: {}",
+                mixnodes_count, layer1, layer2, layer3
+            );
+            return false;
+        }
+
+        true
+    }
+
+    async fn get_mixnode_uptimes(&self) -> Option<HashMap<String, u8>> {
+        match self.validator_client.get_mixnode_avg_uptimes().await {
+            Err(err) => {
+                warn!("failed to get mixnode uptimes - {}", err);
+                None
+            }
+            Ok(uptimes) => Some(
+                uptimes
+                    .into_iter()
+                    .map(|uptime| (uptime.identity, uptime.avg_uptime))
+                    .collect(),
+            ),
+        }
+    }
+
+    async fn get_current_compatible_topology(&self) -> Option<NymTopology> {
+        // TODO: optimization for the future:
+        // only refresh mixnodes on timer and refresh gateways only when
+        // we have to send to a new, unknown, gateway
+
+        let mixnodes = match self.validator_client.get_cached_active_mixnodes().await {
+            Err(err) => {
+                error!("failed to get network mixnodes - {}", err);
+                return None;
+            }
+            Ok(mixes) => mixes,
+        };
+
+        let gateways = match self.validator_client.get_cached_gateways().await {
+            Err(err) => {
+                error!("failed to get network gateways - {}", err);
+                return None;
+            }
+            Ok(gateways) => gateways,
+        };
+
+        let mixnodes_count = mixnodes.len();
+        let mut topology =
+            nym_topology_from_bonds(mixnodes, gateways).filter_system_version(&self.client_version);
+
+        if self.fetch_uptimes {
+            // without the uptimes, the nodes are going to be chosen uniformly
+            if let Some(uptimes) = self.get_mixnode_uptimes().await {
+                topology.set_mix_uptimes(&uptimes);
+            }
+        }
+
+        if !self.check_layer_distribution(&topology, mixnodes_count) {
+            warn!("The current filtered active topology has extremely skewed layer distribution. It cannot be used.");
+            None
+        } else {
+            Some(topology)
+        }
+    }
+}
+
+#[async_trait]
+impl TopologyProvider for ValidatorApiTopologyProvider {
+    async fn get_new_topology(&mut self) -> Option<NymTopology> {
+        let topology = self.get_current_compatible_topology().await;
+        if topology.is_none() {
+            self.use_next_validator_api();
+        }
+        topology
+    }
+}
@@ -7,6 +7,7 @@ use serde::{Deserialize, Serialize};
 use std::marker::PhantomData;
 use std::path::PathBuf;
 use std::time::Duration;
+use topology::route_selection::{RouteSelection, RouteSelectionStrategy};
 use url::Url;
 
 pub mod persistence;
@@ -17,6 +18,9 @@ pub const MISSING_VALUE: &str = "MISSING VALUE";
 const DEFAULT_ACK_WAIT_MULTIPLIER: f64 = 1.5;
 
 const DEFAULT_ACK_WAIT_ADDITION: Duration = Duration::from_millis(1_500);
+const DEFAULT_MAXIMUM_RETRANSMISSIONS: u32 = 10;
+const DEFAULT_RETRANSMISSION_DEADLINE: Duration = Duration::from_secs(5 * 60);
+const DEFAULT_RETRANSMISSION_BACKOFF_MULTIPLIER: f64 = 1.5;
 const DEFAULT_LOOP_COVER_STREAM_AVERAGE_DELAY: Duration = Duration::from_millis(200);
 const DEFAULT_MESSAGE_STREAM_AVERAGE_DELAY: Duration = Duration::from_millis(20);
 const DEFAULT_AVERAGE_PACKET_DELAY: Duration = Duration::from_millis(50);
@@ -103,6 +107,11 @@ impl<T: NymConfig> Config<T> {
                 self::Client::<T>::default_reply_encryption_key_store_path(&id);
         }
 
+        if self.client.reply_surb_store_path.as_os_str().is_empty() {
+            self.client.reply_surb_store_path =
+                self::Client::<T>::default_reply_surb_store_path(&id);
+        }
+
         if self.client.database_path.as_os_str().is_empty() {
             self.client.database_path = self::Client::<T>::default_database_path(&id);
         }
@@ -140,6 +149,11 @@ impl<T: NymConfig> Config<T> {
         self.client.validator_api_urls = validator_api_urls;
     }
 
+    pub fn set_topology_file<P: Into<PathBuf>>(&mut self, topology_file: P, watch: bool) {
+        self.client.topology_file = topology_file.into();
+        self.client.watch_topology_file = watch;
+    }
+
     pub fn set_high_default_traffic_volume(&mut self) {
         self.debug.average_packet_delay = Duration::from_millis(10);
         self.debug.loop_cover_traffic_average_delay = Duration::from_millis(2000000); // basically don't really send cover messages
@@ -186,6 +200,15 @@ impl<T: NymConfig> Config<T> {
         self.client.reply_encryption_key_store_path.clone()
     }
 
+    pub fn get_reply_surb_store_path(&self) -> PathBuf {
+        // configs created before the reply SURB store was introduced do not specify its path
+        if self.client.reply_surb_store_path.as_os_str().is_empty() {
+            self::Client::<T>::default_reply_surb_store_path(&self.client.id)
+        } else {
+            self.client.reply_surb_store_path.clone()
+        }
+    }
+
     pub fn get_ack_key_file(&self) -> PathBuf {
         self.client.ack_key_file.clone()
     }
@@ -194,6 +217,18 @@ impl<T: NymConfig> Config<T> {
         self.client.validator_api_urls.clone()
     }
 
+    pub fn get_topology_file(&self) -> Option<PathBuf> {
+        if self.client.topology_file.as_os_str().is_empty() {
+            None
+        } else {
+            Some(self.client.topology_file.clone())
+        }
+    }
+
+    pub fn get_watch_topology_file(&self) -> bool {
+        self.client.watch_topology_file
+    }
+
     pub fn get_gateway_id(&self) -> String {
         self.client.gateway_endpoint.gateway_id.clone()
     }
@@ -237,6 +272,18 @@ impl<T: NymConfig> Config<T> {
         self.debug.ack_wait_addition
     }
 
+    pub fn get_maximum_retransmissions(&self) -> u32 {
+        self.debug.maximum_retransmissions
+    }
+
+    pub fn get_retransmission_deadline(&self) -> Duration {
+        self.debug.retransmission_deadline
+    }
+
+    pub fn get_retransmission_backoff_multiplier(&self) -> f64 {
+        self.debug.retransmission_backoff_multiplier
+    }
+
     pub fn get_loop_cover_traffic_average_delay(&self) -> Duration {
         self.debug.loop_cover_traffic_average_delay
     }
@@ -257,6 +304,14 @@ impl<T: NymConfig> Config<T> {
         self.debug.topology_resolution_timeout
     }
 
+    pub fn get_route_selection(&self) -> RouteSelection {
+        RouteSelection {
+            strategy: self.debug.route_selection_strategy,
+            exclude_same_owner: self.debug.exclude_same_owner_on_route,
+            exclude_same_subnet: self.debug.exclude_same_subnet_on_route,
+        }
+    }
+
     pub fn get_version(&self) -> &str {
         &self.client.version
     }
@@ -302,6 +357,17 @@ pub struct Client<T> {
     /// Addresses to APIs running on validator from which the client gets the view of the network.
     validator_api_urls: Vec<Url>,
 
+    /// Path to a JSON or TOML file describing the network topology. If set, the client uses it
+    /// instead of the validator APIs for obtaining the view of the network. If it points to
+    /// a directory, the most recently modified topology file inside it is used.
+    #[serde(default)]
+    topology_file: PathBuf,
+
+    /// Indicates whether the topology file should be reloaded whenever it gets modified.
+    /// The changes are picked up on the next topology refresh.
+    #[serde(default)]
+    watch_topology_file: bool,
+
     /// Path to file containing private identity key.
     private_identity_key_file: PathBuf,
 
@@ -326,6 +392,11 @@ pub struct Client<T> {
     /// sent but not received back.
     reply_encryption_key_store_path: PathBuf,
 
+    /// Full path to file containing reply-SURBs received from other clients, stored under
+    /// locally assigned sender tags.
+    #[serde(default)]
+    reply_surb_store_path: PathBuf,
+
     /// Information regarding how the client should send data to gateway.
     gateway_endpoint: GatewayEndpoint,
 
@@ -356,6 +427,8 @@ impl<T: NymConfig> Default for Client<T> {
             id: "".to_string(),
             disabled_credentials_mode: true,
             validator_api_urls: default_api_endpoints(),
+            topology_file: Default::default(),
+            watch_topology_file: false,
             private_identity_key_file: Default::default(),
             public_identity_key_file: Default::default(),
             private_encryption_key_file: Default::default(),
@@ -363,6 +436,7 @@ impl<T: NymConfig> Default for Client<T> {
             gateway_shared_key_file: Default::default(),
             ack_key_file: Default::default(),
             reply_encryption_key_store_path: Default::default(),
+            reply_surb_store_path: Default::default(),
             gateway_endpoint: Default::default(),
             database_path: Default::default(),
             #[cfg(not(feature = "coconut"))]
@@ -403,6 +477,10 @@ impl<T: NymConfig> Client<T> {
     fn default_reply_encryption_key_store_path(id: &str) -> PathBuf {
         T::default_data_directory(Some(id)).join("reply_key_store")
     }
+
+    fn default_reply_surb_store_path(id: &str) -> PathBuf {
+        T::default_data_directory(Some(id)).join("reply_surb_store")
+    }
     fn default_database_path(id: &str) -> PathBuf {
         T::default_data_directory(Some(id)).join("db.sqlite")
     }
@@ -440,6 +518,20 @@ pub struct Debug {
     #[serde(with = "humantime_serde")]
     ack_wait_addition: Duration,
 
+    /// Maximum number of times a single data packet is going to be retransmitted before
+    /// the message it is part of is deemed undeliverable.
+    maximum_retransmissions: u32,
+
+    /// Maximum amount of time, since the message was first sent, during which its packets
+    /// are going to be retransmitted before it is deemed undeliverable.
+    #[serde(with = "humantime_serde")]
+    retransmission_deadline: Duration,
+
+    /// Value by which the acknowledgement timeout is multiplied upon each subsequent
+    /// retransmission of the same packet, i.e. the n-th retransmission waits for
+    /// `ack_timeout * retransmission_backoff_multiplier^n`.
+    retransmission_backoff_multiplier: f64,
+
     /// The parameter of Poisson distribution determining how long, on average,
     /// it is going to take for another loop cover traffic message to be sent.
     #[serde(with = "humantime_serde")]
@@ -467,6 +559,17 @@ pub struct Debug {
     /// did not reach its destination.
     #[serde(with = "humantime_serde")]
     topology_resolution_timeout: Duration,
+
+    /// Strategy used for choosing mix nodes on each layer when constructing packet routes.
+    /// Either `uniform`, `stake_weighted` or `uptime_weighted`.
+    route_selection_strategy: RouteSelectionStrategy,
+
+    /// Refuse to construct routes going through multiple mix nodes of the same owner.
+    exclude_same_owner_on_route: bool,
+
+    /// Refuse to construct routes going through multiple mix nodes in the same /24 (IPv4)
+    /// or /48 (IPv6) network.
+    exclude_same_subnet_on_route: bool,
 }
 
 impl Default for Debug {
@@ -476,11 +579,17 @@ impl Default for Debug {
             average_ack_delay: DEFAULT_AVERAGE_PACKET_DELAY,
             ack_wait_multiplier: DEFAULT_ACK_WAIT_MULTIPLIER,
             ack_wait_addition: DEFAULT_ACK_WAIT_ADDITION,
+            maximum_retransmissions: DEFAULT_MAXIMUM_RETRANSMISSIONS,
+            retransmission_deadline: DEFAULT_RETRANSMISSION_DEADLINE,
+            retransmission_backoff_multiplier: DEFAULT_RETRANSMISSION_BACKOFF_MULTIPLIER,
             loop_cover_traffic_average_delay: DEFAULT_LOOP_COVER_STREAM_AVERAGE_DELAY,
             message_sending_average_delay: DEFAULT_MESSAGE_STREAM_AVERAGE_DELAY,
             gateway_response_timeout: DEFAULT_GATEWAY_RESPONSE_TIMEOUT,
             topology_refresh_rate: DEFAULT_TOPOLOGY_REFRESH_RATE,
             topology_resolution_timeout: DEFAULT_TOPOLOGY_RESOLUTION_TIMEOUT,
+            route_selection_strategy: Default::default(),
+            exclude_same_owner_on_route: false,
+            exclude_same_subnet_on_route: false,
         }
     }
 }
@@ -5,20 +5,29 @@ use async_trait::async_trait;
 use clap::{Args, Subcommand};
 use pickledb::PickleDb;
 use rand::rngs::OsRng;
+use std::fs;
+use std::path::PathBuf;
 use std::str::FromStr;
 use url::Url;
 
-use coconut_interface::{Attribute, Base58, BlindSignRequest, Bytable, Parameters};
+use coconut_interface::{
+    Attribute, Base58, BlindSignRequest, Bytable, Parameters, Signature, SignatureShare,
+    SignerIndex,
+};
 use credential_storage::storage::Storage;
 use credential_storage::PersistentStorage;
 use credentials::coconut::bandwidth::{BandwidthVoucher, TOTAL_ATTRIBUTES};
-use credentials::coconut::utils::obtain_aggregate_signature;
+use credentials::coconut::utils::{
+    aggregate_partial_signatures, obtain_partial_signatures, obtain_threshold_verification_key,
+    signing_threshold, DEFAULT_SIGNER_TIMEOUT,
+};
 use crypto::asymmetric::{encryption, identity};
 use network_defaults::VOUCHER_INFO;
 use validator_client::nymd::tx::Hash;
 
 use crate::client::Client;
 use crate::error::{CredentialClientError, Result};
+use crate::export::{decrypt_credentials, encrypt_credentials, read_password, ExportedCredential};
 use crate::state::{KeyPair, RequestData, State};
 use crate::SIGNER_AUTHORITIES;
 
@@ -28,8 +37,19 @@ pub(crate) enum Commands {
     Deposit(Deposit),
     /// Lists the tx hashes of previous deposits
     ListDeposits(ListDeposits),
-    /// Get a credential for a given deposit
+    /// Get a credential for a given deposit. If some of the signers failed to respond previously,
+    /// only those are contacted again
     GetCredential(GetCredential),
+    /// Lists the stored credentials with their voucher values
+    ListCredentials(ListCredentials),
+    /// Shows the total bandwidth of all the unused credentials
+    Balance(Balance),
+    /// Exports the unused credentials into a password-protected file
+    ExportCredentials(ExportCredentials),
+    /// Imports credentials from a password-protected file
+    ImportCredentials(ImportCredentials),
+    /// Removes all the consumed credentials from the storage
+    DeleteConsumed(DeleteConsumed),
 }
 
 #[async_trait]
@@ -67,6 +87,7 @@ impl Execute for Deposit {
             signing_keypair,
             encryption_keypair,
             blind_request_data: None,
+            partial_signatures: Default::default(),
             signature: None,
         };
         db.set(&tx_hash, &state).unwrap();
@@ -100,6 +121,37 @@ pub(crate) struct GetCredential {
     /// there is already a signature stored on the signer
     #[clap(long, parse(from_flag))]
     __no_request: bool,
+    /// Number of partial signatures required to aggregate the credential. If not provided,
+    /// more than 2/3 of the signers have to respond
+    #[clap(long)]
+    threshold: Option<u64>,
+}
+
+// restores the voucher out of the backed up blind sign request data
+fn restore_voucher(state: &State, blind_request_data: &RequestData) -> Result<BandwidthVoucher> {
+    let serial_number = Attribute::try_from_byte_slice(&blind_request_data.serial_number)
+        .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?;
+    let binding_number = Attribute::try_from_byte_slice(&blind_request_data.binding_number)
+        .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?;
+    let pedersen_commitments_openings = vec![
+        Attribute::try_from_byte_slice(&blind_request_data.first_attribute)
+            .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?,
+        Attribute::try_from_byte_slice(&blind_request_data.second_attribute)
+            .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?,
+    ];
+    let blind_sign_request =
+        BlindSignRequest::from_bytes(blind_request_data.blind_sign_req.as_slice())
+            .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?;
+
+    Ok(BandwidthVoucher::new_with_blind_sign_req(
+        [serial_number, binding_number],
+        [&state.amount.to_string(), VOUCHER_INFO],
+        Hash::from_str(&state.tx_hash).map_err(|_| CredentialClientError::InvalidTxHash)?,
+        identity::PrivateKey::from_base58_string(&state.signing_keypair.private_key)?,
+        encryption::PrivateKey::from_base58_string(&state.encryption_keypair.private_key)?,
+        pedersen_commitments_openings,
+        blind_sign_request,
+    ))
 }
 
 #[async_trait]
@@ -108,62 +160,109 @@ impl Execute for GetCredential {
         let mut state = db
             .get::<State>(&self.tx_hash)
             .ok_or(CredentialClientError::NoDeposit)?;
+        if state.signature.is_some() {
+            println!("Signature: {:?}", state.signature);
+            return Ok(());
+        }
         let urls = SIGNER_AUTHORITIES.map(|addr| Url::from_str(addr).unwrap());
 
         let params = Parameters::new(TOTAL_ATTRIBUTES).unwrap();
-        let bandwidth_credential_attributes = if self.__no_request {
-            if let Some(blind_request_data) = state.blind_request_data {
-                let serial_number =
-                    Attribute::try_from_byte_slice(&blind_request_data.serial_number)
-                        .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?;
-                let binding_number =
-                    Attribute::try_from_byte_slice(&blind_request_data.binding_number)
-                        .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?;
-                let pedersen_commitments_openings = vec![
-                    Attribute::try_from_byte_slice(&blind_request_data.first_attribute)
-                        .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?,
-                    Attribute::try_from_byte_slice(&blind_request_data.second_attribute)
-                        .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?,
-                ];
-                let blind_sign_request =
-                    BlindSignRequest::from_bytes(blind_request_data.blind_sign_req.as_slice())
-                        .map_err(|_| CredentialClientError::CorruptedBlindSignRequest)?;
-                BandwidthVoucher::new_with_blind_sign_req(
-                    [serial_number, binding_number],
-                    [&state.amount.to_string(), VOUCHER_INFO],
-                    Hash::from_str(&self.tx_hash)
-                        .map_err(|_| CredentialClientError::InvalidTxHash)?,
-                    identity::PrivateKey::from_base58_string(&state.signing_keypair.private_key)?,
-                    encryption::PrivateKey::from_base58_string(
-                        &state.encryption_keypair.private_key,
-                    )?,
-                    pedersen_commitments_openings,
-                    blind_sign_request,
-                )
-            } else {
-                return Err(CredentialClientError::NoLocalBlindSignRequest);
-            }
+        let bandwidth_credential_attributes = if let Some(blind_request_data) =
+            &state.blind_request_data
+        {
+            // the signers that have already responded have signed the backed up request,
+            // so it has to be reused for all the remaining ones
+            restore_voucher(&state, blind_request_data)?.with_use_request(!self.__no_request)
+        } else if self.__no_request {
+            return Err(CredentialClientError::NoLocalBlindSignRequest);
         } else {
-            BandwidthVoucher::new(
+            let voucher = BandwidthVoucher::new(
                 &params,
                 state.amount.to_string(),
                 VOUCHER_INFO.to_string(),
                 Hash::from_str(&self.tx_hash).map_err(|_| CredentialClientError::InvalidTxHash)?,
                 identity::PrivateKey::from_base58_string(&state.signing_keypair.private_key)?,
                 encryption::PrivateKey::from_base58_string(&state.encryption_keypair.private_key)?,
-            )
+            );
+
+            // Back up the blind sign req data, in case of sporadic failures
+            state.blind_request_data = Some(RequestData::new(
+                voucher.get_private_attributes(),
+                voucher.pedersen_commitments_openings(),
+                voucher.blind_sign_request(),
+            )?);
+            db.set(&self.tx_hash, &state).unwrap();
+            voucher
         };
 
-        // Back up the blind sign req data, in case of sporadic failures
-        state.blind_request_data = Some(RequestData::new(
-            bandwidth_credential_attributes.get_private_attributes(),
-            bandwidth_credential_attributes.pedersen_commitments_openings(),
-            bandwidth_credential_attributes.blind_sign_request(),
-        )?);
-        db.set(&self.tx_hash, &state).unwrap();
+        let threshold = self
+            .threshold
+            .unwrap_or_else(|| signing_threshold(urls.len()));
+        let needed = threshold.saturating_sub(state.partial_signatures.len() as u64);
+        if needed > 0 {
+            let remaining = urls
+                .iter()
+                .enumerate()
+                .map(|(id, url)| ((id + 1) as SignerIndex, url.clone()))
+                .filter(|(index, _)| !state.partial_signatures.contains_key(index))
+                .collect::<Vec<_>>();
+            let (shares, failures) = obtain_partial_signatures(
+                &params,
+                &bandwidth_credential_attributes,
+                &remaining,
+                needed,
+                DEFAULT_SIGNER_TIMEOUT,
+            )
+            .await;
 
-        let signature =
-            obtain_aggregate_signature(&params, &bandwidth_credential_attributes, &urls).await?;
+            // back up the partial signatures straight away, so that the signers
+            // would not have to be contacted again
+            for share in shares {
+                state
+                    .partial_signatures
+                    .insert(share.index(), share.signature().to_bs58());
+            }
+            db.set(&self.tx_hash, &state).unwrap();
+
+            for failure in &failures {
+                println!(
+                    "Failed to obtain partial signature from {}: {}",
+                    failure.url, failure.error
+                );
+            }
+            if (state.partial_signatures.len() as u64) < threshold {
+                return Err(CredentialClientError::MissingPartialSignatures(
+                    failures.len(),
+                ));
+            }
+        }
+
+        let shares = state
+            .partial_signatures
+            .iter()
+            .map(|(index, signature)| {
+                Ok(SignatureShare::new(
+                    Signature::try_from_bs58(signature)?,
+                    *index,
+                ))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        // any `threshold` of the verification key shares aggregate to the same key
+        let verification_key =
+            obtain_threshold_verification_key(&urls, threshold, DEFAULT_SIGNER_TIMEOUT).await?;
+        for failure in &verification_key.failures {
+            println!(
+                "Failed to obtain verification key from {}: {}",
+                failure.url, failure.error
+            );
+        }
+        let verification_key = verification_key.value;
+        let signature = aggregate_partial_signatures(
+            &params,
+            &bandwidth_credential_attributes,
+            &shares,
+            &verification_key,
+        )?;
         shared_storage
             .insert_coconut_credential(
                 state.amount.to_string(),
@@ -181,3 +280,144 @@ impl Execute for GetCredential {
         Ok(())
     }
 }
+
+#[derive(Args, Clone)]
+pub(crate) struct ListCredentials {}
+
+#[async_trait]
+impl Execute for ListCredentials {
+    async fn execute(&self, _db: &mut PickleDb, shared_storage: PersistentStorage) -> Result<()> {
+        for credential in shared_storage.get_all_coconut_credentials().await? {
+            println!(
+                "{}: voucher value {} ({}){}",
+                credential.id,
+                credential.voucher_value,
+                credential.voucher_info,
+                if credential.consumed {
+                    " - consumed"
+                } else {
+                    ""
+                }
+            );
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Args, Clone)]
+pub(crate) struct Balance {}
+
+#[async_trait]
+impl Execute for Balance {
+    async fn execute(&self, _db: &mut PickleDb, shared_storage: PersistentStorage) -> Result<()> {
+        let mut total = 0u64;
+        let mut unused = 0;
+        for credential in shared_storage.get_all_coconut_credentials().await? {
+            if credential.consumed {
+                continue;
+            }
+            total += u64::from_str(&credential.voucher_value)
+                .map_err(|_| CredentialClientError::InvalidVoucherValue)?;
+            unused += 1;
+        }
+
+        println!(
+            "Remaining bandwidth: {} in {} unused credential(s)",
+            total, unused
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Args, Clone)]
+pub(crate) struct ExportCredentials {
+    /// Path to the file the credentials are going to be exported to
+    #[clap(long)]
+    output: PathBuf,
+    /// Path to the file containing the password used for encrypting the exported credentials.
+    /// If not provided, the password is read from the `NYM_CREDENTIALS_PASSWORD` environment
+    /// variable or prompted for
+    #[clap(long)]
+    password_file: Option<PathBuf>,
+}
+
+#[async_trait]
+impl Execute for ExportCredentials {
+    async fn execute(&self, _db: &mut PickleDb, shared_storage: PersistentStorage) -> Result<()> {
+        let credentials = shared_storage
+            .get_all_coconut_credentials()
+            .await?
+            .into_iter()
+            .filter(|credential| !credential.consumed)
+            .map(ExportedCredential::from)
+            .collect::<Vec<_>>();
+
+        let password = read_password(self.password_file.as_deref(), true)?;
+        fs::write(&self.output, encrypt_credentials(&credentials, &password)?)?;
+        println!(
+            "Exported {} credential(s) to {}",
+            credentials.len(),
+            self.output.display()
+        );
+
+        Ok(())
+    }
+}
+
+#[derive(Args, Clone)]
+pub(crate) struct ImportCredentials {
+    /// Path to the file containing the exported credentials
+    #[clap(long)]
+    input: PathBuf,
+    /// Path to the file containing the password the credentials were exported with.
+    /// If not provided, the password is read from the `NYM_CREDENTIALS_PASSWORD` environment
+    /// variable or prompted for
+    #[clap(long)]
+    password_file: Option<PathBuf>,
+}
+
+#[async_trait]
+impl Execute for ImportCredentials {
+    async fn execute(&self, _db: &mut PickleDb, shared_storage: PersistentStorage) -> Result<()> {
+        let content = fs::read_to_string(&self.input)?;
+        let password = read_password(self.password_file.as_deref(), false)?;
+        let credentials = decrypt_credentials(&content, &password)?;
+
+        let mut imported = 0;
+        for credential in credentials {
+            // the signatures are unique, so this would fail for already present credentials
+            if let Err(err) = shared_storage
+                .insert_coconut_credential(
+                    credential.voucher_value,
+                    credential.voucher_info,
+                    credential.serial_number,
+                    credential.binding_number,
+                    credential.signature,
+                )
+                .await
+            {
+                println!("Skipping credential that could not be imported: {}", err);
+                continue;
+            }
+            imported += 1;
+        }
+        println!("Imported {} credential(s)", imported);
+
+        Ok(())
+    }
+}
+
+#[derive(Args, Clone)]
+pub(crate) struct DeleteConsumed {}
+
+#[async_trait]
+impl Execute for DeleteConsumed {
+    async fn execute(&self, _db: &mut PickleDb, shared_storage: PersistentStorage) -> Result<()> {
+        let removed = shared_storage.remove_consumed_coconut_credentials().await?;
+        println!("Removed {} consumed credential(s)", removed);
+
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@
 
 use thiserror::Error;
 
+use coconut_interface::CoconutError;
 use credential_storage::error::StorageError;
 use credentials::error::Error as CredentialError;
 use crypto::asymmetric::encryption::KeyRecoveryError;
@@ -42,4 +43,28 @@ pub enum CredentialClientError {
 
     #[error("Could not use shared storage")]
     SharedStorageError(#[from] StorageError),
+
+    #[error("Coconut error: {0}")]
+    Coconut(#[from] CoconutError),
+
+    #[error("The stored voucher value is not a valid number")]
+    InvalidVoucherValue,
+
+    #[error("Could not obtain partial signatures from {0} signer(s). Run the command again to retry only those signers")]
+    MissingPartialSignatures(usize),
+
+    #[error("Failed to access the file: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Failed to encrypt the credentials")]
+    EncryptionError,
+
+    #[error("Failed to decrypt the credentials. Is the password correct?")]
+    DecryptionError,
+
+    #[error("The provided file does not contain exported credentials")]
+    MalformedExportFile,
+
+    #[error("The provided passwords do not match")]
+    PasswordMismatch,
 }
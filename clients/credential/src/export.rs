@@ -0,0 +1,223 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use aes_gcm::aead::generic_array::ArrayLength;
+use aes_gcm::aead::{Aead, NewAead};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::{Algorithm, Argon2, Params, Version};
+use rand::rngs::OsRng;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::{env, fs};
+
+use credential_storage::models::CoconutCredential;
+
+use crate::error::{CredentialClientError, Result};
+
+const MEMORY_COST: u32 = 16 * 1024;
+const ITERATIONS: u32 = 3;
+const PARALLELISM: u32 = 1;
+const OUTPUT_LENGTH: usize = 32;
+
+// as per Argon2 recommendation
+const SALT_LEN: usize = 16;
+
+// AES256GCM Nonce is 96 bit long.
+const IV_LEN: usize = 12;
+
+/// Environment variable holding the password of the exported credentials, for non-interactive use.
+pub(crate) const PASSWORD_ENV_VAR: &str = "NYM_CREDENTIALS_PASSWORD";
+
+/// Coconut credential in the form it is exported in, i.e. without any local storage details.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ExportedCredential {
+    pub voucher_value: String,
+    pub voucher_info: String,
+    pub serial_number: String,
+    pub binding_number: String,
+    pub signature: String,
+}
+
+impl From<CoconutCredential> for ExportedCredential {
+    fn from(credential: CoconutCredential) -> Self {
+        ExportedCredential {
+            voucher_value: credential.voucher_value,
+            voucher_info: credential.voucher_info,
+            serial_number: credential.serial_number,
+            binding_number: credential.binding_number,
+            signature: credential.signature,
+        }
+    }
+}
+
+// all the values are base58-encoded
+#[derive(Deserialize, Serialize)]
+struct EncryptedExport {
+    ciphertext: String,
+    salt: String,
+    iv: String,
+}
+
+fn derive_cipher_key<KeySize>(password: &str, salt: &[u8]) -> Result<Key<KeySize>>
+where
+    KeySize: ArrayLength<u8>,
+{
+    // this can only fail if output length is either smaller than 4 or larger than 2^32 - 1 which is not the case here
+    let params = Params::new(MEMORY_COST, ITERATIONS, PARALLELISM, Some(OUTPUT_LENGTH)).unwrap();
+
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+    let mut key = Key::default();
+    argon2
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|_| CredentialClientError::EncryptionError)?;
+
+    Ok(key)
+}
+
+/// Obtains the password of the exported credentials, so that it wouldn't have to be passed
+/// on the command line (and end up in the shell history). It's read from the provided file,
+/// or the `NYM_CREDENTIALS_PASSWORD` environment variable or, if neither is available,
+/// the user is prompted for it.
+///
+/// # Arguments
+///
+/// * `password_file`: optional path to the file containing the password.
+/// * `confirm`: whether the prompted password should be entered twice, e.g. when exporting.
+pub(crate) fn read_password(password_file: Option<&Path>, confirm: bool) -> Result<String> {
+    if let Some(password_file) = password_file {
+        let password = fs::read_to_string(password_file)?;
+        return Ok(password.trim_end_matches(&['\r', '\n'][..]).to_string());
+    }
+
+    if let Ok(password) = env::var(PASSWORD_ENV_VAR) {
+        return Ok(password);
+    }
+
+    let password = rpassword::prompt_password("Password: ")?;
+    if confirm && rpassword::prompt_password("Confirm password: ")? != password {
+        return Err(CredentialClientError::PasswordMismatch);
+    }
+    Ok(password)
+}
+
+/// Encrypts the provided credentials with the key derived from the password, returning
+/// the content of the export file.
+pub(crate) fn encrypt_credentials(
+    credentials: &[ExportedCredential],
+    password: &str,
+) -> Result<String> {
+    let mut rng = OsRng;
+
+    let mut salt = vec![0u8; SALT_LEN];
+    rng.fill_bytes(&mut salt);
+
+    let mut iv = vec![0u8; IV_LEN];
+    rng.fill_bytes(&mut iv);
+
+    let plaintext =
+        serde_json::to_vec(credentials).map_err(|_| CredentialClientError::EncryptionError)?;
+    let key = derive_cipher_key(password, &salt)?;
+    let ciphertext = Aes256Gcm::new(&key)
+        .encrypt(Nonce::from_slice(&iv), plaintext.as_ref())
+        .map_err(|_| CredentialClientError::EncryptionError)?;
+
+    let export = EncryptedExport {
+        ciphertext: bs58::encode(ciphertext).into_string(),
+        salt: bs58::encode(salt).into_string(),
+        iv: bs58::encode(iv).into_string(),
+    };
+    serde_json::to_string_pretty(&export).map_err(|_| CredentialClientError::EncryptionError)
+}
+
+/// Decrypts credentials out of the content of the export file using the key derived
+/// from the password.
+pub(crate) fn decrypt_credentials(
+    content: &str,
+    password: &str,
+) -> Result<Vec<ExportedCredential>> {
+    let export: EncryptedExport =
+        serde_json::from_str(content).map_err(|_| CredentialClientError::MalformedExportFile)?;
+    let decode = |value: &str| {
+        bs58::decode(value)
+            .into_vec()
+            .map_err(|_| CredentialClientError::MalformedExportFile)
+    };
+    let ciphertext = decode(&export.ciphertext)?;
+    let salt = decode(&export.salt)?;
+    let iv = decode(&export.iv)?;
+    if iv.len() != IV_LEN {
+        return Err(CredentialClientError::MalformedExportFile);
+    }
+
+    let key = derive_cipher_key(password, &salt)?;
+    let plaintext = Aes256Gcm::new(&key)
+        .decrypt(Nonce::from_slice(&iv), ciphertext.as_ref())
+        .map_err(|_| CredentialClientError::DecryptionError)?;
+
+    serde_json::from_slice(&plaintext).map_err(|_| CredentialClientError::MalformedExportFile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn credential(value: &str) -> ExportedCredential {
+        ExportedCredential {
+            voucher_value: value.to_string(),
+            voucher_info: "voucher info".to_string(),
+            serial_number: "serial number".to_string(),
+            binding_number: "binding number".to_string(),
+            signature: format!("signature {}", value),
+        }
+    }
+
+    #[test]
+    fn exported_credentials_can_be_decrypted_with_the_same_password() {
+        let credentials = vec![credential("100"), credential("200")];
+        let content = encrypt_credentials(&credentials, "password").unwrap();
+
+        let decrypted = decrypt_credentials(&content, "password").unwrap();
+        assert_eq!(decrypted.len(), 2);
+        for (original, decrypted) in credentials.iter().zip(decrypted.iter()) {
+            assert_eq!(original.voucher_value, decrypted.voucher_value);
+            assert_eq!(original.voucher_info, decrypted.voucher_info);
+            assert_eq!(original.serial_number, decrypted.serial_number);
+            assert_eq!(original.binding_number, decrypted.binding_number);
+            assert_eq!(original.signature, decrypted.signature);
+        }
+    }
+
+    #[test]
+    fn wrong_password_is_rejected() {
+        let content = encrypt_credentials(&[credential("100")], "password").unwrap();
+        assert!(matches!(
+            decrypt_credentials(&content, "wrong password"),
+            Err(CredentialClientError::DecryptionError)
+        ));
+    }
+
+    #[test]
+    fn malformed_export_is_rejected() {
+        assert!(matches!(
+            decrypt_credentials("not an export", "password"),
+            Err(CredentialClientError::MalformedExportFile)
+        ));
+    }
+
+    #[test]
+    fn password_is_read_from_the_file_without_the_trailing_newline() {
+        let dir = std::env::temp_dir().join(format!("credential-{}", rand::random::<u32>()));
+        fs::create_dir_all(&dir).unwrap();
+        let password_file = dir.join("password");
+        fs::write(&password_file, "my password\n").unwrap();
+
+        assert_eq!(
+            read_password(Some(&password_file), true).unwrap(),
+            "my password"
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -7,6 +7,7 @@ cfg_if::cfg_if! {
         mod client;
         mod commands;
         mod error;
+        mod export;
         mod state;
 
         use commands::{Commands, Execute};
@@ -51,6 +52,11 @@ cfg_if::cfg_if! {
                 Commands::Deposit(m) => m.execute(&mut db, shared_storage).await?,
                 Commands::ListDeposits(m) => m.execute(&mut db, shared_storage).await?,
                 Commands::GetCredential(m) => m.execute(&mut db, shared_storage).await?,
+                Commands::ListCredentials(m) => m.execute(&mut db, shared_storage).await?,
+                Commands::Balance(m) => m.execute(&mut db, shared_storage).await?,
+                Commands::ExportCredentials(m) => m.execute(&mut db, shared_storage).await?,
+                Commands::ImportCredentials(m) => m.execute(&mut db, shared_storage).await?,
+                Commands::DeleteConsumed(m) => m.execute(&mut db, shared_storage).await?,
             }
 
             Ok(())
@@ -3,6 +3,7 @@
 
 use coconut_interface::{Attribute, BlindSignRequest, Bytable, PrivateAttribute};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 use crypto::asymmetric::{encryption, identity};
 
@@ -39,6 +40,9 @@ pub(crate) struct State {
     pub signing_keypair: KeyPair,
     pub encryption_keypair: KeyPair,
     pub blind_request_data: Option<RequestData>,
+    /// Base58-encoded partial signatures obtained so far, keyed by the (1-based) index of the signer.
+    #[serde(default)]
+    pub partial_signatures: BTreeMap<u64, String>,
     pub signature: Option<String>,
 }
 
@@ -59,7 +59,7 @@ async fn send_file_with_reply() {
     let reply_message = b"hello from reply SURB! - thanks for sending me the file!".to_vec();
     let reply_request = ClientRequest::Reply {
         message: reply_message.clone(),
-        reply_surb: received.reply_surb.unwrap(),
+        reply_surb: received.reply_surbs.into_iter().next().unwrap(),
     };
 
     println!(
@@ -2,6 +2,9 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use client_core::client::cover_traffic_stream::LoopCoverTrafficStream;
+use client_core::client::delivery_receipts::{
+    delivery_receipt_channel, DeliveryReceiptReceiver, DeliveryReceiptSender,
+};
 use client_core::client::inbound_messages::{
     InputMessage, InputMessageReceiver, InputMessageSender,
 };
@@ -16,8 +19,9 @@ use client_core::client::received_buffer::{
     ReceivedMessagesBufferController, ReconstructedMessagesReceiver,
 };
 use client_core::client::reply_key_storage::ReplyKeyStorage;
+use client_core::client::reply_surb_storage::{ReplySurbExpirer, ReplySurbStorage};
 use client_core::client::topology_control::{
-    TopologyAccessor, TopologyRefresher, TopologyRefresherConfig,
+    topology_provider_from_config, TopologyAccessor, TopologyRefresher, TopologyRefresherConfig,
 };
 use client_core::config::persistence::key_pathfinder::ClientKeyPathfinder;
 use crypto::asymmetric::identity;
@@ -109,11 +113,17 @@ impl NymClient {
         ack_receiver: AcknowledgementReceiver,
         input_receiver: InputMessageReceiver,
         mix_sender: BatchMixMessageSender,
+        delivery_receipt_sender: Option<DeliveryReceiptSender>,
     ) {
         let controller_config = real_messages_control::Config::new(
             self.key_manager.ack_key(),
             self.config.get_base().get_ack_wait_multiplier(),
             self.config.get_base().get_ack_wait_addition(),
+            self.config.get_base().get_maximum_retransmissions(),
+            self.config.get_base().get_retransmission_deadline(),
+            self.config
+                .get_base()
+                .get_retransmission_backoff_multiplier(),
             self.config.get_base().get_average_ack_delay(),
             self.config.get_base().get_message_sending_average_delay(),
             self.config.get_base().get_average_packet_delay(),
@@ -129,6 +139,7 @@ impl NymClient {
             mix_sender,
             topology_accessor,
             reply_key_storage,
+            delivery_receipt_sender,
         )
         .start();
     }
@@ -140,6 +151,7 @@ impl NymClient {
         query_receiver: ReceivedBufferRequestReceiver,
         mixnet_receiver: MixnetMessageReceiver,
         reply_key_storage: ReplyKeyStorage,
+        reply_surb_storage: Option<ReplySurbStorage>,
     ) {
         info!("Starting received messages buffer controller...");
         ReceivedMessagesBufferController::new(
@@ -147,10 +159,27 @@ impl NymClient {
             query_receiver,
             mixnet_receiver,
             reply_key_storage,
+            reply_surb_storage,
         )
         .start()
     }
 
+    // future responsible for periodically removing reply SURBs that got stale due to
+    // the network topology changes
+    fn start_reply_surb_expirer(
+        &self,
+        reply_surb_storage: ReplySurbStorage,
+        topology_accessor: TopologyAccessor,
+    ) {
+        info!("Starting reply SURB expirer...");
+        ReplySurbExpirer::new(
+            reply_surb_storage,
+            topology_accessor,
+            self.config.get_base().get_topology_refresh_rate(),
+        )
+        .start();
+    }
+
     async fn start_gateway_client(
         &mut self,
         mixnet_message_sender: MixnetMessageSender,
@@ -214,12 +243,20 @@ impl NymClient {
     // the current global view of topology
     async fn start_topology_refresher(&mut self, topology_accessor: TopologyAccessor) {
         let topology_refresher_config = TopologyRefresherConfig::new(
-            self.config.get_base().get_validator_api_endpoints(),
             self.config.get_base().get_topology_refresh_rate(),
+            self.config.get_base().get_route_selection(),
+        );
+        let topology_provider = topology_provider_from_config(
+            self.config.get_base(),
             env!("CARGO_PKG_VERSION").to_string(),
+        )
+        .await
+        .expect("failed to load the topology file");
+        let mut topology_refresher = TopologyRefresher::new(
+            topology_refresher_config,
+            topology_provider,
+            topology_accessor,
         );
-        let mut topology_refresher =
-            TopologyRefresher::new(topology_refresher_config, topology_accessor);
         // before returning, block entire runtime to refresh the current network view so that any
         // components depending on topology would see a non-empty view
         info!("Obtaining initial network topology");
@@ -254,11 +291,18 @@ impl NymClient {
         &self,
         buffer_requester: ReceivedBufferRequestSender,
         msg_input: InputMessageSender,
+        reply_surb_storage: ReplySurbStorage,
+        delivery_receipts: DeliveryReceiptReceiver,
     ) {
         info!("Starting websocket listener...");
 
-        let websocket_handler =
-            websocket::Handler::new(msg_input, buffer_requester, self.as_mix_recipient());
+        let websocket_handler = websocket::Handler::new(
+            msg_input,
+            buffer_requester,
+            self.as_mix_recipient(),
+            reply_surb_storage,
+            delivery_receipts,
+        );
 
         websocket::Listener::new(self.config.get_listening_port()).start(websocket_handler);
     }
@@ -351,6 +395,21 @@ impl NymClient {
             ReplyKeyStorage::load(self.config.get_base().get_reply_encryption_key_store_path())
                 .expect("Failed to load reply key storage!");
 
+        // received reply SURBs are only stored on behalf of the websocket clients, the native
+        // ones get them directly alongside the received messages
+        let reply_surb_storage = match self.config.get_socket_type() {
+            SocketType::WebSocket => Some(
+                ReplySurbStorage::load(self.config.get_base().get_reply_surb_store_path())
+                    .expect("Failed to load reply SURB storage!"),
+            ),
+            SocketType::None => None,
+        };
+
+        // channels responsible for reporting delivery status of messages sent with an id
+        // (currently only possible via the websocket). The receipts are only buffered up to a limit
+        // while no websocket client is connected.
+        let (delivery_receipt_sender, delivery_receipt_receiver) = delivery_receipt_channel();
+
         // the components are started in very specific order. Unless you know what you are doing,
         // do not change that.
         self.start_topology_refresher(shared_topology_accessor.clone())
@@ -359,6 +418,7 @@ impl NymClient {
             received_buffer_request_receiver,
             mixnet_messages_receiver,
             reply_key_storage.clone(),
+            reply_surb_storage.clone(),
         );
 
         let gateway_client = self
@@ -372,15 +432,26 @@ impl NymClient {
             ack_receiver,
             input_receiver,
             sphinx_message_sender.clone(),
+            Some(delivery_receipt_sender),
         );
 
+        if let Some(reply_surb_storage) = &reply_surb_storage {
+            self.start_reply_surb_expirer(
+                reply_surb_storage.clone(),
+                shared_topology_accessor.clone(),
+            );
+        }
+
         self.start_cover_traffic_stream(shared_topology_accessor, sphinx_message_sender);
 
-        match self.config.get_socket_type() {
-            SocketType::WebSocket => {
-                self.start_websocket_listener(received_buffer_request_sender, input_sender)
-            }
-            SocketType::None => {
+        match reply_surb_storage {
+            Some(reply_surb_storage) => self.start_websocket_listener(
+                received_buffer_request_sender,
+                input_sender,
+                reply_surb_storage,
+                delivery_receipt_receiver,
+            ),
+            None => {
                 // if we did not start the socket, it means we're running (supposedly) in the native mode
                 // and hence we should announce 'ourselves' to the buffer
                 let (reconstructed_sender, reconstructed_receiver) = mpsc::unbounded();
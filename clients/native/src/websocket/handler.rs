@@ -2,24 +2,29 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use client_core::client::{
+    delivery_receipts::{DeliveryReceipt, DeliveryReceiptReceiver, DeliveryStatus, MessageId},
     inbound_messages::{InputMessage, InputMessageSender},
     received_buffer::{
         ReceivedBufferMessage, ReceivedBufferRequestSender, ReconstructedMessagesReceiver,
     },
+    reply_surb_storage::ReplySurbStorage,
 };
 use futures::channel::mpsc;
+use futures::lock::Mutex;
 use futures::{SinkExt, StreamExt};
 use log::*;
 use nymsphinx::addressing::clients::Recipient;
-use nymsphinx::anonymous_replies::ReplySurb;
+use nymsphinx::anonymous_replies::{ReplySurb, SenderTag};
+use nymsphinx::preparer::MAX_REPLY_SURBS_PER_MESSAGE;
 use nymsphinx::receiver::ReconstructedMessage;
+use std::sync::Arc;
 use tokio::net::TcpStream;
 use tokio_tungstenite::{
     accept_async,
     tungstenite::{protocol::Message as WsMessage, Error as WsError},
     WebSocketStream,
 };
-use websocket_requests::{requests::ClientRequest, responses::ServerResponse};
+use websocket_requests::{requests::ClientRequest, responses, responses::ServerResponse};
 
 enum ReceivedResponseType {
     Binary,
@@ -36,6 +41,9 @@ pub(crate) struct Handler {
     msg_input: InputMessageSender,
     buffer_requester: ReceivedBufferRequestSender,
     self_full_address: Recipient,
+    reply_surb_storage: ReplySurbStorage,
+    // only a single connection is ever handled at a time, so the lock is never contested
+    delivery_receipts: Arc<Mutex<DeliveryReceiptReceiver>>,
     socket: Option<WebSocketStream<TcpStream>>,
     received_response_type: ReceivedResponseType,
 }
@@ -47,6 +55,8 @@ impl Clone for Handler {
             msg_input: self.msg_input.clone(),
             buffer_requester: self.buffer_requester.clone(),
             self_full_address: self.self_full_address,
+            reply_surb_storage: self.reply_surb_storage.clone(),
+            delivery_receipts: Arc::clone(&self.delivery_receipts),
             socket: None,
             received_response_type: Default::default(),
         }
@@ -66,11 +76,15 @@ impl Handler {
         msg_input: InputMessageSender,
         buffer_requester: ReceivedBufferRequestSender,
         self_full_address: Recipient,
+        reply_surb_storage: ReplySurbStorage,
+        delivery_receipts: DeliveryReceiptReceiver,
     ) -> Self {
         Handler {
             msg_input,
             buffer_requester,
             self_full_address,
+            reply_surb_storage,
+            delivery_receipts: Arc::new(Mutex::new(delivery_receipts)),
             socket: None,
             received_response_type: Default::default(),
         }
@@ -81,17 +95,44 @@ impl Handler {
         recipient: Recipient,
         message: Vec<u8>,
         with_reply_surb: bool,
+        message_id: Option<MessageId>,
     ) -> Option<ServerResponse> {
         // the ack control is now responsible for chunking, etc.
-        let input_msg = InputMessage::new_fresh(recipient, message, with_reply_surb);
+        let mut input_msg = InputMessage::new_fresh(recipient, message, with_reply_surb);
+        if let Some(message_id) = message_id {
+            input_msg = input_msg.with_message_id(message_id)
+        }
+        self.msg_input.unbounded_send(input_msg).unwrap();
+
+        None
+    }
+
+    fn handle_send_with_reply_surbs(
+        &mut self,
+        recipient: Recipient,
+        message: Vec<u8>,
+        reply_surbs: u32,
+    ) -> Option<ServerResponse> {
+        if reply_surbs > MAX_REPLY_SURBS_PER_MESSAGE {
+            return Some(ServerResponse::new_error(format!(
+                "too many reply SURBs requested. Requested: {} and maximum is {}",
+                reply_surbs, MAX_REPLY_SURBS_PER_MESSAGE
+            )));
+        }
+
+        let input_msg = InputMessage::new_fresh_with_reply_surbs(recipient, message, reply_surbs);
         self.msg_input.unbounded_send(input_msg).unwrap();
 
         None
     }
 
+    fn handle_reply_too_long(&self, message_len: usize) -> Option<ServerResponse> {
+        Some(ServerResponse::new_error(format!("too long message to put inside a reply SURB. Received: {} bytes and maximum is {} bytes", message_len, ReplySurb::max_msg_len(Default::default()))))
+    }
+
     fn handle_reply(&mut self, reply_surb: ReplySurb, message: Vec<u8>) -> Option<ServerResponse> {
         if message.len() > ReplySurb::max_msg_len(Default::default()) {
-            return Some(ServerResponse::new_error(format!("too long message to put inside a reply SURB. Received: {} bytes and maximum is {} bytes", message.len(), ReplySurb::max_msg_len(Default::default()))));
+            return self.handle_reply_too_long(message.len());
         }
 
         let input_msg = InputMessage::new_reply(reply_surb, message);
@@ -100,6 +141,38 @@ impl Handler {
         None
     }
 
+    fn handle_reply_to_tag(
+        &mut self,
+        sender_tag: SenderTag,
+        message: Vec<u8>,
+    ) -> Option<ServerResponse> {
+        // make sure to not waste the SURB if the message can't fit in it anyway
+        if message.len() > ReplySurb::max_msg_len(Default::default()) {
+            return self.handle_reply_too_long(message.len());
+        }
+
+        let reply_surb = match self
+            .reply_surb_storage
+            .get_and_remove_reply_surb(sender_tag)
+        {
+            Err(err) => {
+                error!("Failed to retrieve reply SURB from the storage - {:?}", err);
+                return Some(ServerResponse::new_error(
+                    "failed to retrieve reply SURB from the storage",
+                ));
+            }
+            Ok(None) => {
+                return Some(ServerResponse::new_error(format!(
+                    "there are no valid reply SURBs stored for tag {}",
+                    sender_tag
+                )))
+            }
+            Ok(Some(reply_surb)) => reply_surb,
+        };
+
+        self.handle_reply(reply_surb, message)
+    }
+
     fn handle_self_address(&self) -> ServerResponse {
         ServerResponse::SelfAddress(self.self_full_address)
     }
@@ -110,11 +183,21 @@ impl Handler {
                 recipient,
                 message,
                 with_reply_surb,
-            } => self.handle_send(recipient, message, with_reply_surb),
+                message_id,
+            } => self.handle_send(recipient, message, with_reply_surb, message_id),
+            ClientRequest::SendWithReplySurbs {
+                recipient,
+                message,
+                reply_surbs,
+            } => self.handle_send_with_reply_surbs(recipient, message, reply_surbs),
             ClientRequest::Reply {
                 message,
                 reply_surb,
             } => self.handle_reply(reply_surb, message),
+            ClientRequest::ReplyToTag {
+                message,
+                sender_tag,
+            } => self.handle_reply_to_tag(sender_tag, message),
             ClientRequest::SelfAddress => Some(self.handle_self_address()),
         }
     }
@@ -207,6 +290,27 @@ impl Handler {
             .await
     }
 
+    async fn push_websocket_delivery_receipt(
+        &mut self,
+        receipt: DeliveryReceipt,
+    ) -> Result<(), WsError> {
+        let status = match receipt.status {
+            DeliveryStatus::Delivered => responses::DeliveryStatus::Delivered,
+            DeliveryStatus::Retransmitting => responses::DeliveryStatus::Retransmitting,
+            DeliveryStatus::GaveUp => responses::DeliveryStatus::GaveUp,
+        };
+        let response = ServerResponse::DeliveryStatus {
+            message_id: receipt.message_id,
+            status,
+        };
+
+        let msg = match self.received_response_type {
+            ReceivedResponseType::Binary => WsMessage::Binary(response.into_binary()),
+            ReceivedResponseType::Text => WsMessage::Text(response.into_text()),
+        };
+        self.send_websocket_response(msg).await
+    }
+
     async fn send_websocket_response(&mut self, msg: WsMessage) -> Result<(), WsError> {
         match self.socket {
             // TODO: more closely investigate difference between `Sink::send` and `Sink::send_all`
@@ -225,6 +329,9 @@ impl Handler {
     }
 
     async fn listen_for_requests(&mut self, mut msg_receiver: ReconstructedMessagesReceiver) {
+        let delivery_receipts = Arc::clone(&self.delivery_receipts);
+        let mut delivery_receipts = delivery_receipts.lock().await;
+
         loop {
             tokio::select! {
                 // we can either get a client request from the websocket
@@ -264,6 +371,16 @@ impl Handler {
                         break;
                     }
                 }
+                // or a change in the delivery status of a previously sent message
+                receipt = delivery_receipts.recv() => {
+                    let receipt = receipt.expect(
+                        "delivery receipts sender was unexpectedly closed! this shouldn't have ever happened!",
+                    );
+                    if let Err(e) = self.push_websocket_delivery_receipt(receipt).await {
+                        warn!("failed to send delivery receipt to the client - {:?}, assuming the connection is dead", e);
+                        break;
+                    }
+                }
             }
         }
     }
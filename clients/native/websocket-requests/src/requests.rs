@@ -7,7 +7,7 @@
 use crate::error::{self, ErrorKind};
 use crate::text::ClientRequestText;
 use nymsphinx::addressing::clients::Recipient;
-use nymsphinx::anonymous_replies::ReplySurb;
+use nymsphinx::anonymous_replies::{ReplySurb, SenderTag, SENDER_TAG_SIZE};
 use std::convert::{TryFrom, TryInto};
 use std::mem::size_of;
 
@@ -20,39 +20,77 @@ pub const REPLY_REQUEST_TAG: u8 = 0x01;
 /// Value tag representing [`SelfAddress`] variant of the [`ClientRequest`]
 pub const SELF_ADDRESS_REQUEST_TAG: u8 = 0x02;
 
+/// Value tag representing [`SendWithReplySurbs`] variant of the [`ClientRequest`]
+pub const SEND_WITH_REPLY_SURBS_REQUEST_TAG: u8 = 0x03;
+
+/// Value tag representing [`ReplyToTag`] variant of the [`ClientRequest`]
+pub const REPLY_TO_TAG_REQUEST_TAG: u8 = 0x04;
+
+// flags of the [`Send`] request
+const SEND_WITH_REPLY_SURB_FLAG: u8 = 0b01;
+const SEND_WITH_MESSAGE_ID_FLAG: u8 = 0b10;
+
 #[allow(non_snake_case)]
 #[derive(Debug)]
 pub enum ClientRequest {
     Send {
         recipient: Recipient,
         message: Vec<u8>,
-        // Perhaps we could change it to a number to indicate how many reply_SURBs we want to include?
         with_reply_surb: bool,
+        /// Optional application-provided id under which the delivery status of the message
+        /// is going to be reported.
+        message_id: Option<u64>,
+    },
+    SendWithReplySurbs {
+        recipient: Recipient,
+        message: Vec<u8>,
+        reply_surbs: u32,
     },
     Reply {
         message: Vec<u8>,
         reply_surb: ReplySurb,
     },
+    /// Reply to the sender of a previously received message using one of the reply SURBs
+    /// the client has stored under the provided tag.
+    ReplyToTag {
+        message: Vec<u8>,
+        sender_tag: SenderTag,
+    },
     SelfAddress,
 }
 
 // we could have been parsing it directly TryFrom<WsMessage>, but we want to retain
 // information about whether it came from binary or text to send appropriate response back
 impl ClientRequest {
-    // SEND_REQUEST_TAG || with_surb || recipient || data_len || data
-    fn serialize_send(recipient: Recipient, data: Vec<u8>, with_reply_surb: bool) -> Vec<u8> {
+    // SEND_REQUEST_TAG || flags || recipient || [message_id] || data_len || data
+    fn serialize_send(
+        recipient: Recipient,
+        data: Vec<u8>,
+        with_reply_surb: bool,
+        message_id: Option<u64>,
+    ) -> Vec<u8> {
         let data_len_bytes = (data.len() as u64).to_be_bytes();
+
+        let mut flags = 0;
+        if with_reply_surb {
+            flags |= SEND_WITH_REPLY_SURB_FLAG
+        }
+        if message_id.is_some() {
+            flags |= SEND_WITH_MESSAGE_ID_FLAG
+        }
+
         std::iter::once(SEND_REQUEST_TAG)
-            .chain(std::iter::once(with_reply_surb as u8))
+            .chain(std::iter::once(flags))
             .chain(recipient.to_bytes().iter().cloned()) // will not be length prefixed because the length is constant
+            .chain(message_id.into_iter().flat_map(|id| id.to_be_bytes()))
             .chain(data_len_bytes.iter().cloned())
             .chain(data.into_iter())
             .collect()
     }
 
-    // SEND_REQUEST_TAG || with_reply || recipient || data_len || data
+    // SEND_REQUEST_TAG || flags || recipient || [message_id] || data_len || data
     fn deserialize_send(b: &[u8]) -> Result<Self, error::Error> {
-        // we need to have at least 1 (tag) + 1 (reply flag) + Recipient::LEN + sizeof<u64> bytes
+        // we need to have at least 1 (tag) + 1 (flags) + Recipient::LEN + sizeof<u64> bytes
         if b.len() < 2 + Recipient::LEN + size_of::<u64>() {
             return Err(error::Error::new(
                 ErrorKind::TooShortRequest,
@@ -63,19 +101,97 @@ impl ClientRequest {
         // this MUST match because it was called by 'deserialize'
         debug_assert_eq!(b[0], SEND_REQUEST_TAG);
 
-        let with_reply_surb = match b[1] {
-            0 => false,
-            1 => true,
-            n => {
+        let flags = b[1];
+        if flags & !(SEND_WITH_REPLY_SURB_FLAG | SEND_WITH_MESSAGE_ID_FLAG) != 0 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                format!("invalid send flags {}", flags),
+            ));
+        }
+        let with_reply_surb = flags & SEND_WITH_REPLY_SURB_FLAG != 0;
+
+        let mut recipient_bytes = [0u8; Recipient::LEN];
+        recipient_bytes.copy_from_slice(&b[2..2 + Recipient::LEN]);
+        let recipient = match Recipient::try_from_bytes(recipient_bytes) {
+            Ok(recipient) => recipient,
+            Err(err) => {
                 return Err(error::Error::new(
                     ErrorKind::MalformedRequest,
-                    format!("invalid reply surb flag {}", n),
+                    format!("malformed recipient: {:?}", err),
                 ))
             }
         };
 
+        let mut offset = 2 + Recipient::LEN;
+        let message_id = if flags & SEND_WITH_MESSAGE_ID_FLAG != 0 {
+            if b.len() < offset + 2 * size_of::<u64>() {
+                return Err(error::Error::new(
+                    ErrorKind::TooShortRequest,
+                    "not enough data provided to recover 'send' with message id".to_string(),
+                ));
+            }
+            let message_id_bytes = &b[offset..offset + size_of::<u64>()];
+            offset += size_of::<u64>();
+            Some(u64::from_be_bytes(message_id_bytes.try_into().unwrap()))
+        } else {
+            None
+        };
+
+        let data_len_bytes = &b[offset..offset + size_of::<u64>()];
+        let data_len = u64::from_be_bytes(data_len_bytes.try_into().unwrap());
+        let data = &b[offset + size_of::<u64>()..];
+        if data.len() as u64 != data_len {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                format!(
+                    "data len has inconsistent length. specified: {} got: {}",
+                    data_len,
+                    data.len()
+                ),
+            ));
+        }
+
+        Ok(ClientRequest::Send {
+            with_reply_surb,
+            recipient,
+            message: data.to_vec(),
+            message_id,
+        })
+    }
+
+    // SEND_WITH_REPLY_SURBS_REQUEST_TAG || reply_surbs || recipient || data_len || data
+    fn serialize_send_with_reply_surbs(
+        recipient: Recipient,
+        data: Vec<u8>,
+        reply_surbs: u32,
+    ) -> Vec<u8> {
+        let data_len_bytes = (data.len() as u64).to_be_bytes();
+        std::iter::once(SEND_WITH_REPLY_SURBS_REQUEST_TAG)
+            .chain(reply_surbs.to_be_bytes().iter().cloned())
+            .chain(recipient.to_bytes().iter().cloned()) // will not be length prefixed because the length is constant
+            .chain(data_len_bytes.iter().cloned())
+            .chain(data.into_iter())
+            .collect()
+    }
+
+    // SEND_WITH_REPLY_SURBS_REQUEST_TAG || reply_surbs || recipient || data_len || data
+    fn deserialize_send_with_reply_surbs(b: &[u8]) -> Result<Self, error::Error> {
+        // we need to have at least 1 (tag) + sizeof<u32> (number of surbs) + Recipient::LEN + sizeof<u64> bytes
+        let header_len = 1 + size_of::<u32>();
+        if b.len() < header_len + Recipient::LEN + size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::TooShortRequest,
+                "not enough data provided to recover 'send with reply surbs'".to_string(),
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], SEND_WITH_REPLY_SURBS_REQUEST_TAG);
+
+        let reply_surbs = u32::from_be_bytes(b[1..header_len].try_into().unwrap());
+
         let mut recipient_bytes = [0u8; Recipient::LEN];
-        recipient_bytes.copy_from_slice(&b[2..2 + Recipient::LEN]);
+        recipient_bytes.copy_from_slice(&b[header_len..header_len + Recipient::LEN]);
         let recipient = match Recipient::try_from_bytes(recipient_bytes) {
             Ok(recipient) => recipient,
             Err(err) => {
@@ -86,9 +202,10 @@ impl ClientRequest {
             }
         };
 
-        let data_len_bytes = &b[2 + Recipient::LEN..2 + Recipient::LEN + size_of::<u64>()];
+        let data_start = header_len + Recipient::LEN + size_of::<u64>();
+        let data_len_bytes = &b[header_len + Recipient::LEN..data_start];
         let data_len = u64::from_be_bytes(data_len_bytes.try_into().unwrap());
-        let data = &b[2 + Recipient::LEN + size_of::<u64>()..];
+        let data = &b[data_start..];
         if data.len() as u64 != data_len {
             return Err(error::Error::new(
                 ErrorKind::MalformedRequest,
@@ -100,10 +217,10 @@ impl ClientRequest {
             ));
         }
 
-        Ok(ClientRequest::Send {
-            with_reply_surb,
+        Ok(ClientRequest::SendWithReplySurbs {
             recipient,
             message: data.to_vec(),
+            reply_surbs,
         })
     }
 
@@ -189,6 +306,57 @@ impl ClientRequest {
         })
     }
 
+    // REPLY_TO_TAG_REQUEST_TAG || sender_tag || message_len || message
+    fn serialize_reply_to_tag(message: Vec<u8>, sender_tag: SenderTag) -> Vec<u8> {
+        let message_len_bytes = (message.len() as u64).to_be_bytes();
+
+        std::iter::once(REPLY_TO_TAG_REQUEST_TAG)
+            .chain(sender_tag.to_bytes().iter().cloned())
+            .chain(message_len_bytes.iter().cloned())
+            .chain(message.into_iter())
+            .collect()
+    }
+
+    // REPLY_TO_TAG_REQUEST_TAG || sender_tag || message_len || message
+    fn deserialize_reply_to_tag(b: &[u8]) -> Result<Self, error::Error> {
+        if b.len() < 1 + SENDER_TAG_SIZE + size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::TooShortRequest,
+                "not enough data provided to recover 'reply to tag'".to_string(),
+            ));
+        }
+
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], REPLY_TO_TAG_REQUEST_TAG);
+
+        // this can't fail as we've just checked the length
+        let sender_tag = SenderTag::try_from_bytes(&b[1..1 + SENDER_TAG_SIZE]).unwrap();
+
+        let message_len_offset = 1 + SENDER_TAG_SIZE;
+        let message_len = u64::from_be_bytes(
+            b[message_len_offset..message_len_offset + size_of::<u64>()]
+                .as_ref()
+                .try_into()
+                .unwrap(),
+        );
+        let message = &b[message_len_offset + size_of::<u64>()..];
+        if message.len() as u64 != message_len {
+            return Err(error::Error::new(
+                ErrorKind::MalformedRequest,
+                format!(
+                    "message len has inconsistent length. specified: {} got: {}",
+                    message_len,
+                    message.len()
+                ),
+            ));
+        }
+
+        Ok(ClientRequest::ReplyToTag {
+            sender_tag,
+            message: message.to_vec(),
+        })
+    }
+
     // SELF_ADDRESS_REQUEST_TAG
     fn serialize_self_address() -> Vec<u8> {
         std::iter::once(SELF_ADDRESS_REQUEST_TAG).collect()
@@ -208,13 +376,25 @@ impl ClientRequest {
                 recipient,
                 message,
                 with_reply_surb,
-            } => Self::serialize_send(recipient, message, with_reply_surb),
+                message_id,
+            } => Self::serialize_send(recipient, message, with_reply_surb, message_id),
+
+            ClientRequest::SendWithReplySurbs {
+                recipient,
+                message,
+                reply_surbs,
+            } => Self::serialize_send_with_reply_surbs(recipient, message, reply_surbs),
 
             ClientRequest::Reply {
                 message,
                 reply_surb,
             } => Self::serialize_reply(message, reply_surb),
 
+            ClientRequest::ReplyToTag {
+                message,
+                sender_tag,
+            } => Self::serialize_reply_to_tag(message, sender_tag),
+
             ClientRequest::SelfAddress => Self::serialize_self_address(),
         }
     }
@@ -245,6 +425,8 @@ impl ClientRequest {
             SEND_REQUEST_TAG => Self::deserialize_send(b),
             REPLY_REQUEST_TAG => Self::deserialize_reply(b),
             SELF_ADDRESS_REQUEST_TAG => Ok(Self::deserialize_self_address(b)),
+            SEND_WITH_REPLY_SURBS_REQUEST_TAG => Self::deserialize_send_with_reply_surbs(b),
+            REPLY_TO_TAG_REQUEST_TAG => Self::deserialize_reply_to_tag(b),
             n => Err(error::Error::new(
                 ErrorKind::UnknownRequest,
                 format!("type {}", n),
@@ -280,6 +462,7 @@ mod tests {
             recipient,
             message: b"foomp".to_vec(),
             with_reply_surb: false,
+            message_id: None,
         };
 
         let bytes = send_request_no_surb.serialize();
@@ -289,10 +472,12 @@ mod tests {
                 recipient,
                 message,
                 with_reply_surb,
+                message_id,
             } => {
                 assert_eq!(recipient.to_string(), recipient_string);
                 assert_eq!(message, b"foomp".to_vec());
-                assert!(!with_reply_surb)
+                assert!(!with_reply_surb);
+                assert!(message_id.is_none())
             }
             _ => unreachable!(),
         }
@@ -301,6 +486,7 @@ mod tests {
             recipient,
             message: b"foomp".to_vec(),
             with_reply_surb: true,
+            message_id: Some(42),
         };
 
         let bytes = send_request_surb.serialize();
@@ -310,10 +496,39 @@ mod tests {
                 recipient,
                 message,
                 with_reply_surb,
+                message_id,
             } => {
                 assert_eq!(recipient.to_string(), recipient_string);
                 assert_eq!(message, b"foomp".to_vec());
-                assert!(with_reply_surb)
+                assert!(with_reply_surb);
+                assert_eq!(message_id, Some(42))
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn send_with_reply_surbs_request_serialization_works() {
+        let recipient = Recipient::try_from_base58_string("CytBseW6yFXUMzz4SGAKdNLGR7q3sJLLYxyBGvutNEQV.4QXYyEVc5fUDjmmi8PrHN9tdUFV4PCvSJE1278cHyvoe@4sBbL1ngf1vtNqykydQKTFh26sQCw888GpUqvPvyNB4f").unwrap();
+        let recipient_string = recipient.to_string();
+
+        let send_request = ClientRequest::SendWithReplySurbs {
+            recipient,
+            message: b"foomp".to_vec(),
+            reply_surbs: 42,
+        };
+
+        let bytes = send_request.serialize();
+        let recovered = ClientRequest::deserialize(&bytes).unwrap();
+        match recovered {
+            ClientRequest::SendWithReplySurbs {
+                recipient,
+                message,
+                reply_surbs,
+            } => {
+                assert_eq!(recipient.to_string(), recipient_string);
+                assert_eq!(message, b"foomp".to_vec());
+                assert_eq!(reply_surbs, 42)
             }
             _ => unreachable!(),
         }
@@ -342,6 +557,28 @@ mod tests {
         }
     }
 
+    #[test]
+    fn reply_to_tag_request_serialization_works() {
+        let sender_tag = SenderTag::from_bytes([42; SENDER_TAG_SIZE]);
+        let reply_request = ClientRequest::ReplyToTag {
+            message: b"foomp".to_vec(),
+            sender_tag,
+        };
+
+        let bytes = reply_request.serialize();
+        let recovered = ClientRequest::deserialize(&bytes).unwrap();
+        match recovered {
+            ClientRequest::ReplyToTag {
+                sender_tag: recovered_tag,
+                message,
+            } => {
+                assert_eq!(recovered_tag, sender_tag);
+                assert_eq!(message, b"foomp".to_vec());
+            }
+            _ => unreachable!(),
+        }
+    }
+
     #[test]
     fn self_address_request_serialization_works() {
         let self_address_request = ClientRequest::SelfAddress;
@@ -4,12 +4,10 @@
 // all variable size data is always prefixed with u64 length
 // tags are u8
 
-#![allow(unknown_lints)] // due to using `clippy::branches_sharing_code` which does not exist on `stable` just yet
-
 use crate::error::{self, ErrorKind};
 use crate::text::ServerResponseText;
 use nymsphinx::addressing::clients::Recipient;
-use nymsphinx::anonymous_replies::ReplySurb;
+use nymsphinx::anonymous_replies::{ReplySurb, SenderTag, SENDER_TAG_SIZE};
 use nymsphinx::receiver::ReconstructedMessage;
 use std::convert::TryInto;
 use std::mem::size_of;
@@ -23,10 +21,52 @@ pub const RECEIVED_RESPONSE_TAG: u8 = 0x01;
 /// Value tag representing [`SelfAddress`] variant of the [`ServerResponse`]
 pub const SELF_ADDRESS_RESPONSE_TAG: u8 = 0x02;
 
+/// Value tag representing [`DeliveryStatus`] variant of the [`ServerResponse`]
+pub const DELIVERY_STATUS_RESPONSE_TAG: u8 = 0x03;
+
+// flags indicating how many reply surbs are attached to the [`Received`] response
+const WITHOUT_REPLY_SURB_FLAG: u8 = 0x00;
+const WITH_REPLY_SURB_FLAG: u8 = 0x01;
+const WITH_REPLY_SURBS_FLAG: u8 = 0x02;
+// the reply surbs were stored by the client under the attached sender tag
+const WITH_SENDER_TAG_FLAG: u8 = 0x03;
+
+// values representing each variant of the [`DeliveryStatus`]
+const DELIVERED_STATUS: u8 = 0x00;
+const RETRANSMITTING_STATUS: u8 = 0x01;
+const GAVE_UP_STATUS: u8 = 0x02;
+
+/// Delivery state of a message sent with an application-provided id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+pub enum DeliveryStatus {
+    /// All fragments of the message got acknowledged.
+    Delivered = DELIVERED_STATUS,
+    /// Some fragments of the message were not acknowledged in time and are being retransmitted.
+    Retransmitting = RETRANSMITTING_STATUS,
+    /// The client has stopped trying to deliver the message.
+    GaveUp = GAVE_UP_STATUS,
+}
+
+impl DeliveryStatus {
+    fn try_from_u8(value: u8) -> Option<Self> {
+        match value {
+            DELIVERED_STATUS => Some(DeliveryStatus::Delivered),
+            RETRANSMITTING_STATUS => Some(DeliveryStatus::Retransmitting),
+            GAVE_UP_STATUS => Some(DeliveryStatus::GaveUp),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum ServerResponse {
     Received(ReconstructedMessage),
     SelfAddress(Recipient),
+    DeliveryStatus {
+        message_id: u64,
+        status: DeliveryStatus,
+    },
     Error(error::Error),
 }
 
@@ -39,31 +79,91 @@ impl ServerResponse {
     }
 
     // RECEIVED_RESPONSE_TAG || with_reply || (surb_len || surb) || msg_len || msg
+    // OR, if there are multiple reply surbs
+    // RECEIVED_RESPONSE_TAG || 2 || num_surbs || (surb_len || surb)* || msg_len || msg
+    // OR, if the reply surbs got stored by the client
+    // RECEIVED_RESPONSE_TAG || 3 || sender_tag || msg_len || msg
     fn serialize_received(reconstructed_message: ReconstructedMessage) -> Vec<u8> {
         let message_len_bytes = (reconstructed_message.message.len() as u64).to_be_bytes();
-        if let Some(reply_surb) = reconstructed_message.reply_surb {
-            let reply_surb_bytes = reply_surb.to_bytes();
-            let surb_len_bytes = (reply_surb_bytes.len() as u64).to_be_bytes();
-
-            // with_reply || surb_len || surb || msg_len || msg
-            std::iter::once(RECEIVED_RESPONSE_TAG)
-                .chain(std::iter::once(true as u8))
-                .chain(surb_len_bytes.iter().cloned())
-                .chain(reply_surb_bytes.iter().cloned())
-                .chain(message_len_bytes.iter().cloned())
-                .chain(reconstructed_message.message.into_iter())
-                .collect()
+        // if the reply surbs got stored by the client, they're not included in the response
+        let num_surbs = if reconstructed_message.sender_tag.is_some() {
+            0
         } else {
+            reconstructed_message.reply_surbs.len()
+        };
+
+        let surbs_bytes = reconstructed_message
+            .reply_surbs
+            .iter()
+            .take(num_surbs)
+            .flat_map(|reply_surb| {
+                let reply_surb_bytes = reply_surb.to_bytes();
+                let surb_len_bytes = (reply_surb_bytes.len() as u64).to_be_bytes();
+                surb_len_bytes
+                    .iter()
+                    .cloned()
+                    .chain(reply_surb_bytes.into_iter())
+                    .collect::<Vec<_>>()
+            });
+
+        let surbs_flag: Vec<u8> = match (reconstructed_message.sender_tag, num_surbs) {
+            // with_sender_tag || sender_tag || msg_len || msg
+            (Some(sender_tag), _) => std::iter::once(WITH_SENDER_TAG_FLAG)
+                .chain(sender_tag.to_bytes().iter().cloned())
+                .collect(),
             // without_reply || msg_len || msg
-            std::iter::once(RECEIVED_RESPONSE_TAG)
-                .chain(std::iter::once(false as u8))
-                .chain(message_len_bytes.iter().cloned())
-                .chain(reconstructed_message.message.into_iter())
-                .collect()
+            (None, 0) => vec![WITHOUT_REPLY_SURB_FLAG],
+            // with_reply || surb_len || surb || msg_len || msg
+            (None, 1) => vec![WITH_REPLY_SURB_FLAG],
+            // with_reply_surbs || num_surbs || (surb_len || surb)* || msg_len || msg
+            (None, n) => std::iter::once(WITH_REPLY_SURBS_FLAG)
+                .chain((n as u32).to_be_bytes().iter().cloned())
+                .collect(),
+        };
+
+        std::iter::once(RECEIVED_RESPONSE_TAG)
+            .chain(surbs_flag.into_iter())
+            .chain(surbs_bytes)
+            .chain(message_len_bytes.iter().cloned())
+            .chain(reconstructed_message.message.into_iter())
+            .collect()
+    }
+
+    // (surb_len || surb)
+    // returns the recovered surb alongside the number of bytes consumed
+    fn deserialize_length_prefixed_surb(b: &[u8]) -> Result<(ReplySurb, usize), error::Error> {
+        if b.len() < size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::TooShortResponse,
+                "not enough bytes to read reply_surb length!".to_string(),
+            ));
+        }
+
+        let reply_surb_len = u64::from_be_bytes(b[..size_of::<u64>()].as_ref().try_into().unwrap());
+
+        // make sure we won't go out of bounds here
+        if reply_surb_len > (b.len() - size_of::<u64>()) as u64 {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                "not enough bytes to read reply_surb bytes!".to_string(),
+            ));
+        }
+
+        let surb_bound = size_of::<u64>() + reply_surb_len as usize;
+        match ReplySurb::from_bytes(&b[size_of::<u64>()..surb_bound]) {
+            Ok(reply_surb) => Ok((reply_surb, surb_bound)),
+            Err(err) => Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                format!("malformed reply SURB: {:?}", err),
+            )),
         }
     }
 
     // RECEIVED_RESPONSE_TAG || with_reply || (surb_len || surb) || msg_len || msg
+    // OR, if there are multiple reply surbs
+    // RECEIVED_RESPONSE_TAG || 2 || num_surbs || (surb_len || surb)* || msg_len || msg
+    // OR, if the reply surbs got stored by the client
+    // RECEIVED_RESPONSE_TAG || 3 || sender_tag || msg_len || msg
     fn deserialize_received(b: &[u8]) -> Result<Self, error::Error> {
         // this MUST match because it was called by 'deserialize'
         debug_assert_eq!(b[0], RECEIVED_RESPONSE_TAG);
@@ -76,9 +176,26 @@ impl ServerResponse {
             ));
         }
 
-        let with_reply_surb = match b[1] {
-            0 => false,
-            1 => true,
+        let mut sender_tag = None;
+        let (num_surbs, mut offset) = match b[1] {
+            WITHOUT_REPLY_SURB_FLAG => (0, 2),
+            WITH_REPLY_SURB_FLAG => (1, 2),
+            WITH_SENDER_TAG_FLAG => {
+                if b.len() < 2 + SENDER_TAG_SIZE {
+                    return Err(error::Error::new(
+                        ErrorKind::TooShortResponse,
+                        "not enough data provided to recover sender tag".to_string(),
+                    ));
+                }
+                // this can't fail as we've just checked the length
+                sender_tag = Some(SenderTag::try_from_bytes(&b[2..2 + SENDER_TAG_SIZE]).unwrap());
+                (0, 2 + SENDER_TAG_SIZE)
+            }
+            WITH_REPLY_SURBS_FLAG => {
+                let num_surbs =
+                    u32::from_be_bytes(b[2..2 + size_of::<u32>()].as_ref().try_into().unwrap());
+                (num_surbs, 2 + size_of::<u32>())
+            }
             n => {
                 return Err(error::Error::new(
                     ErrorKind::MalformedResponse,
@@ -87,75 +204,44 @@ impl ServerResponse {
             }
         };
 
-        // this is a false positive as even though the code is the same, it refers to different things
-        #[allow(clippy::branches_sharing_code)]
-        if with_reply_surb {
-            let reply_surb_len =
-                u64::from_be_bytes(b[2..2 + size_of::<u64>()].as_ref().try_into().unwrap());
-
-            // make sure we won't go out of bounds here
-            if reply_surb_len > (b.len() - 2 + 2 * size_of::<u64>()) as u64 {
-                return Err(error::Error::new(
-                    ErrorKind::MalformedResponse,
-                    "not enough bytes to read reply_surb bytes!".to_string(),
-                ));
-            }
-
-            let surb_bound = 2 + size_of::<u64>() + reply_surb_len as usize;
-
-            let reply_surb_bytes = &b[2 + size_of::<u64>()..surb_bound];
-            let reply_surb = match ReplySurb::from_bytes(reply_surb_bytes) {
-                Ok(reply_surb) => reply_surb,
-                Err(err) => {
-                    return Err(error::Error::new(
-                        ErrorKind::MalformedResponse,
-                        format!("malformed reply SURB: {:?}", err),
-                    ))
-                }
-            };
-
-            let message_len = u64::from_be_bytes(
-                b[surb_bound..surb_bound + size_of::<u64>()]
-                    .as_ref()
-                    .try_into()
-                    .unwrap(),
-            );
-            let message = &b[surb_bound + size_of::<u64>()..];
-            if message.len() as u64 != message_len {
-                return Err(error::Error::new(
-                    ErrorKind::MalformedResponse,
-                    format!(
-                        "message len has inconsistent length. specified: {} got: {}",
-                        message_len,
-                        message.len()
-                    ),
-                ));
-            }
+        // don't trust the provided number of surbs for the allocation
+        let mut reply_surbs = Vec::new();
+        for _ in 0..num_surbs {
+            let (reply_surb, consumed) = Self::deserialize_length_prefixed_surb(&b[offset..])?;
+            reply_surbs.push(reply_surb);
+            offset += consumed;
+        }
 
-            Ok(ServerResponse::Received(ReconstructedMessage {
-                message: message.to_vec(),
-                reply_surb: Some(reply_surb),
-            }))
-        } else {
-            let message_len =
-                u64::from_be_bytes(b[2..2 + size_of::<u64>()].as_ref().try_into().unwrap());
-            let message = &b[2 + size_of::<u64>()..];
-            if message.len() as u64 != message_len {
-                return Err(error::Error::new(
-                    ErrorKind::MalformedResponse,
-                    format!(
-                        "message len has inconsistent length. specified: {} got: {}",
-                        message_len,
-                        message.len()
-                    ),
-                ));
-            }
+        if b.len() < offset + size_of::<u64>() {
+            return Err(error::Error::new(
+                ErrorKind::TooShortResponse,
+                "not enough data provided to recover message length".to_string(),
+            ));
+        }
 
-            Ok(ServerResponse::Received(ReconstructedMessage {
-                message: message.to_vec(),
-                reply_surb: None,
-            }))
+        let message_len = u64::from_be_bytes(
+            b[offset..offset + size_of::<u64>()]
+                .as_ref()
+                .try_into()
+                .unwrap(),
+        );
+        let message = &b[offset + size_of::<u64>()..];
+        if message.len() as u64 != message_len {
+            return Err(error::Error::new(
+                ErrorKind::MalformedResponse,
+                format!(
+                    "message len has inconsistent length. specified: {} got: {}",
+                    message_len,
+                    message.len()
+                ),
+            ));
         }
+
+        Ok(ServerResponse::Received(ReconstructedMessage {
+            message: message.to_vec(),
+            reply_surbs,
+            sender_tag,
+        }))
     }
 
     // SELF_ADDRESS_RESPONSE_TAG || self_address
@@ -193,6 +279,40 @@ impl ServerResponse {
         Ok(ServerResponse::SelfAddress(recipient))
     }
 
+    // DELIVERY_STATUS_RESPONSE_TAG || message_id || status
+    fn serialize_delivery_status(message_id: u64, status: DeliveryStatus) -> Vec<u8> {
+        std::iter::once(DELIVERY_STATUS_RESPONSE_TAG)
+            .chain(message_id.to_be_bytes().iter().cloned())
+            .chain(std::iter::once(status as u8))
+            .collect()
+    }
+
+    // DELIVERY_STATUS_RESPONSE_TAG || message_id || status
+    fn deserialize_delivery_status(b: &[u8]) -> Result<Self, error::Error> {
+        // this MUST match because it was called by 'deserialize'
+        debug_assert_eq!(b[0], DELIVERY_STATUS_RESPONSE_TAG);
+
+        if b.len() != 1 + size_of::<u64>() + 1 {
+            return Err(error::Error::new(
+                ErrorKind::TooShortResponse,
+                "not enough data provided to recover 'delivery status'".to_string(),
+            ));
+        }
+
+        let message_id = u64::from_be_bytes(b[1..1 + size_of::<u64>()].try_into().unwrap());
+        let status = match DeliveryStatus::try_from_u8(b[1 + size_of::<u64>()]) {
+            Some(status) => status,
+            None => {
+                return Err(error::Error::new(
+                    ErrorKind::MalformedResponse,
+                    format!("invalid delivery status {}", b[1 + size_of::<u64>()]),
+                ))
+            }
+        };
+
+        Ok(ServerResponse::DeliveryStatus { message_id, status })
+    }
+
     // ERROR_RESPONSE_TAG || err_code || msg_len || msg
     fn serialize_error(error: error::Error) -> Vec<u8> {
         let message_len_bytes = (error.message.len() as u64).to_be_bytes();
@@ -272,6 +392,9 @@ impl ServerResponse {
                 Self::serialize_received(reconstructed_message)
             }
             ServerResponse::SelfAddress(address) => Self::serialize_self_address(address),
+            ServerResponse::DeliveryStatus { message_id, status } => {
+                Self::serialize_delivery_status(message_id, status)
+            }
             ServerResponse::Error(err) => Self::serialize_error(err),
         }
     }
@@ -302,6 +425,7 @@ impl ServerResponse {
         match response_tag {
             RECEIVED_RESPONSE_TAG => Self::deserialize_received(b),
             SELF_ADDRESS_RESPONSE_TAG => Self::deserialize_self_address(b),
+            DELIVERY_STATUS_RESPONSE_TAG => Self::deserialize_delivery_status(b),
             ERROR_RESPONSE_TAG => Self::deserialize_error(b),
             n => Err(error::Error::new(
                 ErrorKind::UnknownResponse,
@@ -332,15 +456,17 @@ mod tests {
 
         let received_with_surb = ServerResponse::Received(ReconstructedMessage {
             message: b"foomp".to_vec(),
-            reply_surb: Some(ReplySurb::from_base58_string(reply_surb_string).unwrap()),
+            reply_surbs: vec![ReplySurb::from_base58_string(reply_surb_string).unwrap()],
+            sender_tag: None,
         });
         let bytes = received_with_surb.serialize();
         let recovered = ServerResponse::deserialize(&bytes).unwrap();
         match recovered {
             ServerResponse::Received(reconstructed) => {
                 assert_eq!(reconstructed.message, b"foomp".to_vec());
+                assert_eq!(reconstructed.reply_surbs.len(), 1);
                 assert_eq!(
-                    reconstructed.reply_surb.unwrap().to_base58_string(),
+                    reconstructed.reply_surbs[0].to_base58_string(),
                     reply_surb_string
                 )
             }
@@ -349,14 +475,54 @@ mod tests {
 
         let received_without_surb = ServerResponse::Received(ReconstructedMessage {
             message: b"foomp".to_vec(),
-            reply_surb: None,
+            reply_surbs: Vec::new(),
+            sender_tag: None,
         });
         let bytes = received_without_surb.serialize();
         let recovered = ServerResponse::deserialize(&bytes).unwrap();
         match recovered {
             ServerResponse::Received(reconstructed) => {
                 assert_eq!(reconstructed.message, b"foomp".to_vec());
-                assert!(reconstructed.reply_surb.is_none())
+                assert!(reconstructed.reply_surbs.is_empty())
+            }
+            _ => unreachable!(),
+        }
+
+        let received_with_surbs = ServerResponse::Received(ReconstructedMessage {
+            message: b"foomp".to_vec(),
+            reply_surbs: vec![
+                ReplySurb::from_base58_string(reply_surb_string).unwrap(),
+                ReplySurb::from_base58_string(reply_surb_string).unwrap(),
+                ReplySurb::from_base58_string(reply_surb_string).unwrap(),
+            ],
+            sender_tag: None,
+        });
+        let bytes = received_with_surbs.serialize();
+        let recovered = ServerResponse::deserialize(&bytes).unwrap();
+        match recovered {
+            ServerResponse::Received(reconstructed) => {
+                assert_eq!(reconstructed.message, b"foomp".to_vec());
+                assert_eq!(reconstructed.reply_surbs.len(), 3);
+                for reply_surb in reconstructed.reply_surbs {
+                    assert_eq!(reply_surb.to_base58_string(), reply_surb_string)
+                }
+            }
+            _ => unreachable!(),
+        }
+
+        let sender_tag = SenderTag::from_bytes([42; SENDER_TAG_SIZE]);
+        let received_with_sender_tag = ServerResponse::Received(ReconstructedMessage {
+            message: b"foomp".to_vec(),
+            reply_surbs: Vec::new(),
+            sender_tag: Some(sender_tag),
+        });
+        let bytes = received_with_sender_tag.serialize();
+        let recovered = ServerResponse::deserialize(&bytes).unwrap();
+        match recovered {
+            ServerResponse::Received(reconstructed) => {
+                assert_eq!(reconstructed.message, b"foomp".to_vec());
+                assert!(reconstructed.reply_surbs.is_empty());
+                assert_eq!(reconstructed.sender_tag, Some(sender_tag))
             }
             _ => unreachable!(),
         }
@@ -389,4 +555,42 @@ mod tests {
             _ => unreachable!(),
         }
     }
+
+    #[test]
+    fn delivery_status_response_serialization_works() {
+        for status in [
+            DeliveryStatus::Delivered,
+            DeliveryStatus::Retransmitting,
+            DeliveryStatus::GaveUp,
+        ] {
+            let response = ServerResponse::DeliveryStatus {
+                message_id: 42,
+                status,
+            };
+            let bytes = response.serialize();
+            let recovered = ServerResponse::deserialize(&bytes).unwrap();
+            match recovered {
+                ServerResponse::DeliveryStatus {
+                    message_id,
+                    status: recovered_status,
+                } => {
+                    assert_eq!(message_id, 42);
+                    assert_eq!(recovered_status, status);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn delivery_status_response_with_unknown_status_is_rejected() {
+        let mut bytes = ServerResponse::DeliveryStatus {
+            message_id: 42,
+            status: DeliveryStatus::Delivered,
+        }
+        .serialize();
+        *bytes.last_mut().unwrap() = GAVE_UP_STATUS + 1;
+
+        assert!(ServerResponse::deserialize(&bytes).is_err());
+    }
 }
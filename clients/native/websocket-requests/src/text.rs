@@ -3,9 +3,9 @@
 
 use crate::error::ErrorKind;
 use crate::requests::ClientRequest;
-use crate::responses::ServerResponse;
+use crate::responses::{DeliveryStatus, ServerResponse};
 use nymsphinx::addressing::clients::Recipient;
-use nymsphinx::anonymous_replies::ReplySurb;
+use nymsphinx::anonymous_replies::{ReplySurb, SenderTag};
 use serde::{Deserialize, Serialize};
 use std::convert::{TryFrom, TryInto};
 
@@ -20,6 +20,14 @@ pub(super) enum ClientRequestText {
         message: String,
         recipient: String,
         with_reply_surb: bool,
+        #[serde(default)]
+        message_id: Option<u64>,
+    },
+    #[serde(rename_all = "camelCase")]
+    SendWithReplySurbs {
+        message: String,
+        recipient: String,
+        reply_surbs: u32,
     },
     SelfAddress,
     #[serde(rename_all = "camelCase")]
@@ -27,6 +35,11 @@ pub(super) enum ClientRequestText {
         message: String,
         reply_surb: String,
     },
+    #[serde(rename_all = "camelCase")]
+    ReplyToTag {
+        message: String,
+        sender_tag: String,
+    },
 }
 
 impl TryFrom<String> for ClientRequestText {
@@ -46,6 +59,7 @@ impl TryInto<ClientRequest> for ClientRequestText {
                 message,
                 recipient,
                 with_reply_surb,
+                message_id,
             } => {
                 let message_bytes = message.into_bytes();
                 let recipient = Recipient::try_from_base58_string(recipient).map_err(|err| {
@@ -56,6 +70,23 @@ impl TryInto<ClientRequest> for ClientRequestText {
                     message: message_bytes,
                     recipient,
                     with_reply_surb,
+                    message_id,
+                })
+            }
+            ClientRequestText::SendWithReplySurbs {
+                message,
+                recipient,
+                reply_surbs,
+            } => {
+                let message_bytes = message.into_bytes();
+                let recipient = Recipient::try_from_base58_string(recipient).map_err(|err| {
+                    Self::Error::new(ErrorKind::MalformedRequest, err.to_string())
+                })?;
+
+                Ok(ClientRequest::SendWithReplySurbs {
+                    message: message_bytes,
+                    recipient,
+                    reply_surbs,
                 })
             }
             ClientRequestText::SelfAddress => Ok(ClientRequest::SelfAddress),
@@ -73,6 +104,20 @@ impl TryInto<ClientRequest> for ClientRequestText {
                     reply_surb,
                 })
             }
+            ClientRequestText::ReplyToTag {
+                message,
+                sender_tag,
+            } => {
+                let message_bytes = message.into_bytes();
+                let sender_tag = SenderTag::try_from_base58_string(sender_tag).map_err(|err| {
+                    Self::Error::new(ErrorKind::MalformedRequest, err.to_string())
+                })?;
+
+                Ok(ClientRequest::ReplyToTag {
+                    message: message_bytes,
+                    sender_tag,
+                })
+            }
         }
     }
 }
@@ -87,15 +132,46 @@ pub(super) enum ServerResponseText {
     Received {
         message: String,
         reply_surb: Option<String>,
+        // only present if the message had more than a single reply surb attached,
+        // in which case `reply_surb` is not set
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        reply_surbs: Vec<String>,
+        // only present if the client has stored the attached reply surbs itself,
+        // in which case neither `reply_surb` nor `reply_surbs` are set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        sender_tag: Option<String>,
     },
     SelfAddress {
         address: String,
     },
+    #[serde(rename_all = "camelCase")]
+    DeliveryStatus {
+        message_id: u64,
+        status: DeliveryStatusText,
+    },
     Error {
         message: String,
     },
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub(super) enum DeliveryStatusText {
+    Delivered,
+    Retransmitting,
+    GaveUp,
+}
+
+impl From<DeliveryStatus> for DeliveryStatusText {
+    fn from(status: DeliveryStatus) -> Self {
+        match status {
+            DeliveryStatus::Delivered => DeliveryStatusText::Delivered,
+            DeliveryStatus::Retransmitting => DeliveryStatusText::Retransmitting,
+            DeliveryStatus::GaveUp => DeliveryStatusText::GaveUp,
+        }
+    }
+}
+
 impl TryFrom<String> for ServerResponseText {
     type Error = serde_json::Error;
 
@@ -120,18 +196,39 @@ impl From<ServerResponse> for ServerResponseText {
     fn from(resp: ServerResponse) -> Self {
         match resp {
             ServerResponse::Received(reconstructed) => {
+                let mut reply_surbs: Vec<_> = reconstructed
+                    .reply_surbs
+                    .iter()
+                    .map(|reply_surb| reply_surb.to_base58_string())
+                    .collect();
+
+                // keep the old format if there's at most a single reply surb
+                let reply_surb = if reply_surbs.len() == 1 {
+                    reply_surbs.pop()
+                } else {
+                    None
+                };
+
                 ServerResponseText::Received {
                     // TODO: ask DH what is more appropriate, lossy utf8 conversion or returning error and then
                     // pure binary later
                     message: String::from_utf8_lossy(&reconstructed.message).into_owned(),
-                    reply_surb: reconstructed
-                        .reply_surb
-                        .map(|reply_surb| reply_surb.to_base58_string()),
+                    reply_surb,
+                    reply_surbs,
+                    sender_tag: reconstructed
+                        .sender_tag
+                        .map(|sender_tag| sender_tag.to_base58_string()),
                 }
             }
             ServerResponse::SelfAddress(recipient) => ServerResponseText::SelfAddress {
                 address: recipient.to_string(),
             },
+            ServerResponse::DeliveryStatus { message_id, status } => {
+                ServerResponseText::DeliveryStatus {
+                    message_id,
+                    status: status.into(),
+                }
+            }
             ServerResponse::Error(err) => ServerResponseText::Error {
                 message: err.to_string(),
             },
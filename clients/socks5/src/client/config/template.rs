@@ -30,6 +30,15 @@ validator_api_urls = [
     {{/each}}
 ]
 
+# Path to a JSON or TOML file describing the network topology. If set, it is used instead of
+# the validator APIs for obtaining the view of the network, for example in a local mixnet.
+# If it points to a directory, the most recently modified topology file inside it is used.
+topology_file = '{{ client.topology_file }}'
+
+# Indicates whether the topology file should be reloaded whenever it gets modified. The
+# changes are picked up on the next topology refresh.
+watch_topology_file = {{ client.watch_topology_file }}
+
 # Path to file containing private identity key.
 private_identity_key_file = '{{ client.private_identity_key_file }}'
 
@@ -46,6 +55,10 @@ public_encryption_key_file = '{{ client.public_encryption_key_file }}'
 # sent but not received back.
 reply_encryption_key_store_path = '{{ client.reply_encryption_key_store_path }}'
 
+# Full path to file containing reply-SURBs received from other clients, stored under
+# locally assigned sender tags.
+reply_surb_store_path = '{{ client.reply_surb_store_path }}'
+
 # Path to the database containing bandwidth credentials
 database_path = '{{ client.database_path }}'
 
@@ -15,7 +15,7 @@ use client_core::client::received_buffer::{
 };
 use client_core::client::reply_key_storage::ReplyKeyStorage;
 use client_core::client::topology_control::{
-    TopologyAccessor, TopologyRefresher, TopologyRefresherConfig,
+    topology_provider_from_config, TopologyAccessor, TopologyRefresher, TopologyRefresherConfig,
 };
 use client_core::config::persistence::key_pathfinder::ClientKeyPathfinder;
 use crypto::asymmetric::identity;
@@ -102,6 +102,11 @@ impl NymClient {
             self.key_manager.ack_key(),
             self.config.get_base().get_ack_wait_multiplier(),
             self.config.get_base().get_ack_wait_addition(),
+            self.config.get_base().get_maximum_retransmissions(),
+            self.config.get_base().get_retransmission_deadline(),
+            self.config
+                .get_base()
+                .get_retransmission_backoff_multiplier(),
             self.config.get_base().get_average_ack_delay(),
             self.config.get_base().get_message_sending_average_delay(),
             self.config.get_base().get_average_packet_delay(),
@@ -117,6 +122,7 @@ impl NymClient {
             mix_sender,
             topology_accessor,
             reply_key_storage,
+            None,
         )
         .start();
     }
@@ -135,6 +141,7 @@ impl NymClient {
             query_receiver,
             mixnet_receiver,
             reply_key_storage,
+            None,
         )
         .start()
     }
@@ -202,12 +209,20 @@ impl NymClient {
     // the current global view of topology
     async fn start_topology_refresher(&mut self, topology_accessor: TopologyAccessor) {
         let topology_refresher_config = TopologyRefresherConfig::new(
-            self.config.get_base().get_validator_api_endpoints(),
             self.config.get_base().get_topology_refresh_rate(),
+            self.config.get_base().get_route_selection(),
+        );
+        let topology_provider = topology_provider_from_config(
+            self.config.get_base(),
             env!("CARGO_PKG_VERSION").to_string(),
+        )
+        .await
+        .expect("failed to load the topology file");
+        let mut topology_refresher = TopologyRefresher::new(
+            topology_refresher_config,
+            topology_provider,
+            topology_accessor,
         );
-        let mut topology_refresher =
-            TopologyRefresher::new(topology_refresher_config, topology_accessor);
         // before returning, block entire runtime to refresh the current network view so that any
         // components depending on topology would see a non-empty view
         info!("Obtaining initial network topology");
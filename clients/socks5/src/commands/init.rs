@@ -3,6 +3,7 @@
 
 use clap::{App, Arg, ArgMatches};
 use client_core::client::key_manager::KeyManager;
+use client_core::client::topology_control::load_topology_file;
 use client_core::config::persistence::key_pathfinder::ClientKeyPathfinder;
 use config::NymConfig;
 use crypto::asymmetric::{encryption, identity};
@@ -12,13 +13,14 @@ use nymsphinx::addressing::clients::Recipient;
 use nymsphinx::addressing::nodes::NodeIdentity;
 use rand::{prelude::SliceRandom, rngs::OsRng, thread_rng};
 use std::convert::TryInto;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
 use topology::{filter::VersionFilterable, gateway};
 use url::Url;
 
 use crate::client::config::Config;
-use crate::commands::override_config;
+use crate::commands::{override_config, TOPOLOGY_FILE_ARG_NAME, WATCH_TOPOLOGY_FILE_ARG_NAME};
 #[cfg(feature = "eth")]
 #[cfg(not(feature = "coconut"))]
 use crate::commands::{
@@ -51,6 +53,16 @@ pub fn command_args<'a, 'b>() -> clap::App<'a, 'b> {
                  .help("Comma separated list of rest endpoints of the validators")
                  .takes_value(true),
         )
+        .arg(Arg::with_name(TOPOLOGY_FILE_ARG_NAME)
+            .long(TOPOLOGY_FILE_ARG_NAME)
+            .help("Path to a JSON or TOML file, or a directory of them, describing the network topology that is going to be used instead of querying the validators")
+            .takes_value(true)
+        )
+        .arg(Arg::with_name(WATCH_TOPOLOGY_FILE_ARG_NAME)
+            .long(WATCH_TOPOLOGY_FILE_ARG_NAME)
+            .help("Reload the topology file, or pick up the newest file of the topology directory, on every topology refresh")
+            .requires(TOPOLOGY_FILE_ARG_NAME)
+        )
         .arg(Arg::with_name("port")
             .short("p")
             .long("port")
@@ -88,44 +100,69 @@ pub fn command_args<'a, 'b>() -> clap::App<'a, 'b> {
     app
 }
 
+// returns the shared keys alongside the address of the gateway listener that was used
 async fn register_with_gateway(
     gateway: &gateway::Node,
     our_identity: Arc<identity::KeyPair>,
-) -> Arc<SharedKeys> {
+) -> (Arc<SharedKeys>, String) {
     let timeout = Duration::from_millis(1500);
-    let mut gateway_client = GatewayClient::new_init(
-        gateway.clients_address(),
-        gateway.identity_key,
-        gateway.owner.clone(),
-        our_identity.clone(),
-        timeout,
-    );
-    gateway_client
-        .establish_connection()
-        .await
-        .expect("failed to establish connection with the gateway!");
-    gateway_client
-        .perform_initial_authentication()
-        .await
-        .expect("failed to register with the gateway!")
+
+    // prefer the TLS-secured listener if the gateway has one, but fall back to the plain one
+    let gateway_addresses = gateway
+        .clients_tls_address()
+        .into_iter()
+        .chain(std::iter::once(gateway.clients_address()));
+    for gateway_address in gateway_addresses {
+        let mut gateway_client = GatewayClient::new_init(
+            gateway_address.clone(),
+            gateway.identity_key,
+            gateway.owner.clone(),
+            our_identity.clone(),
+            timeout,
+        );
+        if let Err(err) = gateway_client.establish_connection().await {
+            log::warn!(
+                "failed to establish connection with the gateway at {} - {}",
+                gateway_address,
+                err
+            );
+            continue;
+        }
+        let shared_keys = gateway_client
+            .perform_initial_authentication()
+            .await
+            .expect("failed to register with the gateway!");
+        return (shared_keys, gateway_address);
+    }
+    panic!("failed to establish connection with the gateway!")
 }
 
 async fn gateway_details(
     validator_servers: Vec<Url>,
+    topology_file: Option<PathBuf>,
     chosen_gateway_id: Option<&str>,
 ) -> gateway::Node {
-    let validator_api = validator_servers
-        .choose(&mut thread_rng())
-        .expect("The list of validator apis is empty");
-    let validator_client = validator_client::ApiClient::new(validator_api.clone());
+    let filtered_gateways = if let Some(topology_file) = topology_file {
+        log::trace!("Loading list of gateways from: {:?}", topology_file);
+        load_topology_file(&topology_file)
+            .await
+            .expect("failed to load the topology file")
+            .gateways()
+            .to_vec()
+    } else {
+        let validator_api = validator_servers
+            .choose(&mut thread_rng())
+            .expect("The list of validator apis is empty");
+        let validator_client = validator_client::ApiClient::new(validator_api.clone());
 
-    let gateways = validator_client.get_cached_gateways().await.unwrap();
-    let valid_gateways = gateways
-        .into_iter()
-        .filter_map(|gateway| gateway.try_into().ok())
-        .collect::<Vec<gateway::Node>>();
+        let gateways = validator_client.get_cached_gateways().await.unwrap();
+        let valid_gateways = gateways
+            .into_iter()
+            .filter_map(|gateway| gateway.try_into().ok())
+            .collect::<Vec<gateway::Node>>();
 
-    let filtered_gateways = valid_gateways.filter_by_version(env!("CARGO_PKG_VERSION"));
+        valid_gateways.filter_by_version(env!("CARGO_PKG_VERSION"))
+    };
 
     // if we have chosen particular gateway - use it, otherwise choose a random one.
     // (remember that in active topology all gateways have at least 100 reputation so should
@@ -217,16 +254,17 @@ pub async fn execute(matches: ArgMatches<'static>) {
 
         let gateway_details = gateway_details(
             config.get_base().get_validator_api_endpoints(),
+            config.get_base().get_topology_file(),
             chosen_gateway_id,
         )
         .await;
-        let shared_keys =
+        let (shared_keys, gateway_address) =
             register_with_gateway(&gateway_details, key_manager.identity_keypair()).await;
 
         config.get_base_mut().with_gateway_endpoint(
             gateway_details.identity_key.to_base58_string(),
             gateway_details.owner.clone(),
-            gateway_details.clients_address(),
+            gateway_address,
         );
         key_manager.insert_gateway_shared_key(shared_keys);
 
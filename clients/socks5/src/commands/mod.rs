@@ -10,6 +10,8 @@ pub(crate) mod run;
 pub(crate) mod upgrade;
 
 pub(crate) const ENABLED_CREDENTIALS_MODE_ARG_NAME: &str = "enabled-credentials-mode";
+pub(crate) const TOPOLOGY_FILE_ARG_NAME: &str = "topology-file";
+pub(crate) const WATCH_TOPOLOGY_FILE_ARG_NAME: &str = "watch-topology-file";
 #[cfg(not(feature = "coconut"))]
 pub(crate) const ETH_ENDPOINT_ARG_NAME: &str = "eth_endpoint";
 #[cfg(not(feature = "coconut"))]
@@ -39,6 +41,13 @@ pub(crate) fn override_config(mut config: Config, matches: &ArgMatches<'_>) -> C
             .set_custom_validator_apis(parse_validators(raw_validators));
     }
 
+    if let Some(topology_file) = matches.value_of(TOPOLOGY_FILE_ARG_NAME) {
+        config.get_base_mut().set_topology_file(
+            topology_file,
+            matches.is_present(WATCH_TOPOLOGY_FILE_ARG_NAME),
+        );
+    }
+
     if let Some(port) = matches.value_of("port").map(|port| port.parse::<u16>()) {
         if let Err(err) = port {
             // if port was overridden, it must be parsable
@@ -3,7 +3,7 @@
 
 use crate::client::config::Config;
 use crate::client::NymClient;
-use crate::commands::override_config;
+use crate::commands::{override_config, TOPOLOGY_FILE_ARG_NAME, WATCH_TOPOLOGY_FILE_ARG_NAME};
 #[cfg(feature = "eth")]
 #[cfg(not(feature = "coconut"))]
 use crate::commands::{
@@ -39,6 +39,16 @@ pub fn command_args<'a, 'b>() -> clap::App<'a, 'b> {
                 .help("Comma separated list of rest endpoints of the validators")
                 .takes_value(true),
         )
+        .arg(Arg::with_name(TOPOLOGY_FILE_ARG_NAME)
+            .long(TOPOLOGY_FILE_ARG_NAME)
+            .help("Path to a JSON or TOML file, or a directory of them, describing the network topology that is going to be used instead of querying the validators")
+            .takes_value(true)
+        )
+        .arg(Arg::with_name(WATCH_TOPOLOGY_FILE_ARG_NAME)
+            .long(WATCH_TOPOLOGY_FILE_ARG_NAME)
+            .help("Reload the topology file, or pick up the newest file of the topology directory, on every topology refresh")
+            .requires(TOPOLOGY_FILE_ARG_NAME)
+        )
         .arg(Arg::with_name("gateway")
             .long("gateway")
             .help("Id of the gateway we want to connect to. If overridden, it is user's responsibility to ensure prior registration happened")
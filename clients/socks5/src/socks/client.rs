@@ -2,7 +2,8 @@
 
 use super::authentication::{AuthenticationMethods, Authenticator, User};
 use super::request::{SocksCommand, SocksRequest};
-use super::types::{ResponseCode, SocksProxyError};
+use super::types::{AddrType, ResponseCode, SocksProxyError};
+use super::udp::UdpRelay;
 use super::{RESERVED, SOCKS_VERSION};
 use client_core::client::inbound_messages::InputMessage;
 use client_core::client::inbound_messages::InputMessageSender;
@@ -18,7 +19,7 @@ use proxy_helpers::proxy_runner::ProxyRunner;
 use rand::RngCore;
 use socks5_requests::{ConnectionId, Message, RemoteAddress, Request};
 use std::io;
-use std::net::SocketAddr;
+use std::net::{IpAddr, SocketAddr};
 use std::pin::Pin;
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, ReadBuf};
 use tokio::{self, net::TcpStream};
@@ -62,6 +63,17 @@ impl StreamState {
         }
     }
 
+    /// Returns the local address that this stream is bound to.
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            StreamState::RunningProxy => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "stream is being used to run the proxy",
+            )),
+            StreamState::Available(ref stream) => stream.local_addr(),
+        }
+    }
+
     async fn shutdown(&mut self) -> io::Result<()> {
         // shutdown should only be called if proxy is not being run. If it is, there's some bug
         // somewhere
@@ -250,22 +262,67 @@ impl SocksClient {
             conn_receiver,
             input_sender,
             connection_id,
+            // we have to let the network requester know we support flow control in the first place
+            true,
+        )
+        .run(
+            move |conn_id, read_data, socket_closed| {
+                let provider_request = Request::new_send(conn_id, read_data, socket_closed);
+                let provider_message = Message::Request(provider_request);
+                InputMessage::new_fresh(recipient, provider_message.into_bytes(), false)
+            },
+            move |conn_id, credit| {
+                let provider_request = Request::new_credit(conn_id, credit);
+                let provider_message = Message::Request(provider_request);
+                InputMessage::new_fresh(recipient, provider_message.into_bytes(), false)
+            },
         )
-        .run(move |conn_id, read_data, socket_closed| {
-            let provider_request = Request::new_send(conn_id, read_data, socket_closed);
-            let provider_message = Message::Request(provider_request);
-            InputMessage::new_fresh(recipient, provider_message.into_bytes(), false)
-        })
         .await
         .into_inner();
         // recover stream from the proxy
         self.stream.finish_proxy(stream)
     }
 
+    async fn run_udp_relay(&mut self) -> Result<(), SocksProxyError> {
+        // setup for receiving from the mixnet
+        let (datagram_sender, datagram_receiver) = mpsc::unbounded();
+
+        let relay = UdpRelay::new(
+            self.stream.local_addr()?.ip(),
+            self.stream.peer_addr()?.ip(),
+            self.connection_id,
+            self.service_provider,
+            self.self_address,
+            self.input_sender.clone(),
+            datagram_receiver,
+        )
+        .await?;
+        let relay_address = relay.local_addr()?;
+        self.acknowledge_udp_associate(relay_address).await?;
+
+        self.started_proxy = true;
+        self.controller_sender
+            .unbounded_send(ControllerCommand::InsertDatagram(
+                self.connection_id,
+                datagram_sender,
+            ))
+            .unwrap();
+
+        info!(
+            "Starting UDP relay on {} (id: {})",
+            relay_address, self.connection_id
+        );
+        relay.run(&mut self.stream).await;
+        info!(
+            "UDP relay on {} is finished (id: {})",
+            relay_address, self.connection_id
+        );
+
+        Ok(())
+    }
+
     /// Handles a client request.
     async fn handle_request(&mut self) -> Result<(), SocksProxyError> {
-        debug!("Handling CONNECT Command");
-
         let request = SocksRequest::from_stream(&mut self.stream).await?;
         let remote_address = request.to_string();
 
@@ -275,6 +332,7 @@ impl SocksClient {
         match request.command {
             // Use the Proxy to connect to the specified addr/port
             SocksCommand::Connect => {
+                debug!("Handling CONNECT Command");
                 trace!("Connecting to: {:?}", remote_address.clone());
                 self.acknowledge_socks5().await;
 
@@ -295,8 +353,16 @@ impl SocksClient {
                 );
             }
 
-            SocksCommand::Bind => unimplemented!(), // not handled
-            SocksCommand::UdpAssociate => unimplemented!(), // not handled
+            SocksCommand::UdpAssociate => {
+                debug!("Handling UDP ASSOCIATE Command");
+                self.run_udp_relay().await?;
+            }
+
+            SocksCommand::Bind => {
+                warn!("BIND command is not supported");
+                self.error(ResponseCode::CommandNotSupported).await?;
+                self.shutdown().await?;
+            }
         };
 
         Ok(())
@@ -322,6 +388,29 @@ impl SocksClient {
             .unwrap();
     }
 
+    /// Writes a Socks5 header back to the requesting client's TCP stream, informing it
+    /// about the address of the relay it should be sending its datagrams to.
+    async fn acknowledge_udp_associate(
+        &mut self,
+        relay_address: SocketAddr,
+    ) -> Result<(), SocksProxyError> {
+        let mut response = vec![SOCKS_VERSION, ResponseCode::Success as u8, RESERVED];
+        match relay_address.ip() {
+            IpAddr::V4(ip) => {
+                response.push(AddrType::V4 as u8);
+                response.extend_from_slice(&ip.octets());
+            }
+            IpAddr::V6(ip) => {
+                response.push(AddrType::V6 as u8);
+                response.extend_from_slice(&ip.octets());
+            }
+        }
+        response.extend_from_slice(&relay_address.port().to_be_bytes());
+
+        self.stream.write_all(&response).await?;
+        Ok(())
+    }
+
     /// Authenticate the incoming request. Each request is checked for its
     /// authentication method. A user/password request will extract the
     /// username and password from the stream, then check with the Authenticator
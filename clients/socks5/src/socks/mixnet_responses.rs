@@ -5,7 +5,7 @@ use futures::StreamExt;
 use log::*;
 use nymsphinx::receiver::ReconstructedMessage;
 use proxy_helpers::connection_controller::{ControllerCommand, ControllerSender};
-use socks5_requests::Response;
+use socks5_requests::Message;
 
 pub(crate) struct MixnetResponseListener {
     buffer_requester: ReceivedBufferRequestSender,
@@ -40,25 +40,33 @@ impl MixnetResponseListener {
 
     async fn on_message(&self, reconstructed_message: ReconstructedMessage) {
         let raw_message = reconstructed_message.message;
-        if reconstructed_message.reply_surb.is_some() {
-            warn!("this message had a surb - we didn't do anything with it");
+        if !reconstructed_message.reply_surbs.is_empty() {
+            warn!("this message had surbs - we didn't do anything with them");
         }
 
-        let response = match Response::try_from_bytes(&raw_message) {
+        let command = match Message::try_from_bytes(&raw_message) {
             Err(err) => {
-                warn!("failed to parse received response - {:?}", err);
+                warn!("failed to parse received response - {}", err);
                 return;
             }
-            Ok(data) => data,
-        };
-
-        self.controller_sender
-            .unbounded_send(ControllerCommand::Send(
+            Ok(Message::Response(response)) => {
+                ControllerCommand::Send(response.connection_id, response.data, response.is_closed)
+            }
+            Ok(Message::DatagramResponse(response)) => ControllerCommand::SendDatagram(
                 response.connection_id,
+                response.remote_addr,
                 response.data,
-                response.is_closed,
-            ))
-            .unwrap();
+            ),
+            Ok(Message::CreditResponse(response)) => {
+                ControllerCommand::Credit(response.connection_id, response.credit)
+            }
+            Ok(Message::Request(_)) => {
+                warn!("received a request instead of a response - ignoring it");
+                return;
+            }
+        };
+
+        self.controller_sender.unbounded_send(command).unwrap();
     }
 
     pub(crate) async fn run(&mut self) {
@@ -0,0 +1,292 @@
+#![forbid(unsafe_code)]
+
+use super::types::{AddrType, SocksProxyError};
+use super::utils as socks_utils;
+use client_core::client::inbound_messages::{InputMessage, InputMessageSender};
+use futures::StreamExt;
+use log::*;
+use nymsphinx::addressing::clients::Recipient;
+use proxy_helpers::connection_controller::{DatagramMessage, DatagramReceiver};
+use socks5_requests::{ConnectionId, Message, RemoteAddress, Request};
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::net::UdpSocket;
+
+/// Maximum size of a single UDP datagram we are willing to receive.
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// Parses the SOCKS5 UDP request header that precedes every datagram sent by the client
+/// to the relay. Returns the destination address and the actual data to be sent.
+///
+/// The header looks like this:
+///
+/// +----+------+------+----------+----------+----------+
+/// |RSV | FRAG | ATYP | DST.ADDR | DST.PORT |   DATA   |
+/// +----+------+------+----------+----------+----------+
+/// | 2  |  1   |  1   | Variable |    2     | Variable |
+/// +----+------+------+----------+----------+----------+
+pub(crate) fn parse_udp_request(
+    datagram: &[u8],
+) -> Result<(RemoteAddress, &[u8]), SocksProxyError> {
+    if datagram.len() < 4 {
+        return Err(malformed("datagram too short to contain the header"));
+    }
+
+    // we do not support fragmentation, as recommended by the RFC, such datagrams are dropped
+    if datagram[2] != 0 {
+        return Err(malformed("fragmented datagrams are not supported"));
+    }
+
+    let addr_type =
+        AddrType::from(datagram[3] as usize).ok_or_else(|| malformed("unknown address type"))?;
+
+    let (addr, rest) = match addr_type {
+        AddrType::V4 => split_checked(&datagram[4..], 4)?,
+        AddrType::V6 => split_checked(&datagram[4..], 16)?,
+        AddrType::Domain => {
+            let domain_length = *datagram
+                .get(4)
+                .ok_or_else(|| malformed("missing domain length"))?;
+            split_checked(&datagram[5..], domain_length as usize)?
+        }
+    };
+
+    let (port, data) = split_checked(rest, 2)?;
+    let port = u16::from_be_bytes([port[0], port[1]]);
+
+    let address = socks_utils::pretty_print_addr(&addr_type, addr);
+    let remote_address = match addr_type {
+        AddrType::V6 => format!("[{}]:{}", address, port),
+        _ => format!("{}:{}", address, port),
+    };
+
+    Ok((remote_address, data))
+}
+
+/// Prepends the SOCKS5 UDP header to the datagram received from the specified remote so that
+/// it could be sent back to the client.
+pub(crate) fn encode_udp_response(
+    remote_addr: &str,
+    data: &[u8],
+) -> Result<Vec<u8>, SocksProxyError> {
+    let remote: SocketAddr = remote_addr
+        .parse()
+        .map_err(|_| malformed("datagram originated from an invalid address"))?;
+
+    let mut encoded = vec![0, 0, 0];
+    match remote.ip() {
+        IpAddr::V4(ip) => {
+            encoded.push(AddrType::V4 as u8);
+            encoded.extend_from_slice(&ip.octets());
+        }
+        IpAddr::V6(ip) => {
+            encoded.push(AddrType::V6 as u8);
+            encoded.extend_from_slice(&ip.octets());
+        }
+    }
+    encoded.extend_from_slice(&remote.port().to_be_bytes());
+    encoded.extend_from_slice(data);
+
+    Ok(encoded)
+}
+
+fn split_checked(data: &[u8], at: usize) -> Result<(&[u8], &[u8]), SocksProxyError> {
+    if data.len() < at {
+        return Err(malformed("datagram too short to contain the destination"));
+    }
+    Ok(data.split_at(at))
+}
+
+fn malformed(reason: &'static str) -> SocksProxyError {
+    io::Error::new(io::ErrorKind::InvalidData, reason).into()
+}
+
+/// Local UDP relay created for each UDP ASSOCIATE request. It forwards any datagrams received
+/// from the client into the mix network and writes back any datagrams received from it.
+pub(crate) struct UdpRelay {
+    socket: UdpSocket,
+    client_ip: IpAddr,
+    client_address: Option<SocketAddr>,
+    connection_id: ConnectionId,
+    service_provider: Recipient,
+    self_address: Recipient,
+    input_sender: InputMessageSender,
+    mix_receiver: DatagramReceiver,
+}
+
+impl UdpRelay {
+    pub(crate) async fn new(
+        bind_ip: IpAddr,
+        client_ip: IpAddr,
+        connection_id: ConnectionId,
+        service_provider: Recipient,
+        self_address: Recipient,
+        input_sender: InputMessageSender,
+        mix_receiver: DatagramReceiver,
+    ) -> io::Result<Self> {
+        let socket = UdpSocket::bind(SocketAddr::new(bind_ip, 0)).await?;
+        Ok(UdpRelay {
+            socket,
+            client_ip,
+            client_address: None,
+            connection_id,
+            service_provider,
+            self_address,
+            input_sender,
+            mix_receiver,
+        })
+    }
+
+    pub(crate) fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    fn send_to_mixnet(&self, remote_address: RemoteAddress, data: Vec<u8>) {
+        let req =
+            Request::new_send_datagram(self.connection_id, remote_address, self.self_address, data);
+        let msg = Message::Request(req);
+
+        let input_message = InputMessage::new_fresh(self.service_provider, msg.into_bytes(), false);
+        self.input_sender.unbounded_send(input_message).unwrap();
+    }
+
+    fn handle_client_datagram(&mut self, datagram: &[u8], source: SocketAddr) {
+        // as per RFC1928, only accept datagrams coming from the client that requested the association
+        if source.ip() != self.client_ip {
+            warn!(
+                "Received a datagram from {} which is not the associated client - dropping it",
+                source
+            );
+            return;
+        }
+        self.client_address = Some(source);
+
+        match parse_udp_request(datagram) {
+            Ok((remote_address, data)) => {
+                trace!(
+                    "Forwarding {} bytes to {} (id: {})",
+                    data.len(),
+                    remote_address,
+                    self.connection_id
+                );
+                self.send_to_mixnet(remote_address, data.to_vec())
+            }
+            Err(err) => warn!("Dropping malformed client datagram - {}", err),
+        }
+    }
+
+    async fn handle_mix_datagram(&self, datagram: DatagramMessage) {
+        let client_address = match self.client_address {
+            Some(address) => address,
+            None => {
+                warn!("Received a datagram before the client has sent anything - dropping it");
+                return;
+            }
+        };
+
+        match encode_udp_response(&datagram.remote_addr, &datagram.payload) {
+            Ok(encoded) => {
+                if let Err(err) = self.socket.send_to(&encoded, client_address).await {
+                    warn!("Failed to send datagram to the client - {}", err)
+                }
+            }
+            Err(err) => warn!("Dropping malformed mix datagram - {}", err),
+        }
+    }
+
+    /// Runs the relay for as long as the TCP connection that requested the association
+    /// stays open.
+    pub(crate) async fn run<R>(mut self, control_stream: &mut R)
+    where
+        R: AsyncRead + Unpin,
+    {
+        let mut buf = vec![0u8; MAX_DATAGRAM_SIZE];
+        let mut control_buf = [0u8; 64];
+
+        loop {
+            tokio::select! {
+                read = control_stream.read(&mut control_buf) => {
+                    match read {
+                        // the association terminates when the TCP connection it arrived on terminates
+                        Ok(0) | Err(_) => break,
+                        Ok(_) => trace!("ignoring data received on the UDP association control stream"),
+                    }
+                }
+                received = self.socket.recv_from(&mut buf) => {
+                    match received {
+                        Ok((len, source)) => self.handle_client_datagram(&buf[..len], source),
+                        Err(err) => warn!("Failed to receive client datagram - {}", err),
+                    }
+                }
+                mix_datagram = self.mix_receiver.next() => {
+                    match mix_datagram {
+                        Some(datagram) => self.handle_mix_datagram(datagram).await,
+                        None => break,
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_ipv4_request() {
+        let datagram = [0, 0, 0, 1, 1, 1, 1, 1, 0, 53, 42, 42];
+        let (remote, data) = parse_udp_request(&datagram).unwrap();
+        assert_eq!("1.1.1.1:53", remote);
+        assert_eq!(&[42, 42], data);
+    }
+
+    #[test]
+    fn parsing_domain_request() {
+        let mut datagram = vec![0, 0, 0, 3, 7];
+        datagram.extend_from_slice(b"foo.com");
+        datagram.extend_from_slice(&[1, 187, 42]);
+        let (remote, data) = parse_udp_request(&datagram).unwrap();
+        assert_eq!("foo.com:443", remote);
+        assert_eq!(&[42], data);
+    }
+
+    #[test]
+    fn parsing_ipv6_request() {
+        let mut datagram = vec![0, 0, 0, 4];
+        datagram.extend_from_slice(&[0; 15]);
+        datagram.extend_from_slice(&[1, 0, 53]);
+        let (remote, data) = parse_udp_request(&datagram).unwrap();
+        assert_eq!("[0:0:0:0:0:0:0:1]:53", remote);
+        assert!(data.is_empty());
+        assert!(remote.parse::<SocketAddr>().is_ok());
+    }
+
+    #[test]
+    fn parsing_rejects_fragmented_and_truncated_requests() {
+        assert!(parse_udp_request(&[0, 0, 1, 1, 1, 1, 1, 1, 0, 53]).is_err());
+        assert!(parse_udp_request(&[0, 0, 0, 1, 1, 1]).is_err());
+        assert!(parse_udp_request(&[0, 0, 0, 1, 1, 1, 1, 1, 0]).is_err());
+        assert!(parse_udp_request(&[0, 0, 0, 3, 10, 102]).is_err());
+        assert!(parse_udp_request(&[0, 0, 0, 2, 1, 1, 1, 1, 0, 53]).is_err());
+    }
+
+    #[test]
+    fn encoded_response_can_be_parsed_back() {
+        let encoded = encode_udp_response("8.8.8.8:53", &[1, 2, 3]).unwrap();
+        assert_eq!(vec![0, 0, 0, 1, 8, 8, 8, 8, 0, 53, 1, 2, 3], encoded);
+
+        let (remote, data) = parse_udp_request(&encoded).unwrap();
+        assert_eq!("8.8.8.8:53", remote);
+        assert_eq!(&[1, 2, 3], data);
+
+        let encoded = encode_udp_response("[::1]:53", &[1, 2, 3]).unwrap();
+        let (remote, data) = parse_udp_request(&encoded).unwrap();
+        assert_eq!(
+            "[::1]:53".parse::<SocketAddr>().unwrap(),
+            remote.parse().unwrap()
+        );
+        assert_eq!(&[1, 2, 3], data);
+    }
+}
@@ -9,15 +9,19 @@ use nymsphinx::addressing::clients::Recipient;
 use nymsphinx::preparer::MessagePreparer;
 use rand::rngs::OsRng;
 use received_processor::ReceivedMessagesProcessor;
+use std::convert::TryFrom;
 use std::sync::Arc;
 use std::time::Duration;
-use topology::{gateway, nym_topology_from_bonds, NymTopology};
-use url::Url;
+use topology::provider::{StaticTopologyProvider, TopologyProvider};
+use topology::serialized::SerializableTopology;
+use topology::{gateway, NymTopology};
+use topology_provider::ValidatorApiTopologyProvider;
 use wasm_bindgen::prelude::*;
 use wasm_bindgen_futures::spawn_local;
 use wasm_utils::{console_log, console_warn};
 
 pub(crate) mod received_processor;
+mod topology_provider;
 
 const DEFAULT_AVERAGE_PACKET_DELAY: Duration = Duration::from_millis(200);
 const DEFAULT_AVERAGE_ACK_DELAY: Duration = Duration::from_millis(200);
@@ -25,7 +29,7 @@ const DEFAULT_GATEWAY_RESPONSE_TIMEOUT: Duration = Duration::from_millis(1_500);
 
 #[wasm_bindgen]
 pub struct NymClient {
-    validator_server: Url,
+    topology_provider: Box<dyn TopologyProvider>,
     disabled_credentials_mode: bool,
 
     // TODO: technically this doesn't need to be an Arc since wasm is run on a single thread
@@ -62,9 +66,11 @@ impl NymClient {
             identity: Arc::new(identity),
             encryption_keys: Arc::new(encryption_keys),
             ack_key: Arc::new(ack_key),
-            validator_server: validator_server
-                .parse()
-                .expect("malformed validator server url provided"),
+            topology_provider: Box::new(ValidatorApiTopologyProvider::new(
+                validator_server
+                    .parse()
+                    .expect("malformed validator server url provided"),
+            )),
             message_preparer: None,
             // received_keys: Default::default(),
             topology: None,
@@ -85,6 +91,18 @@ impl NymClient {
         self.on_gateway_connect = Some(on_connect)
     }
 
+    /// Makes the client use the provided topology, in the same format as topology files of
+    /// the native clients, instead of querying the validator API, e.g. to connect to a local mixnet.
+    pub fn set_topology(&mut self, topology: JsValue) -> Result<(), JsValue> {
+        let serialized: SerializableTopology = topology
+            .into_serde()
+            .map_err(|err| JsValue::from_str(&format!("malformed topology provided - {}", err)))?;
+        let topology = NymTopology::try_from(serialized)
+            .map_err(|err| JsValue::from_str(&format!("invalid topology provided - {}", err)))?;
+        self.topology_provider = Box::new(StaticTopologyProvider::new(topology));
+        Ok(())
+    }
+
     pub fn set_disabled_credentials_mode(&mut self, disabled_credentials_mode: bool) {
         console_log!(
             "Setting disabled credentials mode to {}",
@@ -112,34 +130,50 @@ impl NymClient {
     pub async fn initial_setup(self) -> Self {
         let disabled_credentials_mode = self.disabled_credentials_mode;
 
-        let bandwidth_controller = None;
-
         let mut client = self.get_and_update_topology().await;
         let gateway = client.choose_gateway();
 
         let (mixnet_messages_sender, mixnet_messages_receiver) = mpsc::unbounded();
         let (ack_sender, ack_receiver) = mpsc::unbounded();
 
-        let mut gateway_client = GatewayClient::new(
-            gateway.clients_address(),
-            Arc::clone(&client.identity),
-            gateway.identity_key,
-            gateway.owner.clone(),
-            None,
-            mixnet_messages_sender,
-            ack_sender,
-            DEFAULT_GATEWAY_RESPONSE_TIMEOUT,
-            bandwidth_controller,
-        );
+        // unlike the native clients, browsers only accept certificates trusted by the web PKI and
+        // not the ones issued for the gateway identity key, so fall back to the plain listener
+        let mut gateway_client = None;
+        let gateway_addresses = gateway
+            .clients_tls_address()
+            .into_iter()
+            .chain(std::iter::once(gateway.clients_address()));
+        for gateway_address in gateway_addresses {
+            let mut candidate_client = GatewayClient::new(
+                gateway_address.clone(),
+                Arc::clone(&client.identity),
+                gateway.identity_key,
+                gateway.owner.clone(),
+                None,
+                mixnet_messages_sender.clone(),
+                ack_sender.clone(),
+                DEFAULT_GATEWAY_RESPONSE_TIMEOUT,
+                None,
+            );
+
+            if disabled_credentials_mode {
+                candidate_client.set_disabled_credentials_mode(true)
+            }
 
-        if disabled_credentials_mode {
-            gateway_client.set_disabled_credentials_mode(true)
+            match candidate_client.authenticate_and_start().await {
+                Ok(_) => {
+                    gateway_client = Some(candidate_client);
+                    break;
+                }
+                Err(err) => console_warn!(
+                    "could not authenticate and start up the gateway connection at {} - {}",
+                    gateway_address,
+                    err
+                ),
+            }
         }
-
-        gateway_client
-            .authenticate_and_start()
-            .await
-            .expect("could not authenticate and start up the gateway connection");
+        let gateway_client =
+            gateway_client.expect("could not authenticate and start up the gateway connection");
 
         client.gateway_client = Some(gateway_client);
         match client.on_gateway_connect.as_ref() {
@@ -255,21 +289,10 @@ impl NymClient {
     //     })
     // }
 
-    pub(crate) async fn get_nym_topology(&self) -> NymTopology {
-        let validator_client = validator_client::ApiClient::new(self.validator_server.clone());
-
-        let mixnodes = match validator_client.get_cached_active_mixnodes().await {
-            Err(err) => panic!("{:?}", err),
-            Ok(mixes) => mixes,
-        };
-
-        let gateways = match validator_client.get_cached_gateways().await {
-            Err(err) => panic!("{}", err),
-            Ok(gateways) => gateways,
-        };
-
-        let topology = nym_topology_from_bonds(mixnodes, gateways);
-        let version = env!("CARGO_PKG_VERSION");
-        topology.filter_system_version(version)
+    pub(crate) async fn get_nym_topology(&mut self) -> NymTopology {
+        self.topology_provider
+            .get_new_topology()
+            .await
+            .expect("failed to obtain the network topology")
     }
 }
@@ -24,8 +24,10 @@ impl From<ReconstructedMessage> for ProcessedMessage {
     fn from(reconstructed: ReconstructedMessage) -> Self {
         ProcessedMessage {
             message: String::from_utf8_lossy(&reconstructed.message).into_owned(),
+            // TODO: expose all reply SURBs once the js side is able to make use of them
             reply_surb: reconstructed
-                .reply_surb
+                .reply_surbs
+                .first()
                 .map(|reply_surb| reply_surb.to_base58_string()),
         }
     }
@@ -0,0 +1,46 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use async_trait::async_trait;
+use topology::provider::TopologyProvider;
+use topology::{nym_topology_from_bonds, NymTopology};
+use url::Url;
+use wasm_utils::console_error;
+
+/// Provider obtaining the active network topology from the validator API.
+pub(crate) struct ValidatorApiTopologyProvider {
+    validator_client: validator_client::ApiClient,
+}
+
+impl ValidatorApiTopologyProvider {
+    pub(crate) fn new(validator_server: Url) -> Self {
+        ValidatorApiTopologyProvider {
+            validator_client: validator_client::ApiClient::new(validator_server),
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl TopologyProvider for ValidatorApiTopologyProvider {
+    async fn get_new_topology(&mut self) -> Option<NymTopology> {
+        let mixnodes = match self.validator_client.get_cached_active_mixnodes().await {
+            Err(err) => {
+                console_error!("failed to get network mixnodes - {}", err);
+                return None;
+            }
+            Ok(mixes) => mixes,
+        };
+
+        let gateways = match self.validator_client.get_cached_gateways().await {
+            Err(err) => {
+                console_error!("failed to get network gateways - {}", err);
+                return None;
+            }
+            Ok(gateways) => gateways,
+        };
+
+        let topology = nym_topology_from_bonds(mixnodes, gateways);
+        let version = env!("CARGO_PKG_VERSION");
+        Some(topology.filter_system_version(version))
+    }
+}
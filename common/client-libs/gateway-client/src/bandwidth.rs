@@ -12,7 +12,8 @@ use credential_storage::error::StorageError;
 use credential_storage::storage::Storage;
 #[cfg(feature = "coconut")]
 use credentials::coconut::{
-    bandwidth::prepare_for_spending, utils::obtain_aggregate_verification_key,
+    bandwidth::prepare_for_spending,
+    utils::{obtain_threshold_verification_key, signing_threshold, DEFAULT_SIGNER_TIMEOUT},
 };
 #[cfg(not(feature = "coconut"))]
 use credentials::token::bandwidth::TokenCredential;
@@ -151,11 +152,25 @@ where
         Ok(())
     }
 
+    /// Prepares the next unused credential for spending, returning it alongside its storage id.
     #[cfg(feature = "coconut")]
     pub async fn prepare_coconut_credential(
         &self,
-    ) -> Result<coconut_interface::Credential, GatewayClientError> {
-        let verification_key = obtain_aggregate_verification_key(&self.validator_endpoints).await?;
+    ) -> Result<(coconut_interface::Credential, i64), GatewayClientError> {
+        let verification_key = obtain_threshold_verification_key(
+            &self.validator_endpoints,
+            signing_threshold(self.validator_endpoints.len()),
+            DEFAULT_SIGNER_TIMEOUT,
+        )
+        .await?;
+        for failure in verification_key.failures {
+            log::warn!(
+                "Failed to obtain the verification key of {} - {}",
+                failure.url,
+                failure.error
+            );
+        }
+        let verification_key = verification_key.value;
         let bandwidth_credential = self.storage.get_next_coconut_credential().await?;
         let voucher_value = u64::from_str(&bandwidth_credential.voucher_value)
             .map_err(|_| StorageError::InconsistentData)?;
@@ -168,14 +183,23 @@ where
             coconut_interface::Signature::try_from_bs58(bandwidth_credential.signature)?;
 
         // the below would only be executed once we know where we want to spend it (i.e. which gateway and stuff)
-        Ok(prepare_for_spending(
+        let credential = prepare_for_spending(
             voucher_value,
             voucher_info,
             serial_number,
             binding_number,
             &signature,
             &verification_key,
-        )?)
+        )?;
+
+        Ok((credential, bandwidth_credential.id))
+    }
+
+    #[cfg(feature = "coconut")]
+    pub async fn consume_coconut_credential(&self, id: i64) -> Result<(), GatewayClientError> {
+        self.storage.consume_coconut_credential(id).await?;
+
+        Ok(())
     }
 
     #[cfg(not(feature = "coconut"))]
@@ -194,9 +194,13 @@ impl GatewayClient {
 
     #[cfg(not(target_arch = "wasm32"))]
     pub async fn establish_connection(&mut self) -> Result<(), GatewayClientError> {
-        let ws_stream = match connect_async(&self.gateway_address).await {
-            Ok((ws_stream, _)) => ws_stream,
-            Err(e) => return Err(GatewayClientError::NetworkError(e)),
+        let ws_stream = if self.gateway_address.starts_with("wss://") {
+            crate::tls::connect_tls(&self.gateway_address, self.gateway_identity).await?
+        } else {
+            match connect_async(&self.gateway_address).await {
+                Ok((ws_stream, _)) => ws_stream,
+                Err(e) => return Err(GatewayClientError::NetworkError(e)),
+            }
         };
 
         self.connection = SocketState::Available(Box::new(ws_stream));
@@ -487,6 +491,7 @@ impl GatewayClient {
     async fn claim_coconut_bandwidth(
         &mut self,
         credential: Credential,
+        credential_id: i64,
     ) -> Result<(), GatewayClientError> {
         let mut rng = OsRng;
         let iv = IV::new_random(&mut rng);
@@ -502,6 +507,14 @@ impl GatewayClient {
             ServerResponse::Error { message } => Err(GatewayClientError::GatewayError(message)),
             _ => Err(GatewayClientError::UnexpectedResponse),
         }?;
+
+        // if the claim has failed, the credential is kept around so that the release of its funds
+        // could be resumed by presenting it to the same gateway again
+        self.bandwidth_controller
+            .as_ref()
+            .unwrap()
+            .consume_coconut_credential(credential_id)
+            .await?;
         Ok(())
     }
 
@@ -560,7 +573,7 @@ impl GatewayClient {
         let _gateway_owner = self.gateway_owner.clone();
 
         #[cfg(feature = "coconut")]
-        let credential = self
+        let (credential, credential_id) = self
             .bandwidth_controller
             .as_ref()
             .unwrap()
@@ -575,7 +588,9 @@ impl GatewayClient {
             .await?;
 
         #[cfg(feature = "coconut")]
-        return self.claim_coconut_bandwidth(credential).await;
+        return self
+            .claim_coconut_bandwidth(credential, credential_id)
+            .await;
         #[cfg(not(feature = "coconut"))]
         return self.claim_token_bandwidth(credential).await;
     }
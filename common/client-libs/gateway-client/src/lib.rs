@@ -13,6 +13,8 @@ pub mod client;
 pub mod error;
 pub mod packet_router;
 pub mod socket_state;
+#[cfg(not(target_arch = "wasm32"))]
+mod tls;
 #[cfg(feature = "wasm")]
 mod wasm_storage;
 
@@ -0,0 +1,214 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::GatewayClientError;
+use crypto::asymmetric::identity;
+use rustls::{
+    Certificate, ClientConfig, RootCertStore, ServerCertVerified, ServerCertVerifier, TLSError,
+    WebPKIVerifier,
+};
+use std::sync::Arc;
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_tungstenite::{client_async, MaybeTlsStream, WebSocketStream};
+use url::Url;
+use webpki::DNSNameRef;
+
+/// Object identifier of the Ed25519 algorithm as it appears in the `SubjectPublicKeyInfo`
+/// of a certificate.
+const ED25519_OID: &str = "1.3.101.112";
+
+/// Server name used for the handshake if the gateway is addressed directly by its IP,
+/// as webpki only accepts dns names. Certificates pinned to the gateway identity do not
+/// depend on it, while any other certificate would be rejected regardless.
+const IP_HOST_SERVER_NAME: &str = "gateway.nym.invalid";
+
+const DEFAULT_WSS_PORT: u16 = 443;
+
+/// Checks whether the provided certificate was issued for the gateway identity key,
+/// i.e. whether its subject public key is the Ed25519 identity of the gateway.
+fn is_identity_certificate(
+    certificate: &Certificate,
+    gateway_identity: &identity::PublicKey,
+) -> bool {
+    match x509_parser::parse_x509_certificate(&certificate.0) {
+        Ok((_, certificate)) => {
+            let public_key_info = &certificate.tbs_certificate.subject_pki;
+            public_key_info.algorithm.algorithm.to_id_string() == ED25519_OID
+                && public_key_info.subject_public_key.data == &gateway_identity.to_bytes()[..]
+        }
+        Err(_) => false,
+    }
+}
+
+/// Certificate verifier pinning the certificate to the identity of the gateway we are connecting to.
+/// If the gateway presents a certificate for a different key, e.g. one issued by a public
+/// certificate authority, it is verified against the standard web PKI instead.
+struct GatewayCertificateVerifier {
+    gateway_identity: identity::PublicKey,
+    webpki_verifier: WebPKIVerifier,
+}
+
+impl ServerCertVerifier for GatewayCertificateVerifier {
+    fn verify_server_cert(
+        &self,
+        roots: &RootCertStore,
+        presented_certs: &[Certificate],
+        dns_name: DNSNameRef<'_>,
+        ocsp_response: &[u8],
+    ) -> Result<ServerCertVerified, TLSError> {
+        let end_entity = presented_certs
+            .first()
+            .ok_or(TLSError::NoCertificatesPresented)?;
+
+        // possession of the corresponding private key is proven by the handshake signature itself
+        if is_identity_certificate(end_entity, &self.gateway_identity) {
+            return Ok(ServerCertVerified::assertion());
+        }
+
+        self.webpki_verifier
+            .verify_server_cert(roots, presented_certs, dns_name, ocsp_response)
+    }
+}
+
+fn client_config(gateway_identity: identity::PublicKey) -> ClientConfig {
+    let mut config = ClientConfig::new();
+    config
+        .root_store
+        .add_server_trust_anchors(&webpki_roots::TLS_SERVER_ROOTS);
+    config
+        .dangerous()
+        .set_certificate_verifier(Arc::new(GatewayCertificateVerifier {
+            gateway_identity,
+            webpki_verifier: WebPKIVerifier::new(),
+        }));
+    config
+}
+
+/// Establishes a websocket connection secured with TLS (i.e. `wss://`) with the gateway.
+///
+/// # Arguments
+///
+/// * `gateway_address`: `wss://` address of the gateway.
+/// * `gateway_identity`: identity of the gateway its certificate might be pinned to.
+pub(crate) async fn connect_tls(
+    gateway_address: &str,
+    gateway_identity: identity::PublicKey,
+) -> Result<WebSocketStream<MaybeTlsStream<TcpStream>>, GatewayClientError> {
+    let url = Url::parse(gateway_address)
+        .map_err(|err| GatewayClientError::InvalidURL(err.to_string()))?;
+    let host = url.host_str().ok_or_else(|| {
+        GatewayClientError::InvalidURL(format!("{} does not specify a host", gateway_address))
+    })?;
+    let port = url.port().unwrap_or(DEFAULT_WSS_PORT);
+
+    let server_name = DNSNameRef::try_from_ascii_str(host)
+        .or_else(|_| DNSNameRef::try_from_ascii_str(IP_HOST_SERVER_NAME))
+        .expect("the fallback server name is a valid dns name");
+
+    let tcp_stream = TcpStream::connect(format!("{}:{}", host, port))
+        .await
+        .map_err(|err| GatewayClientError::NetworkError(err.into()))?;
+
+    let tls_stream = TlsConnector::from(Arc::new(client_config(gateway_identity)))
+        .connect(server_name, tcp_stream)
+        .await
+        .map_err(|err| GatewayClientError::NetworkError(err.into()))?;
+
+    let (ws_stream, _) = client_async(gateway_address, MaybeTlsStream::Rustls(tls_stream)).await?;
+    Ok(ws_stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::OsRng;
+    use tokio::net::TcpListener;
+    use tokio_rustls::rustls::{NoClientAuth, PrivateKey, ServerConfig};
+    use tokio_rustls::TlsAcceptor;
+
+    // PKCS#8 v1 encoding of an Ed25519 private key is this prefix followed by the 32 byte seed
+    const ED25519_PKCS8_PREFIX: [u8; 16] = [
+        0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04,
+        0x20,
+    ];
+
+    fn self_signed_identity_certificate(identity: &identity::KeyPair) -> (Certificate, PrivateKey) {
+        let mut pkcs8 = ED25519_PKCS8_PREFIX.to_vec();
+        pkcs8.extend_from_slice(&identity.private_key().to_bytes());
+
+        let mut params = rcgen::CertificateParams::new(vec!["localhost".to_string()]);
+        params.alg = &rcgen::PKCS_ED25519;
+        params.key_pair = Some(rcgen::KeyPair::from_der(&pkcs8).unwrap());
+        let certificate = rcgen::Certificate::from_params(params).unwrap();
+
+        (
+            Certificate(certificate.serialize_der().unwrap()),
+            PrivateKey(certificate.serialize_private_key_der()),
+        )
+    }
+
+    // starts a local wss server accepting a single connection and returns its address
+    async fn start_wss_server(certificate: Certificate, private_key: PrivateKey) -> String {
+        let mut config = ServerConfig::new(NoClientAuth::new());
+        config
+            .set_single_cert(vec![certificate], private_key)
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("wss://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            if let Ok(tls_stream) = acceptor.accept(socket).await {
+                let _ = tokio_tungstenite::accept_async(tls_stream).await;
+            }
+        });
+
+        address
+    }
+
+    #[test]
+    fn only_accepts_certificates_for_the_gateway_identity() {
+        let gateway_identity = identity::KeyPair::new(&mut OsRng);
+        let other_identity = identity::KeyPair::new(&mut OsRng);
+        let (certificate, _) = self_signed_identity_certificate(&gateway_identity);
+
+        assert!(is_identity_certificate(
+            &certificate,
+            gateway_identity.public_key()
+        ));
+        assert!(!is_identity_certificate(
+            &certificate,
+            other_identity.public_key()
+        ));
+        assert!(!is_identity_certificate(
+            &Certificate(vec![1, 2, 3]),
+            gateway_identity.public_key()
+        ));
+    }
+
+    #[tokio::test]
+    async fn connects_to_gateway_with_pinned_self_signed_certificate() {
+        let gateway_identity = identity::KeyPair::new(&mut OsRng);
+        let (certificate, private_key) = self_signed_identity_certificate(&gateway_identity);
+        let address = start_wss_server(certificate, private_key).await;
+
+        assert!(connect_tls(&address, *gateway_identity.public_key())
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn rejects_self_signed_certificate_of_different_identity() {
+        let gateway_identity = identity::KeyPair::new(&mut OsRng);
+        let impostor_identity = identity::KeyPair::new(&mut OsRng);
+        let (certificate, private_key) = self_signed_identity_certificate(&impostor_identity);
+        let address = start_wss_server(certificate, private_key).await;
+
+        assert!(connect_tls(&address, *gateway_identity.public_key())
+            .await
+            .is_err());
+    }
+}
@@ -24,6 +24,7 @@ pub struct CoconutCredential {
     pub serial_number: String,
     pub binding_number: String,
     pub signature: String,
+    pub consumed: bool,
 }
 
 pub struct ERC20Credential {
@@ -46,8 +47,14 @@ pub trait Storage: Send + Sync {
 
     async fn get_next_coconut_credential(&self) -> Result<CoconutCredential, StorageError>;
 
+    async fn get_all_coconut_credentials(&self) -> Result<Vec<CoconutCredential>, StorageError>;
+
+    async fn consume_coconut_credential(&self, id: i64) -> Result<(), StorageError>;
+
     async fn remove_coconut_credential(&self, id: i64) -> Result<(), StorageError>;
 
+    async fn remove_consumed_coconut_credentials(&self) -> Result<u64, StorageError>;
+
     async fn insert_erc20_credential(
         &self,
         public_key: String,
@@ -76,10 +83,22 @@ impl Storage for PersistentStorage {
         Err(StorageError::WasmNotSupported)
     }
 
+    async fn get_all_coconut_credentials(&self) -> Result<Vec<CoconutCredential>, StorageError> {
+        Err(StorageError::WasmNotSupported)
+    }
+
+    async fn consume_coconut_credential(&self, _id: i64) -> Result<(), StorageError> {
+        Err(StorageError::WasmNotSupported)
+    }
+
     async fn remove_coconut_credential(&self, _id: i64) -> Result<(), StorageError> {
         Err(StorageError::WasmNotSupported)
     }
 
+    async fn remove_consumed_coconut_credentials(&self) -> Result<u64, StorageError> {
+        Err(StorageError::WasmNotSupported)
+    }
+
     async fn insert_erc20_credential(
         &self,
         _public_key: String,
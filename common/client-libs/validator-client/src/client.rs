@@ -3,8 +3,9 @@
 
 use crate::{validator_api, ValidatorClientError};
 use coconut_interface::{
-    BlindSignRequestBody, BlindedSignatureResponse, ExecuteReleaseFundsRequestBody,
-    ProposeReleaseFundsRequestBody, ProposeReleaseFundsResponse, VerificationKeyResponse,
+    BlindSignRequestBody, BlindedSignatureResponse, DkgDealingsResponse, DkgPublicKeyResponse,
+    ExecuteReleaseFundsRequestBody, ProposeReleaseFundsRequestBody, ProposeReleaseFundsResponse,
+    SpentCredentialRequestBody, SpentCredentialResponse, VerificationKeyResponse,
     VerifyCredentialBody, VerifyCredentialResponse,
 };
 use mixnet_contract_common::{GatewayBond, IdentityKeyRef, MixNodeBond};
@@ -15,7 +16,9 @@ use validator_api_requests::models::{
     StakeSaturationResponse,
 };
 #[cfg(feature = "nymd-client")]
-use validator_api_requests::models::{MixNodeBondAnnotated, UptimeResponse};
+use validator_api_requests::models::{
+    GatewayStatusReportResponse, MixNodeBondAnnotated, UptimeResponse,
+};
 
 #[cfg(feature = "nymd-client")]
 use network_defaults::DEFAULT_NETWORK;
@@ -609,6 +612,13 @@ impl<C> Client<C> {
         Ok(self.validator_api.get_mixnode_avg_uptimes().await?)
     }
 
+    pub async fn get_gateway_report(
+        &self,
+        identity: IdentityKeyRef<'_>,
+    ) -> Result<GatewayStatusReportResponse, ValidatorClientError> {
+        Ok(self.validator_api.get_gateway_report(identity).await?)
+    }
+
     pub async fn blind_sign(
         &self,
         request_body: &BlindSignRequestBody,
@@ -734,6 +744,14 @@ impl ApiClient {
         Ok(self.validator_api.get_coconut_verification_key().await?)
     }
 
+    pub async fn get_dkg_public_key(&self) -> Result<DkgPublicKeyResponse, ValidatorClientError> {
+        Ok(self.validator_api.get_dkg_public_key().await?)
+    }
+
+    pub async fn get_dkg_dealings(&self) -> Result<DkgDealingsResponse, ValidatorClientError> {
+        Ok(self.validator_api.get_dkg_dealings().await?)
+    }
+
     pub async fn verify_bandwidth_credential(
         &self,
         request_body: &VerifyCredentialBody,
@@ -763,4 +781,14 @@ impl ApiClient {
             .execute_release_funds(request_body)
             .await?)
     }
+
+    pub async fn mark_credential_spent(
+        &self,
+        request_body: &SpentCredentialRequestBody,
+    ) -> Result<SpentCredentialResponse, ValidatorClientError> {
+        Ok(self
+            .validator_api
+            .mark_credential_spent(request_body)
+            .await?)
+    }
 }
@@ -18,11 +18,11 @@ use execute::execute;
 pub use fee::gas_price::GasPrice;
 use mixnet_contract_common::mixnode::DelegationEvent;
 use mixnet_contract_common::{
-    ContractStateParams, Delegation, ExecuteMsg, Gateway, GatewayBond, GatewayOwnershipResponse,
-    IdentityKey, Interval, LayerDistribution, MixNode, MixNodeBond, MixOwnershipResponse,
-    MixnetContractVersion, MixnodeRewardingStatusResponse, PagedDelegatorDelegationsResponse,
-    PagedGatewayResponse, PagedMixDelegationsResponse, PagedMixnodeResponse,
-    PagedRewardedSetResponse, QueryMsg, RewardedSetUpdateDetails,
+    ContractStateParams, Delegation, ExecuteMsg, Gateway, GatewayBond, GatewayConfigUpdate,
+    GatewayOwnershipResponse, IdentityKey, Interval, LayerDistribution, MixNode, MixNodeBond,
+    MixOwnershipResponse, MixnetContractVersion, MixnodeRewardingStatusResponse,
+    PagedDelegatorDelegationsResponse, PagedGatewayResponse, PagedMixDelegationsResponse,
+    PagedMixnodeResponse, PagedRewardedSetResponse, QueryMsg, RewardedSetUpdateDetails, SphinxKey,
 };
 use network_defaults::DEFAULT_NETWORK;
 use serde::Serialize;
@@ -580,6 +580,20 @@ impl<C> NymdClient<C> {
         Ok(response.mixnode)
     }
 
+    /// Gets the bond of the mixnode with the provided identity key, regardless of who owns it
+    pub async fn get_mixnode_bond(
+        &self,
+        mix_identity: IdentityKey,
+    ) -> Result<Option<MixNodeBond>, NymdError>
+    where
+        C: CosmWasmClient + Sync,
+    {
+        let request = QueryMsg::GetMixnodeBond { mix_identity };
+        self.client
+            .query_contract_smart(self.mixnet_contract_address(), &request)
+            .await
+    }
+
     /// Checks whether there is a bonded gateway associated with the provided client's address
     pub async fn owns_gateway(&self, address: &AccountId) -> Result<Option<GatewayBond>, NymdError>
     where
@@ -1065,6 +1079,58 @@ impl<C> NymdClient<C> {
             .await
     }
 
+    /// Increase the pledge of the owned mixnode by the provided amount.
+    /// The change is only applied once the current epoch is over.
+    pub async fn pledge_more(
+        &self,
+        additional_pledge: Coin,
+        fee: Option<Fee>,
+    ) -> Result<ExecuteResult, NymdError>
+    where
+        C: SigningCosmWasmClient + Sync,
+    {
+        let fee = fee.unwrap_or(Fee::Auto(Some(self.simulated_gas_multiplier)));
+
+        let req = ExecuteMsg::PledgeMore {};
+        self.client
+            .execute(
+                self.address(),
+                self.mixnet_contract_address(),
+                &req,
+                fee,
+                "Pledging more from rust!",
+                vec![additional_pledge],
+            )
+            .await
+    }
+
+    /// Decrease the pledge of the owned mixnode by the provided amount.
+    /// The change is only applied, and the tokens returned, once the current epoch is over.
+    pub async fn decrease_pledge(
+        &self,
+        decrease_by: Coin,
+        fee: Option<Fee>,
+    ) -> Result<ExecuteResult, NymdError>
+    where
+        C: SigningCosmWasmClient + Sync,
+    {
+        let fee = fee.unwrap_or(Fee::Auto(Some(self.simulated_gas_multiplier)));
+
+        let req = ExecuteMsg::DecreasePledge {
+            decrease_by: decrease_by.into(),
+        };
+        self.client
+            .execute(
+                self.address(),
+                self.mixnet_contract_address(),
+                &req,
+                fee,
+                "Decreasing pledge from rust!",
+                vec![],
+            )
+            .await
+    }
+
     /// Unbond a mixnode on behalf of the owner, removing it from the network and reclaiming staked coins
     pub async fn unbond_mixnode_on_behalf(
         &self,
@@ -1115,6 +1181,61 @@ impl<C> NymdClient<C> {
             .await
     }
 
+    /// Replaces the sphinx key of the specified mixnode. The update is authorised by the signature
+    /// made with the mixnode's identity key rather than by the sender of the transaction.
+    pub async fn update_mixnode_sphinx_key(
+        &self,
+        mix_identity: IdentityKey,
+        sphinx_key: SphinxKey,
+        signature: String,
+        fee: Option<Fee>,
+    ) -> Result<ExecuteResult, NymdError>
+    where
+        C: SigningCosmWasmClient + Sync,
+    {
+        let fee = fee.unwrap_or(Fee::Auto(Some(self.simulated_gas_multiplier)));
+
+        let req = ExecuteMsg::UpdateMixnodeSphinxKey {
+            mix_identity,
+            sphinx_key,
+            signature,
+        };
+        self.client
+            .execute(
+                self.address(),
+                self.mixnet_contract_address(),
+                &req,
+                fee,
+                "Updating mixnode sphinx key from rust!",
+                vec![],
+            )
+            .await
+    }
+
+    /// Update the configuration of a gateway without having to rebond it.
+    pub async fn update_gateway_config(
+        &self,
+        new_config: GatewayConfigUpdate,
+        fee: Option<Fee>,
+    ) -> Result<ExecuteResult, NymdError>
+    where
+        C: SigningCosmWasmClient + Sync,
+    {
+        let fee = fee.unwrap_or(Fee::Auto(Some(self.simulated_gas_multiplier)));
+
+        let req = ExecuteMsg::UpdateGatewayConfig { new_config };
+        self.client
+            .execute(
+                self.address(),
+                self.mixnet_contract_address(),
+                &req,
+                fee,
+                "Updating gateway configuration from rust!",
+                vec![],
+            )
+            .await
+    }
+
     /// Delegates specified amount of stake to particular mixnode.
     pub async fn delegate_to_mixnode(
         &self,
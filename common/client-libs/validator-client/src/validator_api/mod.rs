@@ -4,8 +4,9 @@
 use crate::validator_api::error::ValidatorAPIError;
 use crate::validator_api::routes::{CORE_STATUS_COUNT, SINCE_ARG};
 use coconut_interface::{
-    BlindSignRequestBody, BlindedSignatureResponse, ExecuteReleaseFundsRequestBody,
-    ProposeReleaseFundsRequestBody, ProposeReleaseFundsResponse, VerificationKeyResponse,
+    BlindSignRequestBody, BlindedSignatureResponse, DkgDealingsResponse, DkgPublicKeyResponse,
+    ExecuteReleaseFundsRequestBody, ProposeReleaseFundsRequestBody, ProposeReleaseFundsResponse,
+    SpentCredentialRequestBody, SpentCredentialResponse, VerificationKeyResponse,
     VerifyCredentialBody, VerifyCredentialResponse,
 };
 use mixnet_contract_common::{GatewayBond, IdentityKeyRef, MixNodeBond};
@@ -13,8 +14,9 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use url::Url;
 use validator_api_requests::models::{
-    CoreNodeStatusResponse, InclusionProbabilityResponse, MixNodeBondAnnotated,
-    MixnodeStatusResponse, RewardEstimationResponse, StakeSaturationResponse, UptimeResponse,
+    CoreNodeStatusResponse, GatewayStatusReportResponse, InclusionProbabilityResponse,
+    MixNodeBondAnnotated, MixnodeStatusResponse, RewardEstimationResponse, StakeSaturationResponse,
+    UptimeResponse,
 };
 
 pub mod error;
@@ -229,6 +231,23 @@ impl Client {
         }
     }
 
+    pub async fn get_gateway_report(
+        &self,
+        identity: IdentityKeyRef<'_>,
+    ) -> Result<GatewayStatusReportResponse, ValidatorAPIError> {
+        self.query_validator_api(
+            &[
+                routes::API_VERSION,
+                routes::STATUS_ROUTES,
+                routes::GATEWAY,
+                identity,
+                routes::REPORT,
+            ],
+            NO_PARAMS,
+        )
+        .await
+    }
+
     pub async fn get_mixnode_status(
         &self,
         identity: IdentityKeyRef<'_>,
@@ -376,6 +395,32 @@ impl Client {
         .await
     }
 
+    pub async fn get_dkg_public_key(&self) -> Result<DkgPublicKeyResponse, ValidatorAPIError> {
+        self.query_validator_api(
+            &[
+                routes::API_VERSION,
+                routes::COCONUT_ROUTES,
+                routes::COCONUT_DKG,
+                routes::DKG_PUBLIC_KEY,
+            ],
+            NO_PARAMS,
+        )
+        .await
+    }
+
+    pub async fn get_dkg_dealings(&self) -> Result<DkgDealingsResponse, ValidatorAPIError> {
+        self.query_validator_api(
+            &[
+                routes::API_VERSION,
+                routes::COCONUT_ROUTES,
+                routes::COCONUT_DKG,
+                routes::DKG_DEALINGS,
+            ],
+            NO_PARAMS,
+        )
+        .await
+    }
+
     pub async fn verify_bandwidth_credential(
         &self,
         request_body: &VerifyCredentialBody,
@@ -426,6 +471,23 @@ impl Client {
         )
         .await
     }
+
+    pub async fn mark_credential_spent(
+        &self,
+        request_body: &SpentCredentialRequestBody,
+    ) -> Result<SpentCredentialResponse, ValidatorAPIError> {
+        self.post_validator_api(
+            &[
+                routes::API_VERSION,
+                routes::COCONUT_ROUTES,
+                routes::BANDWIDTH,
+                routes::COCONUT_SPENT_CREDENTIAL,
+            ],
+            NO_PARAMS,
+            request_body,
+        )
+        .await
+    }
 }
 
 // utility function that should solve the double slash problem in validator API forever.
@@ -20,6 +20,11 @@ pub const COCONUT_VERIFICATION_KEY: &str = "verification-key";
 pub const COCONUT_VERIFY_BANDWIDTH_CREDENTIAL: &str = "verify-bandwidth-credential";
 pub const COCONUT_PROPOSE_RELEASE_FUNDS: &str = "propose-release-funds";
 pub const COCONUT_EXECUTE_RELEASE_FUNDS: &str = "execute-release-funds";
+pub const COCONUT_SPENT_CREDENTIAL: &str = "spent-credential";
+
+pub const COCONUT_DKG: &str = "dkg";
+pub const DKG_PUBLIC_KEY: &str = "public-key";
+pub const DKG_DEALINGS: &str = "dealings";
 
 pub const STATUS_ROUTES: &str = "status";
 pub const MIXNODE: &str = "mixnode";
@@ -29,6 +34,7 @@ pub const CORE_STATUS_COUNT: &str = "core-status-count";
 pub const SINCE_ARG: &str = "since";
 
 pub const STATUS: &str = "status";
+pub const REPORT: &str = "report";
 pub const REWARD_ESTIMATION: &str = "reward-estimation";
 pub const AVG_UPTIME: &str = "avg_uptime";
 pub const STAKE_SATURATION: &str = "stake-saturation";
@@ -257,6 +257,30 @@ impl VerificationKeyResponse {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DkgPublicKeyResponse {
+    /// Base58-encoded BTE public key (with the proof of possession) of the dealer.
+    pub public_key: String,
+}
+
+impl DkgPublicKeyResponse {
+    pub fn new(public_key: String) -> Self {
+        DkgPublicKeyResponse { public_key }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DkgDealingsResponse {
+    /// Base58-encoded dealings of the dealer, one for each secret of the coconut key.
+    pub dealings: Vec<String>,
+}
+
+impl DkgDealingsResponse {
+    pub fn new(dealings: Vec<String>) -> Self {
+        DkgDealingsResponse { dealings }
+    }
+}
+
 #[derive(Serialize, Deserialize, Getters, CopyGetters)]
 pub struct ProposeReleaseFundsRequestBody {
     #[getset(get = "pub")]
@@ -292,6 +316,29 @@ impl ExecuteReleaseFundsRequestBody {
     }
 }
 
+#[derive(Serialize, Deserialize, Getters, CopyGetters)]
+pub struct SpentCredentialRequestBody {
+    #[getset(get = "pub")]
+    credential: Credential,
+}
+
+impl SpentCredentialRequestBody {
+    pub fn new(credential: Credential) -> Self {
+        SpentCredentialRequestBody { credential }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SpentCredentialResponse {
+    pub already_spent: bool,
+}
+
+impl SpentCredentialResponse {
+    pub fn new(already_spent: bool) -> Self {
+        SpentCredentialResponse { already_spent }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,7 +1,7 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
-use crate::mixnode::NodeRewardResult;
-use crate::{ContractStateParams, IdentityKeyRef, Interval, Layer};
+use crate::mixnode::{NodeRewardResult, PledgeChange};
+use crate::{ContractStateParams, GatewayConfigUpdate, IdentityKeyRef, Interval, Layer};
 use cosmwasm_std::{Addr, Coin, Event, Uint128};
 
 pub use contracts_common::events::*;
@@ -14,8 +14,12 @@ pub const UNDELEGATION_EVENT_TYPE: &str = "undelegation";
 pub const PENDING_UNDELEGATION_EVENT_TYPE: &str = "pending_undelegation";
 pub const GATEWAY_BONDING_EVENT_TYPE: &str = "gateway_bonding";
 pub const GATEWAY_UNBONDING_EVENT_TYPE: &str = "gateway_unbonding";
+pub const GATEWAY_CONFIG_UPDATE_EVENT_TYPE: &str = "gateway_config_update";
 pub const MIXNODE_BONDING_EVENT_TYPE: &str = "mixnode_bonding";
 pub const MIXNODE_UNBONDING_EVENT_TYPE: &str = "mixnode_unbonding";
+pub const MIXNODE_SPHINX_KEY_UPDATE_EVENT_TYPE: &str = "mixnode_sphinx_key_update";
+pub const PENDING_PLEDGE_CHANGE_EVENT_TYPE: &str = "pending_pledge_change";
+pub const PLEDGE_CHANGE_EVENT_TYPE: &str = "pledge_change";
 pub const SETTINGS_UPDATE_EVENT_TYPE: &str = "settings_update";
 pub const OPERATOR_REWARDING_EVENT_TYPE: &str = "mix_rewarding";
 pub const MIX_DELEGATORS_REWARDING_EVENT_TYPE: &str = "mix_delegators_rewarding";
@@ -44,6 +48,21 @@ pub const DELEGATION_HEIGHT_KEY: &str = "delegation_latest_block_height";
 pub const NODE_IDENTITY_KEY: &str = "identity";
 pub const ASSIGNED_LAYER_KEY: &str = "assigned_layer";
 
+// gateway config update
+pub const UPDATED_GATEWAY_HOST_KEY: &str = "updated_gateway_host";
+pub const UPDATED_GATEWAY_CLIENTS_PORT_KEY: &str = "updated_gateway_clients_port";
+pub const UPDATED_GATEWAY_CLIENTS_WSS_PORT_KEY: &str = "updated_gateway_clients_wss_port";
+pub const UPDATED_GATEWAY_LOCATION_KEY: &str = "updated_gateway_location";
+pub const UPDATED_GATEWAY_VERSION_KEY: &str = "updated_gateway_version";
+
+// pledge change
+pub const PLEDGE_CHANGE_KIND_KEY: &str = "pledge_change_kind";
+pub const NEW_PLEDGE_KEY: &str = "new_pledge";
+
+// sphinx key update
+pub const OLD_SPHINX_KEY_KEY: &str = "old_sphinx_key";
+pub const NEW_SPHINX_KEY_KEY: &str = "new_sphinx_key";
+
 // settings change
 pub const OLD_MINIMUM_MIXNODE_PLEDGE_KEY: &str = "old_minimum_mixnode_pledge";
 pub const OLD_MINIMUM_GATEWAY_PLEDGE_KEY: &str = "old_minimum_gateway_pledge";
@@ -268,6 +287,37 @@ pub fn new_gateway_unbonding_event(
     event.add_attribute(AMOUNT_KEY, amount.to_string())
 }
 
+pub fn new_gateway_config_update_event(
+    owner: &Addr,
+    proxy: &Option<Addr>,
+    identity: IdentityKeyRef<'_>,
+    update: &GatewayConfigUpdate,
+) -> Event {
+    let mut event = Event::new(GATEWAY_CONFIG_UPDATE_EVENT_TYPE)
+        .add_attribute(OWNER_KEY, owner)
+        .add_attribute(NODE_IDENTITY_KEY, identity);
+
+    if let Some(proxy) = proxy {
+        event = event.add_attribute(PROXY_KEY, proxy)
+    }
+
+    if let Some(clients_wss_port) = update.clients_wss_port {
+        event = event.add_attribute(
+            UPDATED_GATEWAY_CLIENTS_WSS_PORT_KEY,
+            clients_wss_port.to_string(),
+        )
+    }
+
+    event
+        .add_attribute(UPDATED_GATEWAY_HOST_KEY, &update.host)
+        .add_attribute(
+            UPDATED_GATEWAY_CLIENTS_PORT_KEY,
+            update.clients_port.to_string(),
+        )
+        .add_attribute(UPDATED_GATEWAY_LOCATION_KEY, &update.location)
+        .add_attribute(UPDATED_GATEWAY_VERSION_KEY, &update.version)
+}
+
 pub fn new_mixnode_bonding_event(
     owner: &Addr,
     proxy: &Option<Addr>,
@@ -307,6 +357,59 @@ pub fn new_mixnode_unbonding_event(
     event.add_attribute(AMOUNT_KEY, amount.to_string())
 }
 
+pub fn new_pending_pledge_change_event(
+    owner: &Addr,
+    proxy: &Option<Addr>,
+    identity: IdentityKeyRef<'_>,
+    change: &PledgeChange,
+) -> Event {
+    let mut event = Event::new(PENDING_PLEDGE_CHANGE_EVENT_TYPE)
+        .add_attribute(OWNER_KEY, owner)
+        .add_attribute(NODE_IDENTITY_KEY, identity);
+
+    if let Some(proxy) = proxy {
+        event = event.add_attribute(PROXY_KEY, proxy)
+    }
+
+    // coin implements Display trait and we use that implementation here
+    event
+        .add_attribute(PLEDGE_CHANGE_KIND_KEY, change.kind())
+        .add_attribute(AMOUNT_KEY, change.amount().to_string())
+}
+
+pub fn new_pledge_change_event(
+    owner: &Addr,
+    proxy: &Option<Addr>,
+    identity: IdentityKeyRef<'_>,
+    change: &PledgeChange,
+    new_pledge: &Coin,
+) -> Event {
+    let mut event = Event::new(PLEDGE_CHANGE_EVENT_TYPE)
+        .add_attribute(OWNER_KEY, owner)
+        .add_attribute(NODE_IDENTITY_KEY, identity);
+
+    if let Some(proxy) = proxy {
+        event = event.add_attribute(PROXY_KEY, proxy)
+    }
+
+    // coin implements Display trait and we use that implementation here
+    event
+        .add_attribute(PLEDGE_CHANGE_KIND_KEY, change.kind())
+        .add_attribute(AMOUNT_KEY, change.amount().to_string())
+        .add_attribute(NEW_PLEDGE_KEY, new_pledge.to_string())
+}
+
+pub fn new_mixnode_sphinx_key_update_event(
+    identity: IdentityKeyRef<'_>,
+    old_sphinx_key: &str,
+    new_sphinx_key: &str,
+) -> Event {
+    Event::new(MIXNODE_SPHINX_KEY_UPDATE_EVENT_TYPE)
+        .add_attribute(NODE_IDENTITY_KEY, identity)
+        .add_attribute(OLD_SPHINX_KEY_KEY, old_sphinx_key)
+        .add_attribute(NEW_SPHINX_KEY_KEY, new_sphinx_key)
+}
+
 pub fn new_settings_update_event(
     old_params: &ContractStateParams,
     new_params: &ContractStateParams,
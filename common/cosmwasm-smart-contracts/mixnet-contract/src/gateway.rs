@@ -13,6 +13,9 @@ pub struct Gateway {
     pub host: String,
     pub mix_port: u16,
     pub clients_port: u16,
+    /// Port on which the gateway accepts TLS-secured (`wss://`) client connections, if it does.
+    #[serde(default)]
+    pub clients_wss_port: Option<u16>,
     pub location: String,
     pub sphinx_key: SphinxKey,
     /// Base58 encoded ed25519 EdDSA public key of the gateway used to derive shared keys with clients
@@ -20,6 +23,17 @@ pub struct Gateway {
     pub version: String,
 }
 
+/// Subset of the [`Gateway`] fields that can be changed without rebonding the node.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
+pub struct GatewayConfigUpdate {
+    pub host: String,
+    pub clients_port: u16,
+    #[serde(default)]
+    pub clients_wss_port: Option<u16>,
+    pub location: String,
+    pub version: String,
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
 pub struct GatewayBond {
     pub pledge_amount: Coin,
@@ -145,6 +159,7 @@ mod tests {
             host: "1.1.1.1".to_string(),
             mix_port: 123,
             clients_port: 456,
+            clients_wss_port: None,
             location: "foomplandia".to_string(),
             sphinx_key: "sphinxkey".to_string(),
             identity_key: "identitykey".to_string(),
@@ -18,10 +18,13 @@ pub use delegation::{
     Delegation, PagedAllDelegationsResponse, PagedDelegatorDelegationsResponse,
     PagedMixDelegationsResponse,
 };
-pub use gateway::{Gateway, GatewayBond, GatewayOwnershipResponse, PagedGatewayResponse};
+pub use gateway::{
+    Gateway, GatewayBond, GatewayConfigUpdate, GatewayOwnershipResponse, PagedGatewayResponse,
+};
 pub use interval::Interval;
 pub use mixnode::{
-    Layer, MixNode, MixNodeBond, MixOwnershipResponse, PagedMixnodeResponse, RewardedSetNodeStatus,
+    Layer, MixNode, MixNodeBond, MixOwnershipResponse, PagedMixnodeResponse, PendingPledgeChange,
+    PledgeChange, RewardedSetNodeStatus,
 };
 pub use msg::*;
 pub use types::*;
@@ -106,6 +106,58 @@ impl PendingUndelegate {
     }
 }
 
+/// Change of the operator's pledge. It is not applied immediately, but only once the current
+/// epoch is over, so that the rewarding for the epoch would be based on a consistent pledge.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
+pub enum PledgeChange {
+    Increase(Coin),
+    Decrease(Coin),
+}
+
+impl PledgeChange {
+    pub fn amount(&self) -> &Coin {
+        match self {
+            PledgeChange::Increase(amount) | PledgeChange::Decrease(amount) => amount,
+        }
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            PledgeChange::Increase(_) => "increase",
+            PledgeChange::Decrease(_) => "decrease",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize, JsonSchema)]
+pub struct PendingPledgeChange {
+    pub owner: Addr,
+    pub proxy: Option<Addr>,
+    pub change: PledgeChange,
+    pub block_height: u64,
+}
+
+impl PendingPledgeChange {
+    pub fn new(owner: Addr, proxy: Option<Addr>, change: PledgeChange, block_height: u64) -> Self {
+        PendingPledgeChange {
+            owner,
+            proxy,
+            change,
+            block_height,
+        }
+    }
+}
+
+/// Message that has to be signed with the mixnode's identity key in order to replace
+/// its current sphinx key with the new one.
+/// Including the current key ensures the signature cannot be replayed to revert to an older key.
+pub fn sphinx_key_update_message(current_sphinx_key: &str, new_sphinx_key: &str) -> String {
+    format!(
+        "sphinx-key-update:{}:{}",
+        current_sphinx_key, new_sphinx_key
+    )
+}
+
 #[derive(Clone, Debug, Deserialize, PartialEq, PartialOrd, Serialize, JsonSchema)]
 pub struct MixNode {
     pub host: String,
@@ -3,7 +3,8 @@
 
 use crate::reward_params::NodeRewardParams;
 use crate::ContractStateParams;
-use crate::{Gateway, IdentityKey, MixNode};
+use crate::{Gateway, GatewayConfigUpdate, IdentityKey, MixNode, SphinxKey};
+use cosmwasm_std::Coin;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -37,6 +38,18 @@ pub enum ExecuteMsg {
         owner_signature: String,
     },
     UnbondMixnode {},
+    // the pledge increase has to be sent alongside the message
+    PledgeMore {},
+    PledgeMoreOnBehalf {
+        owner: String,
+    },
+    DecreasePledge {
+        decrease_by: Coin,
+    },
+    DecreasePledgeOnBehalf {
+        decrease_by: Coin,
+        owner: String,
+    },
     UpdateMixnodeConfig {
         profit_margin_percent: u8,
     },
@@ -44,11 +57,24 @@ pub enum ExecuteMsg {
         profit_margin_percent: u8,
         owner: String,
     },
+    UpdateMixnodeSphinxKey {
+        mix_identity: IdentityKey,
+        sphinx_key: SphinxKey,
+        // signature of `sphinx_key_update_message(current_key, sphinx_key)` using the mixnode's identity key
+        signature: String,
+    },
     BondGateway {
         gateway: Gateway,
         owner_signature: String,
     },
     UnbondGateway {},
+    UpdateGatewayConfig {
+        new_config: GatewayConfigUpdate,
+    },
+    UpdateGatewayConfigOnBehalf {
+        new_config: GatewayConfigUpdate,
+        owner: String,
+    },
     UpdateContractStateParams(ContractStateParams),
 
     DelegateToMixnode {
@@ -130,6 +156,9 @@ pub enum QueryMsg {
     OwnsMixnode {
         address: String,
     },
+    GetMixnodeBond {
+        mix_identity: IdentityKey,
+    },
     OwnsGateway {
         address: String,
     },
@@ -195,6 +224,9 @@ pub enum QueryMsg {
         mix_identity: IdentityKey,
         height: u64,
     },
+    GetPendingPledgeChange {
+        mix_identity: IdentityKey,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
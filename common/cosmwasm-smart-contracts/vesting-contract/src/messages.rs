@@ -1,5 +1,5 @@
 use cosmwasm_std::{Coin, Timestamp};
-use mixnet_contract_common::{Gateway, IdentityKey, MixNode};
+use mixnet_contract_common::{Gateway, GatewayConfigUpdate, IdentityKey, MixNode};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
@@ -97,12 +97,25 @@ pub enum ExecuteMsg {
         owner: String,
         amount: Coin,
     },
+    PledgeMore {
+        amount: Coin,
+    },
+    DecreasePledge {
+        amount: Coin,
+    },
+    TrackDecreasePledge {
+        owner: String,
+        amount: Coin,
+    },
     BondGateway {
         gateway: Gateway,
         owner_signature: String,
         amount: Coin,
     },
     UnbondGateway {},
+    UpdateGatewayConfig {
+        new_config: GatewayConfigUpdate,
+    },
     TrackUnbondGateway {
         owner: String,
         amount: Coin,
@@ -0,0 +1,6 @@
+/*
+ * Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+ALTER TABLE coconut_credentials ADD COLUMN consumed BOOLEAN NOT NULL DEFAULT false;
@@ -48,11 +48,38 @@ impl CoconutCredentialManager {
     pub(crate) async fn get_next_coconut_credential(
         &self,
     ) -> Result<CoconutCredential, sqlx::Error> {
+        sqlx::query_as!(
+            CoconutCredential,
+            "SELECT * FROM coconut_credentials WHERE consumed = false"
+        )
+        .fetch_one(&self.connection_pool)
+        .await
+    }
+
+    /// Retrieves all the stored credentials, including the consumed ones.
+    pub(crate) async fn get_all_coconut_credentials(
+        &self,
+    ) -> Result<Vec<CoconutCredential>, sqlx::Error> {
         sqlx::query_as!(CoconutCredential, "SELECT * FROM coconut_credentials")
-            .fetch_one(&self.connection_pool)
+            .fetch_all(&self.connection_pool)
             .await
     }
 
+    /// Marks the specified credential as being consumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: Database id.
+    pub(crate) async fn consume_coconut_credential(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE coconut_credentials SET consumed = true WHERE id = ?",
+            id
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
     /// Removes from the database the specified credential.
     ///
     /// # Arguments
@@ -64,4 +91,13 @@ impl CoconutCredentialManager {
             .await?;
         Ok(())
     }
+
+    /// Removes from the database all the consumed credentials.
+    /// Returns the number of removed credentials.
+    pub(crate) async fn remove_consumed_coconut_credentials(&self) -> Result<u64, sqlx::Error> {
+        let res = sqlx::query!("DELETE FROM coconut_credentials WHERE consumed = true")
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(res.rows_affected())
+    }
 }
@@ -17,7 +17,7 @@ use std::path::{Path, PathBuf};
 mod coconut;
 mod erc20;
 pub mod error;
-mod models;
+pub mod models;
 pub mod storage;
 
 // note that clone here is fine as upon cloning the same underlying pool will be used
@@ -97,6 +97,23 @@ impl Storage for PersistentStorage {
         Ok(credential)
     }
 
+    async fn get_all_coconut_credentials(&self) -> Result<Vec<CoconutCredential>, StorageError> {
+        let credentials = self
+            .coconut_credential_manager
+            .get_all_coconut_credentials()
+            .await?;
+
+        Ok(credentials)
+    }
+
+    async fn consume_coconut_credential(&self, id: i64) -> Result<(), StorageError> {
+        self.coconut_credential_manager
+            .consume_coconut_credential(id)
+            .await?;
+
+        Ok(())
+    }
+
     async fn remove_coconut_credential(&self, id: i64) -> Result<(), StorageError> {
         self.coconut_credential_manager
             .remove_coconut_credential(id)
@@ -105,6 +122,15 @@ impl Storage for PersistentStorage {
         Ok(())
     }
 
+    async fn remove_consumed_coconut_credentials(&self) -> Result<u64, StorageError> {
+        let removed = self
+            .coconut_credential_manager
+            .remove_consumed_coconut_credentials()
+            .await?;
+
+        Ok(removed)
+    }
+
     async fn insert_erc20_credential(
         &self,
         public_key: String,
@@ -9,6 +9,7 @@ pub struct CoconutCredential {
     pub serial_number: String,
     pub binding_number: String,
     pub signature: String,
+    pub consumed: bool,
 }
 
 pub struct ERC20Credential {
@@ -25,6 +25,16 @@ pub trait Storage: Send + Sync {
     /// Tries to retrieve one of the stored, unused credentials.
     async fn get_next_coconut_credential(&self) -> Result<CoconutCredential, StorageError>;
 
+    /// Retrieves all the stored credentials, including the consumed ones.
+    async fn get_all_coconut_credentials(&self) -> Result<Vec<CoconutCredential>, StorageError>;
+
+    /// Mark a credential as being consumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id`: Database id of the credential.
+    async fn consume_coconut_credential(&self, id: i64) -> Result<(), StorageError>;
+
     /// Removes from the database the specified credential.
     ///
     /// # Arguments
@@ -32,6 +42,10 @@ pub trait Storage: Send + Sync {
     /// * `signature`: Coconut credential in the form of a signature.
     async fn remove_coconut_credential(&self, id: i64) -> Result<(), StorageError>;
 
+    /// Removes from the database all the consumed credentials.
+    /// Returns the number of removed credentials.
+    async fn remove_consumed_coconut_credentials(&self) -> Result<u64, StorageError>;
+
     /// Inserts provided signature into the database.
     ///
     /// # Arguments
@@ -145,6 +145,13 @@ impl BandwidthVoucher {
         self.use_request
     }
 
+    /// Sets whether the blind sign request should be sent to the validators, rather than only
+    /// asking them for the signatures they have already issued on it.
+    pub fn with_use_request(mut self, use_request: bool) -> Self {
+        self.use_request = use_request;
+        self
+    }
+
     pub fn get_public_attributes_plain(&self) -> Vec<String> {
         vec![
             self.voucher_value_plain.clone(),
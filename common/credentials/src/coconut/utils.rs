@@ -4,11 +4,15 @@
 use coconut_interface::{
     aggregate_signature_shares, aggregate_verification_keys, prove_bandwidth_credential, Attribute,
     BlindSignRequestBody, BlindedSignature, Credential, Parameters, Signature, SignatureShare,
-    VerificationKey,
+    SignerIndex, VerificationKey,
 };
 use crypto::asymmetric::encryption::PublicKey;
 use crypto::shared_key::recompute_shared_key;
 use crypto::symmetric::stream_cipher;
+use futures::future::{self, Either};
+use futures::stream::{FuturesUnordered, StreamExt};
+use std::future::Future;
+use std::time::Duration;
 use url::Url;
 
 use crate::coconut::bandwidth::{BandwidthVoucher, PRIVATE_ATTRIBUTES, PUBLIC_ATTRIBUTES};
@@ -17,15 +21,168 @@ use crate::coconut::params::{
 };
 use crate::error::Error;
 
-/// Contacts all provided validators and then aggregate their verification keys.
+/// Default maximum amount of time a single validator is given to respond with its share.
+pub const DEFAULT_SIGNER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of signers whose shares are required for issuing credentials and for aggregating their
+/// verification key, i.e. more than 2/3 of them. The signers derive their keys with this threshold
+/// during the distributed key generation.
+pub fn signing_threshold(num_signers: usize) -> u64 {
+    (num_signers as u64 * 2) / 3 + 1
+}
+
+/// Validator that failed to provide its share of the verification key or of the signature.
+#[derive(Debug)]
+pub struct SignerFailure {
+    /// Index of the validator, i.e. its (1-based) position on the provided list.
+    pub index: SignerIndex,
+    pub url: Url,
+    pub error: Error,
+}
+
+/// Result of collecting shares from the validators alongside the report of the ones that failed.
+#[derive(Debug)]
+pub struct ThresholdOutcome<T> {
+    pub value: T,
+
+    /// Validators that failed to respond before the threshold got reached. Note that validators
+    /// that were still being waited for at that point are not included.
+    pub failures: Vec<SignerFailure>,
+}
+
+async fn with_timeout<T, F>(request: F, timeout: Duration) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    #[cfg(not(target_arch = "wasm32"))]
+    let delay = tokio::time::sleep(timeout);
+    #[cfg(target_arch = "wasm32")]
+    let delay = fluvio_wasm_timer::Delay::new(timeout);
+
+    futures::pin_mut!(request, delay);
+    match future::select(request, delay).await {
+        Either::Left((response, _)) => response,
+        Either::Right(_) => Err(Error::SignerTimeout(timeout)),
+    }
+}
+
+// Sends the request to all the provided validators in parallel and returns as soon as `threshold`
+// of them have responded successfully, or once all of them have either responded or failed.
+// Shares are returned alongside the index of the validator.
+async fn request_shares<T, F, Fut>(
+    validators: &[(SignerIndex, Url)],
+    threshold: u64,
+    signer_timeout: Duration,
+    request: F,
+) -> (Vec<(SignerIndex, T)>, Vec<SignerFailure>)
+where
+    F: Fn(Url) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut pending = validators
+        .iter()
+        .map(|(index, url)| {
+            let response = with_timeout(request(url.clone()), signer_timeout);
+            async move { (*index, url, response.await) }
+        })
+        .collect::<FuturesUnordered<_>>();
+
+    let mut shares = Vec::with_capacity(validators.len());
+    let mut failures = Vec::new();
+    while let Some((index, url, response)) = pending.next().await {
+        match response {
+            Ok(share) => {
+                shares.push((index, share));
+                if shares.len() as u64 >= threshold {
+                    break;
+                }
+            }
+            Err(error) => failures.push(SignerFailure {
+                index,
+                url: url.clone(),
+                error,
+            }),
+        }
+    }
+
+    // keep the shares ordered by the signer indices regardless of the order of responses
+    shares.sort_by_key(|(index, _)| *index);
+    (shares, failures)
+}
+
+// Sends the request to all the validators in parallel and returns as soon as `threshold` of them
+// have responded successfully. Validators are indexed by their (1-based) position on the list.
+async fn collect_shares<T, F, Fut>(
+    validators: &[Url],
+    threshold: u64,
+    signer_timeout: Duration,
+    request: F,
+) -> Result<(Vec<(SignerIndex, T)>, Vec<SignerFailure>), Error>
+where
+    F: Fn(Url) -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    if validators.is_empty() {
+        return Err(Error::NoValidatorsAvailable);
+    }
+
+    let validators = validators
+        .iter()
+        .enumerate()
+        .map(|(id, url)| ((id + 1) as SignerIndex, url.clone()))
+        .collect::<Vec<_>>();
+    let (shares, failures) = request_shares(&validators, threshold, signer_timeout, request).await;
+
+    if (shares.len() as u64) < threshold {
+        return Err(Error::NotEnoughShares {
+            obtained: shares.len(),
+            threshold,
+            failures,
+        });
+    }
+
+    Ok((shares, failures))
+}
+
+/// Contacts the provided validators in parallel and aggregates the verification keys of the first
+/// `threshold` of them to respond.
 ///
 /// # Arguments
 ///
 /// * `validators`: list of validators to obtain verification keys from.
+/// * `threshold`: number of verification key shares required for the aggregation.
+/// * `signer_timeout`: maximum amount of time a single validator is given to respond.
 ///
 /// Note: list of validators must be correctly ordered by the polynomial coordinates used
-/// during key generation and it is responsibility of the caller to ensure that correct
-/// number of them is provided
+/// during key generation.
+pub async fn obtain_threshold_verification_key(
+    validators: &[Url],
+    threshold: u64,
+    signer_timeout: Duration,
+) -> Result<ThresholdOutcome<VerificationKey>, Error> {
+    let (responses, failures) =
+        collect_shares(validators, threshold, signer_timeout, |url| async move {
+            let client = validator_client::ApiClient::new(url);
+            Ok(client.get_coconut_verification_key().await?.key)
+        })
+        .await?;
+
+    let (indices, shares): (Vec<_>, Vec<_>) = responses.into_iter().unzip();
+    Ok(ThresholdOutcome {
+        value: aggregate_verification_keys(&shares, Some(&indices))?,
+        failures,
+    })
+}
+
+/// Contacts the provided validators and aggregates the verification keys of the first
+/// [`signing_threshold`] of them to respond.
+///
+/// # Arguments
+///
+/// * `validators`: list of all the validators to obtain verification keys from.
+///
+/// Note: list of validators must be correctly ordered by the polynomial coordinates used
+/// during key generation.
 ///
 /// # Examples
 ///
@@ -43,22 +200,13 @@ use crate::error::Error;
 pub async fn obtain_aggregate_verification_key(
     validators: &[Url],
 ) -> Result<VerificationKey, Error> {
-    if validators.is_empty() {
-        return Err(Error::NoValidatorsAvailable);
-    }
-
-    let mut indices = Vec::with_capacity(validators.len());
-    let mut shares = Vec::with_capacity(validators.len());
-
-    let mut client = validator_client::ApiClient::new(validators[0].clone());
-    for (id, validator_url) in validators.iter().enumerate() {
-        client.change_validator_api(validator_url.clone());
-        let response = client.get_coconut_verification_key().await?;
-        indices.push((id + 1) as u64);
-        shares.push(response.key);
-    }
-
-    Ok(aggregate_verification_keys(&shares, Some(&indices))?)
+    obtain_threshold_verification_key(
+        validators,
+        signing_threshold(validators.len()),
+        DEFAULT_SIGNER_TIMEOUT,
+    )
+    .await
+    .map(|outcome| outcome.value)
 }
 
 async fn obtain_partial_credential(
@@ -115,51 +263,132 @@ async fn obtain_partial_credential(
     Ok(unblinded_signature)
 }
 
-pub async fn obtain_aggregate_signature(
+/// Contacts the provided validators in parallel to obtain their partial signatures on the
+/// attributes, returning as soon as `needed` of them have responded. Unlike
+/// `obtain_threshold_signature`, the obtained signatures are returned even if not enough
+/// validators have responded, so that they could be stored and only the remaining
+/// validators would have to be contacted again.
+///
+/// # Arguments
+///
+/// * `params`: coconut parameters the attributes were created with.
+/// * `attributes`: attributes of the bandwidth voucher to obtain the signatures on.
+/// * `validators`: validators to obtain the partial signatures from alongside their indices.
+/// * `needed`: number of partial signatures after which the remaining validators are not waited for.
+/// * `signer_timeout`: maximum amount of time a single validator is given to respond.
+pub async fn obtain_partial_signatures(
     params: &Parameters,
     attributes: &BandwidthVoucher,
-    validators: &[Url],
+    validators: &[(SignerIndex, Url)],
+    needed: u64,
+    signer_timeout: Duration,
+) -> (Vec<SignatureShare>, Vec<SignerFailure>) {
+    let (responses, failures) =
+        request_shares(validators, needed, signer_timeout, |url| async move {
+            let client = validator_client::ApiClient::new(url);
+            let validator_partial_vk = client.get_coconut_verification_key().await?.key;
+            obtain_partial_credential(params, attributes, &client, &validator_partial_vk).await
+        })
+        .await;
+
+    let shares = responses
+        .into_iter()
+        .map(|(index, signature)| SignatureShare::new(signature, index))
+        .collect();
+    (shares, failures)
+}
+
+/// Aggregates the previously obtained partial signatures on the attributes.
+///
+/// # Arguments
+///
+/// * `params`: coconut parameters the attributes were created with.
+/// * `attributes`: attributes of the bandwidth voucher the partial signatures are on.
+/// * `shares`: partial signatures alongside the indices of the validators that issued them.
+/// * `verification_key`: aggregated verification key of the validators.
+pub fn aggregate_partial_signatures(
+    params: &Parameters,
+    attributes: &BandwidthVoucher,
+    shares: &[SignatureShare],
+    verification_key: &VerificationKey,
 ) -> Result<Signature, Error> {
-    if validators.is_empty() {
-        return Err(Error::NoValidatorsAvailable);
-    }
     let public_attributes = attributes.get_public_attributes();
     let private_attributes = attributes.get_private_attributes();
 
-    let mut shares = Vec::with_capacity(validators.len());
-    let mut validators_partial_vks: Vec<VerificationKey> = Vec::with_capacity(validators.len());
-
-    let mut client = validator_client::ApiClient::new(validators[0].clone());
-    for (id, validator_url) in validators.iter().enumerate() {
-        client.change_validator_api(validator_url.clone());
-        let validator_partial_vk = client.get_coconut_verification_key().await?;
-        validators_partial_vks.push(validator_partial_vk.key.clone());
-        let signature =
-            obtain_partial_credential(params, attributes, &client, &validator_partial_vk.key)
-                .await?;
-        let share = SignatureShare::new(signature, (id + 1) as u64);
-        shares.push(share)
-    }
-
     let mut attributes = Vec::with_capacity(private_attributes.len() + public_attributes.len());
     attributes.extend_from_slice(&private_attributes);
     attributes.extend_from_slice(&public_attributes);
 
-    let mut indices: Vec<u64> = Vec::with_capacity(validators_partial_vks.len());
-    for i in 0..validators_partial_vks.len() {
-        indices.push((i + 1) as u64);
-    }
-    let verification_key =
-        aggregate_verification_keys(&validators_partial_vks, Some(indices.as_ref()))?;
-
     Ok(aggregate_signature_shares(
         params,
-        &verification_key,
+        verification_key,
         &attributes,
-        &shares,
+        shares,
     )?)
 }
 
+/// Contacts the provided validators in parallel and aggregates the partial signatures of the first
+/// `threshold` of them to respond.
+///
+/// # Arguments
+///
+/// * `params`: coconut parameters the attributes were created with.
+/// * `attributes`: attributes of the bandwidth voucher to obtain the signature on.
+/// * `validators`: list of validators to obtain the partial signatures from.
+/// * `threshold`: number of partial signatures required for the aggregation.
+/// * `signer_timeout`: maximum amount of time a single validator is given to respond.
+pub async fn obtain_threshold_signature(
+    params: &Parameters,
+    attributes: &BandwidthVoucher,
+    validators: &[Url],
+    threshold: u64,
+    signer_timeout: Duration,
+) -> Result<ThresholdOutcome<Signature>, Error> {
+    let (responses, failures) =
+        collect_shares(validators, threshold, signer_timeout, |url| async move {
+            let client = validator_client::ApiClient::new(url);
+            let validator_partial_vk = client.get_coconut_verification_key().await?.key;
+            let signature =
+                obtain_partial_credential(params, attributes, &client, &validator_partial_vk)
+                    .await?;
+            Ok((validator_partial_vk, signature))
+        })
+        .await?;
+
+    let mut indices = Vec::with_capacity(responses.len());
+    let mut validators_partial_vks = Vec::with_capacity(responses.len());
+    let mut shares = Vec::with_capacity(responses.len());
+    for (index, (validator_partial_vk, signature)) in responses {
+        indices.push(index);
+        validators_partial_vks.push(validator_partial_vk);
+        shares.push(SignatureShare::new(signature, index));
+    }
+
+    let verification_key = aggregate_verification_keys(&validators_partial_vks, Some(&indices))?;
+    Ok(ThresholdOutcome {
+        value: aggregate_partial_signatures(params, attributes, &shares, &verification_key)?,
+        failures,
+    })
+}
+
+/// Contacts the provided validators and aggregates the partial signatures of the first
+/// [`signing_threshold`] of them to respond.
+pub async fn obtain_aggregate_signature(
+    params: &Parameters,
+    attributes: &BandwidthVoucher,
+    validators: &[Url],
+) -> Result<Signature, Error> {
+    obtain_threshold_signature(
+        params,
+        attributes,
+        validators,
+        signing_threshold(validators.len()),
+        DEFAULT_SIGNER_TIMEOUT,
+    )
+    .await
+    .map(|outcome| outcome.value)
+}
+
 // TODO: better type flow
 pub fn prepare_credential_for_spending(
     params: &Parameters,
@@ -185,3 +414,127 @@ pub fn prepare_credential_for_spending(
         voucher_info,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validators(n: usize) -> Vec<Url> {
+        (1..=n)
+            .map(|i| format!("http://validator{}.local", i).parse().unwrap())
+            .collect()
+    }
+
+    // the request "succeeds" by returning the index encoded in the url, unless the validator is
+    // on the list of the failing ones
+    async fn respond(url: Url, failing: &[&str], hanging: &[&str]) -> Result<u64, Error> {
+        let host = url.host_str().unwrap().to_string();
+        if failing.contains(&host.as_str()) {
+            return Err(Error::BandwidthCredentialError);
+        }
+        if hanging.contains(&host.as_str()) {
+            future::pending::<()>().await;
+        }
+        Ok(host
+            .trim_start_matches("validator")
+            .trim_end_matches(".local")
+            .parse()
+            .unwrap())
+    }
+
+    #[test]
+    fn signing_threshold_is_more_than_two_thirds() {
+        assert_eq!(signing_threshold(1), 1);
+        assert_eq!(signing_threshold(3), 3);
+        assert_eq!(signing_threshold(4), 3);
+        assert_eq!(signing_threshold(10), 7);
+    }
+
+    #[tokio::test]
+    async fn collection_tolerates_failures_below_threshold() {
+        let validators = validators(3);
+        let (shares, failures) = collect_shares(&validators, 2, DEFAULT_SIGNER_TIMEOUT, |url| {
+            respond(url, &["validator2.local"], &[])
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(shares, vec![(1, 1), (3, 3)]);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].index, 2);
+        assert_eq!(failures[0].url, validators[1]);
+    }
+
+    #[tokio::test]
+    async fn collection_fails_below_threshold() {
+        let validators = validators(3);
+        let err = collect_shares(&validators, 2, DEFAULT_SIGNER_TIMEOUT, |url| {
+            respond(url, &["validator1.local", "validator3.local"], &[])
+        })
+        .await
+        .unwrap_err();
+
+        match err {
+            Error::NotEnoughShares {
+                obtained,
+                threshold,
+                failures,
+            } => {
+                assert_eq!(obtained, 1);
+                assert_eq!(threshold, 2);
+                let failed = failures.iter().map(|f| f.index).collect::<Vec<_>>();
+                assert!(failed.contains(&1) && failed.contains(&3));
+            }
+            err => panic!("unexpected error - {}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn unresponsive_signers_time_out() {
+        let validators = validators(3);
+        let timeout = Duration::from_millis(50);
+        let err = collect_shares(&validators, 3, timeout, |url| {
+            respond(url, &[], &["validator3.local"])
+        })
+        .await
+        .unwrap_err();
+
+        match err {
+            Error::NotEnoughShares { failures, .. } => {
+                assert_eq!(failures.len(), 1);
+                assert_eq!(failures[0].index, 3);
+                assert!(matches!(failures[0].error, Error::SignerTimeout(_)));
+            }
+            err => panic!("unexpected error - {}", err),
+        }
+    }
+
+    #[tokio::test]
+    async fn collection_does_not_wait_for_signers_beyond_threshold() {
+        let validators = validators(3);
+        let (shares, failures) = collect_shares(&validators, 2, DEFAULT_SIGNER_TIMEOUT, |url| {
+            respond(url, &[], &["validator2.local"])
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(shares, vec![(1, 1), (3, 3)]);
+        assert!(failures.is_empty());
+    }
+
+    #[tokio::test]
+    async fn requests_keep_signer_indices_and_return_shares_below_threshold() {
+        let validators = validators(4);
+        // only the signers that haven't responded before are contacted again
+        let remaining = vec![(2, validators[1].clone()), (4, validators[3].clone())];
+        let (shares, failures) = request_shares(&remaining, 2, DEFAULT_SIGNER_TIMEOUT, |url| {
+            respond(url, &["validator4.local"], &[])
+        })
+        .await;
+
+        assert_eq!(shares, vec![(2, 2)]);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].index, 4);
+        assert_eq!(failures[0].url, validators[3]);
+    }
+}
@@ -1,13 +1,26 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(feature = "coconut")]
+use crate::coconut::utils::SignerFailure;
 #[cfg(feature = "coconut")]
 use coconut_interface::CoconutError;
 use crypto::asymmetric::encryption::KeyRecoveryError;
+#[cfg(feature = "coconut")]
+use std::time::Duration;
 use validator_client::ValidatorClientError;
 
 use thiserror::Error;
 
+#[cfg(feature = "coconut")]
+fn format_failures(failures: &[SignerFailure]) -> String {
+    failures
+        .iter()
+        .map(|failure| format!("{} ({}): {}", failure.index, failure.url, failure.error))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 #[derive(Debug, Error)]
 pub enum Error {
     #[error("The detailed description is yet to be determined")]
@@ -31,4 +44,16 @@ pub enum Error {
 
     #[error("Could not parse the key - {0}")]
     ParsePublicKey(#[from] KeyRecoveryError),
+
+    #[cfg(feature = "coconut")]
+    #[error("The validator did not respond within {0:?}")]
+    SignerTimeout(Duration),
+
+    #[cfg(feature = "coconut")]
+    #[error("Obtained only {obtained} out of the required {threshold} shares. Failed signers: [{}]", format_failures(.failures))]
+    NotEnoughShares {
+        obtained: usize,
+        threshold: u64,
+        failures: Vec<SignerFailure>,
+    },
 }
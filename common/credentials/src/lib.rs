@@ -8,4 +8,7 @@ pub mod error;
 pub mod token;
 
 #[cfg(feature = "coconut")]
-pub use coconut::utils::{obtain_aggregate_signature, obtain_aggregate_verification_key};
+pub use coconut::utils::{
+    obtain_aggregate_signature, obtain_aggregate_verification_key, obtain_threshold_signature,
+    obtain_threshold_verification_key, signing_threshold,
+};
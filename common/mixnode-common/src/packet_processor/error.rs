@@ -14,6 +14,7 @@ pub enum MixProcessingError {
     MalformedSurbAck(SurbAckRecoveryError),
 
     ReceivedOldTypeVpnPacket,
+    ReplayedPacket,
 }
 
 impl From<SphinxError> for MixProcessingError {
@@ -59,6 +60,9 @@ impl Display for MixProcessingError {
             MixProcessingError::ReceivedOldTypeVpnPacket => {
                 write!(f, "Received an old-type unsafe 'VPN' mode packet")
             }
+            MixProcessingError::ReplayedPacket => {
+                write!(f, "Received a replayed sphinx packet")
+            }
         }
     }
 }
@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::packet_processor::error::MixProcessingError;
+use crate::packet_processor::replay::ReplayDetector;
+use crate::packet_processor::sphinx_keys::SphinxKeys;
 use log::*;
 use nymsphinx_acknowledgements::surb_ack::SurbAck;
 use nymsphinx_addressing::nodes::NymNodeRoutingAddress;
@@ -33,24 +35,62 @@ pub enum MixProcessingResult {
 
 #[derive(Clone)]
 pub struct SphinxPacketProcessor {
-    /// Private sphinx key of this node required to unwrap received sphinx packet.
-    sphinx_key: Arc<PrivateKey>,
+    /// Private sphinx key(s) of this node required to unwrap received sphinx packet.
+    sphinx_keys: Arc<SphinxKeys>,
+
+    /// Keeps track of recently processed packets in order to reject any replays.
+    replay_detector: Arc<ReplayDetector>,
 }
 
 impl SphinxPacketProcessor {
     /// Creates new instance of `CachedPacketProcessor`
     pub fn new(sphinx_key: PrivateKey) -> Self {
+        Self::new_with_replay_detector(sphinx_key, ReplayDetector::default())
+    }
+
+    /// Creates new instance of `CachedPacketProcessor` using the provided replay detector
+    pub fn new_with_replay_detector(
+        sphinx_key: PrivateKey,
+        replay_detector: ReplayDetector,
+    ) -> Self {
+        Self::new_with_rotating_keys(Arc::new(SphinxKeys::new(sphinx_key)), replay_detector)
+    }
+
+    /// Creates new instance of `CachedPacketProcessor` using the provided, possibly rotating,
+    /// sphinx keys and replay detector
+    pub fn new_with_rotating_keys(
+        sphinx_keys: Arc<SphinxKeys>,
+        replay_detector: ReplayDetector,
+    ) -> Self {
         SphinxPacketProcessor {
-            sphinx_key: Arc::new(sphinx_key),
+            sphinx_keys,
+            replay_detector: Arc::new(replay_detector),
         }
     }
 
     /// Performs a fresh sphinx unwrapping using no cache.
+    /// If the sphinx key has been recently rotated, unwrapping using the previous key is
+    /// attempted if the current one fails.
     fn perform_initial_sphinx_packet_processing(
         &self,
         packet: SphinxPacket,
     ) -> Result<ProcessedPacket, MixProcessingError> {
-        packet.process(&self.sphinx_key).map_err(|err| {
+        let (current_key, previous_key) = self.sphinx_keys.active_keys();
+
+        let processed = match previous_key {
+            None => packet.process(&current_key),
+            Some(previous_key) => {
+                // processing consumes the packet so we need to keep its copy around in case
+                // it was constructed using our previous key
+                let packet_bytes = packet.to_bytes();
+                packet.process(&current_key).or_else(|_| {
+                    SphinxPacket::from_bytes(&packet_bytes)
+                        .and_then(|packet| packet.process(&previous_key))
+                })
+            }
+        };
+
+        processed.map_err(|err| {
             debug!("Failed to unwrap Sphinx packet: {:?}", err);
             MixProcessingError::SphinxProcessingError(err)
         })
@@ -68,7 +108,24 @@ impl SphinxPacketProcessor {
             return Err(MixProcessingError::ReceivedOldTypeVpnPacket);
         }
 
-        self.perform_initial_sphinx_packet_processing(sphinx_packet)
+        // the packet is going to get consumed during processing so grab the replay tag beforehand
+        let replay_tag = *sphinx_packet.header.shared_secret.as_bytes();
+        let processed = self.perform_initial_sphinx_packet_processing(sphinx_packet)?;
+
+        // only remember tags of packets we managed to successfully unwrap, so that garbage
+        // could not be used to fill up the replay detector
+        self.check_replay(&replay_tag)?;
+
+        Ok(processed)
+    }
+
+    /// Checks whether a packet with the given tag has already been processed before.
+    fn check_replay(&self, replay_tag: &[u8]) -> Result<(), MixProcessingError> {
+        if self.replay_detector.check_and_insert(replay_tag) {
+            debug!("Received a replayed sphinx packet");
+            return Err(MixProcessingError::ReplayedPacket);
+        }
+        Ok(())
     }
 
     /// Processed received forward hop packet - tries to extract next hop address, sets delay
@@ -176,7 +233,7 @@ impl SphinxPacketProcessor {
         let packet_size = received.packet_size();
         let packet_mode = received.packet_mode();
 
-        // unwrap the sphinx packet and if possible and appropriate, cache keys
+        // unwrap the sphinx packet and make sure it's not a replay
         let processed_packet = self.perform_initial_unwrapping(received)?;
 
         // for forward packets, extract next hop and set delay (but do NOT delay here)
@@ -189,7 +246,14 @@ impl SphinxPacketProcessor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use nymsphinx_types::builder::SphinxPacketBuilder;
     use nymsphinx_types::crypto::keygen;
+    use nymsphinx_types::{
+        Destination, Node, PublicKey, DESTINATION_ADDRESS_LENGTH, IDENTIFIER_LENGTH,
+        NODE_ADDRESS_LENGTH,
+    };
+    use std::convert::TryInto;
+    use std::net::SocketAddr;
 
     fn fixture() -> SphinxPacketProcessor {
         let local_keys = keygen();
@@ -220,6 +284,114 @@ mod tests {
         assert_eq!(data.len(), SurbAck::len() * 4)
     }
 
+    fn make_forward_hop_packet(first_hop_key: &PublicKey) -> SphinxPacket {
+        let next_hop_address: SocketAddr = "127.0.0.1:1789".parse().unwrap();
+        let next_hop_address: NodeAddressBytes = NymNodeRoutingAddress::from(next_hop_address)
+            .try_into()
+            .unwrap();
+
+        let node1 = Node::new(
+            NodeAddressBytes::from_bytes([5u8; NODE_ADDRESS_LENGTH]),
+            PublicKey::from(*first_hop_key.as_bytes()),
+        );
+        let (_, node2_pk) = keygen();
+        let node2 = Node::new(next_hop_address, node2_pk);
+
+        let route = [node1, node2];
+        let destination = Destination::new(
+            DestinationAddressBytes::from_bytes([3u8; DESTINATION_ADDRESS_LENGTH]),
+            [4u8; IDENTIFIER_LENGTH],
+        );
+        let delays = vec![
+            SphinxDelay::new_from_nanos(42),
+            SphinxDelay::new_from_nanos(42),
+        ];
+        SphinxPacketBuilder::new()
+            .with_payload_size(PacketSize::default().payload_size())
+            .build_packet(b"foomp".to_vec(), &route, &destination, &delays)
+            .unwrap()
+    }
+
+    fn framed(packet_bytes: &[u8]) -> FramedSphinxPacket {
+        FramedSphinxPacket::new(
+            SphinxPacket::from_bytes(packet_bytes).unwrap(),
+            Default::default(),
+        )
+    }
+
+    #[test]
+    fn identical_packet_is_rejected_the_second_time() {
+        let (private_key, public_key) = keygen();
+        let processor = SphinxPacketProcessor::new(private_key);
+        let packet_bytes = make_forward_hop_packet(&public_key).to_bytes();
+
+        assert!(matches!(
+            processor.process_received(framed(&packet_bytes)),
+            Ok(MixProcessingResult::ForwardHop(..))
+        ));
+        assert!(matches!(
+            processor.process_received(framed(&packet_bytes)),
+            Err(MixProcessingError::ReplayedPacket)
+        ));
+
+        // and the same is true for any clones of the processor as they share the replay detector
+        assert!(matches!(
+            processor.clone().process_received(framed(&packet_bytes)),
+            Err(MixProcessingError::ReplayedPacket)
+        ));
+    }
+
+    #[test]
+    fn distinct_packets_are_not_treated_as_replays() {
+        let (private_key, public_key) = keygen();
+        let processor = SphinxPacketProcessor::new(private_key);
+
+        for _ in 0..10 {
+            let packet_bytes = make_forward_hop_packet(&public_key).to_bytes();
+            assert!(processor.process_received(framed(&packet_bytes)).is_ok());
+        }
+    }
+
+    #[test]
+    fn packets_failing_processing_are_not_remembered() {
+        let (private_key, _) = keygen();
+        let (_, other_public_key) = keygen();
+        let processor = SphinxPacketProcessor::new(private_key);
+        let packet_bytes = make_forward_hop_packet(&other_public_key).to_bytes();
+
+        for _ in 0..2 {
+            assert!(matches!(
+                processor.process_received(framed(&packet_bytes)),
+                Err(MixProcessingError::SphinxProcessingError(..))
+            ));
+        }
+    }
+
+    #[test]
+    fn packets_for_previous_key_are_accepted_during_overlap() {
+        let (old_private_key, old_public_key) = keygen();
+        let (new_private_key, new_public_key) = keygen();
+        let (newest_private_key, _) = keygen();
+
+        let keys = Arc::new(SphinxKeys::new(old_private_key));
+        let processor =
+            SphinxPacketProcessor::new_with_rotating_keys(Arc::clone(&keys), Default::default());
+
+        keys.rotate(new_private_key, std::time::Duration::from_secs(60));
+        let old_packet = make_forward_hop_packet(&old_public_key).to_bytes();
+        let new_packet = make_forward_hop_packet(&new_public_key).to_bytes();
+        assert!(processor.process_received(framed(&old_packet)).is_ok());
+        assert!(processor.process_received(framed(&new_packet)).is_ok());
+
+        // once the overlap is over, the old key is no longer accepted
+        keys.rotate(newest_private_key, std::time::Duration::from_secs(0));
+        let new_packet = make_forward_hop_packet(&new_public_key).to_bytes();
+        assert!(matches!(
+            processor.process_received(framed(&new_packet)),
+            Err(MixProcessingError::SphinxProcessingError(..))
+        ));
+    }
+
     #[tokio::test]
     async fn splitting_into_ack_and_message_returns_whole_data_for_ack() {
         let processor = fixture();
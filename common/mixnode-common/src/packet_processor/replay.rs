@@ -0,0 +1,211 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Default interval after which the current filter becomes the previous one and a fresh one
+/// is created. Since we keep two generations, a replayed packet is detected if it arrives
+/// within at least this interval from the original.
+pub const DEFAULT_ROTATION_INTERVAL: Duration = Duration::from_secs(30 * 60);
+
+/// Default number of packets a single filter generation is sized for.
+/// If more packets are seen before `DEFAULT_ROTATION_INTERVAL` elapses, the filters get rotated early.
+pub const DEFAULT_GENERATION_CAPACITY: usize = 5_000_000;
+
+/// Default target false positive rate of a single filter generation.
+pub const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.0001;
+
+/// Simple bloom filter using double hashing (based on keyed SipHash) to derive bit positions.
+struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+    items: usize,
+}
+
+impl BloomFilter {
+    fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        // standard optimal parameters:
+        // m = -n * ln(p) / (ln(2))^2
+        // k = m / n * ln(2)
+        let capacity = capacity.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+        let num_bits = (-capacity * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / capacity) * ln2).round().max(1.0) as u32;
+
+        let words = ((num_bits + 63) / 64) as usize;
+        BloomFilter {
+            bits: vec![0; words],
+            num_bits: words as u64 * 64,
+            num_hashes,
+            items: 0,
+        }
+    }
+
+    fn bit_indices(
+        num_bits: u64,
+        num_hashes: u32,
+        hashes: (u64, u64),
+    ) -> impl Iterator<Item = u64> {
+        let (h1, h2) = hashes;
+        (0..num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+
+    fn contains(&self, hashes: (u64, u64)) -> bool {
+        Self::bit_indices(self.num_bits, self.num_hashes, hashes)
+            .all(|idx| self.bits[(idx / 64) as usize] & (1 << (idx % 64)) != 0)
+    }
+
+    fn insert(&mut self, hashes: (u64, u64)) {
+        for idx in Self::bit_indices(self.num_bits, self.num_hashes, hashes) {
+            self.bits[(idx / 64) as usize] |= 1 << (idx % 64);
+        }
+        self.items += 1;
+    }
+
+    fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|word| *word = 0);
+        self.items = 0;
+    }
+}
+
+struct RotatingFilters {
+    current: BloomFilter,
+    previous: BloomFilter,
+    last_rotation: Instant,
+}
+
+/// Detects replayed sphinx packets by remembering tags (i.e. the shared secrets included in the
+/// sphinx headers) of all recently processed packets.
+///
+/// Internally it keeps two generations of bloom filters that are periodically rotated, so that
+/// the memory usage stays bounded. As a result, false positives are possible (with probability
+/// controlled by `false_positive_rate`), while false negatives are only possible for packets
+/// replayed after their tag has been rotated out.
+pub struct ReplayDetector {
+    hasher_state: RandomState,
+    rotation_interval: Duration,
+    generation_capacity: usize,
+    filters: Mutex<RotatingFilters>,
+}
+
+impl Default for ReplayDetector {
+    fn default() -> Self {
+        ReplayDetector::new(
+            DEFAULT_ROTATION_INTERVAL,
+            DEFAULT_GENERATION_CAPACITY,
+            DEFAULT_FALSE_POSITIVE_RATE,
+        )
+    }
+}
+
+impl ReplayDetector {
+    pub fn new(
+        rotation_interval: Duration,
+        generation_capacity: usize,
+        false_positive_rate: f64,
+    ) -> Self {
+        ReplayDetector {
+            hasher_state: RandomState::new(),
+            rotation_interval,
+            generation_capacity,
+            filters: Mutex::new(RotatingFilters {
+                current: BloomFilter::new(generation_capacity, false_positive_rate),
+                previous: BloomFilter::new(generation_capacity, false_positive_rate),
+                last_rotation: Instant::now(),
+            }),
+        }
+    }
+
+    fn hash_tag(&self, tag: &[u8]) -> (u64, u64) {
+        let mut hasher = self.hasher_state.build_hasher();
+        hasher.write(tag);
+        let h1 = hasher.finish();
+
+        // continue with the same state to get the second, independent-enough, value
+        hasher.write_u8(0xff);
+        let h2 = hasher.finish();
+
+        // make sure the step is odd so that we never keep hitting the same bit
+        (h1, h2 | 1)
+    }
+
+    /// Checks whether the provided tag has been seen before and if not, marks it as seen.
+    /// Returns `true` if the tag was already present, i.e. the packet is a replay.
+    pub fn check_and_insert(&self, tag: &[u8]) -> bool {
+        let hashes = self.hash_tag(tag);
+
+        // if the lock got poisoned, some thread has panicked while holding it meaning
+        // the node is in an undefined state anyway
+        let mut filters = self
+            .filters
+            .lock()
+            .expect("replay detector lock got poisoned");
+
+        if filters.last_rotation.elapsed() >= self.rotation_interval
+            || filters.current.items >= self.generation_capacity
+        {
+            let filters = &mut *filters;
+            std::mem::swap(&mut filters.current, &mut filters.previous);
+            filters.current.clear();
+            filters.last_rotation = Instant::now();
+        }
+
+        if filters.current.contains(hashes) || filters.previous.contains(hashes) {
+            return true;
+        }
+
+        filters.current.insert(hashes);
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_repeated_tags() {
+        let detector = ReplayDetector::default();
+        let tag = [42u8; 32];
+
+        assert!(!detector.check_and_insert(&tag));
+        assert!(detector.check_and_insert(&tag));
+        assert!(detector.check_and_insert(&tag));
+    }
+
+    #[test]
+    fn does_not_flag_distinct_tags() {
+        let detector = ReplayDetector::new(DEFAULT_ROTATION_INTERVAL, 1000, 0.0001);
+        for i in 0..100u32 {
+            let mut tag = [0u8; 32];
+            tag[..4].copy_from_slice(&i.to_be_bytes());
+            assert!(!detector.check_and_insert(&tag));
+        }
+    }
+
+    #[test]
+    fn remembers_tags_for_one_rotation() {
+        let detector = ReplayDetector::new(DEFAULT_ROTATION_INTERVAL, 2, 0.0001);
+        let first = [1u8; 32];
+        let second = [2u8; 32];
+        let third = [3u8; 32];
+
+        assert!(!detector.check_and_insert(&first));
+        assert!(!detector.check_and_insert(&second));
+
+        // this causes rotation, but the previous generation is still consulted
+        assert!(!detector.check_and_insert(&third));
+        assert!(detector.check_and_insert(&first));
+
+        // and after another rotation the first tag is forgotten
+        assert!(!detector.check_and_insert(&[4u8; 32]));
+        assert!(!detector.check_and_insert(&[5u8; 32]));
+        assert!(!detector.check_and_insert(&first));
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use nymsphinx_types::PrivateKey;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+
+struct PreviousKey {
+    key: Arc<PrivateKey>,
+    valid_until: Instant,
+}
+
+struct Keys {
+    current: Arc<PrivateKey>,
+    previous: Option<PreviousKey>,
+}
+
+/// Sphinx keys used by the node to unwrap received packets.
+///
+/// After the key is rotated, the previous one is still accepted for the duration of the overlap
+/// window, so that packets constructed by clients that have not yet learned about the new key
+/// (for example due to their topology being slightly stale) are not dropped.
+pub struct SphinxKeys {
+    inner: RwLock<Keys>,
+}
+
+impl SphinxKeys {
+    pub fn new(current: PrivateKey) -> Self {
+        SphinxKeys {
+            inner: RwLock::new(Keys {
+                current: Arc::new(current),
+                previous: None,
+            }),
+        }
+    }
+
+    /// Replaces the current key with the provided one. The replaced key is going to be
+    /// accepted for the duration of the specified overlap.
+    pub fn rotate(&self, new_key: PrivateKey, overlap: Duration) {
+        // if the lock got poisoned, some thread has panicked while holding it meaning
+        // the node is in an undefined state anyway
+        let mut keys = self.inner.write().expect("sphinx keys lock got poisoned");
+
+        let previous = std::mem::replace(&mut keys.current, Arc::new(new_key));
+        keys.previous = Some(PreviousKey {
+            key: previous,
+            valid_until: Instant::now() + overlap,
+        });
+    }
+
+    /// Returns the current key alongside the previous one, if it's still within its overlap window.
+    pub fn active_keys(&self) -> (Arc<PrivateKey>, Option<Arc<PrivateKey>>) {
+        let keys = self.inner.read().expect("sphinx keys lock got poisoned");
+
+        let previous = keys
+            .previous
+            .as_ref()
+            .filter(|previous| previous.valid_until > Instant::now())
+            .map(|previous| Arc::clone(&previous.key));
+
+        (Arc::clone(&keys.current), previous)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nymsphinx_types::crypto::keygen;
+
+    #[test]
+    fn previous_key_is_only_used_during_overlap() {
+        let (first, _) = keygen();
+        let (second, _) = keygen();
+        let (third, _) = keygen();
+        let first_bytes = first.to_bytes();
+        let second_bytes = second.to_bytes();
+        let third_bytes = third.to_bytes();
+
+        let keys = SphinxKeys::new(first);
+        let (current, previous) = keys.active_keys();
+        assert_eq!(current.to_bytes(), first_bytes);
+        assert!(previous.is_none());
+
+        keys.rotate(second, Duration::from_secs(60));
+        let (current, previous) = keys.active_keys();
+        assert_eq!(current.to_bytes(), second_bytes);
+        assert_eq!(previous.unwrap().to_bytes(), first_bytes);
+
+        keys.rotate(third, Duration::from_secs(0));
+        let (current, previous) = keys.active_keys();
+        assert_eq!(current.to_bytes(), third_bytes);
+        assert!(previous.is_none());
+    }
+}
@@ -23,6 +23,20 @@ pub struct VerlocResult {
     results: Vec<Verloc>,
 }
 
+impl VerlocResult {
+    pub fn total_tested(&self) -> usize {
+        self.total_tested
+    }
+
+    pub fn run_finished(&self) -> Option<std::time::SystemTime> {
+        self.run_finished
+    }
+
+    pub fn results(&self) -> &[Verloc] {
+        &self.results
+    }
+}
+
 impl AtomicVerlocResult {
     pub(crate) fn new() -> Self {
         AtomicVerlocResult {
@@ -179,6 +179,8 @@ pub const DEFAULT_MIX_LISTENING_PORT: u16 = 1789;
 
 // 'GATEWAY'
 pub const DEFAULT_CLIENT_LISTENING_PORT: u16 = 9000;
+pub const DEFAULT_CLIENT_WSS_LISTENING_PORT: u16 = 9001;
+pub const DEFAULT_GATEWAY_HTTP_API_LISTENING_PORT: u16 = 8080;
 
 // 'MIXNODE'
 pub const DEFAULT_VERLOC_LISTENING_PORT: u16 = 1790;
@@ -45,6 +45,14 @@ impl<T> NonExhaustiveDelayQueue<T> {
         key
     }
 
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
     // TODO: it seems like this one can cause panic in very rare edge cases, however,
     // I can't seem to be able to reproduce it at all.
     pub fn remove(&mut self, key: &QueueKey) -> Expired<T> {
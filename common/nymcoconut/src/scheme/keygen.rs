@@ -363,6 +363,24 @@ pub struct KeyPair {
 impl KeyPair {
     const MARKER_BYTES: &'static [u8] = b"coconutkeypair";
 
+    /// Creates a keypair out of already known secret values, such as the ones derived during
+    /// a distributed key generation, rather than by a trusted third party.
+    pub fn from_secrets(
+        params: &Parameters,
+        x: Scalar,
+        ys: Vec<Scalar>,
+        index: Option<SignerIndex>,
+    ) -> Self {
+        let secret_key = SecretKey { x, ys };
+        let verification_key = secret_key.verification_key(params);
+
+        KeyPair {
+            secret_key,
+            verification_key,
+            index,
+        }
+    }
+
     pub fn secret_key(&self) -> SecretKey {
         self.secret_key.clone()
     }
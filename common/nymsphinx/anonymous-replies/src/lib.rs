@@ -3,6 +3,8 @@
 
 pub mod encryption_key;
 pub mod reply_surb;
+pub mod sender_tag;
 
 pub use encryption_key::{SurbEncryptionKey, SurbEncryptionKeySize};
 pub use reply_surb::{ReplySurb, ReplySurbError};
+pub use sender_tag::{SenderTag, SenderTagError, SENDER_TAG_SIZE};
@@ -4,7 +4,9 @@
 use crate::encryption_key::{SurbEncryptionKey, SurbEncryptionKeyError, SurbEncryptionKeySize};
 use crypto::{generic_array::typenum::Unsigned, Digest};
 use nymsphinx_addressing::clients::Recipient;
-use nymsphinx_addressing::nodes::{NymNodeRoutingAddress, MAX_NODE_ADDRESS_UNPADDED_LEN};
+use nymsphinx_addressing::nodes::{
+    NymNodeRoutingAddress, NymNodeRoutingAddressError, MAX_NODE_ADDRESS_UNPADDED_LEN,
+};
 use nymsphinx_params::packet_sizes::PacketSize;
 use nymsphinx_params::{ReplySurbKeyDigestAlgorithm, DEFAULT_NUM_MIX_HOPS};
 use nymsphinx_types::{delays, Error as SphinxError, SURBMaterial, SphinxPacket, SURB};
@@ -22,6 +24,7 @@ pub enum ReplySurbError {
     MalformedStringError(bs58::decode::Error),
     RecoveryError(SphinxError),
     InvalidEncryptionKeyData(SurbEncryptionKeyError),
+    MalformedFirstHop(NymNodeRoutingAddressError),
 }
 
 impl fmt::Display for ReplySurbError {
@@ -41,6 +44,11 @@ impl fmt::Display for ReplySurbError {
                 "failed to recover reply SURB encryption key from bytes: {}",
                 surb_key_err
             ),
+            ReplySurbError::MalformedFirstHop(address_err) => write!(
+                f,
+                "reply SURB contains malformed first hop address: {:?}",
+                address_err
+            ),
         }
     }
 }
@@ -147,6 +155,22 @@ impl ReplySurb {
         &self.encryption_key
     }
 
+    /// Returns the address of the first mix node on the route of this [`ReplySurb`].
+    /// Note that it's the only node on the route visible to the holder of the SURB,
+    /// the remaining hops are hidden inside its encrypted header.
+    pub fn first_hop_address(&self) -> Result<NymNodeRoutingAddress, ReplySurbError> {
+        use nymsphinx_types::{HEADER_SIZE, NODE_ADDRESS_LENGTH};
+
+        // SURB_BYTES = SURB_HEADER || FIRST_HOP || PAYLOAD_KEYS
+        let surb_bytes = self.surb.to_bytes();
+        let first_hop = surb_bytes
+            .get(HEADER_SIZE..HEADER_SIZE + NODE_ADDRESS_LENGTH)
+            .ok_or(ReplySurbError::MalformedFirstHop(
+                NymNodeRoutingAddressError::InsufficientNumberOfBytesAvailableError,
+            ))?;
+        NymNodeRoutingAddress::try_from_bytes(first_hop).map_err(ReplySurbError::MalformedFirstHop)
+    }
+
     pub fn to_bytes(&self) -> Vec<u8> {
         // KEY || SURB_BYTES
         self.encryption_key
@@ -206,7 +230,8 @@ impl ReplySurb {
             .use_surb(message, packet_size.payload_size())
             .expect("this error indicates inconsistent message length checking - it shouldn't have happened!");
 
-        let first_hop_address = NymNodeRoutingAddress::try_from(first_hop).unwrap();
+        let first_hop_address = NymNodeRoutingAddress::try_from(first_hop)
+            .map_err(ReplySurbError::MalformedFirstHop)?;
 
         Ok((packet, first_hop_address))
     }
@@ -0,0 +1,96 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use rand::{CryptoRng, RngCore};
+use std::fmt::{self, Display, Formatter};
+
+pub const SENDER_TAG_SIZE: usize = 16;
+
+/// Locally assigned, pseudorandom identifier of an anonymous sender that has attached reply SURBs
+/// to its message. It is never sent through the network and only allows the recipient to refer to
+/// the stored reply SURBs without having to handle them directly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SenderTag([u8; SENDER_TAG_SIZE]);
+
+#[derive(Debug)]
+pub enum SenderTagError {
+    MalformedStringError(bs58::decode::Error),
+    BytesOfInvalidLengthError,
+}
+
+impl Display for SenderTagError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SenderTagError::MalformedStringError(decode_err) => {
+                write!(f, "sender tag is incorrectly formatted: {}", decode_err)
+            }
+            SenderTagError::BytesOfInvalidLengthError => {
+                write!(f, "provided bytes have invalid length")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SenderTagError {}
+
+impl SenderTag {
+    pub fn new_random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let mut tag = [0u8; SENDER_TAG_SIZE];
+        rng.fill_bytes(&mut tag);
+        SenderTag(tag)
+    }
+
+    pub fn from_bytes(bytes: [u8; SENDER_TAG_SIZE]) -> Self {
+        SenderTag(bytes)
+    }
+
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<Self, SenderTagError> {
+        if bytes.len() != SENDER_TAG_SIZE {
+            return Err(SenderTagError::BytesOfInvalidLengthError);
+        }
+
+        let mut tag = [0u8; SENDER_TAG_SIZE];
+        tag.copy_from_slice(bytes);
+        Ok(SenderTag(tag))
+    }
+
+    pub fn to_bytes(self) -> [u8; SENDER_TAG_SIZE] {
+        self.0
+    }
+
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    pub fn to_base58_string(self) -> String {
+        bs58::encode(self.0).into_string()
+    }
+
+    pub fn try_from_base58_string<S: Into<String>>(val: S) -> Result<Self, SenderTagError> {
+        let bytes = bs58::decode(val.into())
+            .into_vec()
+            .map_err(SenderTagError::MalformedStringError)?;
+        Self::try_from_bytes(&bytes)
+    }
+}
+
+impl Display for SenderTag {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_base58_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn base58_string_conversion_works() {
+        let tag = SenderTag::new_random(&mut rand::rngs::OsRng);
+        let recovered = SenderTag::try_from_base58_string(tag.to_base58_string()).unwrap();
+        assert_eq!(tag, recovered);
+
+        assert!(SenderTag::try_from_base58_string("foo").is_err());
+        assert!(SenderTag::try_from_base58_string("0OIl").is_err());
+    }
+}
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::chunking;
+use crate::receiver::MULTIPLE_REPLY_SURBS_PREFIX;
 use crypto::asymmetric::encryption;
 use crypto::shared_key::new_ephemeral_shared_key;
 use crypto::symmetric::stream_cipher;
@@ -26,6 +27,9 @@ use std::convert::TryFrom;
 use std::time::Duration;
 use topology::{NymTopology, NymTopologyError};
 
+/// Maximum number of reply SURBs that can be attached to a single message.
+pub const MAX_REPLY_SURBS_PER_MESSAGE: u32 = 256;
+
 /// Represents fully packed and prepared [`Fragment`] that can be sent through the mix network.
 pub struct PreparedFragment {
     /// Indicates the total expected round-trip time, i.e. delay from the sending of this message
@@ -36,12 +40,16 @@ pub struct PreparedFragment {
     /// address of the node to which the message should be sent, the actual 'chunk' of the message
     /// going through the mix network and also the 'mode' of the packet, i.e. VPN or Mix.
     pub mix_packet: MixPacket,
+
+    /// Addresses of all mix nodes on the route of the packet, in order, not including the gateway.
+    pub mix_route: Vec<NymNodeRoutingAddress>,
 }
 
 #[derive(Debug)]
 pub enum PreparationError {
     TopologyError(NymTopologyError),
     TooLongReplyMessageError,
+    TooManyReplySurbs(u32),
 }
 
 impl From<NymTopologyError> for PreparationError {
@@ -51,7 +59,7 @@ impl From<NymTopologyError> for PreparationError {
 }
 
 /// Prepares the message that is to be sent through the mix network by attaching
-/// an optional reply-SURB, padding it to appropriate length, encrypting its content,
+/// optional reply-SURBs, padding it to appropriate length, encrypting its content,
 /// and chunking into appropriate size [`Fragment`]s.
 #[cfg_attr(not(target_arch = "wasm32"), derive(Clone))]
 #[must_use]
@@ -148,43 +156,61 @@ where
             .collect()
     }
 
-    /// Attaches reply-SURB to the message alongside the reply key.
+    /// Attaches the specified number of reply-SURBs to the message alongside their reply keys.
     /// Results in:
     /// new_message = 0 || message
     /// OR
     /// new_message = 1 || REPLY_KEY || REPLY_SURB || message
-    fn optionally_attach_reply_surb(
+    /// OR (if more than a single reply-SURB is requested)
+    /// new_message = 2 || NUM_SURBS || (REPLY_KEY || REPLY_SURB)* || message
+    fn attach_reply_surbs(
         &mut self,
         message: Vec<u8>,
-        should_attach: bool,
+        num_reply_surbs: u32,
         topology: &NymTopology,
-    ) -> Result<(Vec<u8>, Option<SurbEncryptionKey>), PreparationError> {
-        if should_attach {
-            let reply_surb = ReplySurb::construct(
+    ) -> Result<(Vec<u8>, Vec<SurbEncryptionKey>), PreparationError> {
+        if num_reply_surbs > MAX_REPLY_SURBS_PER_MESSAGE {
+            return Err(PreparationError::TooManyReplySurbs(num_reply_surbs));
+        }
+
+        let mut reply_surbs = Vec::with_capacity(num_reply_surbs as usize);
+        for _ in 0..num_reply_surbs {
+            reply_surbs.push(ReplySurb::construct(
                 &mut self.rng,
                 &self.sender_address,
                 self.average_packet_delay,
                 topology,
-            )?;
-
-            let reply_key = reply_surb.encryption_key();
-            // if there's a reply surb, the message takes form of `1 || REPLY_KEY || REPLY_SURB || MSG`
-            Ok((
-                std::iter::once(true as u8)
-                    .chain(reply_surb.to_bytes().iter().cloned())
-                    .chain(message.into_iter())
-                    .collect(),
-                Some(reply_key.clone()),
-            ))
-        } else {
-            // but if there's no reply surb, the message takes form of `0 || MSG`
-            Ok((
-                std::iter::once(false as u8)
-                    .chain(message.into_iter())
-                    .collect(),
-                None,
-            ))
+            )?);
         }
+
+        let reply_keys = reply_surbs
+            .iter()
+            .map(|reply_surb| reply_surb.encryption_key().clone())
+            .collect();
+
+        let prefix: Vec<u8> = match num_reply_surbs {
+            // if there's no reply surb, the message takes form of `0 || MSG`
+            0 => vec![false as u8],
+            // if there's a single reply surb, the message takes form of `1 || REPLY_KEY || REPLY_SURB || MSG`
+            1 => vec![true as u8],
+            // and otherwise it's `2 || NUM_SURBS || (REPLY_KEY || REPLY_SURB)* || MSG`
+            n => std::iter::once(MULTIPLE_REPLY_SURBS_PREFIX)
+                .chain(n.to_be_bytes().iter().cloned())
+                .collect(),
+        };
+
+        Ok((
+            prefix
+                .into_iter()
+                .chain(
+                    reply_surbs
+                        .iter()
+                        .flat_map(|reply_surb| reply_surb.to_bytes()),
+                )
+                .chain(message.into_iter())
+                .collect(),
+            reply_keys,
+        ))
     }
 
     /// Splits the message into [`Fragment`] that are going to be put later put into sphinx packets.
@@ -284,12 +310,20 @@ where
         let first_hop_address =
             NymNodeRoutingAddress::try_from(route.first().unwrap().address).unwrap();
 
+        // and remember all the mix nodes in case the packet had to be routed around them later
+        let mix_route = route
+            .iter()
+            .take(self.num_mix_hops as usize)
+            .map(|node| NymNodeRoutingAddress::try_from(node.address).unwrap())
+            .collect();
+
         Ok(PreparedFragment {
             // the round-trip delay is the sum of delays of all hops on the forward route as
             // well as the total delay of the ack packet.
             // note that the last hop of the packet is a gateway that does not do any delays
             total_delay: delays.iter().take(delays.len() - 1).sum::<Delay>() + ack_delay,
             mix_packet: MixPacket::new(first_hop_address, sphinx_packet, Default::default()),
+            mix_route,
         })
     }
 
@@ -319,12 +353,30 @@ where
         with_reply_surb: bool,
         topology: &NymTopology,
     ) -> Result<(Vec<Fragment>, Option<SurbEncryptionKey>), PreparationError> {
-        let (message, reply_key) =
-            self.optionally_attach_reply_surb(message, with_reply_surb, topology)?;
+        let (fragments, mut reply_keys) = self.prepare_and_split_message_with_reply_surbs(
+            message,
+            with_reply_surb as u32,
+            topology,
+        )?;
+
+        Ok((fragments, reply_keys.pop()))
+    }
+
+    /// Attaches the specified number of reply-surbs and correct padding to the underlying message
+    /// and splits it into [`Fragment`] that can be later packed into sphinx packets to be
+    /// sent through the mix network.
+    /// Returns the keys of all attached reply-surbs alongside the fragments.
+    pub fn prepare_and_split_message_with_reply_surbs(
+        &mut self,
+        message: Vec<u8>,
+        num_reply_surbs: u32,
+        topology: &NymTopology,
+    ) -> Result<(Vec<Fragment>, Vec<SurbEncryptionKey>), PreparationError> {
+        let (message, reply_keys) = self.attach_reply_surbs(message, num_reply_surbs, topology)?;
 
         let message = self.pad_message(message);
 
-        Ok((self.split_message(message), reply_key))
+        Ok((self.split_message(message), reply_keys))
     }
 
     // TODO: perhaps the return type could somehow be combined with [`PreparedFragment`] ?
@@ -5,9 +5,15 @@ use crypto::asymmetric::encryption;
 use crypto::shared_key::recompute_shared_key;
 use crypto::symmetric::stream_cipher;
 use nymsphinx_anonymous_replies::reply_surb::{ReplySurb, ReplySurbError};
+use nymsphinx_anonymous_replies::SenderTag;
 use nymsphinx_chunking::fragment::Fragment;
 use nymsphinx_chunking::reconstruction::MessageReconstructor;
 use nymsphinx_params::{PacketEncryptionAlgorithm, PacketHkdfAlgorithm, DEFAULT_NUM_MIX_HOPS};
+use std::convert::TryInto;
+use std::mem::size_of;
+
+/// Prefix of a message that had multiple reply SURBs attached to it.
+pub(crate) const MULTIPLE_REPLY_SURBS_PREFIX: u8 = 2;
 
 // TODO: should this live in this file?
 #[derive(Debug)]
@@ -15,8 +21,12 @@ pub struct ReconstructedMessage {
     /// The actual plaintext message that was received.
     pub message: Vec<u8>,
 
-    /// Optional ReplySURB to allow for an anonymous reply to the sender.
-    pub reply_surb: Option<ReplySurb>,
+    /// ReplySURBs (if any) to allow for anonymous replies to the sender.
+    pub reply_surbs: Vec<ReplySurb>,
+
+    /// Local tag under which the attached reply SURBs got stored, if the client is configured to
+    /// store them instead of passing them on. In that case `reply_surbs` is always empty.
+    pub sender_tag: Option<SenderTag>,
 }
 
 #[derive(Debug)]
@@ -64,27 +74,46 @@ impl MessageReceiver {
         self
     }
 
-    /// Parses the message to strip and optionally recover reply SURB.
-    fn recover_reply_surb_from_message(
+    /// Parses the message to strip and recover all attached reply SURBs.
+    fn recover_reply_surbs_from_message(
         &self,
         message: &mut Vec<u8>,
-    ) -> Result<Option<ReplySurb>, MessageRecoveryError> {
-        match message[0] {
-            n if n == false as u8 => {
-                message.remove(0);
-                Ok(None)
-            }
-            n if n == true as u8 => {
-                let surb_len: usize = ReplySurb::serialized_len(self.num_mix_hops);
-                // note the extra +1 (due to 0/1 message prefix)
-                let surb_bytes = &message[1..1 + surb_len];
-                let reply_surb = ReplySurb::from_bytes(surb_bytes)?;
-
-                *message = message.drain(1 + surb_len..).collect();
-                Ok(Some(reply_surb))
+    ) -> Result<Vec<ReplySurb>, MessageRecoveryError> {
+        if message.is_empty() {
+            return Err(MessageRecoveryError::TooShortMessageError);
+        }
+
+        // note the extra +1 (due to 0/1/2 message prefix)
+        let (num_surbs, surbs_start) = match message[0] {
+            n if n == false as u8 => (0, 1),
+            n if n == true as u8 => (1, 1),
+            MULTIPLE_REPLY_SURBS_PREFIX => {
+                if message.len() < 1 + size_of::<u32>() {
+                    return Err(MessageRecoveryError::TooShortMessageError);
+                }
+                let num_surbs =
+                    u32::from_be_bytes(message[1..1 + size_of::<u32>()].try_into().unwrap());
+                (num_surbs as usize, 1 + size_of::<u32>())
             }
-            _ => Err(MessageRecoveryError::InvalidSurbPrefixError),
+            _ => return Err(MessageRecoveryError::InvalidSurbPrefixError),
+        };
+
+        let surb_len: usize = ReplySurb::serialized_len(self.num_mix_hops);
+        let surbs_end = num_surbs
+            .checked_mul(surb_len)
+            .and_then(|surbs_len| surbs_len.checked_add(surbs_start))
+            .ok_or(MessageRecoveryError::TooShortMessageError)?;
+        if message.len() < surbs_end {
+            return Err(MessageRecoveryError::TooShortMessageError);
         }
+
+        let reply_surbs: Vec<ReplySurb> = message[surbs_start..surbs_end]
+            .chunks_exact(surb_len)
+            .map(ReplySurb::from_bytes)
+            .collect::<Result<_, _>>()?;
+
+        *message = message.drain(surbs_end..).collect();
+        Ok(reply_surbs)
     }
 
     /// Given raw fragment data, recovers the remote ephemeral key, recomputes shared secret,
@@ -139,16 +168,16 @@ impl MessageReceiver {
     /// and returned alongside all (if applicable) set ids used in the message.
     ///
     /// # Returns:
-    /// - The reconstructed message alongside optional reply SURB,
+    /// - The reconstructed message alongside any attached reply SURBs,
     /// - List of ids of all the [`Set`]s used during reconstruction to detect stale retransmissions.
     pub fn insert_new_fragment(
         &mut self,
         fragment: Fragment,
     ) -> Result<Option<(ReconstructedMessage, Vec<i32>)>, MessageRecoveryError> {
         if let Some((mut message, used_sets)) = self.reconstructor.insert_new_fragment(fragment) {
-            // Split message into plaintext and reply-SURB
-            let reply_surb = match self.recover_reply_surb_from_message(&mut message) {
-                Ok(reply_surb) => reply_surb,
+            // Split message into plaintext and reply-SURBs
+            let reply_surbs = match self.recover_reply_surbs_from_message(&mut message) {
+                Ok(reply_surbs) => reply_surbs,
                 Err(_) => {
                     return Err(MessageRecoveryError::MalformedReconstructedMessage(
                         used_sets,
@@ -164,7 +193,8 @@ impl MessageReceiver {
             Ok(Some((
                 ReconstructedMessage {
                     message,
-                    reply_surb,
+                    reply_surbs,
+                    sender_tag: None,
                 },
                 used_sets,
             )))
@@ -206,6 +236,7 @@ mod message_receiver {
                 owner: "foomp1".to_string(),
                 stake: 123,
                 delegation: 456,
+                avg_uptime: None,
                 host: "10.20.30.40".parse().unwrap(),
                 mix_host: "10.20.30.40:1789".parse().unwrap(),
                 identity_key: identity::PublicKey::from_base58_string(
@@ -227,6 +258,7 @@ mod message_receiver {
                 owner: "foomp2".to_string(),
                 stake: 123,
                 delegation: 456,
+                avg_uptime: None,
                 host: "11.21.31.41".parse().unwrap(),
                 mix_host: "11.21.31.41:1789".parse().unwrap(),
                 identity_key: identity::PublicKey::from_base58_string(
@@ -248,6 +280,7 @@ mod message_receiver {
                 owner: "foomp3".to_string(),
                 stake: 123,
                 delegation: 456,
+                avg_uptime: None,
                 host: "12.22.32.42".parse().unwrap(),
                 mix_host: "12.22.32.42:1789".parse().unwrap(),
                 identity_key: identity::PublicKey::from_base58_string(
@@ -273,6 +306,7 @@ mod message_receiver {
                 host: "1.2.3.4".parse().unwrap(),
                 mix_host: "1.2.3.4:1789".parse().unwrap(),
                 clients_port: 9000,
+                clients_wss_port: None,
                 identity_key: identity::PublicKey::from_base58_string(
                     "FioFa8nMmPpQnYi7JyojoTuwGLeyNS8BF4ChPr29zUML",
                 )
@@ -306,20 +340,65 @@ mod message_receiver {
         let mut received_without_surb: Vec<_> =
             std::iter::once(0).chain(message.iter().cloned()).collect();
 
-        let reply_surb = message_receiver
-            .recover_reply_surb_from_message(&mut received_without_surb)
+        let reply_surbs = message_receiver
+            .recover_reply_surbs_from_message(&mut received_without_surb)
             .unwrap();
         assert_eq!(received_without_surb, message);
-        assert!(reply_surb.is_none());
+        assert!(reply_surbs.is_empty());
 
         let mut received_with_surb: Vec<_> = std::iter::once(1)
             .chain(reply_surb_bytes.iter().cloned())
             .chain(message.iter().cloned())
             .collect();
-        let reply_surb = message_receiver
-            .recover_reply_surb_from_message(&mut received_with_surb)
+        let reply_surbs = message_receiver
+            .recover_reply_surbs_from_message(&mut received_with_surb)
             .unwrap();
         assert_eq!(received_with_surb, message);
-        assert_eq!(reply_surb_bytes, reply_surb.unwrap().to_bytes());
+        assert_eq!(1, reply_surbs.len());
+        assert_eq!(reply_surb_bytes, reply_surbs[0].to_bytes());
+    }
+
+    #[test]
+    fn correctly_splits_message_into_plaintext_and_multiple_surbs() {
+        let message_receiver: MessageReceiver = Default::default();
+
+        let message = vec![42; 100];
+        let dummy_recipient = Recipient::try_from_base58_string("CytBseW6yFXUMzz4SGAKdNLGR7q3sJLLYxyBGvutNEQV.4QXYyEVc5fUDjmmi8PrHN9tdUFV4PCvSJE1278cHyvoe@FioFa8nMmPpQnYi7JyojoTuwGLeyNS8BF4ChPr29zUML").unwrap();
+        let average_delay = Duration::from_millis(500);
+        let topology = topology_fixture();
+
+        let reply_surbs_bytes: Vec<_> = (0..3)
+            .map(|_| {
+                ReplySurb::construct(&mut OsRng, &dummy_recipient, average_delay, &topology)
+                    .unwrap()
+                    .to_bytes()
+            })
+            .collect();
+
+        let mut received_with_surbs: Vec<_> = std::iter::once(MULTIPLE_REPLY_SURBS_PREFIX)
+            .chain(3u32.to_be_bytes().iter().cloned())
+            .chain(reply_surbs_bytes.iter().flatten().cloned())
+            .chain(message.iter().cloned())
+            .collect();
+        let reply_surbs = message_receiver
+            .recover_reply_surbs_from_message(&mut received_with_surbs)
+            .unwrap();
+        assert_eq!(received_with_surbs, message);
+        assert_eq!(
+            reply_surbs_bytes,
+            reply_surbs
+                .iter()
+                .map(|reply_surb| reply_surb.to_bytes())
+                .collect::<Vec<_>>()
+        );
+
+        // claiming more surbs than there is data for must not blow up
+        let mut malformed: Vec<_> = std::iter::once(MULTIPLE_REPLY_SURBS_PREFIX)
+            .chain(u32::MAX.to_be_bytes().iter().cloned())
+            .chain(message.iter().cloned())
+            .collect();
+        assert!(message_receiver
+            .recover_reply_surbs_from_message(&mut malformed)
+            .is_err());
     }
 }
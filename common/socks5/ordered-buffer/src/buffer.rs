@@ -1,4 +1,5 @@
 use crate::message::OrderedMessage;
+use crate::DEFAULT_WINDOW_SIZE;
 use log::*;
 use std::collections::HashMap;
 
@@ -7,16 +8,30 @@ use std::collections::HashMap;
 /// Only contiguous messages with an index less than or equal to `next_index`
 /// will be returned - this avoids returning gaps while we wait for the buffer
 /// to fill up with the full sequence.
+///
+/// Once the sender has shown it respects the advertised [`credit`](OrderedMessageBuffer::credit),
+/// i.e. once the empty message it sends upon being granted the credit for the first time
+/// has been read, at most `window_size` messages past the last read one are accepted, so that
+/// the buffer could never grow without bound. Before that, the sender might be running an older
+/// version unaware of any credit and all messages are accepted.
 #[derive(Debug)]
 pub struct OrderedMessageBuffer {
     next_index: u64,
+    window_size: u64,
+    window_enforced: bool,
     messages: HashMap<u64, OrderedMessage>,
 }
 
 impl OrderedMessageBuffer {
     pub fn new() -> OrderedMessageBuffer {
+        OrderedMessageBuffer::with_window_size(DEFAULT_WINDOW_SIZE)
+    }
+
+    pub fn with_window_size(window_size: u64) -> OrderedMessageBuffer {
         OrderedMessageBuffer {
             next_index: 0,
+            window_size,
+            window_enforced: false,
             messages: HashMap::new(),
         }
     }
@@ -24,6 +39,11 @@ impl OrderedMessageBuffer {
     /// Writes a message to the buffer. messages are sort on insertion, so
     /// that later on multiple reads for incomplete sequences don't result in
     /// useless sort work.
+    ///
+    /// Messages that were already read or, once the window is enforced, that lie beyond
+    /// the current window are dropped. The only exception are empty messages, which do not take
+    /// any space and are used for signalling closed connections and the start of the flow control,
+    /// which should never have to wait for credit.
     pub fn write(&mut self, message: OrderedMessage) {
         trace!(
             "Writing message index: {} length {:?} to OrderedMessageBuffer.",
@@ -31,9 +51,41 @@ impl OrderedMessageBuffer {
             message.data.len()
         );
 
+        if message.index < self.next_index {
+            warn!(
+                "Received message {} that has already been read - dropping it",
+                message.index
+            );
+            return;
+        }
+
+        if self.window_enforced && message.index >= self.credit() && !message.data.is_empty() {
+            warn!(
+                "Received message {} outside the window (credit was given up to {}) - dropping it",
+                message.index,
+                self.credit()
+            );
+            return;
+        }
+
         self.messages.insert(message.index, message);
     }
 
+    /// Index of the first message that is not going to be accepted by the buffer.
+    /// This value should be advertised to the sender once the data was read from the buffer.
+    pub fn credit(&self) -> u64 {
+        self.next_index + self.window_size
+    }
+
+    /// Number of messages currently stored in the buffer.
+    pub fn len(&self) -> usize {
+        self.messages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
     /// Returns `Option<Vec<u8>>` where it's `Some(bytes)` if there is gapless
     /// ordered data in the buffer, and `None` if the buffer is empty or has
     /// gaps in the contained data.
@@ -51,6 +103,11 @@ impl OrderedMessageBuffer {
         let mut index = self.next_index;
 
         while let Some(ordered_message) = self.messages.remove(&index) {
+            // all messages sent before the sender started respecting our credit have been read
+            if ordered_message.data.is_empty() && !self.window_enforced {
+                trace!("Enforcing the window from message {}", index);
+                self.window_enforced = true;
+            }
             contiguous_messages.push(ordered_message);
             index += 1;
         }
@@ -79,6 +136,7 @@ impl Default for OrderedMessageBuffer {
 #[cfg(test)]
 mod test_chunking_and_reassembling {
     use super::*;
+    use crate::OrderedMessageSender;
 
     #[cfg(test)]
     mod reading_from_and_writing_to_the_buffer {
@@ -303,4 +361,137 @@ mod test_chunking_and_reassembling {
             }
         }
     }
+
+    #[cfg(test)]
+    mod flow_control {
+        use super::*;
+
+        fn message(index: u64) -> OrderedMessage {
+            OrderedMessage {
+                data: vec![1, 2, 3, 4],
+                index,
+            }
+        }
+
+        fn flow_control_start() -> OrderedMessage {
+            OrderedMessage {
+                data: Vec::new(),
+                index: 0,
+            }
+        }
+
+        #[test]
+        fn window_is_not_enforced_before_sender_respects_the_credit() {
+            let mut buffer = OrderedMessageBuffer::with_window_size(4);
+
+            // an old sender keeps on sending regardless of the credit, so nothing can be dropped
+            for index in 1..100 {
+                buffer.write(message(index));
+            }
+            assert_eq!(buffer.len(), 99);
+
+            buffer.write(message(0));
+            assert_eq!(buffer.read().unwrap().len(), 400);
+        }
+
+        #[test]
+        fn messages_beyond_the_window_are_dropped() {
+            let mut buffer = OrderedMessageBuffer::with_window_size(4);
+            buffer.write(flow_control_start());
+            assert!(buffer.read().unwrap().is_empty());
+            assert_eq!(buffer.credit(), 5);
+
+            // message 1 is missing, so nothing can be read, yet the buffer doesn't grow past the window
+            for index in 2..100 {
+                buffer.write(message(index));
+            }
+            assert_eq!(buffer.len(), 3);
+            assert!(buffer.read().is_none());
+
+            buffer.write(message(1));
+            assert_eq!(buffer.read().unwrap().len(), 16);
+            assert!(buffer.is_empty());
+            assert_eq!(buffer.credit(), 9);
+        }
+
+        #[test]
+        fn window_is_only_enforced_once_earlier_messages_are_read() {
+            let mut buffer = OrderedMessageBuffer::with_window_size(4);
+
+            // the sender got the credit only after sending 10 messages, which are still in flight
+            buffer.write(message(0));
+            buffer.write(OrderedMessage {
+                data: Vec::new(),
+                index: 10,
+            });
+            buffer.write(message(12));
+            assert_eq!(buffer.read().unwrap().len(), 4);
+
+            for index in 1..10 {
+                buffer.write(message(index));
+            }
+            buffer.write(message(11));
+            assert_eq!(buffer.read().unwrap().len(), 4 * 11);
+
+            buffer.write(message(17));
+            buffer.write(message(16));
+            assert_eq!(buffer.len(), 1);
+        }
+
+        #[test]
+        fn already_read_messages_are_dropped() {
+            let mut buffer = OrderedMessageBuffer::with_window_size(4);
+            buffer.write(message(0));
+            assert!(buffer.read().is_some());
+
+            buffer.write(message(0));
+            assert!(buffer.is_empty());
+            assert!(buffer.read().is_none());
+        }
+
+        #[test]
+        fn empty_closing_message_is_accepted_regardless_of_window() {
+            let mut buffer = OrderedMessageBuffer::with_window_size(2);
+            buffer.write(message(0));
+            buffer.write(message(1));
+            buffer.write(OrderedMessage {
+                data: Vec::new(),
+                index: 2,
+            });
+            assert_eq!(buffer.len(), 3);
+
+            assert_eq!(buffer.read().unwrap().len(), 8);
+            assert_eq!(buffer.next_index, 3);
+        }
+
+        #[test]
+        fn buffer_stays_bounded_with_sender_respecting_the_credit() {
+            let mut sender = OrderedMessageSender::new();
+            let mut buffer = OrderedMessageBuffer::with_window_size(8);
+
+            let flow_control_start = sender.grant_credit(buffer.credit()).unwrap();
+            buffer.write(flow_control_start);
+            assert!(buffer.read().unwrap().is_empty());
+            sender.grant_credit(buffer.credit());
+
+            // keep on delivering messages in reverse order (worst case for reordering) and only
+            // read them once the sender runs out of credit
+            let mut delivered = 0;
+            for _ in 0..10 {
+                let mut in_flight = Vec::new();
+                while sender.has_credit() {
+                    in_flight.push(sender.wrap_message(vec![42; 100]));
+                }
+                assert_eq!(in_flight.len(), 8);
+
+                for message in in_flight.into_iter().rev() {
+                    buffer.write(message);
+                    assert!(buffer.len() <= 8);
+                }
+                delivered += buffer.read().unwrap().len();
+                sender.grant_credit(buffer.credit());
+            }
+            assert_eq!(delivered, 10 * 8 * 100);
+        }
+    }
 }
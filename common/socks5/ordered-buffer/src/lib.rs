@@ -6,3 +6,9 @@ pub use buffer::OrderedMessageBuffer;
 pub use message::MessageError;
 pub use message::OrderedMessage;
 pub use sender::OrderedMessageSender;
+
+/// Default number of messages that can be in flight on a single connection, i.e. sent by the
+/// [`OrderedMessageSender`] but not yet read from the receiving [`OrderedMessageBuffer`].
+/// Note that the window is only enforced once the sender has shown it respects the credit
+/// granted to it, so that remotes running older versions could keep on sending data unrestricted.
+pub const DEFAULT_WINDOW_SIZE: u64 = 32;
@@ -1,15 +1,48 @@
 use crate::message::OrderedMessage;
 
 /// Assigns sequence numbers to outbound byte vectors. These messages can then
-/// be reassembled into an ordered sequence by the `OrderedMessageSender`.
-#[derive(Debug)]
+/// be reassembled into an ordered sequence by the `OrderedMessageBuffer`.
+///
+/// It also keeps track of the credit granted by the receiver, i.e. the index of the first
+/// message the remote `OrderedMessageBuffer` is not yet willing to accept. Until the receiver
+/// grants any credit, it is assumed not to support flow control (e.g. because it's running
+/// an older version) and the sender is not limited in any way.
+#[derive(Debug, Default)]
 pub struct OrderedMessageSender {
     next_index: u64,
+    credit: Option<u64>,
 }
 
 impl OrderedMessageSender {
     pub fn new() -> OrderedMessageSender {
-        OrderedMessageSender { next_index: 0 }
+        OrderedMessageSender::default()
+    }
+
+    /// Checks whether the receiver is willing to accept another message.
+    pub fn has_credit(&self) -> bool {
+        match self.credit {
+            Some(credit) => self.next_index < credit,
+            None => true,
+        }
+    }
+
+    /// Updates the credit granted by the receiver. Note that the credit can never decrease
+    /// so that out of order (stale) updates would not stall the sender.
+    ///
+    /// When the credit is granted for the first time, an empty message is returned which has to
+    /// be sent to the receiver. It marks the point in the sequence from which the credit is being
+    /// respected, as any message sent before might have been sent past it.
+    pub fn grant_credit(&mut self, credit: u64) -> Option<OrderedMessage> {
+        match self.credit {
+            Some(granted) => {
+                self.credit = Some(granted.max(credit));
+                None
+            }
+            None => {
+                self.credit = Some(credit);
+                Some(self.wrap_message(Vec::new()))
+            }
+        }
     }
 
     /// Turns raw bytes into an OrderedMessage containing the original bytes
@@ -24,12 +57,6 @@ impl OrderedMessageSender {
     }
 }
 
-impl Default for OrderedMessageSender {
-    fn default() -> Self {
-        OrderedMessageSender::new()
-    }
-}
-
 #[cfg(test)]
 mod ordered_message_sender {
     use super::*;
@@ -54,4 +81,46 @@ mod ordered_message_sender {
             assert_eq!(second_message.index, 1);
         }
     }
+
+    #[cfg(test)]
+    mod credit {
+        use super::*;
+
+        #[test]
+        fn is_unlimited_until_granted_for_the_first_time() {
+            let mut sender = OrderedMessageSender::new();
+            for _ in 0..1000 {
+                assert!(sender.has_credit());
+                sender.wrap_message(vec![1]);
+            }
+        }
+
+        #[test]
+        fn runs_out_after_granted_credit_is_used() {
+            let mut sender = OrderedMessageSender::new();
+            let marker = sender.grant_credit(2).unwrap();
+            assert_eq!(marker.index, 0);
+            assert!(marker.data.is_empty());
+
+            assert!(sender.has_credit());
+            sender.wrap_message(vec![1]);
+            assert!(!sender.has_credit());
+
+            assert!(sender.grant_credit(3).is_none());
+            assert!(sender.has_credit());
+        }
+
+        #[test]
+        fn is_never_decreased_by_stale_grants() {
+            let mut sender = OrderedMessageSender::new();
+            sender.grant_credit(3);
+            sender.wrap_message(vec![1]);
+            sender.wrap_message(vec![2]);
+            assert!(!sender.has_credit());
+
+            sender.grant_credit(5);
+            sender.grant_credit(3);
+            assert!(sender.has_credit());
+        }
+    }
 }
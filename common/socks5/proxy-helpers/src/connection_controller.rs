@@ -5,7 +5,7 @@ use futures::channel::mpsc;
 use futures::StreamExt;
 use log::*;
 use ordered_buffer::{OrderedMessage, OrderedMessageBuffer};
-use socks5_requests::ConnectionId;
+use socks5_requests::{ConnectionId, RemoteAddress};
 use std::collections::{HashMap, HashSet};
 
 /// A generic message produced after reading from a socket/connection. It includes data that was
@@ -15,24 +15,59 @@ use std::collections::{HashMap, HashSet};
 pub struct ConnectionMessage {
     pub payload: Vec<u8>,
     pub socket_closed: bool,
+
+    /// Credit that should be advertised to the remote once the payload got written onto
+    /// the connection, i.e. the index of the first ordered message we are not yet willing to accept.
+    pub credit: u64,
+}
+
+/// Instruction for particular connection produced by the [`Controller`] after receiving
+/// a message from the mix network.
+#[derive(Debug)]
+pub enum ConnectionCommand {
+    /// Data that is to be written onto the connection.
+    Data(ConnectionMessage),
+
+    /// Credit granted by the remote, i.e. the index of the first ordered message it is not yet
+    /// willing to accept. Until it is increased, no further data should be read from the connection.
+    Credit(u64),
 }
 
 /// Channel responsible for sending data that was received from mix network into particular connection.
 /// Data includes the actual payload that is to be written onto the connection
 /// alongside boolean indicating whether the remote connection was closed after producing this message,
 /// so that the local connection should also shut down.
-pub type ConnectionSender = mpsc::UnboundedSender<ConnectionMessage>;
+/// It is also used for passing the credit granted by the remote.
+pub type ConnectionSender = mpsc::UnboundedSender<ConnectionCommand>;
 
 /// Receiver part of the [`ConnectionSender`]
-pub type ConnectionReceiver = mpsc::UnboundedReceiver<ConnectionMessage>;
+pub type ConnectionReceiver = mpsc::UnboundedReceiver<ConnectionCommand>;
+
+/// A single datagram received from the mix network on an UDP association alongside the address
+/// of the remote it originated from.
+#[derive(Debug)]
+pub struct DatagramMessage {
+    pub remote_addr: RemoteAddress,
+    pub payload: Vec<u8>,
+}
+
+/// Channel responsible for sending datagrams that were received from mix network into particular
+/// UDP association.
+pub type DatagramSender = mpsc::UnboundedSender<DatagramMessage>;
+
+/// Receiver part of the [`DatagramSender`]
+pub type DatagramReceiver = mpsc::UnboundedReceiver<DatagramMessage>;
 
 pub type ControllerSender = mpsc::UnboundedSender<ControllerCommand>;
 pub type ControllerReceiver = mpsc::UnboundedReceiver<ControllerCommand>;
 
 pub enum ControllerCommand {
     Insert(ConnectionId, ConnectionSender),
+    InsertDatagram(ConnectionId, DatagramSender),
     Remove(ConnectionId),
     Send(ConnectionId, Vec<u8>, bool),
+    SendDatagram(ConnectionId, RemoteAddress, Vec<u8>),
+    Credit(ConnectionId, u64),
 }
 
 struct ActiveConnection {
@@ -62,18 +97,25 @@ impl ActiveConnection {
 /// proxy.
 pub struct Controller {
     active_connections: HashMap<ConnectionId, ActiveConnection>,
+
+    // datagrams are not ordered nor reliable, so we don't need to do any buffering for them
+    active_datagram_associations: HashMap<ConnectionId, DatagramSender>,
     receiver: ControllerReceiver,
 
     // TODO: this will need to be either completely removed (from code) or periodically cleaned
     // to avoid memory issues
     recently_closed: HashSet<ConnectionId>,
 
-    // TODO: this can potentially be abused to ddos and kill provider. Not sure at this point
-    // how to handle it more gracefully
+    // TODO: this can potentially be abused to ddos and kill provider by sending data for many
+    // non-existent connections. Not sure at this point how to handle it more gracefully
 
     // buffer for messages received before connection was established due to mixnet being able to
     // un-order messages. Note we don't ever expect to have more than 1-2 messages per connection here
     pending_messages: HashMap<ConnectionId, Vec<(Vec<u8>, bool)>>,
+
+    // similarly, the credit can overtake the 'Connect' as the remote grants it as soon as it has
+    // established its side of the connection. Only the highest credit received is kept.
+    pending_credit: HashMap<ConnectionId, u64>,
 }
 
 impl Controller {
@@ -82,9 +124,11 @@ impl Controller {
         (
             Controller {
                 active_connections: HashMap::new(),
+                active_datagram_associations: HashMap::new(),
                 receiver,
                 recently_closed: HashSet::new(),
                 pending_messages: HashMap::new(),
+                pending_credit: HashMap::new(),
             },
             sender,
         )
@@ -106,11 +150,32 @@ impl Controller {
                     self.send_to_connection(conn_id, payload, is_closed)
                 }
             }
+            if let Some(credit) = self.pending_credit.remove(&conn_id) {
+                debug!("There was some pending credit for {}", conn_id);
+                self.grant_credit(conn_id, credit)
+            }
+        }
+    }
+
+    fn insert_datagram_association(
+        &mut self,
+        conn_id: ConnectionId,
+        datagram_sender: DatagramSender,
+    ) {
+        if self
+            .active_datagram_associations
+            .insert(conn_id, datagram_sender)
+            .is_some()
+        {
+            error!("Received a duplicate UDP association!")
         }
     }
 
     fn remove_connection(&mut self, conn_id: ConnectionId) {
         debug!("Removing {} from controller", conn_id);
+        if self.active_datagram_associations.remove(&conn_id).is_some() {
+            return;
+        }
         if self.active_connections.remove(&conn_id).is_none() {
             error!(
                 "tried to remove non-existing connection with id: {:?}",
@@ -132,14 +197,16 @@ impl Controller {
             active_connection.is_closed |= is_closed;
 
             if let Some(payload) = active_connection.read_from_buf() {
+                let credit = active_connection.ordered_buffer.credit();
                 if let Err(err) = active_connection
                     .connection_sender
                     .as_mut()
                     .unwrap()
-                    .unbounded_send(ConnectionMessage {
+                    .unbounded_send(ConnectionCommand::Data(ConnectionMessage {
                         payload,
                         socket_closed: active_connection.is_closed,
-                    })
+                        credit,
+                    }))
                 {
                     error!("WTF IS THIS: {:?}", err);
                 }
@@ -169,6 +236,54 @@ impl Controller {
         }
     }
 
+    fn send_datagram(
+        &mut self,
+        conn_id: ConnectionId,
+        remote_addr: RemoteAddress,
+        payload: Vec<u8>,
+    ) {
+        if let Some(datagram_sender) = self.active_datagram_associations.get(&conn_id) {
+            if datagram_sender
+                .unbounded_send(DatagramMessage {
+                    remote_addr,
+                    payload,
+                })
+                .is_err()
+            {
+                debug!("UDP association {} is already closed", conn_id);
+            }
+        } else {
+            // datagrams are unreliable by nature so there's no point in buffering them
+            debug!(
+                "Received a datagram for an unknown UDP association {} ({} bytes were dropped)",
+                conn_id,
+                payload.len()
+            );
+        }
+    }
+
+    fn grant_credit(&mut self, conn_id: ConnectionId, credit: u64) {
+        if let Some(active_connection) = self.active_connections.get_mut(&conn_id) {
+            if let Some(connection_sender) = active_connection.connection_sender.as_mut() {
+                if connection_sender
+                    .unbounded_send(ConnectionCommand::Credit(credit))
+                    .is_err()
+                {
+                    debug!("Connection {} is already closed", conn_id);
+                }
+            }
+        } else if !self.recently_closed.contains(&conn_id) {
+            debug!("Received a 'Credit' before 'Connect' - going to buffer it");
+            let pending = self.pending_credit.entry(conn_id).or_insert(credit);
+            *pending = (*pending).max(credit);
+        } else {
+            debug!(
+                "Received credit for an already closed connection {}",
+                conn_id
+            );
+        }
+    }
+
     pub async fn run(&mut self) {
         while let Some(command) = self.receiver.next().await {
             match command {
@@ -178,8 +293,116 @@ impl Controller {
                 ControllerCommand::Insert(conn_id, sender) => {
                     self.insert_connection(conn_id, sender)
                 }
+                ControllerCommand::InsertDatagram(conn_id, sender) => {
+                    self.insert_datagram_association(conn_id, sender)
+                }
                 ControllerCommand::Remove(conn_id) => self.remove_connection(conn_id),
+                ControllerCommand::SendDatagram(conn_id, remote_addr, data) => {
+                    self.send_datagram(conn_id, remote_addr, data)
+                }
+                ControllerCommand::Credit(conn_id, credit) => self.grant_credit(conn_id, credit),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::FutureExt;
+    use ordered_buffer::DEFAULT_WINDOW_SIZE;
+
+    fn ordered_payload(index: u64) -> Vec<u8> {
+        OrderedMessage {
+            data: vec![42; 1024],
+            index,
+        }
+        .into_bytes()
+    }
+
+    #[test]
+    fn ordered_buffer_is_bounded_by_the_window() {
+        let (mut controller, _) = Controller::new();
+        let (connection_sender, mut connection_receiver) = mpsc::unbounded();
+        controller.insert_connection(42, connection_sender);
+
+        // the remote lets us know it respects the credit from now on
+        let flow_control_start = OrderedMessage {
+            data: Vec::new(),
+            index: 0,
+        };
+        controller.send_to_connection(42, flow_control_start.into_bytes(), false);
+        assert!(connection_receiver.next().now_or_never().is_some());
+
+        // the next message never arrives so nothing can be forwarded to the connection
+        for index in 2..DEFAULT_WINDOW_SIZE * 4 {
+            controller.send_to_connection(42, ordered_payload(index), false);
+        }
+        assert_eq!(
+            controller.active_connections[&42].ordered_buffer.len() as u64,
+            DEFAULT_WINDOW_SIZE - 1
+        );
+        assert!(connection_receiver.next().now_or_never().is_none());
+
+        controller.send_to_connection(42, ordered_payload(1), false);
+        match connection_receiver.next().now_or_never().unwrap().unwrap() {
+            ConnectionCommand::Data(message) => {
+                assert_eq!(message.payload.len() as u64, DEFAULT_WINDOW_SIZE * 1024);
+                assert_eq!(message.credit, 2 * DEFAULT_WINDOW_SIZE + 1);
+            }
+            _ => panic!("expected connection data"),
+        }
+    }
+
+    #[test]
+    fn data_of_remotes_unaware_of_credit_is_never_dropped() {
+        let (mut controller, _) = Controller::new();
+        for index in 1..DEFAULT_WINDOW_SIZE * 4 {
+            controller.send_to_connection(42, ordered_payload(index), false);
+        }
+
+        let (connection_sender, mut connection_receiver) = mpsc::unbounded();
+        controller.insert_connection(42, connection_sender);
+        controller.send_to_connection(42, ordered_payload(0), false);
+        match connection_receiver.next().now_or_never().unwrap().unwrap() {
+            ConnectionCommand::Data(message) => {
+                assert_eq!(message.payload.len() as u64, DEFAULT_WINDOW_SIZE * 4 * 1024)
             }
+            _ => panic!("expected connection data"),
         }
     }
+
+    #[test]
+    fn credit_is_forwarded_to_the_connection() {
+        let (mut controller, _) = Controller::new();
+        let (connection_sender, mut connection_receiver) = mpsc::unbounded();
+        controller.insert_connection(42, connection_sender);
+
+        controller.grant_credit(42, 123);
+        assert!(matches!(
+            connection_receiver.next().now_or_never().unwrap().unwrap(),
+            ConnectionCommand::Credit(123)
+        ));
+    }
+
+    #[test]
+    fn credit_received_before_the_connection_is_not_lost() {
+        let (mut controller, _) = Controller::new();
+        // credit might get reordered as well
+        controller.grant_credit(42, 123);
+        controller.grant_credit(42, 100);
+
+        let (connection_sender, mut connection_receiver) = mpsc::unbounded();
+        controller.insert_connection(42, connection_sender);
+        assert!(matches!(
+            connection_receiver.next().now_or_never().unwrap().unwrap(),
+            ConnectionCommand::Credit(123)
+        ));
+        assert!(controller.pending_credit.is_empty());
+
+        // but it's not kept around for the connections that are already gone
+        controller.remove_connection(42);
+        controller.grant_credit(42, 456);
+        assert!(controller.pending_credit.is_empty());
+    }
 }
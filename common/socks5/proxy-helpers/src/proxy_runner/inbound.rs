@@ -12,7 +12,7 @@ use ordered_buffer::OrderedMessageSender;
 use socks5_requests::ConnectionId;
 use std::{io, sync::Arc};
 use tokio::select;
-use tokio::{net::tcp::OwnedReadHalf, sync::Notify, time::sleep};
+use tokio::{net::tcp::OwnedReadHalf, sync::watch, sync::Notify, time::sleep};
 
 fn send_empty_close<F, S>(
     connection_id: ConnectionId,
@@ -69,11 +69,14 @@ where
     if is_finished {
         // technically we already informed it when we sent the message to mixnet above
         debug!(target: &*format!("({}) socks5 inbound", connection_id), "The local socket is closed - won't receive any more data. Informing remote about that...");
+    } else if !message_sender.has_credit() {
+        debug!(target: &*format!("({}) socks5 inbound", connection_id), "Ran out of credit - won't read from the socket until remote grants more");
     }
 
     is_finished
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(super) async fn run_inbound<F, S>(
     mut reader: OwnedReadHalf,
     local_destination_address: String, // addresses are provided for better logging
@@ -81,6 +84,7 @@ pub(super) async fn run_inbound<F, S>(
     connection_id: ConnectionId,
     mix_sender: MixProxySender<S>,
     adapter_fn: F,
+    mut credit_receiver: watch::Receiver<u64>,
     shutdown_notify: Arc<Notify>,
 ) -> OwnedReadHalf
 where
@@ -92,13 +96,31 @@ where
 
     tokio::pin!(shutdown_future);
 
+    // once outbound is done, there's no one to tell us about new credit
+    let mut credit_open = true;
+
     loop {
         select! {
-            read_data = &mut available_reader.next() => {
+            // if we ran out of credit, stop reading so that the data would wait in the socket
+            // rather than in our memory (and the sender would eventually get slowed down by TCP)
+            read_data = &mut available_reader.next(), if message_sender.has_credit() => {
                 if deal_with_data(read_data, &local_destination_address, &remote_source_address, connection_id, &mut message_sender, &mix_sender, &adapter_fn) {
                     break
                 }
             }
+            credit_update = credit_receiver.changed(), if credit_open => {
+                if credit_update.is_ok() {
+                    let credit = *credit_receiver.borrow();
+                    if let Some(flow_control_start) = message_sender.grant_credit(credit) {
+                        // let the remote know that from now on we're going to respect its credit
+                        mix_sender
+                            .unbounded_send(adapter_fn(connection_id, flow_control_start.into_bytes(), false))
+                            .unwrap();
+                    }
+                } else {
+                    credit_open = false;
+                }
+            }
             _ = &mut shutdown_future => {
                 debug!("closing inbound proxy after outbound was closed {:?} ago", SHUTDOWN_TIMEOUT);
                 // inform remote just in case it was closed because of lack of heartbeat.
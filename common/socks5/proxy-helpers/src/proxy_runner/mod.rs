@@ -5,7 +5,7 @@ use crate::connection_controller::ConnectionReceiver;
 use futures::channel::mpsc;
 use socks5_requests::ConnectionId;
 use std::{sync::Arc, time::Duration};
-use tokio::{net::TcpStream, sync::Notify};
+use tokio::{net::TcpStream, sync::watch, sync::Notify};
 
 mod inbound;
 mod outbound;
@@ -44,6 +44,10 @@ pub struct ProxyRunner<S> {
     local_destination_address: String,
     remote_source_address: String,
     connection_id: ConnectionId,
+
+    /// indicates whether we should advertise the credit to the remote before it has shown
+    /// it supports the flow control itself
+    announce_credit: bool,
 }
 
 impl<S> ProxyRunner<S>
@@ -57,6 +61,7 @@ where
         mix_receiver: ConnectionReceiver,
         mix_sender: MixProxySender<S>,
         connection_id: ConnectionId,
+        announce_credit: bool,
     ) -> Self {
         ProxyRunner {
             mix_receiver: Some(mix_receiver),
@@ -65,18 +70,28 @@ where
             local_destination_address,
             remote_source_address,
             connection_id,
+            announce_credit,
         }
     }
 
     // The `adapter_fn` is used to transform whatever was read into appropriate
     // request/response as required by entity running particular side of the proxy.
-    pub async fn run<F>(mut self, adapter_fn: F) -> Self
+    // Similarly, the `credit_adapter_fn` is used to transform credit granted to the remote
+    // (after the data it sent got written onto the socket) into appropriate request/response.
+    // Note that only one side of the proxy (i.e. the socks5 client) should announce the credit
+    // right away. The other one should wait until it's sure the remote is going to understand it.
+    pub async fn run<F, C>(mut self, adapter_fn: F, credit_adapter_fn: C) -> Self
     where
         F: Fn(ConnectionId, Vec<u8>, bool) -> S + Send + 'static,
+        C: Fn(ConnectionId, u64) -> S + Send + 'static,
     {
         let (read_half, write_half) = self.socket.take().unwrap().into_split();
         let shutdown_notify = Arc::new(Notify::new());
 
+        // credit granted by the remote arrives alongside the data to write, so outbound
+        // has to let inbound know about it
+        let (credit_sender, credit_receiver) = watch::channel(0);
+
         // should run until either inbound closes or is notified from outbound
         let inbound_future = inbound::run_inbound(
             read_half,
@@ -85,6 +100,7 @@ where
             self.connection_id,
             self.mix_sender.clone(),
             adapter_fn,
+            credit_receiver,
             Arc::clone(&shutdown_notify),
         );
 
@@ -94,6 +110,10 @@ where
             self.remote_source_address.clone(),
             self.mix_receiver.take().unwrap(),
             self.connection_id,
+            self.mix_sender.clone(),
+            credit_adapter_fn,
+            self.announce_credit,
+            credit_sender,
             shutdown_notify,
         );
 
@@ -125,3 +145,197 @@ where
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection_controller::{ConnectionCommand, ConnectionMessage};
+    use futures::{FutureExt, StreamExt};
+    use ordered_buffer::DEFAULT_WINDOW_SIZE;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::time::sleep;
+
+    #[derive(Debug)]
+    enum TestMessage {
+        Data,
+        Credit(u64),
+    }
+
+    async fn socket_pair() -> (TcpStream, TcpStream) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let remote = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (local, _) = listener.accept().await.unwrap();
+        (local, remote)
+    }
+
+    fn spawn_runner(
+        socket: TcpStream,
+        mix_receiver: ConnectionReceiver,
+        mix_sender: MixProxySender<TestMessage>,
+        announce_credit: bool,
+    ) {
+        let runner = ProxyRunner::new(
+            socket,
+            "local".to_string(),
+            "remote".to_string(),
+            mix_receiver,
+            mix_sender,
+            42,
+            announce_credit,
+        );
+        tokio::spawn(runner.run(
+            |_, _, _| TestMessage::Data,
+            |_, credit| TestMessage::Credit(credit),
+        ));
+    }
+
+    fn count_sent_data(mix_receiver: &mut mpsc::UnboundedReceiver<TestMessage>) -> u64 {
+        let mut sent = 0;
+        while let Some(Some(message)) = mix_receiver.next().now_or_never() {
+            assert!(matches!(message, TestMessage::Data));
+            sent += 1;
+        }
+        sent
+    }
+
+    fn write_chunks_slowly(mut remote: TcpStream, chunks: u64) {
+        tokio::spawn(async move {
+            for _ in 0..chunks {
+                remote.write_all(&[42; 1024]).await.unwrap();
+                sleep(Duration::from_millis(5)).await;
+            }
+            // keep the socket open
+            sleep(Duration::from_secs(10)).await;
+        });
+    }
+
+    fn next_message(mix_receiver: &mut mpsc::UnboundedReceiver<TestMessage>) -> TestMessage {
+        mix_receiver.next().now_or_never().unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn reading_from_socket_is_paused_when_credit_runs_out() {
+        let (local, remote) = socket_pair().await;
+        let (connection_sender, connection_receiver) = mpsc::unbounded();
+        let (mix_sender, mut mix_receiver) = mpsc::unbounded();
+        spawn_runner(local, connection_receiver, mix_sender, true);
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(matches!(
+            next_message(&mut mix_receiver),
+            TestMessage::Credit(DEFAULT_WINDOW_SIZE)
+        ));
+        connection_sender
+            .unbounded_send(ConnectionCommand::Credit(DEFAULT_WINDOW_SIZE))
+            .unwrap();
+
+        // remote keeps on pushing data way faster than we could have sent it through the mixnet
+        write_chunks_slowly(remote, DEFAULT_WINDOW_SIZE * 4);
+        sleep(Duration::from_millis(DEFAULT_WINDOW_SIZE * 4 * 5 + 500)).await;
+        // (including the empty message marking the start of the flow control)
+        assert_eq!(count_sent_data(&mut mix_receiver), DEFAULT_WINDOW_SIZE);
+
+        connection_sender
+            .unbounded_send(ConnectionCommand::Credit(DEFAULT_WINDOW_SIZE + 10))
+            .unwrap();
+        sleep(Duration::from_millis(200)).await;
+        // the data that was waiting in the socket might get read in bigger chunks now
+        let sent = count_sent_data(&mut mix_receiver);
+        assert!(sent > 0 && sent <= 10);
+    }
+
+    #[tokio::test]
+    async fn reading_from_socket_is_not_limited_until_remote_grants_credit() {
+        let (local, remote) = socket_pair().await;
+        let (_connection_sender, connection_receiver) = mpsc::unbounded();
+        let (mix_sender, mut mix_receiver) = mpsc::unbounded();
+        spawn_runner(local, connection_receiver, mix_sender, false);
+
+        // remote might be running an older version that's never going to grant us any credit
+        write_chunks_slowly(remote, DEFAULT_WINDOW_SIZE * 4);
+        sleep(Duration::from_millis(DEFAULT_WINDOW_SIZE * 4 * 5 + 500)).await;
+        assert!(count_sent_data(&mut mix_receiver) > DEFAULT_WINDOW_SIZE);
+    }
+
+    #[tokio::test]
+    async fn credit_is_advertised_after_data_is_written_to_socket() {
+        let (local, mut remote) = socket_pair().await;
+        let (connection_sender, connection_receiver) = mpsc::unbounded();
+        let (mix_sender, mut mix_receiver) = mpsc::unbounded();
+        spawn_runner(local, connection_receiver, mix_sender, true);
+
+        sleep(Duration::from_millis(100)).await;
+        assert!(matches!(
+            next_message(&mut mix_receiver),
+            TestMessage::Credit(DEFAULT_WINDOW_SIZE)
+        ));
+
+        // not worth advertising yet
+        connection_sender
+            .unbounded_send(ConnectionCommand::Data(ConnectionMessage {
+                payload: vec![1, 2, 3],
+                socket_closed: false,
+                credit: DEFAULT_WINDOW_SIZE + 1,
+            }))
+            .unwrap();
+        connection_sender
+            .unbounded_send(ConnectionCommand::Data(ConnectionMessage {
+                payload: vec![4, 5, 6],
+                socket_closed: false,
+                credit: DEFAULT_WINDOW_SIZE * 2,
+            }))
+            .unwrap();
+
+        let mut received = [0u8; 6];
+        remote.read_exact(&mut received).await.unwrap();
+        assert_eq!(received, [1, 2, 3, 4, 5, 6]);
+
+        sleep(Duration::from_millis(100)).await;
+        match next_message(&mut mix_receiver) {
+            TestMessage::Credit(credit) => assert_eq!(credit, DEFAULT_WINDOW_SIZE * 2),
+            message => panic!("unexpected message {:?}", message),
+        }
+        assert!(mix_receiver.next().now_or_never().is_none());
+    }
+
+    #[tokio::test]
+    async fn credit_is_only_advertised_once_remote_supports_it() {
+        let (local, mut remote) = socket_pair().await;
+        let (connection_sender, connection_receiver) = mpsc::unbounded();
+        let (mix_sender, mut mix_receiver) = mpsc::unbounded();
+        spawn_runner(local, connection_receiver, mix_sender, false);
+
+        connection_sender
+            .unbounded_send(ConnectionCommand::Data(ConnectionMessage {
+                payload: vec![1, 2, 3],
+                socket_closed: false,
+                credit: DEFAULT_WINDOW_SIZE * 2,
+            }))
+            .unwrap();
+        let mut received = [0u8; 3];
+        remote.read_exact(&mut received).await.unwrap();
+        sleep(Duration::from_millis(100)).await;
+        assert!(mix_receiver.next().now_or_never().is_none());
+
+        // remote has shown it understands the credit, so we can tell it about ours
+        // and start respecting its credit
+        connection_sender
+            .unbounded_send(ConnectionCommand::Credit(DEFAULT_WINDOW_SIZE))
+            .unwrap();
+        sleep(Duration::from_millis(100)).await;
+        let messages = [
+            next_message(&mut mix_receiver),
+            next_message(&mut mix_receiver),
+        ];
+        assert!(messages
+            .iter()
+            .any(|message| matches!(message, TestMessage::Credit(credit) if *credit == DEFAULT_WINDOW_SIZE * 2)));
+        assert!(messages
+            .iter()
+            .any(|message| matches!(message, TestMessage::Data)));
+        assert!(mix_receiver.next().now_or_never().is_none());
+    }
+}
@@ -1,19 +1,73 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use super::MixProxySender;
 use super::SHUTDOWN_TIMEOUT;
-use crate::connection_controller::{ConnectionMessage, ConnectionReceiver};
+use crate::connection_controller::{ConnectionCommand, ConnectionMessage, ConnectionReceiver};
 use futures::FutureExt;
 use futures::StreamExt;
 use log::*;
+use ordered_buffer::DEFAULT_WINDOW_SIZE;
 use socks5_requests::ConnectionId;
 use std::{sync::Arc, time::Duration};
 use tokio::io::AsyncWriteExt;
 use tokio::select;
-use tokio::{net::tcp::OwnedWriteHalf, sync::Notify, time::sleep, time::Instant};
+use tokio::{net::tcp::OwnedWriteHalf, sync::watch, sync::Notify, time::sleep, time::Instant};
 
 const MIX_TTL: Duration = Duration::from_secs(5 * 60);
 
+// don't send a separate message through the mixnet for every single chunk of data we consumed,
+// the remote still has plenty of credit left at this point
+const CREDIT_ADVERTISEMENT_THRESHOLD: u64 = DEFAULT_WINDOW_SIZE / 2;
+
+/// Keeps track of the credit exchanged with the remote in both directions.
+struct CreditState {
+    /// Indicates whether the remote is known to understand credit messages, i.e. whether
+    /// we can advertise the credit to it.
+    remote_supports_credit: bool,
+
+    /// The highest credit we are willing to give to the remote.
+    available: u64,
+
+    /// The highest credit we have advertised to the remote.
+    advertised: u64,
+
+    /// The highest credit the remote has granted us.
+    granted: u64,
+}
+
+fn advertise_credit<F, S>(
+    credit_state: &mut CreditState,
+    connection_id: ConnectionId,
+    mix_sender: &MixProxySender<S>,
+    credit_adapter_fn: &F,
+) where
+    F: Fn(ConnectionId, u64) -> S,
+{
+    // older remotes would have no idea what to do with the credit and would just complain about it
+    if !credit_state.remote_supports_credit {
+        return;
+    }
+
+    let credit = credit_state.available;
+    if credit < credit_state.advertised + CREDIT_ADVERTISEMENT_THRESHOLD {
+        return;
+    }
+
+    trace!(
+        target: &*format!("({}) socks5 outbound", connection_id),
+        "Granting credit up to message {} to the remote",
+        credit
+    );
+    credit_state.advertised = credit;
+    if mix_sender
+        .unbounded_send(credit_adapter_fn(connection_id, credit))
+        .is_err()
+    {
+        debug!(target: &*format!("({}) socks5 outbound", connection_id), "failed to grant credit to the remote - the mixnet sender is gone");
+    }
+}
+
 async fn deal_with_message(
     connection_message: ConnectionMessage,
     writer: &mut OwnedWriteHalf,
@@ -43,25 +97,66 @@ async fn deal_with_message(
     false
 }
 
-pub(super) async fn run_outbound(
+#[allow(clippy::too_many_arguments)]
+pub(super) async fn run_outbound<F, S>(
     mut writer: OwnedWriteHalf,
     local_destination_address: String, // addresses are provided for better logging
     remote_source_address: String,
     mut mix_receiver: ConnectionReceiver,
     connection_id: ConnectionId,
+    mix_sender: MixProxySender<S>,
+    credit_adapter_fn: F,
+    announce_credit: bool,
+    credit_sender: watch::Sender<u64>,
     shutdown_notify: Arc<Notify>,
-) -> (OwnedWriteHalf, ConnectionReceiver) {
+) -> (OwnedWriteHalf, ConnectionReceiver)
+where
+    F: Fn(ConnectionId, u64) -> S + Send + 'static,
+{
     let shutdown_future = shutdown_notify.notified().then(|_| sleep(SHUTDOWN_TIMEOUT));
     tokio::pin!(shutdown_future);
 
     let mut mix_timeout = Box::pin(sleep(MIX_TTL));
 
+    // the remote is not limited in any way until we advertise the credit to it and nor are we
+    let mut credit_state = CreditState {
+        remote_supports_credit: announce_credit,
+        available: DEFAULT_WINDOW_SIZE,
+        advertised: 0,
+        granted: 0,
+    };
+    advertise_credit(
+        &mut credit_state,
+        connection_id,
+        &mix_sender,
+        &credit_adapter_fn,
+    );
+
     loop {
         select! {
-            connection_message = &mut mix_receiver.next() => {
-                if let Some(connection_message) = connection_message {
-                    if deal_with_message(connection_message, &mut writer, &local_destination_address, &remote_source_address, connection_id).await {
-                        break;
+            connection_command = &mut mix_receiver.next() => {
+                if let Some(connection_command) = connection_command {
+                    match connection_command {
+                        ConnectionCommand::Data(connection_message) => {
+                            let credit = connection_message.credit;
+                            if deal_with_message(connection_message, &mut writer, &local_destination_address, &remote_source_address, connection_id).await {
+                                break;
+                            }
+                            // only now the data has left our memory, so the remote can send some more
+                            credit_state.available = credit_state.available.max(credit);
+                            advertise_credit(&mut credit_state, connection_id, &mix_sender, &credit_adapter_fn);
+                        }
+                        ConnectionCommand::Credit(credit) => {
+                            if !credit_state.remote_supports_credit {
+                                credit_state.remote_supports_credit = true;
+                                advertise_credit(&mut credit_state, connection_id, &mix_sender, &credit_adapter_fn);
+                            }
+                            if credit > credit_state.granted {
+                                credit_state.granted = credit;
+                                // if inbound is already gone, we don't care about the credit anymore
+                                let _ = credit_sender.send(credit);
+                            }
+                        }
                     }
                     mix_timeout.as_mut().reset(Instant::now() + MIX_TTL);
                 } else {
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::request::{Request, RequestError};
-use crate::response::{Response, ResponseError};
+use crate::response::{CreditResponse, DatagramResponse, Response, ResponseError};
 
 #[derive(Debug)]
 pub enum MessageError {
@@ -26,19 +26,27 @@ impl std::fmt::Display for MessageError {
 pub enum Message {
     Request(Request),
     Response(Response),
+    DatagramResponse(DatagramResponse),
+    CreditResponse(CreditResponse),
 }
 
 impl Message {
     const REQUEST_FLAG: u8 = 0;
     const RESPONSE_FLAG: u8 = 1;
+    const DATAGRAM_RESPONSE_FLAG: u8 = 2;
+    const CREDIT_RESPONSE_FLAG: u8 = 3;
 
     pub fn conn_id(&self) -> u64 {
         match self {
             Message::Request(req) => match req {
                 Request::Connect(c) => c.conn_id,
                 Request::Send(conn_id, _, _) => *conn_id,
+                Request::SendDatagram(d) => d.conn_id,
+                Request::Credit(conn_id, _) => *conn_id,
             },
             Message::Response(resp) => resp.connection_id,
+            Message::DatagramResponse(resp) => resp.connection_id,
+            Message::CreditResponse(resp) => resp.connection_id,
         }
     }
 
@@ -47,8 +55,12 @@ impl Message {
             Message::Request(req) => match req {
                 Request::Connect(_) => 0,
                 Request::Send(_, data, _) => data.len(),
+                Request::SendDatagram(d) => d.data.len(),
+                Request::Credit(..) => 0,
             },
             Message::Response(resp) => resp.data.len(),
+            Message::DatagramResponse(resp) => resp.data.len(),
+            Message::CreditResponse(_) => 0,
         }
     }
 
@@ -65,6 +77,14 @@ impl Message {
             Response::try_from_bytes(&b[1..])
                 .map(Message::Response)
                 .map_err(MessageError::Response)
+        } else if b[0] == Self::DATAGRAM_RESPONSE_FLAG {
+            DatagramResponse::try_from_bytes(&b[1..])
+                .map(Message::DatagramResponse)
+                .map_err(MessageError::Response)
+        } else if b[0] == Self::CREDIT_RESPONSE_FLAG {
+            CreditResponse::try_from_bytes(&b[1..])
+                .map(Message::CreditResponse)
+                .map_err(MessageError::Response)
         } else {
             Err(MessageError::UnknownMessageType)
         }
@@ -78,6 +98,12 @@ impl Message {
             Self::Response(r) => std::iter::once(Self::RESPONSE_FLAG)
                 .chain(r.into_bytes().iter().cloned())
                 .collect(),
+            Self::DatagramResponse(r) => std::iter::once(Self::DATAGRAM_RESPONSE_FLAG)
+                .chain(r.into_bytes().iter().cloned())
+                .collect(),
+            Self::CreditResponse(r) => std::iter::once(Self::CREDIT_RESPONSE_FLAG)
+                .chain(r.into_bytes().iter().cloned())
+                .collect(),
         }
     }
 }
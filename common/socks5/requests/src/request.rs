@@ -10,6 +10,8 @@ pub type RemoteAddress = String;
 pub enum RequestFlag {
     Connect = 0,
     Send = 1,
+    SendDatagram = 2,
+    Credit = 3,
 }
 
 #[derive(Debug)]
@@ -17,6 +19,7 @@ pub enum RequestError {
     AddressLengthTooShort,
     AddressTooShort,
     ConnectionIdTooShort,
+    CreditTooShort,
     NoData,
     UnknownRequestFlag,
     ReturnAddressTooShort,
@@ -33,6 +36,9 @@ impl fmt::Display for RequestError {
             RequestError::ConnectionIdTooShort => {
                 write!(f, "not enough bytes to recover the connection id")
             }
+            RequestError::CreditTooShort => {
+                write!(f, "not enough bytes to recover the granted credit")
+            }
             RequestError::NoData => write!(f, "no data provided"),
             RequestError::UnknownRequestFlag => write!(f, "request of unknown type"),
             RequestError::ReturnAddressTooShort => write!(f, "too short return address"),
@@ -58,6 +64,8 @@ impl TryFrom<u8> for RequestFlag {
         match value {
             _ if value == (RequestFlag::Connect as u8) => Ok(Self::Connect),
             _ if value == (RequestFlag::Send as u8) => Ok(Self::Send),
+            _ if value == (RequestFlag::SendDatagram as u8) => Ok(Self::SendDatagram),
+            _ if value == (RequestFlag::Credit as u8) => Ok(Self::Credit),
             _ => Err(RequestError::UnknownRequestFlag),
         }
     }
@@ -70,6 +78,14 @@ pub struct ConnectRequest {
     pub return_address: Recipient,
 }
 
+#[derive(Debug)]
+pub struct DatagramRequest {
+    pub conn_id: ConnectionId,
+    pub remote_addr: RemoteAddress,
+    pub return_address: Recipient,
+    pub data: Vec<u8>,
+}
+
 /// A request from a SOCKS5 client that a Nym Socks5 service provider should
 /// take an action for an application using a (probably local) Nym Socks5 proxy.
 #[derive(Debug)]
@@ -81,6 +97,15 @@ pub enum Request {
 
     /// Re-use an existing TCP connection, sending more request data up it.
     Send(ConnectionId, Vec<u8>, bool),
+
+    /// Send a single UDP datagram to the specified `RemoteAddress` as part of the UDP association
+    /// identified by the `ConnectionId`.
+    /// Any datagrams received back on this association should come back to the specified `Recipient`
+    SendDatagram(Box<DatagramRequest>),
+
+    /// Inform the service provider it is allowed to send responses on the connection identified
+    /// by the `ConnectionId` up to (excluding) the specified ordered message index.
+    Credit(ConnectionId, u64),
 }
 
 impl Request {
@@ -102,6 +127,64 @@ impl Request {
         Request::Send(conn_id, data, local_closed)
     }
 
+    /// Construct a new Request::SendDatagram instance
+    pub fn new_send_datagram(
+        conn_id: ConnectionId,
+        remote_addr: RemoteAddress,
+        return_address: Recipient,
+        data: Vec<u8>,
+    ) -> Request {
+        Request::SendDatagram(Box::new(DatagramRequest {
+            conn_id,
+            remote_addr,
+            return_address,
+            data,
+        }))
+    }
+
+    /// Construct a new Request::Credit instance
+    pub fn new_credit(conn_id: ConnectionId, credit: u64) -> Request {
+        Request::Credit(conn_id, credit)
+    }
+
+    /// Attempts to recover remote address and the return address from the provided bytes.
+    /// Returns the recovered values alongside any remaining bytes.
+    fn recover_addresses(b: &[u8]) -> Result<(RemoteAddress, Recipient, &[u8]), RequestError> {
+        // we need to be able to read at least 2 bytes that specify address length
+        if b.len() < 2 {
+            return Err(RequestError::AddressLengthTooShort);
+        }
+
+        let address_length = u16::from_be_bytes([b[0], b[1]]) as usize;
+
+        if b.len() < 2 + address_length {
+            return Err(RequestError::AddressTooShort);
+        }
+
+        let address_start = 2;
+        let address_end = address_start + address_length;
+        let address_bytes = &b[address_start..address_end];
+        let remote_address = String::from_utf8_lossy(address_bytes).to_string();
+
+        // just a temporary reference to mid-slice for ease of use
+        let recipient_data_bytes = &b[address_end..];
+
+        if recipient_data_bytes.len() < Recipient::LEN {
+            return Err(RequestError::ReturnAddressTooShort);
+        }
+
+        let mut return_bytes = [0u8; Recipient::LEN];
+        return_bytes.copy_from_slice(&recipient_data_bytes[..Recipient::LEN]);
+        let return_address = Recipient::try_from_bytes(return_bytes)
+            .map_err(RequestError::MalformedReturnAddress)?;
+
+        Ok((
+            remote_address,
+            return_address,
+            &recipient_data_bytes[Recipient::LEN..],
+        ))
+    }
+
     /// Deserialize the request type, connection id, destination address and port,
     /// and the request body from bytes.
     ///
@@ -113,8 +196,10 @@ impl Request {
     /// --------------------------------------------------------------------------------------
     ///
     /// The request_flag tells us whether this is a new connection request (`new_connect`),
-    /// an already-established connection we should send up (`new_send`), or
-    /// a request to close an established connection (`new_close`).
+    /// an already-established connection we should send up (`new_send`),
+    /// a request to close an established connection (`new_close`),
+    /// an UDP datagram that should be sent to the remote (`new_send_datagram`), or
+    /// a credit granted for sending responses on an established connection (`new_credit`).
     pub fn try_from_bytes(b: &[u8]) -> Result<Request, RequestError> {
         // each request needs to at least contain flag and ConnectionId
         if b.is_empty() {
@@ -127,38 +212,12 @@ impl Request {
         let connection_id = u64::from_be_bytes([b[1], b[2], b[3], b[4], b[5], b[6], b[7], b[8]]);
         match RequestFlag::try_from(b[0])? {
             RequestFlag::Connect => {
-                let connect_request_bytes = &b[9..];
+                let (remote_address, return_address, remaining) = Self::recover_addresses(&b[9..])?;
 
-                // we need to be able to read at least 2 bytes that specify address length
-                if connect_request_bytes.len() < 2 {
-                    return Err(RequestError::AddressLengthTooShort);
-                }
-
-                let address_length =
-                    u16::from_be_bytes([connect_request_bytes[0], connect_request_bytes[1]])
-                        as usize;
-
-                if connect_request_bytes.len() < 2 + address_length {
-                    return Err(RequestError::AddressTooShort);
-                }
-
-                let address_start = 2;
-                let address_end = address_start + address_length;
-                let address_bytes = &connect_request_bytes[address_start..address_end];
-                let remote_address = String::from_utf8_lossy(address_bytes).to_string();
-
-                // just a temporary reference to mid-slice for ease of use
-                let recipient_data_bytes = &connect_request_bytes[address_end..];
-
-                if recipient_data_bytes.len() != Recipient::LEN {
+                if !remaining.is_empty() {
                     return Err(RequestError::ReturnAddressTooShort);
                 }
 
-                let mut return_bytes = [0u8; Recipient::LEN];
-                return_bytes.copy_from_slice(&recipient_data_bytes[..Recipient::LEN]);
-                let return_address = Recipient::try_from_bytes(return_bytes)
-                    .map_err(RequestError::MalformedReturnAddress)?;
-
                 Ok(Request::new_connect(
                     connection_id,
                     remote_address,
@@ -171,6 +230,25 @@ impl Request {
 
                 Ok(Request::Send(connection_id, data, local_closed))
             }
+            RequestFlag::SendDatagram => {
+                let (remote_address, return_address, data) = Self::recover_addresses(&b[9..])?;
+
+                Ok(Request::new_send_datagram(
+                    connection_id,
+                    remote_address,
+                    return_address,
+                    data.to_vec(),
+                ))
+            }
+            RequestFlag::Credit => {
+                if b.len() < 17 {
+                    return Err(RequestError::CreditTooShort);
+                }
+                let credit =
+                    u64::from_be_bytes([b[9], b[10], b[11], b[12], b[13], b[14], b[15], b[16]]);
+
+                Ok(Request::new_credit(connection_id, credit))
+            }
         }
     }
 
@@ -196,6 +274,24 @@ impl Request {
                 .chain(std::iter::once(local_closed as u8))
                 .chain(data.into_iter())
                 .collect(),
+            // datagram is: DATAGRAM_FLAG || CONN_ID || REMOTE_LEN || REMOTE || RETURN || DATA
+            Request::SendDatagram(req) => {
+                let remote_address_bytes = req.remote_addr.into_bytes();
+                let remote_address_bytes_len = remote_address_bytes.len() as u16;
+
+                std::iter::once(RequestFlag::SendDatagram as u8)
+                    .chain(req.conn_id.to_be_bytes().iter().cloned())
+                    .chain(remote_address_bytes_len.to_be_bytes().iter().cloned())
+                    .chain(remote_address_bytes.into_iter())
+                    .chain(req.return_address.to_bytes().iter().cloned())
+                    .chain(req.data.into_iter())
+                    .collect()
+            }
+            // credit is: CREDIT_FLAG || CONN_ID || CREDIT
+            Request::Credit(conn_id, credit) => std::iter::once(RequestFlag::Credit as u8)
+                .chain(conn_id.to_be_bytes().iter().cloned())
+                .chain(credit.to_be_bytes().iter().cloned())
+                .collect(),
         }
     }
 }
@@ -482,4 +578,119 @@ mod request_deserialization_tests {
             }
         }
     }
+
+    #[cfg(test)]
+    mod sending_datagrams {
+        use super::*;
+
+        #[test]
+        fn returns_error_when_return_address_is_too_short() {
+            // this one has "foo.com:53" remote address and correct 8 bytes of connection_id
+            let request_bytes = [
+                RequestFlag::SendDatagram as u8,
+                1,
+                2,
+                3,
+                4,
+                5,
+                6,
+                7,
+                8,
+                0,
+                10,
+                102,
+                111,
+                111,
+                46,
+                99,
+                111,
+                109,
+                58,
+                53,
+                51,
+            ]
+            .to_vec();
+
+            let recipient = Recipient::try_from_base58_string("CytBseW6yFXUMzz4SGAKdNLGR7q3sJLLYxyBGvutNEQV.4QXYyEVc5fUDjmmi8PrHN9tdUFV4PCvSJE1278cHyvoe@4sBbL1ngf1vtNqykydQKTFh26sQCw888GpUqvPvyNB4f").unwrap();
+            let recipient_bytes = recipient.to_bytes();
+
+            let request_bytes: Vec<_> = request_bytes
+                .into_iter()
+                .chain(recipient_bytes.iter().take(40).cloned())
+                .collect();
+
+            match Request::try_from_bytes(&request_bytes).unwrap_err() {
+                RequestError::ReturnAddressTooShort => {}
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn can_be_recovered_from_serialized_bytes() {
+            let recipient = Recipient::try_from_base58_string("CytBseW6yFXUMzz4SGAKdNLGR7q3sJLLYxyBGvutNEQV.4QXYyEVc5fUDjmmi8PrHN9tdUFV4PCvSJE1278cHyvoe@4sBbL1ngf1vtNqykydQKTFh26sQCw888GpUqvPvyNB4f").unwrap();
+            let request = Request::new_send_datagram(
+                u64::from_be_bytes([1, 2, 3, 4, 5, 6, 7, 8]),
+                "foo.com:53".to_string(),
+                recipient,
+                vec![255, 255, 255],
+            );
+
+            let request_bytes = request.into_bytes();
+            match Request::try_from_bytes(&request_bytes).unwrap() {
+                Request::SendDatagram(req) => {
+                    assert_eq!("foo.com:53".to_string(), req.remote_addr);
+                    assert_eq!(u64::from_be_bytes([1, 2, 3, 4, 5, 6, 7, 8]), req.conn_id);
+                    assert_eq!(
+                        req.return_address.to_bytes().to_vec(),
+                        recipient.to_bytes().to_vec()
+                    );
+                    assert_eq!(vec![255, 255, 255], req.data);
+                }
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn works_without_data() {
+            let recipient = Recipient::try_from_base58_string("CytBseW6yFXUMzz4SGAKdNLGR7q3sJLLYxyBGvutNEQV.4QXYyEVc5fUDjmmi8PrHN9tdUFV4PCvSJE1278cHyvoe@4sBbL1ngf1vtNqykydQKTFh26sQCw888GpUqvPvyNB4f").unwrap();
+            let request =
+                Request::new_send_datagram(42, "1.1.1.1:53".to_string(), recipient, Vec::new());
+
+            let request_bytes = request.into_bytes();
+            match Request::try_from_bytes(&request_bytes).unwrap() {
+                Request::SendDatagram(req) => {
+                    assert_eq!("1.1.1.1:53".to_string(), req.remote_addr);
+                    assert_eq!(42, req.conn_id);
+                    assert!(req.data.is_empty());
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[cfg(test)]
+    mod granting_credit {
+        use super::*;
+
+        #[test]
+        fn returns_error_when_credit_is_too_short() {
+            let request_bytes = [RequestFlag::Credit as u8, 1, 2, 3, 4, 5, 6, 7, 8, 0, 0].to_vec();
+            match Request::try_from_bytes(&request_bytes).unwrap_err() {
+                RequestError::CreditTooShort => {}
+                _ => unreachable!(),
+            }
+        }
+
+        #[test]
+        fn can_be_recovered_from_serialized_bytes() {
+            let request_bytes = Request::new_credit(42, 128).into_bytes();
+            match Request::try_from_bytes(&request_bytes).unwrap() {
+                Request::Credit(conn_id, credit) => {
+                    assert_eq!(42, conn_id);
+                    assert_eq!(128, credit);
+                }
+                _ => unreachable!(),
+            }
+        }
+    }
 }
@@ -1,8 +1,11 @@
-use crate::ConnectionId;
+use crate::{ConnectionId, RemoteAddress};
 
 #[derive(Debug, PartialEq)]
 pub enum ResponseError {
     ConnectionIdTooShort,
+    AddressLengthTooShort,
+    AddressTooShort,
+    CreditTooShort,
     NoData,
 }
 /// A remote network response retrieved by the Socks5 service provider. This
@@ -64,6 +67,131 @@ impl Response {
     }
 }
 
+/// A single UDP datagram received by the Socks5 service provider on an UDP association.
+/// It can be serialized and sent back through the mixnet to the requesting application.
+#[derive(Debug)]
+pub struct DatagramResponse {
+    pub data: Vec<u8>,
+    pub connection_id: ConnectionId,
+    pub remote_addr: RemoteAddress,
+}
+
+impl DatagramResponse {
+    /// Constructor for datagram responses
+    pub fn new(connection_id: ConnectionId, remote_addr: RemoteAddress, data: Vec<u8>) -> Self {
+        DatagramResponse {
+            data,
+            connection_id,
+            remote_addr,
+        }
+    }
+
+    /// Serialized bytes looks like this:
+    ///
+    /// --------------------------------------------------------------
+    ///  connection_id | address_length | remote_address_bytes | data |
+    ///        8       |      2         |    address_length    | ...  |
+    /// --------------------------------------------------------------
+    pub fn try_from_bytes(b: &[u8]) -> Result<DatagramResponse, ResponseError> {
+        if b.is_empty() {
+            return Err(ResponseError::NoData);
+        }
+
+        if b.len() < 8 {
+            return Err(ResponseError::ConnectionIdTooShort);
+        }
+
+        let connection_id = u64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]);
+
+        if b.len() < 10 {
+            return Err(ResponseError::AddressLengthTooShort);
+        }
+
+        let address_length = u16::from_be_bytes([b[8], b[9]]) as usize;
+        let address_start = 10;
+        let address_end = address_start + address_length;
+
+        if b.len() < address_end {
+            return Err(ResponseError::AddressTooShort);
+        }
+
+        let remote_addr = String::from_utf8_lossy(&b[address_start..address_end]).to_string();
+        let data = b[address_end..].to_vec();
+
+        Ok(DatagramResponse::new(connection_id, remote_addr, data))
+    }
+
+    /// Serializes the datagram response into bytes so that it can be sent back through
+    /// the mixnet to the requesting application.
+    pub fn into_bytes(self) -> Vec<u8> {
+        let remote_address_bytes = self.remote_addr.into_bytes();
+        let remote_address_bytes_len = remote_address_bytes.len() as u16;
+
+        self.connection_id
+            .to_be_bytes()
+            .iter()
+            .cloned()
+            .chain(remote_address_bytes_len.to_be_bytes().iter().cloned())
+            .chain(remote_address_bytes.into_iter())
+            .chain(self.data.into_iter())
+            .collect()
+    }
+}
+
+/// Credit granted by the Socks5 service provider for sending requests on an established
+/// connection, i.e. the index of the first ordered message it is not yet willing to accept.
+#[derive(Debug)]
+pub struct CreditResponse {
+    pub connection_id: ConnectionId,
+    pub credit: u64,
+}
+
+impl CreditResponse {
+    /// Constructor for credit responses
+    pub fn new(connection_id: ConnectionId, credit: u64) -> Self {
+        CreditResponse {
+            connection_id,
+            credit,
+        }
+    }
+
+    /// Serialized bytes looks like this:
+    ///
+    /// --------------------------
+    ///  connection_id | credit |
+    ///        8       |   8    |
+    /// --------------------------
+    pub fn try_from_bytes(b: &[u8]) -> Result<CreditResponse, ResponseError> {
+        if b.is_empty() {
+            return Err(ResponseError::NoData);
+        }
+
+        if b.len() < 8 {
+            return Err(ResponseError::ConnectionIdTooShort);
+        }
+
+        if b.len() < 16 {
+            return Err(ResponseError::CreditTooShort);
+        }
+
+        let connection_id = u64::from_be_bytes([b[0], b[1], b[2], b[3], b[4], b[5], b[6], b[7]]);
+        let credit = u64::from_be_bytes([b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]]);
+
+        Ok(CreditResponse::new(connection_id, credit))
+    }
+
+    /// Serializes the credit response into bytes so that it can be sent back through
+    /// the mixnet to the requesting application.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.connection_id
+            .to_be_bytes()
+            .iter()
+            .cloned()
+            .chain(self.credit.to_be_bytes().iter().cloned())
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod constructing_socks5_responses_from_bytes {
     use super::*;
@@ -115,3 +243,83 @@ mod constructing_socks5_responses_from_bytes {
         assert_eq!(expected.is_closed, actual.is_closed);
     }
 }
+
+#[cfg(test)]
+mod constructing_socks5_datagram_responses_from_bytes {
+    use super::*;
+
+    #[test]
+    fn fails_when_zero_bytes_are_supplied() {
+        let response_bytes = Vec::new();
+
+        assert_eq!(
+            ResponseError::NoData,
+            DatagramResponse::try_from_bytes(&response_bytes).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn fails_when_connection_id_bytes_are_too_short() {
+        let response_bytes = vec![0, 1, 2, 3, 4, 5, 6];
+        assert_eq!(
+            ResponseError::ConnectionIdTooShort,
+            DatagramResponse::try_from_bytes(&response_bytes).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn fails_when_address_is_too_short() {
+        let response_bytes = vec![0, 1, 2, 3, 4, 5, 6, 7, 0];
+        assert_eq!(
+            ResponseError::AddressLengthTooShort,
+            DatagramResponse::try_from_bytes(&response_bytes).unwrap_err()
+        );
+
+        let response_bytes = vec![0, 1, 2, 3, 4, 5, 6, 7, 0, 5, 49, 46];
+        assert_eq!(
+            ResponseError::AddressTooShort,
+            DatagramResponse::try_from_bytes(&response_bytes).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn works_when_there_is_data() {
+        let response = DatagramResponse::new(
+            u64::from_be_bytes([0, 1, 2, 3, 4, 5, 6, 7]),
+            "1.1.1.1:53".to_string(),
+            vec![255, 255, 255],
+        );
+        let actual = DatagramResponse::try_from_bytes(&response.into_bytes()).unwrap();
+        assert_eq!(
+            u64::from_be_bytes([0, 1, 2, 3, 4, 5, 6, 7]),
+            actual.connection_id
+        );
+        assert_eq!("1.1.1.1:53", actual.remote_addr);
+        assert_eq!(vec![255, 255, 255], actual.data);
+    }
+}
+
+#[cfg(test)]
+mod constructing_socks5_credit_responses_from_bytes {
+    use super::*;
+
+    #[test]
+    fn fails_when_credit_bytes_are_too_short() {
+        let response_bytes = vec![0, 1, 2, 3, 4, 5, 6, 7, 0, 0];
+        assert_eq!(
+            ResponseError::CreditTooShort,
+            CreditResponse::try_from_bytes(&response_bytes).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn works_with_serialized_response() {
+        let response = CreditResponse::new(u64::from_be_bytes([0, 1, 2, 3, 4, 5, 6, 7]), 64);
+        let actual = CreditResponse::try_from_bytes(&response.into_bytes()).unwrap();
+        assert_eq!(
+            u64::from_be_bytes([0, 1, 2, 3, 4, 5, 6, 7]),
+            actual.connection_id
+        );
+        assert_eq!(64, actual.credit);
+    }
+}
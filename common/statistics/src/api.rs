@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::error::StatsError;
-use crate::StatsMessage;
+use crate::SignedStatsMessage;
 
 pub const DEFAULT_STATISTICS_SERVICE_ADDRESS: &str = "127.0.0.1";
 pub const DEFAULT_STATISTICS_SERVICE_PORT: u16 = 8090;
@@ -10,7 +10,7 @@ pub const DEFAULT_STATISTICS_SERVICE_PORT: u16 = 8090;
 pub const STATISTICS_SERVICE_VERSION: &str = "/v1";
 pub const STATISTICS_SERVICE_API_STATISTICS: &str = "statistic";
 
-pub fn build_statistics_request_bytes(msg: StatsMessage) -> Result<Vec<u8>, StatsError> {
+pub fn build_statistics_request_bytes(msg: SignedStatsMessage) -> Result<Vec<u8>, StatsError> {
     let json_msg = msg.to_json()?;
 
     let req = reqwest::Request::new(
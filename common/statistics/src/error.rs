@@ -1,10 +1,20 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crypto::asymmetric::identity::Ed25519RecoveryError;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
 pub enum StatsError {
     #[error("Serde JSON error: {0}")]
     SerdeJsonError(#[from] serde_json::Error),
+
+    #[error("The reporter identity key is malformed: {0}")]
+    MalformedReporterKey(Ed25519RecoveryError),
+
+    #[error("The signature is malformed: {0}")]
+    MalformedSignature(Ed25519RecoveryError),
+
+    #[error("The signature does not match the reporter identity")]
+    InvalidSignature,
 }
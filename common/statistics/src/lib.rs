@@ -1,6 +1,7 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use crypto::asymmetric::identity;
 use serde::{Deserialize, Serialize};
 
 use error::StatsError;
@@ -23,6 +24,48 @@ impl StatsMessage {
     pub fn from_json(s: &str) -> Result<Self, StatsError> {
         Ok(serde_json::from_str(s)?)
     }
+
+    /// Signs the message with the identity key of the reporter.
+    pub fn sign(&self, identity: &identity::KeyPair) -> Result<SignedStatsMessage, StatsError> {
+        let payload = self.to_json()?;
+        let signature = identity.private_key().sign(payload.as_bytes());
+
+        Ok(SignedStatsMessage {
+            reporter: identity.public_key().to_base58_string(),
+            payload,
+            signature: signature.to_base58_string(),
+        })
+    }
+}
+
+/// [`StatsMessage`] alongside the identity of its reporter and the signature on its JSON encoding.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SignedStatsMessage {
+    /// Base58 encoded ed25519 public key of the reporter.
+    pub reporter: String,
+    /// JSON encoded [`StatsMessage`], exactly as signed by the reporter.
+    pub payload: String,
+    /// Base58 encoded ed25519 signature on the payload.
+    pub signature: String,
+}
+
+impl SignedStatsMessage {
+    pub fn to_json(&self) -> Result<String, StatsError> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    /// Verifies the signature against the reporter identity and recovers the underlying message.
+    pub fn verify(&self) -> Result<StatsMessage, StatsError> {
+        let reporter = identity::PublicKey::from_base58_string(&self.reporter)
+            .map_err(StatsError::MalformedReporterKey)?;
+        let signature = identity::Signature::from_base58_string(&self.signature)
+            .map_err(StatsError::MalformedSignature)?;
+        reporter
+            .verify(self.payload.as_bytes(), &signature)
+            .map_err(|_| StatsError::InvalidSignature)?;
+
+        StatsMessage::from_json(&self.payload)
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
@@ -41,3 +84,67 @@ impl StatsServiceData {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_message() -> StatsMessage {
+        StatsMessage {
+            stats_data: vec![StatsServiceData::new("nymtech.net".to_string(), 10, 100)],
+            interval_seconds: 60,
+            timestamp: "2022-08-05T12:00:00+00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn signed_message_is_verified_against_its_reporter() {
+        let identity = identity::KeyPair::new(&mut rand::rngs::OsRng);
+        let signed = test_message().sign(&identity).unwrap();
+        assert_eq!(signed.reporter, identity.public_key().to_base58_string());
+
+        let recovered = signed.verify().unwrap();
+        assert_eq!(recovered.timestamp, test_message().timestamp);
+        assert_eq!(recovered.stats_data[0].requested_service, "nymtech.net");
+        assert_eq!(recovered.stats_data[0].response_bytes, 100);
+    }
+
+    #[test]
+    fn tampered_payload_is_rejected() {
+        let identity = identity::KeyPair::new(&mut rand::rngs::OsRng);
+        let mut signed = test_message().sign(&identity).unwrap();
+        signed.payload = signed.payload.replace("100", "1000");
+
+        assert!(matches!(signed.verify(), Err(StatsError::InvalidSignature)));
+    }
+
+    #[test]
+    fn message_signed_by_another_reporter_is_rejected() {
+        let identity = identity::KeyPair::new(&mut rand::rngs::OsRng);
+        let impersonated = identity::KeyPair::new(&mut rand::rngs::OsRng);
+        let mut signed = test_message().sign(&identity).unwrap();
+        signed.reporter = impersonated.public_key().to_base58_string();
+
+        assert!(matches!(signed.verify(), Err(StatsError::InvalidSignature)));
+    }
+
+    #[test]
+    fn malformed_keys_and_signatures_are_told_apart_from_invalid_signatures() {
+        let identity = identity::KeyPair::new(&mut rand::rngs::OsRng);
+        let signed = test_message().sign(&identity).unwrap();
+
+        let mut malformed_reporter = signed.clone();
+        malformed_reporter.reporter = "not a key".to_string();
+        assert!(matches!(
+            malformed_reporter.verify(),
+            Err(StatsError::MalformedReporterKey(_))
+        ));
+
+        let mut malformed_signature = signed;
+        malformed_signature.signature = "not a signature".to_string();
+        assert!(matches!(
+            malformed_signature.verify(),
+            Err(StatsError::MalformedSignature(_))
+        ));
+    }
+}
@@ -78,6 +78,7 @@ pub struct Node {
     // hostname every time we want to construct a path via this node
     pub mix_host: SocketAddr,
     pub clients_port: u16,
+    pub clients_wss_port: Option<u16>,
     pub identity_key: identity::PublicKey,
     pub sphinx_key: encryption::PublicKey, // TODO: or nymsphinx::PublicKey? both are x25519
     pub version: String,
@@ -91,6 +92,12 @@ impl Node {
     pub fn clients_address(&self) -> String {
         format!("ws://{}:{}", self.host, self.clients_port)
     }
+
+    /// Returns the `wss://` address of the gateway, if it accepts TLS-secured client connections.
+    pub fn clients_tls_address(&self) -> Option<String> {
+        self.clients_wss_port
+            .map(|port| format!("wss://{}:{}", self.host, port))
+    }
 }
 
 impl fmt::Display for Node {
@@ -140,6 +147,7 @@ impl<'a> TryFrom<&'a GatewayBond> for Node {
             host,
             mix_host,
             clients_port: bond.gateway.clients_port,
+            clients_wss_port: bond.gateway.clients_wss_port,
             identity_key: identity::PublicKey::from_base58_string(&bond.gateway.identity_key)?,
             sphinx_key: encryption::PublicKey::from_base58_string(&bond.gateway.sphinx_key)?,
             version: bond.gateway.version.clone(),
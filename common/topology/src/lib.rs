@@ -2,9 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::filter::VersionFilterable;
+use crate::route_selection::RouteSelection;
 use log::warn;
 use mixnet_contract_common::{GatewayBond, MixNodeBond};
-use nymsphinx_addressing::nodes::NodeIdentity;
+use nymsphinx_addressing::nodes::{NodeIdentity, NymNodeRoutingAddress};
 use nymsphinx_types::Node as SphinxNode;
 use rand::Rng;
 use std::collections::HashMap;
@@ -17,6 +18,9 @@ use std::str::FromStr;
 pub mod filter;
 pub mod gateway;
 pub mod mix;
+pub mod provider;
+pub mod route_selection;
+pub mod serialized;
 
 #[derive(Debug)]
 pub enum NymTopologyError {
@@ -26,6 +30,7 @@ pub enum NymTopologyError {
 
     InvalidNumberOfHopsError,
     NoMixesOnLayerAvailable(MixLayer),
+    NoSuitableMixesOnLayer(MixLayer),
 }
 
 #[derive(Debug, Clone)]
@@ -72,11 +77,34 @@ pub type MixLayer = u8;
 pub struct NymTopology {
     mixes: HashMap<MixLayer, Vec<mix::Node>>,
     gateways: Vec<gateway::Node>,
+    route_selection: RouteSelection,
 }
 
 impl NymTopology {
     pub fn new(mixes: HashMap<MixLayer, Vec<mix::Node>>, gateways: Vec<gateway::Node>) -> Self {
-        NymTopology { mixes, gateways }
+        NymTopology {
+            mixes,
+            gateways,
+            route_selection: Default::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_route_selection(mut self, route_selection: RouteSelection) -> Self {
+        self.route_selection = route_selection;
+        self
+    }
+
+    pub fn route_selection(&self) -> RouteSelection {
+        self.route_selection
+    }
+
+    /// Sets average uptimes of the mixnodes, keyed by their base58-encoded identity keys.
+    /// Uptimes of nodes not present in the map are cleared.
+    pub fn set_mix_uptimes(&mut self, uptimes: &HashMap<String, u8>) {
+        for mix in self.mixes.values_mut().flatten() {
+            mix.avg_uptime = uptimes.get(&mix.identity_key.to_base58_string()).copied();
+        }
     }
 
     pub fn mixes(&self) -> &HashMap<MixLayer, Vec<mix::Node>> {
@@ -115,43 +143,78 @@ impl NymTopology {
         self.gateways = gateways
     }
 
-    /// Returns a vec of size of `num_mix_hops` of mixnodes, such that each subsequent node is on
-    /// next layer, starting from layer 1
-    pub fn random_mix_route<R>(
+    fn random_mix_nodes<R>(
         &self,
         rng: &mut R,
         num_mix_hops: u8,
-    ) -> Result<Vec<SphinxNode>, NymTopologyError>
+    ) -> Result<Vec<&mix::Node>, NymTopologyError>
     where
-        // I don't think there's a need for this RNG to be crypto-secure
         R: Rng + ?Sized,
     {
-        use rand::seq::SliceRandom;
-
         if self.mixes.len() < num_mix_hops as usize {
             return Err(NymTopologyError::InvalidNumberOfHopsError);
         }
-        let mut route = Vec::with_capacity(num_mix_hops as usize);
 
-        // there is no "layer 0"
+        // get all mixes on each layer, there is no "layer 0"
+        let mut layers = Vec::with_capacity(num_mix_hops as usize);
         for layer in 1..=num_mix_hops {
-            // get all mixes on particular layer
-            let layer_mixes = self
-                .mixes
-                .get(&layer)
-                .ok_or(NymTopologyError::NoMixesOnLayerAvailable(layer))?;
-
-            // choose a random mix from the above list
-            // this can return a 'None' only if slice is empty
-            let random_mix = layer_mixes
-                .choose(rng)
-                .ok_or(NymTopologyError::NoMixesOnLayerAvailable(layer))?;
-            route.push(random_mix.into());
+            match self.mixes.get(&layer) {
+                Some(layer_mixes) if !layer_mixes.is_empty() => layers.push(layer_mixes),
+                _ => return Err(NymTopologyError::NoMixesOnLayerAvailable(layer)),
+            }
+        }
+
+        let mut route: Vec<&mix::Node> = Vec::with_capacity(num_mix_hops as usize);
+        // nodes that, given the current beginning of the route, lead to a dead end on each layer
+        let mut dead_ends: Vec<Vec<&mix::Node>> = vec![Vec::new(); layers.len()];
+        let mut deepest_failed_layer = 0;
+
+        while route.len() < layers.len() {
+            let depth = route.len();
+            // choose a random mix from the layer, according to our route selection rules
+            match self
+                .route_selection
+                .choose_node(rng, layers[depth], &route, &dead_ends[depth])
+            {
+                Some(random_mix) => route.push(random_mix),
+                None => {
+                    // no node on this layer fits the route, so backtrack and replace
+                    // the node chosen for the previous layer instead
+                    deepest_failed_layer = deepest_failed_layer.max(depth as MixLayer + 1);
+                    dead_ends[depth].clear();
+                    match route.pop() {
+                        Some(previous_mix) => dead_ends[depth - 1].push(previous_mix),
+                        None => {
+                            return Err(NymTopologyError::NoSuitableMixesOnLayer(
+                                deepest_failed_layer,
+                            ))
+                        }
+                    }
+                }
+            }
         }
 
         Ok(route)
     }
 
+    /// Returns a vec of size of `num_mix_hops` of mixnodes, such that each subsequent node is on
+    /// next layer, starting from layer 1
+    pub fn random_mix_route<R>(
+        &self,
+        rng: &mut R,
+        num_mix_hops: u8,
+    ) -> Result<Vec<SphinxNode>, NymTopologyError>
+    where
+        // I don't think there's a need for this RNG to be crypto-secure
+        R: Rng + ?Sized,
+    {
+        Ok(self
+            .random_mix_nodes(rng, num_mix_hops)?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
     /// Tries to create a route to the specified gateway, such that it goes through mixnode on layer 1,
     /// mixnode on layer2, .... mixnode on layer n and finally the target gateway
     pub fn random_route_to_gateway<R>(
@@ -220,6 +283,31 @@ impl NymTopology {
         NymTopology {
             mixes: self.mixes.filter_by_version(expected_mix_version),
             gateways: self.gateways.filter_by_version(expected_gateway_version),
+            route_selection: self.route_selection,
+        }
+    }
+
+    /// Returns a copy of the topology without the mix nodes listening on any of the provided
+    /// addresses, for example, so that a new route would avoid nodes that might be misbehaving.
+    #[must_use]
+    pub fn without_mixes(&self, excluded: &[NymNodeRoutingAddress]) -> Self {
+        let mixes = self
+            .mixes
+            .iter()
+            .map(|(layer, nodes)| {
+                let nodes = nodes
+                    .iter()
+                    .filter(|node| !excluded.contains(&NymNodeRoutingAddress::from(node.mix_host)))
+                    .cloned()
+                    .collect();
+                (*layer, nodes)
+            })
+            .collect();
+
+        NymTopology {
+            mixes,
+            gateways: self.gateways.clone(),
+            route_selection: self.route_selection,
         }
     }
 }
@@ -282,6 +370,7 @@ mod converting_mixes_to_vec {
                 owner: "N/A".to_string(),
                 stake: 0,
                 delegation: 0,
+                avg_uptime: None,
                 host: "3.3.3.3".parse().unwrap(),
                 mix_host: "3.3.3.3:1789".parse().unwrap(),
                 identity_key: identity::PublicKey::from_base58_string(
@@ -328,3 +417,59 @@ mod converting_mixes_to_vec {
         }
     }
 }
+
+#[cfg(test)]
+mod excluding_mixes {
+    use super::*;
+    use crypto::asymmetric::{encryption, identity};
+    use mixnet_contract_common::Layer;
+
+    fn node(mix_host: &str, layer: Layer) -> mix::Node {
+        mix::Node {
+            owner: "N/A".to_string(),
+            stake: 0,
+            delegation: 0,
+            avg_uptime: None,
+            host: "3.3.3.3".parse().unwrap(),
+            mix_host: mix_host.parse().unwrap(),
+            identity_key: identity::PublicKey::from_base58_string(
+                "3ebjp1Fb9hdcS1AR6AZihgeJiMHkB5jjJUsvqNnfQwU7",
+            )
+            .unwrap(),
+            sphinx_key: encryption::PublicKey::from_base58_string(
+                "C7cown6dYCLZpLiMFC1PaBmhvLvmJmLDJGeRTbPD45bX",
+            )
+            .unwrap(),
+            layer,
+            version: "0.x.0".to_string(),
+        }
+    }
+
+    #[test]
+    fn only_the_provided_mixes_are_removed() {
+        let mut mixes: HashMap<MixLayer, Vec<mix::Node>> = HashMap::new();
+        mixes.insert(
+            1,
+            vec![
+                node("1.1.1.1:1789", Layer::One),
+                node("1.1.1.2:1789", Layer::One),
+            ],
+        );
+        mixes.insert(2, vec![node("2.2.2.2:1789", Layer::Two)]);
+        let topology = NymTopology::new(mixes, vec![]);
+
+        let excluded = [
+            NymNodeRoutingAddress::from("1.1.1.1:1789".parse::<SocketAddr>().unwrap()),
+            NymNodeRoutingAddress::from("2.2.2.2:1789".parse::<SocketAddr>().unwrap()),
+        ];
+        let filtered = topology.without_mixes(&excluded);
+
+        assert_eq!(filtered.mixes()[&1].len(), 1);
+        assert_eq!(
+            filtered.mixes()[&1][0].mix_host,
+            "1.1.1.2:1789".parse().unwrap()
+        );
+        assert!(filtered.mixes()[&2].is_empty());
+        assert_eq!(topology.mixes()[&1].len(), 2);
+    }
+}
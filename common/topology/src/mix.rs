@@ -73,6 +73,8 @@ pub struct Node {
     // on the network at a type, right?
     pub stake: u128,
     pub delegation: u128,
+    // average uptime of the node as reported by the validator API, if it was retrieved
+    pub avg_uptime: Option<u8>,
     pub host: NetworkAddress,
     // we're keeping this as separate resolved field since we do not want to be resolving the potential
     // hostname every time we want to construct a path via this node
@@ -83,6 +85,12 @@ pub struct Node {
     pub version: String,
 }
 
+impl Node {
+    pub fn total_stake(&self) -> u128 {
+        self.stake.saturating_add(self.delegation)
+    }
+}
+
 impl filter::Versioned for Node {
     fn version(&self) -> String {
         self.version.clone()
@@ -119,6 +127,7 @@ impl<'a> TryFrom<&'a MixNodeBond> for Node {
             owner: bond.owner.as_str().to_owned(),
             stake: bond.pledge_amount.amount.into(),
             delegation: bond.total_delegation.amount.into(),
+            avg_uptime: None,
             host,
             mix_host,
             identity_key: identity::PublicKey::from_base58_string(&bond.mix_node.identity_key)?,
@@ -0,0 +1,33 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::NymTopology;
+use async_trait::async_trait;
+
+/// Source of the network topology used by the clients for constructing packet routes.
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait TopologyProvider {
+    /// Attempts to obtain the current view of the network.
+    /// Returns `None` if no valid topology could have been obtained at this time.
+    async fn get_new_topology(&mut self) -> Option<NymTopology>;
+}
+
+/// Provider always returning the same, predefined, topology.
+pub struct StaticTopologyProvider {
+    topology: NymTopology,
+}
+
+impl StaticTopologyProvider {
+    pub fn new(topology: NymTopology) -> Self {
+        StaticTopologyProvider { topology }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl TopologyProvider for StaticTopologyProvider {
+    async fn get_new_topology(&mut self) -> Option<NymTopology> {
+        Some(self.topology.clone())
+    }
+}
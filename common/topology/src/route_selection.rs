@@ -0,0 +1,423 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::mix;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+
+/// Determines how likely each mix node on a given layer is to get chosen for a route.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteSelectionStrategy {
+    /// Every node on the layer is equally likely to get chosen.
+    Uniform,
+
+    /// Nodes are chosen proportionally to their total stake, i.e. their pledge and all delegations.
+    StakeWeighted,
+
+    /// Nodes are chosen proportionally to their average uptime as reported by the validator API.
+    UptimeWeighted,
+}
+
+impl Default for RouteSelectionStrategy {
+    fn default() -> Self {
+        RouteSelectionStrategy::Uniform
+    }
+}
+
+impl RouteSelectionStrategy {
+    /// Weight of the node, where nodes of unknown uptime are weighted with `unknown_uptime`.
+    fn weight(&self, node: &mix::Node, unknown_uptime: f64) -> f64 {
+        match self {
+            RouteSelectionStrategy::Uniform => 1.0,
+            RouteSelectionStrategy::StakeWeighted => node.total_stake() as f64,
+            RouteSelectionStrategy::UptimeWeighted => {
+                node.avg_uptime.map(f64::from).unwrap_or(unknown_uptime)
+            }
+        }
+    }
+}
+
+/// Average of the known uptimes of the nodes, used as a neutral weight for the nodes whose
+/// uptime is not known yet, e.g. because they have just joined the network.
+fn average_known_uptime(nodes: &[&mix::Node]) -> f64 {
+    let known_uptimes = nodes
+        .iter()
+        .filter_map(|node| node.avg_uptime)
+        .map(f64::from)
+        .collect::<Vec<_>>();
+    if known_uptimes.is_empty() {
+        // any positive value makes all the nodes equally likely to get chosen
+        1.0
+    } else {
+        known_uptimes.iter().sum::<f64>() / known_uptimes.len() as f64
+    }
+}
+
+/// Rules used for choosing mix nodes when constructing routes through the network.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RouteSelection {
+    pub strategy: RouteSelectionStrategy,
+
+    /// Refuse to put more than a single node of any given owner on a route.
+    pub exclude_same_owner: bool,
+
+    /// Refuse to put more than a single node from any given network on a route,
+    /// where network is the /24 prefix for IPv4 and the /48 prefix for IPv6 addresses.
+    pub exclude_same_subnet: bool,
+}
+
+fn same_subnet(node1: &mix::Node, node2: &mix::Node) -> bool {
+    match (node1.mix_host.ip(), node2.mix_host.ip()) {
+        (IpAddr::V4(ip1), IpAddr::V4(ip2)) => ip1.octets()[..3] == ip2.octets()[..3],
+        (IpAddr::V6(ip1), IpAddr::V6(ip2)) => ip1.segments()[..3] == ip2.segments()[..3],
+        _ => false,
+    }
+}
+
+impl RouteSelection {
+    fn conflicts(&self, candidate: &mix::Node, route: &[&mix::Node]) -> bool {
+        route.iter().any(|node| {
+            (self.exclude_same_owner && node.owner == candidate.owner)
+                || (self.exclude_same_subnet && same_subnet(node, candidate))
+        })
+    }
+
+    /// Chooses a node out of the provided layer that does not conflict with any of the nodes
+    /// already put on the route and is not excluded. Returns `None` if no such node exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `rng`: source of randomness used for the choice.
+    /// * `layer_mixes`: all mix nodes on the layer.
+    /// * `route`: nodes already chosen for the preceding layers.
+    /// * `excluded`: nodes of the layer that must not be chosen, e.g. as they are known not to
+    ///   lead to a complete route.
+    pub(crate) fn choose_node<'a, R>(
+        &self,
+        rng: &mut R,
+        layer_mixes: &'a [mix::Node],
+        route: &[&mix::Node],
+        excluded: &[&mix::Node],
+    ) -> Option<&'a mix::Node>
+    where
+        R: Rng + ?Sized,
+    {
+        let candidates = layer_mixes
+            .iter()
+            .filter(|node| {
+                !excluded
+                    .iter()
+                    .any(|excluded| std::ptr::eq(*excluded, *node))
+            })
+            .filter(|node| !self.conflicts(node, route))
+            .collect::<Vec<_>>();
+
+        if self.strategy == RouteSelectionStrategy::Uniform {
+            return candidates.choose(rng).copied();
+        }
+
+        // if none of the candidates has a positive weight, for example because all of them
+        // have no stake, fallback to choosing uniformly
+        let unknown_uptime = average_known_uptime(&candidates);
+        match candidates.choose_weighted(rng, |node| self.strategy.weight(node, unknown_uptime)) {
+            Ok(node) => Some(*node),
+            Err(_) => candidates.choose(rng).copied(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MixLayer, NymTopology, NymTopologyError};
+    use crypto::asymmetric::{encryption, identity};
+    use mixnet_contract_common::Layer;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    const SAMPLES: usize = 20_000;
+    // with the number of samples above, the standard deviation of each observed frequency is
+    // below 0.0035, so this is a very comfortable margin
+    const TOLERANCE: f64 = 0.02;
+
+    fn node_fixture(rng: &mut StdRng, owner: &str, ip: &str, layer: Layer) -> mix::Node {
+        mix::Node {
+            owner: owner.to_string(),
+            stake: 0,
+            delegation: 0,
+            avg_uptime: None,
+            host: ip.parse().unwrap(),
+            mix_host: format!("{}:1789", ip).parse().unwrap(),
+            identity_key: *identity::KeyPair::new(rng).public_key(),
+            sphinx_key: *encryption::KeyPair::new(rng).public_key(),
+            layer,
+            version: "1.0.0".to_string(),
+        }
+    }
+
+    fn observed_frequencies<F>(nodes: &[mix::Node], mut choose: F) -> Vec<f64>
+    where
+        F: FnMut() -> String,
+    {
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for _ in 0..SAMPLES {
+            *counts.entry(choose()).or_default() += 1;
+        }
+        nodes
+            .iter()
+            .map(|node| *counts.get(&node.owner).unwrap_or(&0) as f64 / SAMPLES as f64)
+            .collect()
+    }
+
+    fn assert_distribution(observed: &[f64], expected: &[f64]) {
+        for (observed, expected) in observed.iter().zip(expected) {
+            assert!(
+                (observed - expected).abs() < TOLERANCE,
+                "observed frequency {} while {} was expected",
+                observed,
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn uniform_selection_chooses_every_node_equally_often() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let nodes = (0..4)
+            .map(|i| node_fixture(&mut rng, &format!("owner{}", i), "1.1.1.1", Layer::One))
+            .collect::<Vec<_>>();
+
+        let selection = RouteSelection::default();
+        let observed = observed_frequencies(&nodes, || {
+            selection
+                .choose_node(&mut rng, &nodes, &[], &[])
+                .unwrap()
+                .owner
+                .clone()
+        });
+
+        assert_distribution(&observed, &[0.25, 0.25, 0.25, 0.25]);
+    }
+
+    #[test]
+    fn stake_weighted_selection_is_proportional_to_total_stake() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut nodes = (0..3)
+            .map(|i| node_fixture(&mut rng, &format!("owner{}", i), "1.1.1.1", Layer::One))
+            .collect::<Vec<_>>();
+        // total stakes of 1000, 2000 and 7000
+        nodes[0].stake = 1000;
+        nodes[1].stake = 500;
+        nodes[1].delegation = 1500;
+        nodes[2].delegation = 7000;
+
+        let selection = RouteSelection {
+            strategy: RouteSelectionStrategy::StakeWeighted,
+            ..Default::default()
+        };
+        let observed = observed_frequencies(&nodes, || {
+            selection
+                .choose_node(&mut rng, &nodes, &[], &[])
+                .unwrap()
+                .owner
+                .clone()
+        });
+
+        assert_distribution(&observed, &[0.1, 0.2, 0.7]);
+    }
+
+    #[test]
+    fn uptime_weighted_selection_is_proportional_to_uptime() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut nodes = (0..4)
+            .map(|i| node_fixture(&mut rng, &format!("owner{}", i), "1.1.1.1", Layer::One))
+            .collect::<Vec<_>>();
+        nodes[0].avg_uptime = Some(100);
+        nodes[1].avg_uptime = Some(75);
+        nodes[2].avg_uptime = Some(25);
+        nodes[3].avg_uptime = Some(0);
+
+        let selection = RouteSelection {
+            strategy: RouteSelectionStrategy::UptimeWeighted,
+            ..Default::default()
+        };
+        let observed = observed_frequencies(&nodes, || {
+            selection
+                .choose_node(&mut rng, &nodes, &[], &[])
+                .unwrap()
+                .owner
+                .clone()
+        });
+
+        assert_distribution(&observed, &[0.5, 0.375, 0.125, 0.0]);
+    }
+
+    #[test]
+    fn nodes_of_unknown_uptime_are_weighted_with_the_average_uptime() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut nodes = (0..3)
+            .map(|i| node_fixture(&mut rng, &format!("owner{}", i), "1.1.1.1", Layer::One))
+            .collect::<Vec<_>>();
+        // the last node is weighted as if its uptime was 75
+        nodes[0].avg_uptime = Some(100);
+        nodes[1].avg_uptime = Some(50);
+
+        let selection = RouteSelection {
+            strategy: RouteSelectionStrategy::UptimeWeighted,
+            ..Default::default()
+        };
+        let observed = observed_frequencies(&nodes, || {
+            selection
+                .choose_node(&mut rng, &nodes, &[], &[])
+                .unwrap()
+                .owner
+                .clone()
+        });
+
+        assert_distribution(&observed, &[100.0 / 225.0, 50.0 / 225.0, 75.0 / 225.0]);
+    }
+
+    #[test]
+    fn weighted_selection_falls_back_to_uniform_without_any_weights() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let nodes = (0..2)
+            .map(|i| node_fixture(&mut rng, &format!("owner{}", i), "1.1.1.1", Layer::One))
+            .collect::<Vec<_>>();
+
+        let selection = RouteSelection {
+            strategy: RouteSelectionStrategy::StakeWeighted,
+            ..Default::default()
+        };
+        let observed = observed_frequencies(&nodes, || {
+            selection
+                .choose_node(&mut rng, &nodes, &[], &[])
+                .unwrap()
+                .owner
+                .clone()
+        });
+
+        assert_distribution(&observed, &[0.5, 0.5]);
+    }
+
+    fn topology_fixture(rng: &mut StdRng) -> NymTopology {
+        let mut mixes: HashMap<MixLayer, Vec<mix::Node>> = HashMap::new();
+        mixes.insert(
+            1,
+            vec![
+                node_fixture(rng, "alice", "1.1.1.1", Layer::One),
+                node_fixture(rng, "bob", "2.2.2.1", Layer::One),
+            ],
+        );
+        mixes.insert(
+            2,
+            vec![
+                node_fixture(rng, "alice", "3.3.3.1", Layer::Two),
+                node_fixture(rng, "carol", "1.1.1.2", Layer::Two),
+                node_fixture(rng, "dave", "4.4.4.1", Layer::Two),
+            ],
+        );
+        mixes.insert(
+            3,
+            vec![
+                node_fixture(rng, "bob", "5.5.5.1", Layer::Three),
+                node_fixture(rng, "erin", "2.2.2.2", Layer::Three),
+                node_fixture(rng, "frank", "6.6.6.1", Layer::Three),
+            ],
+        );
+        NymTopology::new(mixes, vec![])
+    }
+
+    #[test]
+    fn routes_never_contain_two_nodes_of_the_same_owner_if_excluded() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let topology = topology_fixture(&mut rng).with_route_selection(RouteSelection {
+            exclude_same_owner: true,
+            ..Default::default()
+        });
+
+        for _ in 0..1000 {
+            let route = topology.random_mix_nodes(&mut rng, 3).unwrap();
+            let mut owners = route.iter().map(|node| &node.owner).collect::<Vec<_>>();
+            owners.sort();
+            owners.dedup();
+            assert_eq!(owners.len(), 3);
+        }
+    }
+
+    #[test]
+    fn routes_never_contain_two_nodes_of_the_same_subnet_if_excluded() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let topology = topology_fixture(&mut rng).with_route_selection(RouteSelection {
+            exclude_same_subnet: true,
+            ..Default::default()
+        });
+
+        for _ in 0..1000 {
+            let route = topology.random_mix_nodes(&mut rng, 3).unwrap();
+            for (i, node) in route.iter().enumerate() {
+                for other in route.iter().skip(i + 1) {
+                    assert!(!same_subnet(node, other));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nodes_leading_to_dead_ends_are_replaced() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut mixes: HashMap<MixLayer, Vec<mix::Node>> = HashMap::new();
+        mixes.insert(
+            1,
+            vec![
+                node_fixture(&mut rng, "alice", "1.1.1.1", Layer::One),
+                node_fixture(&mut rng, "bob", "2.2.2.1", Layer::One),
+            ],
+        );
+        mixes.insert(
+            2,
+            vec![node_fixture(&mut rng, "carol", "3.3.3.1", Layer::Two)],
+        );
+        // choosing bob on the first layer makes it impossible to complete the route
+        mixes.insert(
+            3,
+            vec![node_fixture(&mut rng, "bob", "4.4.4.1", Layer::Three)],
+        );
+        let topology = NymTopology::new(mixes, vec![]).with_route_selection(RouteSelection {
+            exclude_same_owner: true,
+            ..Default::default()
+        });
+
+        for _ in 0..100 {
+            let route = topology.random_mix_nodes(&mut rng, 3).unwrap();
+            let owners = route.iter().map(|node| &node.owner).collect::<Vec<_>>();
+            assert_eq!(owners, vec!["alice", "carol", "bob"]);
+        }
+    }
+
+    #[test]
+    fn routes_are_not_constructed_if_all_nodes_on_a_layer_are_excluded() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let mut mixes: HashMap<MixLayer, Vec<mix::Node>> = HashMap::new();
+        mixes.insert(
+            1,
+            vec![node_fixture(&mut rng, "alice", "1.1.1.1", Layer::One)],
+        );
+        mixes.insert(
+            2,
+            vec![node_fixture(&mut rng, "alice", "2.2.2.2", Layer::Two)],
+        );
+        let topology = NymTopology::new(mixes, vec![]).with_route_selection(RouteSelection {
+            exclude_same_owner: true,
+            ..Default::default()
+        });
+
+        assert!(matches!(
+            topology.random_mix_route(&mut rng, 2),
+            Err(NymTopologyError::NoSuitableMixesOnLayer(2))
+        ));
+    }
+}
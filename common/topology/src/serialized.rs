@@ -0,0 +1,244 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Human-writable representation of the network topology, so that clients could be pointed
+//! at a network without a validator API, for example a local mixnet running on a single machine.
+
+use crate::gateway::GatewayConversionError;
+use crate::mix::MixnodeConversionError;
+use crate::{gateway, mix, MixLayer, NetworkAddress, NymTopology};
+use crypto::asymmetric::{encryption, identity};
+use mixnet_contract_common::Layer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Debug)]
+pub enum SerializedTopologyError {
+    InvalidMixnode(MixnodeConversionError),
+    InvalidGateway(GatewayConversionError),
+    InvalidMixLayer(String),
+}
+
+impl From<MixnodeConversionError> for SerializedTopologyError {
+    fn from(err: MixnodeConversionError) -> Self {
+        SerializedTopologyError::InvalidMixnode(err)
+    }
+}
+
+impl From<GatewayConversionError> for SerializedTopologyError {
+    fn from(err: GatewayConversionError) -> Self {
+        SerializedTopologyError::InvalidGateway(err)
+    }
+}
+
+impl Display for SerializedTopologyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SerializedTopologyError::InvalidMixnode(err) => err.fmt(f),
+            SerializedTopologyError::InvalidGateway(err) => err.fmt(f),
+            SerializedTopologyError::InvalidMixLayer(identity) => {
+                write!(f, "mixnode {} is not placed on a mix layer", identity)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SerializedTopologyError {}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializableMixNode {
+    #[serde(default)]
+    pub owner: String,
+    #[serde(default)]
+    pub stake: u64,
+    #[serde(default)]
+    pub delegation: u64,
+    pub host: String,
+    pub mix_port: u16,
+    pub identity_key: String,
+    pub sphinx_key: String,
+    pub layer: Layer,
+    pub version: String,
+}
+
+impl TryFrom<SerializableMixNode> for mix::Node {
+    type Error = SerializedTopologyError;
+
+    fn try_from(node: SerializableMixNode) -> Result<Self, Self::Error> {
+        if node.layer == Layer::Gateway {
+            return Err(SerializedTopologyError::InvalidMixLayer(node.identity_key));
+        }
+
+        let host: NetworkAddress = node
+            .host
+            .parse()
+            .map_err(|err| MixnodeConversionError::InvalidAddress(node.host.clone(), err))?;
+        let mix_host = host
+            .to_socket_addrs(node.mix_port)
+            .map_err(|err| MixnodeConversionError::InvalidAddress(node.host.clone(), err))?[0];
+
+        Ok(mix::Node {
+            owner: node.owner,
+            stake: node.stake.into(),
+            delegation: node.delegation.into(),
+            avg_uptime: None,
+            host,
+            mix_host,
+            identity_key: identity::PublicKey::from_base58_string(&node.identity_key)
+                .map_err(MixnodeConversionError::from)?,
+            sphinx_key: encryption::PublicKey::from_base58_string(&node.sphinx_key)
+                .map_err(MixnodeConversionError::from)?,
+            layer: node.layer,
+            version: node.version,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SerializableGateway {
+    #[serde(default)]
+    pub owner: String,
+    #[serde(default)]
+    pub stake: u64,
+    #[serde(default)]
+    pub location: String,
+    pub host: String,
+    pub mix_port: u16,
+    pub clients_port: u16,
+    #[serde(default)]
+    pub clients_wss_port: Option<u16>,
+    pub identity_key: String,
+    pub sphinx_key: String,
+    pub version: String,
+}
+
+impl TryFrom<SerializableGateway> for gateway::Node {
+    type Error = SerializedTopologyError;
+
+    fn try_from(node: SerializableGateway) -> Result<Self, Self::Error> {
+        let host: NetworkAddress = node
+            .host
+            .parse()
+            .map_err(|err| GatewayConversionError::InvalidAddress(node.host.clone(), err))?;
+        let mix_host = host
+            .to_socket_addrs(node.mix_port)
+            .map_err(|err| GatewayConversionError::InvalidAddress(node.host.clone(), err))?[0];
+
+        Ok(gateway::Node {
+            owner: node.owner,
+            stake: node.stake.into(),
+            location: node.location,
+            host,
+            mix_host,
+            clients_port: node.clients_port,
+            clients_wss_port: node.clients_wss_port,
+            identity_key: identity::PublicKey::from_base58_string(&node.identity_key)
+                .map_err(GatewayConversionError::from)?,
+            sphinx_key: encryption::PublicKey::from_base58_string(&node.sphinx_key)
+                .map_err(GatewayConversionError::from)?,
+            version: node.version,
+        })
+    }
+}
+
+/// Network topology as described in a topology file, i.e.
+///
+/// ```toml
+/// [[mixnodes]]
+/// host = '127.0.0.1'
+/// mix_port = 1789
+/// identity_key = '...'
+/// sphinx_key = '...'
+/// layer = 1
+/// version = '1.0.1'
+///
+/// [[gateways]]
+/// host = '127.0.0.1'
+/// mix_port = 1790
+/// clients_port = 9000
+/// identity_key = '...'
+/// sphinx_key = '...'
+/// version = '1.0.1'
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SerializableTopology {
+    #[serde(default)]
+    pub mixnodes: Vec<SerializableMixNode>,
+    #[serde(default)]
+    pub gateways: Vec<SerializableGateway>,
+}
+
+impl TryFrom<SerializableTopology> for NymTopology {
+    type Error = SerializedTopologyError;
+
+    fn try_from(topology: SerializableTopology) -> Result<Self, Self::Error> {
+        let mut mixes: HashMap<MixLayer, Vec<mix::Node>> = HashMap::new();
+        for node in topology.mixnodes {
+            let node = mix::Node::try_from(node)?;
+            mixes.entry(node.layer as MixLayer).or_default().push(node);
+        }
+
+        let gateways = topology
+            .gateways
+            .into_iter()
+            .map(gateway::Node::try_from)
+            .collect::<Result<_, _>>()?;
+
+        Ok(NymTopology::new(mixes, gateways))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOPOLOGY_JSON: &str = r#"{
+        "mixnodes": [
+            {
+                "host": "127.0.0.1",
+                "mix_port": 1789,
+                "identity_key": "3ebjp1Fb9hdcS1AR6AZihgeJiMHkB5jjJUsvqNnfQwU7",
+                "sphinx_key": "C7cown6dYCLZpLiMFC1PaBmhvLvmJmLDJGeRTbPD45bX",
+                "layer": 2,
+                "version": "1.0.1"
+            }
+        ],
+        "gateways": [
+            {
+                "host": "127.0.0.1",
+                "mix_port": 1790,
+                "clients_port": 9000,
+                "identity_key": "3ebjp1Fb9hdcS1AR6AZihgeJiMHkB5jjJUsvqNnfQwU7",
+                "sphinx_key": "C7cown6dYCLZpLiMFC1PaBmhvLvmJmLDJGeRTbPD45bX",
+                "version": "1.0.1"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn topology_is_constructed_from_serialized_nodes() {
+        let serialized: SerializableTopology = serde_json::from_str(TOPOLOGY_JSON).unwrap();
+        let topology = NymTopology::try_from(serialized).unwrap();
+
+        assert_eq!(topology.mixes()[&2].len(), 1);
+        assert_eq!(
+            topology.mixes()[&2][0].mix_host,
+            "127.0.0.1:1789".parse().unwrap()
+        );
+        assert_eq!(topology.gateways().len(), 1);
+        assert_eq!(topology.gateways()[0].clients_port, 9000);
+    }
+
+    #[test]
+    fn mixnodes_on_the_gateway_layer_are_rejected() {
+        let mut serialized: SerializableTopology = serde_json::from_str(TOPOLOGY_JSON).unwrap();
+        serialized.mixnodes[0].layer = Layer::Gateway;
+
+        assert!(matches!(
+            NymTopology::try_from(serialized),
+            Err(SerializedTopologyError::InvalidMixLayer(_))
+        ));
+    }
+}
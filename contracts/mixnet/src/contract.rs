@@ -123,6 +123,24 @@ pub fn execute(
         ExecuteMsg::UnbondMixnode {} => {
             crate::mixnodes::transactions::try_remove_mixnode(env, deps, info)
         }
+        ExecuteMsg::PledgeMore {} => {
+            crate::mixnodes::transactions::try_pledge_more(deps, env, info)
+        }
+        ExecuteMsg::PledgeMoreOnBehalf { owner } => {
+            crate::mixnodes::transactions::try_pledge_more_on_behalf(deps, env, info, owner)
+        }
+        ExecuteMsg::DecreasePledge { decrease_by } => {
+            crate::mixnodes::transactions::try_decrease_pledge(deps, env, info, decrease_by)
+        }
+        ExecuteMsg::DecreasePledgeOnBehalf { decrease_by, owner } => {
+            crate::mixnodes::transactions::try_decrease_pledge_on_behalf(
+                deps,
+                env,
+                info,
+                decrease_by,
+                owner,
+            )
+        }
         ExecuteMsg::UpdateMixnodeConfig {
             profit_margin_percent,
         } => crate::mixnodes::transactions::try_update_mixnode_config(
@@ -141,6 +159,17 @@ pub fn execute(
             profit_margin_percent,
             owner,
         ),
+        ExecuteMsg::UpdateMixnodeSphinxKey {
+            mix_identity,
+            sphinx_key,
+            signature,
+        } => crate::mixnodes::transactions::try_update_mixnode_sphinx_key(
+            deps,
+            env,
+            mix_identity,
+            sphinx_key,
+            signature,
+        ),
         ExecuteMsg::BondGateway {
             gateway,
             owner_signature,
@@ -154,6 +183,14 @@ pub fn execute(
         ExecuteMsg::UnbondGateway {} => {
             crate::gateways::transactions::try_remove_gateway(deps, info)
         }
+        ExecuteMsg::UpdateGatewayConfig { new_config } => {
+            crate::gateways::transactions::try_update_gateway_config(deps, info, new_config)
+        }
+        ExecuteMsg::UpdateGatewayConfigOnBehalf { new_config, owner } => {
+            crate::gateways::transactions::try_update_gateway_config_on_behalf(
+                deps, info, new_config, owner,
+            )
+        }
         ExecuteMsg::UpdateContractStateParams(params) => {
             crate::mixnet_contract_settings::transactions::try_update_contract_settings(
                 deps, info, params,
@@ -328,6 +365,9 @@ pub fn query(deps: Deps<'_>, env: Env, msg: QueryMsg) -> Result<QueryResponse, C
         QueryMsg::OwnsMixnode { address } => {
             to_binary(&mixnode_queries::query_owns_mixnode(deps, address)?)
         }
+        QueryMsg::GetMixnodeBond { mix_identity } => {
+            to_binary(&mixnode_queries::query_mixnode_bond(deps, mix_identity)?)
+        }
         QueryMsg::OwnsGateway { address } => to_binary(&query_owns_gateway(deps, address)?),
         QueryMsg::StateParams {} => to_binary(&query_contract_settings_params(deps)?),
         QueryMsg::LayerDistribution {} => to_binary(&query_layer_distribution(deps)?),
@@ -427,6 +467,9 @@ pub fn query(deps: Deps<'_>, env: Env, msg: QueryMsg) -> Result<QueryResponse, C
             mix_identity,
             height,
         } => to_binary(&query_mixnode_at_height(deps, mix_identity, height)?),
+        QueryMsg::GetPendingPledgeChange { mix_identity } => to_binary(
+            &mixnode_queries::query_pending_pledge_change(deps, mix_identity)?,
+        ),
     };
 
     Ok(query_res?)
@@ -99,6 +99,9 @@ pub enum ContractError {
     #[error("MIXNET ({}): Provided ed25519 signature did not verify correctly", line!())]
     InvalidEd25519Signature,
 
+    #[error("MIXNET ({}): The provided sphinx key is already used by mixnode {identity}", line!())]
+    UnchangedSphinxKey { identity: String },
+
     #[error("MIXNET ({}): Profit margin percent needs to be an integer in range [0, 100], received {0}", line!())]
     InvalidProfitMarginPercent(u8),
 
@@ -174,6 +177,19 @@ pub enum ContractError {
         total_node_delegation: u128,
         to_subtract: u128,
     },
+    #[error("MIXNET ({}): The pledge change has to be greater than zero", line!())]
+    EmptyPledgeChange,
+
+    #[error("MIXNET ({}): There is already a pending pledge change for mixnode {identity}. It is going to be applied at the end of the current epoch", line!())]
+    PledgeChangeAlreadyPending { identity: IdentityKey },
+
+    #[error("MIXNET ({}): Decreasing the pledge of {current} by {decrease_by} would put it below the minimum of {minimum}", line!())]
+    PledgeDecreaseBelowMinimum {
+        current: u128,
+        decrease_by: u128,
+        minimum: u128,
+    },
+
     #[error("Profit margin can be updated only once during a rolling 30 day interval, last update was at {last_update_time} and current block time is {current_block_time}")]
     UpdatePMTooSoon {
         last_update_time: u64,
@@ -9,8 +9,10 @@ use config::defaults::DENOM;
 use cosmwasm_std::{
     wasm_execute, Addr, BankMsg, Coin, DepsMut, Env, MessageInfo, Response, Uint128,
 };
-use mixnet_contract_common::events::{new_gateway_bonding_event, new_gateway_unbonding_event};
-use mixnet_contract_common::{Gateway, GatewayBond, Layer};
+use mixnet_contract_common::events::{
+    new_gateway_bonding_event, new_gateway_config_update_event, new_gateway_unbonding_event,
+};
+use mixnet_contract_common::{Gateway, GatewayBond, GatewayConfigUpdate, Layer};
 use vesting_contract_common::messages::ExecuteMsg as VestingContractExecuteMsg;
 use vesting_contract_common::one_ucoin;
 
@@ -189,6 +191,84 @@ pub(crate) fn _try_remove_gateway(
     )))
 }
 
+pub(crate) fn try_update_gateway_config(
+    deps: DepsMut<'_>,
+    info: MessageInfo,
+    new_config: GatewayConfigUpdate,
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(info.sender.as_ref())?;
+    _try_update_gateway_config(deps, new_config, owner, None)
+}
+
+pub(crate) fn try_update_gateway_config_on_behalf(
+    deps: DepsMut<'_>,
+    info: MessageInfo,
+    new_config: GatewayConfigUpdate,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let proxy = deps.api.addr_validate(info.sender.as_ref())?;
+    _try_update_gateway_config(deps, new_config, owner, Some(proxy))
+}
+
+pub(crate) fn _try_update_gateway_config(
+    deps: DepsMut<'_>,
+    new_config: GatewayConfigUpdate,
+    owner: Addr,
+    proxy: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let gateway_bond = storage::gateways()
+        .idx
+        .owner
+        .item(deps.storage, owner.clone())?
+        .ok_or(ContractError::NoAssociatedGatewayBond {
+            owner: owner.clone(),
+        })?
+        .1;
+
+    if proxy != gateway_bond.proxy {
+        return Err(ContractError::ProxyMismatch {
+            existing: gateway_bond
+                .proxy
+                .map_or_else(|| "None".to_string(), |a| a.as_str().to_string()),
+            incoming: proxy.map_or_else(|| "None".to_string(), |a| a.as_str().to_string()),
+        });
+    }
+
+    // note: we're explicitly not touching the `block_height` of the bond so that the node
+    // would not lose its bond age
+    storage::gateways().update(deps.storage, gateway_bond.identity(), |gateway_bond_opt| {
+        gateway_bond_opt
+            .map(|mut gateway_bond| {
+                gateway_bond.gateway.host = new_config.host.clone();
+                gateway_bond.gateway.clients_port = new_config.clients_port;
+                gateway_bond.gateway.clients_wss_port = new_config.clients_wss_port;
+                gateway_bond.gateway.location = new_config.location.clone();
+                gateway_bond.gateway.version = new_config.version.clone();
+                gateway_bond
+            })
+            .ok_or(ContractError::NoBondFound)
+    })?;
+
+    let mut response = Response::new();
+
+    if let Some(proxy) = &proxy {
+        // Returns one_ucoin proxy had to send in order to execute the contract to contract transaction
+        let return_one_ucoin = BankMsg::Send {
+            to_address: proxy.as_str().to_string(),
+            amount: vec![one_ucoin()],
+        };
+        response = response.add_message(return_one_ucoin);
+    }
+
+    Ok(response.add_event(new_gateway_config_update_event(
+        &owner,
+        &proxy,
+        gateway_bond.identity(),
+        &new_config,
+    )))
+}
+
 fn validate_gateway_pledge(
     mut pledge: Vec<Coin>,
     minimum_pledge: Uint128,
@@ -232,6 +312,88 @@ pub mod tests {
     use cosmwasm_std::{from_binary, Addr, Uint128};
     use mixnet_contract_common::{ExecuteMsg, Gateway, PagedGatewayResponse, QueryMsg};
 
+    fn config_update() -> GatewayConfigUpdate {
+        GatewayConfigUpdate {
+            host: "1.1.1.1".to_string(),
+            clients_port: 4242,
+            clients_wss_port: Some(4243),
+            location: "Neptune".to_string(),
+            version: "1.2.3".to_string(),
+        }
+    }
+
+    #[test]
+    fn updating_gateway_config() {
+        let mut deps = test_helpers::init_contract();
+
+        // the sender must own a gateway
+        let info = mock_info("gateway-owner", &[]);
+        let msg = ExecuteMsg::UpdateGatewayConfig {
+            new_config: config_update(),
+        };
+        assert_eq!(
+            execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()),
+            Err(ContractError::NoAssociatedGatewayBond {
+                owner: Addr::unchecked("gateway-owner")
+            })
+        );
+
+        let identity = test_helpers::add_gateway(
+            "gateway-owner",
+            tests::fixtures::good_gateway_pledge(),
+            deps.as_mut(),
+        );
+        let bond_before = storage::gateways()
+            .load(deps.as_ref().storage, &identity)
+            .unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 1000;
+        execute(deps.as_mut(), env, info, msg).unwrap();
+
+        let bond_after = storage::gateways()
+            .load(deps.as_ref().storage, &identity)
+            .unwrap();
+        let expected = Gateway {
+            host: "1.1.1.1".to_string(),
+            clients_port: 4242,
+            clients_wss_port: Some(4243),
+            location: "Neptune".to_string(),
+            version: "1.2.3".to_string(),
+            ..bond_before.gateway.clone()
+        };
+        assert_eq!(expected, bond_after.gateway);
+
+        // all other bond information is left unchanged, in particular, its age
+        assert_eq!(bond_before.block_height, bond_after.block_height);
+        assert_eq!(bond_before.pledge_amount, bond_after.pledge_amount);
+        assert_eq!(bond_before.owner, bond_after.owner);
+    }
+
+    #[test]
+    fn updating_gateway_config_on_behalf_requires_matching_proxy() {
+        let mut deps = test_helpers::init_contract();
+        test_helpers::add_gateway(
+            "gateway-owner",
+            tests::fixtures::good_gateway_pledge(),
+            deps.as_mut(),
+        );
+
+        // the gateway was bonded directly, so the proxy can't update it
+        let info = mock_info("proxy", &[]);
+        let msg = ExecuteMsg::UpdateGatewayConfigOnBehalf {
+            new_config: config_update(),
+            owner: "gateway-owner".to_string(),
+        };
+        assert_eq!(
+            execute(deps.as_mut(), mock_env(), info, msg),
+            Err(ContractError::ProxyMismatch {
+                existing: "None".to_string(),
+                incoming: "proxy".to_string(),
+            })
+        );
+    }
+
     #[test]
     fn gateway_add() {
         let mut deps = test_helpers::init_contract();
@@ -88,12 +88,19 @@ pub fn try_advance_epoch(
 
     let current_epoch = storage::current_epoch(storage)?;
     if current_epoch.is_over(env.clone()) {
+        let block_height = env.block.height;
         let next_epoch = current_epoch.next_on_chain(env);
 
         storage::save_epoch(storage, &next_epoch)?;
         storage::save_epoch_reward_params(next_epoch.id(), storage)?;
 
-        return Ok(Response::new().add_event(new_advance_interval_event(next_epoch)));
+        // the epoch has been rewarded by now, so it's safe to apply any pledge changes
+        let response = crate::mixnodes::transactions::_try_apply_pending_pledge_changes(
+            storage,
+            block_height,
+        )?;
+
+        return Ok(response.add_event(new_advance_interval_event(next_epoch)));
     }
     Err(EpochInProgress {
         current_block_time: env.block.time.seconds(),
@@ -5,7 +5,7 @@ use super::storage::{self, StoredMixnodeBond};
 use cosmwasm_std::{Deps, Order, StdResult};
 use cw_storage_plus::Bound;
 use mixnet_contract_common::{
-    IdentityKey, MixNodeBond, MixOwnershipResponse, PagedMixnodeResponse,
+    IdentityKey, MixNodeBond, MixOwnershipResponse, PagedMixnodeResponse, PendingPledgeChange,
 };
 
 pub fn query_mixnode_at_height(
@@ -16,6 +16,13 @@ pub fn query_mixnode_at_height(
     storage::mixnodes().may_load_at_height(deps.storage, &mix_identity, height)
 }
 
+pub fn query_pending_pledge_change(
+    deps: Deps<'_>,
+    mix_identity: IdentityKey,
+) -> StdResult<Option<PendingPledgeChange>> {
+    storage::PENDING_PLEDGE_CHANGES.may_load(deps.storage, &mix_identity)
+}
+
 pub fn query_checkpoints_for_mixnode(
     deps: Deps<'_>,
     mix_identity: IdentityKey,
@@ -82,6 +89,20 @@ pub fn query_owns_mixnode(deps: Deps<'_>, address: String) -> StdResult<MixOwner
     })
 }
 
+pub fn query_mixnode_bond(
+    deps: Deps<'_>,
+    mix_identity: IdentityKey,
+) -> StdResult<Option<MixNodeBond>> {
+    let stored_bond = match storage::mixnodes().may_load(deps.storage, &mix_identity)? {
+        Some(stored_bond) => stored_bond,
+        None => return Ok(None),
+    };
+    let total_delegation = storage::TOTAL_DELEGATION.may_load(deps.storage, &mix_identity)?;
+    Ok(Some(
+        stored_bond.attach_delegation(total_delegation.unwrap_or_default()),
+    ))
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use super::storage;
@@ -256,4 +277,31 @@ pub(crate) mod tests {
         let res = query_owns_mixnode(deps.as_ref(), "fred".to_string()).unwrap();
         assert!(res.mixnode.is_none());
     }
+
+    #[test]
+    fn query_for_mixnode_bond_by_identity_works() {
+        let mut deps = test_helpers::init_contract();
+
+        let res = query_mixnode_bond(deps.as_ref(), "nonexistent".to_string()).unwrap();
+        assert!(res.is_none());
+
+        let identity =
+            test_helpers::add_mixnode("bob", tests::fixtures::good_mixnode_pledge(), deps.as_mut());
+
+        let bond = query_mixnode_bond(deps.as_ref(), identity.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(bond.identity(), &identity);
+        assert_eq!(bond.owner.as_str(), "bob");
+
+        crate::mixnodes::transactions::try_remove_mixnode(
+            mock_env(),
+            deps.as_mut(),
+            mock_info("bob", &[]),
+        )
+        .unwrap();
+
+        let res = query_mixnode_bond(deps.as_ref(), identity).unwrap();
+        assert!(res.is_none());
+    }
 }
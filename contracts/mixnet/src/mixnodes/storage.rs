@@ -6,6 +6,7 @@ use cosmwasm_std::{StdResult, Storage, Uint128};
 use cw_storage_plus::{Index, IndexList, IndexedSnapshotMap, Map, Strategy, UniqueIndex};
 use mixnet_contract_common::{
     reward_params::NodeEpochRewards, Addr, Coin, IdentityKeyRef, Layer, MixNode, MixNodeBond,
+    PendingPledgeChange,
 };
 use mixnet_contract_common::{SphinxKey, U128};
 use serde::{Deserialize, Serialize};
@@ -20,6 +21,7 @@ const MIXNODES_OWNER_IDX_NAMESPACE: &str = "mno";
 const MIXNODES_SPHINX_IDX_NAMESPACE: &str = "mns";
 
 const LAST_PM_UPDATE_NAMESPACE: &str = "lpm";
+const PENDING_PLEDGE_CHANGES_NAMESPACE: &str = "ppc";
 
 // paged retrieval limits for all queries and transactions
 pub(crate) const BOND_PAGE_MAX_LIMIT: u32 = 75;
@@ -31,6 +33,10 @@ pub(crate) const TOTAL_DELEGATION: Map<'_, IdentityKeyRef<'_>, Uint128> =
 pub(crate) const LAST_PM_UPDATE_TIME: Map<'_, IdentityKeyRef<'_>, u64> =
     Map::new(LAST_PM_UPDATE_NAMESPACE);
 
+// pledge changes are applied only once the current epoch is over
+pub(crate) const PENDING_PLEDGE_CHANGES: Map<'_, IdentityKeyRef<'_>, PendingPledgeChange> =
+    Map::new(PENDING_PLEDGE_CHANGES_NAMESPACE);
+
 pub(crate) struct MixnodeBondIndex<'a> {
     pub(crate) owner: UniqueIndex<'a, Addr, StoredMixnodeBond>,
 
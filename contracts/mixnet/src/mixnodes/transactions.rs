@@ -1,20 +1,26 @@
 // Copyright 2021 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use super::storage::{self, LAST_PM_UPDATE_TIME};
+use super::storage::{self, LAST_PM_UPDATE_TIME, PENDING_PLEDGE_CHANGES};
 use crate::error::ContractError;
 use crate::mixnet_contract_settings::storage as mixnet_params_storage;
 use crate::mixnodes::layer_queries::query_layer_distribution;
 use crate::mixnodes::storage::StoredMixnodeBond;
-use crate::support::helpers::{ensure_no_existing_bond, validate_node_identity_signature};
+use crate::support::helpers::{
+    ensure_no_existing_bond, validate_node_identity_signature, verify_node_identity_signature,
+};
 use config::defaults::DENOM;
 use cosmwasm_std::{
-    wasm_execute, Addr, BankMsg, Coin, DepsMut, Env, MessageInfo, Response, Storage, Uint128,
+    wasm_execute, Addr, BankMsg, Coin, DepsMut, Env, MessageInfo, Order, Response, StdResult,
+    Storage, Uint128,
 };
 use mixnet_contract_common::events::{
-    new_checkpoint_mixnodes_event, new_mixnode_bonding_event, new_mixnode_unbonding_event,
+    new_checkpoint_mixnodes_event, new_error_event, new_mixnode_bonding_event,
+    new_mixnode_sphinx_key_update_event, new_mixnode_unbonding_event,
+    new_pending_pledge_change_event, new_pledge_change_event,
 };
-use mixnet_contract_common::MixNode;
+use mixnet_contract_common::mixnode::sphinx_key_update_message;
+use mixnet_contract_common::{IdentityKey, MixNode, PendingPledgeChange, PledgeChange, SphinxKey};
 use vesting_contract_common::messages::ExecuteMsg as VestingContractExecuteMsg;
 use vesting_contract_common::one_ucoin;
 
@@ -219,10 +225,22 @@ pub(crate) fn _try_remove_mixnode(
             incoming: proxy.map_or_else(|| "None".to_string(), |a| a.as_str().to_string()),
         });
     }
+    // if the pledge was going to get increased at the end of the epoch, the increase has to be
+    // returned alongside the current pledge. any pending decrease simply no longer applies.
+    let mut returned_pledge = mixnode_bond.pledge_amount();
+    if let Some(pending_change) =
+        PENDING_PLEDGE_CHANGES.may_load(deps.storage, mixnode_bond.identity())?
+    {
+        if let PledgeChange::Increase(increase) = pending_change.change {
+            returned_pledge.amount += increase.amount;
+        }
+        PENDING_PLEDGE_CHANGES.remove(deps.storage, mixnode_bond.identity());
+    }
+
     // send bonded funds back to the bond owner
     let return_tokens = BankMsg::Send {
         to_address: proxy.as_ref().unwrap_or(&owner).to_string(),
-        amount: vec![mixnode_bond.pledge_amount()],
+        amount: vec![returned_pledge.clone()],
     };
 
     // remove the bond
@@ -236,7 +254,7 @@ pub(crate) fn _try_remove_mixnode(
     if let Some(proxy) = &proxy {
         let msg = VestingContractExecuteMsg::TrackUnbondMixnode {
             owner: owner.as_str().to_string(),
-            amount: mixnode_bond.pledge_amount(),
+            amount: returned_pledge.clone(),
         };
 
         let track_unbond_message = wasm_execute(proxy, &msg, vec![one_ucoin()])?;
@@ -248,7 +266,7 @@ pub(crate) fn _try_remove_mixnode(
     Ok(response.add_event(new_mixnode_unbonding_event(
         &owner,
         &proxy,
-        &mixnode_bond.pledge_amount,
+        &returned_pledge,
         mixnode_bond.identity(),
     )))
 }
@@ -350,6 +368,320 @@ pub(crate) fn _try_update_mixnode_config(
     Ok(response)
 }
 
+// note that the sender of this transaction does not matter, i.e. it does not have to be the owner,
+// as the update is authorised by the signature made with the mixnode's identity key.
+// This allows the mixnodes to rotate their keys without having access to the owner's account.
+pub(crate) fn try_update_mixnode_sphinx_key(
+    deps: DepsMut<'_>,
+    env: Env,
+    mix_identity: IdentityKey,
+    sphinx_key: SphinxKey,
+    signature: String,
+) -> Result<Response, ContractError> {
+    let mixnode_bond = storage::mixnodes()
+        .may_load(deps.storage, &mix_identity)?
+        .ok_or(ContractError::MixNodeBondNotFound {
+            identity: mix_identity.clone(),
+        })?;
+
+    let old_sphinx_key = mixnode_bond.mix_node.sphinx_key;
+    if old_sphinx_key == sphinx_key {
+        return Err(ContractError::UnchangedSphinxKey {
+            identity: mix_identity,
+        });
+    }
+
+    let message = sphinx_key_update_message(&old_sphinx_key, &sphinx_key);
+    verify_node_identity_signature(deps.as_ref(), message.as_bytes(), signature, &mix_identity)?;
+
+    // note: the sphinx key index is unique, so this will fail if any other node uses the same key
+    storage::mixnodes().update(
+        deps.storage,
+        &mix_identity,
+        env.block.height,
+        |mixnode_bond_opt| {
+            mixnode_bond_opt
+                .map(|mut mixnode_bond| {
+                    mixnode_bond.mix_node.sphinx_key = sphinx_key.clone();
+                    mixnode_bond
+                })
+                .ok_or(ContractError::NoBondFound)
+        },
+    )?;
+
+    Ok(
+        Response::new().add_event(new_mixnode_sphinx_key_update_event(
+            &mix_identity,
+            &old_sphinx_key,
+            &sphinx_key,
+        )),
+    )
+}
+
+pub(crate) fn try_pledge_more(
+    deps: DepsMut<'_>,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let increase = validate_pledge_increase(info.funds)?;
+    _try_request_pledge_change(
+        deps,
+        env,
+        PledgeChange::Increase(increase),
+        info.sender,
+        None,
+    )
+}
+
+pub(crate) fn try_pledge_more_on_behalf(
+    deps: DepsMut<'_>,
+    env: Env,
+    info: MessageInfo,
+    owner: String,
+) -> Result<Response, ContractError> {
+    let increase = validate_pledge_increase(info.funds)?;
+    let owner = deps.api.addr_validate(&owner)?;
+    _try_request_pledge_change(
+        deps,
+        env,
+        PledgeChange::Increase(increase),
+        owner,
+        Some(info.sender),
+    )
+}
+
+pub(crate) fn try_decrease_pledge(
+    deps: DepsMut<'_>,
+    env: Env,
+    info: MessageInfo,
+    decrease_by: Coin,
+) -> Result<Response, ContractError> {
+    validate_pledge_change_amount(&decrease_by)?;
+    _try_request_pledge_change(
+        deps,
+        env,
+        PledgeChange::Decrease(decrease_by),
+        info.sender,
+        None,
+    )
+}
+
+pub(crate) fn try_decrease_pledge_on_behalf(
+    deps: DepsMut<'_>,
+    env: Env,
+    info: MessageInfo,
+    decrease_by: Coin,
+    owner: String,
+) -> Result<Response, ContractError> {
+    validate_pledge_change_amount(&decrease_by)?;
+    let owner = deps.api.addr_validate(&owner)?;
+    _try_request_pledge_change(
+        deps,
+        env,
+        PledgeChange::Decrease(decrease_by),
+        owner,
+        Some(info.sender),
+    )
+}
+
+fn ensure_pledge_decrease_allowed(
+    current_pledge: Uint128,
+    decrease_by: Uint128,
+    minimum_pledge: Uint128,
+) -> Result<Uint128, ContractError> {
+    match current_pledge.checked_sub(decrease_by) {
+        Ok(remaining) if remaining >= minimum_pledge => Ok(remaining),
+        _ => Err(ContractError::PledgeDecreaseBelowMinimum {
+            current: current_pledge.u128(),
+            decrease_by: decrease_by.u128(),
+            minimum: minimum_pledge.u128(),
+        }),
+    }
+}
+
+// The change is only recorded here and is applied when the epoch gets advanced, so that the
+// rewarding for the current epoch would still be based on the pledge the node had during it.
+pub(crate) fn _try_request_pledge_change(
+    deps: DepsMut<'_>,
+    env: Env,
+    change: PledgeChange,
+    owner: Addr,
+    proxy: Option<Addr>,
+) -> Result<Response, ContractError> {
+    let mixnode_bond = storage::mixnodes()
+        .idx
+        .owner
+        .item(deps.storage, owner.clone())?
+        .ok_or(ContractError::NoAssociatedMixNodeBond {
+            owner: owner.clone(),
+        })?
+        .1;
+
+    if proxy != mixnode_bond.proxy {
+        return Err(ContractError::ProxyMismatch {
+            existing: mixnode_bond
+                .proxy
+                .map_or_else(|| "None".to_string(), |a| a.as_str().to_string()),
+            incoming: proxy.map_or_else(|| "None".to_string(), |a| a.as_str().to_string()),
+        });
+    }
+
+    if PENDING_PLEDGE_CHANGES
+        .may_load(deps.storage, mixnode_bond.identity())?
+        .is_some()
+    {
+        return Err(ContractError::PledgeChangeAlreadyPending {
+            identity: mixnode_bond.identity().clone(),
+        });
+    }
+
+    if let PledgeChange::Decrease(decrease_by) = &change {
+        let minimum_pledge = mixnet_params_storage::CONTRACT_STATE
+            .load(deps.storage)?
+            .params
+            .minimum_mixnode_pledge;
+        ensure_pledge_decrease_allowed(
+            mixnode_bond.pledge_amount.amount,
+            decrease_by.amount,
+            minimum_pledge,
+        )?;
+    }
+
+    let event = new_pending_pledge_change_event(&owner, &proxy, mixnode_bond.identity(), &change);
+    let is_decrease = matches!(change, PledgeChange::Decrease(_));
+
+    PENDING_PLEDGE_CHANGES.save(
+        deps.storage,
+        mixnode_bond.identity(),
+        &PendingPledgeChange::new(owner, proxy.clone(), change, env.block.height),
+    )?;
+
+    let mut response = Response::new().add_event(event);
+
+    // when increasing the pledge, the proxy sends the actual tokens rather than the one_ucoin
+    if let Some(proxy) = proxy.filter(|_| is_decrease) {
+        let return_one_ucoin = BankMsg::Send {
+            to_address: proxy.as_str().to_string(),
+            amount: vec![one_ucoin()],
+        };
+        response = response.add_message(return_one_ucoin);
+    }
+
+    Ok(response)
+}
+
+/// Applies all pledge changes that were requested during the epoch that has just finished.
+pub(crate) fn _try_apply_pending_pledge_changes(
+    storage: &mut dyn Storage,
+    block_height: u64,
+) -> Result<Response, ContractError> {
+    let pending_changes = PENDING_PLEDGE_CHANGES
+        .range(storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<_>>>()?;
+
+    let minimum_pledge = mixnet_params_storage::CONTRACT_STATE
+        .load(storage)?
+        .params
+        .minimum_mixnode_pledge;
+
+    let mut response = Response::new();
+
+    for (identity, pending_change) in pending_changes {
+        PENDING_PLEDGE_CHANGES.remove(storage, &identity);
+
+        // pending changes are removed upon unbonding, so the bond should always exist
+        let mut mixnode_bond = match storage::mixnodes().may_load(storage, &identity)? {
+            Some(mixnode_bond) => mixnode_bond,
+            None => {
+                response = response.add_event(new_error_event(
+                    ContractError::MixNodeBondNotFound { identity }.to_string(),
+                ));
+                continue;
+            }
+        };
+
+        let PendingPledgeChange {
+            owner,
+            proxy,
+            change,
+            ..
+        } = pending_change;
+
+        match &change {
+            PledgeChange::Increase(increase) => {
+                mixnode_bond.pledge_amount.amount += increase.amount;
+            }
+            PledgeChange::Decrease(decrease) => {
+                // the minimum pledge might have changed since the decrease was requested
+                let remaining = match ensure_pledge_decrease_allowed(
+                    mixnode_bond.pledge_amount.amount,
+                    decrease.amount,
+                    minimum_pledge,
+                ) {
+                    Ok(remaining) => remaining,
+                    Err(err) => {
+                        response = response.add_event(new_error_event(err.to_string()));
+                        continue;
+                    }
+                };
+                mixnode_bond.pledge_amount.amount = remaining;
+
+                if let Some(proxy) = &proxy {
+                    let msg = VestingContractExecuteMsg::TrackDecreasePledge {
+                        owner: owner.as_str().to_string(),
+                        amount: decrease.clone(),
+                    };
+                    response = response.add_message(wasm_execute(proxy, &msg, vec![one_ucoin()])?);
+                }
+
+                response = response.add_message(BankMsg::Send {
+                    to_address: proxy.as_ref().unwrap_or(&owner).to_string(),
+                    amount: vec![decrease.clone()],
+                });
+            }
+        }
+
+        storage::mixnodes().save(storage, &identity, &mixnode_bond, block_height)?;
+
+        response = response.add_event(new_pledge_change_event(
+            &owner,
+            &proxy,
+            &identity,
+            &change,
+            &mixnode_bond.pledge_amount,
+        ));
+    }
+
+    Ok(response)
+}
+
+fn validate_pledge_increase(mut pledge: Vec<Coin>) -> Result<Coin, ContractError> {
+    // check if anything was sent
+    if pledge.is_empty() {
+        return Err(ContractError::NoBondFound);
+    }
+
+    if pledge.len() > 1 {
+        return Err(ContractError::MultipleDenoms);
+    }
+
+    let increase = pledge.pop().unwrap();
+    validate_pledge_change_amount(&increase)?;
+    Ok(increase)
+}
+
+fn validate_pledge_change_amount(change: &Coin) -> Result<(), ContractError> {
+    if change.denom != DENOM {
+        return Err(ContractError::WrongDenom {});
+    }
+
+    if change.amount.is_zero() {
+        return Err(ContractError::EmptyPledgeChange);
+    }
+
+    Ok(())
+}
+
 fn validate_mixnode_pledge(
     mut pledge: Vec<Coin>,
     minimum_pledge: Uint128,
@@ -391,7 +723,7 @@ pub mod tests {
     use crate::support::tests::test_helpers;
     use config::defaults::DENOM;
     use cosmwasm_std::testing::{mock_env, mock_info};
-    use cosmwasm_std::{coins, BankMsg, Response};
+    use cosmwasm_std::{coin, coins, BankMsg, Response, SubMsg};
     use cosmwasm_std::{from_binary, Addr, Uint128};
     use mixnet_contract_common::{
         ExecuteMsg, Layer, LayerDistribution, MixNode, PagedMixnodeResponse, QueryMsg,
@@ -907,6 +1239,81 @@ pub mod tests {
         assert!(try_add_mixnode(deps.as_mut(), mock_env(), info_bob, mixnode, sig2).is_err());
     }
 
+    #[test]
+    fn updating_mixnode_sphinx_key() {
+        let mut deps = test_helpers::init_contract();
+
+        let identity_keypair = crypto::asymmetric::identity::KeyPair::new(&mut thread_rng());
+        let owner_signature = identity_keypair.private_key().sign_text("alice");
+        let old_sphinx_key = crypto::asymmetric::encryption::KeyPair::new(&mut thread_rng())
+            .public_key()
+            .to_base58_string();
+        let new_sphinx_key = crypto::asymmetric::encryption::KeyPair::new(&mut thread_rng())
+            .public_key()
+            .to_base58_string();
+        let identity = identity_keypair.public_key().to_base58_string();
+
+        let mixnode = MixNode {
+            host: "1.2.3.4".to_string(),
+            mix_port: 1234,
+            verloc_port: 1234,
+            http_api_port: 1234,
+            sphinx_key: old_sphinx_key.clone(),
+            identity_key: identity.clone(),
+            version: "v0.1.2.3".to_string(),
+            profit_margin_percent: 10,
+        };
+        let info_alice = mock_info("alice", &tests::fixtures::good_mixnode_pledge());
+        try_add_mixnode(
+            deps.as_mut(),
+            mock_env(),
+            info_alice,
+            mixnode,
+            owner_signature,
+        )
+        .unwrap();
+
+        // signature over a different message is rejected
+        let bad_signature = identity_keypair.private_key().sign_text(&new_sphinx_key);
+        assert_eq!(
+            try_update_mixnode_sphinx_key(
+                deps.as_mut(),
+                mock_env(),
+                identity.clone(),
+                new_sphinx_key.clone(),
+                bad_signature
+            ),
+            Err(ContractError::InvalidEd25519Signature)
+        );
+
+        let signature = identity_keypair
+            .private_key()
+            .sign_text(&sphinx_key_update_message(&old_sphinx_key, &new_sphinx_key));
+        try_update_mixnode_sphinx_key(
+            deps.as_mut(),
+            mock_env(),
+            identity.clone(),
+            new_sphinx_key.clone(),
+            signature.clone(),
+        )
+        .unwrap();
+
+        let bond = storage::mixnodes()
+            .load(deps.as_ref().storage, &identity)
+            .unwrap();
+        assert_eq!(bond.mix_node.sphinx_key, new_sphinx_key);
+
+        // and the same signature can't be used again
+        assert!(try_update_mixnode_sphinx_key(
+            deps.as_mut(),
+            mock_env(),
+            identity,
+            new_sphinx_key,
+            signature
+        )
+        .is_err());
+    }
+
     #[test]
     fn updating_pm_too_often_fails() {
         use super::MIN_PM_UPDATE_INTERVAL;
@@ -953,4 +1360,102 @@ pub mod tests {
         // succeds after some time
         assert!(try_update_mixnode_config(deps.as_mut(), env, info_alice, 20).is_ok());
     }
+
+    fn current_pledge(storage: &dyn Storage, identity: &str) -> u128 {
+        storage::mixnodes()
+            .load(storage, identity)
+            .unwrap()
+            .pledge_amount
+            .amount
+            .u128()
+    }
+
+    #[test]
+    fn pledge_changes_are_applied_at_the_end_of_epoch() {
+        let mut deps = test_helpers::init_contract();
+        let env = mock_env();
+        let pledge = INITIAL_MIXNODE_PLEDGE.u128();
+        let identity = test_helpers::add_mixnode("alice", coins(pledge, DENOM), deps.as_mut());
+
+        // the pledge can't go below the minimum
+        let res = try_decrease_pledge(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            coin(1, DENOM),
+        );
+        assert_eq!(
+            Err(ContractError::PledgeDecreaseBelowMinimum {
+                current: pledge,
+                decrease_by: 1,
+                minimum: pledge
+            }),
+            res
+        );
+
+        let info = mock_info("alice", &coins(1000, DENOM));
+        try_pledge_more(deps.as_mut(), env.clone(), info.clone()).unwrap();
+
+        // only a single change can be pending at any time
+        assert_eq!(
+            Err(ContractError::PledgeChangeAlreadyPending {
+                identity: identity.clone()
+            }),
+            try_pledge_more(deps.as_mut(), env.clone(), info)
+        );
+
+        // nothing changes until the epoch is over
+        assert_eq!(pledge, current_pledge(&deps.storage, &identity));
+        _try_apply_pending_pledge_changes(deps.as_mut().storage, env.block.height).unwrap();
+        assert_eq!(pledge + 1000, current_pledge(&deps.storage, &identity));
+        assert!(PENDING_PLEDGE_CHANGES
+            .may_load(&deps.storage, &identity)
+            .unwrap()
+            .is_none());
+
+        try_decrease_pledge(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &[]),
+            coin(600, DENOM),
+        )
+        .unwrap();
+        assert_eq!(pledge + 1000, current_pledge(&deps.storage, &identity));
+
+        let res =
+            _try_apply_pending_pledge_changes(deps.as_mut().storage, env.block.height).unwrap();
+        assert_eq!(pledge + 400, current_pledge(&deps.storage, &identity));
+        assert_eq!(
+            vec![SubMsg::new(BankMsg::Send {
+                to_address: "alice".to_string(),
+                amount: coins(600, DENOM),
+            })],
+            res.messages
+        );
+    }
+
+    #[test]
+    fn unbonding_returns_pending_pledge_increase() {
+        let mut deps = test_helpers::init_contract();
+        let env = mock_env();
+        let pledge = INITIAL_MIXNODE_PLEDGE.u128();
+        let identity = test_helpers::add_mixnode("alice", coins(pledge, DENOM), deps.as_mut());
+
+        try_pledge_more(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("alice", &coins(1000, DENOM)),
+        )
+        .unwrap();
+
+        let res = try_remove_mixnode(env, deps.as_mut(), mock_info("alice", &[])).unwrap();
+        assert!(res.messages.contains(&SubMsg::new(BankMsg::Send {
+            to_address: "alice".to_string(),
+            amount: coins(pledge + 1000, DENOM),
+        })));
+        assert!(PENDING_PLEDGE_CHANGES
+            .may_load(&deps.storage, &identity)
+            .unwrap()
+            .is_none());
+    }
 }
@@ -9,12 +9,12 @@ use cosmwasm_std::{
     coin, entry_point, to_binary, BankMsg, Coin, Deps, DepsMut, Env, MessageInfo, QueryResponse,
     Response, Timestamp,
 };
-use mixnet_contract_common::{Gateway, IdentityKey, MixNode};
+use mixnet_contract_common::{Gateway, GatewayConfigUpdate, IdentityKey, MixNode};
 use vesting_contract_common::events::{
     new_ownership_transfer_event, new_periodic_vesting_account_event,
-    new_staking_address_update_event, new_track_gateway_unbond_event,
-    new_track_mixnode_unbond_event, new_track_reward_event, new_track_undelegation_event,
-    new_vested_coins_withdraw_event,
+    new_staking_address_update_event, new_track_decrease_pledge_event,
+    new_track_gateway_unbond_event, new_track_mixnode_unbond_event, new_track_reward_event,
+    new_track_undelegation_event, new_vested_coins_withdraw_event,
 };
 use vesting_contract_common::messages::{
     ExecuteMsg, InitMsg, MigrateMsg, QueryMsg, VestingSpecification,
@@ -100,12 +100,20 @@ pub fn execute(
         ExecuteMsg::TrackUnbondMixnode { owner, amount } => {
             try_track_unbond_mixnode(&owner, amount, info, deps)
         }
+        ExecuteMsg::PledgeMore { amount } => try_pledge_more(amount, info, deps),
+        ExecuteMsg::DecreasePledge { amount } => try_decrease_pledge(amount, info, deps),
+        ExecuteMsg::TrackDecreasePledge { owner, amount } => {
+            try_track_decrease_pledge(&owner, amount, info, deps)
+        }
         ExecuteMsg::BondGateway {
             gateway,
             owner_signature,
             amount,
         } => try_bond_gateway(gateway, owner_signature, amount, info, env, deps),
         ExecuteMsg::UnbondGateway {} => try_unbond_gateway(info, deps),
+        ExecuteMsg::UpdateGatewayConfig { new_config } => {
+            try_update_gateway_config(new_config, info, deps)
+        }
         ExecuteMsg::TrackUnbondGateway { owner, amount } => {
             try_track_unbond_gateway(&owner, amount, info, deps)
         }
@@ -232,6 +240,15 @@ pub fn try_unbond_gateway(info: MessageInfo, deps: DepsMut<'_>) -> Result<Respon
     account.try_unbond_gateway(deps.storage)
 }
 
+pub fn try_update_gateway_config(
+    new_config: GatewayConfigUpdate,
+    info: MessageInfo,
+    deps: DepsMut<'_>,
+) -> Result<Response, ContractError> {
+    let account = account_from_address(info.sender.as_str(), deps.storage, deps.api)?;
+    account.try_update_gateway_config(new_config, deps.storage)
+}
+
 pub fn try_track_unbond_gateway(
     owner: &str,
     amount: Coin,
@@ -286,6 +303,40 @@ pub fn try_track_unbond_mixnode(
     Ok(Response::new().add_event(new_track_mixnode_unbond_event()))
 }
 
+pub fn try_pledge_more(
+    amount: Coin,
+    info: MessageInfo,
+    deps: DepsMut<'_>,
+) -> Result<Response, ContractError> {
+    let amount = validate_funds(&[amount])?;
+    let account = account_from_address(info.sender.as_str(), deps.storage, deps.api)?;
+    account.try_pledge_more(amount, deps.storage)
+}
+
+pub fn try_decrease_pledge(
+    amount: Coin,
+    info: MessageInfo,
+    deps: DepsMut<'_>,
+) -> Result<Response, ContractError> {
+    let amount = validate_funds(&[amount])?;
+    let account = account_from_address(info.sender.as_str(), deps.storage, deps.api)?;
+    account.try_decrease_pledge(amount, deps.storage)
+}
+
+pub fn try_track_decrease_pledge(
+    owner: &str,
+    amount: Coin,
+    info: MessageInfo,
+    deps: DepsMut<'_>,
+) -> Result<Response, ContractError> {
+    if info.sender != MIXNET_CONTRACT_ADDRESS.load(deps.storage)? {
+        return Err(ContractError::NotMixnetContract(info.sender));
+    }
+    let account = account_from_address(owner, deps.storage, deps.api)?;
+    account.try_track_decrease_pledge(amount, deps.storage)?;
+    Ok(Response::new().add_event(new_track_decrease_pledge_event()))
+}
+
 fn try_track_reward(
     deps: DepsMut<'_>,
     info: MessageInfo,
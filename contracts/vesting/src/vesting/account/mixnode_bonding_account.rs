@@ -4,7 +4,8 @@ use crate::traits::MixnodeBondingAccount;
 use cosmwasm_std::{wasm_execute, Coin, Env, Response, Storage, Uint128};
 use mixnet_contract_common::{ExecuteMsg as MixnetExecuteMsg, MixNode};
 use vesting_contract_common::events::{
-    new_vesting_mixnode_bonding_event, new_vesting_mixnode_unbonding_event,
+    new_vesting_decrease_pledge_event, new_vesting_mixnode_bonding_event,
+    new_vesting_mixnode_unbonding_event, new_vesting_pledge_more_event,
     new_vesting_update_mixnode_config_event,
 };
 
@@ -143,4 +144,102 @@ impl MixnodeBondingAccount for Account {
         self.remove_mixnode_pledge(storage)?;
         Ok(())
     }
+
+    fn try_pledge_more(
+        &self,
+        amount: Coin,
+        storage: &mut dyn Storage,
+    ) -> Result<Response, ContractError> {
+        let current_balance = self.load_balance(storage)?;
+
+        if current_balance < amount.amount {
+            return Err(ContractError::InsufficientBalance(
+                self.owner_address().as_str().to_string(),
+                current_balance.u128(),
+            ));
+        }
+
+        let pledge_data = match self.load_mixnode_pledge(storage)? {
+            // the increase is going to get returned alongside the rest of the pledge upon unbonding,
+            // so we can track it straight away
+            Some(pledge_data) => PledgeData::new(
+                Coin::new(
+                    pledge_data.amount().amount.u128() + amount.amount.u128(),
+                    amount.denom.clone(),
+                ),
+                pledge_data.block_time(),
+            ),
+            None => {
+                return Err(ContractError::NoBondFound(
+                    self.owner_address().as_str().to_string(),
+                ))
+            }
+        };
+
+        let msg = MixnetExecuteMsg::PledgeMoreOnBehalf {
+            owner: self.owner_address().into_string(),
+        };
+
+        let new_balance = Uint128::new(current_balance.u128() - amount.amount.u128());
+
+        let pledge_more_msg =
+            wasm_execute(MIXNET_CONTRACT_ADDRESS.load(storage)?, &msg, vec![amount])?;
+
+        self.save_balance(new_balance, storage)?;
+        self.save_mixnode_pledge(pledge_data, storage)?;
+
+        Ok(Response::new()
+            .add_message(pledge_more_msg)
+            .add_event(new_vesting_pledge_more_event()))
+    }
+
+    fn try_decrease_pledge(
+        &self,
+        amount: Coin,
+        storage: &dyn Storage,
+    ) -> Result<Response, ContractError> {
+        if self.load_mixnode_pledge(storage)?.is_none() {
+            return Err(ContractError::NoBondFound(
+                self.owner_address().as_str().to_string(),
+            ));
+        }
+
+        // the balance is only updated once the mixnet contract actually returns the tokens
+        // at the end of the epoch
+        let msg = MixnetExecuteMsg::DecreasePledgeOnBehalf {
+            decrease_by: amount,
+            owner: self.owner_address().into_string(),
+        };
+
+        let decrease_pledge_msg = wasm_execute(
+            MIXNET_CONTRACT_ADDRESS.load(storage)?,
+            &msg,
+            vec![one_ucoin()],
+        )?;
+
+        Ok(Response::new()
+            .add_message(decrease_pledge_msg)
+            .add_event(new_vesting_decrease_pledge_event()))
+    }
+
+    fn try_track_decrease_pledge(
+        &self,
+        amount: Coin,
+        storage: &mut dyn Storage,
+    ) -> Result<(), ContractError> {
+        let new_balance = Uint128::new(self.load_balance(storage)?.u128() + amount.amount.u128());
+        self.save_balance(new_balance, storage)?;
+
+        if let Some(pledge_data) = self.load_mixnode_pledge(storage)? {
+            let remaining = pledge_data
+                .amount()
+                .amount
+                .u128()
+                .saturating_sub(amount.amount.u128());
+            let pledge_data =
+                PledgeData::new(Coin::new(remaining, amount.denom), pledge_data.block_time());
+            self.save_mixnode_pledge(pledge_data, storage)?;
+        }
+        Ok(())
+    }
 }
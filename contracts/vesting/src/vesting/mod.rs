@@ -618,6 +618,7 @@ mod tests {
             host: "1.1.1.1".to_string(),
             mix_port: 1789,
             clients_port: 9000,
+            clients_wss_port: None,
             location: "Sweden".to_string(),
             sphinx_key: "sphinx".to_string(),
             identity_key: "identity".to_string(),
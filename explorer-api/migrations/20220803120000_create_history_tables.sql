@@ -0,0 +1,40 @@
+/*
+ * Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- state of each bonded mixnode sampled once per epoch
+CREATE TABLE mixnode_history
+(
+    id                   INTEGER PRIMARY KEY AUTOINCREMENT,
+    identity_key         VARCHAR NOT NULL,
+    epoch_id             INTEGER NOT NULL,
+    timestamp            INTEGER NOT NULL,
+    -- amounts are kept as text as they might not fit in a sqlite integer
+    pledge_amount        VARCHAR NOT NULL,
+    total_delegation     VARCHAR NOT NULL,
+    stake_saturation     REAL    NOT NULL,
+    avg_uptime           INTEGER,
+    is_active            BOOLEAN NOT NULL,
+    is_rewarded          BOOLEAN NOT NULL,
+
+    UNIQUE (identity_key, epoch_id)
+);
+
+CREATE INDEX mixnode_history_identity_key_timestamp ON mixnode_history (identity_key, timestamp);
+
+-- aggregated state of the whole network sampled once per epoch
+CREATE TABLE network_history
+(
+    epoch_id             INTEGER PRIMARY KEY,
+    timestamp            INTEGER NOT NULL,
+    mixnodes             INTEGER NOT NULL,
+    active_mixnodes      INTEGER NOT NULL,
+    standby_mixnodes     INTEGER NOT NULL,
+    gateways             INTEGER NOT NULL,
+    total_pledge         VARCHAR NOT NULL,
+    total_delegation     VARCHAR NOT NULL,
+    avg_uptime           REAL
+);
+
+CREATE INDEX network_history_timestamp ON network_history (timestamp);
@@ -24,13 +24,14 @@ impl GeoLocateTask {
             return;
         }
 
-        info!("Spawning mix node locator task runner...");
+        info!("Spawning mix node and gateway locator task runner...");
         tokio::spawn(async move {
             let mut interval_timer = tokio::time::interval(std::time::Duration::from_millis(50));
             loop {
                 // wait for the next interval tick
                 interval_timer.tick().await;
                 self.locate_mix_nodes().await;
+                self.locate_gateways().await;
             }
         });
     }
@@ -112,6 +113,68 @@ impl GeoLocateTask {
 
         trace!("All mix nodes located");
     }
+
+    async fn locate_gateways(&mut self) {
+        let gateway_bonds = self.state.inner.gateways.get_gateways().await;
+
+        for (i, bond) in gateway_bonds.iter().enumerate() {
+            if self
+                .state
+                .inner
+                .gateways
+                .is_location_valid(&bond.gateway.identity_key)
+                .await
+            {
+                // when the cached location is valid, don't locate and continue to next gateway
+                continue;
+            }
+
+            match locate(&bond.gateway.host).await {
+                Ok(geo_location) => {
+                    let location = Location::new(geo_location);
+
+                    trace!(
+                        "{} gateways already located. Ip {} is located in {:#?}",
+                        i,
+                        bond.gateway.host,
+                        location.three_letter_iso_country_code,
+                    );
+
+                    self.state
+                        .inner
+                        .gateways
+                        .set_location(&bond.gateway.identity_key, Some(location))
+                        .await;
+
+                    // one gateway has been located, so return out of the loop
+                    return;
+                }
+                Err(e) => match e {
+                    LocateError::ReqwestError(e) => warn!(
+                        "❌ Oh no! Location for {} failed {}",
+                        bond.gateway.host, e
+                    ),
+                    LocateError::NotFound(e) => {
+                        warn!(
+                            "❌ Location for {} not found. Response body: {}",
+                            bond.gateway.host, e
+                        );
+                        self.state
+                            .inner
+                            .gateways
+                            .set_location(&bond.gateway.identity_key, None)
+                            .await;
+                    }
+                    LocateError::RateLimited(e) => warn!(
+                        "❌ Oh no, we've been rate limited! Location for {} failed. Response body: {}",
+                        bond.gateway.host, e
+                    ),
+                },
+            }
+        }
+
+        trace!("All gateways located");
+    }
 }
 
 #[derive(Debug, Error)]
@@ -8,11 +8,13 @@ use rocket_okapi::okapi::openapi3::OpenApi;
 use rocket_okapi::openapi_get_routes_spec;
 use rocket_okapi::settings::OpenApiSettings;
 
+use crate::gateways::models::PrettyDetailedGatewayBond;
 use crate::state::ExplorerApiStateContext;
 use mixnet_contract_common::GatewayBond;
+use validator_client::models::GatewayStatusReportResponse;
 
 pub fn gateways_make_default_routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
-    openapi_get_routes_spec![settings: list]
+    openapi_get_routes_spec![settings: list, get_by_id, get_report]
 }
 
 #[openapi(tag = "gateways")]
@@ -22,3 +24,60 @@ pub(crate) async fn list(
 ) -> Result<Json<Vec<GatewayBond>>, NotFound<String>> {
     Ok(Json(state.inner.gateways.get_gateways().await))
 }
+
+#[openapi(tag = "gateways")]
+#[get("/<pubkey>")]
+pub(crate) async fn get_by_id(
+    pubkey: &str,
+    state: &State<ExplorerApiStateContext>,
+) -> Result<Json<PrettyDetailedGatewayBond>, NotFound<String>> {
+    match state
+        .inner
+        .gateways
+        .get_detailed_gateway_by_id(pubkey)
+        .await
+    {
+        Some(gateway) => Ok(Json(gateway)),
+        None => Err(NotFound("Gateway not found".to_string())),
+    }
+}
+
+#[openapi(tag = "gateways")]
+#[get("/<pubkey>/report")]
+pub(crate) async fn get_report(
+    pubkey: &str,
+    state: &State<ExplorerApiStateContext>,
+) -> Option<Json<GatewayStatusReportResponse>> {
+    match state.inner.gateways.get_report(pubkey).await {
+        Some(cache_value) => {
+            trace!("Returning cached value for {}", pubkey);
+            Some(Json(cache_value))
+        }
+        None => {
+            trace!("No valid cache value for {}", pubkey);
+
+            // get fresh value from the validator API
+            match state
+                .inner
+                .validator_client
+                .0
+                .get_gateway_report(pubkey)
+                .await
+            {
+                Ok(report) => {
+                    // update cache
+                    state
+                        .inner
+                        .gateways
+                        .set_report(pubkey, report.clone())
+                        .await;
+                    Some(Json(report))
+                }
+                Err(e) => {
+                    error!("Unable to get report for gateway {} -> {}", pubkey, e);
+                    None
+                }
+            }
+        }
+    }
+}
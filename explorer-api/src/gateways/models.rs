@@ -2,16 +2,21 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use serde::Serialize;
 use tokio::sync::RwLock;
 
-use mixnet_contract_common::GatewayBond;
+use mixnet_contract_common::{Addr, Coin, Gateway, GatewayBond};
+use validator_client::models::GatewayStatusReportResponse;
 
 use crate::cache::Cache;
+use crate::mix_nodes::location::{Location, LocationCache, LocationCacheItem};
 
 pub(crate) struct GatewayCache {
     pub(crate) gateways: Cache<GatewayBond>,
+    pub(crate) locations: LocationCache,
+    pub(crate) reports: Cache<GatewayStatusReportResponse>,
 }
 
 #[derive(Clone, Debug, Serialize, JsonSchema)]
@@ -19,6 +24,16 @@ pub(crate) struct GatewaySummary {
     pub count: usize,
 }
 
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub(crate) struct PrettyDetailedGatewayBond {
+    pub location: Option<Location>,
+    pub pledge_amount: Coin,
+    pub owner: Addr,
+    pub block_height: u64,
+    pub gateway: Gateway,
+    pub proxy: Option<Addr>,
+}
+
 #[derive(Clone)]
 pub(crate) struct ThreadsafeGatewayCache {
     inner: Arc<RwLock<GatewayCache>>,
@@ -26,9 +41,15 @@ pub(crate) struct ThreadsafeGatewayCache {
 
 impl ThreadsafeGatewayCache {
     pub(crate) fn new() -> Self {
+        ThreadsafeGatewayCache::new_with_location_cache(LocationCache::new())
+    }
+
+    pub(crate) fn new_with_location_cache(locations: LocationCache) -> Self {
         ThreadsafeGatewayCache {
             inner: Arc::new(RwLock::new(GatewayCache {
                 gateways: Cache::new(),
+                locations,
+                reports: Cache::new(),
             })),
         }
     }
@@ -37,12 +58,69 @@ impl ThreadsafeGatewayCache {
         self.inner.read().await.gateways.get_all()
     }
 
+    pub(crate) async fn get_gateway(&self, identity_key: &str) -> Option<GatewayBond> {
+        self.inner.read().await.gateways.get(identity_key)
+    }
+
+    pub(crate) async fn get_detailed_gateway_by_id(
+        &self,
+        identity_key: &str,
+    ) -> Option<PrettyDetailedGatewayBond> {
+        let guard = self.inner.read().await;
+        let bond = guard.gateways.get(identity_key)?;
+        let location = guard
+            .locations
+            .get(identity_key)
+            .and_then(|cache_item| cache_item.location.clone());
+
+        Some(PrettyDetailedGatewayBond {
+            location,
+            pledge_amount: bond.pledge_amount,
+            owner: bond.owner,
+            block_height: bond.block_height,
+            gateway: bond.gateway,
+            proxy: bond.proxy,
+        })
+    }
+
     pub(crate) async fn get_gateway_summary(&self) -> GatewaySummary {
         GatewaySummary {
             count: self.inner.read().await.gateways.len(),
         }
     }
 
+    pub(crate) async fn is_location_valid(&self, identity_key: &str) -> bool {
+        self.inner
+            .read()
+            .await
+            .locations
+            .get(identity_key)
+            .map(|cache_item| cache_item.valid_until > SystemTime::now())
+            .unwrap_or(false)
+    }
+
+    pub(crate) async fn get_locations(&self) -> LocationCache {
+        self.inner.read().await.locations.clone()
+    }
+
+    pub(crate) async fn set_location(&self, identity_key: &str, location: Option<Location>) {
+        self.inner.write().await.locations.insert(
+            identity_key.to_string(),
+            LocationCacheItem::new_from_location(location),
+        );
+    }
+
+    pub(crate) async fn get_report(
+        &self,
+        identity_key: &str,
+    ) -> Option<GatewayStatusReportResponse> {
+        self.inner.read().await.reports.get(identity_key)
+    }
+
+    pub(crate) async fn set_report(&self, identity_key: &str, report: GatewayStatusReportResponse) {
+        self.inner.write().await.reports.set(identity_key, report);
+    }
+
     pub(crate) async fn update_cache(&self, gateways: Vec<GatewayBond>) {
         let mut guard = self.inner.write().await;
 
@@ -0,0 +1,230 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use std::time::Duration;
+
+use chrono::Utc;
+
+use crate::history::models::{MixNodeHistoryEntry, NetworkHistoryEntry};
+use crate::mix_node::models::MixnodeStatus;
+use crate::state::ExplorerApiStateContext;
+
+pub(crate) mod models;
+pub(crate) mod storage;
+
+// how often we check whether a new epoch has started and thus whether we should take a new sample
+const HISTORY_SAMPLING_CHECK_RATE: Duration = Duration::from_secs(5 * 60);
+
+pub(crate) struct HistorySamplingTask {
+    state: ExplorerApiStateContext,
+}
+
+impl HistorySamplingTask {
+    pub(crate) fn new(state: ExplorerApiStateContext) -> Self {
+        HistorySamplingTask { state }
+    }
+
+    pub(crate) fn start(self) {
+        info!("Spawning history sampling task runner...");
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(HISTORY_SAMPLING_CHECK_RATE);
+            loop {
+                // wait for the next interval tick
+                interval_timer.tick().await;
+                self.sample_if_new_epoch().await;
+            }
+        });
+    }
+
+    /// Stores the current state of the mixnodes and of the network as a whole if it has not yet
+    /// been sampled during the current epoch.
+    async fn sample_if_new_epoch(&self) {
+        let epoch = match self
+            .state
+            .inner
+            .validator_client
+            .0
+            .get_current_epoch()
+            .await
+        {
+            Ok(epoch) => epoch,
+            Err(e) => {
+                error!("Failed to get the current epoch: {:?}", e);
+                return;
+            }
+        };
+
+        match self.state.inner.history.get_last_sampled_epoch().await {
+            Ok(last_sampled) if !should_sample(last_sampled, epoch.id()) => return,
+            Ok(_) => (),
+            Err(e) => {
+                error!("Failed to get the last sampled epoch: {:?}", e);
+                return;
+            }
+        }
+
+        let mixnodes = self.state.inner.mixnodes.get_detailed_mixnodes().await;
+        if mixnodes.is_empty() {
+            warn!(
+                "The mix node cache is empty - the sample for epoch {} is going to be taken later",
+                epoch.id()
+            );
+            return;
+        }
+
+        info!("Sampling network state for epoch {}...", epoch.id());
+        let timestamp = Utc::now().timestamp();
+
+        let mixnode_entries = mixnodes
+            .into_iter()
+            .map(|mixnode| {
+                let entry = MixNodeHistoryEntry {
+                    epoch_id: epoch.id(),
+                    timestamp,
+                    pledge_amount: mixnode.pledge_amount.amount.u128(),
+                    total_delegation: mixnode.total_delegation.amount.u128(),
+                    stake_saturation: mixnode.stake_saturation,
+                    avg_uptime: mixnode.avg_uptime,
+                    is_active: mixnode.status == MixnodeStatus::Active,
+                    is_rewarded: mixnode.status != MixnodeStatus::Inactive,
+                };
+                (mixnode.mix_node.identity_key, entry)
+            })
+            .collect::<Vec<_>>();
+
+        let gateways = self.state.inner.gateways.get_gateway_summary().await.count as u32;
+        let network_entry = summarise_network(epoch.id(), timestamp, gateways, &mixnode_entries);
+
+        match self
+            .state
+            .inner
+            .history
+            .insert_epoch_sample(network_entry, mixnode_entries)
+            .await
+        {
+            Ok(_) => info!("Done"),
+            Err(e) => error!(
+                "Failed to store the sample for epoch {}: {:?}",
+                epoch.id(),
+                e
+            ),
+        }
+    }
+}
+
+/// Checks whether the network should be sampled in the current epoch, i.e. whether it has not
+/// already been sampled in it (or in any later epoch).
+fn should_sample(last_sampled_epoch: Option<u32>, current_epoch: u32) -> bool {
+    match last_sampled_epoch {
+        Some(last_sampled) => last_sampled < current_epoch,
+        None => true,
+    }
+}
+
+/// Aggregates the sampled state of the individual mixnodes into the state of the whole network.
+fn summarise_network(
+    epoch_id: u32,
+    timestamp: i64,
+    gateways: u32,
+    mixnodes: &[(String, MixNodeHistoryEntry)],
+) -> NetworkHistoryEntry {
+    let uptimes = mixnodes
+        .iter()
+        .filter_map(|(_, entry)| entry.avg_uptime)
+        .collect::<Vec<_>>();
+    let avg_uptime = if uptimes.is_empty() {
+        None
+    } else {
+        Some(uptimes.iter().map(|&uptime| uptime as f32).sum::<f32>() / uptimes.len() as f32)
+    };
+
+    NetworkHistoryEntry {
+        epoch_id,
+        timestamp,
+        mixnodes: mixnodes.len() as u32,
+        active_mixnodes: mixnodes.iter().filter(|(_, entry)| entry.is_active).count() as u32,
+        standby_mixnodes: mixnodes
+            .iter()
+            .filter(|(_, entry)| entry.is_rewarded && !entry.is_active)
+            .count() as u32,
+        gateways,
+        total_pledge: mixnodes.iter().map(|(_, entry)| entry.pledge_amount).sum(),
+        total_delegation: mixnodes
+            .iter()
+            .map(|(_, entry)| entry.total_delegation)
+            .sum(),
+        avg_uptime,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mixnode_entry(
+        epoch_id: u32,
+        avg_uptime: Option<u8>,
+        status: MixnodeStatus,
+    ) -> MixNodeHistoryEntry {
+        MixNodeHistoryEntry {
+            epoch_id,
+            timestamp: 1000,
+            pledge_amount: 100,
+            total_delegation: 50,
+            stake_saturation: 0.5,
+            avg_uptime,
+            is_active: status == MixnodeStatus::Active,
+            is_rewarded: status != MixnodeStatus::Inactive,
+        }
+    }
+
+    #[test]
+    fn network_is_sampled_once_per_epoch() {
+        assert!(should_sample(None, 0));
+        assert!(should_sample(None, 42));
+        assert!(should_sample(Some(41), 42));
+        assert!(!should_sample(Some(42), 42));
+
+        // the epoch reported by the validator might lag behind the stored samples
+        assert!(!should_sample(Some(43), 42));
+    }
+
+    #[test]
+    fn network_summary_aggregates_mixnode_samples() {
+        let mixnodes = vec![
+            (
+                "active".to_string(),
+                mixnode_entry(42, Some(100), MixnodeStatus::Active),
+            ),
+            (
+                "standby".to_string(),
+                mixnode_entry(42, Some(50), MixnodeStatus::Standby),
+            ),
+            (
+                "inactive".to_string(),
+                mixnode_entry(42, None, MixnodeStatus::Inactive),
+            ),
+        ];
+
+        let summary = summarise_network(42, 1000, 7, &mixnodes);
+        assert_eq!(summary.epoch_id, 42);
+        assert_eq!(summary.timestamp, 1000);
+        assert_eq!(summary.mixnodes, 3);
+        assert_eq!(summary.active_mixnodes, 1);
+        assert_eq!(summary.standby_mixnodes, 1);
+        assert_eq!(summary.gateways, 7);
+        assert_eq!(summary.total_pledge, 300);
+        assert_eq!(summary.total_delegation, 150);
+        // mixnodes without any uptime data are not taken into account
+        assert_eq!(summary.avg_uptime, Some(75.0));
+    }
+
+    #[test]
+    fn network_summary_of_nodes_without_uptime_has_no_uptime() {
+        let mixnodes = vec![(
+            "new".to_string(),
+            mixnode_entry(42, None, MixnodeStatus::Active),
+        )];
+        assert_eq!(summarise_network(42, 1000, 0, &mixnodes).avg_uptime, None);
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use serde::Serialize;
+
+// raw rows as stored in the database
+pub(crate) struct MixNodeHistoryRow {
+    pub(crate) epoch_id: i64,
+    pub(crate) timestamp: i64,
+    pub(crate) pledge_amount: String,
+    pub(crate) total_delegation: String,
+    pub(crate) stake_saturation: f32,
+    pub(crate) avg_uptime: Option<i64>,
+    pub(crate) is_active: bool,
+    pub(crate) is_rewarded: bool,
+}
+
+pub(crate) struct NetworkHistoryRow {
+    pub(crate) epoch_id: i64,
+    pub(crate) timestamp: i64,
+    pub(crate) mixnodes: i64,
+    pub(crate) active_mixnodes: i64,
+    pub(crate) standby_mixnodes: i64,
+    pub(crate) gateways: i64,
+    pub(crate) total_pledge: String,
+    pub(crate) total_delegation: String,
+    pub(crate) avg_uptime: Option<f32>,
+}
+
+/// State of a single mixnode at the time of sampling in the particular epoch.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub(crate) struct MixNodeHistoryEntry {
+    pub epoch_id: u32,
+    pub timestamp: i64,
+    pub pledge_amount: u128,
+    pub total_delegation: u128,
+    pub stake_saturation: f32,
+    pub avg_uptime: Option<u8>,
+    pub is_active: bool,
+    pub is_rewarded: bool,
+}
+
+impl From<MixNodeHistoryRow> for MixNodeHistoryEntry {
+    fn from(row: MixNodeHistoryRow) -> Self {
+        MixNodeHistoryEntry {
+            epoch_id: row.epoch_id as u32,
+            timestamp: row.timestamp,
+            pledge_amount: row.pledge_amount.parse().unwrap_or_default(),
+            total_delegation: row.total_delegation.parse().unwrap_or_default(),
+            stake_saturation: row.stake_saturation,
+            avg_uptime: row.avg_uptime.map(|uptime| uptime as u8),
+            is_active: row.is_active,
+            is_rewarded: row.is_rewarded,
+        }
+    }
+}
+
+/// Aggregated state of the network at the time of sampling in the particular epoch.
+#[derive(Clone, Debug, Serialize, JsonSchema)]
+pub(crate) struct NetworkHistoryEntry {
+    pub epoch_id: u32,
+    pub timestamp: i64,
+    pub mixnodes: u32,
+    pub active_mixnodes: u32,
+    pub standby_mixnodes: u32,
+    pub gateways: u32,
+    pub total_pledge: u128,
+    pub total_delegation: u128,
+    pub avg_uptime: Option<f32>,
+}
+
+impl From<NetworkHistoryRow> for NetworkHistoryEntry {
+    fn from(row: NetworkHistoryRow) -> Self {
+        NetworkHistoryEntry {
+            epoch_id: row.epoch_id as u32,
+            timestamp: row.timestamp,
+            mixnodes: row.mixnodes as u32,
+            active_mixnodes: row.active_mixnodes as u32,
+            standby_mixnodes: row.standby_mixnodes as u32,
+            gateways: row.gateways as u32,
+            total_pledge: row.total_pledge.parse().unwrap_or_default(),
+            total_delegation: row.total_delegation.parse().unwrap_or_default(),
+            avg_uptime: row.avg_uptime,
+        }
+    }
+}
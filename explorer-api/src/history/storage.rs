@@ -0,0 +1,316 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use sqlx::ConnectOptions;
+use std::path::Path;
+
+use crate::history::models::{
+    MixNodeHistoryEntry, MixNodeHistoryRow, NetworkHistoryEntry, NetworkHistoryRow,
+};
+
+// note that clone here is fine as upon cloning the same underlying pool will be used
+#[derive(Clone)]
+pub(crate) struct HistoryStorage {
+    connection_pool: sqlx::SqlitePool,
+}
+
+impl HistoryStorage {
+    pub(crate) async fn init<P: AsRef<Path>>(database_path: P) -> Result<Self, sqlx::Error> {
+        let mut opts = sqlx::sqlite::SqliteConnectOptions::new()
+            .filename(database_path)
+            .create_if_missing(true);
+
+        opts.disable_statement_logging();
+
+        let connection_pool = sqlx::SqlitePool::connect_with(opts).await?;
+        sqlx::migrate!("./migrations").run(&connection_pool).await?;
+
+        info!("Database migration finished!");
+
+        Ok(HistoryStorage { connection_pool })
+    }
+
+    /// Gets the id of the most recent epoch for which the network state got sampled.
+    pub(crate) async fn get_last_sampled_epoch(&self) -> Result<Option<u32>, sqlx::Error> {
+        let epoch_id =
+            sqlx::query!(r#"SELECT MAX(epoch_id) as "epoch_id?: i64" FROM network_history"#)
+                .fetch_one(&self.connection_pool)
+                .await?
+                .epoch_id;
+
+        Ok(epoch_id.map(|id| id as u32))
+    }
+
+    /// Inserts the sampled state of the network alongside the state of all the individual mixnodes.
+    pub(crate) async fn insert_epoch_sample(
+        &self,
+        network: NetworkHistoryEntry,
+        mixnodes: Vec<(String, MixNodeHistoryEntry)>,
+    ) -> Result<(), sqlx::Error> {
+        let mut tx = self.connection_pool.begin().await?;
+
+        for (identity_key, mixnode) in mixnodes {
+            let pledge_amount = mixnode.pledge_amount.to_string();
+            let total_delegation = mixnode.total_delegation.to_string();
+            sqlx::query!(
+                r#"
+                    INSERT OR IGNORE INTO mixnode_history
+                    (identity_key, epoch_id, timestamp, pledge_amount, total_delegation, stake_saturation, avg_uptime, is_active, is_rewarded)
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+                identity_key,
+                mixnode.epoch_id,
+                mixnode.timestamp,
+                pledge_amount,
+                total_delegation,
+                mixnode.stake_saturation,
+                mixnode.avg_uptime,
+                mixnode.is_active,
+                mixnode.is_rewarded,
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        let total_pledge = network.total_pledge.to_string();
+        let total_delegation = network.total_delegation.to_string();
+        sqlx::query!(
+            r#"
+                INSERT OR IGNORE INTO network_history
+                (epoch_id, timestamp, mixnodes, active_mixnodes, standby_mixnodes, gateways, total_pledge, total_delegation, avg_uptime)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+            network.epoch_id,
+            network.timestamp,
+            network.mixnodes,
+            network.active_mixnodes,
+            network.standby_mixnodes,
+            network.gateways,
+            total_pledge,
+            total_delegation,
+            network.avg_uptime,
+        )
+        .execute(&mut tx)
+        .await?;
+
+        tx.commit().await
+    }
+
+    /// Gets the history of the particular mixnode sampled within the provided time range.
+    ///
+    /// # Arguments
+    ///
+    /// * `identity_key`: identity key of the mixnode.
+    /// * `since`: unix timestamp indicating the lower bound of the time range (inclusive).
+    /// * `until`: unix timestamp indicating the upper bound of the time range (inclusive).
+    pub(crate) async fn get_mixnode_history(
+        &self,
+        identity_key: &str,
+        since: i64,
+        until: i64,
+    ) -> Result<Vec<MixNodeHistoryEntry>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            MixNodeHistoryRow,
+            r#"
+                SELECT epoch_id, timestamp, pledge_amount, total_delegation, stake_saturation,
+                    avg_uptime, is_active as "is_active: bool", is_rewarded as "is_rewarded: bool"
+                FROM mixnode_history
+                WHERE identity_key = ? AND timestamp >= ? AND timestamp <= ?
+                ORDER BY timestamp
+            "#,
+            identity_key,
+            since,
+            until
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+
+    /// Gets the history of the whole network sampled within the provided time range.
+    ///
+    /// # Arguments
+    ///
+    /// * `since`: unix timestamp indicating the lower bound of the time range (inclusive).
+    /// * `until`: unix timestamp indicating the upper bound of the time range (inclusive).
+    pub(crate) async fn get_network_history(
+        &self,
+        since: i64,
+        until: i64,
+    ) -> Result<Vec<NetworkHistoryEntry>, sqlx::Error> {
+        let rows = sqlx::query_as!(
+            NetworkHistoryRow,
+            r#"
+                SELECT epoch_id as "epoch_id!", timestamp, mixnodes, active_mixnodes, standby_mixnodes, gateways,
+                    total_pledge, total_delegation, avg_uptime
+                FROM network_history
+                WHERE timestamp >= ? AND timestamp <= ?
+                ORDER BY timestamp
+            "#,
+            since,
+            until
+        )
+        .fetch_all(&self.connection_pool)
+        .await?;
+
+        Ok(rows.into_iter().map(Into::into).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn test_storage(name: &str) -> HistoryStorage {
+        let database_path = std::env::temp_dir().join(format!("explorer-api-{}.sqlite", name));
+        let _ = std::fs::remove_file(&database_path);
+        HistoryStorage::init(database_path).await.unwrap()
+    }
+
+    fn mixnode_entry(epoch_id: u32, timestamp: i64) -> MixNodeHistoryEntry {
+        MixNodeHistoryEntry {
+            epoch_id,
+            timestamp,
+            // more than what fits in an i64
+            pledge_amount: u128::MAX,
+            total_delegation: 1_000_000,
+            stake_saturation: 0.25,
+            avg_uptime: Some(99),
+            is_active: true,
+            is_rewarded: true,
+        }
+    }
+
+    fn network_entry(epoch_id: u32, timestamp: i64) -> NetworkHistoryEntry {
+        NetworkHistoryEntry {
+            epoch_id,
+            timestamp,
+            mixnodes: 2,
+            active_mixnodes: 1,
+            standby_mixnodes: 1,
+            gateways: 3,
+            total_pledge: u128::MAX,
+            total_delegation: 2_000_000,
+            avg_uptime: Some(99.5),
+        }
+    }
+
+    #[tokio::test]
+    async fn epoch_samples_round_trip() {
+        let storage = test_storage("epoch-samples-round-trip").await;
+        assert_eq!(storage.get_last_sampled_epoch().await.unwrap(), None);
+
+        for (epoch_id, timestamp) in [(1, 100), (2, 200)] {
+            storage
+                .insert_epoch_sample(
+                    network_entry(epoch_id, timestamp),
+                    vec![
+                        ("mix1".to_string(), mixnode_entry(epoch_id, timestamp)),
+                        ("mix2".to_string(), mixnode_entry(epoch_id, timestamp)),
+                    ],
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(storage.get_last_sampled_epoch().await.unwrap(), Some(2));
+
+        let history = storage.get_mixnode_history("mix1", 0, 200).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].epoch_id, 1);
+        assert_eq!(history[0].timestamp, 100);
+        assert_eq!(history[0].pledge_amount, u128::MAX);
+        assert_eq!(history[0].total_delegation, 1_000_000);
+        assert_eq!(history[0].stake_saturation, 0.25);
+        assert_eq!(history[0].avg_uptime, Some(99));
+        assert!(history[0].is_active);
+        assert!(history[0].is_rewarded);
+        assert_eq!(history[1].epoch_id, 2);
+
+        let history = storage.get_network_history(0, 200).await.unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[1].epoch_id, 2);
+        assert_eq!(history[1].timestamp, 200);
+        assert_eq!(history[1].mixnodes, 2);
+        assert_eq!(history[1].active_mixnodes, 1);
+        assert_eq!(history[1].standby_mixnodes, 1);
+        assert_eq!(history[1].gateways, 3);
+        assert_eq!(history[1].total_pledge, u128::MAX);
+        assert_eq!(history[1].total_delegation, 2_000_000);
+        assert_eq!(history[1].avg_uptime, Some(99.5));
+    }
+
+    #[tokio::test]
+    async fn history_is_filtered_by_time_range() {
+        let storage = test_storage("history-is-filtered-by-time-range").await;
+        for (epoch_id, timestamp) in [(1, 100), (2, 200), (3, 300)] {
+            storage
+                .insert_epoch_sample(
+                    network_entry(epoch_id, timestamp),
+                    vec![("mix1".to_string(), mixnode_entry(epoch_id, timestamp))],
+                )
+                .await
+                .unwrap();
+        }
+
+        let epochs = |history: Vec<MixNodeHistoryEntry>| {
+            history
+                .into_iter()
+                .map(|entry| entry.epoch_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            epochs(storage.get_mixnode_history("mix1", 100, 200).await.unwrap()),
+            vec![1, 2]
+        );
+        assert_eq!(
+            epochs(
+                storage
+                    .get_mixnode_history("mix1", 150, 1000)
+                    .await
+                    .unwrap()
+            ),
+            vec![2, 3]
+        );
+        assert!(storage
+            .get_mixnode_history("unknown", 0, 1000)
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            storage.get_network_history(300, 300).await.unwrap().len(),
+            1
+        );
+        assert!(storage
+            .get_network_history(301, 1000)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn resampling_an_epoch_keeps_the_original_sample() {
+        let storage = test_storage("resampling-an-epoch-keeps-the-original-sample").await;
+        storage
+            .insert_epoch_sample(
+                network_entry(1, 100),
+                vec![("mix1".to_string(), mixnode_entry(1, 100))],
+            )
+            .await
+            .unwrap();
+        storage
+            .insert_epoch_sample(
+                network_entry(1, 150),
+                vec![("mix1".to_string(), mixnode_entry(1, 150))],
+            )
+            .await
+            .unwrap();
+
+        let history = storage.get_mixnode_history("mix1", 0, 1000).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].timestamp, 100);
+        let history = storage.get_network_history(0, 1000).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].timestamp, 100);
+    }
+}
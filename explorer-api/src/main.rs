@@ -9,6 +9,7 @@ pub(crate) mod cache;
 mod client;
 mod country_statistics;
 mod gateways;
+mod history;
 mod http;
 mod mix_node;
 pub(crate) mod mix_nodes;
@@ -24,7 +25,7 @@ const COUNTRY_DATA_REFRESH_INTERVAL: u64 = 60 * 15; // every 15 minutes
 #[tokio::main]
 async fn main() {
     setup_logging();
-    let mut explorer_api = ExplorerApi::new();
+    let mut explorer_api = ExplorerApi::new().await;
     explorer_api.run().await;
 }
 
@@ -33,9 +34,15 @@ pub struct ExplorerApi {
 }
 
 impl ExplorerApi {
-    fn new() -> ExplorerApi {
+    async fn new() -> ExplorerApi {
+        let database_file = state::get_database_file_path();
+        info!("Using history database {:?}", database_file);
+        let history = history::storage::HistoryStorage::init(&database_file)
+            .await
+            .expect("failed to initialise the history database");
+
         ExplorerApi {
-            state: state::ExplorerApiStateContext::new(),
+            state: state::ExplorerApiStateContext::new(history),
         }
     }
 
@@ -52,6 +59,7 @@ impl ExplorerApi {
         )
         .start();
         country_statistics::geolocate::GeoLocateTask::new(self.state.clone()).start();
+        history::HistorySamplingTask::new(self.state.clone()).start();
         http::start(self.state.clone());
 
         // wait for user to press ctrl+C
@@ -11,6 +11,7 @@ use rocket_okapi::settings::OpenApiSettings;
 
 use mixnet_contract_common::Delegation;
 
+use crate::history::models::MixNodeHistoryEntry;
 use crate::mix_node::delegations::{
     get_single_mixnode_delegations, get_single_mixnode_delegations_summed,
 };
@@ -28,6 +29,7 @@ pub fn mix_node_make_default_routes(settings: &OpenApiSettings) -> (Vec<Route>,
         get_description,
         get_stats,
         get_economic_dynamics_stats,
+        get_history,
     ]
 }
 
@@ -188,6 +190,30 @@ pub(crate) async fn get_economic_dynamics_stats(
     }
 }
 
+/// Gets the state of the mixnode sampled at each epoch, optionally restricted to the provided
+/// range of unix timestamps.
+#[openapi(tag = "mix_node")]
+#[get("/<pubkey>/history?<since>&<until>")]
+pub(crate) async fn get_history(
+    pubkey: &str,
+    since: Option<i64>,
+    until: Option<i64>,
+    state: &State<ExplorerApiStateContext>,
+) -> Option<Json<Vec<MixNodeHistoryEntry>>> {
+    match state
+        .inner
+        .history
+        .get_mixnode_history(pubkey, since.unwrap_or(0), until.unwrap_or(i64::MAX))
+        .await
+    {
+        Ok(history) => Some(Json(history)),
+        Err(e) => {
+            error!("Unable to get history for {} -> {}", pubkey, e);
+            None
+        }
+    }
+}
+
 async fn get_mix_node_description(host: &str, port: &u16) -> Result<NodeDescription, ReqwestError> {
     reqwest::get(format!("http://{}:{}/description", host, port))
         .await?
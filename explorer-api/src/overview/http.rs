@@ -4,12 +4,13 @@ use rocket_okapi::okapi::openapi3::OpenApi;
 use rocket_okapi::openapi_get_routes_spec;
 use rocket_okapi::settings::OpenApiSettings;
 
+use crate::history::models::NetworkHistoryEntry;
 use crate::mix_nodes::http::get_mixnode_summary;
 use crate::overview::models::OverviewSummary;
 use crate::state::ExplorerApiStateContext;
 
 pub fn overview_make_default_routes(settings: &OpenApiSettings) -> (Vec<Route>, OpenApi) {
-    openapi_get_routes_spec![settings: summary]
+    openapi_get_routes_spec![settings: summary, history]
 }
 
 #[openapi(tag = "overview")]
@@ -21,3 +22,26 @@ pub(crate) async fn summary(state: &State<ExplorerApiStateContext>) -> Json<Over
         gateways: state.inner.gateways.get_gateway_summary().await,
     })
 }
+
+/// Gets the aggregated state of the network sampled at each epoch, optionally restricted to the
+/// provided range of unix timestamps.
+#[openapi(tag = "overview")]
+#[get("/history?<since>&<until>")]
+pub(crate) async fn history(
+    since: Option<i64>,
+    until: Option<i64>,
+    state: &State<ExplorerApiStateContext>,
+) -> Option<Json<Vec<NetworkHistoryEntry>>> {
+    match state
+        .inner
+        .history
+        .get_network_history(since.unwrap_or(0), until.unwrap_or(i64::MAX))
+        .await
+    {
+        Ok(history) => Some(Json(history)),
+        Err(e) => {
+            error!("Unable to get network history -> {}", e);
+            None
+        }
+    }
+}
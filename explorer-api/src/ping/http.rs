@@ -8,7 +8,7 @@ use rocket_okapi::okapi::openapi3::OpenApi;
 use rocket_okapi::openapi_get_routes_spec;
 use rocket_okapi::settings::OpenApiSettings;
 
-use mixnet_contract_common::MixNodeBond;
+use mixnet_contract_common::{GatewayBond, MixNodeBond};
 
 use crate::ping::models::PingResponse;
 use crate::state::ExplorerApiStateContext;
@@ -36,28 +36,35 @@ pub(crate) async fn index(
         None => {
             trace!("No cache value for {}", pubkey);
 
-            match state.inner.get_mix_node(pubkey).await {
-                Some(bond) => {
-                    // set status to pending, so that any HTTP requests are pending
-                    state.inner.ping.set_pending(pubkey).await;
-
-                    // do the check
-                    let ports = Some(port_check(&bond.mixnode_bond).await);
-                    trace!("Tested mix node {}: {:?}", pubkey, ports);
-                    let response = PingResponse {
-                        ports,
-                        pending: false,
-                    };
-
-                    // cache for 1 min
-                    trace!("Caching value for {}", pubkey);
-                    state.inner.ping.set(pubkey, response.clone()).await;
-
-                    // return response
-                    Some(Json(response))
-                }
-                None => None,
-            }
+            let ports = if let Some(bond) = state.inner.get_mix_node(pubkey).await {
+                // set status to pending, so that any HTTP requests are pending
+                state.inner.ping.set_pending(pubkey).await;
+
+                // do the check
+                let ports = port_check(&bond.mixnode_bond).await;
+                trace!("Tested mix node {}: {:?}", pubkey, ports);
+                ports
+            } else if let Some(bond) = state.inner.gateways.get_gateway(pubkey).await {
+                state.inner.ping.set_pending(pubkey).await;
+
+                let ports = gateway_port_check(&bond).await;
+                trace!("Tested gateway {}: {:?}", pubkey, ports);
+                ports
+            } else {
+                return None;
+            };
+
+            let response = PingResponse {
+                ports: Some(ports),
+                pending: false,
+            };
+
+            // cache for 1 min
+            trace!("Caching value for {}", pubkey);
+            state.inner.ping.set(pubkey, response.clone()).await;
+
+            // return response
+            Some(Json(response))
         }
     }
 }
@@ -84,6 +91,25 @@ async fn port_check(bond: &MixNodeBond) -> HashMap<u16, bool> {
     ports
 }
 
+async fn gateway_port_check(bond: &GatewayBond) -> HashMap<u16, bool> {
+    let mut ports: HashMap<u16, bool> = HashMap::new();
+
+    let mut ports_to_test = vec![bond.gateway.mix_port, bond.gateway.clients_port];
+    ports_to_test.extend(bond.gateway.clients_wss_port);
+
+    trace!(
+        "Testing gateway {} on ports {:?}...",
+        bond.gateway.identity_key,
+        ports_to_test
+    );
+
+    for port in ports_to_test {
+        ports.insert(port, do_port_check(&bond.gateway.host, port).await);
+    }
+
+    ports
+}
+
 fn sanitize_and_resolve_host(host: &str, port: u16) -> Option<SocketAddr> {
     // trim the host
     let trimmed_host = host.trim();
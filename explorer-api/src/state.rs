@@ -12,6 +12,7 @@ use crate::country_statistics::country_nodes_distribution::{
     CountryNodesDistribution, ThreadsafeCountryNodesDistribution,
 };
 use crate::gateways::models::ThreadsafeGatewayCache;
+use crate::history::storage::HistoryStorage;
 use crate::mix_node::models::ThreadsafeMixNodeCache;
 use crate::mix_nodes::location::LocationCache;
 use crate::mix_nodes::models::ThreadsafeMixNodesCache;
@@ -20,11 +21,13 @@ use crate::validators::models::ThreadsafeValidatorCache;
 
 // TODO: change to an environment variable with a default value
 const STATE_FILE: &str = "explorer-api-state.json";
+const DATABASE_FILE: &str = "explorer-api.sqlite";
 
 #[derive(Clone)]
 pub struct ExplorerApiState {
     pub(crate) country_node_distribution: ThreadsafeCountryNodesDistribution,
     pub(crate) gateways: ThreadsafeGatewayCache,
+    pub(crate) history: HistoryStorage,
     pub(crate) mixnode: ThreadsafeMixNodeCache,
     pub(crate) mixnodes: ThreadsafeMixNodesCache,
     pub(crate) ping: ThreadsafePingCache,
@@ -44,6 +47,8 @@ impl ExplorerApiState {
 pub struct ExplorerApiStateOnDisk {
     pub(crate) country_node_distribution: CountryNodesDistribution,
     pub(crate) location_cache: LocationCache,
+    #[serde(default)]
+    pub(crate) gateway_location_cache: LocationCache,
     pub(crate) as_at: DateTime<Utc>,
 }
 
@@ -53,13 +58,13 @@ pub(crate) struct ExplorerApiStateContext {
 }
 
 impl ExplorerApiStateContext {
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(history: HistoryStorage) -> Self {
         ExplorerApiStateContext {
-            inner: ExplorerApiStateContext::read_from_file(),
+            inner: ExplorerApiStateContext::read_from_file(history),
         }
     }
 
-    pub(crate) fn read_from_file() -> ExplorerApiState {
+    pub(crate) fn read_from_file(history: HistoryStorage) -> ExplorerApiState {
         let json_file = get_state_file_path();
         let json_file_path = Path::new(&json_file);
         info!("Loading state from file {:?}...", json_file);
@@ -72,7 +77,10 @@ impl ExplorerApiStateContext {
                         ThreadsafeCountryNodesDistribution::new_from_distribution(
                             state.country_node_distribution,
                         ),
-                    gateways: ThreadsafeGatewayCache::new(),
+                    gateways: ThreadsafeGatewayCache::new_with_location_cache(
+                        state.gateway_location_cache,
+                    ),
+                    history,
                     mixnode: ThreadsafeMixNodeCache::new(),
                     mixnodes: ThreadsafeMixNodesCache::new_with_location_cache(
                         state.location_cache,
@@ -91,6 +99,7 @@ impl ExplorerApiStateContext {
                 ExplorerApiState {
                     country_node_distribution: ThreadsafeCountryNodesDistribution::new(),
                     gateways: ThreadsafeGatewayCache::new(),
+                    history,
                     mixnode: ThreadsafeMixNodeCache::new(),
                     mixnodes: ThreadsafeMixNodesCache::new(),
                     ping: ThreadsafePingCache::new(),
@@ -108,6 +117,7 @@ impl ExplorerApiStateContext {
         let state = ExplorerApiStateOnDisk {
             country_node_distribution: self.inner.country_node_distribution.get_all().await,
             location_cache: self.inner.mixnodes.get_locations().await,
+            gateway_location_cache: self.inner.gateways.get_locations().await,
             as_at: Utc::now(),
         };
         serde_json::to_writer(file, &state).expect("error writing state to disk");
@@ -118,3 +128,7 @@ impl ExplorerApiStateContext {
 fn get_state_file_path() -> String {
     std::env::var("API_STATE_FILE").unwrap_or_else(|_| STATE_FILE.to_string())
 }
+
+pub(crate) fn get_database_file_path() -> String {
+    std::env::var("API_DATABASE_FILE").unwrap_or_else(|_| DATABASE_FILE.to_string())
+}
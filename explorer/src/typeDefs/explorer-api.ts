@@ -38,6 +38,7 @@ export interface Gateway {
   host: string;
   mix_port: number;
   clients_port: number;
+  clients_wss_port?: number | null;
   location: string;
   sphinx_key: string;
   identity_key: string;
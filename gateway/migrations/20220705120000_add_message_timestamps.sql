@@ -0,0 +1,13 @@
+/*
+ * Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- unix timestamp (in seconds) of when the message got stored
+ALTER TABLE message_store ADD COLUMN timestamp INTEGER NOT NULL DEFAULT 0;
+
+-- treat all existing messages as if they were received right now so that they would not get
+-- pruned immediately after the upgrade
+UPDATE message_store SET timestamp = CAST(strftime('%s', 'now') AS INTEGER);
+
+CREATE INDEX `message_store_timestamp_index` ON `message_store` (`timestamp`);
@@ -0,0 +1,15 @@
+/*
+ * Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- blinded serial numbers of all coconut credentials redeemed at this gateway.
+-- `pending_release` indicates the credential has already been shared with the validator APIs,
+-- but releasing its funds has failed, so the same client is allowed to resume the redemption
+CREATE TABLE spent_credentials
+(
+    blinded_serial_number_bs58 TEXT    NOT NULL PRIMARY KEY UNIQUE,
+    client_address_bs58        TEXT    NOT NULL,
+    timestamp                  INTEGER NOT NULL,
+    pending_release            BOOLEAN NOT NULL DEFAULT false
+);
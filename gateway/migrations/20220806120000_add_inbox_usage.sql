@@ -0,0 +1,40 @@
+/*
+ * Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- number and total size of messages stored for each client, so that the inbox quota could be
+-- checked without having to scan the entire inbox on every insert
+CREATE TABLE inbox_usage
+(
+    client_address_bs58 TEXT    NOT NULL PRIMARY KEY,
+    messages            INTEGER NOT NULL,
+    bytes               INTEGER NOT NULL
+);
+
+INSERT INTO inbox_usage(client_address_bs58, messages, bytes)
+SELECT client_address_bs58, COUNT(*), SUM(LENGTH(content))
+FROM message_store
+GROUP BY client_address_bs58;
+
+CREATE TRIGGER message_store_insert_usage
+    AFTER INSERT
+    ON message_store
+BEGIN
+    INSERT INTO inbox_usage(client_address_bs58, messages, bytes)
+    VALUES (NEW.client_address_bs58, 1, LENGTH(NEW.content))
+    ON CONFLICT(client_address_bs58) DO UPDATE SET messages = messages + 1,
+                                                   bytes    = bytes + LENGTH(NEW.content);
+END;
+
+CREATE TRIGGER message_store_delete_usage
+    AFTER DELETE
+    ON message_store
+BEGIN
+    UPDATE inbox_usage
+    SET messages = messages - 1,
+        bytes    = bytes - LENGTH(OLD.content)
+    WHERE client_address_bs58 = OLD.client_address_bs58;
+
+    DELETE FROM inbox_usage WHERE client_address_bs58 = OLD.client_address_bs58 AND messages <= 0;
+END;
@@ -0,0 +1,234 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    commands::sign::load_identity_keys,
+    config::{persistence::pathfinder::GatewayPathfinder, Config},
+};
+use clap::Args;
+use config::NymConfig;
+use crypto::asymmetric::identity;
+use log::error;
+use rcgen::{Certificate, CertificateParams, KeyPair, RcgenError, SanType, PKCS_ED25519};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::Path;
+
+const DEFAULT_TLS_CERTIFICATE_FILE: &str = "tls_certificate.pem";
+const DEFAULT_TLS_PRIVATE_KEY_FILE: &str = "tls_private_key.pem";
+
+// PKCS#8 encoding of an Ed25519 private key is this fixed prefix followed by the 32 key bytes
+const ED25519_PKCS8_PREFIX: [u8; 16] = [
+    0x30, 0x2e, 0x02, 0x01, 0x00, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x04, 0x22, 0x04, 0x20,
+];
+
+#[derive(Args, Clone)]
+pub struct GenerateTlsCertificate {
+    /// The id of the gateway you want to generate the TLS certificate for
+    #[clap(long)]
+    id: String,
+}
+
+/// Creates a self-signed certificate whose public key is the gateway identity key, which the
+/// native clients accept without any certificate authority involved.
+fn identity_certificate(
+    identity_keypair: &identity::KeyPair,
+    host: &str,
+) -> Result<Certificate, RcgenError> {
+    let mut pkcs8 = ED25519_PKCS8_PREFIX.to_vec();
+    pkcs8.extend_from_slice(&identity_keypair.private_key().to_bytes());
+
+    let mut params = CertificateParams::default();
+    params.alg = &PKCS_ED25519;
+    params.key_pair = Some(KeyPair::from_der_and_sign_algo(&pkcs8, &PKCS_ED25519)?);
+    if !host.is_empty() {
+        params.subject_alt_names = vec![match host.parse::<IpAddr>() {
+            Ok(ip) => SanType::IpAddress(ip),
+            Err(_) => SanType::DnsName(host.to_string()),
+        }];
+    }
+    Certificate::from_params(params)
+}
+
+/// Saves the PEM-encoded private key of the certificate. As it's the gateway identity key,
+/// the file is only made accessible to its owner, same as the identity key file itself.
+fn write_private_key(path: &Path, private_key_pem: &str) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    // note: this is only supported on unix (on different systems, like Windows, it will just
+    // be ignored)
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+
+    // the mode is only applied to newly created files, so restrict an already existing one
+    // before the key gets written into it
+    #[cfg(target_family = "unix")]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        file.set_permissions(fs::Permissions::from_mode(0o600))?;
+    }
+
+    file.write_all(private_key_pem.as_bytes())
+}
+
+pub fn execute(args: &GenerateTlsCertificate) {
+    let config = match Config::load_from_file(Some(&args.id)) {
+        Ok(cfg) => cfg,
+        Err(err) => {
+            error!(
+                "Failed to load config for {}. Are you sure you have run `init` before? (Error was: {})",
+                args.id,
+                err
+            );
+            return;
+        }
+    };
+
+    let pathfinder = GatewayPathfinder::new_from_config(&config);
+    let identity_keypair = load_identity_keys(&pathfinder);
+
+    let certificate = match identity_certificate(&identity_keypair, &config.get_announce_address())
+    {
+        Ok(certificate) => certificate,
+        Err(err) => {
+            error!("Failed to generate the TLS certificate - {}", err);
+            return;
+        }
+    };
+    let certificate_pem = match certificate.serialize_pem() {
+        Ok(certificate_pem) => certificate_pem,
+        Err(err) => {
+            error!("Failed to serialize the TLS certificate - {}", err);
+            return;
+        }
+    };
+
+    // overwrite the already configured files, if any
+    let (certificate_file, private_key_file) = config.get_tls_files().unwrap_or_else(|| {
+        (
+            config.data_directory().join(DEFAULT_TLS_CERTIFICATE_FILE),
+            config.data_directory().join(DEFAULT_TLS_PRIVATE_KEY_FILE),
+        )
+    });
+    if let Err(err) = fs::write(&certificate_file, certificate_pem).and_then(|_| {
+        write_private_key(&private_key_file, &certificate.serialize_private_key_pem())
+    }) {
+        error!("Failed to save the TLS certificate - {}", err);
+        return;
+    }
+
+    let config = config
+        .with_tls_certificate_file(&certificate_file)
+        .with_tls_private_key_file(&private_key_file);
+    if let Err(err) = config.save_to_file(None) {
+        error!("Failed to save the updated config - {}", err);
+        return;
+    }
+
+    println!(
+        "Saved the TLS certificate issued for the gateway identity key to {:?} and its private key to {:?}",
+        certificate_file, private_key_file
+    );
+    println!("Note that browsers only accept certificates trusted by the web PKI, so the webassembly clients are going to keep using the plain listener");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::client_handling::websocket::tls::load_tls_acceptor;
+    use gateway_client::GatewayClient;
+    use rand::rngs::OsRng;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    // saves the certificate the same way as the command does and loads it as the tls listener would
+    fn save_and_load_certificate(certificate: &Certificate, dir: &Path) -> TlsAcceptor {
+        let certificate_file = dir.join(DEFAULT_TLS_CERTIFICATE_FILE);
+        let private_key_file = dir.join(DEFAULT_TLS_PRIVATE_KEY_FILE);
+        fs::write(&certificate_file, certificate.serialize_pem().unwrap()).unwrap();
+        write_private_key(&private_key_file, &certificate.serialize_private_key_pem()).unwrap();
+        load_tls_acceptor(&certificate_file, &private_key_file).unwrap()
+    }
+
+    // starts a local wss listener accepting a single connection and returns its address
+    async fn start_wss_listener(acceptor: TlsAcceptor) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("wss://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            if let Ok(tls_stream) = acceptor.accept(socket).await {
+                let _ = tokio_tungstenite::accept_async(tls_stream).await;
+            }
+        });
+
+        address
+    }
+
+    async fn connect_client(address: String, gateway_identity: identity::PublicKey) -> bool {
+        let mut client = GatewayClient::new_init(
+            address,
+            gateway_identity,
+            "owner".to_string(),
+            Arc::new(identity::KeyPair::new(&mut OsRng)),
+            Duration::from_secs(5),
+        );
+        client.establish_connection().await.is_ok()
+    }
+
+    #[test]
+    fn certificate_is_issued_for_the_identity_key() {
+        let identity_keypair = identity::KeyPair::new(&mut OsRng);
+        let certificate = identity_certificate(&identity_keypair, "1.2.3.4").unwrap();
+        assert_eq!(
+            certificate.get_key_pair().public_key_raw(),
+            &identity_keypair.public_key().to_bytes()[..]
+        );
+
+        // and it can be used by the tls listener
+        let dir = tempfile::tempdir().unwrap();
+        save_and_load_certificate(&certificate, dir.path());
+    }
+
+    #[cfg(target_family = "unix")]
+    #[test]
+    fn private_key_is_only_accessible_by_the_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let private_key_file = dir.path().join(DEFAULT_TLS_PRIVATE_KEY_FILE);
+        let mode = |path: &Path| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+
+        write_private_key(&private_key_file, "key").unwrap();
+        assert_eq!(mode(&private_key_file), 0o600);
+
+        // including when overwriting a file created with the default permissions
+        fs::set_permissions(&private_key_file, fs::Permissions::from_mode(0o644)).unwrap();
+        write_private_key(&private_key_file, "new key").unwrap();
+        assert_eq!(mode(&private_key_file), 0o600);
+        assert_eq!(fs::read_to_string(&private_key_file).unwrap(), "new key");
+    }
+
+    #[tokio::test]
+    async fn clients_connect_over_wss_to_the_pinned_certificate() {
+        let identity_keypair = identity::KeyPair::new(&mut OsRng);
+        let certificate = identity_certificate(&identity_keypair, "127.0.0.1").unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let acceptor = save_and_load_certificate(&certificate, dir.path());
+
+        let address = start_wss_listener(acceptor.clone()).await;
+        assert!(connect_client(address, *identity_keypair.public_key()).await);
+
+        // the certificate is not accepted for any other gateway
+        let address = start_wss_listener(acceptor).await;
+        let other_identity = identity::KeyPair::new(&mut OsRng);
+        assert!(!connect_client(address, *other_identity.public_key()).await);
+    }
+}
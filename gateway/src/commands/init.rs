@@ -31,6 +31,22 @@ pub struct Init {
     #[clap(long)]
     clients_port: Option<u16>,
 
+    /// The port on which the gateway will be listening for clients connecting over TLS
+    #[clap(long)]
+    clients_wss_port: Option<u16>,
+
+    /// Path to PEM file containing the certificate chain used for TLS client connections
+    #[clap(long)]
+    tls_certificate: Option<String>,
+
+    /// Path to PEM file containing the private key of the TLS certificate
+    #[clap(long)]
+    tls_private_key: Option<String>,
+
+    /// The port on which the gateway will be listening for http requests
+    #[clap(long)]
+    http_api_port: Option<u16>,
+
     /// The host that will be reported to the directory server
     #[clap(long)]
     announce_host: Option<String>,
@@ -70,6 +86,10 @@ impl From<Init> for OverrideConfig {
             wallet_address: Some(init_config.wallet_address),
             mix_port: init_config.mix_port,
             clients_port: init_config.clients_port,
+            clients_wss_port: init_config.clients_wss_port,
+            tls_certificate: init_config.tls_certificate,
+            tls_private_key: init_config.tls_private_key,
+            http_api_port: init_config.http_api_port,
             datastore: init_config.datastore,
             announce_host: init_config.announce_host,
             validator_apis: init_config.validator_apis,
@@ -160,6 +180,10 @@ mod tests {
             wallet_address: "n1z9egw0knv47nmur0p8vk4rcx59h9gg4zjx9ede".to_string(),
             mix_port: Some(42),
             clients_port: Some(43),
+            clients_wss_port: None,
+            tls_certificate: None,
+            tls_private_key: None,
+            http_api_port: Some(44),
             announce_host: Some("foo-announce-host".to_string()),
             datastore: Some("foo-datastore".to_string()),
             validator_apis: None,
@@ -9,6 +9,7 @@ use colored::Colorize;
 use crypto::bech32_address_validation;
 use url::Url;
 
+pub(crate) mod generate_tls_certificate;
 pub(crate) mod init;
 pub(crate) mod node_details;
 pub(crate) mod run;
@@ -22,6 +23,9 @@ const DEFAULT_VALIDATOR_ENDPOINT: &str = "http://localhost:26657";
 
 #[derive(Subcommand)]
 pub(crate) enum Commands {
+    /// Generate a TLS certificate issued for the gateway identity key
+    GenerateTlsCertificate(generate_tls_certificate::GenerateTlsCertificate),
+
     /// Initialise the gateway
     Init(init::Init),
 
@@ -44,6 +48,10 @@ pub(crate) struct OverrideConfig {
     wallet_address: Option<String>,
     mix_port: Option<u16>,
     clients_port: Option<u16>,
+    clients_wss_port: Option<u16>,
+    tls_certificate: Option<String>,
+    tls_private_key: Option<String>,
+    http_api_port: Option<u16>,
     datastore: Option<String>,
     announce_host: Option<String>,
     validator_apis: Option<String>,
@@ -61,6 +69,7 @@ pub(crate) struct OverrideConfig {
 
 pub(crate) async fn execute(args: Cli) {
     match &args.command {
+        Commands::GenerateTlsCertificate(m) => generate_tls_certificate::execute(m),
         Commands::Init(m) => init::execute(m).await,
         Commands::NodeDetails(m) => node_details::execute(m).await,
         Commands::Run(m) => run::execute(m).await,
@@ -95,6 +104,22 @@ pub(crate) fn override_config(mut config: Config, args: OverrideConfig) -> Confi
         config = config.with_clients_port(clients_port);
     }
 
+    if let Some(clients_wss_port) = args.clients_wss_port {
+        config = config.with_clients_wss_port(clients_wss_port);
+    }
+
+    if let Some(tls_certificate) = args.tls_certificate {
+        config = config.with_tls_certificate_file(tls_certificate);
+    }
+
+    if let Some(tls_private_key) = args.tls_private_key {
+        config = config.with_tls_private_key_file(tls_private_key);
+    }
+
+    if let Some(http_api_port) = args.http_api_port {
+        config = config.with_http_api_port(http_api_port);
+    }
+
     if let Some(announce_host) = args.announce_host {
         config = config.with_announce_address(announce_host);
     } else if was_host_overridden {
@@ -31,6 +31,22 @@ pub struct Run {
     #[clap(long)]
     clients_port: Option<u16>,
 
+    /// The port on which the gateway will be listening for clients connecting over TLS
+    #[clap(long)]
+    clients_wss_port: Option<u16>,
+
+    /// Path to PEM file containing the certificate chain used for TLS client connections
+    #[clap(long)]
+    tls_certificate: Option<String>,
+
+    /// Path to PEM file containing the private key of the TLS certificate
+    #[clap(long)]
+    tls_private_key: Option<String>,
+
+    /// The port on which the gateway will be listening for http requests
+    #[clap(long)]
+    http_api_port: Option<u16>,
+
     /// The host that will be reported to the directory server
     #[clap(long)]
     announce_host: Option<String>,
@@ -70,6 +86,10 @@ impl From<Run> for OverrideConfig {
             wallet_address: run_config.wallet_address,
             mix_port: run_config.mix_port,
             clients_port: run_config.clients_port,
+            clients_wss_port: run_config.clients_wss_port,
+            tls_certificate: run_config.tls_certificate,
+            tls_private_key: run_config.tls_private_key,
+            http_api_port: run_config.http_api_port,
             datastore: run_config.datastore,
             announce_host: run_config.announce_host,
             validator_apis: run_config.validator_apis,
@@ -26,6 +26,10 @@ const DEFAULT_MAXIMUM_CONNECTION_BUFFER_SIZE: usize = 128;
 
 const DEFAULT_STORED_MESSAGE_FILENAME_LENGTH: u16 = 16;
 const DEFAULT_MESSAGE_RETRIEVAL_LIMIT: i64 = 100;
+const DEFAULT_STORED_MESSAGE_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const DEFAULT_MESSAGE_PRUNING_INTERVAL: Duration = Duration::from_secs(10 * 60);
+const DEFAULT_MAX_STORED_MESSAGES_PER_CLIENT: i64 = 50_000;
+const DEFAULT_MAX_STORED_BYTES_PER_CLIENT: i64 = 100 * 1024 * 1024;
 
 pub fn missing_string_value() -> String {
     MISSING_VALUE.to_string()
@@ -43,6 +47,14 @@ fn default_clients_port() -> u16 {
     DEFAULT_CLIENT_LISTENING_PORT
 }
 
+fn default_clients_wss_port() -> u16 {
+    DEFAULT_CLIENT_WSS_LISTENING_PORT
+}
+
+fn default_http_api_port() -> u16 {
+    DEFAULT_GATEWAY_HTTP_API_LISTENING_PORT
+}
+
 #[derive(Debug, Default, Deserialize, PartialEq, Serialize)]
 pub struct Config {
     gateway: Gateway,
@@ -177,6 +189,26 @@ impl Config {
         self
     }
 
+    pub fn with_clients_wss_port(mut self, port: u16) -> Self {
+        self.gateway.clients_wss_port = port;
+        self
+    }
+
+    pub fn with_tls_certificate_file<S: Into<PathBuf>>(mut self, path: S) -> Self {
+        self.gateway.tls_certificate_file = path.into();
+        self
+    }
+
+    pub fn with_tls_private_key_file<S: Into<PathBuf>>(mut self, path: S) -> Self {
+        self.gateway.tls_private_key_file = path.into();
+        self
+    }
+
+    pub fn with_http_api_port(mut self, port: u16) -> Self {
+        self.gateway.http_api_port = port;
+        self
+    }
+
     pub fn announce_host_from_listening_host(mut self) -> Self {
         self.gateway.announce_address = self.gateway.listening_address.to_string();
         self
@@ -231,6 +263,10 @@ impl Config {
         self.gateway.validator_api_urls.clone()
     }
 
+    pub fn get_share_spent_credentials(&self) -> bool {
+        self.gateway.share_spent_credentials
+    }
+
     #[cfg(not(feature = "coconut"))]
     pub fn get_validator_nymd_endpoints(&self) -> Vec<Url> {
         self.gateway.validator_nymd_urls.clone()
@@ -256,6 +292,29 @@ impl Config {
         self.gateway.clients_port
     }
 
+    pub fn get_clients_wss_port(&self) -> u16 {
+        self.gateway.clients_wss_port
+    }
+
+    /// Returns paths to the certificate chain and the private key used for the TLS client listener
+    /// if both of them were set.
+    pub fn get_tls_files(&self) -> Option<(PathBuf, PathBuf)> {
+        if self.gateway.tls_certificate_file.as_os_str().is_empty()
+            || self.gateway.tls_private_key_file.as_os_str().is_empty()
+        {
+            None
+        } else {
+            Some((
+                self.gateway.tls_certificate_file.clone(),
+                self.gateway.tls_private_key_file.clone(),
+            ))
+        }
+    }
+
+    pub fn get_http_api_port(&self) -> u16 {
+        self.gateway.http_api_port
+    }
+
     pub fn get_persistent_store_path(&self) -> PathBuf {
         self.gateway.persistent_storage.clone()
     }
@@ -280,6 +339,22 @@ impl Config {
         self.debug.message_retrieval_limit
     }
 
+    pub fn get_stored_message_ttl(&self) -> Duration {
+        self.debug.stored_message_ttl
+    }
+
+    pub fn get_message_pruning_interval(&self) -> Duration {
+        self.debug.message_pruning_interval
+    }
+
+    pub fn get_max_stored_messages_per_client(&self) -> i64 {
+        self.debug.max_stored_messages_per_client
+    }
+
+    pub fn get_max_stored_bytes_per_client(&self) -> i64 {
+        self.debug.max_stored_bytes_per_client
+    }
+
     pub fn get_version(&self) -> &str {
         &self.gateway.version
     }
@@ -323,6 +398,29 @@ pub struct Gateway {
     #[serde(default = "default_clients_port")]
     clients_port: u16,
 
+    /// Port used for listening for client traffic secured with TLS. It is only used if both
+    /// `tls_certificate_file` and `tls_private_key_file` are set.
+    /// (default: 9001)
+    #[serde(default = "default_clients_wss_port")]
+    clients_wss_port: u16,
+
+    /// Path to PEM file containing the certificate chain presented to clients connecting over TLS.
+    /// If the public key of the certificate is the gateway ed25519 identity key, native clients
+    /// are going to accept it without any certificate authority involved, while browsers only
+    /// accept certificates trusted by the web PKI. Such a certificate can be created with the
+    /// `generate-tls-certificate` command.
+    #[serde(default)]
+    tls_certificate_file: PathBuf,
+
+    /// Path to PEM file containing the private key (PKCS#8 or RSA) of the TLS certificate.
+    #[serde(default)]
+    tls_private_key_file: PathBuf,
+
+    /// Port used for listening for http requests.
+    /// (default: 8080)
+    #[serde(default = "default_http_api_port")]
+    http_api_port: u16,
+
     /// Path to file containing private identity key.
     private_identity_key_file: PathBuf,
 
@@ -346,6 +444,12 @@ pub struct Gateway {
     #[cfg(not(feature = "coconut"))]
     validator_nymd_urls: Vec<Url>,
 
+    /// Indicates whether serial numbers of redeemed coconut credentials should be shared with
+    /// the validator APIs, so that other gateways using them would immediately reject the same
+    /// credential.
+    #[serde(default)]
+    share_spent_credentials: bool,
+
     /// Mnemonic of a cosmos wallet used in checking for double spending.
     cosmos_mnemonic: String,
 
@@ -393,6 +497,10 @@ impl Default for Gateway {
             announce_address: "127.0.0.1".to_string(),
             mix_port: DEFAULT_MIX_LISTENING_PORT,
             clients_port: DEFAULT_CLIENT_LISTENING_PORT,
+            clients_wss_port: DEFAULT_CLIENT_WSS_LISTENING_PORT,
+            tls_certificate_file: Default::default(),
+            tls_private_key_file: Default::default(),
+            http_api_port: DEFAULT_GATEWAY_HTTP_API_LISTENING_PORT,
             private_identity_key_file: Default::default(),
             public_identity_key_file: Default::default(),
             private_sphinx_key_file: Default::default(),
@@ -402,6 +510,7 @@ impl Default for Gateway {
             validator_api_urls: default_api_endpoints(),
             #[cfg(not(feature = "coconut"))]
             validator_nymd_urls: default_nymd_endpoints(),
+            share_spent_credentials: false,
             cosmos_mnemonic: "".to_string(),
             nym_root_directory: Config::default_root_directory(),
             persistent_storage: Default::default(),
@@ -443,6 +552,20 @@ struct Debug {
 
     /// Number of messages from offline client that can be pulled at once from the storage.
     message_retrieval_limit: i64,
+
+    /// Duration for which messages for offline clients are retained before getting removed.
+    #[serde(with = "humantime_serde")]
+    stored_message_ttl: Duration,
+
+    /// Delay between subsequent runs of the task removing expired messages.
+    #[serde(with = "humantime_serde")]
+    message_pruning_interval: Duration,
+
+    /// Maximum number of messages that can be stored for a single offline client.
+    max_stored_messages_per_client: i64,
+
+    /// Maximum total size, in bytes, of messages that can be stored for a single offline client.
+    max_stored_bytes_per_client: i64,
 }
 
 impl Default for Debug {
@@ -455,6 +578,10 @@ impl Default for Debug {
             maximum_connection_buffer_size: DEFAULT_MAXIMUM_CONNECTION_BUFFER_SIZE,
             stored_messages_filename_length: DEFAULT_STORED_MESSAGE_FILENAME_LENGTH,
             message_retrieval_limit: DEFAULT_MESSAGE_RETRIEVAL_LIMIT,
+            stored_message_ttl: DEFAULT_STORED_MESSAGE_TTL,
+            message_pruning_interval: DEFAULT_MESSAGE_PRUNING_INTERVAL,
+            max_stored_messages_per_client: DEFAULT_MAX_STORED_MESSAGES_PER_CLIENT,
+            max_stored_bytes_per_client: DEFAULT_MAX_STORED_BYTES_PER_CLIENT,
         }
     }
 }
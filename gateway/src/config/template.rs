@@ -56,6 +56,25 @@ mix_port = {{ gateway.mix_port }}
 # (default: 9000)
 clients_port = {{ gateway.clients_port }}
 
+# Port used for listening for client websocket traffic secured with TLS.
+# It is only used if both `tls_certificate_file` and `tls_private_key_file` are set.
+# (default: 9001)
+clients_wss_port = {{ gateway.clients_wss_port }}
+
+# Path to PEM file containing the certificate chain presented to clients connecting over TLS.
+# If the public key of the certificate is the gateway ed25519 identity key, native clients
+# are going to accept it without any certificate authority involved, while browsers only
+# accept certificates trusted by the web PKI. Such a certificate can be created with the
+# `generate-tls-certificate` command.
+tls_certificate_file = '{{ gateway.tls_certificate_file }}'
+
+# Path to PEM file containing the private key (PKCS#8 or RSA) of the TLS certificate.
+tls_private_key_file = '{{ gateway.tls_private_key_file }}'
+
+# Port used for listening for http requests.
+# (default: 8080)
+http_api_port = {{ gateway.http_api_port }}
+
 # Addresses to APIs running on validator from which the node gets the view of the network.
 validator_api_urls = [
     {{#each gateway.validator_api_urls }}
@@ -70,6 +89,10 @@ validator_nymd_urls = [
     {{/each}}
 ]
 
+# Indicates whether serial numbers of redeemed coconut credentials should be shared with
+# the validator APIs, so that other gateways using them would immediately reject the same credential.
+share_spent_credentials = {{ gateway.share_spent_credentials }}
+
 cosmos_mnemonic = "{{ gateway.cosmos_mnemonic }}"
 
 # Nym wallet address on the blockchain that should control this gateway
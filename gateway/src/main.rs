@@ -1,6 +1,9 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+#[macro_use]
+extern crate rocket;
+
 use clap::{crate_version, Parser};
 use network_defaults::DEFAULT_NETWORK;
 use once_cell::sync::OnceCell;
@@ -55,4 +55,12 @@ impl ActiveClientsStore {
     pub(crate) fn insert(&self, client: DestinationAddressBytes, handle: MixMessageSender) {
         self.0.insert(client, handle);
     }
+
+    /// Gets the number of clients currently connected to the gateway.
+    pub(crate) fn size(&self) -> usize {
+        self.0
+            .iter()
+            .filter(|entry| !entry.value().is_closed())
+            .count()
+    }
 }
@@ -11,6 +11,8 @@ use gateway_requests::types::{BinaryRequest, ServerResponse};
 use gateway_requests::{ClientControlRequest, GatewayRequestsError};
 use log::*;
 use nymsphinx::forwarding::packet::MixPacket;
+#[cfg(feature = "coconut")]
+use nymsphinx::DestinationAddressBytes;
 use rand::{CryptoRng, Rng};
 use std::convert::TryFrom;
 use std::process;
@@ -71,6 +73,10 @@ pub(crate) enum RequestHandlingError {
     #[cfg(feature = "coconut")]
     #[error("Not enough validator API endpoints provided. Needed {needed}, received {received}")]
     NotEnoughValidatorAPIs { received: usize, needed: usize },
+
+    #[cfg(feature = "coconut")]
+    #[error("The provided bandwidth credential has already been spent")]
+    CredentialAlreadySpent,
 }
 
 impl RequestHandlingError {
@@ -94,6 +100,36 @@ impl IntoWSMessage for Result<ServerResponse, RequestHandlingError> {
     }
 }
 
+#[cfg(feature = "coconut")]
+/// Marks the credential as spent by the client. If it has already been spent, the redemption
+/// is only allowed to proceed if it's the same client resuming the previously failed release
+/// of funds. Returns whether the release is being resumed.
+///
+/// # Arguments
+///
+/// * `storage`: gateway storage keeping track of the spent credentials.
+/// * `blinded_serial_number`: base58-encoded blinded serial number of the credential.
+/// * `client_address`: address of the client redeeming the credential.
+async fn start_credential_redemption<St: Storage>(
+    storage: &St,
+    blinded_serial_number: &str,
+    client_address: DestinationAddressBytes,
+) -> Result<bool, RequestHandlingError> {
+    if storage
+        .insert_spent_credential(blinded_serial_number, client_address)
+        .await?
+    {
+        Ok(false)
+    } else if storage
+        .resume_credential_release(blinded_serial_number, client_address)
+        .await?
+    {
+        Ok(true)
+    } else {
+        Err(RequestHandlingError::CredentialAlreadySpent)
+    }
+}
+
 pub(crate) struct AuthenticatedHandler<R, S, St> {
     inner: FreshHandler<R, S, St>,
     client: ClientDetails,
@@ -164,6 +200,7 @@ where
             .storage
             .increase_bandwidth(self.client.address, amount)
             .await?;
+        self.inner.statistics.add_bandwidth_credited(amount);
         Ok(())
     }
 
@@ -177,6 +214,7 @@ where
             .storage
             .consume_bandwidth(self.client.address, amount)
             .await?;
+        self.inner.statistics.add_bandwidth_consumed(amount);
         Ok(())
     }
 
@@ -190,6 +228,7 @@ where
             error!("We failed to forward requested mix packet - {}. Presumably our mix forwarder has crashed. We cannot continue.", err);
             process::exit(1);
         }
+        self.inner.statistics.increment_mix_packets_forwarded();
     }
 
     #[cfg(feature = "coconut")]
@@ -223,6 +262,107 @@ where
             ));
         }
 
+        // mark the credential as spent before doing anything else so that any concurrent (or later)
+        // attempt at redeeming it again would get rejected straight away, unless it's the same
+        // client resuming the release of funds that has previously failed
+        let blinded_serial_number = credential.blinded_serial_number();
+        let resumed = start_credential_redemption(
+            &self.inner.storage,
+            &blinded_serial_number,
+            self.client.address,
+        )
+        .await?;
+
+        if let Err(err) = self.redeem_credential(&credential, resumed).await {
+            // if it wasn't spent elsewhere, the credential might have still reached some of
+            // the validator APIs, so rather than forgetting about it, let the same client
+            // retry the release later on
+            if !matches!(err, RequestHandlingError::CredentialAlreadySpent) {
+                self.inner
+                    .storage
+                    .mark_credential_release_pending(&blinded_serial_number)
+                    .await?;
+            }
+            return Err(err);
+        }
+
+        let bandwidth = Bandwidth::from(credential);
+        let bandwidth_value = bandwidth.value();
+
+        if bandwidth_value > i64::MAX as u64 {
+            // note that this would have represented more than 1 exabyte,
+            // which is like 125,000 worth of hard drives so I don't think we have
+            // to worry about it for now...
+            warn!("Somehow we received bandwidth value higher than 9223372036854775807. We don't really want to deal with this now");
+            return Err(RequestHandlingError::UnsupportedBandwidthValue(
+                bandwidth_value,
+            ));
+        }
+
+        self.increase_bandwidth(bandwidth_value as i64).await?;
+        let available_total = self.get_available_bandwidth().await?;
+
+        Ok(ServerResponse::Bandwidth { available_total })
+    }
+
+    #[cfg(feature = "coconut")]
+    /// Shares the spent credential with the validator APIs and releases the funds backing it.
+    ///
+    /// # Arguments
+    ///
+    /// * `credential`: verified bandwidth credential that is being redeemed.
+    /// * `resumed`: indicates whether this is a retry of a previously failed release, in which
+    ///   case the credential is not shared again, as the validator APIs that have already
+    ///   recorded it would now report it as spent.
+    async fn redeem_credential(
+        &self,
+        credential: &coconut_interface::Credential,
+        resumed: bool,
+    ) -> Result<(), RequestHandlingError> {
+        if !resumed {
+            self.share_spent_credential(credential).await?;
+        }
+        self.release_credential_funds(credential).await
+    }
+
+    #[cfg(feature = "coconut")]
+    /// Informs the validator APIs about the credential being spent, if enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `credential`: verified bandwidth credential that is being redeemed.
+    async fn share_spent_credential(
+        &self,
+        credential: &coconut_interface::Credential,
+    ) -> Result<(), RequestHandlingError> {
+        if !self.inner.coconut_verifier.share_spent_credentials() {
+            return Ok(());
+        }
+
+        let req = coconut_interface::SpentCredentialRequestBody::new(credential.clone());
+        for client in self.inner.coconut_verifier.api_clients() {
+            if client.mark_credential_spent(&req).await?.already_spent {
+                debug!(
+                    "Validator {} has reported the credential as already spent",
+                    client.validator_api.current_url()
+                );
+                return Err(RequestHandlingError::CredentialAlreadySpent);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "coconut")]
+    /// Releases the funds backing the credential via the multisig proposal.
+    ///
+    /// # Arguments
+    ///
+    /// * `credential`: verified bandwidth credential that is being redeemed.
+    async fn release_credential_funds(
+        &self,
+        credential: &coconut_interface::Credential,
+    ) -> Result<(), RequestHandlingError> {
         let req = coconut_interface::ProposeReleaseFundsRequestBody::new(credential.clone());
         let proposal_id = self
             .inner
@@ -260,23 +400,7 @@ where
             .execute_release_funds(&req)
             .await?;
 
-        let bandwidth = Bandwidth::from(credential);
-        let bandwidth_value = bandwidth.value();
-
-        if bandwidth_value > i64::MAX as u64 {
-            // note that this would have represented more than 1 exabyte,
-            // which is like 125,000 worth of hard drives so I don't think we have
-            // to worry about it for now...
-            warn!("Somehow we received bandwidth value higher than 9223372036854775807. We don't really want to deal with this now");
-            return Err(RequestHandlingError::UnsupportedBandwidthValue(
-                bandwidth_value,
-            ));
-        }
-
-        self.increase_bandwidth(bandwidth_value as i64).await?;
-        let available_total = self.get_available_bandwidth().await?;
-
-        Ok(ServerResponse::Bandwidth { available_total })
+        Ok(())
     }
 
     #[cfg(not(feature = "coconut"))]
@@ -508,3 +632,51 @@ where
         trace!("The stream was closed!");
     }
 }
+
+#[cfg(all(test, feature = "coconut"))]
+mod tests {
+    use super::*;
+    use crate::node::storage::PersistentStorage;
+
+    fn client(byte: u8) -> DestinationAddressBytes {
+        DestinationAddressBytes::from_bytes([byte; 32])
+    }
+
+    #[tokio::test]
+    async fn failed_release_can_only_be_resumed_by_the_redeeming_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = PersistentStorage::init(dir.path().join("gateway.sqlite"), 100, 100, 1000)
+            .await
+            .unwrap();
+
+        assert!(!start_credential_redemption(&storage, "serial", client(1))
+            .await
+            .unwrap());
+
+        // the release is still in progress (or has succeeded), so nobody can redeem it again
+        assert!(matches!(
+            start_credential_redemption(&storage, "serial", client(1)).await,
+            Err(RequestHandlingError::CredentialAlreadySpent)
+        ));
+
+        // the release has failed
+        storage
+            .mark_credential_release_pending("serial")
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            start_credential_redemption(&storage, "serial", client(2)).await,
+            Err(RequestHandlingError::CredentialAlreadySpent)
+        ));
+        assert!(start_credential_redemption(&storage, "serial", client(1))
+            .await
+            .unwrap());
+
+        // once resumed, the credential can't be presented again
+        assert!(matches!(
+            start_credential_redemption(&storage, "serial", client(1)).await,
+            Err(RequestHandlingError::CredentialAlreadySpent)
+        ));
+    }
+}
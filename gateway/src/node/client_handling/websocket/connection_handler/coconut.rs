@@ -7,13 +7,19 @@ use validator_client::ApiClient;
 pub struct CoconutVerifier {
     api_clients: Vec<ApiClient>,
     aggregated_verification_key: VerificationKey,
+    share_spent_credentials: bool,
 }
 
 impl CoconutVerifier {
-    pub fn new(api_clients: Vec<ApiClient>, aggregated_verification_key: VerificationKey) -> Self {
+    pub fn new(
+        api_clients: Vec<ApiClient>,
+        aggregated_verification_key: VerificationKey,
+        share_spent_credentials: bool,
+    ) -> Self {
         CoconutVerifier {
             api_clients,
             aggregated_verification_key,
+            share_spent_credentials,
         }
     }
 
@@ -24,4 +30,8 @@ impl CoconutVerifier {
     pub fn aggregated_verification_key(&self) -> &VerificationKey {
         &self.aggregated_verification_key
     }
+
+    pub fn share_spent_credentials(&self) -> bool {
+        self.share_spent_credentials
+    }
 }
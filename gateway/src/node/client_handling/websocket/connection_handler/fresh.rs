@@ -7,6 +7,7 @@ use crate::node::client_handling::websocket::connection_handler::coconut::Coconu
 use crate::node::client_handling::websocket::connection_handler::{
     AuthenticatedHandler, ClientDetails, InitialAuthResult, SocketStream,
 };
+use crate::node::statistics::GatewayStatistics;
 use crate::node::storage::error::StorageError;
 use crate::node::storage::Storage;
 use crypto::asymmetric::identity;
@@ -75,6 +76,7 @@ pub(crate) struct FreshHandler<R, S, St> {
     pub(crate) outbound_mix_sender: MixForwardingSender,
     pub(crate) socket_connection: SocketStream<S>,
     pub(crate) storage: St,
+    pub(crate) statistics: GatewayStatistics,
 
     #[cfg(not(feature = "coconut"))]
     pub(crate) erc20_bridge: Arc<ERC20Bridge>,
@@ -100,6 +102,7 @@ where
         local_identity: Arc<identity::KeyPair>,
         storage: St,
         active_clients_store: ActiveClientsStore,
+        statistics: GatewayStatistics,
         #[cfg(feature = "coconut")] coconut_verifier: Arc<CoconutVerifier>,
         #[cfg(not(feature = "coconut"))] erc20_bridge: Arc<ERC20Bridge>,
     ) -> Self {
@@ -111,6 +114,7 @@ where
             socket_connection: SocketStream::RawTcp(conn),
             local_identity,
             storage,
+            statistics,
             #[cfg(feature = "coconut")]
             coconut_verifier,
             #[cfg(not(feature = "coconut"))]
@@ -3,6 +3,7 @@
 
 use crate::node::client_handling::active_clients::ActiveClientsStore;
 use crate::node::client_handling::websocket::connection_handler::FreshHandler;
+use crate::node::statistics::GatewayStatistics;
 use crate::node::storage::Storage;
 use crypto::asymmetric::identity;
 use log::*;
@@ -12,6 +13,7 @@ use std::net::SocketAddr;
 use std::process;
 use std::sync::Arc;
 use tokio::task::JoinHandle;
+use tokio_rustls::TlsAcceptor;
 
 #[cfg(feature = "coconut")]
 use crate::node::client_handling::websocket::connection_handler::coconut::CoconutVerifier;
@@ -19,10 +21,13 @@ use crate::node::client_handling::websocket::connection_handler::coconut::Coconu
 #[cfg(not(feature = "coconut"))]
 use crate::node::client_handling::websocket::connection_handler::eth_events::ERC20Bridge;
 
+#[derive(Clone)]
 pub(crate) struct Listener {
     address: SocketAddr,
     local_identity: Arc<identity::KeyPair>,
     disabled_credentials_mode: bool,
+    /// If specified, all connections are going to be secured with TLS before upgrading them to websockets.
+    tls_acceptor: Option<TlsAcceptor>,
 
     #[cfg(feature = "coconut")]
     pub(crate) coconut_verifier: Arc<CoconutVerifier>,
@@ -35,20 +40,49 @@ impl Listener {
         address: SocketAddr,
         local_identity: Arc<identity::KeyPair>,
         disabled_credentials_mode: bool,
+        tls_acceptor: Option<TlsAcceptor>,
         #[cfg(feature = "coconut")] coconut_verifier: Arc<CoconutVerifier>,
-        #[cfg(not(feature = "coconut"))] erc20_bridge: ERC20Bridge,
+        #[cfg(not(feature = "coconut"))] erc20_bridge: Arc<ERC20Bridge>,
     ) -> Self {
         Listener {
             address,
             local_identity,
             disabled_credentials_mode,
+            tls_acceptor,
             #[cfg(feature = "coconut")]
             coconut_verifier,
             #[cfg(not(feature = "coconut"))]
-            erc20_bridge: Arc::new(erc20_bridge),
+            erc20_bridge,
         }
     }
 
+    fn new_handler<S, St>(
+        &self,
+        conn: S,
+        outbound_mix_sender: MixForwardingSender,
+        storage: St,
+        active_clients_store: ActiveClientsStore,
+        statistics: GatewayStatistics,
+    ) -> FreshHandler<OsRng, S, St>
+    where
+        St: Storage,
+    {
+        FreshHandler::new(
+            OsRng,
+            conn,
+            self.disabled_credentials_mode,
+            outbound_mix_sender,
+            Arc::clone(&self.local_identity),
+            storage,
+            active_clients_store,
+            statistics,
+            #[cfg(feature = "coconut")]
+            Arc::clone(&self.coconut_verifier),
+            #[cfg(not(feature = "coconut"))]
+            Arc::clone(&self.erc20_bridge),
+        )
+    }
+
     // TODO: change the signature to pub(crate) async fn run(&self, handler: Handler)
 
     pub(crate) async fn run<St>(
@@ -56,10 +90,15 @@ impl Listener {
         outbound_mix_sender: MixForwardingSender,
         storage: St,
         active_clients_store: ActiveClientsStore,
+        statistics: GatewayStatistics,
     ) where
         St: Storage + Clone + 'static,
     {
-        info!("Starting websocket listener at {}", self.address);
+        if self.tls_acceptor.is_some() {
+            info!("Starting TLS websocket listener at {}", self.address);
+        } else {
+            info!("Starting websocket listener at {}", self.address);
+        }
         let tcp_listener = match tokio::net::TcpListener::bind(self.address).await {
             Ok(listener) => listener,
             Err(err) => {
@@ -74,20 +113,48 @@ impl Listener {
                     trace!("received a socket connection from {}", remote_addr);
                     // TODO: I think we *REALLY* need a mechanism for having a maximum number of connected
                     // clients or spawned tokio tasks -> perhaps a worker system?
-                    let handle = FreshHandler::new(
-                        OsRng,
-                        socket,
-                        self.disabled_credentials_mode,
-                        outbound_mix_sender.clone(),
-                        Arc::clone(&self.local_identity),
-                        storage.clone(),
-                        active_clients_store.clone(),
-                        #[cfg(feature = "coconut")]
-                        Arc::clone(&self.coconut_verifier),
-                        #[cfg(not(feature = "coconut"))]
-                        Arc::clone(&self.erc20_bridge),
-                    );
-                    tokio::spawn(async move { handle.start_handling().await });
+                    match &self.tls_acceptor {
+                        None => {
+                            let handle = self.new_handler(
+                                socket,
+                                outbound_mix_sender.clone(),
+                                storage.clone(),
+                                active_clients_store.clone(),
+                                statistics.clone(),
+                            );
+                            tokio::spawn(async move { handle.start_handling().await });
+                        }
+                        Some(tls_acceptor) => {
+                            // the handshake is performed in the spawned task so that a slow client
+                            // would not block the listener
+                            let tls_stream = tls_acceptor.accept(socket);
+                            let listener = self.clone();
+                            let outbound_mix_sender = outbound_mix_sender.clone();
+                            let storage = storage.clone();
+                            let active_clients_store = active_clients_store.clone();
+                            let statistics = statistics.clone();
+                            tokio::spawn(async move {
+                                match tls_stream.await {
+                                    Ok(tls_stream) => {
+                                        listener
+                                            .new_handler(
+                                                tls_stream,
+                                                outbound_mix_sender,
+                                                storage,
+                                                active_clients_store,
+                                                statistics,
+                                            )
+                                            .start_handling()
+                                            .await
+                                    }
+                                    Err(err) => debug!(
+                                        "TLS handshake with {} has failed - {}",
+                                        remote_addr, err
+                                    ),
+                                }
+                            });
+                        }
+                    }
                 }
                 Err(e) => warn!("failed to get client: {:?}", e),
             }
@@ -99,13 +166,19 @@ impl Listener {
         outbound_mix_sender: MixForwardingSender,
         storage: St,
         active_clients_store: ActiveClientsStore,
+        statistics: GatewayStatistics,
     ) -> JoinHandle<()>
     where
         St: Storage + Clone + 'static,
     {
         tokio::spawn(async move {
-            self.run(outbound_mix_sender, storage, active_clients_store)
-                .await
+            self.run(
+                outbound_mix_sender,
+                storage,
+                active_clients_store,
+                statistics,
+            )
+            .await
         })
     }
 }
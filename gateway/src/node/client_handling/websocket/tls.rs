@@ -0,0 +1,74 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+use tokio_rustls::rustls::internal::pemfile::{certs, pkcs8_private_keys, rsa_private_keys};
+use tokio_rustls::rustls::{Certificate, NoClientAuth, PrivateKey, ServerConfig, TLSError};
+use tokio_rustls::TlsAcceptor;
+
+#[derive(Debug, Error)]
+pub(crate) enum TlsSetupError {
+    #[error("failed to read {path:?} - {source}")]
+    FileReadError { path: PathBuf, source: io::Error },
+
+    #[error("{0:?} does not contain any valid PEM-encoded certificates")]
+    NoCertificates(PathBuf),
+
+    #[error("{0:?} does not contain a valid PEM-encoded PKCS#8 or RSA private key")]
+    NoPrivateKey(PathBuf),
+
+    #[error("the provided certificate chain and private key could not be used - {0}")]
+    InvalidCertificate(#[from] TLSError),
+}
+
+fn open_file(path: &Path) -> Result<BufReader<File>, TlsSetupError> {
+    File::open(path)
+        .map(BufReader::new)
+        .map_err(|source| TlsSetupError::FileReadError {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+fn load_certificates(path: &Path) -> Result<Vec<Certificate>, TlsSetupError> {
+    let certificates =
+        certs(&mut open_file(path)?).map_err(|_| TlsSetupError::NoCertificates(path.to_owned()))?;
+    if certificates.is_empty() {
+        return Err(TlsSetupError::NoCertificates(path.to_owned()));
+    }
+    Ok(certificates)
+}
+
+fn load_private_key(path: &Path) -> Result<PrivateKey, TlsSetupError> {
+    let mut keys = pkcs8_private_keys(&mut open_file(path)?).unwrap_or_default();
+    if keys.is_empty() {
+        keys = rsa_private_keys(&mut open_file(path)?).unwrap_or_default();
+    }
+    keys.into_iter()
+        .next()
+        .ok_or_else(|| TlsSetupError::NoPrivateKey(path.to_owned()))
+}
+
+/// Creates the acceptor performing TLS handshakes with the connecting clients, using
+/// the PEM-encoded certificate chain and private key from the provided files.
+///
+/// # Arguments
+///
+/// * `certificate_file`: path to the file containing the certificate chain, starting with the end-entity certificate.
+/// * `private_key_file`: path to the file containing the private key of the end-entity certificate.
+pub(crate) fn load_tls_acceptor(
+    certificate_file: &Path,
+    private_key_file: &Path,
+) -> Result<TlsAcceptor, TlsSetupError> {
+    let certificates = load_certificates(certificate_file)?;
+    let private_key = load_private_key(private_key_file)?;
+
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config.set_single_cert(certificates, private_key)?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
@@ -0,0 +1,12 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::node_description::NodeDescription;
+use rocket::serde::json::Json;
+use rocket::State;
+
+/// Returns a description of the gateway as provided by its operator.
+#[get("/description")]
+pub(crate) fn description(description: &State<NodeDescription>) -> Json<NodeDescription> {
+    Json(description.inner().clone())
+}
@@ -0,0 +1,26 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+pub(crate) mod description;
+pub(crate) mod stats;
+
+use crate::node::http::stats::StatsState;
+use crate::node::node_description::NodeDescription;
+use rocket::{Build, Request, Rocket};
+
+#[catch(404)]
+pub(crate) fn not_found(req: &Request<'_>) -> String {
+    format!("I couldn't find '{}'. Try something else?", req.uri())
+}
+
+/// Builds the HTTP API of the gateway serving its description and its running stats.
+pub(crate) fn build_http_api(
+    descriptor: NodeDescription,
+    stats_state: StatsState,
+) -> Rocket<Build> {
+    rocket::build()
+        .mount("/", routes![description::description, stats::stats])
+        .register("/", catchers![not_found])
+        .manage(descriptor)
+        .manage(stats_state)
+}
@@ -0,0 +1,199 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::client_handling::active_clients::ActiveClientsStore;
+use crate::node::statistics::{GatewayStatistics, GatewayStatisticsSnapshot};
+use crate::node::storage::Storage;
+use log::error;
+use rocket::http::Status;
+use rocket::serde::json::Json;
+use rocket::State;
+use serde::Serialize;
+
+pub(crate) struct StatsState {
+    statistics: GatewayStatistics,
+    active_clients_store: ActiveClientsStore,
+    storage: Box<dyn Storage>,
+}
+
+impl StatsState {
+    pub(crate) fn new<St>(
+        statistics: GatewayStatistics,
+        active_clients_store: ActiveClientsStore,
+        storage: St,
+    ) -> Self
+    where
+        St: Storage + 'static,
+    {
+        StatsState {
+            statistics,
+            active_clients_store,
+            storage: Box::new(storage),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub(crate) struct StoredMessagesStats {
+    messages: i64,
+    bytes: i64,
+    clients: i64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct BandwidthStats {
+    credited: u64,
+    consumed: u64,
+    available: i64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct MixPacketsStats {
+    received: u64,
+    forwarded: u64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct GatewayStatsResponse {
+    connected_clients: usize,
+    stored_messages: StoredMessagesStats,
+    bandwidth: BandwidthStats,
+    mix_packets: MixPacketsStats,
+}
+
+/// Returns running stats of the gateway. Traffic and bandwidth counters are reset on restart,
+/// while stored messages and available bandwidth reflect the current state of the storage.
+#[get("/stats")]
+pub(crate) async fn stats(state: &State<StatsState>) -> Result<Json<GatewayStatsResponse>, Status> {
+    let inbox_totals = state.storage.get_inbox_totals().await.map_err(|err| {
+        error!("failed to obtain stored messages totals - {}", err);
+        Status::InternalServerError
+    })?;
+    let available_bandwidth = state
+        .storage
+        .get_total_available_bandwidth()
+        .await
+        .map_err(|err| {
+            error!("failed to obtain total available bandwidth - {}", err);
+            Status::InternalServerError
+        })?;
+
+    let GatewayStatisticsSnapshot {
+        mix_packets_received,
+        mix_packets_forwarded,
+        bandwidth_credited,
+        bandwidth_consumed,
+    } = state.statistics.snapshot();
+
+    Ok(Json(GatewayStatsResponse {
+        connected_clients: state.active_clients_store.size(),
+        stored_messages: StoredMessagesStats {
+            messages: inbox_totals.messages,
+            bytes: inbox_totals.bytes,
+            clients: inbox_totals.clients,
+        },
+        bandwidth: BandwidthStats {
+            credited: bandwidth_credited,
+            consumed: bandwidth_consumed,
+            available: available_bandwidth,
+        },
+        mix_packets: MixPacketsStats {
+            received: mix_packets_received,
+            forwarded: mix_packets_forwarded,
+        },
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::http::build_http_api;
+    use crate::node::node_description::NodeDescription;
+    use crate::node::storage::{InMemStorage, PersistentStorage};
+    use futures::channel::mpsc;
+    use nymsphinx::DestinationAddressBytes;
+    use rocket::local::asynchronous::Client;
+    use rocket::serde::json::{json, Value};
+
+    #[tokio::test]
+    async fn description_and_stats_are_served() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = PersistentStorage::init(dir.path().join("gateway.sqlite"), 100, 100, 1000)
+            .await
+            .unwrap();
+        let client_address = DestinationAddressBytes::from_bytes([1; 32]);
+        storage
+            .store_message(client_address, vec![42; 10])
+            .await
+            .unwrap();
+        storage
+            .create_bandwidth_entry(client_address)
+            .await
+            .unwrap();
+        storage
+            .increase_bandwidth(client_address, 1000)
+            .await
+            .unwrap();
+        storage
+            .consume_bandwidth(client_address, 300)
+            .await
+            .unwrap();
+
+        let statistics = GatewayStatistics::new();
+        statistics.increment_mix_packets_received();
+        statistics.increment_mix_packets_forwarded();
+        statistics.increment_mix_packets_forwarded();
+        statistics.add_bandwidth_credited(1000);
+        statistics.add_bandwidth_consumed(300);
+
+        let active_clients_store = ActiveClientsStore::new();
+        let (sender, _receiver) = mpsc::unbounded();
+        active_clients_store.insert(client_address, sender);
+
+        let description = NodeDescription::default();
+        let rocket = build_http_api(
+            description.clone(),
+            StatsState::new(statistics, active_clients_store, storage),
+        );
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/description").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.into_json::<NodeDescription>().await.unwrap(),
+            description
+        );
+
+        let response = client.get("/stats").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        assert_eq!(
+            response.into_json::<Value>().await.unwrap(),
+            json!({
+                "connected_clients": 1,
+                "stored_messages": { "messages": 1, "bytes": 10, "clients": 1 },
+                "bandwidth": { "credited": 1000, "consumed": 300, "available": 700 },
+                "mix_packets": { "received": 1, "forwarded": 2 },
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn storage_failures_are_reported_as_server_errors() {
+        let rocket = build_http_api(
+            NodeDescription::default(),
+            StatsState::new(
+                GatewayStatistics::new(),
+                ActiveClientsStore::new(),
+                InMemStorage,
+            ),
+        );
+        let client = Client::tracked(rocket)
+            .await
+            .expect("valid rocket instance");
+
+        let response = client.get("/stats").dispatch().await;
+        assert_eq!(response.status(), Status::InternalServerError);
+    }
+}
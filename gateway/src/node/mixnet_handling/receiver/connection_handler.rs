@@ -4,6 +4,7 @@
 use crate::node::client_handling::active_clients::ActiveClientsStore;
 use crate::node::client_handling::websocket::message_receiver::MixMessageSender;
 use crate::node::mixnet_handling::receiver::packet_processing::PacketProcessor;
+use crate::node::statistics::GatewayStatistics;
 use crate::node::storage::error::StorageError;
 use crate::node::storage::Storage;
 use futures::StreamExt;
@@ -30,6 +31,7 @@ pub(crate) struct ConnectionHandler<St: Storage> {
     active_clients_store: ActiveClientsStore,
     storage: St,
     ack_sender: MixForwardingSender,
+    statistics: GatewayStatistics,
 }
 
 impl<St: Storage + Clone> Clone for ConnectionHandler<St> {
@@ -48,6 +50,7 @@ impl<St: Storage + Clone> Clone for ConnectionHandler<St> {
             active_clients_store: self.active_clients_store.clone(),
             storage: self.storage.clone(),
             ack_sender: self.ack_sender.clone(),
+            statistics: self.statistics.clone(),
         }
     }
 }
@@ -58,6 +61,7 @@ impl<St: Storage> ConnectionHandler<St> {
         storage: St,
         ack_sender: MixForwardingSender,
         active_clients_store: ActiveClientsStore,
+        statistics: GatewayStatistics,
     ) -> Self {
         ConnectionHandler {
             packet_processor,
@@ -65,6 +69,7 @@ impl<St: Storage> ConnectionHandler<St> {
             storage,
             active_clients_store,
             ack_sender,
+            statistics,
         }
     }
 
@@ -127,6 +132,7 @@ impl<St: Storage> ConnectionHandler<St> {
             );
 
             self.ack_sender.unbounded_send(forward_ack).unwrap();
+            self.statistics.increment_mix_packets_forwarded();
         }
     }
 
@@ -142,6 +148,14 @@ impl<St: Storage> ConnectionHandler<St> {
                 .store_processed_packet_payload(client_address, unsent_plaintext)
                 .await
             {
+                Err(StorageError::InboxQuotaExceeded) => {
+                    // don't send the ack so that the sender would know the message didn't get through
+                    warn!(
+                        "The inbox of {} is full - dropping the packet",
+                        client_address
+                    );
+                    return;
+                }
                 Err(err) => error!("Failed to store client data - {}", err),
                 Ok(_) => trace!("Stored packet for {}", client_address),
             },
@@ -155,12 +169,9 @@ impl<St: Storage> ConnectionHandler<St> {
     }
 
     async fn handle_received_packet(&mut self, framed_sphinx_packet: FramedSphinxPacket) {
-        //
-        // TODO: here be replay attack detection - it will require similar key cache to the one in
-        // packet processor for vpn packets,
-        // question: can it also be per connection vs global?
-        //
+        self.statistics.increment_mix_packets_received();
 
+        // note: replay detection is performed by the packet processor itself
         let processed_final_hop = match self.packet_processor.process_received(framed_sphinx_packet)
         {
             Err(e) => {
@@ -6,8 +6,15 @@ use crate::commands::validate_bech32_address_or_exit;
 use crate::config::Config;
 use crate::node::client_handling::active_clients::ActiveClientsStore;
 use crate::node::client_handling::websocket;
+use crate::node::client_handling::websocket::tls::load_tls_acceptor;
+use crate::node::http::build_http_api;
+use crate::node::http::stats::StatsState;
 use crate::node::mixnet_handling::receiver::connection_handler::ConnectionHandler;
+use crate::node::node_description::NodeDescription;
+use crate::node::statistics::GatewayStatistics;
+use crate::node::storage::pruning::StaleMessagesPruner;
 use crate::node::storage::Storage;
+use config::NymConfig;
 use crypto::asymmetric::{encryption, identity};
 use log::*;
 use mixnet_client::forwarder::{MixForwardingSender, PacketForwarder};
@@ -23,12 +30,17 @@ use crate::node::client_handling::websocket::connection_handler::coconut::Coconu
 #[cfg(not(feature = "coconut"))]
 use crate::node::client_handling::websocket::connection_handler::eth_events::ERC20Bridge;
 #[cfg(feature = "coconut")]
-use credentials::obtain_aggregate_verification_key;
+use credentials::coconut::utils::DEFAULT_SIGNER_TIMEOUT;
+#[cfg(feature = "coconut")]
+use credentials::{obtain_threshold_verification_key, signing_threshold};
 
 use self::storage::PersistentStorage;
 
 pub(crate) mod client_handling;
+pub(crate) mod http;
 pub(crate) mod mixnet_handling;
+pub(crate) mod node_description;
+pub(crate) mod statistics;
 pub(crate) mod storage;
 
 /// Wire up and create Gateway instance
@@ -40,7 +52,14 @@ pub(crate) async fn create_gateway(config: Config) -> Gateway<PersistentStorage>
 async fn initialise_storage(config: &Config) -> PersistentStorage {
     let path = config.get_persistent_store_path();
     let retrieval_limit = config.get_message_retrieval_limit();
-    match PersistentStorage::init(path, retrieval_limit).await {
+    match PersistentStorage::init(
+        path,
+        retrieval_limit,
+        config.get_max_stored_messages_per_client(),
+        config.get_max_stored_bytes_per_client(),
+    )
+    .await
+    {
         Err(err) => panic!("failed to initialise gateway storage - {}", err),
         Ok(storage) => storage,
     }
@@ -53,6 +72,7 @@ pub(crate) struct Gateway<St: Storage> {
     /// x25519 keypair used for Diffie-Hellman. Currently only used for sphinx key derivation.
     sphinx_keypair: Arc<encryption::KeyPair>,
     storage: St,
+    descriptor: NodeDescription,
 }
 
 impl<St> Gateway<St>
@@ -65,9 +85,10 @@ where
         // let storage = Self::initialise_storage(&config).await;
 
         Gateway {
-            config,
             identity_keypair: Arc::new(Self::load_identity_keys(&pathfinder)),
             sphinx_keypair: Arc::new(Self::load_sphinx_keys(&pathfinder)),
+            descriptor: Self::load_node_description(&config),
+            config,
             storage,
         }
     }
@@ -84,9 +105,14 @@ where
             identity_keypair: Arc::new(identity_keypair),
             sphinx_keypair: Arc::new(sphinx_keypair),
             storage,
+            descriptor: Default::default(),
         }
     }
 
+    fn load_node_description(config: &Config) -> NodeDescription {
+        NodeDescription::load_from_file(config.config_directory()).unwrap_or_default()
+    }
+
     fn load_identity_keys(pathfinder: &GatewayPathfinder) -> identity::KeyPair {
         let identity_keypair: identity::KeyPair =
             pemstore::load_keypair(&pemstore::KeyPairPath::new(
@@ -135,10 +161,17 @@ where
         );
         println!("Version: {}", self.config.get_version());
         println!(
-            "Mix Port: {}, Clients port: {}",
+            "Mix Port: {}, Clients port: {}, Http Port: {}",
             self.config.get_mix_port(),
-            self.config.get_clients_port()
+            self.config.get_clients_port(),
+            self.config.get_http_api_port()
         );
+        if self.config.get_tls_files().is_some() {
+            println!(
+                "Clients wss port: {} (remember to include it in your bond)",
+                self.config.get_clients_wss_port()
+            );
+        }
 
         println!(
             "Data store is at: {:?}",
@@ -150,6 +183,7 @@ where
         &self,
         ack_sender: MixForwardingSender,
         active_clients_store: ActiveClientsStore,
+        statistics: GatewayStatistics,
     ) {
         info!("Starting mix socket listener...");
 
@@ -161,6 +195,7 @@ where
             self.storage.clone(),
             ack_sender,
             active_clients_store,
+            statistics,
         );
 
         let listening_address = SocketAddr::new(
@@ -175,6 +210,7 @@ where
         &self,
         forwarding_channel: MixForwardingSender,
         active_clients_store: ActiveClientsStore,
+        statistics: GatewayStatistics,
         #[cfg(feature = "coconut")] coconut_verifier: Arc<CoconutVerifier>,
         #[cfg(not(feature = "coconut"))] erc20_bridge: ERC20Bridge,
     ) {
@@ -185,10 +221,46 @@ where
             self.config.get_clients_port(),
         );
 
+        #[cfg(not(feature = "coconut"))]
+        let erc20_bridge = Arc::new(erc20_bridge);
+
+        if let Some((certificate_file, private_key_file)) = self.config.get_tls_files() {
+            let tls_acceptor = match load_tls_acceptor(&certificate_file, &private_key_file) {
+                Ok(tls_acceptor) => tls_acceptor,
+                Err(err) => {
+                    error!("Failed to set up TLS for the client listener - {}", err);
+                    process::exit(1);
+                }
+            };
+
+            let tls_listening_address = SocketAddr::new(
+                self.config.get_listening_address(),
+                self.config.get_clients_wss_port(),
+            );
+
+            websocket::Listener::new(
+                tls_listening_address,
+                Arc::clone(&self.identity_keypair),
+                self.config.get_disabled_credentials_mode(),
+                Some(tls_acceptor),
+                #[cfg(feature = "coconut")]
+                Arc::clone(&coconut_verifier),
+                #[cfg(not(feature = "coconut"))]
+                Arc::clone(&erc20_bridge),
+            )
+            .start(
+                forwarding_channel.clone(),
+                self.storage.clone(),
+                active_clients_store.clone(),
+                statistics.clone(),
+            );
+        }
+
         websocket::Listener::new(
             listening_address,
             Arc::clone(&self.identity_keypair),
             self.config.get_disabled_credentials_mode(),
+            None,
             #[cfg(feature = "coconut")]
             coconut_verifier,
             #[cfg(not(feature = "coconut"))]
@@ -198,7 +270,36 @@ where
             forwarding_channel,
             self.storage.clone(),
             active_clients_store,
+            statistics,
+        );
+    }
+
+    fn start_http_api(
+        &self,
+        active_clients_store: ActiveClientsStore,
+        statistics: GatewayStatistics,
+    ) {
+        info!(
+            "Starting HTTP API on http://{}:{}",
+            self.config.get_listening_address(),
+            self.config.get_http_api_port()
         );
+
+        let mut config = rocket::config::Config::release_default();
+
+        // bind to the same address as we are using for the mix and client traffic
+        config.address = self.config.get_listening_address();
+        config.port = self.config.get_http_api_port();
+
+        let descriptor = self.descriptor.clone();
+        let stats_state = StatsState::new(statistics, active_clients_store, self.storage.clone());
+
+        tokio::spawn(async move {
+            build_http_api(descriptor, stats_state)
+                .configure(config)
+                .launch()
+                .await
+        });
     }
 
     fn start_packet_forwarder(&self) -> MixForwardingSender {
@@ -215,6 +316,17 @@ where
         packet_sender
     }
 
+    fn start_stale_messages_pruner(&self) {
+        info!("Starting stale messages pruner...");
+
+        StaleMessagesPruner::new(
+            self.storage.clone(),
+            self.config.get_stored_message_ttl(),
+            self.config.get_message_pruning_interval(),
+        )
+        .start();
+    }
+
     async fn wait_for_interrupt(&self) {
         if let Err(e) = tokio::signal::ctrl_c().await {
             error!(
@@ -281,13 +393,29 @@ where
         }
 
         #[cfg(feature = "coconut")]
-        let validators_verification_key =
-            obtain_aggregate_verification_key(&self.config.get_validator_api_endpoints())
-                .await
-                .expect("failed to contact validators to obtain their verification keys");
+        let validators_verification_key = {
+            let validator_apis = self.config.get_validator_api_endpoints();
+            let verification_key = obtain_threshold_verification_key(
+                &validator_apis,
+                signing_threshold(validator_apis.len()),
+                DEFAULT_SIGNER_TIMEOUT,
+            )
+            .await
+            .expect("failed to contact validators to obtain their verification keys");
+            for failure in verification_key.failures {
+                warn!(
+                    "Failed to obtain the verification key of {} - {}",
+                    failure.url, failure.error
+                );
+            }
+            verification_key.value
+        };
         #[cfg(feature = "coconut")]
-        let coconut_verifier =
-            CoconutVerifier::new(self.all_api_clients(), validators_verification_key);
+        let coconut_verifier = CoconutVerifier::new(
+            self.all_api_clients(),
+            validators_verification_key,
+            self.config.get_share_spent_credentials(),
+        );
 
         #[cfg(not(feature = "coconut"))]
         let erc20_bridge = ERC20Bridge::new(
@@ -296,17 +424,24 @@ where
             self.config._get_cosmos_mnemonic(),
         );
 
+        self.start_stale_messages_pruner();
+
         let mix_forwarding_channel = self.start_packet_forwarder();
 
         let active_clients_store = ActiveClientsStore::new();
+        let statistics = GatewayStatistics::new();
         self.start_mix_socket_listener(
             mix_forwarding_channel.clone(),
             active_clients_store.clone(),
+            statistics.clone(),
         );
 
+        self.start_http_api(active_clients_store.clone(), statistics.clone());
+
         self.start_client_websocket_listener(
             mix_forwarding_channel,
             active_clients_store,
+            statistics,
             #[cfg(feature = "coconut")]
             Arc::new(coconut_verifier),
             #[cfg(not(feature = "coconut"))]
@@ -0,0 +1,102 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Running counters of the traffic handled by the gateway since it has started.
+/// Cloning it is cheap as all clones share the same underlying counters.
+#[derive(Clone, Default)]
+pub(crate) struct GatewayStatistics(Arc<GatewayStatisticsInner>);
+
+#[derive(Default)]
+struct GatewayStatisticsInner {
+    mix_packets_received: AtomicU64,
+    mix_packets_forwarded: AtomicU64,
+    bandwidth_credited: AtomicU64,
+    bandwidth_consumed: AtomicU64,
+}
+
+#[derive(Debug)]
+pub(crate) struct GatewayStatisticsSnapshot {
+    pub(crate) mix_packets_received: u64,
+    pub(crate) mix_packets_forwarded: u64,
+    pub(crate) bandwidth_credited: u64,
+    pub(crate) bandwidth_consumed: u64,
+}
+
+impl GatewayStatistics {
+    pub(crate) fn new() -> Self {
+        Default::default()
+    }
+
+    /// Indicates a sphinx packet has been received from the mix network.
+    pub(crate) fn increment_mix_packets_received(&self) {
+        self.0.mix_packets_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Indicates a packet, either sent by one of the clients or an ack, has been pushed
+    /// into the mix network.
+    pub(crate) fn increment_mix_packets_forwarded(&self) {
+        self.0.mix_packets_forwarded.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Indicates the specified amount of bandwidth has been granted to one of the clients.
+    pub(crate) fn add_bandwidth_credited(&self, amount: i64) {
+        self.0
+            .bandwidth_credited
+            .fetch_add(amount.max(0) as u64, Ordering::Relaxed);
+    }
+
+    /// Indicates the specified amount of bandwidth has been used up by one of the clients.
+    pub(crate) fn add_bandwidth_consumed(&self, amount: i64) {
+        self.0
+            .bandwidth_consumed
+            .fetch_add(amount.max(0) as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn snapshot(&self) -> GatewayStatisticsSnapshot {
+        GatewayStatisticsSnapshot {
+            mix_packets_received: self.0.mix_packets_received.load(Ordering::Relaxed),
+            mix_packets_forwarded: self.0.mix_packets_forwarded.load(Ordering::Relaxed),
+            bandwidth_credited: self.0.bandwidth_credited.load(Ordering::Relaxed),
+            bandwidth_consumed: self.0.bandwidth_consumed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_are_shared_between_clones() {
+        let statistics = GatewayStatistics::new();
+        let cloned = statistics.clone();
+
+        statistics.increment_mix_packets_received();
+        cloned.increment_mix_packets_received();
+        cloned.increment_mix_packets_forwarded();
+        statistics.add_bandwidth_credited(1000);
+        cloned.add_bandwidth_credited(500);
+        statistics.add_bandwidth_consumed(300);
+
+        for snapshot in [statistics.snapshot(), cloned.snapshot()] {
+            assert_eq!(snapshot.mix_packets_received, 2);
+            assert_eq!(snapshot.mix_packets_forwarded, 1);
+            assert_eq!(snapshot.bandwidth_credited, 1500);
+            assert_eq!(snapshot.bandwidth_consumed, 300);
+        }
+    }
+
+    #[test]
+    fn negative_bandwidth_amounts_are_ignored() {
+        let statistics = GatewayStatistics::new();
+        statistics.add_bandwidth_credited(-1000);
+        statistics.add_bandwidth_consumed(-1000);
+
+        let snapshot = statistics.snapshot();
+        assert_eq!(snapshot.bandwidth_credited, 0);
+        assert_eq!(snapshot.bandwidth_consumed, 0);
+    }
+}
@@ -103,4 +103,15 @@ impl BandwidthManager {
         .await?;
         Ok(())
     }
+
+    /// Gets the sum of bandwidth currently available to all clients.
+    pub(crate) async fn get_total_available_bandwidth(&self) -> Result<i64, sqlx::Error> {
+        let total = sqlx::query!(
+            r#"SELECT COALESCE(SUM(available), 0) as "total!: i64" FROM available_bandwidth"#
+        )
+        .fetch_one(&self.connection_pool)
+        .await?
+        .total;
+        Ok(total)
+    }
 }
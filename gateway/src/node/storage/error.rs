@@ -10,4 +10,11 @@ pub(crate) enum StorageError {
 
     #[error("Failed to perform database migration - {0}")]
     MigrationError(#[from] sqlx::migrate::MigrateError),
+
+    #[error("The inbox of the client has reached its storage quota")]
+    InboxQuotaExceeded,
+
+    #[cfg(test)]
+    #[error("The operation is not supported by the in-memory storage")]
+    UnsupportedOperation,
 }
@@ -1,7 +1,7 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::node::storage::models::StoredMessage;
+use crate::node::storage::models::{InboxTotals, StoredMessage};
 
 #[derive(Clone)]
 pub(crate) struct InboxManager {
@@ -10,6 +10,12 @@ pub(crate) struct InboxManager {
     /// It is used to prevent out of memory errors in the case of client receiving a lot of data while
     /// offline and then loading it all at once when he comes back online.
     retrieval_limit: i64,
+
+    /// Maximum number of messages that can be stored for a single client.
+    max_client_messages: i64,
+
+    /// Maximum total size (in bytes) of all messages that can be stored for a single client.
+    max_client_bytes: i64,
 }
 
 impl InboxManager {
@@ -18,32 +24,69 @@ impl InboxManager {
     /// # Arguments
     ///
     /// * `connection_pool`: database connection pool to use.
-    pub(crate) fn new(connection_pool: sqlx::SqlitePool, retrieval_limit: i64) -> Self {
+    /// * `retrieval_limit`: maximum number of messages that can be retrieved at once.
+    /// * `max_client_messages`: maximum number of messages that can be stored for a single client.
+    /// * `max_client_bytes`: maximum total size of messages that can be stored for a single client.
+    pub(crate) fn new(
+        connection_pool: sqlx::SqlitePool,
+        retrieval_limit: i64,
+        max_client_messages: i64,
+        max_client_bytes: i64,
+    ) -> Self {
         InboxManager {
             connection_pool,
             retrieval_limit,
+            max_client_messages,
+            max_client_bytes,
         }
     }
 
-    /// Inserts new message to the storage for an offline client for future retrieval.
+    /// Inserts new message to the storage for an offline client for future retrieval,
+    /// as long as it does not exceed the client's inbox quota.
     ///
     /// # Arguments
     ///
     /// * `client_address_bs58`: base58-encoded address of the client
     /// * `content`: raw content of the message to store.
+    /// * `timestamp`: unix timestamp of when the message was received.
+    ///
+    /// returns whether the message was actually stored.
     pub(crate) async fn insert_message(
         &self,
         client_address_bs58: &str,
         content: Vec<u8>,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "INSERT INTO message_store(client_address_bs58, content) VALUES (?, ?)",
+        timestamp: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let content_length = content.len() as i64;
+
+        // check the quota and insert the message in a single statement so that concurrent
+        // inserts could not overflow it. The usage itself is kept up to date by triggers
+        // on `message_store`, so we don't need to go through the entire inbox here.
+        let affected = sqlx::query!(
+            r#"
+                INSERT INTO message_store(client_address_bs58, content, timestamp)
+                SELECT ?, ?, ?
+                WHERE ? <= ?
+                AND NOT EXISTS (
+                    SELECT 1 FROM inbox_usage
+                    WHERE client_address_bs58 = ? AND (messages >= ? OR bytes + ? > ?)
+                );
+            "#,
             client_address_bs58,
             content,
+            timestamp,
+            content_length,
+            self.max_client_bytes,
+            client_address_bs58,
+            self.max_client_messages,
+            content_length,
+            self.max_client_bytes,
         )
         .execute(&self.connection_pool)
-        .await?;
-        Ok(())
+        .await?
+        .rows_affected();
+
+        Ok(affected > 0)
     }
 
     /// Retrieves messages stored for the particular client specified by the provided address.
@@ -119,4 +162,36 @@ impl InboxManager {
             .await?;
         Ok(())
     }
+
+    /// Removes all messages, for all clients, that were stored before the specified timestamp.
+    ///
+    /// # Arguments
+    ///
+    /// * `cutoff`: unix timestamp before which all messages are removed.
+    ///
+    /// returns the number of removed messages.
+    pub(crate) async fn remove_messages_older_than(&self, cutoff: i64) -> Result<u64, sqlx::Error> {
+        let removed = sqlx::query!("DELETE FROM message_store WHERE timestamp < ?", cutoff)
+            .execute(&self.connection_pool)
+            .await?
+            .rows_affected();
+        Ok(removed)
+    }
+
+    /// Gets the total number and size of messages currently stored, alongside the number of
+    /// distinct clients they belong to.
+    pub(crate) async fn get_totals(&self) -> Result<InboxTotals, sqlx::Error> {
+        sqlx::query_as!(
+            InboxTotals,
+            r#"
+                SELECT
+                    COALESCE(SUM(messages), 0) as "messages!: i64",
+                    COALESCE(SUM(bytes), 0) as "bytes!: i64",
+                    COUNT(*) as "clients!: i64"
+                FROM inbox_usage;
+            "#
+        )
+        .fetch_one(&self.connection_pool)
+        .await
+    }
 }
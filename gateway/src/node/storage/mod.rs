@@ -4,20 +4,33 @@
 use crate::node::storage::bandwidth::BandwidthManager;
 use crate::node::storage::error::StorageError;
 use crate::node::storage::inboxes::InboxManager;
-use crate::node::storage::models::{PersistedSharedKeys, StoredMessage};
+use crate::node::storage::models::{InboxTotals, PersistedSharedKeys, StoredMessage};
 use crate::node::storage::shared_keys::SharedKeysManager;
+#[cfg(feature = "coconut")]
+use crate::node::storage::spent_credentials::SpentCredentialsManager;
 use async_trait::async_trait;
 use gateway_requests::registration::handshake::SharedKeys;
 use log::{debug, error};
 use nymsphinx::DestinationAddressBytes;
 use sqlx::ConnectOptions;
 use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 mod bandwidth;
 pub(crate) mod error;
 mod inboxes;
 mod models;
+pub(crate) mod pruning;
 mod shared_keys;
+#[cfg(feature = "coconut")]
+mod spent_credentials;
+
+fn unix_timestamp(time: SystemTime) -> i64 {
+    // the system clock would have to be set to before 1970 for this to fail
+    time.duration_since(UNIX_EPOCH)
+        .expect("system time is before the unix epoch")
+        .as_secs() as i64
+}
 
 #[async_trait]
 pub(crate) trait Storage: Send + Sync {
@@ -58,6 +71,7 @@ pub(crate) trait Storage: Send + Sync {
     ) -> Result<(), StorageError>;
 
     /// Inserts new message to the storage for an offline client for future retrieval.
+    /// Returns `StorageError::InboxQuotaExceeded` if the client's inbox is already full.
     ///
     /// # Arguments
     ///
@@ -91,6 +105,18 @@ pub(crate) trait Storage: Send + Sync {
     /// * `ids`: ids of the messages to remove
     async fn remove_messages(&self, ids: Vec<i64>) -> Result<(), StorageError>;
 
+    /// Removes all messages, for all clients, that have been stored for longer than the specified duration.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_age`: maximum age of a message that is retained.
+    ///
+    /// returns the number of removed messages.
+    async fn remove_stale_messages(&self, max_age: Duration) -> Result<u64, StorageError>;
+
+    /// Gets the total number and size of all messages currently stored for offline clients.
+    async fn get_inbox_totals(&self) -> Result<InboxTotals, StorageError>;
+
     /// Creates a new bandwidth entry for the particular client.
     ///
     /// # Arguments
@@ -134,6 +160,51 @@ pub(crate) trait Storage: Send + Sync {
         client_address: DestinationAddressBytes,
         amount: i64,
     ) -> Result<(), StorageError>;
+
+    /// Gets the sum of bandwidth currently available to all clients.
+    async fn get_total_available_bandwidth(&self) -> Result<i64, StorageError>;
+
+    /// Atomically marks the credential with the provided blinded serial number as spent.
+    /// Returns `false` if the credential has already been spent before.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_serial_number`: base58-encoded blinded serial number of the credential.
+    /// * `client_address`: address of the client that redeemed the credential.
+    #[cfg(feature = "coconut")]
+    async fn insert_spent_credential(
+        &self,
+        blinded_serial_number: &str,
+        client_address: DestinationAddressBytes,
+    ) -> Result<bool, StorageError>;
+
+    /// Marks the release of funds of the spent credential as pending, i.e. the credential
+    /// can no longer be removed as it has already been shared with the validator APIs,
+    /// but its redeeming client should be able to retry the release.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_serial_number`: base58-encoded blinded serial number of the credential.
+    #[cfg(feature = "coconut")]
+    async fn mark_credential_release_pending(
+        &self,
+        blinded_serial_number: &str,
+    ) -> Result<(), StorageError>;
+
+    /// Atomically claims the pending release of funds of the spent credential.
+    /// Returns `false` if the credential has no pending release or if it was redeemed
+    /// by a different client.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_serial_number`: base58-encoded blinded serial number of the credential.
+    /// * `client_address`: address of the client resuming the redemption.
+    #[cfg(feature = "coconut")]
+    async fn resume_credential_release(
+        &self,
+        blinded_serial_number: &str,
+        client_address: DestinationAddressBytes,
+    ) -> Result<bool, StorageError>;
 }
 
 // note that clone here is fine as upon cloning the same underlying pool will be used
@@ -142,6 +213,8 @@ pub(crate) struct PersistentStorage {
     shared_key_manager: SharedKeysManager,
     inbox_manager: InboxManager,
     bandwidth_manager: BandwidthManager,
+    #[cfg(feature = "coconut")]
+    spent_credentials_manager: SpentCredentialsManager,
 }
 
 impl PersistentStorage {
@@ -151,9 +224,13 @@ impl PersistentStorage {
     ///
     /// * `database_path`: path to the database.
     /// * `message_retrieval_limit`: maximum number of stored client messages that can be retrieved at once.
+    /// * `max_client_messages`: maximum number of messages that can be stored for a single client.
+    /// * `max_client_bytes`: maximum total size of messages that can be stored for a single client.
     pub async fn init<P: AsRef<Path> + Send>(
         database_path: P,
         message_retrieval_limit: i64,
+        max_client_messages: i64,
+        max_client_bytes: i64,
     ) -> Result<Self, StorageError> {
         debug!(
             "Attempting to connect to database {:?}",
@@ -186,7 +263,14 @@ impl PersistentStorage {
         // the cloning here are cheap as connection pool is stored behind an Arc
         Ok(PersistentStorage {
             shared_key_manager: SharedKeysManager::new(connection_pool.clone()),
-            inbox_manager: InboxManager::new(connection_pool.clone(), message_retrieval_limit),
+            inbox_manager: InboxManager::new(
+                connection_pool.clone(),
+                message_retrieval_limit,
+                max_client_messages,
+                max_client_bytes,
+            ),
+            #[cfg(feature = "coconut")]
+            spent_credentials_manager: SpentCredentialsManager::new(connection_pool.clone()),
             bandwidth_manager: BandwidthManager::new(connection_pool),
         })
     }
@@ -236,9 +320,17 @@ impl Storage for PersistentStorage {
         client_address: DestinationAddressBytes,
         message: Vec<u8>,
     ) -> Result<(), StorageError> {
-        self.inbox_manager
-            .insert_message(&client_address.as_base58_string(), message)
+        let stored = self
+            .inbox_manager
+            .insert_message(
+                &client_address.as_base58_string(),
+                message,
+                unix_timestamp(SystemTime::now()),
+            )
             .await?;
+        if !stored {
+            return Err(StorageError::InboxQuotaExceeded);
+        }
         Ok(())
     }
 
@@ -261,6 +353,23 @@ impl Storage for PersistentStorage {
         Ok(())
     }
 
+    async fn remove_stale_messages(&self, max_age: Duration) -> Result<u64, StorageError> {
+        // if we can't represent the cutoff, nothing could possibly be that old
+        let cutoff = match SystemTime::now().checked_sub(max_age) {
+            Some(cutoff) if cutoff > UNIX_EPOCH => unix_timestamp(cutoff),
+            _ => return Ok(0),
+        };
+        let removed = self
+            .inbox_manager
+            .remove_messages_older_than(cutoff)
+            .await?;
+        Ok(removed)
+    }
+
+    async fn get_inbox_totals(&self) -> Result<InboxTotals, StorageError> {
+        Ok(self.inbox_manager.get_totals().await?)
+    }
+
     async fn create_bandwidth_entry(
         &self,
         client_address: DestinationAddressBytes,
@@ -304,6 +413,54 @@ impl Storage for PersistentStorage {
             .await?;
         Ok(())
     }
+
+    async fn get_total_available_bandwidth(&self) -> Result<i64, StorageError> {
+        Ok(self
+            .bandwidth_manager
+            .get_total_available_bandwidth()
+            .await?)
+    }
+
+    #[cfg(feature = "coconut")]
+    async fn insert_spent_credential(
+        &self,
+        blinded_serial_number: &str,
+        client_address: DestinationAddressBytes,
+    ) -> Result<bool, StorageError> {
+        let inserted = self
+            .spent_credentials_manager
+            .insert_spent_credential(
+                blinded_serial_number,
+                &client_address.as_base58_string(),
+                unix_timestamp(SystemTime::now()),
+            )
+            .await?;
+        Ok(inserted)
+    }
+
+    #[cfg(feature = "coconut")]
+    async fn mark_credential_release_pending(
+        &self,
+        blinded_serial_number: &str,
+    ) -> Result<(), StorageError> {
+        self.spent_credentials_manager
+            .mark_release_pending(blinded_serial_number)
+            .await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "coconut")]
+    async fn resume_credential_release(
+        &self,
+        blinded_serial_number: &str,
+        client_address: DestinationAddressBytes,
+    ) -> Result<bool, StorageError> {
+        let resumed = self
+            .spent_credentials_manager
+            .resume_pending_release(blinded_serial_number, &client_address.as_base58_string())
+            .await?;
+        Ok(resumed)
+    }
 }
 
 /// In-memory implementation of `Storage`. The intention is primarily in testing environments.
@@ -364,6 +521,14 @@ impl Storage for InMemStorage {
         todo!()
     }
 
+    async fn remove_stale_messages(&self, _max_age: Duration) -> Result<u64, StorageError> {
+        Err(StorageError::UnsupportedOperation)
+    }
+
+    async fn get_inbox_totals(&self) -> Result<InboxTotals, StorageError> {
+        Err(StorageError::UnsupportedOperation)
+    }
+
     async fn create_bandwidth_entry(
         &self,
         _client_address: DestinationAddressBytes,
@@ -393,4 +558,294 @@ impl Storage for InMemStorage {
     ) -> Result<(), StorageError> {
         todo!()
     }
+
+    async fn get_total_available_bandwidth(&self) -> Result<i64, StorageError> {
+        Err(StorageError::UnsupportedOperation)
+    }
+
+    #[cfg(feature = "coconut")]
+    async fn insert_spent_credential(
+        &self,
+        _blinded_serial_number: &str,
+        _client_address: DestinationAddressBytes,
+    ) -> Result<bool, StorageError> {
+        Err(StorageError::UnsupportedOperation)
+    }
+
+    #[cfg(feature = "coconut")]
+    async fn mark_credential_release_pending(
+        &self,
+        _blinded_serial_number: &str,
+    ) -> Result<(), StorageError> {
+        Err(StorageError::UnsupportedOperation)
+    }
+
+    #[cfg(feature = "coconut")]
+    async fn resume_credential_release(
+        &self,
+        _blinded_serial_number: &str,
+        _client_address: DestinationAddressBytes,
+    ) -> Result<bool, StorageError> {
+        Err(StorageError::UnsupportedOperation)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_CLIENT_MESSAGES: i64 = 3;
+    const MAX_CLIENT_BYTES: i64 = 100;
+
+    async fn test_storage(dir: &tempfile::TempDir) -> PersistentStorage {
+        PersistentStorage::init(
+            dir.path().join("gateway.sqlite"),
+            100,
+            MAX_CLIENT_MESSAGES,
+            MAX_CLIENT_BYTES,
+        )
+        .await
+        .unwrap()
+    }
+
+    fn client(byte: u8) -> DestinationAddressBytes {
+        DestinationAddressBytes::from_bytes([byte; 32])
+    }
+
+    #[tokio::test]
+    async fn messages_over_the_count_quota_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(&dir).await;
+
+        for _ in 0..MAX_CLIENT_MESSAGES {
+            storage
+                .store_message(client(1), vec![42; 10])
+                .await
+                .unwrap();
+        }
+        assert!(matches!(
+            storage.store_message(client(1), vec![42; 10]).await,
+            Err(StorageError::InboxQuotaExceeded)
+        ));
+
+        // quota is per client
+        storage
+            .store_message(client(2), vec![42; 10])
+            .await
+            .unwrap();
+
+        let totals = storage.get_inbox_totals().await.unwrap();
+        assert_eq!(totals.messages, MAX_CLIENT_MESSAGES + 1);
+        assert_eq!(totals.bytes, (MAX_CLIENT_MESSAGES + 1) * 10);
+        assert_eq!(totals.clients, 2);
+    }
+
+    #[tokio::test]
+    async fn messages_over_the_size_quota_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(&dir).await;
+
+        assert!(matches!(
+            storage
+                .store_message(client(1), vec![42; MAX_CLIENT_BYTES as usize + 1])
+                .await,
+            Err(StorageError::InboxQuotaExceeded)
+        ));
+
+        storage
+            .store_message(client(1), vec![42; 60])
+            .await
+            .unwrap();
+        assert!(matches!(
+            storage.store_message(client(1), vec![42; 41]).await,
+            Err(StorageError::InboxQuotaExceeded)
+        ));
+        storage
+            .store_message(client(1), vec![42; 40])
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn removing_messages_frees_up_the_quota() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(&dir).await;
+
+        for _ in 0..MAX_CLIENT_MESSAGES {
+            storage
+                .store_message(client(1), vec![42; 10])
+                .await
+                .unwrap();
+        }
+        let (messages, _) = storage.retrieve_messages(client(1), None).await.unwrap();
+        storage
+            .remove_messages(messages.into_iter().map(|message| message.id).collect())
+            .await
+            .unwrap();
+
+        let totals = storage.get_inbox_totals().await.unwrap();
+        assert_eq!(totals.messages, 0);
+        assert_eq!(totals.clients, 0);
+
+        for _ in 0..MAX_CLIENT_MESSAGES {
+            storage
+                .store_message(client(1), vec![42; 10])
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn only_stale_messages_are_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(&dir).await;
+
+        let now = unix_timestamp(SystemTime::now());
+        let address = client(1).as_base58_string();
+        assert!(storage
+            .inbox_manager
+            .insert_message(&address, vec![1], now - 3600)
+            .await
+            .unwrap());
+        assert!(storage
+            .inbox_manager
+            .insert_message(&address, vec![2], now)
+            .await
+            .unwrap());
+
+        let removed = storage
+            .remove_stale_messages(Duration::from_secs(60))
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+
+        let (messages, _) = storage.retrieve_messages(client(1), None).await.unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].content, vec![2]);
+
+        // the removed message no longer counts towards the quota
+        let totals = storage.get_inbox_totals().await.unwrap();
+        assert_eq!(totals.messages, 1);
+        assert_eq!(totals.bytes, 1);
+    }
+
+    #[tokio::test]
+    async fn inbox_totals_include_all_clients() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(&dir).await;
+
+        let totals = storage.get_inbox_totals().await.unwrap();
+        assert_eq!(totals.messages, 0);
+        assert_eq!(totals.bytes, 0);
+        assert_eq!(totals.clients, 0);
+
+        storage
+            .store_message(client(1), vec![42; 10])
+            .await
+            .unwrap();
+        storage
+            .store_message(client(1), vec![42; 20])
+            .await
+            .unwrap();
+        storage.store_message(client(2), vec![42; 5]).await.unwrap();
+
+        let totals = storage.get_inbox_totals().await.unwrap();
+        assert_eq!(totals.messages, 3);
+        assert_eq!(totals.bytes, 35);
+        assert_eq!(totals.clients, 2);
+    }
+
+    #[tokio::test]
+    async fn total_available_bandwidth_includes_all_clients() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(&dir).await;
+
+        assert_eq!(storage.get_total_available_bandwidth().await.unwrap(), 0);
+
+        storage.create_bandwidth_entry(client(1)).await.unwrap();
+        storage.create_bandwidth_entry(client(2)).await.unwrap();
+        assert_eq!(storage.get_total_available_bandwidth().await.unwrap(), 0);
+
+        storage.increase_bandwidth(client(1), 1000).await.unwrap();
+        storage.increase_bandwidth(client(2), 500).await.unwrap();
+        storage.consume_bandwidth(client(1), 300).await.unwrap();
+        assert_eq!(storage.get_total_available_bandwidth().await.unwrap(), 1200);
+    }
+
+    #[cfg(feature = "coconut")]
+    #[tokio::test]
+    async fn spent_credentials_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(&dir).await;
+
+        assert!(storage
+            .insert_spent_credential("serial1", client(1))
+            .await
+            .unwrap());
+        assert!(!storage
+            .insert_spent_credential("serial1", client(1))
+            .await
+            .unwrap());
+        assert!(!storage
+            .insert_spent_credential("serial1", client(2))
+            .await
+            .unwrap());
+
+        // a credential whose release hasn't failed has nothing to resume
+        assert!(!storage
+            .resume_credential_release("serial1", client(1))
+            .await
+            .unwrap());
+
+        assert!(storage
+            .insert_spent_credential("serial2", client(1))
+            .await
+            .unwrap());
+    }
+
+    #[cfg(feature = "coconut")]
+    #[tokio::test]
+    async fn pending_release_can_only_be_resumed_once_by_the_same_client() {
+        let dir = tempfile::tempdir().unwrap();
+        let storage = test_storage(&dir).await;
+
+        assert!(storage
+            .insert_spent_credential("serial1", client(1))
+            .await
+            .unwrap());
+        storage
+            .mark_credential_release_pending("serial1")
+            .await
+            .unwrap();
+
+        // the credential is still considered spent
+        assert!(!storage
+            .insert_spent_credential("serial1", client(1))
+            .await
+            .unwrap());
+
+        assert!(!storage
+            .resume_credential_release("serial1", client(2))
+            .await
+            .unwrap());
+        assert!(storage
+            .resume_credential_release("serial1", client(1))
+            .await
+            .unwrap());
+        // a concurrent (or later) attempt can't claim the same release again
+        assert!(!storage
+            .resume_credential_release("serial1", client(1))
+            .await
+            .unwrap());
+
+        // but it can be resumed again if the release has failed once more
+        storage
+            .mark_credential_release_pending("serial1")
+            .await
+            .unwrap();
+        assert!(storage
+            .resume_credential_release("serial1", client(1))
+            .await
+            .unwrap());
+    }
 }
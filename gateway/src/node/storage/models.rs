@@ -11,6 +11,14 @@ pub(crate) struct StoredMessage {
     #[allow(dead_code)]
     pub(crate) client_address_bs58: String,
     pub(crate) content: Vec<u8>,
+    #[allow(dead_code)]
+    pub(crate) timestamp: i64,
+}
+
+pub(crate) struct InboxTotals {
+    pub(crate) messages: i64,
+    pub(crate) bytes: i64,
+    pub(crate) clients: i64,
 }
 
 pub(crate) struct PersistedBandwidth {
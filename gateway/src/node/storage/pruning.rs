@@ -0,0 +1,58 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::storage::Storage;
+use log::*;
+use std::time::Duration;
+
+/// Background task periodically removing client messages that have been stored for longer than
+/// the configured retention period, so that abandoned inboxes would not grow forever.
+pub(crate) struct StaleMessagesPruner<St: Storage> {
+    storage: St,
+    message_retention: Duration,
+    pruning_interval: Duration,
+}
+
+impl<St> StaleMessagesPruner<St>
+where
+    St: Storage + 'static,
+{
+    pub(crate) fn new(
+        storage: St,
+        message_retention: Duration,
+        pruning_interval: Duration,
+    ) -> Self {
+        StaleMessagesPruner {
+            storage,
+            message_retention,
+            pruning_interval,
+        }
+    }
+
+    async fn prune(&self) {
+        match self
+            .storage
+            .remove_stale_messages(self.message_retention)
+            .await
+        {
+            Ok(0) => trace!("There were no stale messages to remove"),
+            Ok(removed) => info!(
+                "Removed {} messages stored for longer than {:?}",
+                removed, self.message_retention
+            ),
+            Err(err) => error!("Failed to remove stale messages - {}", err),
+        }
+    }
+
+    async fn run(self) {
+        let mut interval = tokio::time::interval(self.pruning_interval);
+        loop {
+            interval.tick().await;
+            self.prune().await;
+        }
+    }
+
+    pub(crate) fn start(self) {
+        tokio::spawn(self.run());
+    }
+}
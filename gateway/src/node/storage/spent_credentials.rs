@@ -0,0 +1,93 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+#[derive(Clone)]
+pub(crate) struct SpentCredentialsManager {
+    connection_pool: sqlx::SqlitePool,
+}
+
+impl SpentCredentialsManager {
+    /// Creates new instance of the `SpentCredentialsManager` with the provided sqlite connection pool.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection_pool`: database connection pool to use.
+    pub(crate) fn new(connection_pool: sqlx::SqlitePool) -> Self {
+        SpentCredentialsManager { connection_pool }
+    }
+
+    /// Atomically marks the credential with the provided blinded serial number as spent.
+    /// Returns `false` if the credential has already been spent before.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_serial_number_bs58`: base58-encoded blinded serial number of the credential.
+    /// * `client_address_bs58`: base58-encoded address of the client that redeemed the credential.
+    /// * `timestamp`: unix timestamp of when the credential got spent.
+    pub(crate) async fn insert_spent_credential(
+        &self,
+        blinded_serial_number_bs58: &str,
+        client_address_bs58: &str,
+        timestamp: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(
+            r#"
+                INSERT OR IGNORE INTO spent_credentials(blinded_serial_number_bs58, client_address_bs58, timestamp)
+                VALUES (?, ?, ?)
+            "#,
+            blinded_serial_number_bs58,
+            client_address_bs58,
+            timestamp
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+
+    /// Marks the release of funds of the credential with the provided blinded serial number
+    /// as pending, so that its redemption could be resumed later on.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_serial_number_bs58`: base58-encoded blinded serial number of the credential.
+    pub(crate) async fn mark_release_pending(
+        &self,
+        blinded_serial_number_bs58: &str,
+    ) -> Result<(), sqlx::Error> {
+        sqlx::query!(
+            "UPDATE spent_credentials SET pending_release = true WHERE blinded_serial_number_bs58 = ?",
+            blinded_serial_number_bs58
+        )
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Atomically claims the pending release of funds of the credential with the provided
+    /// blinded serial number, if it was originally redeemed by the same client.
+    /// Returns `false` if there was no such pending release.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_serial_number_bs58`: base58-encoded blinded serial number of the credential.
+    /// * `client_address_bs58`: base58-encoded address of the client resuming the redemption.
+    pub(crate) async fn resume_pending_release(
+        &self,
+        blinded_serial_number_bs58: &str,
+        client_address_bs58: &str,
+    ) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(
+            r#"
+                UPDATE spent_credentials SET pending_release = false
+                WHERE blinded_serial_number_bs58 = ? AND client_address_bs58 = ? AND pending_release = true
+            "#,
+            blinded_serial_number_bs58,
+            client_address_bs58
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
+}
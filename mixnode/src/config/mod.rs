@@ -32,6 +32,8 @@ const DEFAULT_PACKET_FORWARDING_INITIAL_BACKOFF: Duration = Duration::from_milli
 const DEFAULT_PACKET_FORWARDING_MAXIMUM_BACKOFF: Duration = Duration::from_millis(300_000);
 const DEFAULT_INITIAL_CONNECTION_TIMEOUT: Duration = Duration::from_millis(1_500);
 const DEFAULT_MAXIMUM_CONNECTION_BUFFER_SIZE: usize = 128;
+const DEFAULT_SPHINX_KEY_ROTATION_INTERVAL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+const DEFAULT_SPHINX_KEY_ROTATION_OVERLAP: Duration = Duration::from_secs(2 * 60 * 60);
 
 pub fn missing_string_value<T: From<String>>() -> T {
     MISSING_VALUE.to_string().into()
@@ -196,6 +198,16 @@ impl Config {
         self
     }
 
+    pub fn with_custom_validator_nymd(mut self, validator_nymd_urls: Vec<Url>) -> Self {
+        self.mixnode.validator_nymd_urls = validator_nymd_urls;
+        self
+    }
+
+    pub fn with_cosmos_mnemonic_file<P: Into<PathBuf>>(mut self, cosmos_mnemonic_file: P) -> Self {
+        self.mixnode.cosmos_mnemonic_file = cosmos_mnemonic_file.into();
+        self
+    }
+
     pub fn with_wallet_address(mut self, wallet_address: &str) -> Self {
         self.mixnode.wallet_address = wallet_address.to_string();
         self
@@ -230,6 +242,18 @@ impl Config {
         self.mixnode.validator_api_urls.clone()
     }
 
+    pub fn get_validator_nymd_endpoints(&self) -> Vec<Url> {
+        self.mixnode.validator_nymd_urls.clone()
+    }
+
+    pub fn get_cosmos_mnemonic_file(&self) -> Option<PathBuf> {
+        if self.mixnode.cosmos_mnemonic_file.as_os_str().is_empty() {
+            None
+        } else {
+            Some(self.mixnode.cosmos_mnemonic_file.clone())
+        }
+    }
+
     pub fn get_node_stats_logging_delay(&self) -> Duration {
         self.debug.node_stats_logging_delay
     }
@@ -274,6 +298,14 @@ impl Config {
         self.debug.maximum_connection_buffer_size
     }
 
+    pub fn get_sphinx_key_rotation_interval(&self) -> Duration {
+        self.debug.sphinx_key_rotation_interval
+    }
+
+    pub fn get_sphinx_key_rotation_overlap(&self) -> Duration {
+        self.debug.sphinx_key_rotation_overlap
+    }
+
     pub fn get_version(&self) -> &str {
         &self.mixnode.version
     }
@@ -361,6 +393,17 @@ struct MixNode {
     /// Addresses to APIs running on validator from which the node gets the view of the network.
     validator_api_urls: Vec<Url>,
 
+    /// Addresses to validators to which the node submits transactions announcing its rotated sphinx keys.
+    #[serde(default = "default_nymd_endpoints")]
+    validator_nymd_urls: Vec<Url>,
+
+    /// Path to file containing the mnemonic of a cosmos wallet used for submitting the sphinx key
+    /// rotation transactions. The file must only be accessible by its owner.
+    /// It can be overridden with the `NYM_MIXNODE_COSMOS_MNEMONIC` environment variable.
+    /// If neither is set, the sphinx key is never rotated.
+    #[serde(default)]
+    cosmos_mnemonic_file: PathBuf,
+
     /// nym_home_directory specifies absolute path to the home nym MixNodes directory.
     /// It is expected to use default value and hence .toml file should not redefine this field.
     nym_root_directory: PathBuf,
@@ -402,6 +445,8 @@ impl Default for MixNode {
             private_sphinx_key_file: Default::default(),
             public_sphinx_key_file: Default::default(),
             validator_api_urls: default_api_endpoints(),
+            validator_nymd_urls: default_nymd_endpoints(),
+            cosmos_mnemonic_file: Default::default(),
             nym_root_directory: Config::default_root_directory(),
             wallet_address: "nymXXXXXXXX".to_string(),
         }
@@ -479,6 +524,14 @@ struct Debug {
 
     /// Maximum number of packets that can be stored waiting to get sent to a particular connection.
     maximum_connection_buffer_size: usize,
+
+    /// Delay between subsequent rotations of the sphinx key.
+    #[serde(with = "humantime_serde")]
+    sphinx_key_rotation_interval: Duration,
+
+    /// Duration for which the previous sphinx key is still accepted after being rotated.
+    #[serde(with = "humantime_serde")]
+    sphinx_key_rotation_overlap: Duration,
 }
 
 impl Default for Debug {
@@ -490,6 +543,8 @@ impl Default for Debug {
             packet_forwarding_maximum_backoff: DEFAULT_PACKET_FORWARDING_MAXIMUM_BACKOFF,
             initial_connection_timeout: DEFAULT_INITIAL_CONNECTION_TIMEOUT,
             maximum_connection_buffer_size: DEFAULT_MAXIMUM_CONNECTION_BUFFER_SIZE,
+            sphinx_key_rotation_interval: DEFAULT_SPHINX_KEY_ROTATION_INTERVAL,
+            sphinx_key_rotation_overlap: DEFAULT_SPHINX_KEY_ROTATION_OVERLAP,
         }
     }
 }
@@ -10,15 +10,31 @@ pub struct MixNodePathfinder {
     identity_public_key: PathBuf,
     private_sphinx_key: PathBuf,
     public_sphinx_key: PathBuf,
+    staged_private_sphinx_key: PathBuf,
+    staged_public_sphinx_key: PathBuf,
+    sphinx_key_rotation_timestamp: PathBuf,
+}
+
+fn staged(path: &Path) -> PathBuf {
+    let mut staged = path.as_os_str().to_owned();
+    staged.push(".staged");
+    staged.into()
 }
 
 impl MixNodePathfinder {
     pub fn new_from_config(config: &Config) -> Self {
+        let private_sphinx_key = config.get_private_sphinx_key_file();
+        let public_sphinx_key = config.get_public_sphinx_key_file();
+
         MixNodePathfinder {
             identity_private_key: config.get_private_identity_key_file(),
             identity_public_key: config.get_public_identity_key_file(),
-            private_sphinx_key: config.get_private_sphinx_key_file(),
-            public_sphinx_key: config.get_public_sphinx_key_file(),
+            staged_private_sphinx_key: staged(&private_sphinx_key),
+            staged_public_sphinx_key: staged(&public_sphinx_key),
+            sphinx_key_rotation_timestamp: private_sphinx_key
+                .with_file_name("last_sphinx_key_rotation"),
+            private_sphinx_key,
+            public_sphinx_key,
         }
     }
 
@@ -37,4 +53,19 @@ impl MixNodePathfinder {
     pub fn public_encryption_key(&self) -> &Path {
         &self.public_sphinx_key
     }
+
+    /// Location of the private sphinx key that has been generated, but not yet put in use.
+    pub fn staged_private_encryption_key(&self) -> &Path {
+        &self.staged_private_sphinx_key
+    }
+
+    /// Location of the public sphinx key that has been generated, but not yet put in use.
+    pub fn staged_public_encryption_key(&self) -> &Path {
+        &self.staged_public_sphinx_key
+    }
+
+    /// Location of the file holding the unix timestamp of the last sphinx key rotation.
+    pub fn sphinx_key_rotation_timestamp(&self) -> &Path {
+        &self.sphinx_key_rotation_timestamp
+    }
 }
@@ -60,6 +60,19 @@ validator_api_urls = [
     {{/each}}
 ]
 
+# Addresses to validators to which the node submits transactions announcing its rotated sphinx keys.
+validator_nymd_urls = [
+    {{#each mixnode.validator_nymd_urls }}
+        '{{this}}',
+    {{/each}}
+]
+
+# Path to file containing the mnemonic of a cosmos wallet used for submitting the sphinx key
+# rotation transactions. The file must only be accessible by its owner (e.g. mode 600).
+# It can be overridden with the `NYM_MIXNODE_COSMOS_MNEMONIC` environment variable.
+# If neither is set, the sphinx key is never rotated.
+cosmos_mnemonic_file = '{{ mixnode.cosmos_mnemonic_file }}'
+
 # Nym wallet address on the blockchain that should control this mixnode
 wallet_address = '{{ mixnode.wallet_address }}'
 
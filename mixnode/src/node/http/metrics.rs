@@ -0,0 +1,220 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::node::http::verloc::VerlocState;
+use crate::node::node_statistics::{NodeStats, SharedNodeStats};
+use mixnode_common::verloc::VerlocResult;
+use rocket::http::ContentType;
+use rocket::State;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+const METRICS_PREFIX: &str = "nym_mixnode";
+
+pub(crate) struct MetricsState {
+    start_time: Instant,
+    forwarder_queue_length: Arc<AtomicUsize>,
+}
+
+impl MetricsState {
+    pub(crate) fn new(start_time: Instant, forwarder_queue_length: Arc<AtomicUsize>) -> Self {
+        MetricsState {
+            start_time,
+            forwarder_queue_length,
+        }
+    }
+}
+
+/// Helper for writing metrics in the Prometheus text exposition format.
+#[derive(Default)]
+struct MetricsEncoder {
+    output: String,
+}
+
+impl MetricsEncoder {
+    fn describe(&mut self, name: &str, metric_type: &str, help: &str) {
+        // writing to a String can't fail
+        let _ = writeln!(self.output, "# HELP {}_{} {}", METRICS_PREFIX, name, help);
+        let _ = writeln!(
+            self.output,
+            "# TYPE {}_{} {}",
+            METRICS_PREFIX, name, metric_type
+        );
+    }
+
+    fn sample<V: std::fmt::Display>(&mut self, name: &str, labels: &[(&str, &str)], value: V) {
+        let _ = write!(self.output, "{}_{}", METRICS_PREFIX, name);
+        if !labels.is_empty() {
+            let labels = labels
+                .iter()
+                .map(|(label, value)| format!("{}=\"{}\"", label, escape_label_value(value)))
+                .collect::<Vec<_>>()
+                .join(",");
+            let _ = write!(self.output, "{{{}}}", labels);
+        }
+        let _ = writeln!(self.output, " {}", value);
+    }
+
+    fn single<V: std::fmt::Display>(
+        &mut self,
+        name: &str,
+        metric_type: &str,
+        help: &str,
+        value: V,
+    ) {
+        self.describe(name, metric_type, help);
+        self.sample(name, &[], value);
+    }
+
+    fn encode_packet_stats(&mut self, stats: &NodeStats) {
+        self.single(
+            "packets_received_total",
+            "counter",
+            "Number of sphinx packets received since startup.",
+            stats.packets_received_since_startup(),
+        );
+        self.single(
+            "packets_replayed_total",
+            "counter",
+            "Number of received sphinx packets rejected as replays since startup.",
+            stats.packets_replayed_since_startup(),
+        );
+
+        self.describe(
+            "packets_sent_total",
+            "counter",
+            "Number of sphinx packets sent to each destination since startup.",
+        );
+        for (destination, count) in stats.packets_sent_since_startup() {
+            self.sample(
+                "packets_sent_total",
+                &[("destination", destination.as_str())],
+                count,
+            );
+        }
+
+        self.describe(
+            "packets_dropped_total",
+            "counter",
+            "Number of sphinx packets explicitly dropped for each destination since startup.",
+        );
+        for (destination, count) in stats.packets_explicitly_dropped_since_startup() {
+            self.sample(
+                "packets_dropped_total",
+                &[("destination", destination.as_str())],
+                count,
+            );
+        }
+    }
+
+    fn encode_verloc(&mut self, verloc: &VerlocResult) {
+        self.single(
+            "verloc_tested_nodes",
+            "gauge",
+            "Number of nodes tested during the latest verloc measurement run.",
+            verloc.total_tested(),
+        );
+
+        if let Some(run_finished) = verloc.run_finished() {
+            if let Ok(timestamp) = run_finished.duration_since(UNIX_EPOCH) {
+                self.single(
+                    "verloc_last_run_finished_timestamp_seconds",
+                    "gauge",
+                    "Unix timestamp of the end of the latest completed verloc measurement run.",
+                    timestamp.as_secs(),
+                );
+            }
+        }
+
+        self.describe(
+            "verloc_rtt_seconds",
+            "gauge",
+            "Round-trip time statistics of the latest verloc measurement for each node.",
+        );
+        for result in verloc.results() {
+            if let Some(measurement) = result.latest_measurement {
+                let node = result.identity.to_base58_string();
+                for (statistic, value) in [
+                    ("minimum", measurement.minimum),
+                    ("mean", measurement.mean),
+                    ("maximum", measurement.maximum),
+                    ("standard_deviation", measurement.standard_deviation),
+                ] {
+                    self.sample(
+                        "verloc_rtt_seconds",
+                        &[("node", node.as_str()), ("statistic", statistic)],
+                        value.as_secs_f64(),
+                    );
+                }
+            }
+        }
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Exposes the node metrics in the Prometheus text exposition format.
+#[get("/metrics")]
+pub(crate) async fn metrics(
+    stats: &State<SharedNodeStats>,
+    verloc: &State<VerlocState>,
+    state: &State<MetricsState>,
+) -> (ContentType, String) {
+    let mut encoder = MetricsEncoder::default();
+
+    encoder.single(
+        "uptime_seconds",
+        "gauge",
+        "Number of seconds since the node has started.",
+        state.start_time.elapsed().as_secs(),
+    );
+    if let Ok(now) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        encoder.single(
+            "start_time_seconds",
+            "gauge",
+            "Unix timestamp of the node startup.",
+            now.saturating_sub(state.start_time.elapsed()).as_secs(),
+        );
+    }
+
+    encoder.encode_packet_stats(&stats.clone_data().await);
+    encoder.single(
+        "delay_forwarder_queue_length",
+        "gauge",
+        "Number of packets currently being delayed before getting forwarded.",
+        state.forwarder_queue_length.load(Ordering::Relaxed),
+    );
+    encoder.encode_verloc(&verloc.current_results().await);
+
+    (ContentType::Plain, encoder.output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_are_encoded_with_escaped_labels() {
+        let mut encoder = MetricsEncoder::default();
+        encoder.describe("packets_sent_total", "counter", "Sent packets.");
+        encoder.sample("packets_sent_total", &[("destination", "1.2.3.4:1789")], 42);
+        encoder.sample("packets_sent_total", &[("destination", "a\"b\\c")], 1);
+        encoder.single("uptime_seconds", "gauge", "Uptime.", 10);
+
+        let expected = "# HELP nym_mixnode_packets_sent_total Sent packets.\n\
+            # TYPE nym_mixnode_packets_sent_total counter\n\
+            nym_mixnode_packets_sent_total{destination=\"1.2.3.4:1789\"} 42\n\
+            nym_mixnode_packets_sent_total{destination=\"a\\\"b\\\\c\"} 1\n\
+            # HELP nym_mixnode_uptime_seconds Uptime.\n\
+            # TYPE nym_mixnode_uptime_seconds gauge\n\
+            nym_mixnode_uptime_seconds 10\n";
+        assert_eq!(expected, encoder.output);
+    }
+}
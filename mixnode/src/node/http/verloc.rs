@@ -12,6 +12,10 @@ impl VerlocState {
             shared: atomic_verloc_result,
         }
     }
+
+    pub(crate) async fn current_results(&self) -> VerlocResult {
+        self.shared.clone_data().await
+    }
 }
 
 /// Provides verifiable location (verloc) measurements for this mixnode - a list of the
@@ -19,5 +23,5 @@ impl VerlocState {
 #[get("/verloc")]
 pub(crate) async fn verloc(state: &State<VerlocState>) -> Json<VerlocResult> {
     // since it's impossible to get a mutable reference to the state, we can't cache any results outside the lock : (
-    Json(state.shared.clone_data().await)
+    Json(state.current_results().await)
 }
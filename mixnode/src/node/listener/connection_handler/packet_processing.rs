@@ -2,11 +2,13 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::node::node_statistics;
-use crypto::asymmetric::encryption;
 use mixnode_common::packet_processor::error::MixProcessingError;
 pub use mixnode_common::packet_processor::processor::MixProcessingResult;
 use mixnode_common::packet_processor::processor::SphinxPacketProcessor;
+use mixnode_common::packet_processor::replay::ReplayDetector;
+use mixnode_common::packet_processor::sphinx_keys::SphinxKeys;
 use nymsphinx::framing::packet::FramedSphinxPacket;
+use std::sync::Arc;
 
 // PacketProcessor contains all data required to correctly unwrap and forward sphinx packets
 #[derive(Clone)]
@@ -20,11 +22,14 @@ pub struct PacketProcessor {
 
 impl PacketProcessor {
     pub(crate) fn new(
-        encryption_key: &encryption::PrivateKey,
+        sphinx_keys: Arc<SphinxKeys>,
         node_stats_update_sender: node_statistics::UpdateSender,
     ) -> Self {
         PacketProcessor {
-            inner_processor: SphinxPacketProcessor::new(encryption_key.into()),
+            inner_processor: SphinxPacketProcessor::new_with_rotating_keys(
+                sphinx_keys,
+                ReplayDetector::default(),
+            ),
             node_stats_update_sender,
         }
     }
@@ -34,6 +39,10 @@ impl PacketProcessor {
         received: FramedSphinxPacket,
     ) -> Result<MixProcessingResult, MixProcessingError> {
         self.node_stats_update_sender.report_received();
-        self.inner_processor.process_received(received)
+        let res = self.inner_processor.process_received(received);
+        if let Err(MixProcessingError::ReplayedPacket) = res {
+            self.node_stats_update_sender.report_replayed();
+        }
+        res
     }
 }
@@ -6,6 +6,7 @@ use crate::config::persistence::pathfinder::MixNodePathfinder;
 use crate::config::Config;
 use crate::node::http::{
     description::description,
+    metrics::{metrics, MetricsState},
     not_found,
     stats::stats,
     verloc::{verloc as verlocRoute, VerlocState},
@@ -16,15 +17,19 @@ use crate::node::listener::Listener;
 use crate::node::node_description::NodeDescription;
 use crate::node::node_statistics::SharedNodeStats;
 use crate::node::packet_delayforwarder::{DelayForwarder, PacketDelayForwardSender};
+use crate::node::sphinx_key_rotation::{load_cosmos_mnemonic, SphinxKeyRotator};
 use ::crypto::asymmetric::{encryption, identity};
 use config::NymConfig;
 use log::{error, info, warn};
+use mixnode_common::packet_processor::sphinx_keys::SphinxKeys;
 use mixnode_common::verloc::{self, AtomicVerlocResult, VerlocMeasurer};
 use rand::seq::SliceRandom;
 use rand::thread_rng;
 use std::net::SocketAddr;
 use std::process;
+use std::sync::atomic::AtomicUsize;
 use std::sync::Arc;
+use std::time::Instant;
 use task::{ShutdownListener, ShutdownNotifier};
 use version_checker::parse_version;
 
@@ -33,6 +38,7 @@ mod listener;
 pub(crate) mod node_description;
 mod node_statistics;
 mod packet_delayforwarder;
+mod sphinx_key_rotation;
 
 // the MixNode will live for whole duration of this program
 pub struct MixNode {
@@ -40,16 +46,22 @@ pub struct MixNode {
     descriptor: NodeDescription,
     identity_keypair: Arc<identity::KeyPair>,
     sphinx_keypair: Arc<encryption::KeyPair>,
+    sphinx_keys: Arc<SphinxKeys>,
+    start_time: Instant,
 }
 
 impl MixNode {
     pub fn new(config: Config) -> Self {
         let pathfinder = MixNodePathfinder::new_from_config(&config);
+        let sphinx_keypair = Self::load_sphinx_keys(&pathfinder);
+        let sphinx_keys = SphinxKeys::new(sphinx_keypair.private_key().into());
 
         MixNode {
             descriptor: Self::load_node_description(&config),
             identity_keypair: Arc::new(Self::load_identity_keys(&pathfinder)),
-            sphinx_keypair: Arc::new(Self::load_sphinx_keys(&pathfinder)),
+            sphinx_keypair: Arc::new(sphinx_keypair),
+            sphinx_keys: Arc::new(sphinx_keys),
+            start_time: Instant::now(),
             config,
         }
     }
@@ -125,6 +137,7 @@ impl MixNode {
         &self,
         atomic_verloc_result: AtomicVerlocResult,
         node_stats_pointer: SharedNodeStats,
+        forwarder_queue_length: Arc<AtomicUsize>,
     ) {
         info!("Starting HTTP API on http://localhost:8000");
 
@@ -136,15 +149,17 @@ impl MixNode {
 
         let verloc_state = VerlocState::new(atomic_verloc_result);
         let descriptor = self.descriptor.clone();
+        let metrics_state = MetricsState::new(self.start_time, forwarder_queue_length);
 
         tokio::spawn(async move {
             rocket::build()
                 .configure(config)
-                .mount("/", routes![verlocRoute, description, stats])
+                .mount("/", routes![verlocRoute, description, stats, metrics])
                 .register("/", catchers![not_found])
                 .manage(verloc_state)
                 .manage(descriptor)
                 .manage(node_stats_pointer)
+                .manage(metrics_state)
                 .launch()
                 .await
         });
@@ -175,7 +190,7 @@ impl MixNode {
         info!("Starting socket listener...");
 
         let packet_processor =
-            PacketProcessor::new(self.sphinx_keypair.private_key(), node_stats_update_sender);
+            PacketProcessor::new(Arc::clone(&self.sphinx_keys), node_stats_update_sender);
 
         let connection_handler = ConnectionHandler::new(packet_processor, delay_forwarding_channel);
 
@@ -191,7 +206,7 @@ impl MixNode {
         &mut self,
         node_stats_update_sender: node_statistics::UpdateSender,
         shutdown: ShutdownListener,
-    ) -> PacketDelayForwardSender {
+    ) -> (PacketDelayForwardSender, Arc<AtomicUsize>) {
         info!("Starting packet delay-forwarder...");
 
         let client_config = mixnet_client::Config::new(
@@ -208,9 +223,43 @@ impl MixNode {
         );
 
         let packet_sender = packet_forwarder.sender();
+        let queue_length = packet_forwarder.queue_length_pointer();
 
         tokio::spawn(async move { packet_forwarder.run().await });
-        packet_sender
+        (packet_sender, queue_length)
+    }
+
+    fn start_sphinx_key_rotator(&self, shutdown: ShutdownListener) {
+        let cosmos_mnemonic = match load_cosmos_mnemonic(
+            self.config.get_cosmos_mnemonic_file().as_deref(),
+        ) {
+            Ok(Some(mnemonic)) => mnemonic,
+            Ok(None) => {
+                info!(
+                        "No cosmos mnemonic has been provided - the sphinx key is not going to be rotated"
+                    );
+                return;
+            }
+            Err(err) => {
+                error!("Failed to load the cosmos mnemonic - {}", err);
+                process::exit(1)
+            }
+        };
+
+        info!("Starting sphinx key rotator...");
+
+        SphinxKeyRotator::new(
+            Arc::clone(&self.identity_keypair),
+            *self.sphinx_keypair.public_key(),
+            Arc::clone(&self.sphinx_keys),
+            MixNodePathfinder::new_from_config(&self.config),
+            self.config.get_validator_nymd_endpoints(),
+            cosmos_mnemonic,
+            self.config.get_sphinx_key_rotation_interval(),
+            self.config.get_sphinx_key_rotation_overlap(),
+            shutdown,
+        )
+        .start();
     }
 
     fn start_verloc_measurements(&self) -> AtomicVerlocResult {
@@ -324,17 +373,22 @@ impl MixNode {
 
         let (node_stats_pointer, node_stats_update_sender) =
             self.start_node_stats_controller(shutdown.subscribe());
-        let delay_forwarding_channel = self
+        let (delay_forwarding_channel, forwarder_queue_length) = self
             .start_packet_delay_forwarder(node_stats_update_sender.clone(), shutdown.subscribe());
         self.start_socket_listener(
             node_stats_update_sender,
             delay_forwarding_channel,
             shutdown.subscribe(),
         );
+        self.start_sphinx_key_rotator(shutdown.subscribe());
 
         // TODO: these two also needs to be shutdown
         let atomic_verloc_results = self.start_verloc_measurements();
-        self.start_http_api(atomic_verloc_results, node_stats_pointer);
+        self.start_http_api(
+            atomic_verloc_results,
+            node_stats_pointer,
+            forwarder_queue_length,
+        );
 
         info!("Finished nym mixnode startup procedure - it should now be able to receive mix traffic!");
         self.wait_for_interrupt(shutdown).await
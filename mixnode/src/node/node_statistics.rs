@@ -31,9 +31,11 @@ impl SharedNodeStats {
                 packets_received_since_startup: 0,
                 packets_sent_since_startup: HashMap::new(),
                 packets_explicitly_dropped_since_startup: HashMap::new(),
+                packets_replayed_since_startup: 0,
                 packets_received_since_last_update: 0,
                 packets_sent_since_last_update: HashMap::new(),
                 packets_explicitly_dropped_since_last_update: HashMap::new(),
+                packets_replayed_since_last_update: 0,
             })),
         }
     }
@@ -43,6 +45,7 @@ impl SharedNodeStats {
         new_received: u64,
         new_sent: PacketsMap,
         new_dropped: PacketsMap,
+        new_replayed: u64,
     ) {
         let mut guard = self.inner.write().await;
         let snapshot_time = SystemTime::now();
@@ -51,6 +54,7 @@ impl SharedNodeStats {
         guard.update_time = snapshot_time;
 
         guard.packets_received_since_startup += new_received;
+        guard.packets_replayed_since_startup += new_replayed;
         for (mix, count) in new_sent.iter() {
             *guard
                 .packets_sent_since_startup
@@ -60,7 +64,7 @@ impl SharedNodeStats {
 
         for (mix, count) in new_dropped.iter() {
             *guard
-                .packets_explicitly_dropped_since_last_update
+                .packets_explicitly_dropped_since_startup
                 .entry(mix.clone())
                 .or_insert(0) += *count;
         }
@@ -68,6 +72,7 @@ impl SharedNodeStats {
         guard.packets_received_since_last_update = new_received;
         guard.packets_sent_since_last_update = new_sent;
         guard.packets_explicitly_dropped_since_last_update = new_dropped;
+        guard.packets_replayed_since_last_update = new_replayed;
     }
 
     pub(crate) async fn clone_data(&self) -> NodeStats {
@@ -95,6 +100,9 @@ pub(crate) struct NodeStats {
     // we know for sure we dropped packets to those destinations
     packets_explicitly_dropped_since_startup: PacketsMap,
 
+    // packets we refused to process as we have already seen them before
+    packets_replayed_since_startup: u64,
+
     packets_received_since_last_update: u64,
 
     // note: sent does not imply forwarded. We don't know if it was delivered successfully
@@ -102,9 +110,28 @@ pub(crate) struct NodeStats {
 
     // we know for sure we dropped packets to those destinations
     packets_explicitly_dropped_since_last_update: PacketsMap,
+
+    // packets we refused to process as we have already seen them before
+    packets_replayed_since_last_update: u64,
 }
 
 impl NodeStats {
+    pub(crate) fn packets_received_since_startup(&self) -> u64 {
+        self.packets_received_since_startup
+    }
+
+    pub(crate) fn packets_sent_since_startup(&self) -> &PacketsMap {
+        &self.packets_sent_since_startup
+    }
+
+    pub(crate) fn packets_explicitly_dropped_since_startup(&self) -> &PacketsMap {
+        &self.packets_explicitly_dropped_since_startup
+    }
+
+    pub(crate) fn packets_replayed_since_startup(&self) -> u64 {
+        self.packets_replayed_since_startup
+    }
+
     pub(crate) fn simplify(&self) -> NodeStatsSimple {
         NodeStatsSimple {
             update_time: self.update_time,
@@ -115,12 +142,14 @@ impl NodeStats {
                 .packets_explicitly_dropped_since_startup
                 .values()
                 .sum(),
+            packets_replayed_since_startup: self.packets_replayed_since_startup,
             packets_received_since_last_update: self.packets_received_since_last_update,
             packets_sent_since_last_update: self.packets_sent_since_last_update.values().sum(),
             packets_explicitly_dropped_since_last_update: self
                 .packets_explicitly_dropped_since_last_update
                 .values()
                 .sum(),
+            packets_replayed_since_last_update: self.packets_replayed_since_last_update,
         }
     }
 }
@@ -141,6 +170,9 @@ pub(crate) struct NodeStatsSimple {
     // we know for sure we dropped those packets
     packets_explicitly_dropped_since_startup: u64,
 
+    // packets we refused to process as we have already seen them before
+    packets_replayed_since_startup: u64,
+
     packets_received_since_last_update: u64,
 
     // note: sent does not imply forwarded. We don't know if it was delivered successfully
@@ -148,12 +180,16 @@ pub(crate) struct NodeStatsSimple {
 
     // we know for sure we dropped those packets
     packets_explicitly_dropped_since_last_update: u64,
+
+    // packets we refused to process as we have already seen them before
+    packets_replayed_since_last_update: u64,
 }
 
 pub(crate) enum PacketEvent {
     Sent(String),
     Received,
     Dropped(String),
+    Replayed,
 }
 
 #[derive(Debug, Clone)]
@@ -166,6 +202,7 @@ struct PacketDataInner {
     received: AtomicU64,
     sent: Mutex<PacketsMap>,
     dropped: Mutex<PacketsMap>,
+    replayed: AtomicU64,
 }
 
 impl CurrentPacketData {
@@ -175,6 +212,7 @@ impl CurrentPacketData {
                 received: AtomicU64::new(0),
                 sent: Mutex::new(HashMap::new()),
                 dropped: Mutex::new(HashMap::new()),
+                replayed: AtomicU64::new(0),
             }),
         }
     }
@@ -183,6 +221,10 @@ impl CurrentPacketData {
         self.inner.received.fetch_add(1, Ordering::SeqCst);
     }
 
+    fn increment_replayed(&self) {
+        self.inner.replayed.fetch_add(1, Ordering::SeqCst);
+    }
+
     async fn increment_sent(&self, destination: String) {
         let mut unlocked = self.inner.sent.lock().await;
         let receiver_count = unlocked.entry(destination).or_insert(0);
@@ -195,15 +237,16 @@ impl CurrentPacketData {
         *dropped_count += 1;
     }
 
-    async fn acquire_and_reset(&self) -> (u64, PacketsMap, PacketsMap) {
+    async fn acquire_and_reset(&self) -> (u64, PacketsMap, PacketsMap, u64) {
         let mut unlocked_sent = self.inner.sent.lock().await;
         let mut unlocked_dropped = self.inner.dropped.lock().await;
         let received = self.inner.received.swap(0, Ordering::SeqCst);
+        let replayed = self.inner.replayed.swap(0, Ordering::SeqCst);
 
         let sent = std::mem::take(unlocked_sent.deref_mut());
         let dropped = std::mem::take(unlocked_dropped.deref_mut());
 
-        (received, sent, dropped)
+        (received, sent, dropped, replayed)
     }
 }
 
@@ -240,6 +283,7 @@ impl UpdateHandler {
                         PacketEvent::Dropped(destination) => {
                             self.current_data.increment_dropped(destination).await
                         }
+                        PacketEvent::Replayed => self.current_data.increment_replayed(),
                     }
                 }
                 _ = self.shutdown.recv() => {
@@ -278,6 +322,12 @@ impl UpdateSender {
         self.0.unbounded_send(PacketEvent::Received).unwrap()
     }
 
+    pub(crate) fn report_replayed(&self) {
+        // in unbounded_send() failed it means that the receiver channel was disconnected
+        // and hence something weird must have happened without a way of recovering
+        self.0.unbounded_send(PacketEvent::Replayed).unwrap()
+    }
+
     pub(crate) fn report_dropped(&self, destination: String) {
         // in unbounded_send() failed it means that the receiver channel was disconnected
         // and hence something weird must have happened without a way of recovering
@@ -313,8 +363,11 @@ impl StatsUpdater {
 
     async fn update_stats(&self) {
         // grab new data since last update
-        let (received, sent, dropped) = self.current_packet_data.acquire_and_reset().await;
-        self.current_stats.update(received, sent, dropped).await;
+        let (received, sent, dropped, replayed) =
+            self.current_packet_data.acquire_and_reset().await;
+        self.current_stats
+            .update(received, sent, dropped, replayed)
+            .await;
     }
 
     async fn run(&mut self) {
@@ -377,6 +430,15 @@ impl PacketStatsConsoleLogger {
                 );
             }
 
+            if stats.packets_replayed_since_startup > 0 {
+                info!(
+                    "Since startup rejected {} replayed packets! ({} in last {} seconds)",
+                    stats.packets_replayed_since_startup,
+                    stats.packets_replayed_since_last_update,
+                    difference_secs,
+                );
+            }
+
             debug!(
                 "Since startup received {} packets ({} in last {} seconds)",
                 stats.packets_received_since_startup,
@@ -404,6 +466,13 @@ impl PacketStatsConsoleLogger {
                 );
             }
 
+            if stats.packets_replayed_since_startup > 0 {
+                info!(
+                    "Since startup rejected {} replayed packets!",
+                    stats.packets_replayed_since_startup,
+                );
+            }
+
             debug!(
                 "Since startup received {} packets",
                 stats.packets_received_since_startup
@@ -536,5 +605,32 @@ mod tests {
         assert_eq!(&stats.packets_sent_since_last_update.len(), &1);
         assert_eq!(&stats.packets_received_since_startup, &0u64);
         assert!(&stats.packets_explicitly_dropped_since_startup.is_empty());
+        assert_eq!(&stats.packets_replayed_since_startup, &0u64);
+    }
+
+    #[tokio::test]
+    async fn node_stats_reported_replays_are_received() {
+        let logging_delay = Duration::from_millis(20);
+        let stats_updating_delay = Duration::from_millis(10);
+        let shutdown = ShutdownNotifier::default();
+        let node_stats_controller =
+            Controller::new(logging_delay, stats_updating_delay, shutdown.subscribe());
+
+        let node_stats_pointer = node_stats_controller.get_node_stats_data_pointer();
+        let update_sender = node_stats_controller.start();
+        tokio::time::pause();
+
+        update_sender.report_received();
+        update_sender.report_received();
+        update_sender.report_replayed();
+        tokio::task::yield_now().await;
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        tokio::task::yield_now().await;
+
+        let stats = node_stats_pointer.read().await;
+        assert_eq!(&stats.packets_received_since_startup, &2u64);
+        assert_eq!(&stats.packets_replayed_since_startup, &1u64);
+        assert_eq!(&stats.packets_replayed_since_last_update, &1u64);
     }
 }
@@ -7,6 +7,8 @@ use futures::StreamExt;
 use nonexhaustive_delayqueue::{Expired, NonExhaustiveDelayQueue};
 use nymsphinx::forwarding::packet::MixPacket;
 use std::io;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
 use tokio::time::Instant;
 
 use super::ShutdownListener;
@@ -24,6 +26,8 @@ where
     C: mixnet_client::SendWithoutResponse,
 {
     delay_queue: NonExhaustiveDelayQueue<MixPacket>,
+    /// Number of packets currently in the `delay_queue`, shared with the metrics endpoint.
+    queue_length: Arc<AtomicUsize>,
     mixnet_client: C,
     packet_sender: PacketDelayForwardSender,
     packet_receiver: PacketDelayForwardReceiver,
@@ -44,6 +48,7 @@ where
 
         DelayForwarder::<C> {
             delay_queue: NonExhaustiveDelayQueue::new(),
+            queue_length: Arc::new(AtomicUsize::new(0)),
             mixnet_client: client,
             packet_sender,
             packet_receiver,
@@ -56,6 +61,15 @@ where
         self.packet_sender.clone()
     }
 
+    pub(crate) fn queue_length_pointer(&self) -> Arc<AtomicUsize> {
+        Arc::clone(&self.queue_length)
+    }
+
+    fn update_queue_length(&self) {
+        self.queue_length
+            .store(self.delay_queue.len(), Ordering::Relaxed)
+    }
+
     fn forward_packet(&mut self, packet: MixPacket) {
         let next_hop = packet.next_hop();
         let packet_mode = packet.packet_mode();
@@ -84,6 +98,7 @@ where
 
     /// Upon packet being finished getting delayed, forward it to the mixnet.
     fn handle_done_delaying(&mut self, packet: Expired<MixPacket>) {
+        self.update_queue_length();
         let delayed_packet = packet.into_inner();
         self.forward_packet(delayed_packet)
     }
@@ -98,6 +113,7 @@ where
                 self.forward_packet(new_packet.0)
             } else {
                 self.delay_queue.insert_at(new_packet.0, instant);
+                self.update_queue_length();
             }
         } else {
             self.forward_packet(new_packet.0)
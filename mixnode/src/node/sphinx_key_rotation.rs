@@ -0,0 +1,383 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::config::persistence::pathfinder::MixNodePathfinder;
+use ::crypto::asymmetric::{encryption, identity};
+use bip39::core::str::FromStr;
+use bip39::Mnemonic;
+use config::defaults::DEFAULT_NETWORK;
+use log::{error, info, warn};
+use mixnet_contract_common::mixnode::sphinx_key_update_message;
+use mixnode_common::packet_processor::sphinx_keys::SphinxKeys;
+use rand::seq::SliceRandom;
+use rand::thread_rng;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use task::ShutdownListener;
+use url::Url;
+use validator_client::nymd::error::NymdError;
+use validator_client::nymd::{NymdClient, SigningNymdClient};
+
+/// Delay before attempting the rotation again if the previous attempt has failed.
+const ROTATION_RETRY_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// Environment variable that, if set, takes precedence over the configured mnemonic file.
+pub(crate) const COSMOS_MNEMONIC_ENV_VAR: &str = "NYM_MIXNODE_COSMOS_MNEMONIC";
+
+/// Loads the mnemonic of the wallet used for announcing the rotated sphinx keys either from
+/// the environment or from the provided file. Returns `None` if neither is available.
+/// On unix the file is refused if it's accessible by anyone but its owner.
+pub(crate) fn load_cosmos_mnemonic(mnemonic_file: Option<&Path>) -> io::Result<Option<Mnemonic>> {
+    let raw_mnemonic = if let Ok(mnemonic) = std::env::var(COSMOS_MNEMONIC_ENV_VAR) {
+        mnemonic
+    } else if let Some(mnemonic_file) = mnemonic_file {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = fs::metadata(mnemonic_file)?.permissions().mode();
+            if mode & 0o077 != 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::PermissionDenied,
+                    format!(
+                        "{} is accessible by other users (mode {:o}), restrict it to the owner only",
+                        mnemonic_file.display(),
+                        mode & 0o777
+                    ),
+                ));
+            }
+        }
+        fs::read_to_string(mnemonic_file)?
+    } else {
+        return Ok(None);
+    };
+
+    Mnemonic::from_str(raw_mnemonic.trim())
+        .map(Some)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+}
+
+/// Periodically replaces the sphinx key of the node. The new key is first persisted under
+/// a staging name, then announced to the mixnet contract and only once that succeeds it's
+/// swapped in and used for processing packets. A staged key left behind by a failed attempt
+/// (or a crash) is reused, so the node never announces a key it couldn't recover after restart.
+/// The previous key is still accepted for the duration of the overlap window so that the clients
+/// with slightly stale topology would not get their packets dropped.
+pub(crate) struct SphinxKeyRotator {
+    identity_keypair: Arc<identity::KeyPair>,
+    current_sphinx_key: encryption::PublicKey,
+    sphinx_keys: Arc<SphinxKeys>,
+    pathfinder: MixNodePathfinder,
+    nymd_client: NymdClient<SigningNymdClient>,
+    rotation_interval: Duration,
+    rotation_overlap: Duration,
+    shutdown: ShutdownListener,
+}
+
+impl SphinxKeyRotator {
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        identity_keypair: Arc<identity::KeyPair>,
+        current_sphinx_key: encryption::PublicKey,
+        sphinx_keys: Arc<SphinxKeys>,
+        pathfinder: MixNodePathfinder,
+        nymd_urls: Vec<Url>,
+        cosmos_mnemonic: Mnemonic,
+        rotation_interval: Duration,
+        rotation_overlap: Duration,
+        shutdown: ShutdownListener,
+    ) -> Self {
+        let nymd_url = nymd_urls
+            .choose(&mut thread_rng())
+            .expect("The list of validators is empty");
+        let nymd_client = NymdClient::connect_with_mnemonic(
+            DEFAULT_NETWORK,
+            nymd_url.as_ref(),
+            cosmos_mnemonic,
+            None,
+        )
+        .expect("Could not create nymd client");
+
+        SphinxKeyRotator {
+            identity_keypair,
+            current_sphinx_key,
+            sphinx_keys,
+            pathfinder,
+            nymd_client,
+            rotation_interval,
+            rotation_overlap,
+            shutdown,
+        }
+    }
+
+    fn current_paths(&self) -> pemstore::KeyPairPath {
+        pemstore::KeyPairPath::new(
+            self.pathfinder.private_encryption_key().to_owned(),
+            self.pathfinder.public_encryption_key().to_owned(),
+        )
+    }
+
+    fn staged_paths(&self) -> pemstore::KeyPairPath {
+        pemstore::KeyPairPath::new(
+            self.pathfinder.staged_private_encryption_key().to_owned(),
+            self.pathfinder.staged_public_encryption_key().to_owned(),
+        )
+    }
+
+    fn has_staged_keys(&self) -> bool {
+        self.pathfinder.staged_private_encryption_key().exists()
+    }
+
+    /// Returns the keypair left behind by a previous unfinished rotation or generates
+    /// and persists a fresh one.
+    fn load_or_stage_keypair(&self) -> io::Result<encryption::KeyPair> {
+        let staged_paths = self.staged_paths();
+        match pemstore::load_keypair(&staged_paths) {
+            Ok(keypair) => return Ok(keypair),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => (),
+            Err(err) => return Err(err),
+        }
+
+        let keypair = encryption::KeyPair::new(&mut thread_rng());
+        pemstore::store_keypair(&keypair, &staged_paths)?;
+        Ok(keypair)
+    }
+
+    fn remove_staged_keys(&self) {
+        for path in [
+            self.pathfinder.staged_private_encryption_key(),
+            self.pathfinder.staged_public_encryption_key(),
+        ] {
+            if let Err(err) = fs::remove_file(path) {
+                warn!(
+                    "Failed to remove the staged sphinx key {} - {}",
+                    path.display(),
+                    err
+                )
+            }
+        }
+    }
+
+    fn last_rotation(&self) -> Option<SystemTime> {
+        if let Ok(timestamp) = fs::read_to_string(self.pathfinder.sphinx_key_rotation_timestamp()) {
+            if let Ok(secs) = timestamp.trim().parse() {
+                return Some(UNIX_EPOCH + Duration::from_secs(secs));
+            }
+        }
+
+        // the key has never been rotated, so it's as old as the file it's stored in
+        fs::metadata(self.pathfinder.private_encryption_key())
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    fn store_rotation_time(&self) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        if let Err(err) = fs::write(
+            self.pathfinder.sphinx_key_rotation_timestamp(),
+            now.to_string(),
+        ) {
+            warn!(
+                "Failed to store the time of the sphinx key rotation - {}. The next rotation might happen early after restart",
+                err
+            )
+        }
+    }
+
+    fn initial_delay(&self) -> Duration {
+        // finish whatever got interrupted before doing anything else
+        if self.has_staged_keys() {
+            return Duration::ZERO;
+        }
+
+        match self.last_rotation().and_then(|time| time.elapsed().ok()) {
+            Some(elapsed) => self.rotation_interval.saturating_sub(elapsed),
+            None => self.rotation_interval,
+        }
+    }
+
+    /// The rotation wallet doesn't have to be the bond owner, so the bond is looked up
+    /// by the identity of this node.
+    async fn announced_sphinx_key(&self) -> Result<Option<String>, NymdError> {
+        Ok(self
+            .nymd_client
+            .get_mixnode_bond(self.identity_keypair.public_key().to_base58_string())
+            .await?
+            .map(|bond| bond.mix_node.sphinx_key))
+    }
+
+    async fn announce(&self, new_sphinx_key: &encryption::PublicKey) -> bool {
+        let identity = self.identity_keypair.public_key().to_base58_string();
+        let old_sphinx_key = self.current_sphinx_key.to_base58_string();
+        let new_sphinx_key = new_sphinx_key.to_base58_string();
+
+        // the previous attempt might have reached the contract even though we didn't learn about it
+        // (or we crashed before swapping the keys locally), in which case there's nothing left to announce
+        match self.announced_sphinx_key().await {
+            Ok(Some(announced)) if announced == new_sphinx_key => return true,
+            Ok(Some(announced)) if announced == old_sphinx_key => (),
+            Ok(Some(announced)) => {
+                error!(
+                    "The announced sphinx key {} matches neither the current ({}) nor the staged ({}) key",
+                    announced, old_sphinx_key, new_sphinx_key
+                );
+                return false;
+            }
+            Ok(None) => {
+                warn!("There's no mixnode bonded with identity {}", identity);
+                return false;
+            }
+            Err(err) => {
+                warn!("Failed to query the announced sphinx key - {}", err);
+                return false;
+            }
+        }
+
+        let signature = self
+            .identity_keypair
+            .private_key()
+            .sign_text(&sphinx_key_update_message(&old_sphinx_key, &new_sphinx_key));
+
+        if let Err(err) = self
+            .nymd_client
+            .update_mixnode_sphinx_key(identity, new_sphinx_key, signature, None)
+            .await
+        {
+            warn!("Failed to announce the new sphinx key - {}", err);
+            return false;
+        }
+        true
+    }
+
+    async fn rotate(&mut self) -> bool {
+        let new_keypair = match self.load_or_stage_keypair() {
+            Ok(keypair) => keypair,
+            Err(err) => {
+                error!("Failed to persist the new sphinx key - {}", err);
+                return false;
+            }
+        };
+
+        if !self.announce(new_keypair.public_key()).await {
+            return false;
+        }
+
+        if let Err(err) = pemstore::store_keypair(&new_keypair, &self.current_paths()) {
+            // the staged keys are kept around so the swap is going to be completed after restart
+            error!(
+                "Failed to swap in the rotated sphinx keys - {}. The staged keys are going to be used after restart",
+                err
+            )
+        } else {
+            self.remove_staged_keys();
+        }
+
+        self.sphinx_keys
+            .rotate(new_keypair.private_key().into(), self.rotation_overlap);
+        self.current_sphinx_key = *new_keypair.public_key();
+        self.store_rotation_time();
+
+        info!(
+            "Rotated the sphinx key to {}. The previous one is going to be accepted for {:?}",
+            self.current_sphinx_key.to_base58_string(),
+            self.rotation_overlap
+        );
+        true
+    }
+
+    pub(crate) async fn run(&mut self) {
+        let mut next_attempt = self.initial_delay();
+
+        while !self.shutdown.is_shutdown() {
+            tokio::select! {
+                _ = tokio::time::sleep(next_attempt) => {
+                    next_attempt = if self.rotate().await {
+                        self.rotation_interval
+                    } else {
+                        ROTATION_RETRY_DELAY
+                    };
+                }
+                _ = self.shutdown.recv() => {
+                    log::trace!("SphinxKeyRotator: Received shutdown");
+                }
+            }
+        }
+
+        log::trace!("SphinxKeyRotator: Exiting");
+    }
+
+    pub(crate) fn start(mut self) {
+        tokio::spawn(async move { self.run().await });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::path::PathBuf;
+
+    const TEST_MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon about";
+
+    fn write_mnemonic_file(dir: &Path, mode: u32) -> PathBuf {
+        let path = dir.join("mnemonic");
+        fs::write(&path, format!("{}\n", TEST_MNEMONIC)).unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        }
+        #[cfg(not(unix))]
+        let _ = mode;
+        path
+    }
+
+    #[test]
+    #[serial]
+    fn mnemonic_is_not_loaded_without_a_source() {
+        std::env::remove_var(COSMOS_MNEMONIC_ENV_VAR);
+        assert!(load_cosmos_mnemonic(None).unwrap().is_none());
+    }
+
+    #[test]
+    #[serial]
+    fn mnemonic_is_loaded_from_owner_only_file() {
+        std::env::remove_var(COSMOS_MNEMONIC_ENV_VAR);
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_mnemonic_file(dir.path(), 0o600);
+
+        let mnemonic = load_cosmos_mnemonic(Some(&path)).unwrap().unwrap();
+        assert_eq!(mnemonic.to_string(), TEST_MNEMONIC);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[serial]
+    fn mnemonic_file_readable_by_others_is_refused() {
+        std::env::remove_var(COSMOS_MNEMONIC_ENV_VAR);
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_mnemonic_file(dir.path(), 0o644);
+
+        let err = load_cosmos_mnemonic(Some(&path)).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    #[serial]
+    fn environment_takes_precedence_over_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let missing = dir.path().join("missing");
+
+        std::env::set_var(COSMOS_MNEMONIC_ENV_VAR, TEST_MNEMONIC);
+        let loaded = load_cosmos_mnemonic(Some(&missing));
+        std::env::remove_var(COSMOS_MNEMONIC_ENV_VAR);
+
+        assert_eq!(loaded.unwrap().unwrap().to_string(), TEST_MNEMONIC);
+    }
+}
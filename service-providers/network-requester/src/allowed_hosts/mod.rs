@@ -4,17 +4,30 @@
 use fs::OpenOptions;
 use io::BufReader;
 use ipnetwork::IpNetwork;
-use publicsuffix::{errors, List};
+use publicsuffix::List;
+use rule::{Destination, FilterRule};
 use std::collections::HashSet;
 use std::fs;
 use std::fs::File;
 use std::io;
 use std::io::BufRead;
-use std::net::{IpAddr, SocketAddr};
+use std::net::IpAddr;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
 
-/// Filters outbound requests based on what's in an `allowed_hosts` list.
+mod rule;
+
+/// Snapshot of https://publicsuffix.org/list/public_suffix_list.dat bundled with the binary,
+/// so that the service provider could start without having access to the internet.
+const BUNDLED_PUBLIC_SUFFIX_LIST: &str = include_str!("../../public_suffix_list.dat");
+
+/// Minimum delay between subsequent checks of whether the rules files have changed on disk.
+const RULES_RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Filters outbound requests based on the rules defined in the `allowed_hosts` and `denied_hosts`
+/// lists. A request is permitted only if it matches at least one allow rule and no deny rules.
+/// Both lists are reloaded whenever they are modified on disk, without requiring a restart.
 ///
 /// Requests to unknown hosts are automatically written to an `unknown_hosts`
 /// list so that they can be copy/pasted into the `allowed_hosts` list if desired.
@@ -22,90 +35,86 @@ use std::path::PathBuf;
 /// `unknown_hosts` file and allow new hosts (e.g. if a wallet has added a new outbound request
 /// which needs to be allowed).
 ///
-/// We rely on the list of domains at https://publicsuffix.org/ to figure out what the root
-/// domain is for a given request. This allows us to distinguish all the rules for e.g.
-/// .com, .co.uk, .co.jp, uk.com, etc, so that we can distinguish correct root-ish
-/// domains as allowed. That list is loaded once at startup from the network.
+/// We rely on the list of domains at https://publicsuffix.org/ to figure out whether the
+/// requested domain is valid at all. A copy of that list is bundled with the binary.
 pub(crate) struct OutboundRequestFilter {
-    allowed_hosts: HostsStore,
+    allowed_hosts: RulesStore,
+    denied_hosts: RulesStore,
     domain_list: publicsuffix::List,
     unknown_hosts: HostsStore,
+    last_reload_check: Instant,
 }
 
 impl OutboundRequestFilter {
     pub(crate) fn new(
-        allowed_hosts: HostsStore,
+        allowed_hosts: RulesStore,
+        denied_hosts: RulesStore,
         unknown_hosts: HostsStore,
     ) -> OutboundRequestFilter {
-        let domain_list = match Self::fetch_domain_list() {
-            Ok(list) => list,
-            Err(e) => panic!("Couldn't fetch domain list for request filtering, do you have an internet connection?: {:?}", e),
-        };
+        let domain_list = List::from_str(BUNDLED_PUBLIC_SUFFIX_LIST)
+            .expect("the bundled public suffix list is malformed");
+
         OutboundRequestFilter {
             allowed_hosts,
+            denied_hosts,
             domain_list,
             unknown_hosts,
+            last_reload_check: Instant::now(),
         }
     }
 
-    fn fetch_domain_list() -> Result<List, errors::Error> {
-        publicsuffix::List::fetch()
+    fn maybe_reload_rules(&mut self) {
+        if self.last_reload_check.elapsed() < RULES_RELOAD_CHECK_INTERVAL {
+            return;
+        }
+        self.last_reload_check = Instant::now();
+        self.allowed_hosts.reload_if_modified();
+        self.denied_hosts.reload_if_modified();
     }
 
-    /// Returns `true` if a host's root domain is in the `allowed_hosts` list.
+    /// Returns `true` if the host matches any of the rules in the `allowed_hosts` list
+    /// and none of the rules in the `denied_hosts` list.
     ///
-    /// If it's not in the list, return `false` and write it to the `unknown_hosts` storefile.
+    /// If it's not in the allowed list, return `false` and write it to the `unknown_hosts` storefile.
     pub(crate) fn check(&mut self, host: &str) -> bool {
-        // first check if it's a socket address (ip:port)
-        // (this check is performed to not incorrectly strip what we think might be a port
-        // from ipv6 address, as for example ::1 contains colons but has no port
-        let allowed = if let Ok(socketaddr) = host.parse::<SocketAddr>() {
-            if !self.allowed_hosts.contains_ip_address(socketaddr.ip()) {
-                self.unknown_hosts.maybe_add_ip(socketaddr.ip());
-                return false;
-            }
-            true
-        } else if let Ok(ipaddr) = host.parse::<IpAddr>() {
-            // then check if it was an ip address
-            if !self.allowed_hosts.contains_ip_address(ipaddr) {
-                self.unknown_hosts.maybe_add_ip(ipaddr);
-                return false;
-            }
-            true
-        } else {
-            // finally, then assume it might be a domain
-            let trimmed = Self::trim_port(host);
-            if let Some(domain_root) = self.get_domain_root(&trimmed) {
-                // it's a domain
-                if !self.allowed_hosts.contains_domain(&domain_root) {
-                    self.unknown_hosts.maybe_add_domain(&trimmed);
-                    return false;
-                }
-                true
-            } else {
+        self.maybe_reload_rules();
+
+        let destination = Destination::parse(host);
+        if let Destination::Domain(domain, _) = &destination {
+            if self.get_domain_root(domain).is_none() {
                 // it's something else, no idea what, probably some nonsense
-                false
+                log::warn!("Blocked outbound connection to invalid host {:?}", host);
+                return false;
             }
-        };
+        }
 
-        if !allowed {
+        if let Some(rule) = self.denied_hosts.find_matching(&destination) {
             log::warn!(
-                "Blocked outbound connection to {:?}, add it to allowed.list if needed",
-                &host
+                "Blocked outbound connection to {:?} due to deny rule '{}'",
+                host,
+                rule
             );
+            return false;
         }
 
-        allowed
-    }
+        if let Some(rule) = self.allowed_hosts.find_matching(&destination) {
+            log::debug!(
+                "Allowed outbound connection to {:?} due to allow rule '{}'",
+                host,
+                rule
+            );
+            return true;
+        }
 
-    fn trim_port(host: &str) -> String {
-        let mut tmp: Vec<_> = host.split(':').collect();
-        if tmp.len() > 1 {
-            tmp.pop(); // get rid of last element (port)
-            tmp.join(":") //rejoin
-        } else {
-            host.to_string()
+        match destination {
+            Destination::Ip(ip, _) => self.unknown_hosts.maybe_add_ip(ip),
+            Destination::Domain(domain, _) => self.unknown_hosts.maybe_add_domain(&domain),
         }
+        log::warn!(
+            "Blocked outbound connection to {:?}, add it to allowed.list if needed",
+            host
+        );
+        false
     }
 
     /// Attempts to get the root domain, shorn of subdomains, using publicsuffix.
@@ -120,6 +129,91 @@ impl OutboundRequestFilter {
     }
 }
 
+/// A file-based store of filtering rules, see [`FilterRule`] for the format of each entry.
+/// The rules are reloaded whenever the file gets modified.
+#[derive(Debug)]
+pub(crate) struct RulesStore {
+    storefile: PathBuf,
+    last_modified: Option<(SystemTime, u64)>,
+
+    rules: Vec<FilterRule>,
+}
+
+impl RulesStore {
+    /// Constructs a new RulesStore
+    pub(crate) fn new(base_dir: PathBuf, filename: PathBuf) -> RulesStore {
+        let storefile = setup_storefile(base_dir, filename);
+        let mut store = RulesStore {
+            storefile,
+            last_modified: None,
+            rules: Vec::new(),
+        };
+        if let Err(err) = store.load() {
+            panic!(
+                "Could not load rules from storefile at {:?} - {}",
+                store.storefile, err
+            )
+        }
+        store
+    }
+
+    fn modification_marker(&self) -> io::Result<(SystemTime, u64)> {
+        let metadata = fs::metadata(&self.storefile)?;
+        // include the length in case the modification time resolution is too coarse
+        Ok((metadata.modified()?, metadata.len()))
+    }
+
+    fn load(&mut self) -> io::Result<()> {
+        let last_modified = self.modification_marker()?;
+        let file = File::open(&self.storefile)?;
+
+        let mut rules = Vec::new();
+        for (i, line) in BufReader::new(&file).lines().enumerate() {
+            match FilterRule::parse_line(&line?) {
+                Ok(Some(rule)) => rules.push(rule),
+                Ok(None) => (),
+                Err(err) => log::warn!(
+                    "Ignoring invalid rule in {:?} (line {}) - {}",
+                    self.storefile,
+                    i + 1,
+                    err
+                ),
+            }
+        }
+
+        self.rules = rules;
+        self.last_modified = Some(last_modified);
+        Ok(())
+    }
+
+    fn reload_if_modified(&mut self) {
+        match self.modification_marker() {
+            Ok(marker) if Some(marker) == self.last_modified => (),
+            Ok(_) => match self.load() {
+                Ok(_) => log::info!(
+                    "Reloaded {} filtering rules from {:?}",
+                    self.rules.len(),
+                    self.storefile
+                ),
+                Err(err) => log::error!(
+                    "Failed to reload filtering rules from {:?} - {}. The old rules are still used",
+                    self.storefile,
+                    err
+                ),
+            },
+            Err(err) => log::error!(
+                "Failed to check whether {:?} got modified - {}",
+                self.storefile,
+                err
+            ),
+        }
+    }
+
+    fn find_matching(&self, destination: &Destination) -> Option<&FilterRule> {
+        self.rules.iter().find(|rule| rule.matches(destination))
+    }
+}
+
 // used for parsing file content
 enum Host {
     Domain(String),
@@ -159,10 +253,8 @@ impl Host {
     }
 }
 
-/// A simple file-based store for information about allowed / unknown hosts.
-/// Currently it completely ignores any port information.
-// TODO: in the future allow filtering by port, so for example 1.1.1.1:80 would be a valid filter,
-// which would allow connections to the port :80 while any requests to say 1.1.1.1:1234 would be denied.
+/// A simple file-based store for information about unknown hosts.
+/// It completely ignores any port information.
 #[derive(Debug)]
 pub(crate) struct HostsStore {
     storefile: PathBuf,
@@ -174,7 +266,7 @@ pub(crate) struct HostsStore {
 impl HostsStore {
     /// Constructs a new HostsStore
     pub(crate) fn new(base_dir: PathBuf, filename: PathBuf) -> HostsStore {
-        let storefile = HostsStore::setup_storefile(base_dir, filename);
+        let storefile = setup_storefile(base_dir, filename);
         let hosts = HostsStore::load_from_storefile(&storefile)
             .unwrap_or_else(|_| panic!("Could not load hosts from storefile at {:?}", storefile));
 
@@ -245,18 +337,6 @@ impl HostsStore {
         }
     }
 
-    fn setup_storefile(base_dir: PathBuf, filename: PathBuf) -> PathBuf {
-        let dirpath = base_dir.join("service-providers").join("network-requester");
-        fs::create_dir_all(&dirpath)
-            .unwrap_or_else(|_| panic!("could not create storage directory at {:?}", dirpath));
-        let storefile = dirpath.join(filename);
-        let exists = std::path::Path::new(&storefile).exists();
-        if !exists {
-            File::create(&storefile).unwrap();
-        }
-        storefile
-    }
-
     /// Loads the storefile contents into memory.
     fn load_from_storefile<P>(filename: P) -> io::Result<Vec<Host>>
     where
@@ -271,6 +351,18 @@ impl HostsStore {
     }
 }
 
+fn setup_storefile(base_dir: PathBuf, filename: PathBuf) -> PathBuf {
+    let dirpath = base_dir.join("service-providers").join("network-requester");
+    fs::create_dir_all(&dirpath)
+        .unwrap_or_else(|_| panic!("could not create storage directory at {:?}", dirpath));
+    let storefile = dirpath.join(filename);
+    let exists = std::path::Path::new(&storefile).exists();
+    if !exists {
+        File::create(&storefile).unwrap();
+    }
+    storefile
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -282,13 +374,19 @@ mod tests {
         #[test]
         fn happens_when_port_exists() {
             let host = "nymtech.net:9999";
-            assert_eq!("nymtech.net", OutboundRequestFilter::trim_port(host));
+            assert_eq!(
+                Destination::Domain("nymtech.net".to_string(), Some(9999)),
+                Destination::parse(host)
+            );
         }
 
         #[test]
         fn doesnt_happen_when_no_port_exists() {
             let host = "nymtech.net";
-            assert_eq!("nymtech.net", OutboundRequestFilter::trim_port(host));
+            assert_eq!(
+                Destination::Domain("nymtech.net".to_string(), None),
+                Destination::parse(host)
+            );
         }
     }
 
@@ -299,10 +397,12 @@ mod tests {
         fn setup() -> OutboundRequestFilter {
             let base_dir = test_base_dir();
             let allowed_filename = PathBuf::from(format!("allowed-{}.list", random_string()));
+            let denied_filename = PathBuf::from(format!("denied-{}.list", random_string()));
             let unknown_filename = PathBuf::from(&format!("unknown-{}.list", random_string()));
-            let allowed = HostsStore::new(base_dir.clone(), allowed_filename);
+            let allowed = RulesStore::new(base_dir.clone(), allowed_filename);
+            let denied = RulesStore::new(base_dir.clone(), denied_filename);
             let unknown = HostsStore::new(base_dir, unknown_filename);
-            OutboundRequestFilter::new(allowed, unknown)
+            OutboundRequestFilter::new(allowed, denied, unknown)
         }
 
         #[test]
@@ -361,10 +461,12 @@ mod tests {
         fn setup() -> OutboundRequestFilter {
             let base_dir = test_base_dir();
             let allowed_filename = PathBuf::from(format!("allowed-{}.list", random_string()));
+            let denied_filename = PathBuf::from(format!("denied-{}.list", random_string()));
             let unknown_filename = PathBuf::from(&format!("unknown-{}.list", random_string()));
-            let allowed = HostsStore::new(base_dir.clone(), allowed_filename);
+            let allowed = RulesStore::new(base_dir.clone(), allowed_filename);
+            let denied = RulesStore::new(base_dir.clone(), denied_filename);
             let unknown = HostsStore::new(base_dir, unknown_filename);
-            OutboundRequestFilter::new(allowed, unknown)
+            OutboundRequestFilter::new(allowed, denied, unknown)
         }
 
         #[test]
@@ -392,16 +494,25 @@ mod tests {
         use super::*;
 
         fn setup(allowed: &[&str]) -> OutboundRequestFilter {
+            setup_with_denied(allowed, &[])
+        }
+
+        fn setup_with_denied(allowed: &[&str], denied: &[&str]) -> OutboundRequestFilter {
             let (allowed_storefile, base_dir1, allowed_filename) = create_test_storefile();
-            let (_, base_dir2, unknown_filename) = create_test_storefile();
+            let (denied_storefile, base_dir2, denied_filename) = create_test_storefile();
+            let (_, base_dir3, unknown_filename) = create_test_storefile();
 
             for allowed_host in allowed {
                 HostsStore::append(&allowed_storefile, allowed_host)
             }
+            for denied_host in denied {
+                HostsStore::append(&denied_storefile, denied_host)
+            }
 
-            let allowed = HostsStore::new(base_dir1, allowed_filename);
-            let unknown = HostsStore::new(base_dir2, unknown_filename);
-            OutboundRequestFilter::new(allowed, unknown)
+            let allowed = RulesStore::new(base_dir1, allowed_filename);
+            let denied = RulesStore::new(base_dir2, denied_filename);
+            let unknown = HostsStore::new(base_dir3, unknown_filename);
+            OutboundRequestFilter::new(allowed, denied, unknown)
         }
 
         #[test]
@@ -510,6 +621,50 @@ mod tests {
             assert!(filter.check(top));
             assert!(filter.check(mid));
         }
+
+        #[test]
+        fn are_restricted_to_specified_ports() {
+            let mut filter = setup(&["nymtech.net:443", "1.2.3.4/24:8000-8999"]);
+            assert!(filter.check("nymtech.net:443"));
+            assert!(filter.check("foomp.nymtech.net:443"));
+            assert!(!filter.check("nymtech.net:80"));
+            assert!(!filter.check("nymtech.net"));
+
+            assert!(filter.check("1.2.3.42:8000"));
+            assert!(filter.check("1.2.3.42:8999"));
+            assert!(!filter.check("1.2.3.42:9000"));
+        }
+
+        #[test]
+        fn are_blocked_if_any_deny_rule_matches() {
+            let mut filter = setup_with_denied(
+                &["nymtech.net", "1.2.3.4/24"],
+                &["*.ads.nymtech.net # tracking", "1.2.3.42", "nymtech.net:25"],
+            );
+            assert!(filter.check("nymtech.net:443"));
+            assert!(filter.check("ads.nymtech.net:443"));
+            assert!(!filter.check("foomp.ads.nymtech.net:443"));
+            assert!(!filter.check("nymtech.net:25"));
+            assert!(filter.check("1.2.3.41"));
+            assert!(!filter.check("1.2.3.42:80"));
+
+            // denied hosts are not considered unknown
+            assert!(filter.unknown_hosts.domains.is_empty());
+            assert!(filter.unknown_hosts.ip_nets.is_empty());
+        }
+
+        #[test]
+        fn are_reloaded_when_the_list_changes() {
+            let mut filter = setup(&["nymtech.net"]);
+            assert!(!filter.check("edwardsnowden.com"));
+
+            HostsStore::append(&filter.allowed_hosts.storefile, "edwardsnowden.com");
+            // pretend enough time has passed since the last check
+            filter.last_reload_check -= RULES_RELOAD_CHECK_INTERVAL;
+
+            assert!(filter.check("edwardsnowden.com"));
+            assert!(filter.check("nymtech.net"));
+        }
     }
 
     fn random_string() -> String {
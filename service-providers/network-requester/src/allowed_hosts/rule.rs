@@ -0,0 +1,308 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use ipnetwork::IpNetwork;
+use std::fmt::{self, Display, Formatter};
+use std::net::{IpAddr, SocketAddr};
+use std::ops::RangeInclusive;
+
+/// Destination of an outbound request, as requested by the client.
+#[derive(Debug, PartialEq)]
+pub(crate) enum Destination {
+    Ip(IpAddr, Option<u16>),
+    Domain(String, Option<u16>),
+}
+
+impl Destination {
+    pub(crate) fn parse(raw: &str) -> Self {
+        // first check if it's a socket address (ip:port)
+        // (this check is performed to not incorrectly strip what we think might be a port
+        // from ipv6 address, as for example ::1 contains colons but has no port
+        if let Ok(socket_addr) = raw.parse::<SocketAddr>() {
+            Destination::Ip(socket_addr.ip(), Some(socket_addr.port()))
+        } else if let Ok(ip_addr) = raw.parse::<IpAddr>() {
+            Destination::Ip(ip_addr, None)
+        } else {
+            match raw.rsplit_once(':') {
+                Some((domain, port)) => match port.parse() {
+                    Ok(port) => Destination::Domain(normalise_domain(domain), Some(port)),
+                    Err(_) => Destination::Domain(normalise_domain(raw), None),
+                },
+                None => Destination::Domain(normalise_domain(raw), None),
+            }
+        }
+    }
+
+    fn port(&self) -> Option<u16> {
+        match self {
+            Destination::Ip(_, port) | Destination::Domain(_, port) => *port,
+        }
+    }
+}
+
+fn normalise_domain(domain: &str) -> String {
+    domain.trim_end_matches('.').to_ascii_lowercase()
+}
+
+#[derive(Debug, PartialEq)]
+enum HostPattern {
+    /// Matches the domain itself alongside all of its subdomains, e.g. `nymtech.net`.
+    Domain(String),
+
+    /// Matches only the subdomains of the specified domain, e.g. `*.nymtech.net`.
+    Subdomains(String),
+
+    /// Matches any address within the network, e.g. `1.2.3.4/24`.
+    IpNetwork(IpNetwork),
+}
+
+impl HostPattern {
+    fn parse(raw: &str) -> Result<Self, String> {
+        if raw.is_empty() {
+            return Err("missing host".to_string());
+        }
+
+        if let Ok(ip_network) = raw.parse() {
+            return Ok(HostPattern::IpNetwork(ip_network));
+        }
+
+        let (pattern, domain): (fn(String) -> HostPattern, _) = match raw.strip_prefix("*.") {
+            Some(domain) => (HostPattern::Subdomains, domain),
+            None => (HostPattern::Domain, raw),
+        };
+
+        if domain.is_empty()
+            || domain.contains(|c: char| matches!(c, '*' | ':' | '/') || c.is_whitespace())
+        {
+            return Err(format!("'{}' is not a valid host", raw));
+        }
+
+        Ok(pattern(normalise_domain(domain)))
+    }
+
+    fn matches(&self, destination: &Destination) -> bool {
+        match (self, destination) {
+            (HostPattern::IpNetwork(network), Destination::Ip(ip, _)) => network.contains(*ip),
+            (HostPattern::Domain(pattern), Destination::Domain(domain, _)) => {
+                domain == pattern || is_subdomain(domain, pattern)
+            }
+            (HostPattern::Subdomains(pattern), Destination::Domain(domain, _)) => {
+                is_subdomain(domain, pattern)
+            }
+            _ => false,
+        }
+    }
+}
+
+fn is_subdomain(domain: &str, parent: &str) -> bool {
+    domain.len() > parent.len()
+        && domain.ends_with(parent)
+        && domain.as_bytes()[domain.len() - parent.len() - 1] == b'.'
+}
+
+fn parse_ports(raw: &str) -> Result<RangeInclusive<u16>, String> {
+    let invalid = || format!("'{}' is not a valid port or port range", raw);
+
+    let (start, end) = match raw.split_once('-') {
+        Some((start, end)) => (start, end),
+        None => (raw, raw),
+    };
+    let start = start.trim().parse().map_err(|_| invalid())?;
+    let end = end.trim().parse().map_err(|_| invalid())?;
+    if start > end {
+        return Err(invalid());
+    }
+
+    Ok(start..=end)
+}
+
+/// Single entry of the allowed or denied hosts list.
+///
+/// Each (non-empty) line of the list file defines a single rule, in the form of
+/// `host[:ports] [# reason]`, where:
+/// - `host` is either a domain (which also matches all of its subdomains), a wildcard domain
+///   `*.domain` (which matches only the subdomains), or an ip address or network in CIDR notation.
+///   If ports are specified alongside an ipv6 address, the address has to be put in brackets,
+///   like `[::1]:443`.
+/// - `ports` optionally restrict the rule to a single port or an inclusive range, like `8000-8999`.
+/// - `reason` is an optional comment that is logged whenever the rule gets applied.
+///
+/// Lines starting with `#` or `//` are ignored.
+#[derive(Debug, PartialEq)]
+pub(crate) struct FilterRule {
+    host: HostPattern,
+    ports: Option<RangeInclusive<u16>>,
+    reason: Option<String>,
+}
+
+impl FilterRule {
+    /// Attempts to parse the provided line of the list file. Returns `Ok(None)` if the line
+    /// does not contain any rule, i.e. it's either empty or a comment.
+    pub(crate) fn parse_line(line: &str) -> Result<Option<Self>, String> {
+        if line.trim_start().starts_with("//") {
+            return Ok(None);
+        }
+
+        let (rule, reason) = match line.split_once('#') {
+            Some((rule, reason)) => (rule.trim(), Some(reason.trim())),
+            None => (line.trim(), None),
+        };
+        if rule.is_empty() {
+            return Ok(None);
+        }
+        let reason = reason
+            .filter(|reason| !reason.is_empty())
+            .map(ToString::to_string);
+
+        let (host, ports) = if let Some(bracketed) = rule.strip_prefix('[') {
+            let (host, remainder) = bracketed
+                .split_once(']')
+                .ok_or_else(|| format!("'{}' is missing the closing bracket", rule))?;
+            match remainder {
+                "" => (host, None),
+                remainder => match remainder.strip_prefix(':') {
+                    Some(ports) => (host, Some(ports)),
+                    None => return Err(format!("'{}' has unexpected trailing data", rule)),
+                },
+            }
+        } else if rule.parse::<IpNetwork>().is_ok() {
+            // plain ipv6 addresses (without the brackets) can't specify the port
+            (rule, None)
+        } else {
+            match rule.rsplit_once(':') {
+                Some((host, ports)) => (host, Some(ports)),
+                None => (rule, None),
+            }
+        };
+
+        Ok(Some(FilterRule {
+            host: HostPattern::parse(host)?,
+            ports: ports.map(parse_ports).transpose()?,
+            reason,
+        }))
+    }
+
+    pub(crate) fn matches(&self, destination: &Destination) -> bool {
+        if let Some(ports) = &self.ports {
+            match destination.port() {
+                Some(port) if ports.contains(&port) => (),
+                _ => return false,
+            }
+        }
+
+        self.host.matches(destination)
+    }
+}
+
+impl Display for FilterRule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.host {
+            HostPattern::Domain(domain) => write!(f, "{}", domain)?,
+            HostPattern::Subdomains(domain) => write!(f, "*.{}", domain)?,
+            HostPattern::IpNetwork(IpNetwork::V6(network)) if self.ports.is_some() => {
+                write!(f, "[{}]", network)?
+            }
+            HostPattern::IpNetwork(network) => write!(f, "{}", network)?,
+        }
+        if let Some(ports) = &self.ports {
+            if ports.start() == ports.end() {
+                write!(f, ":{}", ports.start())?
+            } else {
+                write!(f, ":{}-{}", ports.start(), ports.end())?
+            }
+        }
+        if let Some(reason) = &self.reason {
+            write!(f, " ({})", reason)?
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(raw: &str) -> FilterRule {
+        FilterRule::parse_line(raw).unwrap().unwrap()
+    }
+
+    #[test]
+    fn parsing_destinations() {
+        assert_eq!(
+            Destination::parse("Nymtech.net.:443"),
+            Destination::Domain("nymtech.net".to_string(), Some(443))
+        );
+        assert_eq!(
+            Destination::parse("nymtech.net"),
+            Destination::Domain("nymtech.net".to_string(), None)
+        );
+        assert_eq!(
+            Destination::parse("[::1]:80"),
+            Destination::Ip("::1".parse().unwrap(), Some(80))
+        );
+        assert_eq!(
+            Destination::parse("::1"),
+            Destination::Ip("::1".parse().unwrap(), None)
+        );
+    }
+
+    #[test]
+    fn parsing_rules() {
+        assert!(FilterRule::parse_line("").unwrap().is_none());
+        assert!(FilterRule::parse_line("   # just a comment")
+            .unwrap()
+            .is_none());
+        assert!(FilterRule::parse_line("// copyright header")
+            .unwrap()
+            .is_none());
+
+        let parsed = rule("*.nymtech.net:8000-8999 # test servers");
+        assert_eq!(
+            parsed.host,
+            HostPattern::Subdomains("nymtech.net".to_string())
+        );
+        assert_eq!(parsed.ports, Some(8000..=8999));
+        assert_eq!(parsed.reason, Some("test servers".to_string()));
+
+        let parsed = rule("[2001:db8::/32]:443");
+        assert_eq!(
+            parsed.host,
+            HostPattern::IpNetwork("2001:db8::/32".parse().unwrap())
+        );
+        assert_eq!(parsed.ports, Some(443..=443));
+
+        let parsed = rule("2001:db8::1");
+        assert_eq!(
+            parsed.host,
+            HostPattern::IpNetwork("2001:db8::1".parse().unwrap())
+        );
+        assert!(parsed.ports.is_none());
+
+        assert!(FilterRule::parse_line("nymtech.net:foo").is_err());
+        assert!(FilterRule::parse_line("nymtech.net:443-80").is_err());
+        assert!(FilterRule::parse_line("*.").is_err());
+        assert!(FilterRule::parse_line("foo.*.net").is_err());
+        assert!(FilterRule::parse_line("[::1:443").is_err());
+    }
+
+    #[test]
+    fn domain_rules_match_subdomains() {
+        let domain = rule("nymtech.net");
+        assert!(domain.matches(&Destination::parse("nymtech.net:443")));
+        assert!(domain.matches(&Destination::parse("foomp.nymtech.net")));
+        assert!(!domain.matches(&Destination::parse("notnymtech.net")));
+
+        let wildcard = rule("*.nymtech.net");
+        assert!(!wildcard.matches(&Destination::parse("nymtech.net")));
+        assert!(wildcard.matches(&Destination::parse("foomp.nymtech.net")));
+    }
+
+    #[test]
+    fn port_restricted_rules_require_matching_port() {
+        let rule = rule("1.2.3.0/24:443");
+        assert!(rule.matches(&Destination::parse("1.2.3.4:443")));
+        assert!(!rule.matches(&Destination::parse("1.2.3.4:80")));
+        assert!(!rule.matches(&Destination::parse("1.2.3.4")));
+        assert!(!rule.matches(&Destination::parse("1.2.4.4:443")));
+    }
+}
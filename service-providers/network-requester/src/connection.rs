@@ -5,7 +5,9 @@ use futures::channel::mpsc;
 use nymsphinx::addressing::clients::Recipient;
 use proxy_helpers::connection_controller::ConnectionReceiver;
 use proxy_helpers::proxy_runner::ProxyRunner;
-use socks5_requests::{ConnectionId, Message as Socks5Message, RemoteAddress, Response};
+use socks5_requests::{
+    ConnectionId, CreditResponse, Message as Socks5Message, RemoteAddress, Response,
+};
 use std::io;
 use tokio::net::TcpStream;
 
@@ -52,13 +54,23 @@ impl Connection {
             mix_receiver,
             mix_sender,
             connection_id,
+            // older clients don't understand the credit, so wait until they advertise theirs first
+            false,
+        )
+        .run(
+            move |conn_id, read_data, socket_closed| {
+                (
+                    Socks5Message::Response(Response::new(conn_id, read_data, socket_closed)),
+                    recipient,
+                )
+            },
+            move |conn_id, credit| {
+                (
+                    Socks5Message::CreditResponse(CreditResponse::new(conn_id, credit)),
+                    recipient,
+                )
+            },
         )
-        .run(move |conn_id, read_data, socket_closed| {
-            (
-                Socks5Message::Response(Response::new(conn_id, read_data, socket_closed)),
-                recipient,
-            )
-        })
         .await
         .into_inner();
         self.conn = Some(stream);
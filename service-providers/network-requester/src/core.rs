@@ -1,8 +1,9 @@
 // Copyright 2020 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::allowed_hosts::{HostsStore, OutboundRequestFilter};
+use crate::allowed_hosts::{HostsStore, OutboundRequestFilter, RulesStore};
 use crate::connection::Connection;
+use crate::datagram::{DatagramAssociation, DatagramAssociationHandle};
 use crate::statistics::{StatisticsCollector, StatisticsSender, Timer};
 use crate::websocket;
 use crate::websocket::TSWebsocketStream;
@@ -13,7 +14,8 @@ use log::*;
 use nymsphinx::addressing::clients::Recipient;
 use nymsphinx::receiver::ReconstructedMessage;
 use proxy_helpers::connection_controller::{Controller, ControllerCommand, ControllerSender};
-use socks5_requests::{ConnectionId, Message as Socks5Message, Request, Response};
+use socks5_requests::{ConnectionId, DatagramRequest, Message as Socks5Message, Request, Response};
+use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio_tungstenite::tungstenite::protocol::Message;
@@ -29,6 +31,8 @@ pub struct ServiceProvider {
     open_proxy: bool,
     enable_statistics: bool,
     stats_provider_addr: Option<Recipient>,
+    client_id: Option<String>,
+    datagram_associations: HashMap<ConnectionId, DatagramAssociationHandle>,
 }
 
 impl ServiceProvider {
@@ -37,24 +41,31 @@ impl ServiceProvider {
         open_proxy: bool,
         enable_statistics: bool,
         stats_provider_addr: Option<Recipient>,
+        client_id: Option<String>,
     ) -> ServiceProvider {
-        let allowed_hosts = HostsStore::new(
+        let allowed_hosts = RulesStore::new(
             HostsStore::default_base_dir(),
             PathBuf::from("allowed.list"),
         );
 
+        let denied_hosts =
+            RulesStore::new(HostsStore::default_base_dir(), PathBuf::from("denied.list"));
+
         let unknown_hosts = HostsStore::new(
             HostsStore::default_base_dir(),
             PathBuf::from("unknown.list"),
         );
 
-        let outbound_request_filter = OutboundRequestFilter::new(allowed_hosts, unknown_hosts);
+        let outbound_request_filter =
+            OutboundRequestFilter::new(allowed_hosts, denied_hosts, unknown_hosts);
         ServiceProvider {
             listening_address,
             outbound_request_filter,
             open_proxy,
             enable_statistics,
             stats_provider_addr,
+            client_id,
+            datagram_associations: HashMap::new(),
         }
     }
 
@@ -223,11 +234,73 @@ impl ServiceProvider {
             .unwrap()
     }
 
+    fn handle_proxy_credit(
+        &self,
+        controller_sender: &mut ControllerSender,
+        conn_id: ConnectionId,
+        credit: u64,
+    ) {
+        controller_sender
+            .unbounded_send(ControllerCommand::Credit(conn_id, credit))
+            .unwrap()
+    }
+
+    // gets rid of the association if it has been closed, e.g. due to inactivity
+    fn remove_closed_association(&mut self, conn_id: ConnectionId) {
+        // the id might have already been reused by a new association
+        if let Some(true) = self
+            .datagram_associations
+            .get(&conn_id)
+            .map(|handle| handle.is_closed())
+        {
+            debug!("Removing closed UDP association (id: {})", conn_id);
+            self.datagram_associations.remove(&conn_id);
+        }
+    }
+
+    fn handle_proxy_datagram(
+        &mut self,
+        mix_input_sender: &mpsc::UnboundedSender<(Socks5Message, Recipient)>,
+        closed_associations_sender: &mpsc::UnboundedSender<ConnectionId>,
+        req: DatagramRequest,
+    ) {
+        // the association might have been closed before we got notified about it
+        self.remove_closed_association(req.conn_id);
+
+        if !self.datagram_associations.contains_key(&req.conn_id) {
+            let (association, handle) =
+                DatagramAssociation::new(req.conn_id, req.return_address, mix_input_sender.clone());
+
+            info!("Starting new UDP association (id: {})", req.conn_id);
+            let conn_id = req.conn_id;
+            let closed_associations_sender = closed_associations_sender.clone();
+            tokio::spawn(async move {
+                association.run().await;
+                // if we're shutting down, there's nothing to remove the association from anyway
+                let _ = closed_associations_sender.unbounded_send(conn_id);
+            });
+            self.datagram_associations.insert(req.conn_id, handle);
+        }
+
+        // we just ensured the entry exists
+        let association = self.datagram_associations.get_mut(&req.conn_id).unwrap();
+        if !self.open_proxy && !association.is_allowed(&req.remote_addr) {
+            if !self.outbound_request_filter.check(&req.remote_addr) {
+                log::info!("Domain {:?} failed filter check", req.remote_addr);
+                return;
+            }
+            association.mark_allowed(req.remote_addr.clone());
+        }
+
+        association.send(req.remote_addr, req.data)
+    }
+
     async fn handle_proxy_message(
         &mut self,
         raw_request: &[u8],
         controller_sender: &mut ControllerSender,
         mix_input_sender: &mpsc::UnboundedSender<(Socks5Message, Recipient)>,
+        closed_associations_sender: &mpsc::UnboundedSender<ConnectionId>,
         stats_collector: Option<StatisticsCollector>,
     ) {
         let deserialized_msg = match Socks5Message::try_from_bytes(raw_request) {
@@ -273,8 +346,30 @@ impl ServiceProvider {
                     }
                     self.handle_proxy_send(controller_sender, conn_id, data, closed)
                 }
+
+                Request::SendDatagram(req) => {
+                    if let Some(stats_collector) = stats_collector {
+                        stats_collector
+                            .connected_services
+                            .write()
+                            .await
+                            .insert(req.conn_id, req.remote_addr.clone());
+                        stats_collector
+                            .request_stats_data
+                            .write()
+                            .await
+                            .processed(&req.remote_addr, req.data.len() as u32);
+                    }
+                    self.handle_proxy_datagram(mix_input_sender, closed_associations_sender, *req)
+                }
+
+                Request::Credit(conn_id, credit) => {
+                    self.handle_proxy_credit(controller_sender, conn_id, credit)
+                }
             },
-            Socks5Message::Response(_) => {}
+            Socks5Message::Response(_)
+            | Socks5Message::DatagramResponse(_)
+            | Socks5Message::CreditResponse(_) => {}
         }
     }
 
@@ -296,6 +391,9 @@ impl ServiceProvider {
             timer_sender.run().await;
         });
 
+        // channel used by the UDP associations to let us know they have been closed
+        let (closed_associations_sender, mut closed_associations_receiver) = mpsc::unbounded();
+
         // controller for managing all active connections
         let (mut active_connections_controller, mut controller_sender) = Controller::new();
         tokio::spawn(async move {
@@ -303,10 +401,17 @@ impl ServiceProvider {
         });
 
         let stats_collector = if self.enable_statistics {
-            let mut stats_sender =
-                StatisticsSender::new(interval, timer_receiver, self.stats_provider_addr)
-                    .await
-                    .expect("Statistics controller could not be bootstrapped");
+            let client_id = self.client_id.as_deref().expect(
+                "Statistics require the id of the client the network requester is attached to",
+            );
+            let mut stats_sender = StatisticsSender::new(
+                interval,
+                timer_receiver,
+                self.stats_provider_addr,
+                client_id,
+            )
+            .await
+            .expect("Statistics controller could not be bootstrapped");
             let stats_collector = StatisticsCollector::from(&stats_sender);
 
             let mix_input_sender_clone = mix_input_sender.clone();
@@ -332,24 +437,32 @@ impl ServiceProvider {
         println!("\nAll systems go. Press CTRL-C to stop the server.");
         // for each incoming message from the websocket... (which in 99.99% cases is going to be a mix message)
         loop {
-            let received = match Self::read_websocket_message(&mut websocket_reader).await {
-                Some(msg) => msg,
-                None => {
-                    error!("The websocket stream has finished!");
-                    return;
-                }
-            };
+            tokio::select! {
+                received = Self::read_websocket_message(&mut websocket_reader) => {
+                    let received = match received {
+                        Some(msg) => msg,
+                        None => {
+                            error!("The websocket stream has finished!");
+                            return;
+                        }
+                    };
 
-            let raw_message = received.message;
-            // TODO: here be potential SURB (i.e. received.reply_SURB)
+                    let raw_message = received.message;
+                    // TODO: here be potential SURB (i.e. received.reply_SURB)
 
-            self.handle_proxy_message(
-                &raw_message,
-                &mut controller_sender,
-                &mix_input_sender,
-                stats_collector.clone(),
-            )
-            .await;
+                    self.handle_proxy_message(
+                        &raw_message,
+                        &mut controller_sender,
+                        &mix_input_sender,
+                        &closed_associations_sender,
+                        stats_collector.clone(),
+                    )
+                    .await;
+                }
+                Some(conn_id) = closed_associations_receiver.next() => {
+                    self.remove_closed_association(conn_id)
+                }
+            }
         }
     }
 
@@ -0,0 +1,182 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use futures::channel::mpsc;
+use futures::StreamExt;
+use log::*;
+use nymsphinx::addressing::clients::Recipient;
+use socks5_requests::{ConnectionId, DatagramResponse, Message as Socks5Message, RemoteAddress};
+use std::collections::HashSet;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::net::{lookup_host, UdpSocket};
+
+/// Maximum size of a single UDP datagram we are willing to receive.
+const MAX_DATAGRAM_SIZE: usize = 65_535;
+
+/// If no datagram is sent nor received on the association during this period,
+/// the association is closed and its socket is released.
+const ASSOCIATION_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Datagram read from the mix network that should be sent to the specified remote.
+pub(crate) struct OutboundDatagram {
+    remote_addr: RemoteAddress,
+    data: Vec<u8>,
+}
+
+// receives a datagram on the socket or waits forever if it hasn't been bound yet
+async fn recv_from(socket: &Option<UdpSocket>, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+    match socket {
+        Some(socket) => socket.recv_from(buf).await,
+        None => futures::future::pending().await,
+    }
+}
+
+/// Handle to a running UDP association. It keeps track of the destinations that were already
+/// checked against the outbound request filter so that we would not need to do it for every
+/// single datagram.
+pub(crate) struct DatagramAssociationHandle {
+    sender: mpsc::UnboundedSender<OutboundDatagram>,
+    allowed_destinations: HashSet<RemoteAddress>,
+}
+
+impl DatagramAssociationHandle {
+    pub(crate) fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    pub(crate) fn is_allowed(&self, remote_addr: &str) -> bool {
+        self.allowed_destinations.contains(remote_addr)
+    }
+
+    pub(crate) fn mark_allowed(&mut self, remote_addr: RemoteAddress) {
+        self.allowed_destinations.insert(remote_addr);
+    }
+
+    pub(crate) fn send(&self, remote_addr: RemoteAddress, data: Vec<u8>) {
+        if self
+            .sender
+            .unbounded_send(OutboundDatagram { remote_addr, data })
+            .is_err()
+        {
+            debug!("Tried to send a datagram on an already closed association");
+        }
+    }
+}
+
+/// UDP sockets used by the network requester on behalf of a single remote client's UDP association.
+/// Separate sockets are used for IPv4 and IPv6 destinations and each is only bound once needed.
+pub(crate) struct DatagramAssociation {
+    id: ConnectionId,
+    ipv4_socket: Option<UdpSocket>,
+    ipv6_socket: Option<UdpSocket>,
+    return_address: Recipient,
+    mix_receiver: mpsc::UnboundedReceiver<OutboundDatagram>,
+    mix_sender: mpsc::UnboundedSender<(Socks5Message, Recipient)>,
+}
+
+impl DatagramAssociation {
+    pub(crate) fn new(
+        id: ConnectionId,
+        return_address: Recipient,
+        mix_sender: mpsc::UnboundedSender<(Socks5Message, Recipient)>,
+    ) -> (Self, DatagramAssociationHandle) {
+        let (sender, mix_receiver) = mpsc::unbounded();
+
+        (
+            DatagramAssociation {
+                id,
+                ipv4_socket: None,
+                ipv6_socket: None,
+                return_address,
+                mix_receiver,
+                mix_sender,
+            },
+            DatagramAssociationHandle {
+                sender,
+                allowed_destinations: HashSet::new(),
+            },
+        )
+    }
+
+    // gets the socket able to reach the provided address, binding it if it doesn't exist yet
+    async fn socket_for(&mut self, remote_addr: &SocketAddr) -> io::Result<&UdpSocket> {
+        let (socket, bind_address) = if remote_addr.is_ipv4() {
+            (&mut self.ipv4_socket, "0.0.0.0:0")
+        } else {
+            (&mut self.ipv6_socket, "[::]:0")
+        };
+
+        if socket.is_none() {
+            *socket = Some(UdpSocket::bind(bind_address).await?);
+        }
+        Ok(socket.as_ref().unwrap())
+    }
+
+    async fn try_send_to_remote(&mut self, datagram: &OutboundDatagram) -> io::Result<()> {
+        let remote_addr = lookup_host(datagram.remote_addr.as_str())
+            .await?
+            .next()
+            .ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, "the address could not be resolved")
+            })?;
+
+        self.socket_for(&remote_addr)
+            .await?
+            .send_to(&datagram.data, remote_addr)
+            .await?;
+        Ok(())
+    }
+
+    async fn send_to_remote(&mut self, datagram: OutboundDatagram) {
+        if let Err(err) = self.try_send_to_remote(&datagram).await {
+            warn!(
+                "Failed to send datagram to {} - {}",
+                datagram.remote_addr, err
+            )
+        }
+    }
+
+    fn send_to_mixnet(&self, remote_addr: RemoteAddress, data: Vec<u8>) {
+        let response = DatagramResponse::new(self.id, remote_addr, data);
+        self.mix_sender
+            .unbounded_send((
+                Socks5Message::DatagramResponse(response),
+                self.return_address,
+            ))
+            .unwrap();
+    }
+
+    pub(crate) async fn run(mut self) {
+        let mut ipv4_buf = vec![0u8; MAX_DATAGRAM_SIZE];
+        let mut ipv6_buf = vec![0u8; MAX_DATAGRAM_SIZE];
+
+        loop {
+            tokio::select! {
+                datagram = self.mix_receiver.next() => {
+                    match datagram {
+                        Some(datagram) => self.send_to_remote(datagram).await,
+                        None => break,
+                    }
+                }
+                received = recv_from(&self.ipv4_socket, &mut ipv4_buf) => {
+                    match received {
+                        Ok((len, source)) => self.send_to_mixnet(source.to_string(), ipv4_buf[..len].to_vec()),
+                        Err(err) => warn!("Failed to receive datagram - {}", err),
+                    }
+                }
+                received = recv_from(&self.ipv6_socket, &mut ipv6_buf) => {
+                    match received {
+                        Ok((len, source)) => self.send_to_mixnet(source.to_string(), ipv6_buf[..len].to_vec()),
+                        Err(err) => warn!("Failed to receive datagram - {}", err),
+                    }
+                }
+                _ = tokio::time::sleep(ASSOCIATION_IDLE_TIMEOUT) => {
+                    debug!("UDP association {} has been idle for too long", self.id);
+                    break;
+                }
+            }
+        }
+    }
+}
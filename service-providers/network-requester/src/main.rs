@@ -9,6 +9,7 @@ use nymsphinx::addressing::clients::Recipient;
 mod allowed_hosts;
 mod connection;
 mod core;
+mod datagram;
 mod statistics;
 mod websocket;
 
@@ -16,6 +17,7 @@ const OPEN_PROXY_ARG: &str = "open-proxy";
 const WS_PORT: &str = "websocket-port";
 const ENABLE_STATISTICS: &str = "enable-statistics";
 const STATISTICS_RECIPIENT: &str = "statistics-recipient";
+const CLIENT_ID: &str = "client-id";
 
 fn parse_args<'a>() -> ArgMatches<'a> {
     App::new("Nym Network Requester")
@@ -37,7 +39,8 @@ fn parse_args<'a>() -> ArgMatches<'a> {
         .arg(
             Arg::with_name(ENABLE_STATISTICS)
                 .help("enable service statistics that get sent to a statistics aggregator server")
-                .long(ENABLE_STATISTICS),
+                .long(ENABLE_STATISTICS)
+                .requires(CLIENT_ID),
         )
         .arg(
             Arg::with_name(STATISTICS_RECIPIENT)
@@ -46,6 +49,13 @@ fn parse_args<'a>() -> ArgMatches<'a> {
                 .requires(ENABLE_STATISTICS)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name(CLIENT_ID)
+                .help("id of the nym client the network requester is attached to. Its identity keys are used for signing the statistics")
+                .long(CLIENT_ID)
+                .requires(ENABLE_STATISTICS)
+                .takes_value(true),
+        )
         .get_matches()
 }
 
@@ -70,6 +80,8 @@ async fn main() {
         .transpose()
         .unwrap_or(None);
 
+    let client_id = matches.value_of(CLIENT_ID).map(ToString::to_string);
+
     let uri = format!(
         "ws://localhost:{}",
         matches
@@ -78,8 +90,13 @@ async fn main() {
     );
 
     println!("Starting socks5 service provider:");
-    let mut server =
-        core::ServiceProvider::new(uri, open_proxy, enable_statistics, stats_provider_addr);
+    let mut server = core::ServiceProvider::new(
+        uri,
+        open_proxy,
+        enable_statistics,
+        stats_provider_addr,
+        client_id,
+    );
     server.run().await;
 }
 
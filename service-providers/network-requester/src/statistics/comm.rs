@@ -12,7 +12,11 @@ use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::RwLock;
 
+use client_core::config::persistence::key_pathfinder::ClientKeyPathfinder;
+use config::NymConfig;
+use crypto::asymmetric::identity;
 use network_defaults::DEFAULT_NETWORK;
+use nym_client::client::config::Config;
 use nymsphinx::addressing::clients::Recipient;
 use ordered_buffer::OrderedMessageSender;
 use socks5_requests::{ConnectionId, Message as Socks5Message, RemoteAddress, Request};
@@ -27,6 +31,17 @@ use super::error::StatsError;
 const REMOTE_SOURCE_OF_STATS_PROVIDER_CONFIG: &str =
     "https://nymtech.net/.wellknown/network-requester/stats-provider.json";
 
+// Loads the identity keys of the nym client the network requester is attached to, so that the
+// statistics are signed with the identity the network requester is reachable at
+fn load_client_identity_keys(client_id: &str) -> Result<identity::KeyPair, StatsError> {
+    let config = Config::load_from_file(Some(client_id))?;
+    let pathfinder = ClientKeyPathfinder::new_from_config(config.get_base());
+    Ok(pemstore::load_keypair(&pemstore::KeyPairPath::new(
+        pathfinder.private_identity_key().to_owned(),
+        pathfinder.public_identity_key().to_owned(),
+    ))?)
+}
+
 #[derive(Clone, Debug)]
 pub struct StatsData {
     client_processed_bytes: HashMap<String, u32>,
@@ -97,6 +112,7 @@ pub struct StatisticsSender {
     timestamp: DateTime<Utc>,
     timer_receiver: mpsc::Receiver<()>,
     stats_provider_addr: Recipient,
+    identity_keys: identity::KeyPair,
 }
 
 impl StatisticsSender {
@@ -104,6 +120,7 @@ impl StatisticsSender {
         interval_seconds: Duration,
         timer_receiver: mpsc::Receiver<()>,
         stats_provider_addr: Option<Recipient>,
+        client_id: &str,
     ) -> Result<Self, StatsError> {
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(3))
@@ -123,6 +140,12 @@ impl StatisticsSender {
             .map_err(|_| StatsError::InvalidClientAddress)?,
         );
 
+        let identity_keys = load_client_identity_keys(client_id)?;
+        info!(
+            "The statistics are going to be signed with the client identity {}. The statistics service has to allow it for the submissions to be accepted.",
+            identity_keys.public_key().to_base58_string()
+        );
+
         Ok(StatisticsSender {
             request_data: Arc::new(RwLock::new(StatsData::new())),
             response_data: Arc::new(RwLock::new(StatsData::new())),
@@ -130,6 +153,7 @@ impl StatisticsSender {
             interval_seconds: interval_seconds.as_secs() as u32,
             timer_receiver,
             stats_provider_addr,
+            identity_keys,
         })
     }
 
@@ -173,7 +197,10 @@ impl StatisticsSender {
                     interval_seconds: self.interval_seconds,
                     timestamp: self.timestamp.to_rfc3339(),
                 };
-                match build_statistics_request_bytes(stats_message) {
+                match stats_message
+                    .sign(&self.identity_keys)
+                    .and_then(build_statistics_request_bytes)
+                {
                     Ok(data) => {
                         trace!("Connecting to statistics service");
                         let mut rng = rand::rngs::OsRng;
@@ -10,4 +10,7 @@ pub enum StatsError {
 
     #[error("Invalid stats provider client address")]
     InvalidClientAddress,
+
+    #[error("Could not load the client identity keys: {0}")]
+    IdentityKeysError(#[from] std::io::Error),
 }
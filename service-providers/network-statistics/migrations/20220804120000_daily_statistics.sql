@@ -0,0 +1,21 @@
+/*
+ * Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+CREATE INDEX service_statistics_timestamp ON service_statistics (timestamp);
+
+-- raw statistics older than the retention period get rolled up into daily summaries
+CREATE TABLE daily_service_statistics
+(
+    id                         INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    requested_service          VARCHAR NOT NULL,
+    -- date, YYYY-MM-DD format
+    day                        VARCHAR NOT NULL,
+    request_processed_bytes    INTEGER NOT NULL,
+    response_processed_bytes   INTEGER NOT NULL,
+    -- number of raw entries that got rolled up into this summary
+    entries                    INTEGER NOT NULL,
+
+    UNIQUE (requested_service, day)
+);
@@ -0,0 +1,13 @@
+/*
+ * Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- accepted submissions, used for detecting the replayed and duplicate ones
+CREATE TABLE statistics_submissions
+(
+    reporter                   VARCHAR NOT NULL,
+    timestamp                  DATETIME NOT NULL,
+
+    PRIMARY KEY (reporter, timestamp)
+);
@@ -7,6 +7,7 @@ use rocket::{response, Request, Response};
 use std::io::Cursor;
 
 use crate::storage::error::NetworkStatisticsStorageError;
+use statistics::error::StatsError;
 
 pub type Result<T> = std::result::Result<T, NetworkStatisticsAPIError>;
 
@@ -17,15 +18,32 @@ pub enum NetworkStatisticsAPIError {
 
     #[error("{0}")]
     StorageError(#[from] NetworkStatisticsStorageError),
+
+    #[error("Reporter {0} is not allowed to submit statistics")]
+    UnknownReporter(String),
+
+    #[error("Invalid statistics submission - {0}")]
+    InvalidSubmission(#[from] StatsError),
+
+    #[error("Malformed statistics submission - {0}")]
+    MalformedSubmission(String),
+
+    #[error("Statistics submitted with timestamp {0} are not fresh enough to be accepted")]
+    StaleSubmission(String),
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for NetworkStatisticsAPIError {
     fn respond_to(self, _: &'r Request<'_>) -> response::Result<'o> {
+        let status = match self {
+            NetworkStatisticsAPIError::UnknownReporter(_)
+            | NetworkStatisticsAPIError::InvalidSubmission(_) => Status::Forbidden,
+            _ => Status::BadRequest,
+        };
         let err_msg = self.to_string();
         Response::build()
             .header(ContentType::Plain)
             .sized_body(err_msg.len(), Cursor::new(err_msg))
-            .status(Status::BadRequest)
+            .status(status)
             .ok()
     }
 }
@@ -6,25 +6,43 @@ use rocket::{Ignite, Rocket};
 
 use crate::storage::NetworkStatisticsStorage;
 use error::Result;
-use routes::{post_all_statistics, post_statistic};
+use routes::{
+    get_rejected_submissions, post_aggregated_statistics, post_aggregated_statistics_csv,
+    post_all_statistics, post_all_statistics_csv, post_statistic, post_top_services, post_totals,
+};
 
 use statistics::api::STATISTICS_SERVICE_VERSION;
 
 mod error;
 mod routes;
+pub(crate) mod submissions;
 
 pub(crate) struct NetworkStatisticsAPI {
     rocket: Rocket<Ignite>,
 }
 
 impl NetworkStatisticsAPI {
-    pub async fn init(storage: NetworkStatisticsStorage) -> Result<Self> {
+    pub async fn init(
+        storage: NetworkStatisticsStorage,
+        allowlist: submissions::ReporterAllowlist,
+    ) -> Result<Self> {
         let rocket = rocket::build()
             .mount(
                 STATISTICS_SERVICE_VERSION,
-                rocket::routes![post_all_statistics, post_statistic],
+                rocket::routes![
+                    post_all_statistics,
+                    post_all_statistics_csv,
+                    post_aggregated_statistics,
+                    post_aggregated_statistics_csv,
+                    post_top_services,
+                    post_totals,
+                    post_statistic,
+                    get_rejected_submissions
+                ],
             )
             .manage(storage.clone())
+            .manage(allowlist)
+            .manage(submissions::RejectedSubmissions::default())
             .ignite()
             .await
             .map_err(Box::new)?;
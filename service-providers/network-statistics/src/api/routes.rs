@@ -1,14 +1,27 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
-use rocket::serde::json::Json;
+use log::*;
+use rocket::http::ContentType;
+use rocket::serde::json::{self, Json};
 use rocket::State;
 use serde::{Deserialize, Serialize};
+use sqlx::types::chrono::{DateTime, Utc};
 
-use statistics::StatsMessage;
+use statistics::SignedStatsMessage;
 
-use crate::api::error::Result;
-use crate::storage::NetworkStatisticsStorage;
+use crate::api::error::{NetworkStatisticsAPIError, Result};
+use crate::api::submissions::{
+    is_fresh, RejectedSubmissions, RejectedSubmissionsCount, ReporterAllowlist,
+};
+use crate::storage::models::Granularity;
+use crate::storage::{parse_timestamp, NetworkStatisticsStorage};
+
+const DEFAULT_TOP_SERVICES: u32 = 10;
+
+fn default_top_services() -> u32 {
+    DEFAULT_TOP_SERVICES
+}
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct ServiceStatisticsRequest {
@@ -16,6 +29,12 @@ pub struct ServiceStatisticsRequest {
     since: String,
     // date, RFC 3339 format
     until: String,
+    // maximum number of entries to return
+    #[serde(default)]
+    limit: Option<u32>,
+    // number of entries to skip
+    #[serde(default)]
+    offset: Option<u32>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -27,15 +46,108 @@ pub struct ServiceStatistic {
     pub timestamp: String,
 }
 
-#[rocket::post("/all-statistics", data = "<all_statistics_request>")]
-pub(crate) async fn post_all_statistics(
-    all_statistics_request: Json<ServiceStatisticsRequest>,
-    storage: &State<NetworkStatisticsStorage>,
-) -> Result<Json<Vec<ServiceStatistic>>> {
-    let service_statistics = storage
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AggregatedStatisticsRequest {
+    // date, RFC 3339 format
+    since: String,
+    // date, RFC 3339 format
+    until: String,
+    granularity: Granularity,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct AggregatedServiceStatistic {
+    pub requested_service: String,
+    // start of the hour or the day the data was aggregated over
+    pub bucket: String,
+    pub request_processed_bytes: u64,
+    pub response_processed_bytes: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct TopServicesRequest {
+    // date, RFC 3339 format
+    since: String,
+    // date, RFC 3339 format
+    until: String,
+    // number of services to return
+    #[serde(default = "default_top_services")]
+    limit: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct ServiceTotal {
+    pub requested_service: String,
+    pub request_processed_bytes: u64,
+    pub response_processed_bytes: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct IntervalTotalsRequest {
+    // date, RFC 3339 format
+    since: String,
+    // date, RFC 3339 format
+    until: String,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct IntervalTotals {
+    pub services: u64,
+    pub request_processed_bytes: u64,
+    pub response_processed_bytes: u64,
+}
+
+// quotes the field if it contains any character that would break the csv structure
+fn csv_field(field: &str) -> String {
+    if field.contains(&[',', '"', '\n', '\r'][..]) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn all_statistics_csv(statistics: &[ServiceStatistic]) -> String {
+    let mut csv = String::from(
+        "requested_service,request_processed_bytes,response_processed_bytes,interval_seconds,timestamp\n",
+    );
+    for statistic in statistics {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            csv_field(&statistic.requested_service),
+            statistic.request_processed_bytes,
+            statistic.response_processed_bytes,
+            statistic.interval_seconds,
+            statistic.timestamp
+        ));
+    }
+    csv
+}
+
+fn aggregated_statistics_csv(statistics: &[AggregatedServiceStatistic]) -> String {
+    let mut csv =
+        String::from("requested_service,bucket,request_processed_bytes,response_processed_bytes\n");
+    for statistic in statistics {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            csv_field(&statistic.requested_service),
+            statistic.bucket,
+            statistic.request_processed_bytes,
+            statistic.response_processed_bytes
+        ));
+    }
+    csv
+}
+
+async fn get_all_statistics(
+    request: &ServiceStatisticsRequest,
+    storage: &NetworkStatisticsStorage,
+) -> Result<Vec<ServiceStatistic>> {
+    Ok(storage
         .get_service_statistics_in_interval(
-            &all_statistics_request.since,
-            &all_statistics_request.until,
+            &request.since,
+            &request.until,
+            request.limit,
+            request.offset,
         )
         .await?
         .into_iter()
@@ -46,16 +158,288 @@ pub(crate) async fn post_all_statistics(
             interval_seconds: data.interval_seconds as u32,
             timestamp: data.timestamp.to_string(),
         })
+        .collect())
+}
+
+async fn get_aggregated_statistics(
+    request: &AggregatedStatisticsRequest,
+    storage: &NetworkStatisticsStorage,
+) -> Result<Vec<AggregatedServiceStatistic>> {
+    Ok(storage
+        .get_aggregated_service_statistics(&request.since, &request.until, request.granularity)
+        .await?
+        .into_iter()
+        .map(|data| AggregatedServiceStatistic {
+            requested_service: data.requested_service,
+            bucket: data.bucket,
+            request_processed_bytes: data.request_processed_bytes as u64,
+            response_processed_bytes: data.response_processed_bytes as u64,
+        })
+        .collect())
+}
+
+#[rocket::post("/all-statistics", data = "<all_statistics_request>")]
+pub(crate) async fn post_all_statistics(
+    all_statistics_request: Json<ServiceStatisticsRequest>,
+    storage: &State<NetworkStatisticsStorage>,
+) -> Result<Json<Vec<ServiceStatistic>>> {
+    Ok(Json(
+        get_all_statistics(&all_statistics_request, storage).await?,
+    ))
+}
+
+#[rocket::post("/all-statistics/csv", data = "<all_statistics_request>")]
+pub(crate) async fn post_all_statistics_csv(
+    all_statistics_request: Json<ServiceStatisticsRequest>,
+    storage: &State<NetworkStatisticsStorage>,
+) -> Result<(ContentType, String)> {
+    let statistics = get_all_statistics(&all_statistics_request, storage).await?;
+    Ok((ContentType::CSV, all_statistics_csv(&statistics)))
+}
+
+#[rocket::post("/aggregated-statistics", data = "<aggregated_statistics_request>")]
+pub(crate) async fn post_aggregated_statistics(
+    aggregated_statistics_request: Json<AggregatedStatisticsRequest>,
+    storage: &State<NetworkStatisticsStorage>,
+) -> Result<Json<Vec<AggregatedServiceStatistic>>> {
+    Ok(Json(
+        get_aggregated_statistics(&aggregated_statistics_request, storage).await?,
+    ))
+}
+
+#[rocket::post("/aggregated-statistics/csv", data = "<aggregated_statistics_request>")]
+pub(crate) async fn post_aggregated_statistics_csv(
+    aggregated_statistics_request: Json<AggregatedStatisticsRequest>,
+    storage: &State<NetworkStatisticsStorage>,
+) -> Result<(ContentType, String)> {
+    let statistics = get_aggregated_statistics(&aggregated_statistics_request, storage).await?;
+    Ok((ContentType::CSV, aggregated_statistics_csv(&statistics)))
+}
+
+#[rocket::post("/top-services", data = "<top_services_request>")]
+pub(crate) async fn post_top_services(
+    top_services_request: Json<TopServicesRequest>,
+    storage: &State<NetworkStatisticsStorage>,
+) -> Result<Json<Vec<ServiceTotal>>> {
+    let top_services = storage
+        .get_top_services(
+            &top_services_request.since,
+            &top_services_request.until,
+            top_services_request.limit,
+        )
+        .await?
+        .into_iter()
+        .map(|data| ServiceTotal {
+            requested_service: data.requested_service,
+            request_processed_bytes: data.request_processed_bytes as u64,
+            response_processed_bytes: data.response_processed_bytes as u64,
+        })
         .collect();
 
-    Ok(Json(service_statistics))
+    Ok(Json(top_services))
+}
+
+#[rocket::post("/totals", data = "<totals_request>")]
+pub(crate) async fn post_totals(
+    totals_request: Json<IntervalTotalsRequest>,
+    storage: &State<NetworkStatisticsStorage>,
+) -> Result<Json<IntervalTotals>> {
+    let totals = storage
+        .get_interval_totals(&totals_request.since, &totals_request.until)
+        .await?;
+
+    Ok(Json(IntervalTotals {
+        services: totals.services as u64,
+        request_processed_bytes: totals.request_processed_bytes as u64,
+        response_processed_bytes: totals.response_processed_bytes as u64,
+    }))
+}
+
+async fn accept_statistic(
+    statistic: &SignedStatsMessage,
+    storage: &NetworkStatisticsStorage,
+    allowlist: &ReporterAllowlist,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    if !allowlist.is_allowed(&statistic.reporter) {
+        return Err(NetworkStatisticsAPIError::UnknownReporter(
+            statistic.reporter.clone(),
+        ));
+    }
+    let message = statistic.verify()?;
+    if !is_fresh(parse_timestamp(&message.timestamp)?, now) {
+        return Err(NetworkStatisticsAPIError::StaleSubmission(
+            message.timestamp,
+        ));
+    }
+    Ok(storage
+        .insert_service_statistics(&statistic.reporter, message)
+        .await?)
 }
 
 #[rocket::post("/statistic", data = "<statistic>")]
 pub(crate) async fn post_statistic(
-    statistic: Json<StatsMessage>,
+    statistic: std::result::Result<Json<SignedStatsMessage>, json::Error<'_>>,
     storage: &State<NetworkStatisticsStorage>,
+    allowlist: &State<ReporterAllowlist>,
+    rejected_submissions: &State<RejectedSubmissions>,
 ) -> Result<Json<()>> {
-    storage.insert_service_statistics(statistic.0).await?;
+    // body parse failures are handled here rather than by rocket, so that they get counted too
+    let statistic = match statistic {
+        Ok(statistic) => statistic,
+        Err(err) => {
+            let err = NetworkStatisticsAPIError::MalformedSubmission(err.to_string());
+            warn!("Rejected statistics submission - {}", err);
+            rejected_submissions.record(&err);
+            return Err(err);
+        }
+    };
+    if let Err(err) = accept_statistic(&statistic, storage, allowlist, Utc::now()).await {
+        warn!(
+            "Rejected statistics submitted by {} - {}",
+            statistic.reporter, err
+        );
+        rejected_submissions.record(&err);
+        return Err(err);
+    }
     Ok(Json(()))
 }
+
+#[rocket::get("/rejected-submissions")]
+pub(crate) async fn get_rejected_submissions(
+    rejected_submissions: &State<RejectedSubmissions>,
+) -> Json<RejectedSubmissionsCount> {
+    Json(rejected_submissions.count())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::error::NetworkStatisticsStorageError;
+    use crypto::asymmetric::identity;
+    use statistics::error::StatsError;
+    use statistics::{StatsMessage, StatsServiceData};
+
+    #[test]
+    fn csv_fields_with_separators_are_quoted() {
+        assert_eq!(csv_field("nymtech.net"), "nymtech.net");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+        assert_eq!(csv_field("a\nb"), "\"a\nb\"");
+    }
+
+    #[test]
+    fn all_statistics_csv_has_a_row_per_entry() {
+        let statistics = vec![
+            ServiceStatistic {
+                requested_service: "nymtech.net".to_string(),
+                request_processed_bytes: 10,
+                response_processed_bytes: 20,
+                interval_seconds: 60,
+                timestamp: "2022-08-01 12:00:00".to_string(),
+            },
+            ServiceStatistic {
+                requested_service: "a,b".to_string(),
+                request_processed_bytes: 1,
+                response_processed_bytes: 2,
+                interval_seconds: 60,
+                timestamp: "2022-08-01 12:01:00".to_string(),
+            },
+        ];
+
+        assert_eq!(
+            all_statistics_csv(&statistics),
+            "requested_service,request_processed_bytes,response_processed_bytes,interval_seconds,timestamp\n\
+             nymtech.net,10,20,60,2022-08-01 12:00:00\n\
+             \"a,b\",1,2,60,2022-08-01 12:01:00\n"
+        );
+    }
+
+    #[test]
+    fn aggregated_statistics_csv_has_a_row_per_bucket() {
+        let statistics = vec![AggregatedServiceStatistic {
+            requested_service: "nymtech.net".to_string(),
+            bucket: "2022-08-01".to_string(),
+            request_processed_bytes: 100,
+            response_processed_bytes: 200,
+        }];
+
+        assert_eq!(
+            aggregated_statistics_csv(&statistics),
+            "requested_service,bucket,request_processed_bytes,response_processed_bytes\n\
+             nymtech.net,2022-08-01,100,200\n"
+        );
+        assert_eq!(
+            aggregated_statistics_csv(&[]),
+            "requested_service,bucket,request_processed_bytes,response_processed_bytes\n"
+        );
+    }
+
+    async fn test_storage() -> (NetworkStatisticsStorage, std::path::PathBuf) {
+        let mut base_dir = std::env::temp_dir();
+        base_dir.push(format!("network-statistics-api-{}", std::process::id()));
+        base_dir.push(format!("{:?}", std::thread::current().id()));
+        let _ = std::fs::remove_dir_all(&base_dir);
+        let storage = NetworkStatisticsStorage::init(&base_dir).await.unwrap();
+        (storage, base_dir)
+    }
+
+    fn signed_statistic(
+        identity: &identity::KeyPair,
+        timestamp: DateTime<Utc>,
+    ) -> SignedStatsMessage {
+        StatsMessage {
+            stats_data: vec![StatsServiceData::new("nymtech.net".to_string(), 10, 100)],
+            interval_seconds: 60,
+            timestamp: timestamp.to_rfc3339(),
+        }
+        .sign(identity)
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn only_fresh_statistics_of_allowed_reporters_are_accepted_once() {
+        let (storage, base_dir) = test_storage().await;
+        let reporter = identity::KeyPair::new(&mut rand::rngs::OsRng);
+        let unknown = identity::KeyPair::new(&mut rand::rngs::OsRng);
+        let allowlist = ReporterAllowlist::new(
+            [reporter.public_key().to_base58_string()]
+                .into_iter()
+                .collect(),
+        );
+        let now = Utc::now();
+
+        let statistic = signed_statistic(&reporter, now);
+        accept_statistic(&statistic, &storage, &allowlist, now)
+            .await
+            .unwrap();
+        assert!(matches!(
+            accept_statistic(&statistic, &storage, &allowlist, now).await,
+            Err(NetworkStatisticsAPIError::StorageError(
+                NetworkStatisticsStorageError::DuplicateSubmission
+            ))
+        ));
+
+        assert!(matches!(
+            accept_statistic(&signed_statistic(&unknown, now), &storage, &allowlist, now).await,
+            Err(NetworkStatisticsAPIError::UnknownReporter(_))
+        ));
+
+        let mut tampered = signed_statistic(&reporter, now - chrono::Duration::seconds(60));
+        tampered.payload = tampered.payload.replace("100", "1000");
+        assert!(matches!(
+            accept_statistic(&tampered, &storage, &allowlist, now).await,
+            Err(NetworkStatisticsAPIError::InvalidSubmission(
+                StatsError::InvalidSignature
+            ))
+        ));
+
+        let stale = signed_statistic(&reporter, now - chrono::Duration::days(1));
+        assert!(matches!(
+            accept_statistic(&stale, &storage, &allowlist, now).await,
+            Err(NetworkStatisticsAPIError::StaleSubmission(_))
+        ));
+
+        std::fs::remove_dir_all(base_dir).unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use log::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use sqlx::types::chrono::{DateTime, Utc};
+use statistics::error::StatsError;
+
+use crate::api::error::NetworkStatisticsAPIError;
+use crate::storage::error::NetworkStatisticsStorageError;
+
+const ALLOWED_REPORTERS_FILE: &str = "allowed_reporters.list";
+
+/// Maximum difference between the timestamp of a submission and the time it's received at for
+/// it to get accepted. Submissions older than that are never going to be accepted again, so they
+/// don't have to be remembered for detecting the duplicate ones either.
+pub(crate) const SUBMISSION_FRESHNESS_WINDOW: Duration = Duration::from_secs(60 * 60);
+
+/// Checks whether the timestamp of a submission received at `now` is within the freshness window.
+pub(crate) fn is_fresh(timestamp: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    (now - timestamp).num_seconds().unsigned_abs() <= SUBMISSION_FRESHNESS_WINDOW.as_secs()
+}
+
+/// Identities of the reporters whose statistics are accepted.
+pub(crate) struct ReporterAllowlist {
+    reporters: HashSet<String>,
+}
+
+impl ReporterAllowlist {
+    /// Loads the base58 encoded identity keys of the allowed reporters, one per line, from the
+    /// allowlist file in the provided directory. The file is created if it doesn't exist yet.
+    pub(crate) fn load(base_dir: &Path) -> std::io::Result<Self> {
+        let allowlist_path = base_dir.join(ALLOWED_REPORTERS_FILE);
+        if !allowlist_path.exists() {
+            fs::write(&allowlist_path, "")?;
+        }
+
+        let reporters: HashSet<_> = fs::read_to_string(&allowlist_path)?
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(ToString::to_string)
+            .collect();
+
+        if reporters.is_empty() {
+            warn!(
+                "No reporters are allowed in {:?} - all statistics submissions are going to be rejected",
+                allowlist_path
+            );
+        } else {
+            info!("Accepting statistics from {} reporters", reporters.len());
+        }
+
+        Ok(ReporterAllowlist { reporters })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn new(reporters: HashSet<String>) -> Self {
+        ReporterAllowlist { reporters }
+    }
+
+    pub(crate) fn is_allowed(&self, reporter: &str) -> bool {
+        self.reporters.contains(reporter)
+    }
+}
+
+/// Numbers of the statistics submissions rejected since startup, by the reason of the rejection.
+#[derive(Default)]
+pub(crate) struct RejectedSubmissions {
+    unknown_reporter: AtomicU64,
+    invalid_signature: AtomicU64,
+    duplicate: AtomicU64,
+    stale: AtomicU64,
+    malformed: AtomicU64,
+}
+
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct RejectedSubmissionsCount {
+    pub unknown_reporter: u64,
+    pub invalid_signature: u64,
+    pub duplicate: u64,
+    pub stale: u64,
+    pub malformed: u64,
+}
+
+impl RejectedSubmissions {
+    pub(crate) fn record(&self, err: &NetworkStatisticsAPIError) {
+        let counter = match err {
+            NetworkStatisticsAPIError::UnknownReporter(_) => &self.unknown_reporter,
+            NetworkStatisticsAPIError::InvalidSubmission(StatsError::InvalidSignature) => {
+                &self.invalid_signature
+            }
+            // malformed reporter keys and signatures or undecodable payloads
+            NetworkStatisticsAPIError::InvalidSubmission(_)
+            | NetworkStatisticsAPIError::MalformedSubmission(_) => &self.malformed,
+            NetworkStatisticsAPIError::StorageError(
+                NetworkStatisticsStorageError::DuplicateSubmission,
+            ) => &self.duplicate,
+            NetworkStatisticsAPIError::StaleSubmission(_) => &self.stale,
+            NetworkStatisticsAPIError::StorageError(
+                NetworkStatisticsStorageError::TimestampParse,
+            ) => &self.malformed,
+            // the submission itself was fine, we failed to process it
+            _ => return,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn count(&self) -> RejectedSubmissionsCount {
+        RejectedSubmissionsCount {
+            unknown_reporter: self.unknown_reporter.load(Ordering::Relaxed),
+            invalid_signature: self.invalid_signature.load(Ordering::Relaxed),
+            duplicate: self.duplicate.load(Ordering::Relaxed),
+            stale: self.stale.load(Ordering::Relaxed),
+            malformed: self.malformed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::types::chrono::TimeZone;
+    use statistics::{SignedStatsMessage, StatsMessage};
+
+    #[test]
+    fn only_timestamps_within_the_window_are_fresh() {
+        let now = Utc.ymd(2022, 8, 5).and_hms(12, 0, 0);
+        let window = chrono::Duration::from_std(SUBMISSION_FRESHNESS_WINDOW).unwrap();
+
+        assert!(is_fresh(now, now));
+        assert!(is_fresh(now - window, now));
+        assert!(is_fresh(now + window, now));
+        assert!(!is_fresh(now - window - chrono::Duration::seconds(1), now));
+        assert!(!is_fresh(now + window + chrono::Duration::seconds(1), now));
+    }
+
+    #[test]
+    fn rejections_are_counted_by_their_reason() {
+        let rejected = RejectedSubmissions::default();
+        let malformed_key = SignedStatsMessage {
+            reporter: "not a key".to_string(),
+            payload: String::new(),
+            signature: String::new(),
+        }
+        .verify()
+        .unwrap_err();
+        let malformed_json = StatsMessage::from_json("{").unwrap_err();
+
+        for err in [
+            NetworkStatisticsAPIError::UnknownReporter("reporter".to_string()),
+            NetworkStatisticsAPIError::InvalidSubmission(StatsError::InvalidSignature),
+            NetworkStatisticsAPIError::InvalidSubmission(malformed_key),
+            NetworkStatisticsAPIError::InvalidSubmission(malformed_json),
+            NetworkStatisticsAPIError::MalformedSubmission("EOF".to_string()),
+            NetworkStatisticsAPIError::StorageError(NetworkStatisticsStorageError::TimestampParse),
+            NetworkStatisticsAPIError::StorageError(
+                NetworkStatisticsStorageError::DuplicateSubmission,
+            ),
+            NetworkStatisticsAPIError::StaleSubmission("2022-08-05T12:00:00+00:00".to_string()),
+            // failures of the service itself are not the submitter's fault
+            NetworkStatisticsAPIError::StorageError(
+                NetworkStatisticsStorageError::InternalDatabaseError(sqlx::Error::PoolTimedOut),
+            ),
+        ] {
+            rejected.record(&err);
+        }
+
+        let count = rejected.count();
+        assert_eq!(count.unknown_reporter, 1);
+        assert_eq!(count.invalid_signature, 1);
+        assert_eq!(count.duplicate, 1);
+        assert_eq!(count.stale, 1);
+        assert_eq!(count.malformed, 4);
+    }
+}
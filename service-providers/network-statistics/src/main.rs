@@ -3,9 +3,12 @@
 
 use std::path::PathBuf;
 
+use api::submissions::ReporterAllowlist;
 use api::NetworkStatisticsAPI;
+use retention::RetentionTask;
 
 mod api;
+mod retention;
 mod storage;
 
 #[tokio::main]
@@ -17,7 +20,12 @@ async fn main() {
         .await
         .expect("Could not create network statistics storage");
 
-    let api = NetworkStatisticsAPI::init(storage)
+    RetentionTask::new(storage.clone()).start();
+
+    let allowlist =
+        ReporterAllowlist::load(&base_dir).expect("Could not load the allowed reporters");
+
+    let api = NetworkStatisticsAPI::init(storage, allowlist)
         .await
         .expect("Could not ignite stats api service");
     api.run().await;
@@ -0,0 +1,66 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use chrono::Utc;
+use log::*;
+use std::time::Duration;
+
+use crate::api::submissions::SUBMISSION_FRESHNESS_WINDOW;
+use crate::storage::NetworkStatisticsStorage;
+
+// how often the raw statistics are checked for being old enough to get rolled up
+const RETENTION_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+// number of full days for which the raw statistics are kept before being rolled up into daily summaries
+const RAW_STATISTICS_RETENTION_DAYS: i64 = 7;
+
+pub(crate) struct RetentionTask {
+    storage: NetworkStatisticsStorage,
+}
+
+impl RetentionTask {
+    pub(crate) fn new(storage: NetworkStatisticsStorage) -> Self {
+        RetentionTask { storage }
+    }
+
+    async fn roll_up_old_statistics(&self) {
+        // only roll up whole days so that each daily summary is created at once
+        let cutoff = (Utc::now() - chrono::Duration::days(RAW_STATISTICS_RETENTION_DAYS))
+            .date()
+            .and_hms(0, 0, 0);
+
+        match self.storage.roll_up_service_statistics(cutoff).await {
+            Ok(0) => trace!("No statistics older than {} to roll up", cutoff),
+            Ok(rolled_up) => info!(
+                "Rolled up {} statistics entries older than {} into daily summaries",
+                rolled_up, cutoff
+            ),
+            Err(e) => error!("Failed to roll up statistics older than {} - {}", cutoff, e),
+        }
+    }
+
+    async fn forget_old_submissions(&self) {
+        // submissions outside of the freshness window get rejected regardless of being duplicates
+        let cutoff = Utc::now()
+            - chrono::Duration::from_std(SUBMISSION_FRESHNESS_WINDOW)
+                .expect("the freshness window is within the chrono bounds");
+
+        match self.storage.remove_statistics_submissions(cutoff).await {
+            Ok(removed) => trace!("Forgot {} submissions older than {}", removed, cutoff),
+            Err(e) => error!("Failed to forget submissions older than {} - {}", cutoff, e),
+        }
+    }
+
+    pub(crate) fn start(self) {
+        info!("Spawning statistics retention task runner...");
+        tokio::spawn(async move {
+            let mut interval_timer = tokio::time::interval(RETENTION_CHECK_INTERVAL);
+            loop {
+                // wait for the next interval tick
+                interval_timer.tick().await;
+                self.roll_up_old_statistics().await;
+                self.forget_old_submissions().await;
+            }
+        });
+    }
+}
@@ -14,4 +14,7 @@ pub enum NetworkStatisticsStorageError {
 
     #[error("Timestamp could not be parsed")]
     TimestampParse,
+
+    #[error("Statistics with the same timestamp have already been submitted by this reporter")]
+    DuplicateSubmission,
 }
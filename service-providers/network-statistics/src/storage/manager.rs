@@ -3,7 +3,11 @@
 
 use sqlx::types::chrono::{DateTime, Utc};
 
-use crate::storage::models::ServiceStatistics;
+use statistics::StatsServiceData;
+
+use crate::storage::models::{
+    AggregatedServiceStatistics, IntervalTotals, ServiceStatistics, ServiceTotals,
+};
 
 #[derive(Clone)]
 pub(crate) struct StorageManager {
@@ -12,34 +16,51 @@ pub(crate) struct StorageManager {
 
 // all SQL goes here
 impl StorageManager {
-    /// Adds an entry for some statistical data.
+    /// Adds entries for all the statistical data of a submission, unless the reporter has
+    /// already submitted data with the same timestamp. Returns whether the data got inserted.
     ///
     /// # Arguments
     ///
-    /// * `requested_service`: Address of the service requested.
-    /// * `request_processed_bytes`: Number of bytes for socks5 requests.
-    /// * `response_processed_bytes`: Number of bytes for socks5 responses.
+    /// * `reporter`: Base58 encoded identity key of the reporter.
     /// * `interval_seconds`: Duration in seconds in which the data was gathered.
-    pub(super) async fn insert_service_statistics(
+    /// * `timestamp`: Timestamp of the submission.
+    /// * `stats_data`: Number of bytes processed for socks5 requests and responses per service.
+    pub(super) async fn insert_statistics_submission(
         &self,
-        requested_service: String,
-        request_processed_bytes: u32,
-        response_processed_bytes: u32,
+        reporter: &str,
         interval_seconds: u32,
         timestamp: DateTime<Utc>,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query!(
-            "INSERT INTO service_statistics(requested_service, request_processed_bytes, response_processed_bytes, interval_seconds, timestamp) VALUES (?, ?, ?, ?, ?)",
-            requested_service,
-            request_processed_bytes,
-            response_processed_bytes,
-            interval_seconds,
+        stats_data: Vec<StatsServiceData>,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.connection_pool.begin().await?;
+
+        let inserted = sqlx::query!(
+            "INSERT OR IGNORE INTO statistics_submissions(reporter, timestamp) VALUES (?, ?)",
+            reporter,
             timestamp,
         )
-        .execute(&self.connection_pool)
-        .await?;
+        .execute(&mut tx)
+        .await?
+        .rows_affected();
+        if inserted == 0 {
+            return Ok(false);
+        }
 
-        Ok(())
+        for service_data in stats_data {
+            sqlx::query!(
+                "INSERT INTO service_statistics(requested_service, request_processed_bytes, response_processed_bytes, interval_seconds, timestamp) VALUES (?, ?, ?, ?, ?)",
+                service_data.requested_service,
+                service_data.request_bytes,
+                service_data.response_bytes,
+                interval_seconds,
+                timestamp,
+            )
+            .execute(&mut tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(true)
     }
 
     /// Returns data submitted within the provided time interval.
@@ -48,18 +69,209 @@ impl StorageManager {
     ///
     /// * `since`: indicates the lower bound timestamp for the data
     /// * `until`: indicates the upper bound timestamp for the data
+    /// * `limit`: maximum number of entries to return, negative value means no limit
+    /// * `offset`: number of entries to skip
     pub(super) async fn get_service_statistics_in_interval(
         &self,
         since: DateTime<Utc>,
         until: DateTime<Utc>,
+        limit: i64,
+        offset: i64,
     ) -> Result<Vec<ServiceStatistics>, sqlx::Error> {
         sqlx::query_as!(
             ServiceStatistics,
-            "SELECT * FROM service_statistics WHERE timestamp BETWEEN ? AND ?",
+            "SELECT * FROM service_statistics WHERE timestamp BETWEEN ? AND ? ORDER BY timestamp, id LIMIT ? OFFSET ?",
             since,
-            until
+            until,
+            limit,
+            offset
         )
         .fetch_all(&self.connection_pool)
         .await
     }
+
+    /// Returns the number of bytes processed for each service within the provided time interval,
+    /// grouped into buckets. Data that got already rolled up into daily summaries is assigned to
+    /// the bucket containing the start of its day and, as it can't be split any further,
+    /// only included for the days entirely covered by the interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `since`: indicates the lower bound timestamp for the data
+    /// * `until`: indicates the upper bound timestamp for the data
+    /// * `bucket_format`: `strftime` format string determining the bucket of each entry
+    pub(super) async fn get_aggregated_service_statistics(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        bucket_format: &str,
+    ) -> Result<Vec<AggregatedServiceStatistics>, sqlx::Error> {
+        sqlx::query_as!(
+            AggregatedServiceStatistics,
+            r#"
+                SELECT
+                    requested_service as "requested_service!",
+                    bucket as "bucket!: String",
+                    SUM(request_processed_bytes) as "request_processed_bytes!: i64",
+                    SUM(response_processed_bytes) as "response_processed_bytes!: i64"
+                FROM (
+                    SELECT requested_service, strftime(?, timestamp) as bucket, request_processed_bytes, response_processed_bytes
+                    FROM service_statistics
+                    WHERE timestamp BETWEEN ? AND ?
+                    UNION ALL
+                    SELECT requested_service, strftime(?, day) as bucket, request_processed_bytes, response_processed_bytes
+                    FROM daily_service_statistics
+                    WHERE datetime(day) >= ? AND datetime(day, '+1 day', '-1 second') <= ?
+                )
+                GROUP BY requested_service, bucket
+                ORDER BY bucket, requested_service
+            "#,
+            bucket_format,
+            since,
+            until,
+            bucket_format,
+            since,
+            until,
+        )
+        .fetch_all(&self.connection_pool)
+        .await
+    }
+
+    /// Returns the services with the highest number of bytes processed within the provided time
+    /// interval. Daily summaries are only included for the days entirely covered by the interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `since`: indicates the lower bound timestamp for the data
+    /// * `until`: indicates the upper bound timestamp for the data
+    /// * `limit`: maximum number of services to return
+    pub(super) async fn get_top_services(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        limit: i64,
+    ) -> Result<Vec<ServiceTotals>, sqlx::Error> {
+        sqlx::query_as!(
+            ServiceTotals,
+            r#"
+                SELECT
+                    requested_service as "requested_service!",
+                    SUM(request_processed_bytes) as "request_processed_bytes!: i64",
+                    SUM(response_processed_bytes) as "response_processed_bytes!: i64"
+                FROM (
+                    SELECT requested_service, request_processed_bytes, response_processed_bytes
+                    FROM service_statistics
+                    WHERE timestamp BETWEEN ? AND ?
+                    UNION ALL
+                    SELECT requested_service, request_processed_bytes, response_processed_bytes
+                    FROM daily_service_statistics
+                    WHERE datetime(day) >= ? AND datetime(day, '+1 day', '-1 second') <= ?
+                )
+                GROUP BY requested_service
+                ORDER BY SUM(request_processed_bytes) + SUM(response_processed_bytes) DESC
+                LIMIT ?
+            "#,
+            since,
+            until,
+            since,
+            until,
+            limit,
+        )
+        .fetch_all(&self.connection_pool)
+        .await
+    }
+
+    /// Returns the total number of bytes processed, across all services, within the provided time
+    /// interval. Daily summaries are only included for the days entirely covered by the interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `since`: indicates the lower bound timestamp for the data
+    /// * `until`: indicates the upper bound timestamp for the data
+    pub(super) async fn get_interval_totals(
+        &self,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> Result<IntervalTotals, sqlx::Error> {
+        sqlx::query_as!(
+            IntervalTotals,
+            r#"
+                SELECT
+                    COUNT(DISTINCT requested_service) as "services!: i64",
+                    COALESCE(SUM(request_processed_bytes), 0) as "request_processed_bytes!: i64",
+                    COALESCE(SUM(response_processed_bytes), 0) as "response_processed_bytes!: i64"
+                FROM (
+                    SELECT requested_service, request_processed_bytes, response_processed_bytes
+                    FROM service_statistics
+                    WHERE timestamp BETWEEN ? AND ?
+                    UNION ALL
+                    SELECT requested_service, request_processed_bytes, response_processed_bytes
+                    FROM daily_service_statistics
+                    WHERE datetime(day) >= ? AND datetime(day, '+1 day', '-1 second') <= ?
+                )
+            "#,
+            since,
+            until,
+            since,
+            until,
+        )
+        .fetch_one(&self.connection_pool)
+        .await
+    }
+
+    /// Rolls up all the raw statistics submitted before the provided timestamp into daily
+    /// summaries and removes the raw entries.
+    ///
+    /// # Arguments
+    ///
+    /// * `before`: indicates the upper bound (exclusive) timestamp of the data to roll up
+    pub(super) async fn roll_up_service_statistics(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        let mut tx = self.connection_pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+                INSERT INTO daily_service_statistics(requested_service, day, request_processed_bytes, response_processed_bytes, entries)
+                SELECT requested_service, date(timestamp), SUM(request_processed_bytes), SUM(response_processed_bytes), COUNT(*)
+                FROM service_statistics
+                WHERE timestamp < ?
+                GROUP BY requested_service, date(timestamp)
+                ON CONFLICT(requested_service, day) DO UPDATE SET
+                    request_processed_bytes = request_processed_bytes + excluded.request_processed_bytes,
+                    response_processed_bytes = response_processed_bytes + excluded.response_processed_bytes,
+                    entries = entries + excluded.entries
+            "#,
+            before
+        )
+        .execute(&mut tx)
+        .await?;
+
+        let removed = sqlx::query!("DELETE FROM service_statistics WHERE timestamp < ?", before)
+            .execute(&mut tx)
+            .await?
+            .rows_affected();
+
+        tx.commit().await?;
+        Ok(removed)
+    }
+
+    /// Removes the submissions with timestamps before the provided one.
+    ///
+    /// # Arguments
+    ///
+    /// * `before`: indicates the upper bound (exclusive) timestamp of the submissions to remove
+    pub(super) async fn remove_statistics_submissions(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, sqlx::Error> {
+        Ok(sqlx::query!(
+            "DELETE FROM statistics_submissions WHERE timestamp < ?",
+            before
+        )
+        .execute(&self.connection_pool)
+        .await?
+        .rows_affected())
+    }
 }
@@ -10,11 +10,21 @@ use statistics::StatsMessage;
 
 use crate::storage::error::NetworkStatisticsStorageError;
 use crate::storage::manager::StorageManager;
-use crate::storage::models::ServiceStatistics;
+use crate::storage::models::{
+    AggregatedServiceStatistics, Granularity, IntervalTotals, ServiceStatistics, ServiceTotals,
+};
 
 pub(crate) mod error;
 mod manager;
-mod models;
+pub(crate) mod models;
+
+pub(crate) fn parse_timestamp(
+    timestamp: &str,
+) -> Result<DateTime<Utc>, NetworkStatisticsStorageError> {
+    Ok(DateTime::parse_from_rfc3339(timestamp)
+        .map_err(|_| NetworkStatisticsStorageError::TimestampParse)?
+        .into())
+}
 
 // note that clone here is fine as upon cloning the same underlying pool will be used
 #[derive(Clone)]
@@ -44,31 +54,28 @@ impl NetworkStatisticsStorage {
         Ok(storage)
     }
 
-    /// Adds an entry for some statistical data.
+    /// Adds entries for the statistical data submitted by the particular reporter.
     ///
     /// # Arguments
     ///
+    /// * `reporter`: Base58 encoded identity key of the reporter.
     /// * `msg`: Message containing the statistical data.
     pub(super) async fn insert_service_statistics(
         &self,
+        reporter: &str,
         msg: StatsMessage,
     ) -> Result<(), NetworkStatisticsStorageError> {
-        let timestamp: DateTime<Utc> = DateTime::parse_from_rfc3339(&msg.timestamp)
-            .map_err(|_| NetworkStatisticsStorageError::TimestampParse)?
-            .into();
-        for service_data in msg.stats_data {
-            self.manager
-                .insert_service_statistics(
-                    service_data.requested_service.clone(),
-                    service_data.request_bytes,
-                    service_data.response_bytes,
-                    msg.interval_seconds,
-                    timestamp,
-                )
-                .await?;
-        }
+        let timestamp = parse_timestamp(&msg.timestamp)?;
+        let inserted = self
+            .manager
+            .insert_statistics_submission(reporter, msg.interval_seconds, timestamp, msg.stats_data)
+            .await?;
 
-        Ok(())
+        if inserted {
+            Ok(())
+        } else {
+            Err(NetworkStatisticsStorageError::DuplicateSubmission)
+        }
     }
 
     /// Returns data submitted within the provided time interval.
@@ -77,20 +84,389 @@ impl NetworkStatisticsStorage {
     ///
     /// * `since`: indicates the lower bound timestamp for the data, RFC 3339 format
     /// * `until`: indicates the upper bound timestamp for the data, RFC 3339 format
+    /// * `limit`: optional maximum number of entries to return
+    /// * `offset`: optional number of entries to skip
     pub(super) async fn get_service_statistics_in_interval(
         &self,
         since: &str,
         until: &str,
+        limit: Option<u32>,
+        offset: Option<u32>,
     ) -> Result<Vec<ServiceStatistics>, NetworkStatisticsStorageError> {
-        let since = DateTime::parse_from_rfc3339(since)
-            .map_err(|_| NetworkStatisticsStorageError::TimestampParse)?
-            .into();
-        let until = DateTime::parse_from_rfc3339(until)
-            .map_err(|_| NetworkStatisticsStorageError::TimestampParse)?
-            .into();
         Ok(self
             .manager
-            .get_service_statistics_in_interval(since, until)
+            .get_service_statistics_in_interval(
+                parse_timestamp(since)?,
+                parse_timestamp(until)?,
+                limit.map(i64::from).unwrap_or(-1),
+                offset.map(i64::from).unwrap_or_default(),
+            )
+            .await?)
+    }
+
+    /// Returns the number of bytes processed for each service within the provided time interval,
+    /// grouped into buckets of the specified granularity.
+    ///
+    /// # Arguments
+    ///
+    /// * `since`: indicates the lower bound timestamp for the data, RFC 3339 format
+    /// * `until`: indicates the upper bound timestamp for the data, RFC 3339 format
+    /// * `granularity`: size of the buckets
+    pub(super) async fn get_aggregated_service_statistics(
+        &self,
+        since: &str,
+        until: &str,
+        granularity: Granularity,
+    ) -> Result<Vec<AggregatedServiceStatistics>, NetworkStatisticsStorageError> {
+        Ok(self
+            .manager
+            .get_aggregated_service_statistics(
+                parse_timestamp(since)?,
+                parse_timestamp(until)?,
+                granularity.bucket_format(),
+            )
+            .await?)
+    }
+
+    /// Returns the services with the highest number of bytes processed within the provided time
+    /// interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `since`: indicates the lower bound timestamp for the data, RFC 3339 format
+    /// * `until`: indicates the upper bound timestamp for the data, RFC 3339 format
+    /// * `limit`: maximum number of services to return
+    pub(super) async fn get_top_services(
+        &self,
+        since: &str,
+        until: &str,
+        limit: u32,
+    ) -> Result<Vec<ServiceTotals>, NetworkStatisticsStorageError> {
+        Ok(self
+            .manager
+            .get_top_services(
+                parse_timestamp(since)?,
+                parse_timestamp(until)?,
+                limit.into(),
+            )
             .await?)
     }
+
+    /// Returns the total number of bytes processed within the provided time interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `since`: indicates the lower bound timestamp for the data, RFC 3339 format
+    /// * `until`: indicates the upper bound timestamp for the data, RFC 3339 format
+    pub(super) async fn get_interval_totals(
+        &self,
+        since: &str,
+        until: &str,
+    ) -> Result<IntervalTotals, NetworkStatisticsStorageError> {
+        Ok(self
+            .manager
+            .get_interval_totals(parse_timestamp(since)?, parse_timestamp(until)?)
+            .await?)
+    }
+
+    /// Rolls up the raw statistics submitted before the provided timestamp into daily summaries.
+    /// Returns the number of raw entries that got rolled up.
+    ///
+    /// # Arguments
+    ///
+    /// * `before`: indicates the upper bound (exclusive) timestamp of the data to roll up
+    pub(crate) async fn roll_up_service_statistics(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, NetworkStatisticsStorageError> {
+        Ok(self.manager.roll_up_service_statistics(before).await?)
+    }
+
+    /// Forgets the submissions with timestamps before the provided one, which are no longer
+    /// needed for detecting duplicates. Returns the number of forgotten submissions.
+    ///
+    /// # Arguments
+    ///
+    /// * `before`: indicates the upper bound (exclusive) timestamp of the submissions to forget
+    pub(crate) async fn remove_statistics_submissions(
+        &self,
+        before: DateTime<Utc>,
+    ) -> Result<u64, NetworkStatisticsStorageError> {
+        Ok(self.manager.remove_statistics_submissions(before).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::types::chrono::TimeZone;
+    use statistics::StatsServiceData;
+
+    async fn test_storage() -> (NetworkStatisticsStorage, PathBuf) {
+        let mut base_dir = std::env::temp_dir();
+        base_dir.push(format!("network-statistics-{}", std::process::id()));
+        base_dir.push(format!("{:?}", std::thread::current().id()));
+        let _ = std::fs::remove_dir_all(&base_dir);
+        let storage = NetworkStatisticsStorage::init(&base_dir).await.unwrap();
+        (storage, base_dir)
+    }
+
+    fn timestamp(day: u32, hour: u32) -> DateTime<Utc> {
+        Utc.ymd(2022, 8, day).and_hms(hour, 0, 0)
+    }
+
+    fn rfc3339(day: u32, hour: u32) -> String {
+        timestamp(day, hour).to_rfc3339()
+    }
+
+    async fn submit(
+        storage: &NetworkStatisticsStorage,
+        reporter: &str,
+        at: DateTime<Utc>,
+        data: &[(&str, u32, u32)],
+    ) {
+        let msg = StatsMessage {
+            stats_data: data
+                .iter()
+                .map(|(service, request, response)| {
+                    StatsServiceData::new(service.to_string(), *request, *response)
+                })
+                .collect(),
+            interval_seconds: 60,
+            timestamp: at.to_rfc3339(),
+        };
+        storage
+            .insert_service_statistics(reporter, msg)
+            .await
+            .unwrap();
+    }
+
+    // 2nd of August gets rolled up, while the 3rd stays raw
+    async fn storage_with_rolled_up_day() -> (NetworkStatisticsStorage, PathBuf) {
+        let (storage, base_dir) = test_storage().await;
+        submit(
+            &storage,
+            "reporter",
+            timestamp(2, 10),
+            &[("a.com", 10, 100)],
+        )
+        .await;
+        submit(
+            &storage,
+            "reporter",
+            timestamp(2, 20),
+            &[("a.com", 5, 50), ("b.com", 1, 1)],
+        )
+        .await;
+        submit(
+            &storage,
+            "reporter",
+            timestamp(3, 10),
+            &[("a.com", 1, 1), ("b.com", 1000, 1000)],
+        )
+        .await;
+
+        let rolled_up = storage
+            .roll_up_service_statistics(timestamp(3, 0))
+            .await
+            .unwrap();
+        assert_eq!(rolled_up, 3);
+        (storage, base_dir)
+    }
+
+    #[tokio::test]
+    async fn duplicate_submissions_are_rejected() {
+        let (storage, base_dir) = test_storage().await;
+        submit(&storage, "reporter", timestamp(2, 10), &[("a.com", 1, 1)]).await;
+
+        let msg = StatsMessage {
+            stats_data: vec![StatsServiceData::new("a.com".to_string(), 1, 1)],
+            interval_seconds: 60,
+            timestamp: rfc3339(2, 10),
+        };
+        assert!(matches!(
+            storage
+                .insert_service_statistics("reporter", msg.clone())
+                .await,
+            Err(NetworkStatisticsStorageError::DuplicateSubmission)
+        ));
+        // but the same timestamp is fine for a different reporter
+        storage
+            .insert_service_statistics("other reporter", msg)
+            .await
+            .unwrap();
+
+        std::fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn old_submissions_are_forgotten() {
+        let (storage, base_dir) = test_storage().await;
+        submit(&storage, "reporter", timestamp(2, 10), &[("a.com", 1, 1)]).await;
+        submit(&storage, "reporter", timestamp(2, 12), &[("a.com", 1, 1)]).await;
+
+        let removed = storage
+            .remove_statistics_submissions(timestamp(2, 11))
+            .await
+            .unwrap();
+        assert_eq!(removed, 1);
+
+        // the forgotten submission is no longer recognized as a duplicate, unlike the kept one
+        submit(&storage, "reporter", timestamp(2, 10), &[("a.com", 1, 1)]).await;
+        let msg = StatsMessage {
+            stats_data: vec![],
+            interval_seconds: 60,
+            timestamp: rfc3339(2, 12),
+        };
+        assert!(matches!(
+            storage.insert_service_statistics("reporter", msg).await,
+            Err(NetworkStatisticsStorageError::DuplicateSubmission)
+        ));
+
+        std::fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn rolled_up_statistics_are_replaced_with_daily_summaries() {
+        let (storage, base_dir) = storage_with_rolled_up_day().await;
+
+        // only the raw entries are left
+        let raw = storage
+            .get_service_statistics_in_interval(&rfc3339(1, 0), &rfc3339(4, 0), None, None)
+            .await
+            .unwrap();
+        assert_eq!(raw.len(), 2);
+        assert!(raw
+            .iter()
+            .all(|entry| entry.timestamp == timestamp(3, 10).naive_utc()));
+
+        // rolling up again doesn't change anything
+        assert_eq!(
+            storage
+                .roll_up_service_statistics(timestamp(3, 0))
+                .await
+                .unwrap(),
+            0
+        );
+
+        let daily = storage
+            .get_aggregated_service_statistics(&rfc3339(2, 0), &rfc3339(3, 0), Granularity::Day)
+            .await
+            .unwrap();
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].requested_service, "a.com");
+        assert_eq!(daily[0].bucket, "2022-08-02");
+        assert_eq!(daily[0].request_processed_bytes, 15);
+        assert_eq!(daily[0].response_processed_bytes, 150);
+        assert_eq!(daily[1].requested_service, "b.com");
+        assert_eq!(daily[1].request_processed_bytes, 1);
+
+        std::fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn aggregation_spans_the_roll_up_boundary() {
+        let (storage, base_dir) = storage_with_rolled_up_day().await;
+
+        let hourly = storage
+            .get_aggregated_service_statistics(&rfc3339(2, 0), &rfc3339(4, 0), Granularity::Hour)
+            .await
+            .unwrap();
+        let buckets = hourly
+            .iter()
+            .map(|entry| {
+                (
+                    entry.requested_service.as_str(),
+                    entry.bucket.as_str(),
+                    entry.request_processed_bytes,
+                )
+            })
+            .collect::<Vec<_>>();
+        // rolled up data can no longer be split into hours
+        assert_eq!(
+            buckets,
+            vec![
+                ("a.com", "2022-08-02T00:00:00Z", 15),
+                ("b.com", "2022-08-02T00:00:00Z", 1),
+                ("a.com", "2022-08-03T10:00:00Z", 1),
+                ("b.com", "2022-08-03T10:00:00Z", 1000),
+            ]
+        );
+
+        let totals = storage
+            .get_interval_totals(&rfc3339(2, 0), &rfc3339(4, 0))
+            .await
+            .unwrap();
+        assert_eq!(totals.services, 2);
+        assert_eq!(totals.request_processed_bytes, 1017);
+        assert_eq!(totals.response_processed_bytes, 1152);
+
+        std::fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn partially_covered_rolled_up_days_are_excluded() {
+        let (storage, base_dir) = storage_with_rolled_up_day().await;
+
+        // the interval starts in the middle of the rolled up day
+        let totals = storage
+            .get_interval_totals(&rfc3339(2, 12), &rfc3339(4, 0))
+            .await
+            .unwrap();
+        assert_eq!(totals.request_processed_bytes, 1001);
+
+        // the interval ends in the middle of the rolled up day
+        let totals = storage
+            .get_interval_totals(&rfc3339(1, 0), &rfc3339(2, 12))
+            .await
+            .unwrap();
+        assert_eq!(totals.services, 0);
+        assert_eq!(totals.request_processed_bytes, 0);
+
+        // the last second of the day is enough to cover it
+        let totals = storage
+            .get_interval_totals(&rfc3339(2, 0), "2022-08-02T23:59:59+00:00")
+            .await
+            .unwrap();
+        assert_eq!(totals.request_processed_bytes, 16);
+
+        std::fs::remove_dir_all(base_dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn top_services_include_rolled_up_statistics() {
+        let (storage, base_dir) = storage_with_rolled_up_day().await;
+
+        let top = storage
+            .get_top_services(&rfc3339(2, 0), &rfc3339(4, 0), 10)
+            .await
+            .unwrap();
+        let services = top
+            .iter()
+            .map(|entry| {
+                (
+                    entry.requested_service.as_str(),
+                    entry.request_processed_bytes + entry.response_processed_bytes,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(services, vec![("b.com", 2002), ("a.com", 167)]);
+
+        let top = storage
+            .get_top_services(&rfc3339(2, 0), &rfc3339(4, 0), 1)
+            .await
+            .unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].requested_service, "b.com");
+
+        // without the rolled up day, only the raw data counts
+        let top = storage
+            .get_top_services(&rfc3339(3, 0), &rfc3339(4, 0), 10)
+            .await
+            .unwrap();
+        assert_eq!(top[0].requested_service, "b.com");
+        assert_eq!(top[1].request_processed_bytes, 1);
+
+        std::fs::remove_dir_all(base_dir).unwrap();
+    }
 }
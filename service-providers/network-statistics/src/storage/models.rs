@@ -1,6 +1,7 @@
 // Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
 // SPDX-License-Identifier: Apache-2.0
 
+use serde::{Deserialize, Serialize};
 use sqlx::types::chrono::NaiveDateTime;
 
 // Internally used struct to catch results from the database to get mixnet statistics
@@ -13,3 +14,43 @@ pub(crate) struct ServiceStatistics {
     pub(crate) interval_seconds: i64,
     pub(crate) timestamp: NaiveDateTime,
 }
+
+// Internally used struct to catch results of the per service aggregation queries
+pub(crate) struct AggregatedServiceStatistics {
+    pub(crate) requested_service: String,
+    pub(crate) bucket: String,
+    pub(crate) request_processed_bytes: i64,
+    pub(crate) response_processed_bytes: i64,
+}
+
+// Internally used struct to catch results of the per service totals queries
+pub(crate) struct ServiceTotals {
+    pub(crate) requested_service: String,
+    pub(crate) request_processed_bytes: i64,
+    pub(crate) response_processed_bytes: i64,
+}
+
+// Internally used struct to catch results of the interval totals queries
+pub(crate) struct IntervalTotals {
+    pub(crate) services: i64,
+    pub(crate) request_processed_bytes: i64,
+    pub(crate) response_processed_bytes: i64,
+}
+
+/// Size of the time buckets the statistics get aggregated into.
+#[derive(Clone, Copy, Serialize, Deserialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Granularity {
+    Hour,
+    Day,
+}
+
+impl Granularity {
+    // format, as understood by sqlite's `strftime`, of the bucket the timestamp belongs to
+    pub(crate) fn bucket_format(&self) -> &'static str {
+        match self {
+            Granularity::Hour => "%Y-%m-%dT%H:00:00Z",
+            Granularity::Day => "%Y-%m-%d",
+        }
+    }
+}
@@ -1,2 +1,2 @@
 
-export interface Gateway { host: string, mix_port: number, clients_port: number, location: string, sphinx_key: string, identity_key: string, version: string, }
\ No newline at end of file
+export interface Gateway { host: string, mix_port: number, clients_port: number, clients_wss_port: number | null, location: string, sphinx_key: string, identity_key: string, version: string, }
\ No newline at end of file
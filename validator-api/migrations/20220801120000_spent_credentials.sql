@@ -0,0 +1,12 @@
+/*
+ * Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+ * SPDX-License-Identifier: Apache-2.0
+ */
+
+-- blinded serial numbers of credentials already redeemed at any of the gateways using this validator API
+CREATE TABLE spent_credential
+(
+    id                         INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    blinded_serial_number_bs58 VARCHAR NOT NULL UNIQUE,
+    timestamp                  INTEGER NOT NULL
+);
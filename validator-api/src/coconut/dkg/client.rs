@@ -0,0 +1,28 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::coconut::error::Result;
+use coconut_interface::{DkgDealingsResponse, DkgPublicKeyResponse};
+use url::Url;
+
+#[async_trait]
+pub trait Client {
+    async fn get_dkg_public_key(&self, signer: &Url) -> Result<DkgPublicKeyResponse>;
+    async fn get_dkg_dealings(&self, signer: &Url) -> Result<DkgDealingsResponse>;
+}
+
+/// Exchanges the DKG data with other signers via their validator APIs.
+pub(crate) struct ValidatorApiDkgClient;
+
+#[async_trait]
+impl Client for ValidatorApiDkgClient {
+    async fn get_dkg_public_key(&self, signer: &Url) -> Result<DkgPublicKeyResponse> {
+        let client = validator_client::ApiClient::new(signer.clone());
+        Ok(client.get_dkg_public_key().await?)
+    }
+
+    async fn get_dkg_dealings(&self, signer: &Url) -> Result<DkgDealingsResponse> {
+        let client = validator_client::ApiClient::new(signer.clone());
+        Ok(client.get_dkg_dealings().await?)
+    }
+}
@@ -0,0 +1,417 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::coconut::dkg::client::Client as DkgClient;
+use crate::coconut::dkg::persistence::PersistentState;
+use crate::coconut::dkg::DkgState;
+use crate::coconut::error::{CoconutError, Result};
+use crate::coconut::SigningKeyPair;
+use coconut_interface::error::CoconutInterfaceError;
+use coconut_interface::{Attribute, Base58, KeyPair, Parameters};
+use credentials::coconut::bandwidth::TOTAL_ATTRIBUTES;
+use dkg::bte::encryption::BabyStepGiantStepLookup;
+use dkg::bte::{decrypt_share, setup, Epoch, Params, PublicKey, PublicKeyWithProof};
+use dkg::{combine_shares, Dealing, NodeIndex, Threshold};
+use futures::future::join_all;
+use log::{debug, error, info, warn};
+use rand::rngs::OsRng;
+use std::collections::BTreeMap;
+use std::fs;
+use std::future::Future;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+use url::Url;
+
+// the keys are generated once, so there's no need for moving between epochs
+const DKG_EPOCH: u32 = 0;
+
+pub(crate) const DEFAULT_POLLING_RATE: Duration = Duration::from_secs(10);
+pub(crate) const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+// delays between subsequent attempts at the whole DKG, in case it failed
+const INITIAL_RETRY_BACKOFF: Duration = Duration::from_secs(60);
+const MAX_RETRY_BACKOFF: Duration = Duration::from_secs(60 * 60);
+
+/// Default threshold of signers required for issuing credentials, i.e. more than 2/3 of them.
+/// It has to match the threshold the clients and gateways aggregate the shares with.
+pub(crate) fn default_threshold(num_signers: usize) -> Threshold {
+    credentials::signing_threshold(num_signers)
+}
+
+/// Configurable parameters of the `DkgController`
+pub(crate) struct Config {
+    /// Ordered list of all signers taking part in the key generation (including this one).
+    signers: Vec<Url>,
+
+    /// Number of signers required for issuing credentials.
+    threshold: Threshold,
+
+    /// Path to the file containing the intermediate state of the key generation.
+    state_path: PathBuf,
+
+    /// Path to which the derived keypair is going to be saved.
+    keypair_path: PathBuf,
+
+    /// Delay between subsequent attempts at obtaining data from other signers.
+    polling_rate: Duration,
+
+    /// Maximum amount of time we're willing to wait for any other signer.
+    timeout: Duration,
+}
+
+impl Config {
+    pub(crate) fn new(
+        signers: Vec<Url>,
+        threshold: Threshold,
+        state_path: PathBuf,
+        keypair_path: PathBuf,
+        polling_rate: Duration,
+        timeout: Duration,
+    ) -> Self {
+        Config {
+            signers,
+            threshold,
+            state_path,
+            keypair_path,
+            polling_rate,
+            timeout,
+        }
+    }
+}
+
+/// Runs the distributed key generation of this signer and installs the derived keypair.
+pub(crate) struct DkgController<C> {
+    config: Config,
+    client: C,
+    params: Params,
+    epoch: Epoch,
+    persistent_state: PersistentState,
+    dkg_state: DkgState,
+    signing_key_pair: SigningKeyPair,
+}
+
+impl<C> DkgController<C>
+where
+    C: DkgClient + Send + Sync,
+{
+    pub(crate) fn new(
+        config: Config,
+        client: C,
+        signing_key_pair: SigningKeyPair,
+    ) -> Result<(Self, DkgState)> {
+        let params = setup();
+        let persistent_state = PersistentState::load_or_generate(&config.state_path, &params)?;
+        // if we have already created our dealings before a restart, keep serving the same ones
+        let dkg_state = DkgState::new(
+            persistent_state.public_key().to_owned(),
+            persistent_state.dealings().cloned(),
+        );
+
+        let controller = DkgController {
+            config,
+            client,
+            params,
+            epoch: Epoch::new(DKG_EPOCH),
+            persistent_state,
+            dkg_state: dkg_state.clone(),
+            signing_key_pair,
+        };
+
+        Ok((controller, dkg_state))
+    }
+
+    async fn poll_signer<'a, T, F, Fut>(&'a self, signer: &'a Url, query: F) -> Result<T>
+    where
+        F: Fn(&'a C, &'a Url) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let deadline = Instant::now() + self.config.timeout;
+        loop {
+            match query(&self.client, signer).await {
+                Ok(res) => return Ok(res),
+                Err(err) => {
+                    if Instant::now() >= deadline {
+                        return Err(CoconutError::DkgTimeout {
+                            signer: signer.to_string(),
+                        });
+                    }
+                    debug!("{} is not ready for the DKG yet - {}", signer, err);
+                    tokio::time::sleep(self.config.polling_rate).await;
+                }
+            }
+        }
+    }
+
+    fn parse_and_verify_public_key(
+        &self,
+        node_index: NodeIndex,
+        raw_public_key: &str,
+    ) -> Result<PublicKey> {
+        let bytes = bs58::decode(raw_public_key).into_vec()?;
+        let public_key = PublicKeyWithProof::try_from_bytes(&bytes)
+            .map_err(|_| CoconutError::InvalidDkgPublicKey { node_index })?;
+        if !public_key.verify() {
+            return Err(CoconutError::InvalidDkgPublicKey { node_index });
+        }
+        Ok(*public_key.public_key())
+    }
+
+    // signers are identified by their (1-based) position on the list, in the same way
+    // as when aggregating their verification keys.
+    //
+    // All signers are polled concurrently and the ones that did not respond within the timeout
+    // (or responded with an invalid key) are excluded from the DKG, as long as at least
+    // `threshold` of them are left. Note that this relies on all the signers observing
+    // the same set of responsive signers, as otherwise they would reject each other's dealings.
+    async fn collect_public_keys(&self) -> Result<(NodeIndex, BTreeMap<NodeIndex, PublicKey>)> {
+        let responses = join_all(self.config.signers.iter().map(|signer| {
+            self.poll_signer(signer, |client, signer| client.get_dkg_public_key(signer))
+        }))
+        .await;
+
+        let mut own_index = None;
+        let mut receivers = BTreeMap::new();
+        for (i, (signer, response)) in self.config.signers.iter().zip(responses).enumerate() {
+            let node_index = (i + 1) as NodeIndex;
+            let response = match response {
+                Ok(response) => response,
+                Err(err) => {
+                    warn!(
+                        "Signer {} ({}) is unavailable - {}. It's going to be excluded from the DKG",
+                        node_index, signer, err
+                    );
+                    continue;
+                }
+            };
+
+            if response.public_key == self.persistent_state.public_key() {
+                own_index = Some(node_index)
+            }
+
+            match self.parse_and_verify_public_key(node_index, &response.public_key) {
+                Ok(public_key) => {
+                    receivers.insert(node_index, public_key);
+                }
+                Err(err) => warn!(
+                    "Signer {} ({}) has provided an invalid public key - {}. It's going to be excluded from the DKG",
+                    node_index, signer, err
+                ),
+            }
+        }
+
+        let own_index = own_index.ok_or(CoconutError::DkgSelfNotFound)?;
+        if (receivers.len() as Threshold) < self.config.threshold {
+            return Err(CoconutError::NotEnoughDkgParticipants {
+                available: receivers.len(),
+                threshold: self.config.threshold,
+            });
+        }
+
+        Ok((own_index, receivers))
+    }
+
+    // creates a dealing for each secret of the coconut key, i.e. for x and all the ys
+    async fn publish_dealings(
+        &mut self,
+        own_index: NodeIndex,
+        receivers: &BTreeMap<NodeIndex, PublicKey>,
+    ) -> Result<()> {
+        if self.persistent_state.dealings().is_none() {
+            info!("Creating DKG dealings...");
+            let dealings = (0..=TOTAL_ATTRIBUTES)
+                .map(|_| {
+                    let (dealing, _) = Dealing::create(
+                        OsRng,
+                        &self.params,
+                        own_index,
+                        self.config.threshold,
+                        self.epoch,
+                        receivers,
+                        None,
+                    );
+                    bs58::encode(dealing.to_bytes()).into_string()
+                })
+                .collect();
+
+            self.persistent_state.set_dealings(dealings);
+            self.persistent_state.save(&self.config.state_path)?;
+        }
+
+        // we've just made sure they exist
+        let dealings = self.persistent_state.dealings().unwrap().clone();
+        self.dkg_state.publish_dealings(dealings).await;
+        Ok(())
+    }
+
+    fn parse_and_verify_dealings(
+        &self,
+        raw_dealings: &[String],
+        receivers: &BTreeMap<NodeIndex, PublicKey>,
+    ) -> Result<Vec<Dealing>> {
+        if raw_dealings.len() != (TOTAL_ATTRIBUTES + 1) as usize {
+            return Err(CoconutError::InternalError(format!(
+                "received {} dealings while {} were expected",
+                raw_dealings.len(),
+                TOTAL_ATTRIBUTES + 1
+            )));
+        }
+
+        raw_dealings
+            .iter()
+            .map(|raw_dealing| {
+                let bytes = bs58::decode(raw_dealing).into_vec()?;
+                let dealing = Dealing::try_from_bytes(&bytes)?;
+                dealing.verify(
+                    &self.params,
+                    self.epoch,
+                    self.config.threshold,
+                    receivers,
+                    None,
+                )?;
+                Ok(dealing)
+            })
+            .collect()
+    }
+
+    async fn collect_dealings(
+        &self,
+        receivers: &BTreeMap<NodeIndex, PublicKey>,
+    ) -> Result<BTreeMap<NodeIndex, Vec<Dealing>>> {
+        // only the signers that took part in the key exchange could have dealt to us
+        let dealers = self
+            .config
+            .signers
+            .iter()
+            .enumerate()
+            .map(|(i, signer)| ((i + 1) as NodeIndex, signer))
+            .filter(|(dealer_index, _)| receivers.contains_key(dealer_index))
+            .collect::<Vec<_>>();
+
+        let responses = join_all(dealers.iter().map(|(_, signer)| {
+            self.poll_signer(signer, |client, signer| client.get_dkg_dealings(signer))
+        }))
+        .await;
+
+        let mut dealings = BTreeMap::new();
+        for ((dealer_index, signer), response) in dealers.into_iter().zip(responses) {
+            let response = match response {
+                Ok(response) => response,
+                Err(err) => {
+                    warn!(
+                        "Dealer {} ({}) is unavailable - {}. It's going to be excluded from the DKG",
+                        dealer_index, signer, err
+                    );
+                    continue;
+                }
+            };
+
+            match self.parse_and_verify_dealings(&response.dealings, receivers) {
+                Ok(verified) => {
+                    dealings.insert(dealer_index, verified);
+                }
+                Err(err) => warn!(
+                    "Dealer {} ({}) has provided invalid dealings - {}. It's going to be excluded from the DKG",
+                    dealer_index, signer, err
+                ),
+            }
+        }
+
+        if (dealings.len() as Threshold) < self.config.threshold {
+            return Err(CoconutError::NotEnoughDealings {
+                available: dealings.len(),
+                threshold: self.config.threshold,
+            });
+        }
+
+        Ok(dealings)
+    }
+
+    fn derive_key_pair(
+        &self,
+        own_index: NodeIndex,
+        receivers: &BTreeMap<NodeIndex, PublicKey>,
+        dealings: &BTreeMap<NodeIndex, Vec<Dealing>>,
+    ) -> Result<KeyPair> {
+        let mut decryption_key = self.persistent_state.decryption_key()?;
+        decryption_key.try_update_to(self.epoch, &self.params, OsRng)?;
+
+        // position of our ciphertexts within each dealing
+        let receiver_position = receivers
+            .keys()
+            .position(|index| *index == own_index)
+            .ok_or(CoconutError::DkgSelfNotFound)?;
+        let dealer_indices = dealings.keys().copied().collect::<Vec<_>>();
+        let lookup_table = BabyStepGiantStepLookup::default();
+
+        let mut secrets = Vec::with_capacity((TOTAL_ATTRIBUTES + 1) as usize);
+        for i in 0..=TOTAL_ATTRIBUTES as usize {
+            let shares = dealings
+                .values()
+                .map(|dealer_dealings| {
+                    decrypt_share(
+                        &decryption_key,
+                        receiver_position,
+                        &dealer_dealings[i].ciphertexts,
+                        self.epoch,
+                        Some(&lookup_table),
+                    )
+                })
+                .collect::<std::result::Result<Vec<_>, _>>()?;
+
+            let secret = combine_shares(shares, &dealer_indices)?;
+            let secret =
+                Option::from(Attribute::from_bytes(&secret.to_bytes())).ok_or_else(|| {
+                    CoconutError::InternalError("derived an invalid secret scalar".to_string())
+                })?;
+            secrets.push(secret);
+        }
+
+        let x = secrets.remove(0);
+        let params = Parameters::new(TOTAL_ATTRIBUTES).map_err(CoconutInterfaceError::from)?;
+        Ok(KeyPair::from_secrets(&params, x, secrets, Some(own_index)))
+    }
+
+    async fn try_run(&mut self) -> Result<()> {
+        info!(
+            "Starting the DKG with {} signers",
+            self.config.signers.len()
+        );
+
+        let (own_index, receivers) = self.collect_public_keys().await?;
+        info!(
+            "Obtained the DKG public keys of {} signers - our index is {}",
+            receivers.len(),
+            own_index
+        );
+
+        self.publish_dealings(own_index, &receivers).await?;
+        let dealings = self.collect_dealings(&receivers).await?;
+        info!("Obtained valid dealings from {} dealers", dealings.len());
+
+        let key_pair = self.derive_key_pair(own_index, &receivers, &dealings)?;
+        fs::write(&self.config.keypair_path, key_pair.to_bs58())?;
+        self.signing_key_pair.set(key_pair).await;
+
+        info!("The DKG has finished - the signing keypair is now available");
+        Ok(())
+    }
+
+    /// Keeps attempting the DKG, with an exponential backoff between the attempts,
+    /// until the signing keypair is derived.
+    pub(crate) async fn run(mut self) {
+        let mut backoff = INITIAL_RETRY_BACKOFF;
+        loop {
+            match self.try_run().await {
+                Ok(()) => return,
+                Err(err) => {
+                    error!(
+                        "The DKG has failed - {}. Going to retry in {:?}",
+                        err, backoff
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(MAX_RETRY_BACKOFF);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+//! Distributed key generation of the coconut signing keys.
+//!
+//! Rather than relying on a trusted third party to generate the keys of all signers, each signer
+//! derives its own key in cooperation with all the other signers listed in `all_validator_apis`:
+//!
+//! 1. each signer generates a BTE keypair and exposes its public key (with the proof of possession)
+//!    via the `/dkg/public-key` route,
+//! 2. once it knows public keys of all signers, it creates a dealing for every secret of the
+//!    coconut key (`x` and all the `ys`) and exposes them via the `/dkg/dealings` route,
+//! 3. it collects dealings of all the other signers and verifies them, discarding invalid ones,
+//! 4. finally it decrypts its shares out of all the valid dealings, combines them into its
+//!    coconut secret key and persists the resultant keypair.
+//!
+//! Signers that do not respond within the timeout are excluded from the DKG as long as at least
+//! `threshold` signers remain, and the whole procedure is retried with a backoff if it fails.
+//! Once finished, the routes keep being served, so that restarted signers could still complete it.
+//!
+//! Note that dealings are exchanged directly between the signers, so it's assumed the dealers
+//! do not equivocate, i.e. they present the same dealings to everyone.
+
+use crate::coconut::error::{CoconutError, Result};
+use coconut_interface::{DkgDealingsResponse, DkgPublicKeyResponse};
+use config::defaults::VALIDATOR_API_VERSION;
+use rocket::fairing::AdHoc;
+use rocket::serde::json::Json;
+use rocket::State as RocketState;
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use validator_client::validator_api::routes::{COCONUT_DKG, COCONUT_ROUTES};
+
+pub(crate) mod client;
+pub(crate) mod controller;
+pub(crate) mod persistence;
+#[cfg(test)]
+mod tests;
+
+/// Public part of the DKG state of this signer that is exposed to the other signers.
+#[derive(Clone)]
+pub struct DkgState {
+    public_key: String,
+    dealings: Arc<RwLock<Option<Vec<String>>>>,
+}
+
+impl DkgState {
+    pub(crate) fn new(public_key: String, dealings: Option<Vec<String>>) -> Self {
+        DkgState {
+            public_key,
+            dealings: Arc::new(RwLock::new(dealings)),
+        }
+    }
+
+    pub(crate) async fn publish_dealings(&self, dealings: Vec<String>) {
+        *self.dealings.write().await = Some(dealings)
+    }
+
+    pub(crate) fn stage(self) -> AdHoc {
+        AdHoc::on_ignite("DKG Stage", |rocket| async {
+            rocket.manage(self).mount(
+                format!(
+                    "/{}/{}/{}",
+                    VALIDATOR_API_VERSION, COCONUT_ROUTES, COCONUT_DKG
+                ),
+                routes![get_public_key, get_dealings],
+            )
+        })
+    }
+}
+
+#[get("/public-key")]
+pub async fn get_public_key(state: &RocketState<DkgState>) -> Json<DkgPublicKeyResponse> {
+    Json(DkgPublicKeyResponse::new(state.public_key.clone()))
+}
+
+#[get("/dealings")]
+pub async fn get_dealings(state: &RocketState<DkgState>) -> Result<Json<DkgDealingsResponse>> {
+    let dealings = state
+        .dealings
+        .read()
+        .await
+        .clone()
+        .ok_or(CoconutError::DkgDealingsNotReady)?;
+    Ok(Json(DkgDealingsResponse::new(dealings)))
+}
@@ -0,0 +1,74 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::coconut::error::Result;
+use dkg::bte::{keygen, DecryptionKey, Params};
+use rand::rngs::OsRng;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Intermediate state of the DKG that has to survive restarts of the signer, so that other
+/// signers would not observe different keys or dealings of this dealer.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct PersistentState {
+    /// Base58-encoded BTE decryption key.
+    decryption_key: String,
+
+    /// Base58-encoded BTE public key with the proof of possession.
+    public_key: String,
+
+    /// Base58-encoded dealings of this dealer, if they were already created.
+    dealings: Option<Vec<String>>,
+}
+
+impl PersistentState {
+    pub(crate) fn generate(params: &Params) -> Self {
+        let (decryption_key, public_key) = keygen(params, OsRng);
+        PersistentState {
+            decryption_key: bs58::encode(decryption_key.to_bytes()).into_string(),
+            public_key: bs58::encode(public_key.to_bytes()).into_string(),
+            dealings: None,
+        }
+    }
+
+    /// Attempts to load the state from the provided path, generating (and saving) a fresh one
+    /// if it did not exist.
+    pub(crate) fn load_or_generate<P: AsRef<Path>>(path: P, params: &Params) -> Result<Self> {
+        let path = path.as_ref();
+        if path.exists() {
+            let raw = fs::read_to_string(path)?;
+            return Ok(serde_json::from_str(&raw)?);
+        }
+
+        let state = Self::generate(params);
+        state.save(path)?;
+        Ok(state)
+    }
+
+    pub(crate) fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    pub(crate) fn decryption_key(&self) -> Result<DecryptionKey> {
+        let bytes = bs58::decode(&self.decryption_key).into_vec()?;
+        Ok(DecryptionKey::try_from_bytes(&bytes)?)
+    }
+
+    pub(crate) fn public_key(&self) -> &str {
+        &self.public_key
+    }
+
+    pub(crate) fn dealings(&self) -> Option<&Vec<String>> {
+        self.dealings.as_ref()
+    }
+
+    pub(crate) fn set_dealings(&mut self, dealings: Vec<String>) {
+        self.dealings = Some(dealings)
+    }
+}
@@ -0,0 +1,249 @@
+// Copyright 2022 - Nym Technologies SA <contact@nymtech.net>
+// SPDX-License-Identifier: Apache-2.0
+
+use super::client::Client as DkgClient;
+use super::controller::{Config, DkgController};
+use super::persistence::PersistentState;
+use crate::coconut::error::{CoconutError, Result};
+use crate::coconut::SigningKeyPair;
+use async_trait::async_trait;
+use coconut_interface::{
+    aggregate_verification_keys, Base58, DkgDealingsResponse, DkgPublicKeyResponse, KeyPair,
+    VerificationKey,
+};
+use dkg::Threshold;
+use rocket::http::Status;
+use rocket::local::asynchronous::Client;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use url::Url;
+use validator_client::validator_api::routes::{
+    API_VERSION, COCONUT_DKG, COCONUT_ROUTES, DKG_DEALINGS, DKG_PUBLIC_KEY,
+};
+
+/// Routes the DKG requests to local rocket instances of the signers.
+#[derive(Clone, Default)]
+struct LocalSigners {
+    signers: Arc<RwLock<HashMap<Url, Client>>>,
+}
+
+impl LocalSigners {
+    async fn query<T: DeserializeOwned>(&self, signer: &Url, route: &str) -> Result<T> {
+        let signers = self.signers.read().await;
+        let client = signers
+            .get(signer)
+            .ok_or_else(|| CoconutError::InternalError(format!("{} is offline", signer)))?;
+        let response = client
+            .get(format!(
+                "/{}/{}/{}/{}",
+                API_VERSION, COCONUT_ROUTES, COCONUT_DKG, route
+            ))
+            .dispatch()
+            .await;
+
+        if response.status() != Status::Ok {
+            return Err(CoconutError::InternalError(
+                response.into_string().await.unwrap_or_default(),
+            ));
+        }
+
+        // This is a more direct way, but there's a bug which makes it hang https://github.com/SergioBenitez/Rocket/issues/1893
+        // response.into_json::<T>().await
+        Ok(serde_json::from_str(&response.into_string().await.unwrap()).unwrap())
+    }
+}
+
+#[async_trait]
+impl DkgClient for LocalSigners {
+    async fn get_dkg_public_key(&self, signer: &Url) -> Result<DkgPublicKeyResponse> {
+        self.query(signer, DKG_PUBLIC_KEY).await
+    }
+
+    async fn get_dkg_dealings(&self, signer: &Url) -> Result<DkgDealingsResponse> {
+        self.query(signer, DKG_DEALINGS).await
+    }
+}
+
+struct TestSigner {
+    controller: DkgController<LocalSigners>,
+    signing_key_pair: SigningKeyPair,
+    keypair_path: PathBuf,
+}
+
+// creates the controllers of the signers and exposes the DKG routes of the `online` ones
+async fn setup_signers(
+    data_dir: &Path,
+    signer_urls: &[Url],
+    online: &[usize],
+    threshold: Threshold,
+    timeout: Duration,
+) -> (LocalSigners, Vec<TestSigner>) {
+    let local_signers = LocalSigners::default();
+    let mut signers = Vec::new();
+    for (i, url) in signer_urls.iter().enumerate() {
+        let keypair_path = data_dir.join(format!("keypair{}", i));
+        let config = Config::new(
+            signer_urls.to_vec(),
+            threshold,
+            data_dir.join(format!("dkg_state{}.json", i)),
+            keypair_path.clone(),
+            Duration::from_millis(10),
+            timeout,
+        );
+        let signing_key_pair = SigningKeyPair::default();
+        let (controller, dkg_state) =
+            DkgController::new(config, local_signers.clone(), signing_key_pair.clone()).unwrap();
+
+        if online.contains(&i) {
+            let rocket = rocket::build().attach(dkg_state.stage());
+            let client = Client::tracked(rocket)
+                .await
+                .expect("valid rocket instance");
+            local_signers
+                .signers
+                .write()
+                .await
+                .insert(url.clone(), client);
+        }
+
+        signers.push(TestSigner {
+            controller,
+            signing_key_pair,
+            keypair_path,
+        });
+    }
+
+    (local_signers, signers)
+}
+
+fn test_data_dir() -> PathBuf {
+    let mut data_dir = std::env::temp_dir();
+    data_dir.push(format!("dkg-{}", rand::random::<u32>()));
+    data_dir
+}
+
+fn signer_urls(num_signers: usize) -> Vec<Url> {
+    (1..=num_signers)
+        .map(|i| format!("http://signer{}.local", i).parse::<Url>().unwrap())
+        .collect()
+}
+
+// runs the DKG of the provided signers and returns their derived verification keys
+async fn run_dkg(signers: Vec<TestSigner>) -> Vec<VerificationKey> {
+    let mut handles = Vec::new();
+    let mut outputs = Vec::new();
+    for signer in signers {
+        handles.push(signer.controller.run());
+        outputs.push((signer.signing_key_pair, signer.keypair_path));
+    }
+    futures::future::join_all(handles).await;
+
+    let mut verification_keys = Vec::new();
+    for (signing_key_pair, keypair_path) in outputs {
+        let key_pair = signing_key_pair.get().await;
+        let key_pair = key_pair
+            .as_ref()
+            .expect("the keypair should have been derived");
+
+        let persisted = KeyPair::try_from_bs58(fs::read_to_string(&keypair_path).unwrap()).unwrap();
+        assert_eq!(persisted.verification_key(), key_pair.verification_key());
+
+        verification_keys.push(key_pair.verification_key());
+    }
+    verification_keys
+}
+
+#[tokio::test]
+async fn local_signers_derive_consistent_keys() {
+    let data_dir = test_data_dir();
+    let signer_urls = signer_urls(3);
+    let (_local_signers, signers) = setup_signers(
+        &data_dir,
+        &signer_urls,
+        &[0, 1, 2],
+        2,
+        Duration::from_secs(60),
+    )
+    .await;
+
+    let verification_keys = run_dkg(signers).await;
+
+    // any `threshold` signers must agree on the same master verification key
+    let master_key1 = aggregate_verification_keys(&verification_keys[..2], Some(&[1, 2])).unwrap();
+    let master_key2 = aggregate_verification_keys(&verification_keys[1..], Some(&[2, 3])).unwrap();
+    let master_key3 = aggregate_verification_keys(
+        &[verification_keys[0].clone(), verification_keys[2].clone()],
+        Some(&[1, 3]),
+    )
+    .unwrap();
+    assert_eq!(master_key1, master_key2);
+    assert_eq!(master_key1, master_key3);
+
+    fs::remove_dir_all(data_dir).unwrap();
+}
+
+#[tokio::test]
+async fn unresponsive_signer_is_excluded_once_threshold_is_reached() {
+    let data_dir = test_data_dir();
+    let signer_urls = signer_urls(3);
+    let (_local_signers, mut signers) = setup_signers(
+        &data_dir,
+        &signer_urls,
+        &[0, 1],
+        2,
+        Duration::from_millis(100),
+    )
+    .await;
+
+    // the last signer never comes online
+    signers.pop();
+    let verification_keys = run_dkg(signers).await;
+
+    // the remaining signers have derived their keys and can be aggregated
+    assert_eq!(verification_keys.len(), 2);
+    assert!(aggregate_verification_keys(&verification_keys, Some(&[1, 2])).is_ok());
+
+    fs::remove_dir_all(data_dir).unwrap();
+}
+
+#[tokio::test]
+async fn restarted_signer_keeps_serving_its_dealings() {
+    let data_dir = test_data_dir();
+    let signer_urls = signer_urls(3);
+    let (_local_signers, signers) = setup_signers(
+        &data_dir,
+        &signer_urls,
+        &[0, 1, 2],
+        2,
+        Duration::from_secs(60),
+    )
+    .await;
+    run_dkg(signers).await;
+
+    // after a restart, the signer does not need to run the DKG again,
+    // but it still exposes the very same public key and dealings
+    let (restarted_signers, _) =
+        setup_signers(&data_dir, &signer_urls, &[0], 2, Duration::from_secs(60)).await;
+    let state: PersistentState =
+        serde_json::from_str(&fs::read_to_string(data_dir.join("dkg_state0.json")).unwrap())
+            .unwrap();
+
+    let public_key = restarted_signers
+        .get_dkg_public_key(&signer_urls[0])
+        .await
+        .unwrap();
+    assert_eq!(public_key.public_key, state.public_key());
+
+    let dealings = restarted_signers
+        .get_dkg_dealings(&signer_urls[0])
+        .await
+        .unwrap();
+    assert_eq!(Some(&dealings.dealings), state.dealings());
+
+    fs::remove_dir_all(data_dir).unwrap();
+}
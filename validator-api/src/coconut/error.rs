@@ -12,6 +12,7 @@ use crypto::asymmetric::{
     identity::{Ed25519RecoveryError, SignatureError},
 };
 use validator_client::nymd::error::NymdError;
+use validator_client::ValidatorClientError;
 
 use crate::node_status_api::models::ValidatorApiStorageError;
 
@@ -66,6 +67,9 @@ pub enum CoconutError {
     #[error("Could not create proposal for spending credential")]
     CreateProposalError,
 
+    #[error("The provided credential did not verify correctly")]
+    InvalidCredential,
+
     #[error("Storage error - {0}")]
     StorageError(#[from] ValidatorApiStorageError),
 
@@ -79,6 +83,42 @@ pub enum CoconutError {
 
     #[error("Internal error: {0}")]
     InternalError(String),
+
+    #[error("The signing keypair is not available yet")]
+    KeyPairNotAvailable,
+
+    #[error("Validator client error - {0}")]
+    ValidatorClientError(#[from] ValidatorClientError),
+
+    #[error("Could not decode base 58 string - {0}")]
+    MalformedString(#[from] bs58::decode::Error),
+
+    #[error("DKG error - {0}")]
+    DkgError(#[from] dkg::error::DkgError),
+
+    #[error("Failed to access the DKG state - {0}")]
+    DkgStateIoError(#[from] std::io::Error),
+
+    #[error("The DKG state is malformed - {0}")]
+    MalformedDkgState(#[from] serde_json::Error),
+
+    #[error("The dealings of this dealer are not available yet")]
+    DkgDealingsNotReady,
+
+    #[error("Signer {node_index} has provided an invalid DKG public key")]
+    InvalidDkgPublicKey { node_index: u64 },
+
+    #[error("Could not find our own DKG public key among the keys of the signers")]
+    DkgSelfNotFound,
+
+    #[error("Only {available} signers have taken part in the DKG while at least {threshold} are required")]
+    NotEnoughDkgParticipants { available: usize, threshold: u64 },
+
+    #[error("Only {available} dealers have provided valid dealings while at least {threshold} are required")]
+    NotEnoughDealings { available: usize, threshold: u64 },
+
+    #[error("Timed out while waiting for signer {signer} during DKG")]
+    DkgTimeout { signer: String },
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for CoconutError {
@@ -3,6 +3,7 @@
 
 pub(crate) mod client;
 mod deposit;
+pub(crate) mod dkg;
 pub(crate) mod error;
 #[cfg(test)]
 mod tests;
@@ -15,14 +16,15 @@ use crate::ValidatorApiStorage;
 use coconut_interface::{
     Attribute, BlindSignRequest, BlindSignRequestBody, BlindedSignature, BlindedSignatureResponse,
     ExecuteReleaseFundsRequestBody, KeyPair, Parameters, ProposeReleaseFundsRequestBody,
-    ProposeReleaseFundsResponse, VerificationKey, VerificationKeyResponse, VerifyCredentialBody,
-    VerifyCredentialResponse,
+    ProposeReleaseFundsResponse, SpentCredentialRequestBody, SpentCredentialResponse,
+    VerificationKey, VerificationKeyResponse, VerifyCredentialBody, VerifyCredentialResponse,
 };
 use config::defaults::VALIDATOR_API_VERSION;
 use credentials::coconut::params::{
     ValidatorApiCredentialEncryptionAlgorithm, ValidatorApiCredentialHkdfAlgorithm,
 };
-use credentials::obtain_aggregate_verification_key;
+use credentials::coconut::utils::DEFAULT_SIGNER_TIMEOUT;
+use credentials::{obtain_threshold_verification_key, signing_threshold};
 use crypto::asymmetric::encryption;
 use crypto::shared_key::new_ephemeral_shared_key;
 use crypto::symmetric::stream_cipher;
@@ -34,32 +36,58 @@ use rocket::fairing::AdHoc;
 use rocket::serde::json::Json;
 use rocket::State as RocketState;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, RwLock, RwLockReadGuard};
 use url::Url;
 
+/// Signing keypair of this authority. It might not be available straight away if it's
+/// still being derived in the distributed key generation.
+#[derive(Clone, Default)]
+pub struct SigningKeyPair {
+    inner: Arc<RwLock<Option<KeyPair>>>,
+}
+
+impl SigningKeyPair {
+    pub async fn set(&self, key_pair: KeyPair) {
+        *self.inner.write().await = Some(key_pair)
+    }
+
+    pub async fn get(&self) -> RwLockReadGuard<'_, Option<KeyPair>> {
+        self.inner.read().await
+    }
+}
+
+impl From<KeyPair> for SigningKeyPair {
+    fn from(key_pair: KeyPair) -> Self {
+        SigningKeyPair {
+            inner: Arc::new(RwLock::new(Some(key_pair))),
+        }
+    }
+}
+
 pub struct State {
     client: Arc<dyn LocalClient + Send + Sync>,
-    key_pair: KeyPair,
+    key_pair: SigningKeyPair,
     validator_apis: Vec<Url>,
     storage: ValidatorApiStorage,
     rng: Arc<Mutex<OsRng>>,
 }
 
 impl State {
-    pub(crate) fn new<C>(
+    pub(crate) fn new<C, K>(
         client: C,
-        key_pair: KeyPair,
+        key_pair: K,
         validator_apis: Vec<Url>,
         storage: ValidatorApiStorage,
     ) -> Self
     where
         C: LocalClient + Send + Sync + 'static,
+        K: Into<SigningKeyPair>,
     {
         let client = Arc::new(client);
         let rng = Arc::new(Mutex::new(OsRng));
         Self {
             client,
-            key_pair,
+            key_pair: key_pair.into(),
             validator_apis,
             storage,
             rng,
@@ -122,7 +150,20 @@ impl State {
     }
 
     pub async fn verification_key(&self) -> Result<VerificationKey> {
-        Ok(obtain_aggregate_verification_key(&self.validator_apis).await?)
+        let verification_key = obtain_threshold_verification_key(
+            &self.validator_apis,
+            signing_threshold(self.validator_apis.len()),
+            DEFAULT_SIGNER_TIMEOUT,
+        )
+        .await?;
+        for failure in verification_key.failures {
+            log::warn!(
+                "Failed to obtain the verification key of {} - {}",
+                failure.url,
+                failure.error
+            );
+        }
+        Ok(verification_key.value)
     }
 }
 
@@ -150,14 +191,15 @@ impl InternalSignRequest {
         }
     }
 
-    pub fn stage<C>(
+    pub fn stage<C, K>(
         client: C,
-        key_pair: KeyPair,
+        key_pair: K,
         validator_apis: Vec<Url>,
         storage: ValidatorApiStorage,
     ) -> AdHoc
     where
         C: LocalClient + Send + Sync + 'static,
+        K: Into<SigningKeyPair>,
     {
         let state = State::new(client, key_pair, validator_apis, storage);
         AdHoc::on_ignite("Internal Sign Request Stage", |rocket| async {
@@ -173,7 +215,8 @@ impl InternalSignRequest {
                     post_partial_bandwidth_credential,
                     verify_bandwidth_credential,
                     post_propose_release_funds,
-                    post_execute_release_funds
+                    post_execute_release_funds,
+                    post_spent_credential
                 ],
             )
         })
@@ -214,7 +257,11 @@ pub async fn post_blind_sign(
         blind_sign_request_body.public_attributes(),
         blind_sign_request_body.blind_sign_request().clone(),
     );
-    let blinded_signature = blind_sign(internal_request, &state.key_pair);
+    let blinded_signature = {
+        let key_pair = state.key_pair.get().await;
+        let key_pair = key_pair.as_ref().ok_or(CoconutError::KeyPairNotAvailable)?;
+        blind_sign(internal_request, key_pair)
+    };
 
     let response = state
         .encrypt_and_store(
@@ -243,8 +290,10 @@ pub async fn post_partial_bandwidth_credential(
 pub async fn get_verification_key(
     state: &RocketState<State>,
 ) -> Result<Json<VerificationKeyResponse>> {
+    let key_pair = state.key_pair.get().await;
+    let key_pair = key_pair.as_ref().ok_or(CoconutError::KeyPairNotAvailable)?;
     Ok(Json(VerificationKeyResponse::new(
-        state.key_pair.verification_key(),
+        key_pair.verification_key(),
     )))
 }
 
@@ -313,3 +362,25 @@ pub async fn post_execute_release_funds(
 
     Ok(Json(()))
 }
+
+// Shared registry of spent credentials, so that gateways could reject a credential that was
+// already redeemed at some other gateway without having to wait for the spending proposal.
+#[post("/spent-credential", data = "<spent_credential>")]
+pub async fn post_spent_credential(
+    spent_credential: Json<SpentCredentialRequestBody>,
+    state: &RocketState<State>,
+) -> Result<Json<SpentCredentialResponse>> {
+    // only accept valid credentials so that nobody could burn serial numbers of other clients
+    let verification_key = state.verification_key().await?;
+    let credential = spent_credential.0.credential();
+    if !credential.verify(&verification_key) {
+        return Err(CoconutError::InvalidCredential);
+    }
+
+    let newly_spent = state
+        .storage
+        .insert_spent_credential(&credential.blinded_serial_number())
+        .await?;
+
+    Ok(Json(SpentCredentialResponse::new(!newly_spent)))
+}
@@ -7,17 +7,24 @@ use coconut_bandwidth_contract_common::events::{
     DEPOSITED_FUNDS_EVENT_TYPE, DEPOSIT_ENCRYPTION_KEY, DEPOSIT_IDENTITY_KEY, DEPOSIT_INFO,
     DEPOSIT_VALUE,
 };
-use coconut_interface::{BlindSignRequestBody, BlindedSignatureResponse, VerificationKeyResponse};
+use coconut_interface::{
+    BlindSignRequestBody, BlindedSignatureResponse, Credential, SpentCredentialRequestBody,
+    VerificationKeyResponse,
+};
 use config::defaults::VOUCHER_INFO;
 use credentials::coconut::bandwidth::BandwidthVoucher;
 use credentials::coconut::params::{
     ValidatorApiCredentialEncryptionAlgorithm, ValidatorApiCredentialHkdfAlgorithm,
 };
+use credentials::coconut::utils::{prepare_credential_for_spending, DEFAULT_SIGNER_TIMEOUT};
+use credentials::{obtain_threshold_verification_key, signing_threshold};
 use crypto::shared_key::recompute_shared_key;
 use crypto::symmetric::stream_cipher;
 use multisig_contract_common::msg::ProposalResponse;
 use nymcoconut::{
-    prepare_blind_sign, ttp_keygen, Base58, BlindSignRequest, BlindedSignature, KeyPair, Parameters,
+    aggregate_signature_shares, aggregate_verification_keys, blind_sign, prepare_blind_sign,
+    ttp_keygen, Base58, BlindSignRequest, BlindedSignature, KeyPair, Parameters, SignatureShare,
+    SignerIndex, VerificationKey,
 };
 use validator_client::nymd::{tx::Hash, DeliverTx, Event, Fee, Tag, TxResponse};
 use validator_client::validator_api::routes::{
@@ -33,8 +40,11 @@ use rand_07::rngs::OsRng;
 use rocket::http::Status;
 use rocket::local::asynchronous::Client;
 use std::collections::HashMap;
+use std::net::{Ipv4Addr, TcpListener};
 use std::str::FromStr;
 use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use url::Url;
 
 struct DummyClient {
     db: Arc<RwLock<HashMap<String, TxResponse>>>,
@@ -554,3 +564,200 @@ async fn signature_test() {
         expected_response.to_bytes()
     );
 }
+
+// issues a spendable credential signed by the provided signers
+fn issue_credential(
+    params: &Parameters,
+    signers: &[(SignerIndex, &KeyPair)],
+    verification_key: &VerificationKey,
+) -> Credential {
+    let mut rng = OsRng;
+    let voucher = BandwidthVoucher::new(
+        params,
+        "1234".to_string(),
+        VOUCHER_INFO.to_string(),
+        Hash::from_str("6B27412050B823E58BB38447D7870BBC8CBE3C51C905BEA89D459ACCDA80A00E").unwrap(),
+        identity::PrivateKey::from_base58_string(
+            identity::KeyPair::new(&mut rng)
+                .private_key()
+                .to_base58_string(),
+        )
+        .unwrap(),
+        encryption::PrivateKey::from_bytes(
+            &encryption::KeyPair::new(&mut rng).private_key().to_bytes(),
+        )
+        .unwrap(),
+    );
+
+    let private_attributes = voucher.get_private_attributes();
+    let public_attributes = voucher.get_public_attributes();
+    let shares = signers
+        .iter()
+        .map(|(index, key_pair)| {
+            let signature = blind_sign(
+                params,
+                &key_pair.secret_key(),
+                voucher.blind_sign_request(),
+                &public_attributes,
+            )
+            .unwrap()
+            .unblind(
+                params,
+                &key_pair.verification_key(),
+                &private_attributes,
+                &public_attributes,
+                &voucher.blind_sign_request().get_commitment_hash(),
+                voucher.pedersen_commitments_openings(),
+            )
+            .unwrap();
+            SignatureShare::new(signature, *index)
+        })
+        .collect::<Vec<_>>();
+
+    let mut attributes = private_attributes.clone();
+    attributes.extend_from_slice(&public_attributes);
+    let signature =
+        aggregate_signature_shares(params, verification_key, &attributes, &shares).unwrap();
+
+    prepare_credential_for_spending(
+        params,
+        1234,
+        VOUCHER_INFO.to_string(),
+        private_attributes[0],
+        private_attributes[1],
+        &signature,
+        verification_key,
+    )
+    .unwrap()
+}
+
+// reserves a local address for a validator API
+fn local_api_url() -> Url {
+    let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    format!("http://{}:{}", Ipv4Addr::LOCALHOST, port)
+        .parse()
+        .unwrap()
+}
+
+// the aggregated verification key is obtained over http, so unlike in the other tests,
+// the signers have to actually listen on their addresses
+async fn launch_signer(
+    api_url: &Url,
+    key_pair: KeyPair,
+    validator_apis: Vec<Url>,
+) -> validator_client::validator_api::Client {
+    let mut db_dir = std::env::temp_dir();
+    db_dir.push(&key_pair.verification_key().to_bs58()[..8]);
+    let storage = ValidatorApiStorage::init(db_dir).await.unwrap();
+    let nymd_client = DummyClient::new(&Arc::new(RwLock::new(HashMap::new())));
+
+    let figment = rocket::Config::figment()
+        .merge(("address", Ipv4Addr::LOCALHOST))
+        .merge(("port", api_url.port().unwrap()));
+    let rocket = rocket::custom(figment).attach(InternalSignRequest::stage(
+        nymd_client,
+        key_pair,
+        validator_apis,
+        storage,
+    ));
+    tokio::spawn(rocket.launch());
+
+    let api_client = validator_client::validator_api::Client::new(api_url.clone());
+    let mut attempts = 0;
+    while api_client.get_coconut_verification_key().await.is_err() {
+        attempts += 1;
+        assert!(attempts < 50, "the validator API has not started");
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    api_client
+}
+
+#[tokio::test]
+async fn spent_credentials_are_reported() {
+    let params = Parameters::new(4).unwrap();
+    let key_pair = ttp_keygen(&params, 1, 1).unwrap().remove(0);
+    let credential = issue_credential(&params, &[(1, &key_pair)], &key_pair.verification_key());
+    let foreign_key_pair = ttp_keygen(&params, 1, 1).unwrap().remove(0);
+    let foreign_credential = issue_credential(
+        &params,
+        &[(1, &foreign_key_pair)],
+        &foreign_key_pair.verification_key(),
+    );
+
+    // the signer is the sole validator API
+    let api_url = local_api_url();
+    let api_client = launch_signer(&api_url, key_pair, vec![api_url.clone()]).await;
+
+    let request_body = SpentCredentialRequestBody::new(credential);
+    assert!(
+        !api_client
+            .mark_credential_spent(&request_body)
+            .await
+            .unwrap()
+            .already_spent
+    );
+    assert!(
+        api_client
+            .mark_credential_spent(&request_body)
+            .await
+            .unwrap()
+            .already_spent
+    );
+
+    // credentials not issued by the signers can't be used to burn serial numbers
+    assert!(api_client
+        .mark_credential_spent(&SpentCredentialRequestBody::new(foreign_credential))
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn unreachable_signer_does_not_prevent_verification() {
+    let params = Parameters::new(4).unwrap();
+    let num_signers = 4;
+    let threshold = signing_threshold(num_signers as usize);
+    let key_pairs = ttp_keygen(&params, threshold, num_signers).unwrap();
+    let verification_key = aggregate_verification_keys(
+        &key_pairs
+            .iter()
+            .map(|key_pair| key_pair.verification_key())
+            .collect::<Vec<_>>(),
+        Some(&[1, 2, 3, 4]),
+    )
+    .unwrap();
+    let credential = issue_credential(
+        &params,
+        &[(1, &key_pairs[0]), (2, &key_pairs[1]), (4, &key_pairs[3])],
+        &verification_key,
+    );
+
+    // the third signer never comes online
+    let api_urls = (0..num_signers)
+        .map(|_| local_api_url())
+        .collect::<Vec<_>>();
+    let mut api_clients = Vec::new();
+    for (i, key_pair) in key_pairs.into_iter().enumerate() {
+        if i != 2 {
+            api_clients.push(launch_signer(&api_urls[i], key_pair, api_urls.clone()).await);
+        }
+    }
+
+    let obtained = obtain_threshold_verification_key(&api_urls, threshold, DEFAULT_SIGNER_TIMEOUT)
+        .await
+        .unwrap();
+    assert_eq!(obtained.value, verification_key);
+    assert!(obtained.failures.iter().all(|failure| failure.index == 3));
+
+    // and the signers themselves verify the credentials with the threshold of the keys
+    assert!(
+        !api_clients[0]
+            .mark_credential_spent(&SpentCredentialRequestBody::new(credential))
+            .await
+            .unwrap()
+            .already_spent
+    );
+}
@@ -292,6 +292,24 @@ pub struct CoconutSigner {
     /// A special care must be taken to ensure they are in correct order.
     /// The list must also contain THIS validator that is running the test
     all_validator_apis: Vec<Url>,
+
+    /// Specifies whether the signing keypair should be derived in a distributed key generation
+    /// with all the other validators, rather than loaded from `keypair_path`.
+    /// Once derived, the keypair is saved to `keypair_path` and reused on subsequent runs.
+    dkg_enabled: bool,
+
+    /// Path to the file containing the intermediate state of the distributed key generation,
+    /// so that it could be resumed after a restart.
+    dkg_state_path: PathBuf,
+}
+
+#[cfg(feature = "coconut")]
+impl CoconutSigner {
+    pub const DKG_STATE_FILE: &'static str = "dkg_state.json";
+
+    fn default_dkg_state_path() -> PathBuf {
+        Config::default_data_directory(None).join(Self::DKG_STATE_FILE)
+    }
 }
 
 #[cfg(feature = "coconut")]
@@ -301,6 +319,8 @@ impl Default for CoconutSigner {
             enabled: false,
             keypair_path: PathBuf::default(),
             all_validator_apis: config::defaults::default_api_endpoints(),
+            dkg_enabled: false,
+            dkg_state_path: CoconutSigner::default_dkg_state_path(),
         }
     }
 }
@@ -316,6 +336,11 @@ impl Config {
             Config::default_data_directory(Some(id)).join(NodeStatusAPI::DB_FILE);
         self.network_monitor.credentials_database_path =
             Config::default_data_directory(Some(id)).join(NetworkMonitor::DB_FILE);
+        #[cfg(feature = "coconut")]
+        {
+            self.coconut_signer.dkg_state_path =
+                Config::default_data_directory(Some(id)).join(CoconutSigner::DKG_STATE_FILE);
+        }
         self
     }
 
@@ -324,6 +349,11 @@ impl Config {
         self.coconut_signer.keypair_path.clone()
     }
 
+    #[cfg(feature = "coconut")]
+    pub fn dkg_state_path(&self) -> PathBuf {
+        self.coconut_signer.dkg_state_path.clone()
+    }
+
     pub fn with_network_monitor_enabled(mut self, enabled: bool) -> Self {
         self.network_monitor.enabled = enabled;
         self
@@ -345,6 +375,12 @@ impl Config {
         self
     }
 
+    #[cfg(feature = "coconut")]
+    pub fn with_dkg_enabled(mut self, enabled: bool) -> Self {
+        self.coconut_signer.dkg_enabled = enabled;
+        self
+    }
+
     pub fn with_custom_nymd_validator(mut self, validator: Url) -> Self {
         self.base.local_validator = validator;
         self
@@ -412,6 +448,11 @@ impl Config {
         self.coconut_signer.enabled
     }
 
+    #[cfg(feature = "coconut")]
+    pub fn get_dkg_enabled(&self) -> bool {
+        self.coconut_signer.dkg_enabled
+    }
+
     pub fn get_disabled_credentials_mode(&self) -> bool {
         self.network_monitor.disabled_credentials_mode
     }
@@ -111,5 +111,13 @@ all_validator_apis = [
     {{/each}}
 ]
 
+# Specifies whether the signing keypair should be derived in a distributed key generation
+# with all the other validators, rather than loaded from `keypair_path`.
+# Once derived, the keypair is saved to `keypair_path` and reused on subsequent runs.
+dkg_enabled = {{ coconut_signer.dkg_enabled }}
+
+# Path to the file containing the intermediate state of the distributed key generation.
+dkg_state_path = '{{ coconut_signer.dkg_state_path }}'
+
 "#
 }
@@ -32,7 +32,11 @@ use tokio::sync::Notify;
 
 use crate::rewarded_set_updater::RewardedSetUpdater;
 #[cfg(feature = "coconut")]
-use coconut::InternalSignRequest;
+use coconut::dkg::client::ValidatorApiDkgClient;
+#[cfg(feature = "coconut")]
+use coconut::dkg::controller::{self as dkg_controller, DkgController};
+#[cfg(feature = "coconut")]
+use coconut::{InternalSignRequest, SigningKeyPair};
 #[cfg(feature = "coconut")]
 use coconut_interface::{Base58, KeyPair};
 use validator_client::nymd::SigningNymdClient;
@@ -64,6 +68,8 @@ const API_VALIDATORS_ARG: &str = "api-validators";
 const KEYPAIR_ARG: &str = "keypair";
 #[cfg(feature = "coconut")]
 const COCONUT_ENABLED: &str = "enable-coconut";
+#[cfg(feature = "coconut")]
+const DKG_ENABLED: &str = "enable-dkg";
 
 #[cfg(not(feature = "coconut"))]
 const ETH_ENDPOINT: &str = "eth_endpoint";
@@ -199,6 +205,12 @@ fn parse_args<'a>() -> ArgMatches<'a> {
                 .help("Flag to indicate whether coconut signer authority is enabled on this API")
                 .requires_all(&[KEYPAIR_ARG, MNEMONIC_ARG, API_VALIDATORS_ARG])
                 .long(COCONUT_ENABLED),
+        )
+        .arg(
+            Arg::with_name(DKG_ENABLED)
+                .help("Flag to indicate whether the coconut signing keypair should be derived in a distributed key generation with the other validators if it doesn't exist yet")
+                .requires(COCONUT_ENABLED)
+                .long(DKG_ENABLED),
         );
 
     #[cfg(not(feature = "coconut"))]
@@ -270,6 +282,11 @@ fn override_config(mut config: Config, matches: &ArgMatches<'_>) -> Config {
         config = config.with_coconut_signer_enabled(true)
     }
 
+    #[cfg(feature = "coconut")]
+    if matches.is_present(DKG_ENABLED) {
+        config = config.with_dkg_enabled(true)
+    }
+
     #[cfg(feature = "coconut")]
     if let Some(raw_validators) = matches.value_of(API_VALIDATORS_ARG) {
         config = config.with_custom_validator_apis(parse_validators(raw_validators));
@@ -411,6 +428,49 @@ fn expected_monitor_test_runs(config: &Config, interval_length: Duration) -> usi
     (interval_length.as_secs() / test_delay.as_secs()) as usize
 }
 
+#[cfg(feature = "coconut")]
+fn load_signing_keypair(config: &Config) -> Result<SigningKeyPair> {
+    let keypair_bs58 = fs::read_to_string(config.keypair_path())?
+        .trim()
+        .to_string();
+    let keypair = KeyPair::try_from_bs58(keypair_bs58)?;
+    Ok(SigningKeyPair::from(keypair))
+}
+
+// the signing keypair is going to become available once the DKG with other signers is finished.
+// The DKG routes are mounted even if we already have the keypair, as other signers
+// might still need our public key and dealings to finish their own DKG
+#[cfg(feature = "coconut")]
+fn setup_dkg(
+    config: &Config,
+    rocket: Rocket<rocket::Build>,
+) -> Result<(Rocket<rocket::Build>, SigningKeyPair)> {
+    let signers = config.get_all_validator_api_endpoints();
+    let threshold = dkg_controller::default_threshold(signers.len());
+
+    let dkg_config = dkg_controller::Config::new(
+        signers,
+        threshold,
+        config.dkg_state_path(),
+        config.keypair_path(),
+        dkg_controller::DEFAULT_POLLING_RATE,
+        dkg_controller::DEFAULT_TIMEOUT,
+    );
+    let (signing_key_pair, run_dkg) = if config.keypair_path().exists() {
+        (load_signing_keypair(config)?, false)
+    } else {
+        (SigningKeyPair::default(), true)
+    };
+    let (dkg_controller, dkg_state) =
+        DkgController::new(dkg_config, ValidatorApiDkgClient, signing_key_pair.clone())?;
+
+    if run_dkg {
+        tokio::spawn(dkg_controller.run());
+    }
+
+    Ok((rocket.attach(dkg_state.stage()), signing_key_pair))
+}
+
 async fn setup_rocket(
     config: &Config,
     liftoff_notify: Arc<Notify>,
@@ -446,13 +506,15 @@ async fn setup_rocket(
 
     #[cfg(feature = "coconut")]
     let rocket = if config.get_coconut_signer_enabled() {
-        let keypair_bs58 = fs::read_to_string(config.keypair_path())?
-            .trim()
-            .to_string();
-        let keypair = KeyPair::try_from_bs58(keypair_bs58)?;
+        let (rocket, signing_key_pair) = if config.get_dkg_enabled() {
+            setup_dkg(config, rocket)?
+        } else {
+            (rocket, load_signing_keypair(config)?)
+        };
+
         rocket.attach(InternalSignRequest::stage(
             _nymd_client,
-            keypair,
+            signing_key_pair,
             config.get_all_validator_api_endpoints(),
             storage.clone().unwrap(),
         ))
@@ -921,4 +921,28 @@ impl StorageManager {
 
         Ok(blinded_signature_response)
     }
+
+    /// Marks the credential with the provided blinded serial number as spent.
+    /// Returns `false` if it has already been marked as spent before.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_serial_number_bs58`: base58-encoded blinded serial number of the credential.
+    /// * `timestamp`: unix timestamp of when the credential got spent.
+    #[cfg(feature = "coconut")]
+    pub(super) async fn insert_spent_credential(
+        &self,
+        blinded_serial_number_bs58: &str,
+        timestamp: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let res = sqlx::query!(
+            "INSERT OR IGNORE INTO spent_credential(blinded_serial_number_bs58, timestamp) VALUES (?, ?)",
+            blinded_serial_number_bs58,
+            timestamp
+        )
+        .execute(&self.connection_pool)
+        .await?;
+
+        Ok(res.rows_affected() == 1)
+    }
 }
@@ -738,4 +738,22 @@ impl ValidatorApiStorage {
             .await
             .map_err(|e| ValidatorApiStorageError::InternalDatabaseError(e.to_string()))
     }
+
+    /// Marks the credential with the provided blinded serial number as spent.
+    /// Returns `false` if it has already been marked as spent before.
+    ///
+    /// # Arguments
+    ///
+    /// * `blinded_serial_number_bs58`: base58-encoded blinded serial number of the credential.
+    #[cfg(feature = "coconut")]
+    pub(crate) async fn insert_spent_credential(
+        &self,
+        blinded_serial_number_bs58: &str,
+    ) -> Result<bool, ValidatorApiStorageError> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.manager
+            .insert_spent_credential(blinded_serial_number_bs58, now)
+            .await
+            .map_err(|e| ValidatorApiStorageError::InternalDatabaseError(e.to_string()))
+    }
 }
@@ -71,6 +71,15 @@ pub struct RewardEstimationResponse {
     pub as_at: i64,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct GatewayStatusReportResponse {
+    pub identity: String,
+    pub owner: String,
+    pub most_recent: u8,
+    pub last_hour: u8,
+    pub last_day: u8,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct UptimeResponse {
     pub identity: String,